@@ -0,0 +1,776 @@
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+
+use crate::{
+    Action, Announcement, CanceledOrder, Candlestick, Category, Event, EventPosition,
+    ExchangeScheduleStandard, ExchangeStatus, Fill, Kalshi, KalshiError, Market, MarketPosition,
+    MarketStatus, Order, OrderType, Orderbook, Series, Settlement, Side, Snapshot, Trade,
+};
+
+/// The REST surface of [`Kalshi`], for code that should run against a fake exchange in tests.
+///
+/// Write bots against `&dyn KalshiApi` (or a generic `A: KalshiApi`) and pass a [`Kalshi`]
+/// instance in production and a mock in unit tests, instead of hitting demo mode. Every method
+/// behaves like the [`Kalshi`] method of the same name, paginated listings are boxed streams.
+///
+/// Every method has a default implementation failing with [`KalshiError::InternalError`], so a
+/// mock only implements what the code under test calls.
+///
+/// ```
+/// struct FixedBalance(i64);
+///
+/// #[async_trait]
+/// impl KalshiApi for FixedBalance {
+///     async fn get_balance(&self) -> Result<i64, KalshiError> {
+///         Ok(self.0)
+///     }
+/// }
+///
+/// async fn can_afford(api: &dyn KalshiApi, cost: i64) -> Result<bool, KalshiError> {
+///     Ok(api.get_balance().await? >= cost)
+/// }
+///
+/// assert!(can_afford(&FixedBalance(1_000), 400).await?);
+/// assert!(can_afford(&kalshi_instance, 400).await.is_ok());
+/// ```
+#[async_trait]
+pub trait KalshiApi: Send + Sync {
+    async fn get_exchange_status(&self) -> Result<ExchangeStatus, KalshiError> {
+        Err(not_implemented("get_exchange_status"))
+    }
+
+    async fn get_exchange_schedule(&self) -> Result<ExchangeScheduleStandard, KalshiError> {
+        Err(not_implemented("get_exchange_schedule"))
+    }
+
+    async fn get_exchange_announcements(&self) -> Result<Vec<Announcement>, KalshiError> {
+        Err(not_implemented("get_exchange_announcements"))
+    }
+
+    async fn get_single_event(
+        &self,
+        event_ticker: &String,
+        with_nested_markets: Option<bool>,
+    ) -> Result<Event, KalshiError> {
+        let _ = (event_ticker, with_nested_markets);
+        Err(not_implemented("get_single_event"))
+    }
+
+    async fn get_single_market(&self, ticker: &String) -> Result<Market, KalshiError> {
+        let _ = ticker;
+        Err(not_implemented("get_single_market"))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn get_multiple_markets(
+        &self,
+        limit: Option<i64>,
+        page_size: Option<i64>,
+        cursor: Option<String>,
+        event_ticker: Option<String>,
+        series_ticker: Option<String>,
+        max_close_ts: Option<i64>,
+        min_close_ts: Option<i64>,
+        status: &[MarketStatus],
+        tickers: Vec<String>,
+    ) -> BoxStream<'_, Result<Vec<Market>, KalshiError>> {
+        let _ = (limit, page_size, cursor, event_ticker, series_ticker);
+        let _ = (max_close_ts, min_close_ts, status, tickers);
+        failing_stream("get_multiple_markets")
+    }
+
+    async fn get_multiple_events(
+        &self,
+        limit: Option<i64>,
+        page_size: Option<i64>,
+        cursor: Option<String>,
+        status: Option<String>,
+        series_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+    ) -> BoxStream<'_, Result<Vec<Event>, KalshiError>> {
+        let _ = (limit, page_size, cursor);
+        let _ = (status, series_ticker, with_nested_markets);
+        failing_stream("get_multiple_events")
+    }
+
+    async fn get_series(&self, ticker: &String) -> Result<Series, KalshiError> {
+        let _ = ticker;
+        Err(not_implemented("get_series"))
+    }
+
+    async fn get_series_list(
+        &self,
+        category: &Category,
+        include_product_metadata: Option<bool>,
+        tags: Vec<String>,
+    ) -> Result<Vec<Series>, KalshiError> {
+        let _ = (category, include_product_metadata, tags);
+        Err(not_implemented("get_series_list"))
+    }
+
+    async fn get_all_series(
+        &self,
+        include_product_metadata: Option<bool>,
+    ) -> Result<Vec<Series>, KalshiError> {
+        let _ = include_product_metadata;
+        Err(not_implemented("get_all_series"))
+    }
+
+    async fn get_market_orderbook(
+        &self,
+        ticker: &String,
+        depth: Option<i32>,
+    ) -> Result<Orderbook, KalshiError> {
+        let _ = (ticker, depth);
+        Err(not_implemented("get_market_orderbook"))
+    }
+
+    async fn get_market_history(
+        &self,
+        ticker: &String,
+        limit: Option<i32>,
+        page_size: Option<i32>,
+        cursor: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> BoxStream<'_, Result<Snapshot, KalshiError>> {
+        let _ = (ticker, limit, page_size, cursor, min_ts, max_ts);
+        failing_stream("get_market_history")
+    }
+
+    async fn get_trades(
+        &self,
+        limit: Option<i32>,
+        page_size: Option<i32>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> BoxStream<'_, Result<Trade, KalshiError>> {
+        let _ = (limit, page_size, cursor, ticker, min_ts, max_ts);
+        failing_stream("get_trades")
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn get_markets_page(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        event_ticker: Option<String>,
+        series_ticker: Option<String>,
+        max_close_ts: Option<i64>,
+        min_close_ts: Option<i64>,
+        status: &[MarketStatus],
+        tickers: Vec<String>,
+    ) -> Result<(Vec<Market>, Option<String>), KalshiError> {
+        let _ = (limit, cursor, event_ticker, series_ticker);
+        let _ = (max_close_ts, min_close_ts, status, tickers);
+        Err(not_implemented("get_markets_page"))
+    }
+
+    async fn get_events_page(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        status: Option<String>,
+        series_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+    ) -> Result<(Vec<Event>, Option<String>), KalshiError> {
+        let _ = (limit, cursor, status, series_ticker, with_nested_markets);
+        Err(not_implemented("get_events_page"))
+    }
+
+    async fn get_market_history_page(
+        &self,
+        ticker: &String,
+        limit: Option<i32>,
+        cursor: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Vec<Snapshot>, Option<String>), KalshiError> {
+        let _ = (ticker, limit, cursor, min_ts, max_ts);
+        Err(not_implemented("get_market_history_page"))
+    }
+
+    async fn get_trades_page(
+        &self,
+        limit: Option<i32>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Vec<Trade>, Option<String>), KalshiError> {
+        let _ = (limit, cursor, ticker, min_ts, max_ts);
+        Err(not_implemented("get_trades_page"))
+    }
+
+    async fn get_market_candlesticks(
+        &self,
+        series_ticker: &str,
+        ticker: &str,
+        start_ts: i64,
+        end_ts: i64,
+        period_interval: i32,
+    ) -> Result<Vec<Candlestick>, KalshiError> {
+        let _ = (series_ticker, ticker, start_ts, end_ts, period_interval);
+        Err(not_implemented("get_market_candlesticks"))
+    }
+
+    async fn get_balance(&self) -> Result<i64, KalshiError> {
+        Err(not_implemented("get_balance"))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn get_multiple_orders(
+        &self,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        status: Option<String>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> Result<(Option<String>, Vec<Order>), KalshiError> {
+        let _ = (ticker, event_ticker, min_ts, max_ts, status, limit, cursor);
+        Err(not_implemented("get_multiple_orders"))
+    }
+
+    async fn get_single_order(&self, order_id: &String) -> Result<Order, KalshiError> {
+        let _ = order_id;
+        Err(not_implemented("get_single_order"))
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<CanceledOrder, KalshiError> {
+        let _ = order_id;
+        Err(not_implemented("cancel_order"))
+    }
+
+    async fn decrease_order(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> Result<Order, KalshiError> {
+        let _ = (order_id, reduce_by, reduce_to);
+        Err(not_implemented("decrease_order"))
+    }
+
+    async fn get_multiple_fills(
+        &self,
+        ticker: Option<String>,
+        order_id: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> Result<(Option<String>, Vec<Fill>), KalshiError> {
+        let _ = (ticker, order_id, min_ts, max_ts, limit, cursor);
+        Err(not_implemented("get_multiple_fills"))
+    }
+
+    async fn get_portfolio_settlements(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Option<String>, Vec<Settlement>), KalshiError> {
+        let _ = (limit, cursor, ticker, event_ticker, min_ts, max_ts);
+        Err(not_implemented("get_portfolio_settlements"))
+    }
+
+    async fn get_user_positions(
+        &self,
+        limit: Option<i64>,
+        page_size: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+    ) -> BoxStream<'_, Result<(Vec<EventPosition>, Vec<MarketPosition>), KalshiError>> {
+        let _ = (
+            limit,
+            page_size,
+            cursor,
+            settlement_status,
+            ticker,
+            event_ticker,
+        );
+        failing_stream("get_user_positions")
+    }
+
+    async fn get_positions_page(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+    ) -> Result<(Vec<EventPosition>, Vec<MarketPosition>, Option<String>), KalshiError> {
+        let _ = (limit, cursor, settlement_status, ticker, event_ticker);
+        Err(not_implemented("get_positions_page"))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn create_order(
+        &self,
+        action: Action,
+        client_order_id: Option<String>,
+        count: i32,
+        side: Side,
+        ticker: String,
+        input_type: OrderType,
+        buy_max_cost: Option<i64>,
+        expiration_ts: Option<i64>,
+        no_price: Option<i64>,
+        sell_position_floor: Option<i32>,
+        yes_price: Option<i64>,
+    ) -> Result<Order, KalshiError> {
+        let _ = (action, client_order_id, count, side, ticker, input_type);
+        let _ = (
+            buy_max_cost,
+            expiration_ts,
+            no_price,
+            sell_position_floor,
+            yes_price,
+        );
+        Err(not_implemented("create_order"))
+    }
+}
+
+fn not_implemented(method: &str) -> KalshiError {
+    KalshiError::InternalError(format!("{} is not implemented by this KalshiApi", method))
+}
+
+fn failing_stream<'a, T: Send + 'a>(method: &str) -> BoxStream<'a, Result<T, KalshiError>> {
+    futures::stream::once(futures::future::ready(Err(not_implemented(method)))).boxed()
+}
+
+#[async_trait]
+impl KalshiApi for Kalshi {
+    async fn get_exchange_status(&self) -> Result<ExchangeStatus, KalshiError> {
+        Kalshi::get_exchange_status(self).await
+    }
+
+    async fn get_exchange_schedule(&self) -> Result<ExchangeScheduleStandard, KalshiError> {
+        Kalshi::get_exchange_schedule(self).await
+    }
+
+    async fn get_exchange_announcements(&self) -> Result<Vec<Announcement>, KalshiError> {
+        Kalshi::get_exchange_announcements(self).await
+    }
+
+    async fn get_single_event(
+        &self,
+        event_ticker: &String,
+        with_nested_markets: Option<bool>,
+    ) -> Result<Event, KalshiError> {
+        Kalshi::get_single_event(self, event_ticker, with_nested_markets).await
+    }
+
+    async fn get_single_market(&self, ticker: &String) -> Result<Market, KalshiError> {
+        Kalshi::get_single_market(self, ticker).await
+    }
+
+    async fn get_multiple_markets(
+        &self,
+        limit: Option<i64>,
+        page_size: Option<i64>,
+        cursor: Option<String>,
+        event_ticker: Option<String>,
+        series_ticker: Option<String>,
+        max_close_ts: Option<i64>,
+        min_close_ts: Option<i64>,
+        status: &[MarketStatus],
+        tickers: Vec<String>,
+    ) -> BoxStream<'_, Result<Vec<Market>, KalshiError>> {
+        Kalshi::get_multiple_markets(
+            self,
+            limit,
+            page_size,
+            cursor,
+            event_ticker,
+            series_ticker,
+            max_close_ts,
+            min_close_ts,
+            status,
+            tickers,
+        )
+        .await
+        .boxed()
+    }
+
+    async fn get_multiple_events(
+        &self,
+        limit: Option<i64>,
+        page_size: Option<i64>,
+        cursor: Option<String>,
+        status: Option<String>,
+        series_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+    ) -> BoxStream<'_, Result<Vec<Event>, KalshiError>> {
+        Kalshi::get_multiple_events(
+            self,
+            limit,
+            page_size,
+            cursor,
+            status,
+            series_ticker,
+            with_nested_markets,
+        )
+        .await
+        .boxed()
+    }
+
+    async fn get_series(&self, ticker: &String) -> Result<Series, KalshiError> {
+        Kalshi::get_series(self, ticker).await
+    }
+
+    async fn get_series_list(
+        &self,
+        category: &Category,
+        include_product_metadata: Option<bool>,
+        tags: Vec<String>,
+    ) -> Result<Vec<Series>, KalshiError> {
+        Kalshi::get_series_list(self, category, include_product_metadata, tags).await
+    }
+
+    async fn get_all_series(
+        &self,
+        include_product_metadata: Option<bool>,
+    ) -> Result<Vec<Series>, KalshiError> {
+        Kalshi::get_all_series(self, include_product_metadata).await
+    }
+
+    async fn get_market_orderbook(
+        &self,
+        ticker: &String,
+        depth: Option<i32>,
+    ) -> Result<Orderbook, KalshiError> {
+        Kalshi::get_market_orderbook(self, ticker, depth).await
+    }
+
+    async fn get_market_history(
+        &self,
+        ticker: &String,
+        limit: Option<i32>,
+        page_size: Option<i32>,
+        cursor: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> BoxStream<'_, Result<Snapshot, KalshiError>> {
+        Kalshi::get_market_history(self, ticker, limit, page_size, cursor, min_ts, max_ts)
+            .await
+            .boxed()
+    }
+
+    async fn get_trades(
+        &self,
+        limit: Option<i32>,
+        page_size: Option<i32>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> BoxStream<'_, Result<Trade, KalshiError>> {
+        Kalshi::get_trades(self, limit, page_size, cursor, ticker, min_ts, max_ts)
+            .await
+            .boxed()
+    }
+
+    async fn get_markets_page(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        event_ticker: Option<String>,
+        series_ticker: Option<String>,
+        max_close_ts: Option<i64>,
+        min_close_ts: Option<i64>,
+        status: &[MarketStatus],
+        tickers: Vec<String>,
+    ) -> Result<(Vec<Market>, Option<String>), KalshiError> {
+        Kalshi::get_markets_page(
+            self,
+            limit,
+            cursor,
+            event_ticker,
+            series_ticker,
+            max_close_ts,
+            min_close_ts,
+            status,
+            tickers,
+        )
+        .await
+    }
+
+    async fn get_events_page(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        status: Option<String>,
+        series_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+    ) -> Result<(Vec<Event>, Option<String>), KalshiError> {
+        Kalshi::get_events_page(
+            self,
+            limit,
+            cursor,
+            status,
+            series_ticker,
+            with_nested_markets,
+        )
+        .await
+    }
+
+    async fn get_market_history_page(
+        &self,
+        ticker: &String,
+        limit: Option<i32>,
+        cursor: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Vec<Snapshot>, Option<String>), KalshiError> {
+        Kalshi::get_market_history_page(self, ticker, limit, cursor, min_ts, max_ts).await
+    }
+
+    async fn get_trades_page(
+        &self,
+        limit: Option<i32>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Vec<Trade>, Option<String>), KalshiError> {
+        Kalshi::get_trades_page(self, limit, cursor, ticker, min_ts, max_ts).await
+    }
+
+    async fn get_market_candlesticks(
+        &self,
+        series_ticker: &str,
+        ticker: &str,
+        start_ts: i64,
+        end_ts: i64,
+        period_interval: i32,
+    ) -> Result<Vec<Candlestick>, KalshiError> {
+        Kalshi::get_market_candlesticks(
+            self,
+            series_ticker,
+            ticker,
+            start_ts,
+            end_ts,
+            period_interval,
+        )
+        .await
+    }
+
+    async fn get_balance(&self) -> Result<i64, KalshiError> {
+        Kalshi::get_balance(self).await
+    }
+
+    async fn get_multiple_orders(
+        &self,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        status: Option<String>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> Result<(Option<String>, Vec<Order>), KalshiError> {
+        Kalshi::get_multiple_orders(
+            self,
+            ticker,
+            event_ticker,
+            min_ts,
+            max_ts,
+            status,
+            limit,
+            cursor,
+        )
+        .await
+    }
+
+    async fn get_single_order(&self, order_id: &String) -> Result<Order, KalshiError> {
+        Kalshi::get_single_order(self, order_id).await
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<CanceledOrder, KalshiError> {
+        Kalshi::cancel_order(self, order_id).await
+    }
+
+    async fn decrease_order(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> Result<Order, KalshiError> {
+        Kalshi::decrease_order(self, order_id, reduce_by, reduce_to).await
+    }
+
+    async fn get_multiple_fills(
+        &self,
+        ticker: Option<String>,
+        order_id: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> Result<(Option<String>, Vec<Fill>), KalshiError> {
+        Kalshi::get_multiple_fills(self, ticker, order_id, min_ts, max_ts, limit, cursor).await
+    }
+
+    async fn get_portfolio_settlements(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Option<String>, Vec<Settlement>), KalshiError> {
+        Kalshi::get_portfolio_settlements(self, limit, cursor, ticker, event_ticker, min_ts, max_ts)
+            .await
+    }
+
+    async fn get_user_positions(
+        &self,
+        limit: Option<i64>,
+        page_size: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+    ) -> BoxStream<'_, Result<(Vec<EventPosition>, Vec<MarketPosition>), KalshiError>> {
+        Kalshi::get_user_positions(
+            self,
+            limit,
+            page_size,
+            cursor,
+            settlement_status,
+            ticker,
+            event_ticker,
+        )
+        .await
+        .boxed()
+    }
+
+    async fn get_positions_page(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+    ) -> Result<(Vec<EventPosition>, Vec<MarketPosition>, Option<String>), KalshiError> {
+        Kalshi::get_positions_page(self, limit, cursor, settlement_status, ticker, event_ticker)
+            .await
+    }
+
+    async fn create_order(
+        &self,
+        action: Action,
+        client_order_id: Option<String>,
+        count: i32,
+        side: Side,
+        ticker: String,
+        input_type: OrderType,
+        buy_max_cost: Option<i64>,
+        expiration_ts: Option<i64>,
+        no_price: Option<i64>,
+        sell_position_floor: Option<i32>,
+        yes_price: Option<i64>,
+    ) -> Result<Order, KalshiError> {
+        Kalshi::create_order(
+            self,
+            action,
+            client_order_id,
+            count,
+            side,
+            ticker,
+            input_type,
+            buy_max_cost,
+            expiration_ts,
+            no_price,
+            sell_position_floor,
+            yes_price,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct FixedBalance(i64);
+
+    #[async_trait]
+    impl KalshiApi for FixedBalance {
+        async fn get_balance(&self) -> Result<i64, KalshiError> {
+            Ok(self.0)
+        }
+    }
+
+    async fn place_if_affordable(api: &dyn KalshiApi, cost: i64) -> Result<Order, KalshiError> {
+        if api.get_balance().await? < cost {
+            return Err(KalshiError::UserInputError(
+                "Insufficient balance".to_string(),
+            ));
+        }
+        api.create_order(
+            Action::Buy,
+            None,
+            1,
+            Side::Yes,
+            "KXHIGHNY-25OCT02-B80.5".to_string(),
+            OrderType::Limit,
+            None,
+            None,
+            None,
+            None,
+            Some(cost),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_mock_and_client_behind_the_trait() {
+        let mock = FixedBalance(10);
+        assert!(matches!(
+            place_if_affordable(&mock, 40).await,
+            Err(KalshiError::UserInputError(_))
+        ));
+        // Methods the mock doesn't implement fail instead of reaching the exchange
+        assert!(matches!(
+            place_if_affordable(&mock, 5).await,
+            Err(KalshiError::InternalError(_))
+        ));
+        let mut trades = mock.get_trades(None, None, None, None, None, None).await;
+        assert!(trades.next().await.unwrap().is_err());
+        assert!(trades.next().await.is_none());
+
+        let mut kalshi = Kalshi::new(crate::TradingEnvironment::DemoMode);
+        kalshi.set_dry_run(true);
+        let api: &dyn KalshiApi = &kalshi;
+        let order = api
+            .create_order(
+                Action::Buy,
+                None,
+                1,
+                Side::Yes,
+                "KXHIGHNY-25OCT02-B80.5".to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                None,
+                None,
+                Some(40),
+            )
+            .await
+            .unwrap();
+        assert!(order.order_id.starts_with("dry-run-"));
+    }
+}
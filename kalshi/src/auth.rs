@@ -1,7 +1,10 @@
 use super::Kalshi;
 use crate::kalshi_error::*;
-use crate::utils::api_key_headers;
+use crate::utils::{self, api_key_headers};
+use crate::wire::SendLogged;
 use crate::KalshiAuth;
+#[cfg(feature = "fix")]
+use base64::{prelude::BASE64_STANDARD, Engine};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
@@ -24,7 +27,7 @@ impl<'a> Kalshi {
     /// ```
     /// kalshi_instance.login("johndoe@example.com", "example_password").await?;
     /// ```
-    pub async fn login(&mut self, user: &str, password: &str) -> Result<(), KalshiError> {
+    pub async fn login(&self, user: &str, password: &str) -> Result<(), KalshiError> {
         let login_url: &str = &format!("{}/login", self.base_url.to_string());
 
         let login_payload = LoginPayload {
@@ -32,17 +35,18 @@ impl<'a> Kalshi {
             password: password.to_string(),
         };
 
-        let result: LoginResponse = self
-            .client
-            .post(login_url)
-            .json(&login_payload)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: LoginResponse = utils::parse_json(
+            self.client
+                .post(login_url)
+                .json(&login_payload)
+                .send_logged()
+                .await?,
+        )
+        .await?;
 
-        self.curr_token = Some(format!("Bearer {}", result.token));
-        self.member_id = Some(result.member_id);
+        *self.curr_token.write().unwrap_or_else(|p| p.into_inner()) =
+            Some(format!("Bearer {}", result.token));
+        *self.member_id.write().unwrap_or_else(|p| p.into_inner()) = Some(result.member_id);
 
         return Ok(());
     }
@@ -65,9 +69,9 @@ impl<'a> Kalshi {
 
         self.client
             .post(logout_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
+            .header("Authorization", self.get_user_token().unwrap())
             .header("content-type", "application/json".to_string())
-            .send()
+            .send_logged()
             .await?;
 
         return Ok(());
@@ -76,7 +80,9 @@ impl<'a> Kalshi {
     /// Generates authentication headers for HTTP requests based on the current auth method.
     ///
     /// This method handles both email/password authentication (using Bearer token) and
-    /// API key authentication (using KALSHI headers with signature).
+    /// API key authentication (using KALSHI headers with signature). The signer is locked only
+    /// while signing, so an instance shared between tasks, e.g. behind an `Arc`, can authenticate
+    /// requests concurrently.
     ///
     /// # Arguments
     /// * `path` - The request path for API key signing
@@ -91,13 +97,13 @@ impl<'a> Kalshi {
     /// let headers = kalshi_instance.generate_auth_headers("/trade-api/ws/v2", Method::GET)?;
     /// ```
     pub fn generate_auth_headers(
-        &mut self,
+        &self,
         path: &str,
         method: Method,
     ) -> Result<HeaderMap, KalshiError> {
         let mut headers = HeaderMap::new();
 
-        match &mut self.auth {
+        match &self.auth {
             KalshiAuth::EmailPassword => {
                 let curr_token = self.get_user_token().ok_or_else(|| {
                     KalshiError::UserInputError(
@@ -111,8 +117,9 @@ impl<'a> Kalshi {
                 headers.insert(header_name, header_value);
             }
             KalshiAuth::ApiKey { key_id, signer, .. } => {
+                let mut signer = signer.lock().unwrap_or_else(|p| p.into_inner());
                 let api_key_headers =
-                    api_key_headers(key_id, signer, path, method).map_err(|e| {
+                    api_key_headers(key_id, &mut signer, path, method).map_err(|e| {
                         KalshiError::InternalError(format!(
                             "API key header generation failed: {}",
                             e
@@ -132,6 +139,26 @@ impl<'a> Kalshi {
 
         Ok(headers)
     }
+
+    /// Signs `message` with the API key, returning the key id and the base64 encoded signature.
+    ///
+    /// Used by protocols other than REST that authenticate with the same key, such as FIX.
+    #[cfg(feature = "fix")]
+    pub(crate) fn sign_with_api_key(&self, message: &str) -> Result<(String, String), KalshiError> {
+        match &self.auth {
+            KalshiAuth::ApiKey { key_id, signer, .. } => {
+                let mut signer = signer.lock().unwrap_or_else(|p| p.into_inner());
+                let signature = signer
+                    .sign_oneshot_to_vec(message.as_bytes())
+                    .map_err(|e| KalshiError::InternalError(format!("Signing failed: {}", e)))?;
+                Ok((key_id.clone(), BASE64_STANDARD.encode(signature)))
+            }
+            _ => Err(KalshiError::UserInputError(
+                "An API key is required, create the instance with Kalshi::new_with_api_key"
+                    .to_string(),
+            )),
+        }
+    }
 }
 
 // used in login method
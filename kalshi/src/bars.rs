@@ -0,0 +1,216 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::MarketTrade;
+
+/// Open, high, low and close of a market's traded yes price over a bar, with the volume traded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bar {
+    pub market_ticker: String,
+    /// Start of the bar in seconds since the unix epoch, included.
+    pub start_ts: i64,
+    /// End of the bar in seconds since the unix epoch, excluded.
+    pub end_ts: i64,
+    pub open: u32,
+    pub high: u32,
+    pub low: u32,
+    pub close: u32,
+    /// Contracts traded during the bar.
+    pub volume: u64,
+    /// Number of trades during the bar.
+    pub trades: u32,
+}
+
+impl Bar {
+    fn new(trade: &MarketTrade, start_ts: i64, length: i64) -> Self {
+        Bar {
+            market_ticker: trade.market_ticker.clone(),
+            start_ts,
+            end_ts: start_ts + length,
+            open: trade.yes_price,
+            high: trade.yes_price,
+            low: trade.yes_price,
+            close: trade.yes_price,
+            volume: trade.count as u64,
+            trades: 1,
+        }
+    }
+
+    fn add(&mut self, trade: &MarketTrade) {
+        self.high = self.high.max(trade.yes_price);
+        self.low = self.low.min(trade.yes_price);
+        self.close = trade.yes_price;
+        self.volume += trade.count as u64;
+        self.trades += 1;
+    }
+}
+
+/// Builds bars of a fixed length from trades, separately for every market.
+///
+/// Bars are aligned to the unix epoch and only exist for periods a market traded in. A market's
+/// bar completes when one of its trades falls in a later bar, or when closed explicitly with
+/// [`BarAggregator::close_until`]. Trades older than a market's open bar are counted in it, so
+/// trades should come in time order.
+///
+/// ```
+/// let trades = kalshi_instance
+///     .get_trades(None, None, None, Some(ticker), Some(start_ts), None)
+///     .await
+///     .map(|trade| MarketTrade::try_from(trade?))
+///     .try_collect::<Vec<_>>()
+///     .await?;
+/// let bars = BarAggregator::from_trades(trades, Duration::from_secs(300));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BarAggregator {
+    length: i64,
+    open: HashMap<String, Bar>,
+}
+
+impl BarAggregator {
+    /// Aggregates into bars of `length`, rounded down to whole seconds and at least one second.
+    pub fn new(length: Duration) -> Self {
+        BarAggregator {
+            length: length.as_secs().max(1) as i64,
+            open: HashMap::new(),
+        }
+    }
+
+    /// The bars of every trade, sorted by time first, in the order they complete.
+    pub fn from_trades(mut trades: Vec<MarketTrade>, length: Duration) -> Vec<Bar> {
+        trades.sort_by_key(|trade| trade.ts);
+        let mut aggregator = BarAggregator::new(length);
+        let mut bars: Vec<Bar> = trades
+            .iter()
+            .filter_map(|trade| aggregator.on_trade(trade))
+            .collect();
+        bars.extend(aggregator.finish());
+        bars
+    }
+
+    /// Adds a trade, returning the market's previous bar if the trade completed it.
+    pub fn on_trade(&mut self, trade: &MarketTrade) -> Option<Bar> {
+        let start_ts = trade.ts.div_euclid(self.length) * self.length;
+        match self.open.get_mut(&trade.market_ticker) {
+            Some(bar) if trade.ts < bar.end_ts => {
+                bar.add(trade);
+                None
+            }
+            _ => self.open.insert(
+                trade.market_ticker.clone(),
+                Bar::new(trade, start_ts, self.length),
+            ),
+        }
+    }
+
+    /// Completes the bars that ended at or before `ts`, for markets that stopped trading.
+    pub fn close_until(&mut self, ts: i64) -> Vec<Bar> {
+        let ended: Vec<String> = self
+            .open
+            .iter()
+            .filter(|(_, bar)| bar.end_ts <= ts)
+            .map(|(ticker, _)| ticker.clone())
+            .collect();
+        let mut bars: Vec<Bar> = ended
+            .iter()
+            .filter_map(|ticker| self.open.remove(ticker))
+            .collect();
+        bars.sort_by(|a, b| (a.end_ts, &a.market_ticker).cmp(&(b.end_ts, &b.market_ticker)));
+        bars
+    }
+
+    /// The bar a market is still adding trades to.
+    pub fn open_bar(&self, market_ticker: &str) -> Option<&Bar> {
+        self.open.get(market_ticker)
+    }
+
+    /// Completes every open bar, whether or not it ended.
+    pub fn finish(&mut self) -> Vec<Bar> {
+        self.close_until(i64::MAX)
+    }
+}
+
+/// Streams the bars of `trades` as they complete, finishing the open bars when `trades` ends.
+///
+/// Bars only complete on a later trade of the same market, see
+/// [`KalshiWebsocketClient::trade_bars`](crate::KalshiWebsocketClient::trade_bars) for live
+/// bars that also complete on time.
+pub fn bar_stream<S>(trades: S, length: Duration) -> impl Stream<Item = Bar>
+where
+    S: Stream<Item = MarketTrade>,
+{
+    async_stream::stream! {
+        let mut aggregator = BarAggregator::new(length);
+        let mut trades = Box::pin(trades);
+        while let Some(trade) = trades.next().await {
+            if let Some(bar) = aggregator.on_trade(&trade) {
+                yield bar;
+            }
+        }
+        for bar in aggregator.finish() {
+            yield bar;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Side;
+
+    fn trade(ticker: &str, yes_price: u32, count: u32, ts: i64) -> MarketTrade {
+        MarketTrade {
+            trade_id: format!("{}-{}", ticker, ts),
+            market_ticker: ticker.to_string(),
+            taker_side: Side::Yes,
+            count,
+            yes_price,
+            no_price: 100 - yes_price,
+            ts,
+        }
+    }
+
+    #[test]
+    fn test_bars_per_market() {
+        let trades = vec![
+            trade("A", 40, 5, 0),
+            trade("B", 70, 1, 10),
+            trade("A", 45, 2, 30),
+            trade("A", 38, 3, 59),
+            trade("A", 41, 1, 61),
+            trade("A", 42, 1, 200),
+        ];
+        let bars = BarAggregator::from_trades(trades, Duration::from_secs(60));
+
+        assert_eq!(
+            bars[0],
+            Bar {
+                market_ticker: "A".to_string(),
+                start_ts: 0,
+                end_ts: 60,
+                open: 40,
+                high: 45,
+                low: 38,
+                close: 38,
+                volume: 10,
+                trades: 3,
+            }
+        );
+        assert_eq!((bars[1].start_ts, bars[1].close), (60, 41));
+        let finished: Vec<(&str, i64)> = bars[2..]
+            .iter()
+            .map(|bar| (bar.market_ticker.as_str(), bar.start_ts))
+            .collect();
+        assert_eq!(finished, vec![("B", 0), ("A", 180)]);
+    }
+
+    #[tokio::test]
+    async fn test_bar_stream_finishes_open_bars() {
+        let trades = futures::stream::iter(vec![trade("A", 40, 1, 0), trade("A", 50, 1, 90)]);
+        let bars: Vec<Bar> = bar_stream(trades, Duration::from_secs(60)).collect().await;
+        assert_eq!(bars.len(), 2);
+        assert_eq!((bars[1].start_ts, bars[1].open), (60, 50));
+    }
+}
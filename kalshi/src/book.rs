@@ -0,0 +1,750 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Orderbook, Side, Trade};
+
+/// The resting orders of a market, built from either the REST api or the websocket feed.
+///
+/// Kalshi books only contain bids: a yes bid at `p` cents is equivalent to a no ask at `100 - p`.
+/// Each side maps a price in cents to the number of contracts resting at that price. Prices must be
+/// within 1 to 99 cents, levels at other prices are dropped when building or changing the book.
+///
+/// ```
+/// // From REST
+/// let rest = kalshi_instance.get_market_orderbook(&ticker, None).await?;
+/// let mut book = Book::from_orderbook(&ticker, &rest);
+///
+/// // Or from the websocket feed, kept current with deltas
+/// match msg {
+///     KalshiWebsocketResponse::OrderbookSnapshot { msg, .. } => book = Book::from(&msg),
+///     KalshiWebsocketResponse::OrderbookDelta { msg, .. } => book.apply_delta(&msg),
+///     _ => {}
+/// }
+/// println!("best yes bid {:?}, best yes ask {:?}", book.best_yes_bid(), book.best_yes_ask());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Book {
+    pub market_ticker: String,
+    /// Yes bids, price in cents to quantity.
+    pub yes: BTreeMap<u32, i64>,
+    /// No bids, price in cents to quantity.
+    pub no: BTreeMap<u32, i64>,
+}
+
+impl Book {
+    pub fn new(market_ticker: &str) -> Self {
+        Book {
+            market_ticker: market_ticker.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Builds a book from the response of [`Kalshi::get_market_orderbook`](crate::Kalshi::get_market_orderbook).
+    pub fn from_orderbook(market_ticker: &str, orderbook: &Orderbook) -> Self {
+        let levels = |side: &Option<Vec<Vec<i32>>>| {
+            side.iter()
+                .flatten()
+                .filter_map(|level| match level.as_slice() {
+                    [price, quantity, ..] if *price >= 0 && *quantity > 0 => {
+                        let price = *price as u32;
+                        is_valid_price(price).then_some((price, *quantity as i64))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        Book {
+            market_ticker: market_ticker.to_string(),
+            yes: levels(&orderbook.yes),
+            no: levels(&orderbook.no),
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut BTreeMap<u32, i64> {
+        match side {
+            Side::Yes => &mut self.yes,
+            Side::No => &mut self.no,
+        }
+    }
+
+    /// Changes the quantity resting at `price` by `delta`, removing the level once it's empty.
+    pub fn apply_change(&mut self, side: Side, price: u32, delta: i64) {
+        if !is_valid_price(price) {
+            log::warn!(
+                "Ignoring change at {} cents on {}, outside of the book",
+                price,
+                self.market_ticker
+            );
+            return;
+        }
+        let levels = self.side_mut(side);
+        let quantity = levels.entry(price).or_default();
+        *quantity += delta;
+        if *quantity <= 0 {
+            levels.remove(&price);
+        }
+    }
+
+    /// Highest yes bid as `(price, quantity)`.
+    pub fn best_yes_bid(&self) -> Option<(u32, i64)> {
+        self.yes.iter().next_back().map(|(p, q)| (*p, *q))
+    }
+
+    /// Highest no bid as `(price, quantity)`.
+    pub fn best_no_bid(&self) -> Option<(u32, i64)> {
+        self.no.iter().next_back().map(|(p, q)| (*p, *q))
+    }
+
+    /// Lowest yes ask as `(price, quantity)`, implied by the highest no bid.
+    ///
+    /// `None` if the highest no bid is over 100 cents, which only a level inserted into `no`
+    /// directly can be.
+    pub fn best_yes_ask(&self) -> Option<(u32, i64)> {
+        let (price, quantity) = self.best_no_bid()?;
+        Some((100u32.checked_sub(price)?, quantity))
+    }
+
+    /// Lowest no ask as `(price, quantity)`, implied by the highest yes bid.
+    ///
+    /// `None` if the highest yes bid is over 100 cents, which only a level inserted into `yes`
+    /// directly can be.
+    pub fn best_no_ask(&self) -> Option<(u32, i64)> {
+        let (price, quantity) = self.best_yes_bid()?;
+        Some((100u32.checked_sub(price)?, quantity))
+    }
+
+    /// Yes ask minus yes bid in cents, `None` when either side is empty.
+    pub fn spread(&self) -> Option<u32> {
+        let (bid, _) = self.best_yes_bid()?;
+        let (ask, _) = self.best_yes_ask()?;
+        Some(ask.saturating_sub(bid))
+    }
+
+    /// Midpoint of the yes bid and ask in cents, `None` when either side is empty.
+    pub fn mid_price(&self) -> Option<f64> {
+        let (bid, _) = self.best_yes_bid()?;
+        let (ask, _) = self.best_yes_ask()?;
+        Some((bid + ask) as f64 / 2.0)
+    }
+
+    /// Top of book size imbalance between -1 and 1, positive when more contracts bid yes than offer it.
+    ///
+    /// `(bid size - ask size) / (bid size + ask size)` at the best yes bid and ask,
+    /// `None` when either side is empty.
+    pub fn imbalance(&self) -> Option<f64> {
+        let (_, bid_size) = self.best_yes_bid()?;
+        let (_, ask_size) = self.best_yes_ask()?;
+        Some((bid_size - ask_size) as f64 / (bid_size + ask_size) as f64)
+    }
+
+    /// Size weighted mid of the yes bid and ask in cents, leaning towards the side with less size.
+    ///
+    /// The heavier side of the book is the one more likely to hold, so the microprice moves
+    /// towards the other, `None` when either side is empty.
+    pub fn microprice(&self) -> Option<f64> {
+        let (bid, bid_size) = self.best_yes_bid()?;
+        let (ask, ask_size) = self.best_yes_ask()?;
+        Some(
+            (bid as f64 * ask_size as f64 + ask as f64 * bid_size as f64)
+                / (bid_size + ask_size) as f64,
+        )
+    }
+
+    /// Contracts resting in the best `levels` price levels of a side.
+    pub fn depth(&self, side: Side, levels: usize) -> i64 {
+        let book = match side {
+            Side::Yes => &self.yes,
+            Side::No => &self.no,
+        };
+        book.values().rev().take(levels).sum()
+    }
+
+    /// Size imbalance over the best `levels` levels of each side, between -1 and 1, positive when
+    /// more contracts bid yes than no.
+    ///
+    /// `None` when both sides are empty.
+    ///
+    /// ```
+    /// if book.pressure(3).is_some_and(|pressure| pressure > 0.5) {
+    ///     println!("buyers lean on {}", book.market_ticker);
+    /// }
+    /// ```
+    pub fn pressure(&self, levels: usize) -> Option<f64> {
+        let yes = self.depth(Side::Yes, levels);
+        let no = self.depth(Side::No, levels);
+        (yes + no > 0).then(|| (yes - no) as f64 / (yes + no) as f64)
+    }
+
+    /// Contracts resting on both sides of the book.
+    pub fn total_quantity(&self) -> i64 {
+        self.yes.values().chain(self.no.values()).sum()
+    }
+}
+
+/// Prices a market trades at, in cents.
+const PRICE_SLOTS: usize = 100;
+
+/// Whether a market can trade at `price` cents.
+fn is_valid_price(price: u32) -> bool {
+    price > 0 && (price as usize) < PRICE_SLOTS
+}
+
+/// A [`Book`] stored as one array slot per price, for users keeping hundreds of books current.
+///
+/// Applying a delta is an index into the array instead of a tree lookup and the best bids are
+/// cached, so the book never allocates once created. Prices must be within 1 to 99 cents, changes
+/// at other prices are ignored. Convert to a [`Book`] for the analytics it offers.
+///
+/// ```
+/// let mut book = ArrayBook::new(&ticker);
+/// match msg {
+///     KalshiWebsocketResponse::OrderbookSnapshot { msg, .. } => book = ArrayBook::from(&msg),
+///     KalshiWebsocketResponse::OrderbookDelta { msg, .. } => book.apply_delta(&msg),
+///     _ => {}
+/// }
+/// println!("best yes bid {:?}, best yes ask {:?}", book.best_yes_bid(), book.best_yes_ask());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayBook {
+    pub market_ticker: String,
+    /// Yes bids, quantity by price in cents.
+    yes: [i64; PRICE_SLOTS],
+    /// No bids, quantity by price in cents.
+    no: [i64; PRICE_SLOTS],
+    /// Highest yes bid, 0 when there is none.
+    best_yes: u32,
+    /// Highest no bid, 0 when there is none.
+    best_no: u32,
+}
+
+impl ArrayBook {
+    pub fn new(market_ticker: &str) -> Self {
+        ArrayBook {
+            market_ticker: market_ticker.to_string(),
+            yes: [0; PRICE_SLOTS],
+            no: [0; PRICE_SLOTS],
+            best_yes: 0,
+            best_no: 0,
+        }
+    }
+
+    /// Changes the quantity resting at `price` by `delta`, clearing the level once it's empty.
+    pub fn apply_change(&mut self, side: Side, price: u32, delta: i64) {
+        if !is_valid_price(price) {
+            log::warn!(
+                "Ignoring change at {} cents on {}, outside of the book",
+                price,
+                self.market_ticker
+            );
+            return;
+        }
+        let (levels, best) = match side {
+            Side::Yes => (&mut self.yes, &mut self.best_yes),
+            Side::No => (&mut self.no, &mut self.best_no),
+        };
+        let quantity = &mut levels[price as usize];
+        *quantity = (*quantity + delta).max(0);
+        if *quantity > 0 {
+            *best = (*best).max(price);
+        } else if price == *best {
+            // At most 98 slots to look at
+            *best = (1..price)
+                .rev()
+                .find(|p| levels[*p as usize] > 0)
+                .unwrap_or(0);
+        }
+    }
+
+    /// Contracts resting at `price` on a side.
+    pub fn quantity_at(&self, side: Side, price: u32) -> i64 {
+        let levels = match side {
+            Side::Yes => &self.yes,
+            Side::No => &self.no,
+        };
+        levels.get(price as usize).copied().unwrap_or(0)
+    }
+
+    /// The non empty levels of a side as `(price, quantity)`, best first.
+    pub fn levels(&self, side: Side) -> impl Iterator<Item = (u32, i64)> + '_ {
+        let (levels, best) = match side {
+            Side::Yes => (&self.yes, self.best_yes),
+            Side::No => (&self.no, self.best_no),
+        };
+        (1..=best)
+            .rev()
+            .map(|price| (price, levels[price as usize]))
+            .filter(|(_, quantity)| *quantity > 0)
+    }
+
+    /// Highest yes bid as `(price, quantity)`.
+    pub fn best_yes_bid(&self) -> Option<(u32, i64)> {
+        (self.best_yes > 0).then(|| (self.best_yes, self.yes[self.best_yes as usize]))
+    }
+
+    /// Highest no bid as `(price, quantity)`.
+    pub fn best_no_bid(&self) -> Option<(u32, i64)> {
+        (self.best_no > 0).then(|| (self.best_no, self.no[self.best_no as usize]))
+    }
+
+    /// Lowest yes ask as `(price, quantity)`, implied by the highest no bid.
+    pub fn best_yes_ask(&self) -> Option<(u32, i64)> {
+        self.best_no_bid().map(|(p, q)| (100 - p, q))
+    }
+
+    /// Lowest no ask as `(price, quantity)`, implied by the highest yes bid.
+    pub fn best_no_ask(&self) -> Option<(u32, i64)> {
+        self.best_yes_bid().map(|(p, q)| (100 - p, q))
+    }
+
+    /// Yes ask minus yes bid in cents, `None` when either side is empty.
+    pub fn spread(&self) -> Option<u32> {
+        let (bid, _) = self.best_yes_bid()?;
+        let (ask, _) = self.best_yes_ask()?;
+        Some(ask.saturating_sub(bid))
+    }
+
+    /// Midpoint of the yes bid and ask in cents, `None` when either side is empty.
+    pub fn mid_price(&self) -> Option<f64> {
+        let (bid, _) = self.best_yes_bid()?;
+        let (ask, _) = self.best_yes_ask()?;
+        Some((bid + ask) as f64 / 2.0)
+    }
+
+    /// Contracts resting in the best `levels` price levels of a side.
+    pub fn depth(&self, side: Side, levels: usize) -> i64 {
+        self.levels(side).take(levels).map(|(_, q)| q).sum()
+    }
+
+    /// Contracts resting on both sides of the book.
+    pub fn total_quantity(&self) -> i64 {
+        self.yes.iter().chain(self.no.iter()).sum()
+    }
+}
+
+impl From<&Book> for ArrayBook {
+    /// Levels outside of 1 to 99 cents are dropped.
+    fn from(book: &Book) -> Self {
+        let mut array_book = ArrayBook::new(&book.market_ticker);
+        for (price, quantity) in &book.yes {
+            array_book.apply_change(Side::Yes, *price, *quantity);
+        }
+        for (price, quantity) in &book.no {
+            array_book.apply_change(Side::No, *price, *quantity);
+        }
+        array_book
+    }
+}
+
+impl From<&ArrayBook> for Book {
+    fn from(book: &ArrayBook) -> Self {
+        Book {
+            market_ticker: book.market_ticker.clone(),
+            yes: book.levels(Side::Yes).collect(),
+            no: book.levels(Side::No).collect(),
+        }
+    }
+}
+
+/// The books of every market in an event, for questions that span markets.
+///
+/// Markets of an event are usually mutually exclusive buckets (temperature ranges, vote shares...),
+/// so their yes prices together describe the market's view of the outcome.
+///
+/// ```
+/// let mut event_book = EventBook::new("KXHIGHNY-25OCT02");
+/// while let Some(msg) = stream.next().await {
+///     if event_book.on_message(&msg?) {
+///         println!("{:?}", event_book.implied_distribution());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventBook {
+    pub event_ticker: String,
+    /// Books by market ticker.
+    pub books: BTreeMap<String, Book>,
+}
+
+impl EventBook {
+    pub fn new(event_ticker: &str) -> Self {
+        EventBook {
+            event_ticker: event_ticker.to_string(),
+            books: BTreeMap::new(),
+        }
+    }
+
+    /// Whether a market belongs to this event, market tickers extend their event ticker with a `-` suffix.
+    pub fn contains_market(&self, market_ticker: &str) -> bool {
+        market_ticker
+            .strip_prefix(self.event_ticker.as_str())
+            .is_some_and(|rest| rest.starts_with('-'))
+    }
+
+    /// Adds or replaces the book of one of the event's markets.
+    pub fn insert(&mut self, book: Book) {
+        self.books.insert(book.market_ticker.clone(), book);
+    }
+
+    pub fn book(&self, market_ticker: &str) -> Option<&Book> {
+        self.books.get(market_ticker)
+    }
+
+    /// Probability of each market resolving yes, from the yes mid prices scaled to sum to one.
+    ///
+    /// Markets without a two-sided book are left out. Only meaningful for mutually exclusive markets.
+    pub fn implied_distribution(&self) -> Vec<(String, f64)> {
+        let mids: Vec<(String, f64)> = self
+            .books
+            .iter()
+            .filter_map(|(ticker, book)| book.mid_price().map(|mid| (ticker.clone(), mid)))
+            .collect();
+        let total: f64 = mids.iter().map(|(_, mid)| mid).sum();
+        if total <= 0.0 {
+            return Vec::new();
+        }
+        mids.into_iter()
+            .map(|(ticker, mid)| (ticker, mid / total))
+            .collect()
+    }
+
+    /// Sum of the yes mid prices in cents, 100 for a fairly priced set of mutually exclusive markets.
+    pub fn implied_total(&self) -> f64 {
+        self.books.values().filter_map(Book::mid_price).sum()
+    }
+
+    /// Contracts resting across every market of the event.
+    pub fn total_liquidity(&self) -> i64 {
+        self.books.values().map(Book::total_quantity).sum()
+    }
+
+    /// Markets whose yes mid is more than `threshold` cents away from the average of their neighbours.
+    ///
+    /// Markets are ordered by the strike at the end of their ticker (`-B80.5`, `-T85`), or by
+    /// ticker when there's no strike, so a bucket priced out of line with the buckets around it
+    /// stands out. The lowest and highest markets only have one neighbour and are never flagged.
+    pub fn outliers(&self, threshold: f64) -> Vec<String> {
+        let mut mids: Vec<(&String, f64)> = self
+            .books
+            .iter()
+            .filter_map(|(ticker, book)| book.mid_price().map(|mid| (ticker, mid)))
+            .collect();
+        mids.sort_by(|(a, _), (b, _)| {
+            strike(a)
+                .partial_cmp(&strike(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.cmp(b))
+        });
+
+        let mut outliers = Vec::new();
+        for (i, (ticker, mid)) in mids.iter().enumerate() {
+            let (Some((_, below)), Some((_, above))) =
+                (i.checked_sub(1).and_then(|j| mids.get(j)), mids.get(i + 1))
+            else {
+                continue;
+            };
+            if (mid - (below + above) / 2.0).abs() > threshold {
+                outliers.push((*ticker).clone());
+            }
+        }
+        outliers
+    }
+}
+
+/// The strike at the end of a market ticker, `KXHIGHNY-25OCT02-B80.5` is 80.5.
+fn strike(market_ticker: &str) -> Option<f64> {
+    let (_, suffix) = market_ticker.rsplit_once('-')?;
+    suffix
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .parse()
+        .ok()
+}
+
+/// A trade on a market, from either [`Kalshi::get_trades`](crate::Kalshi::get_trades) or the websocket `trade` channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketTrade {
+    pub trade_id: String,
+    pub market_ticker: String,
+    /// The side the taker bought.
+    pub taker_side: Side,
+    pub count: u32,
+    /// Executed yes price in cents.
+    pub yes_price: u32,
+    /// Executed no price in cents.
+    pub no_price: u32,
+    /// Execution time in seconds since the unix epoch.
+    pub ts: i64,
+}
+
+impl TryFrom<Trade> for MarketTrade {
+    type Error = crate::KalshiError;
+
+    fn try_from(trade: Trade) -> Result<Self, Self::Error> {
+        let taker_side = match trade.taker_side.as_str() {
+            "yes" => Side::Yes,
+            "no" => Side::No,
+            other => {
+                return Err(crate::KalshiError::InternalError(format!(
+                    "Unexpected taker side {}",
+                    other
+                )))
+            }
+        };
+        let ts = chrono::DateTime::parse_from_rfc3339(&trade.created_time)
+            .map_err(|e| {
+                crate::KalshiError::InternalError(format!(
+                    "Unexpected trade time {}: {}",
+                    trade.created_time, e
+                ))
+            })?
+            .timestamp();
+        Ok(MarketTrade {
+            trade_id: trade.trade_id,
+            market_ticker: trade.ticker,
+            taker_side,
+            count: trade.count.max(0) as u32,
+            yes_price: trade.yes_price.max(0) as u32,
+            no_price: trade.no_price.max(0) as u32,
+            ts,
+        })
+    }
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::responses::{
+        KalshiOrderbookDeltaMessage, KalshiOrderbookSnapshotMessage, KalshiSide,
+        KalshiTradeMessage, KalshiWebsocketResponse,
+    };
+
+    impl From<KalshiSide> for Side {
+        fn from(side: KalshiSide) -> Self {
+            match side {
+                KalshiSide::Yes => Side::Yes,
+                KalshiSide::No => Side::No,
+            }
+        }
+    }
+
+    impl From<&KalshiOrderbookSnapshotMessage> for Book {
+        fn from(snapshot: &KalshiOrderbookSnapshotMessage) -> Self {
+            let levels = |side: &Option<Vec<(u32, i32)>>| {
+                side.iter()
+                    .flatten()
+                    .filter(|(price, quantity)| *quantity > 0 && is_valid_price(*price))
+                    .map(|(price, quantity)| (*price, *quantity as i64))
+                    .collect()
+            };
+            Book {
+                market_ticker: snapshot.market_ticker.to_string(),
+                yes: levels(&snapshot.yes),
+                no: levels(&snapshot.no),
+            }
+        }
+    }
+
+    impl Book {
+        /// Applies an `orderbook_delta` message to the book.
+        pub fn apply_delta(&mut self, delta: &KalshiOrderbookDeltaMessage) {
+            self.apply_change(delta.side.into(), delta.price, delta.delta as i64);
+        }
+    }
+
+    impl From<&KalshiOrderbookSnapshotMessage> for ArrayBook {
+        fn from(snapshot: &KalshiOrderbookSnapshotMessage) -> Self {
+            let mut book = ArrayBook::new(&snapshot.market_ticker);
+            for (side, levels) in [(Side::Yes, &snapshot.yes), (Side::No, &snapshot.no)] {
+                for (price, quantity) in levels.iter().flatten() {
+                    book.apply_change(side, *price, *quantity as i64);
+                }
+            }
+            book
+        }
+    }
+
+    impl ArrayBook {
+        /// Applies an `orderbook_delta` message to the book.
+        pub fn apply_delta(&mut self, delta: &KalshiOrderbookDeltaMessage) {
+            self.apply_change(delta.side.into(), delta.price, delta.delta as i64);
+        }
+    }
+
+    impl EventBook {
+        /// Applies an orderbook snapshot or delta of one of the event's markets.
+        ///
+        /// Returns whether the message changed a book, messages of other markets and other channels are ignored.
+        pub fn on_message(&mut self, msg: &KalshiWebsocketResponse) -> bool {
+            match msg {
+                KalshiWebsocketResponse::OrderbookSnapshot { msg, .. }
+                    if self.contains_market(&msg.market_ticker) =>
+                {
+                    self.insert(Book::from(msg));
+                    true
+                }
+                KalshiWebsocketResponse::OrderbookDelta { msg, .. }
+                    if self.contains_market(&msg.market_ticker) =>
+                {
+                    // Looked up first so deltas of known markets don't allocate a key
+                    match self.books.get_mut(msg.market_ticker.as_str()) {
+                        Some(book) => book.apply_delta(msg),
+                        None => {
+                            let mut book = Book::new(&msg.market_ticker);
+                            book.apply_delta(msg);
+                            self.insert(book);
+                        }
+                    }
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    impl From<KalshiTradeMessage> for MarketTrade {
+        fn from(trade: KalshiTradeMessage) -> Self {
+            MarketTrade {
+                trade_id: trade.trade_id,
+                market_ticker: trade.market_ticker.into(),
+                taker_side: trade.taker_side.into(),
+                count: trade.count,
+                yes_price: trade.yes_price,
+                no_price: trade.no_price,
+                ts: trade.ts as i64,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rest_book_best_prices() {
+        let orderbook = Orderbook {
+            yes: Some(vec![vec![40, 10], vec![42, 5]]),
+            no: Some(vec![vec![55, 3]]),
+        };
+        let mut book = Book::from_orderbook("KXHIGHNY-25OCT02-B80.5", &orderbook);
+        assert_eq!(book.best_yes_bid(), Some((42, 5)));
+        assert_eq!(book.best_yes_ask(), Some((45, 3)));
+        assert_eq!(book.spread(), Some(3));
+
+        book.apply_change(Side::Yes, 42, -5);
+        assert_eq!(book.best_yes_bid(), Some((40, 10)));
+    }
+
+    #[test]
+    fn test_out_of_range_levels_dropped() {
+        let orderbook = Orderbook {
+            yes: Some(vec![vec![40, 10], vec![150, 5], vec![0, 2]]),
+            no: Some(vec![vec![100, 3], vec![55, 4]]),
+        };
+        let mut book = Book::from_orderbook("KXHIGHNY-25OCT02-B80.5", &orderbook);
+        assert_eq!(book.best_yes_bid(), Some((40, 10)));
+        assert_eq!(book.best_no_ask(), Some((60, 10)));
+        assert_eq!(book.best_yes_ask(), Some((45, 4)));
+
+        book.apply_change(Side::No, 120, 7);
+        assert_eq!(book.best_no_bid(), Some((55, 4)));
+        book.no.insert(120, 7);
+        assert_eq!(book.best_yes_ask(), None);
+        assert_eq!(book.spread(), None);
+    }
+
+    #[test]
+    fn test_book_imbalance_and_microprice() {
+        let orderbook = Orderbook {
+            yes: Some(vec![vec![40, 10], vec![42, 30]]),
+            no: Some(vec![vec![55, 10], vec![50, 50]]),
+        };
+        let book = Book::from_orderbook("KXHIGHNY-25OCT02-B80.5", &orderbook);
+
+        assert_eq!(book.imbalance(), Some(0.5));
+        // (42 * 10 + 45 * 30) / 40
+        assert_eq!(book.microprice(), Some(44.25));
+        assert_eq!(book.depth(Side::Yes, 1), 30);
+        assert_eq!(book.depth(Side::No, 5), 60);
+        assert_eq!(book.pressure(1), Some(0.5));
+        assert_eq!(book.pressure(2), Some(-0.2));
+
+        let empty = Book::new("KXHIGHNY-25OCT02-B80.5");
+        assert_eq!(empty.microprice(), None);
+        assert_eq!(empty.pressure(3), None);
+    }
+
+    #[test]
+    fn test_array_book_matches_book() {
+        let changes = [
+            (Side::Yes, 40, 10),
+            (Side::Yes, 42, 5),
+            (Side::No, 55, 3),
+            (Side::Yes, 42, -5),
+            (Side::No, 57, 8),
+            (Side::Yes, 99, 1),
+            (Side::Yes, 99, -4),
+            (Side::No, 57, -8),
+            (Side::Yes, 1, 2),
+        ];
+        let mut book = Book::new("KXHIGHNY-25OCT02-B80.5");
+        let mut array_book = ArrayBook::new("KXHIGHNY-25OCT02-B80.5");
+        for (side, price, delta) in changes {
+            book.apply_change(side, price, delta);
+            array_book.apply_change(side, price, delta);
+            assert_eq!(array_book.best_yes_bid(), book.best_yes_bid());
+            assert_eq!(array_book.best_yes_ask(), book.best_yes_ask());
+            assert_eq!(array_book.depth(Side::Yes, 2), book.depth(Side::Yes, 2));
+        }
+        assert_eq!(Book::from(&array_book), book);
+        assert_eq!(ArrayBook::from(&book), array_book);
+        assert_eq!(array_book.total_quantity(), book.total_quantity());
+
+        // Outside of the book
+        array_book.apply_change(Side::Yes, 100, 5);
+        array_book.apply_change(Side::Yes, 0, 5);
+        assert_eq!(Book::from(&array_book), book);
+    }
+
+    #[test]
+    fn test_event_book_queries() {
+        let mut event_book = EventBook::new("KXHIGHNY-25OCT02");
+        for (strike, bid) in [("B78.5", 20), ("B80.5", 48), ("B82.5", 20), ("B84.5", 10)] {
+            let mut book = Book::new(&format!("KXHIGHNY-25OCT02-{}", strike));
+            book.apply_change(Side::Yes, bid, 10);
+            book.apply_change(Side::No, 98 - bid, 5);
+            event_book.insert(book);
+        }
+        assert!(event_book.contains_market("KXHIGHNY-25OCT02-B86.5"));
+        assert!(!event_book.contains_market("KXHIGHNY-25OCT03-B86.5"));
+
+        assert_eq!(event_book.implied_total(), 102.0);
+        let distribution = event_book.implied_distribution();
+        assert!((distribution.iter().map(|(_, p)| p).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(event_book.total_liquidity(), 60);
+        assert_eq!(event_book.outliers(20.0), vec!["KXHIGHNY-25OCT02-B80.5"]);
+    }
+
+    #[test]
+    fn test_rest_trade_conversion() {
+        let trade: Trade = serde_json::from_str(r#"{"trade_id":"5b0276ef-7715-46f2-56d8-a1c7b9e59e58","taker_side":"yes","ticker":"KXHIGHCHI-25OCT02-B80.5","count":7,"yes_price":27,"no_price":73,"created_time":"2025-10-01T20:30:09.123Z"}"#).unwrap();
+        let trade = MarketTrade::try_from(trade).unwrap();
+        assert_eq!(trade.taker_side, Side::Yes);
+        assert_eq!(trade.ts, 1759350609);
+    }
+
+    #[cfg(feature = "websockets")]
+    #[test]
+    fn test_ws_trade_matches_rest_trade() {
+        use crate::websockets::responses::KalshiWebsocketResponse;
+
+        let raw = r#"{"type":"trade","sid":1,"seq":16,"msg":{"trade_id":"5b0276ef-7715-46f2-56d8-a1c7b9e59e58","market_ticker":"KXHIGHCHI-25OCT02-B80.5","yes_price":27,"no_price":73,"count":7,"taker_side":"yes","ts":1759350609}}"#;
+        let KalshiWebsocketResponse::Trade { msg, .. } =
+            KalshiWebsocketResponse::from_text(raw).unwrap()
+        else {
+            panic!("expected a trade");
+        };
+        let rest: Trade = serde_json::from_str(r#"{"trade_id":"5b0276ef-7715-46f2-56d8-a1c7b9e59e58","taker_side":"yes","ticker":"KXHIGHCHI-25OCT02-B80.5","count":7,"yes_price":27,"no_price":73,"created_time":"2025-10-01T20:30:09Z"}"#).unwrap();
+        assert_eq!(MarketTrade::from(msg), MarketTrade::try_from(rest).unwrap());
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{Book, Kalshi, KalshiError};
+
+/// How a locally maintained book differs from the exchange's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookDivergence {
+    pub market_ticker: String,
+    /// Price levels, on either side, whose quantity differs or that only one book has.
+    pub mismatched_levels: usize,
+    /// Contracts the local book has in excess or is missing, summed over the mismatched levels.
+    pub quantity_difference: i64,
+    /// Whether the best yes and no bids, prices and quantities, are still the same.
+    pub best_bids_match: bool,
+}
+
+impl Book {
+    /// How this book differs from `reference`, `None` when both hold the same levels.
+    pub fn divergence(&self, reference: &Book) -> Option<BookDivergence> {
+        let mut mismatched_levels = 0;
+        let mut quantity_difference = 0;
+        for (local, exchange) in [(&self.yes, &reference.yes), (&self.no, &reference.no)] {
+            for price in local
+                .keys()
+                .chain(exchange.keys().filter(|p| !local.contains_key(p)))
+            {
+                let difference = local.get(price).copied().unwrap_or_default()
+                    - exchange.get(price).copied().unwrap_or_default();
+                if difference != 0 {
+                    mismatched_levels += 1;
+                    quantity_difference += difference.abs();
+                }
+            }
+        }
+        if mismatched_levels == 0 {
+            return None;
+        }
+        Some(BookDivergence {
+            market_ticker: self.market_ticker.clone(),
+            mismatched_levels,
+            quantity_difference,
+            best_bids_match: self.best_yes_bid() == reference.best_yes_bid()
+                && self.best_no_bid() == reference.best_no_bid(),
+        })
+    }
+}
+
+/// Totals of the checks run by a [`BookVerifier`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookVerifierStats {
+    /// Books compared with a REST snapshot.
+    pub checks: u64,
+    /// Checks that found a divergence.
+    pub divergent: u64,
+    /// Divergent books replaced by the REST snapshot.
+    pub repaired: u64,
+    /// Snapshots that couldn't be fetched.
+    pub failed: u64,
+    /// The latest divergence found.
+    pub last_divergence: Option<BookDivergence>,
+}
+
+/// Periodically cross-checks books kept current from websocket deltas against a fresh REST
+/// snapshot, and replaces the ones that drifted.
+///
+/// A delta lost or applied twice leaves a book wrong until the next websocket snapshot, which
+/// only comes with a new subscription. Comparing with the exchange's book every `interval`
+/// bounds how long such a book goes unnoticed. The REST snapshot and the feed aren't taken at the
+/// same instant, so a busy market can report a divergence that a delta in flight would have
+/// closed; repairing then costs nothing as the next deltas apply on top of the snapshot.
+///
+/// ```
+/// let mut verifier = BookVerifier::new(Duration::from_secs(60));
+/// // In the loop applying deltas to `books`
+/// for divergence in verifier.verify_due(&kalshi_instance, books.values_mut()).await {
+///     println!("{} was off by {} contracts", divergence.market_ticker, divergence.quantity_difference);
+/// }
+/// println!("{} of {} checks diverged", verifier.stats().divergent, verifier.stats().checks);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BookVerifier {
+    interval: Duration,
+    auto_repair: bool,
+    /// When each market was last checked
+    checked_at: HashMap<String, Instant>,
+    stats: BookVerifierStats,
+}
+
+impl BookVerifier {
+    /// Checks each book at most once per `interval`, repairing divergent ones.
+    pub fn new(interval: Duration) -> Self {
+        BookVerifier {
+            interval,
+            auto_repair: true,
+            checked_at: HashMap::new(),
+            stats: BookVerifierStats::default(),
+        }
+    }
+
+    /// Whether divergent books are replaced by the REST snapshot, true by default.
+    pub fn auto_repair(mut self, enabled: bool) -> Self {
+        self.auto_repair = enabled;
+        self
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn stats(&self) -> &BookVerifierStats {
+        &self.stats
+    }
+
+    /// Whether the market wasn't checked within the interval.
+    pub fn is_due(&self, market_ticker: &str) -> bool {
+        self.checked_at
+            .get(market_ticker)
+            .map_or(true, |at| at.elapsed() >= self.interval)
+    }
+
+    /// Compares a book with a REST snapshot right away, repairing it if enabled.
+    pub async fn verify(
+        &mut self,
+        kalshi: &Kalshi,
+        book: &mut Book,
+    ) -> Result<Option<BookDivergence>, KalshiError> {
+        self.checked_at
+            .insert(book.market_ticker.clone(), Instant::now());
+        let orderbook = match kalshi.get_market_orderbook(&book.market_ticker, None).await {
+            Ok(orderbook) => orderbook,
+            Err(e) => {
+                self.stats.failed += 1;
+                return Err(e);
+            }
+        };
+        let reference = Book::from_orderbook(&book.market_ticker, &orderbook);
+        self.stats.checks += 1;
+        let Some(divergence) = book.divergence(&reference) else {
+            return Ok(None);
+        };
+        self.stats.divergent += 1;
+        self.stats.last_divergence = Some(divergence.clone());
+        if self.auto_repair {
+            *book = reference;
+            self.stats.repaired += 1;
+        }
+        Ok(Some(divergence))
+    }
+
+    /// Checks the books due, returning the divergences found.
+    ///
+    /// Snapshots failing to download are logged and counted in [`BookVerifierStats::failed`], the
+    /// book is checked again on the next call.
+    pub async fn verify_due<'a>(
+        &mut self,
+        kalshi: &Kalshi,
+        books: impl IntoIterator<Item = &'a mut Book>,
+    ) -> Vec<BookDivergence> {
+        let mut divergences = Vec::new();
+        for book in books {
+            if !self.is_due(&book.market_ticker) {
+                continue;
+            }
+            match self.verify(kalshi, book).await {
+                Ok(Some(divergence)) => {
+                    log::warn!(
+                        "Book of {} diverged from the exchange's on {} levels",
+                        divergence.market_ticker,
+                        divergence.mismatched_levels
+                    );
+                    divergences.push(divergence);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!("Could not verify the book of {}: {}", book.market_ticker, e);
+                    self.checked_at.remove(&book.market_ticker);
+                }
+            }
+        }
+        divergences
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        testing::{fixtures, MockHttpServer},
+        Side,
+    };
+
+    #[tokio::test]
+    async fn test_divergent_book_repaired() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let mut verifier = BookVerifier::new(Duration::from_secs(60));
+
+        // The fixture's book with a missed delta and a level applied twice
+        let mut book = Book::new(fixtures::MARKET_TICKER);
+        for (side, price, quantity) in [
+            (Side::Yes, 60, 120),
+            (Side::Yes, 62, 45),
+            (Side::Yes, 64, 10),
+            (Side::No, 32, 80),
+            (Side::No, 34, 25),
+        ] {
+            book.apply_change(side, price, quantity);
+        }
+        let correct = book.clone();
+        book.apply_change(Side::Yes, 64, -10);
+        book.apply_change(Side::No, 34, 25);
+
+        let divergences = verifier.verify_due(&kalshi, [&mut book]).await;
+        assert_eq!(
+            divergences,
+            vec![BookDivergence {
+                market_ticker: fixtures::MARKET_TICKER.to_string(),
+                mismatched_levels: 2,
+                quantity_difference: 35,
+                best_bids_match: false,
+            }]
+        );
+        assert_eq!(book, correct);
+        assert_eq!(verifier.stats().repaired, 1);
+
+        // Not due again within the interval
+        book.apply_change(Side::Yes, 60, 1);
+        assert!(verifier.verify_due(&kalshi, [&mut book]).await.is_empty());
+        assert_eq!(verifier.stats().checks, 1);
+        assert_eq!(
+            verifier
+                .verify(&kalshi, &mut book)
+                .await
+                .unwrap()
+                .map(|d| d.best_bids_match),
+            Some(true)
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::time::Duration;
+
+use crate::{Kalshi, KalshiError, TlsConfig, TradingEnvironment};
+
+/// Which HTTP versions the REST client may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HttpVersion {
+    /// HTTP/1.1, or HTTP/2 when the server offers it.
+    #[default]
+    Auto,
+    /// Only HTTP/1.1.
+    Http1Only,
+    /// HTTP/2 without negotiation, every request is multiplexed over a single connection.
+    Http2PriorKnowledge,
+}
+
+/// Builds a [`Kalshi`] instance with a tuned HTTP client.
+///
+/// Every request made through an instance, and through its clones, shares the client's
+/// connection pool, so paginated and streaming endpoints reuse warm connections instead of
+/// opening new ones. For high-frequency polling the defaults can be tightened: keep more idle
+/// connections around for longer, send TCP keep-alives so idle connections aren't dropped by
+/// middleboxes, and ping HTTP/2 connections while idle.
+///
+/// With the `gzip` or `brotli` features, responses are compressed by the exchange, which makes
+/// large listings such as full market pages much smaller on the wire. Every client, including the
+/// one built by [`Kalshi::new`], then advertises the enabled encodings.
+///
+/// ```
+/// let kalshi = Kalshi::builder(TradingEnvironment::LiveMarketMode)
+///     .api_key(key_id, pem)
+///     .pool_max_idle_per_host(16)
+///     .pool_idle_timeout(Duration::from_secs(300))
+///     .tcp_keepalive(Duration::from_secs(30))
+///     .http_version(HttpVersion::Http2PriorKnowledge)
+///     .http2_keep_alive_interval(Duration::from_secs(20))
+///     .build()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct KalshiBuilder {
+    trading_env: TradingEnvironment,
+    api_key: Option<(String, String)>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    tcp_nodelay: bool,
+    http_version: HttpVersion,
+    http2_keep_alive_interval: Option<Duration>,
+    http2_adaptive_window: bool,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    tls: Option<TlsConfig>,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+    #[cfg(feature = "brotli")]
+    brotli: bool,
+}
+
+impl Kalshi {
+    /// Starts building an instance for `trading_env`, see [`KalshiBuilder`].
+    pub fn builder(trading_env: TradingEnvironment) -> KalshiBuilder {
+        KalshiBuilder::new(trading_env)
+    }
+}
+
+impl KalshiBuilder {
+    /// An instance with the same settings as [`Kalshi::new`], to be refined with the other methods.
+    pub fn new(trading_env: TradingEnvironment) -> Self {
+        KalshiBuilder {
+            trading_env,
+            api_key: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            tcp_keepalive: None,
+            tcp_nodelay: true,
+            http_version: HttpVersion::Auto,
+            http2_keep_alive_interval: None,
+            http2_adaptive_window: false,
+            timeout: None,
+            connect_timeout: None,
+            tls: None,
+            #[cfg(feature = "gzip")]
+            gzip: true,
+            #[cfg(feature = "brotli")]
+            brotli: true,
+        }
+    }
+
+    /// Authenticates with an API key, see [`Kalshi::new_with_api_key`].
+    pub fn api_key(mut self, key_id: String, key: String) -> Self {
+        self.api_key = Some((key_id, key));
+        self
+    }
+
+    /// Idle connections kept open per host, unlimited by default.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// How long an idle connection is kept open, 90 seconds by default.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends TCP keep-alive probes on idle connections at this interval, off by default.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Disables Nagle's algorithm so small requests leave immediately, on by default.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.tcp_nodelay = enabled;
+        self
+    }
+
+    pub fn http_version(mut self, version: HttpVersion) -> Self {
+        self.http_version = version;
+        self
+    }
+
+    /// Pings HTTP/2 connections at this interval, even while idle, to keep them open.
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Sizes HTTP/2 flow control windows from the measured bandwidth-delay product.
+    pub fn http2_adaptive_window(mut self, enabled: bool) -> Self {
+        self.http2_adaptive_window = enabled;
+        self
+    }
+
+    /// Fails requests that haven't completed within `timeout`, none by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fails requests whose connection isn't established within `timeout`, none by default.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// TLS settings of every connection, REST, websocket and FIX alike, see [`TlsConfig`].
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Advertises gzip and decompresses gzip responses, on by default with the `gzip` feature.
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, enabled: bool) -> Self {
+        self.gzip = enabled;
+        self
+    }
+
+    /// Advertises brotli and decompresses brotli responses, on by default with the `brotli` feature.
+    #[cfg(feature = "brotli")]
+    pub fn brotli(mut self, enabled: bool) -> Self {
+        self.brotli = enabled;
+        self
+    }
+
+    fn http_client(&self) -> Result<reqwest::Client, KalshiError> {
+        let mut builder = reqwest::Client::builder()
+            .tcp_nodelay(self.tcp_nodelay)
+            .tcp_keepalive(self.tcp_keepalive)
+            .http2_adaptive_window(self.http2_adaptive_window);
+        #[cfg(feature = "gzip")]
+        {
+            builder = builder.gzip(self.gzip);
+        }
+        #[cfg(feature = "brotli")]
+        {
+            builder = builder.brotli(self.brotli);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = self.http2_keep_alive_interval {
+            builder = builder
+                .http2_keep_alive_interval(interval)
+                .http2_keep_alive_while_idle(true);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(tls) = &self.tls {
+            builder = tls.configure_http(builder)?;
+        }
+        builder = match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1Only => builder.http1_only(),
+            HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        };
+        builder
+            .build()
+            .map_err(|e| KalshiError::UserInputError(format!("Invalid HTTP client options: {}", e)))
+    }
+
+    pub fn build(self) -> Result<Kalshi, KalshiError> {
+        let client = self.http_client()?;
+        let mut kalshi = match self.api_key {
+            Some((key_id, key)) => Kalshi::new_with_api_key(self.trading_env, key_id, key),
+            None => Kalshi::new(self.trading_env),
+        };
+        kalshi.client = client;
+        kalshi.tls = self.tls;
+        Ok(kalshi)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builder_keeps_environment() {
+        let kalshi = Kalshi::builder(TradingEnvironment::DemoMode)
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(Duration::from_secs(30))
+            .http_version(HttpVersion::Http1Only)
+            .build()
+            .unwrap();
+        assert_eq!(
+            kalshi.get_base_url(),
+            Kalshi::new(TradingEnvironment::DemoMode).get_base_url()
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{Event, Market, Series};
+
+/// How long each kind of metadata is cached, `None` disables caching for that kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheTtls {
+    pub market: Option<Duration>,
+    pub event: Option<Duration>,
+    pub series: Option<Duration>,
+}
+
+impl Default for CacheTtls {
+    /// Markets change the most (status, close time), series the least.
+    fn default() -> Self {
+        CacheTtls {
+            market: Some(Duration::from_secs(60)),
+            event: Some(Duration::from_secs(300)),
+            series: Some(Duration::from_secs(3600)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entries<K, V> {
+    entries: HashMap<K, (Instant, V)>,
+}
+
+impl<K: Eq + Hash, V: Clone> Entries<K, V> {
+    fn get(&mut self, key: &K, ttl: Option<Duration>) -> Option<V> {
+        let ttl = ttl?;
+        match self.entries.get(key) {
+            Some((stored, value)) if stored.elapsed() < ttl => Some(value.clone()),
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V, ttl: Option<Duration>) {
+        if ttl.is_some() {
+            self.entries.insert(key, (Instant::now(), value));
+        }
+    }
+}
+
+impl<K, V> Default for Entries<K, V> {
+    fn default() -> Self {
+        Entries {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    markets: Entries<String, Market>,
+    /// Keyed by event ticker and whether markets are nested
+    events: Entries<(String, bool), Event>,
+    series: Entries<String, Series>,
+}
+
+/// Caches the results of `get_single_market`, `get_single_event` and `get_series`.
+///
+/// Attach the cache to a [`Kalshi`](crate::Kalshi) instance with
+/// [`Kalshi::set_metadata_cache`](crate::Kalshi::set_metadata_cache) and those calls return the
+/// cached value until its TTL runs out. Market and event entries can also be invalidated as soon
+/// as the exchange announces a change, by following the websocket `market_lifecycle_v2` channel
+/// with `MetadataCache::follow_lifecycle`.
+///
+/// The cache is a cheap handle, clones share the same entries.
+///
+/// ```
+/// let cache = MetadataCache::new(CacheTtls::default());
+/// kalshi_instance.set_metadata_cache(cache.clone());
+/// ws_client.subscribe(vec![KalshiChannel::MarketLifecycleV2], vec![]).await?;
+/// cache.follow_lifecycle(&ws_client);
+///
+/// // Only the first call hits the exchange
+/// for _ in 0..100 {
+///     let market = kalshi_instance.get_single_market(&ticker).await?;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MetadataCache {
+    ttls: CacheTtls,
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl Default for MetadataCache {
+    fn default() -> Self {
+        Self::new(CacheTtls::default())
+    }
+}
+
+impl MetadataCache {
+    pub fn new(ttls: CacheTtls) -> Self {
+        MetadataCache {
+            ttls,
+            state: Arc::new(Mutex::new(CacheState::default())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    pub fn ttls(&self) -> CacheTtls {
+        self.ttls
+    }
+
+    /// A cached market, `None` if it was never fetched or has expired.
+    pub fn market(&self, ticker: &str) -> Option<Market> {
+        self.lock()
+            .markets
+            .get(&ticker.to_string(), self.ttls.market)
+    }
+
+    pub fn insert_market(&self, market: Market) {
+        self.lock()
+            .markets
+            .insert(market.ticker.clone(), market, self.ttls.market);
+    }
+
+    /// A cached event, `None` if it was never fetched with the same `with_nested_markets` or has expired.
+    pub fn event(&self, event_ticker: &str, with_nested_markets: bool) -> Option<Event> {
+        self.lock().events.get(
+            &(event_ticker.to_string(), with_nested_markets),
+            self.ttls.event,
+        )
+    }
+
+    pub fn insert_event(&self, event: Event, with_nested_markets: bool) {
+        self.lock().events.insert(
+            (event.event_ticker.clone(), with_nested_markets),
+            event,
+            self.ttls.event,
+        );
+    }
+
+    /// A cached series, `None` if it was never fetched or has expired.
+    pub fn series(&self, ticker: &str) -> Option<Series> {
+        self.lock()
+            .series
+            .get(&ticker.to_string(), self.ttls.series)
+    }
+
+    pub fn insert_series(&self, series: Series) {
+        self.lock()
+            .series
+            .insert(series.ticker.clone(), series, self.ttls.series);
+    }
+
+    /// Drops a market, and the event it belongs to since its nested markets are stale too.
+    pub fn invalidate_market(&self, ticker: &str) {
+        let mut state = self.lock();
+        let event_ticker = state
+            .markets
+            .entries
+            .remove(ticker)
+            .map(|(_, market)| market.event_ticker);
+        // Market tickers extend their event ticker with a final `-` suffix
+        let event_ticker = event_ticker.unwrap_or_else(|| {
+            ticker
+                .rsplit_once('-')
+                .map(|(event, _)| event.to_string())
+                .unwrap_or_default()
+        });
+        state
+            .events
+            .entries
+            .retain(|(cached, _), _| cached != &event_ticker);
+    }
+
+    /// Drops an event, with and without nested markets.
+    pub fn invalidate_event(&self, event_ticker: &str) {
+        self.lock()
+            .events
+            .entries
+            .retain(|(cached, _), _| cached != event_ticker);
+    }
+
+    pub fn invalidate_series(&self, ticker: &str) {
+        self.lock().series.entries.remove(ticker);
+    }
+
+    /// Drops every entry.
+    pub fn clear(&self) {
+        *self.lock() = CacheState::default();
+    }
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::{client::KalshiWebsocketClient, responses::KalshiWebsocketResponse};
+    use crate::RestartPolicy;
+    use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
+
+    impl MetadataCache {
+        /// Invalidates the market or event a lifecycle message is about, other messages are ignored.
+        pub fn on_message(&self, msg: &KalshiWebsocketResponse) {
+            match msg {
+                KalshiWebsocketResponse::MarketLifecycleV2 { msg, .. } => {
+                    self.invalidate_market(msg.get_market_ticker())
+                }
+                KalshiWebsocketResponse::EventLifecycle { msg, .. } => {
+                    self.invalidate_event(&msg.event_ticker)
+                }
+                _ => {}
+            }
+        }
+
+        /// Applies every lifecycle message delivered by `ws_client` from now on, until the client shuts down.
+        ///
+        /// The client must be subscribed to the `market_lifecycle_v2` channel. Messages missed
+        /// because the cache fell behind clear the whole cache, since any entry could be stale.
+        pub fn follow_lifecycle(&self, ws_client: &KalshiWebsocketClient) -> JoinHandle<()> {
+            let cache = self.clone();
+            let receiver = Arc::new(tokio::sync::Mutex::new(ws_client.receiver()));
+            let shutdown = ws_client.kalshi().shutdown_handle().clone();
+            shutdown.supervise("lifecycle cache", RestartPolicy::default(), move || {
+                let cache = cache.clone();
+                let receiver = Arc::clone(&receiver);
+                async move {
+                    // Restarts pick up where the panicked run stopped
+                    let mut receiver = receiver.lock_owned().await;
+                    loop {
+                        match receiver.recv().await {
+                            Ok(Ok(msg)) => cache.on_message(&msg),
+                            Ok(Err(_)) => {}
+                            Err(RecvError::Lagged(skipped)) => {
+                                log::warn!("Metadata cache lagged, skipped {} messages", skipped);
+                                cache.clear();
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn market(ticker: &str, event_ticker: &str) -> Market {
+        let mut json = crate::testing::fixtures::market();
+        json["ticker"] = serde_json::json!(ticker);
+        json["event_ticker"] = serde_json::json!(event_ticker);
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_entries_expire_and_invalidate() {
+        let cache = MetadataCache::new(CacheTtls {
+            market: Some(Duration::from_secs(60)),
+            event: Some(Duration::from_secs(60)),
+            series: None,
+        });
+        cache.insert_market(market("KXHIGHNY-25OCT02-B80.5", "KXHIGHNY-25OCT02"));
+        assert!(cache.market("KXHIGHNY-25OCT02-B80.5").is_some());
+
+        cache.invalidate_market("KXHIGHNY-25OCT02-B80.5");
+        assert!(cache.market("KXHIGHNY-25OCT02-B80.5").is_none());
+
+        let expired = MetadataCache::new(CacheTtls {
+            market: Some(Duration::ZERO),
+            ..CacheTtls::default()
+        });
+        expired.insert_market(market("KXHIGHNY-25OCT02-B80.5", "KXHIGHNY-25OCT02"));
+        assert!(expired.market("KXHIGHNY-25OCT02-B80.5").is_none());
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{Candlestick, KalshiError, QuoteOhlc, TradeOhlc};
+
+/// Aggregates candlesticks into longer periods, e.g. minute candlesticks into 5 minute ones.
+///
+/// Periods are aligned to the unix epoch, so hourly candlesticks end on the hour and daily ones
+/// at midnight UTC. Each candlestick goes to the period its `end_period_ts` falls in, so the
+/// input has to be sorted and shorter than `period_minutes`. Periods nothing was fetched for are
+/// skipped, see [`fill_candlestick_gaps`].
+///
+/// # Example
+/// ```
+/// let minutes = kalshi_instance
+///     .get_market_candlesticks(&series_ticker, &ticker, start_ts, end_ts, 1)
+///     .await?;
+/// let hours = resample_candlesticks(&minutes, 60)?;
+/// ```
+pub fn resample_candlesticks(
+    candlesticks: &[Candlestick],
+    period_minutes: i32,
+) -> Result<Vec<Candlestick>, KalshiError> {
+    let period = period_seconds(period_minutes)?;
+    let mut resampled: Vec<Candlestick> = Vec::new();
+    // Traded volume behind each resampled candlestick's mean, to weight the next one merged in
+    let mut mean_volume = 0;
+    for candlestick in candlesticks {
+        let end = (candlestick.end_period_ts + period - 1).div_euclid(period) * period;
+        match resampled.last_mut() {
+            Some(last) if last.end_period_ts == end => {
+                merge(last, candlestick, mean_volume);
+                if candlestick.price.mean.is_some() {
+                    mean_volume += candlestick.volume;
+                }
+            }
+            _ => {
+                resampled.push(Candlestick {
+                    end_period_ts: end,
+                    ..candlestick.clone()
+                });
+                mean_volume = match candlestick.price.mean {
+                    Some(_) => candlestick.volume,
+                    None => 0,
+                };
+            }
+        }
+    }
+    Ok(resampled)
+}
+
+/// Inserts a candlestick for every period missing between the first and last candlestick.
+///
+/// Kalshi leaves out periods nothing happened in. The inserted candlesticks hold the quotes at the
+/// previous close, have no trades and carry the open interest forward.
+pub fn fill_candlestick_gaps(
+    candlesticks: &[Candlestick],
+    period_minutes: i32,
+) -> Result<Vec<Candlestick>, KalshiError> {
+    let period = period_seconds(period_minutes)?;
+    let mut filled: Vec<Candlestick> = Vec::with_capacity(candlesticks.len());
+    for candlestick in candlesticks {
+        if let Some(last) = filled.last() {
+            let mut empty = flat(last);
+            empty.end_period_ts += period;
+            while empty.end_period_ts < candlestick.end_period_ts {
+                filled.push(empty.clone());
+                empty.end_period_ts += period;
+            }
+        }
+        filled.push(candlestick.clone());
+    }
+    Ok(filled)
+}
+
+/// Lines up the candlesticks of several markets by period.
+///
+/// Returns the union of every market's period end timestamps, sorted, along with each market's
+/// candlesticks at those timestamps, `None` where a market has none. Fill the gaps of each market
+/// first for them to only be `None` before a market's first or after its last candlestick.
+///
+/// # Example
+/// ```
+/// let (timestamps, aligned) = align_candlesticks(&candlesticks_by_ticker);
+/// for (i, ts) in timestamps.iter().enumerate() {
+///     let bids: Vec<_> = aligned.values().map(|c| c[i].as_ref().map(|c| c.yes_bid.close)).collect();
+///     println!("{}: {:?}", ts, bids);
+/// }
+/// ```
+pub fn align_candlesticks(
+    candlesticks: &HashMap<String, Vec<Candlestick>>,
+) -> (Vec<i64>, BTreeMap<String, Vec<Option<Candlestick>>>) {
+    let timestamps: Vec<i64> = candlesticks
+        .values()
+        .flatten()
+        .map(|candlestick| candlestick.end_period_ts)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let aligned = candlesticks
+        .iter()
+        .map(|(ticker, candlesticks)| {
+            let by_ts: HashMap<i64, &Candlestick> = candlesticks
+                .iter()
+                .map(|candlestick| (candlestick.end_period_ts, candlestick))
+                .collect();
+            let row = timestamps
+                .iter()
+                .map(|ts| by_ts.get(ts).map(|candlestick| (*candlestick).clone()))
+                .collect();
+            (ticker.clone(), row)
+        })
+        .collect();
+    (timestamps, aligned)
+}
+
+fn period_seconds(period_minutes: i32) -> Result<i64, KalshiError> {
+    if period_minutes <= 0 {
+        return Err(KalshiError::UserInputError(format!(
+            "Candlestick period must be positive, got {} minutes",
+            period_minutes
+        )));
+    }
+    Ok(period_minutes as i64 * 60)
+}
+
+/// Merges the next candlestick of a period into the candlestick aggregating it.
+fn merge(into: &mut Candlestick, next: &Candlestick, mean_volume: i64) {
+    merge_quotes(&mut into.yes_bid, &next.yes_bid);
+    merge_quotes(&mut into.yes_ask, &next.yes_ask);
+
+    let (price, traded) = (&mut into.price, &next.price);
+    price.open = price.open.or(traded.open);
+    price.high = price.high.max(traded.high);
+    price.low = match (price.low, traded.low) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    price.close = traded.close.or(price.close);
+    price.mean = match (price.mean, traded.mean) {
+        (Some(a), Some(b)) if mean_volume + next.volume > 0 => {
+            Some((a * mean_volume + b * next.volume) / (mean_volume + next.volume))
+        }
+        (a, b) => a.or(b),
+    };
+
+    into.volume += next.volume;
+    into.open_interest = next.open_interest;
+}
+
+fn merge_quotes(into: &mut QuoteOhlc, next: &QuoteOhlc) {
+    into.high = into.high.max(next.high);
+    into.low = into.low.min(next.low);
+    into.close = next.close;
+}
+
+/// A candlestick for a period nothing happened in after `last`.
+fn flat(last: &Candlestick) -> Candlestick {
+    let at = |close: i64| QuoteOhlc {
+        open: close,
+        high: close,
+        low: close,
+        close,
+    };
+    Candlestick {
+        end_period_ts: last.end_period_ts,
+        yes_bid: at(last.yes_bid.close),
+        yes_ask: at(last.yes_ask.close),
+        price: TradeOhlc {
+            previous: last.price.close.or(last.price.previous),
+            ..TradeOhlc::default()
+        },
+        volume: 0,
+        open_interest: last.open_interest,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn candlestick(end_period_ts: i64, bid: i64, trade: Option<i64>, volume: i64) -> Candlestick {
+        Candlestick {
+            end_period_ts,
+            yes_bid: QuoteOhlc {
+                open: bid,
+                high: bid + 1,
+                low: bid - 1,
+                close: bid,
+            },
+            yes_ask: QuoteOhlc {
+                open: bid + 2,
+                high: bid + 2,
+                low: bid + 2,
+                close: bid + 2,
+            },
+            price: TradeOhlc {
+                open: trade,
+                high: trade,
+                low: trade,
+                close: trade,
+                mean: trade,
+                previous: None,
+            },
+            volume,
+            open_interest: 100 + volume,
+        }
+    }
+
+    #[test]
+    fn test_resample_minutes_to_five_minutes() {
+        let minutes = vec![
+            candlestick(60, 40, Some(41), 10),
+            candlestick(120, 42, None, 0),
+            candlestick(300, 45, Some(46), 30),
+            candlestick(360, 50, Some(50), 5),
+        ];
+        let resampled = resample_candlesticks(&minutes, 5).unwrap();
+
+        assert_eq!(resampled.len(), 2);
+        let first = &resampled[0];
+        assert_eq!(first.end_period_ts, 300);
+        assert_eq!(
+            first.yes_bid,
+            QuoteOhlc {
+                open: 40,
+                high: 46,
+                low: 39,
+                close: 45
+            }
+        );
+        assert_eq!(first.price.open, Some(41));
+        assert_eq!(first.price.high, Some(46));
+        assert_eq!(first.price.low, Some(41));
+        assert_eq!(first.price.close, Some(46));
+        // (41 * 10 + 46 * 30) / 40
+        assert_eq!(first.price.mean, Some(44));
+        assert_eq!(first.volume, 40);
+        assert_eq!(first.open_interest, 130);
+        assert_eq!(resampled[1].end_period_ts, 600);
+
+        assert!(resample_candlesticks(&minutes, 0).is_err());
+    }
+
+    #[test]
+    fn test_fill_gaps_and_align() {
+        let minutes = vec![
+            candlestick(60, 40, Some(41), 10),
+            candlestick(240, 45, None, 0),
+        ];
+        let filled = fill_candlestick_gaps(&minutes, 1).unwrap();
+
+        let timestamps: Vec<i64> = filled.iter().map(|c| c.end_period_ts).collect();
+        assert_eq!(timestamps, vec![60, 120, 180, 240]);
+        assert_eq!(
+            filled[1].yes_bid,
+            QuoteOhlc {
+                open: 40,
+                high: 40,
+                low: 40,
+                close: 40
+            }
+        );
+        assert_eq!(filled[1].price.close, None);
+        assert_eq!(filled[1].price.previous, Some(41));
+        assert_eq!(filled[2].open_interest, 110);
+
+        let markets = HashMap::from([
+            ("A".to_string(), filled),
+            (
+                "B".to_string(),
+                vec![candlestick(180, 60, None, 0), candlestick(300, 61, None, 0)],
+            ),
+        ]);
+        let (timestamps, aligned) = align_candlesticks(&markets);
+        assert_eq!(timestamps, vec![60, 120, 180, 240, 300]);
+        assert!(aligned["A"][4].is_none());
+        assert!(aligned["B"][1].is_none());
+        assert_eq!(aligned["B"][2].as_ref().unwrap().yes_bid.close, 60);
+    }
+}
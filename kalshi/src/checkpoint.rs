@@ -0,0 +1,392 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use futures::Stream;
+
+use crate::{store, KalshiError};
+
+/// Where a paginated collector stopped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The cursor of the next page to fetch, `None` once every page was fetched.
+    pub cursor: Option<String>,
+    /// A timestamp in seconds chosen by the collector, typically the `min_ts` of its next run.
+    pub ts: Option<i64>,
+}
+
+/// Persists the checkpoints of paginated collectors by name, so they restart where they stopped
+/// after a crash.
+///
+/// [`FileCheckpointStore`] keeps them in a text file, `SqliteCheckpointStore` in a SQLite database
+/// with the `sqlite` feature. [`checkpointed_pages`] saves the cursor of any `*_page` method as the
+/// pages are consumed.
+pub trait CheckpointStore: Send + Sync {
+    /// The checkpoint saved under `key`, `None` if there is none.
+    fn load(&self, key: &str) -> Result<Option<Checkpoint>, KalshiError>;
+
+    /// Saves `checkpoint` under `key`, replacing the previous one.
+    fn save(&self, key: &str, checkpoint: &Checkpoint) -> Result<(), KalshiError>;
+
+    /// Removes the checkpoint saved under `key`, so the collector starts over.
+    fn clear(&self, key: &str) -> Result<(), KalshiError>;
+}
+
+/// Keeps checkpoints in a text file, one per line, rewritten through a temporary file on every
+/// save so a crash never leaves it half written.
+///
+/// ```
+/// let store = FileCheckpointStore::open("checkpoints.txt")?;
+/// store.save("fills", &Checkpoint { cursor: None, ts: Some(1758132400) })?;
+/// ```
+#[derive(Debug)]
+pub struct FileCheckpointStore {
+    path: PathBuf,
+    checkpoints: Mutex<BTreeMap<String, Checkpoint>>,
+}
+
+impl FileCheckpointStore {
+    /// Opens the store at `path`, loading the checkpoints already saved there.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, KalshiError> {
+        let path = path.as_ref().to_path_buf();
+        let text = store::read(&path, "checkpoints")?.unwrap_or_default();
+        let text = String::from_utf8(text).map_err(|e| {
+            KalshiError::InternalError(format!("Invalid checkpoints {}: {}", path.display(), e))
+        })?;
+        let checkpoints = text
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| parse_line(line).ok_or(line))
+            .collect::<Result<_, _>>()
+            .map_err(|line| {
+                KalshiError::InternalError(format!(
+                    "Invalid checkpoint in {}: {}",
+                    path.display(),
+                    line
+                ))
+            })?;
+        Ok(FileCheckpointStore {
+            path,
+            checkpoints: Mutex::new(checkpoints),
+        })
+    }
+
+    fn write(&self, checkpoints: &BTreeMap<String, Checkpoint>) -> Result<(), KalshiError> {
+        let text: String = checkpoints
+            .iter()
+            .map(|(key, checkpoint)| {
+                format!(
+                    "{}\t{}\t{}\n",
+                    key,
+                    checkpoint.ts.map(|ts| ts.to_string()).unwrap_or_default(),
+                    checkpoint.cursor.as_deref().unwrap_or_default()
+                )
+            })
+            .collect();
+        store::write_atomic(&self.path, "checkpoints", text.as_bytes())
+    }
+}
+
+/// Parses a `key<TAB>ts<TAB>cursor` line, empty fields being `None`.
+fn parse_line(line: &str) -> Option<(String, Checkpoint)> {
+    let mut fields = line.splitn(3, '\t');
+    let key = fields.next()?.to_string();
+    let ts = match fields.next()? {
+        "" => None,
+        ts => Some(ts.parse().ok()?),
+    };
+    let cursor = Some(fields.next()?.to_string()).filter(|cursor| !cursor.is_empty());
+    Some((key, Checkpoint { cursor, ts }))
+}
+
+/// Checks that a key or cursor fits on its line of the file.
+fn check_field(name: &str, value: &str) -> Result<(), KalshiError> {
+    if value.contains(['\t', '\n', '\r']) {
+        return Err(KalshiError::UserInputError(format!(
+            "Can't save a checkpoint {} containing tabs or newlines: {:?}",
+            name, value
+        )));
+    }
+    Ok(())
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn load(&self, key: &str) -> Result<Option<Checkpoint>, KalshiError> {
+        Ok(self
+            .checkpoints
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(key)
+            .cloned())
+    }
+
+    fn save(&self, key: &str, checkpoint: &Checkpoint) -> Result<(), KalshiError> {
+        check_field("key", key)?;
+        check_field("cursor", checkpoint.cursor.as_deref().unwrap_or_default())?;
+        let mut checkpoints = self.checkpoints.lock().unwrap_or_else(|p| p.into_inner());
+        checkpoints.insert(key.to_string(), checkpoint.clone());
+        self.write(&checkpoints)
+    }
+
+    fn clear(&self, key: &str) -> Result<(), KalshiError> {
+        let mut checkpoints = self.checkpoints.lock().unwrap_or_else(|p| p.into_inner());
+        if checkpoints.remove(key).is_some() {
+            self.write(&checkpoints)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::*;
+    use rusqlite::{params, Connection, OptionalExtension};
+
+    fn sqlite_error(e: rusqlite::Error) -> KalshiError {
+        KalshiError::InternalError(format!("Checkpoint database error: {}", e))
+    }
+
+    /// Keeps checkpoints in a `checkpoints` table of a SQLite database, which can be shared with
+    /// the data the collector stores.
+    ///
+    /// ```
+    /// let store = SqliteCheckpointStore::open("collector.db")?;
+    /// ```
+    #[derive(Debug)]
+    pub struct SqliteCheckpointStore {
+        connection: Mutex<Connection>,
+    }
+
+    impl SqliteCheckpointStore {
+        /// Opens the database at `path`, creating it and the `checkpoints` table when missing.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, KalshiError> {
+            let connection = Connection::open(path).map_err(sqlite_error)?;
+            Self::from_connection(connection)
+        }
+
+        /// Uses an already open database, creating the `checkpoints` table when missing.
+        pub fn from_connection(connection: Connection) -> Result<Self, KalshiError> {
+            connection
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS checkpoints (
+                        key TEXT PRIMARY KEY,
+                        cursor TEXT,
+                        ts INTEGER
+                    )",
+                    [],
+                )
+                .map_err(sqlite_error)?;
+            Ok(SqliteCheckpointStore {
+                connection: Mutex::new(connection),
+            })
+        }
+
+        fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+            self.connection.lock().unwrap_or_else(|p| p.into_inner())
+        }
+    }
+
+    impl CheckpointStore for SqliteCheckpointStore {
+        fn load(&self, key: &str) -> Result<Option<Checkpoint>, KalshiError> {
+            self.connection()
+                .query_row(
+                    "SELECT cursor, ts FROM checkpoints WHERE key = ?1",
+                    params![key],
+                    |row| {
+                        Ok(Checkpoint {
+                            cursor: row.get(0)?,
+                            ts: row.get(1)?,
+                        })
+                    },
+                )
+                .optional()
+                .map_err(sqlite_error)
+        }
+
+        fn save(&self, key: &str, checkpoint: &Checkpoint) -> Result<(), KalshiError> {
+            self.connection()
+                .execute(
+                    "INSERT INTO checkpoints (key, cursor, ts) VALUES (?1, ?2, ?3)
+                     ON CONFLICT(key) DO UPDATE SET cursor = excluded.cursor, ts = excluded.ts",
+                    params![key, checkpoint.cursor, checkpoint.ts],
+                )
+                .map(|_| ())
+                .map_err(sqlite_error)
+        }
+
+        fn clear(&self, key: &str) -> Result<(), KalshiError> {
+            self.connection()
+                .execute("DELETE FROM checkpoints WHERE key = ?1", params![key])
+                .map(|_| ())
+                .map_err(sqlite_error)
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteCheckpointStore;
+
+/// Streams the pages of a paginated endpoint, saving the cursor under `key` as pages are
+/// consumed, and resuming from the saved cursor.
+///
+/// `fetch` gets the cursor of the page to fetch, `None` for the first one, and returns the page
+/// and the cursor of the next one, like the `*_page` methods. The cursor of the next page is only
+/// saved once the consumer asks for it, so a page being processed when the collector crashes is
+/// fetched again on restart. Once every page is fetched the checkpoint's cursor is cleared, its
+/// timestamp is kept. Errors are yielded and end the stream, leaving the checkpoint where it was.
+///
+/// # Example
+/// ```
+/// let store = FileCheckpointStore::open("checkpoints.txt")?;
+/// let pages = checkpointed_pages(&store, "settlements", |cursor| {
+///     kalshi_instance.get_portfolio_settlements(Some(100), cursor, None, None, None, None)
+///         .map_ok(|(cursor, settlements)| (settlements, cursor))
+/// });
+/// pin_mut!(pages);
+/// while let Some(settlements) = pages.next().await {
+///     save_to_db(settlements?);
+/// }
+/// ```
+pub fn checkpointed_pages<'a, T, F, Fut>(
+    store: &'a dyn CheckpointStore,
+    key: &'a str,
+    mut fetch: F,
+) -> impl Stream<Item = Result<Vec<T>, KalshiError>> + 'a
+where
+    T: 'a,
+    F: FnMut(Option<String>) -> Fut + 'a,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>), KalshiError>> + 'a,
+{
+    async_stream::stream! {
+        let mut checkpoint = match store.load(key) {
+            Ok(checkpoint) => checkpoint.unwrap_or_default(),
+            Err(e) => {
+                yield Err(e);
+                return;
+            }
+        };
+        loop {
+            let (page, next) = match fetch(checkpoint.cursor.clone()).await {
+                Ok(page) => page,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+            let next = next.filter(|next| !next.is_empty());
+            let done = next.is_none() || page.is_empty();
+            yield Ok(page);
+
+            checkpoint.cursor = if done { None } else { next };
+            if let Err(e) = store.save(key, &checkpoint) {
+                yield Err(e);
+                return;
+            }
+            if done {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::StreamExt;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("kalshi-{}-{}.txt", name, uuid::Uuid::new_v4()))
+    }
+
+    /// Serves pages of two numbers out of 0..6, the cursor being the first number of the page.
+    async fn page(cursor: Option<String>) -> Result<(Vec<i32>, Option<String>), KalshiError> {
+        let start: i32 = cursor.map_or(0, |cursor| cursor.parse().unwrap());
+        let next = Some(start + 2).filter(|next| *next < 6);
+        Ok((vec![start, start + 1], next.map(|next| next.to_string())))
+    }
+
+    #[test]
+    fn test_file_store_round_trips() {
+        let path = temp_path("checkpoints");
+        let store = FileCheckpointStore::open(&path).unwrap();
+        assert_eq!(store.load("fills").unwrap(), None);
+
+        let fills = Checkpoint {
+            cursor: Some("abc==".to_string()),
+            ts: Some(1758132400),
+        };
+        store.save("fills", &fills).unwrap();
+        store.save("markets", &Checkpoint::default()).unwrap();
+        assert!(store
+            .save(
+                "bad",
+                &Checkpoint {
+                    cursor: Some("a\tb".to_string()),
+                    ts: None
+                }
+            )
+            .is_err());
+
+        let reopened = FileCheckpointStore::open(&path).unwrap();
+        assert_eq!(reopened.load("fills").unwrap(), Some(fills));
+        assert_eq!(
+            reopened.load("markets").unwrap(),
+            Some(Checkpoint::default())
+        );
+        reopened.clear("fills").unwrap();
+        assert_eq!(
+            FileCheckpointStore::open(&path)
+                .unwrap()
+                .load("fills")
+                .unwrap(),
+            None
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pages_resume_from_checkpoint() {
+        let path = temp_path("pages");
+        let store = FileCheckpointStore::open(&path).unwrap();
+
+        // The collector crashes while processing the second page
+        {
+            let pages = checkpointed_pages(&store, "numbers", page);
+            futures::pin_mut!(pages);
+            assert_eq!(pages.next().await.unwrap().unwrap(), vec![0, 1]);
+            assert_eq!(pages.next().await.unwrap().unwrap(), vec![2, 3]);
+        }
+        assert_eq!(
+            store.load("numbers").unwrap().unwrap().cursor.as_deref(),
+            Some("2")
+        );
+
+        let rest: Vec<_> = checkpointed_pages(&store, "numbers", page)
+            .map(|page| page.unwrap())
+            .collect()
+            .await;
+        assert_eq!(rest, vec![vec![2, 3], vec![4, 5]]);
+        assert_eq!(store.load("numbers").unwrap(), Some(Checkpoint::default()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_store_round_trips() {
+        let store =
+            SqliteCheckpointStore::from_connection(rusqlite::Connection::open_in_memory().unwrap())
+                .unwrap();
+        let checkpoint = Checkpoint {
+            cursor: Some("abc".to_string()),
+            ts: None,
+        };
+        store.save("fills", &checkpoint).unwrap();
+        store.save("fills", &checkpoint).unwrap();
+        assert_eq!(store.load("fills").unwrap(), Some(checkpoint));
+        store.clear("fills").unwrap();
+        assert_eq!(store.load("fills").unwrap(), None);
+    }
+}
@@ -0,0 +1,602 @@
+use std::time::Duration;
+
+use openssl::pkey::PKey;
+
+#[cfg(feature = "websockets")]
+use crate::websockets::client::KalshiWebsocketConfig;
+use crate::{utils, Kalshi, KalshiBuilder, KalshiError, TradingEnvironment};
+
+/// An API key and the environment it was created in.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKeyConfig {
+    /// UUID of the key, from the profile page.
+    pub key_id: String,
+    /// PEM formatted RSA private key.
+    pub private_key_pem: String,
+    /// The environment the key was created in, keys only authenticate against their own. `None`
+    /// skips the check.
+    pub environment: Option<TradingEnvironment>,
+}
+
+impl std::fmt::Debug for ApiKeyConfig {
+    /// Leaves the private key out.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKeyConfig")
+            .field("key_id", &self.key_id)
+            .field("environment", &self.environment)
+            .finish()
+    }
+}
+
+/// Everything needed to build a [`Kalshi`] instance, checked as a whole before it's built.
+///
+/// Mistakes that would otherwise surface as authentication failures or panics on the first
+/// request are reported by [`KalshiConfig::validate`]: a key created in the demo environment
+/// used against the live one, a url override pointing at the other environment, a malformed key,
+/// or contradictory timeouts.
+///
+/// ```
+/// let kalshi = Kalshi::from_config(KalshiConfig {
+///     api_key: Some(ApiKeyConfig {
+///         key_id,
+///         private_key_pem: std::fs::read_to_string("kalshi.pem")?,
+///         environment: Some(TradingEnvironment::DemoMode),
+///     }),
+///     request_timeout: Some(Duration::from_secs(10)),
+///     dry_run: true,
+///     ..KalshiConfig::new(TradingEnvironment::DemoMode)
+/// })?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KalshiConfig {
+    pub environment: TradingEnvironment,
+    pub api_key: Option<ApiKeyConfig>,
+    /// Overrides the REST base url picked from the environment.
+    pub base_url: Option<String>,
+    /// Overrides the websocket url picked from the environment.
+    #[cfg(feature = "websockets")]
+    pub ws_url: Option<String>,
+    /// See [`KalshiBuilder::timeout`].
+    pub request_timeout: Option<Duration>,
+    /// See [`KalshiBuilder::connect_timeout`].
+    pub connect_timeout: Option<Duration>,
+    /// Command timeout, command rate limit and reconnect policy of the websocket clients.
+    #[cfg(feature = "websockets")]
+    pub websocket: KalshiWebsocketConfig,
+    /// See [`Kalshi::set_dry_run`].
+    pub dry_run: bool,
+    /// See [`Kalshi::set_validate_orders`].
+    pub validate_orders: bool,
+}
+
+impl KalshiConfig {
+    /// The same settings as [`Kalshi::new`].
+    pub fn new(environment: TradingEnvironment) -> Self {
+        KalshiConfig {
+            environment,
+            api_key: None,
+            base_url: None,
+            #[cfg(feature = "websockets")]
+            ws_url: None,
+            request_timeout: None,
+            connect_timeout: None,
+            #[cfg(feature = "websockets")]
+            websocket: KalshiWebsocketConfig::default(),
+            dry_run: false,
+            validate_orders: false,
+        }
+    }
+
+    /// Checks the settings against each other, returning the first problem found as a
+    /// [`KalshiError::UserInputError`].
+    pub fn validate(&self) -> Result<(), KalshiError> {
+        if let Some(api_key) = &self.api_key {
+            if let Some(key_env) = api_key.environment {
+                if is_demo(key_env) != is_demo(self.environment) {
+                    return invalid(format!(
+                        "The API key was created in {:?} and can't authenticate in {:?}",
+                        key_env, self.environment
+                    ));
+                }
+            }
+            if uuid::Uuid::parse_str(&api_key.key_id).is_err() {
+                return invalid(format!("API key id {:?} isn't a UUID", api_key.key_id));
+            }
+            if let Err(e) = PKey::private_key_from_pem(api_key.private_key_pem.as_bytes()) {
+                return invalid(format!("The API private key isn't a valid PEM key: {}", e));
+            }
+        }
+
+        check_url_override(
+            self.environment,
+            "base url",
+            &self.base_url,
+            utils::build_base_url,
+        )?;
+        #[cfg(feature = "websockets")]
+        check_url_override(
+            self.environment,
+            "websocket url",
+            &self.ws_url,
+            utils::build_ws_url,
+        )?;
+
+        for (name, timeout) in [
+            ("request timeout", self.request_timeout),
+            ("connect timeout", self.connect_timeout),
+        ] {
+            if timeout == Some(Duration::ZERO) {
+                return invalid(format!("The {} must be longer than zero", name));
+            }
+        }
+        if let (Some(request), Some(connect)) = (self.request_timeout, self.connect_timeout) {
+            if connect > request {
+                return invalid(format!(
+                    "The connect timeout ({:?}) is longer than the request timeout ({:?}) including it",
+                    connect, request
+                ));
+            }
+        }
+
+        #[cfg(feature = "websockets")]
+        {
+            let ws = &self.websocket;
+            if ws.command_timeout == Some(Duration::ZERO) {
+                return invalid(
+                    "The websocket command timeout must be longer than zero".to_string(),
+                );
+            }
+            if let Some(limit) = ws.command_rate_limit {
+                if !(limit.per_second.is_finite() && limit.per_second > 0.0) || limit.burst == 0 {
+                    return invalid(format!(
+                        "The websocket command rate limit {:?} never lets a command through",
+                        limit
+                    ));
+                }
+            }
+            if let Some(reconnect) = &ws.reconnect {
+                if reconnect.initial_backoff.is_zero()
+                    || reconnect.initial_backoff > reconnect.max_backoff
+                {
+                    return invalid(format!(
+                        "The reconnect backoff must start above zero and at most at its maximum, got {:?} to {:?}",
+                        reconnect.initial_backoff, reconnect.max_backoff
+                    ));
+                }
+                if reconnect.max_attempts == Some(0) {
+                    return invalid(
+                        "The reconnect policy must allow at least one attempt".to_string(),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Kalshi {
+    /// Validates `config` and builds an instance from it, see [`KalshiConfig`].
+    pub fn from_config(config: KalshiConfig) -> Result<Kalshi, KalshiError> {
+        config.validate()?;
+        let mut builder = KalshiBuilder::new(config.environment);
+        if let Some(api_key) = config.api_key {
+            builder = builder.api_key(api_key.key_id, api_key.private_key_pem);
+        }
+        if let Some(timeout) = config.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        let mut kalshi = builder.build()?;
+        if let Some(base_url) = &config.base_url {
+            kalshi.set_base_url(base_url);
+        }
+        #[cfg(feature = "websockets")]
+        {
+            if let Some(ws_url) = &config.ws_url {
+                kalshi.set_ws_url(ws_url);
+            }
+            kalshi.set_ws_config(config.websocket);
+        }
+        kalshi.set_dry_run(config.dry_run);
+        kalshi.set_validate_orders(config.validate_orders);
+        Ok(kalshi)
+    }
+}
+
+/// Refuses urls pointing at the servers of another environment, proxies and mock servers are fine.
+fn check_url_override(
+    environment: TradingEnvironment,
+    name: &str,
+    url: &Option<String>,
+    build_url: fn(TradingEnvironment) -> &'static str,
+) -> Result<(), KalshiError> {
+    let Some(url) = url else {
+        return Ok(());
+    };
+    let Ok(parsed) = url::Url::parse(url) else {
+        return invalid(format!("The {} {:?} isn't a valid url", name, url));
+    };
+    let other_envs = [
+        TradingEnvironment::DemoMode,
+        TradingEnvironment::LiveMarketMode,
+        TradingEnvironment::LegacyLiveMarketMode,
+    ]
+    .into_iter()
+    .filter(|other| is_demo(*other) != is_demo(environment));
+    for other in other_envs {
+        let other_host = url::Url::parse(build_url(other))
+            .ok()
+            .and_then(|other| other.host_str().map(str::to_string));
+        if parsed.host_str().is_some() && parsed.host_str() == other_host.as_deref() {
+            return invalid(format!(
+                "The {} {:?} points to {:?} but the config targets {:?}",
+                name, url, other, environment
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether the environment is the demo one, the live environments share their keys.
+fn is_demo(environment: TradingEnvironment) -> bool {
+    matches!(environment, TradingEnvironment::DemoMode)
+}
+
+fn invalid(message: String) -> Result<(), KalshiError> {
+    Err(KalshiError::UserInputError(format!(
+        "Invalid Kalshi config: {}",
+        message
+    )))
+}
+
+#[cfg(feature = "toml")]
+mod file {
+    use std::{
+        io::Read,
+        path::{Path, PathBuf},
+        time::Duration,
+    };
+
+    use serde::Deserialize;
+
+    use super::{ApiKeyConfig, KalshiConfig};
+    use crate::{KalshiError, TradingEnvironment};
+
+    #[derive(Debug, Clone, Copy, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum FileEnvironment {
+        Demo,
+        Live,
+        LegacyLive,
+    }
+
+    impl From<FileEnvironment> for TradingEnvironment {
+        fn from(environment: FileEnvironment) -> Self {
+            match environment {
+                FileEnvironment::Demo => TradingEnvironment::DemoMode,
+                FileEnvironment::Live => TradingEnvironment::LiveMarketMode,
+                FileEnvironment::LegacyLive => TradingEnvironment::LegacyLiveMarketMode,
+            }
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct ConfigFile {
+        environment: FileEnvironment,
+        api_key: Option<ApiKeyFile>,
+        base_url: Option<String>,
+        #[cfg(feature = "websockets")]
+        ws_url: Option<String>,
+        request_timeout_secs: Option<f64>,
+        connect_timeout_secs: Option<f64>,
+        #[cfg(feature = "websockets")]
+        websocket: Option<WebsocketFile>,
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        validate_orders: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct ApiKeyFile {
+        key_id: String,
+        /// Path of the PEM key, relative to the config file
+        private_key_file: Option<PathBuf>,
+        /// The PEM key itself
+        private_key: Option<String>,
+        environment: Option<FileEnvironment>,
+    }
+
+    #[cfg(feature = "websockets")]
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct WebsocketFile {
+        command_timeout_secs: Option<f64>,
+        command_rate_limit: Option<RateLimitFile>,
+        reconnect: Option<ReconnectFile>,
+    }
+
+    #[cfg(feature = "websockets")]
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct RateLimitFile {
+        per_second: f64,
+        burst: u32,
+    }
+
+    #[cfg(feature = "websockets")]
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct ReconnectFile {
+        initial_backoff_secs: Option<f64>,
+        max_backoff_secs: Option<f64>,
+        max_attempts: Option<u32>,
+    }
+
+    fn seconds(secs: Option<f64>, name: &str) -> Result<Option<Duration>, KalshiError> {
+        secs.map(|secs| {
+            Duration::try_from_secs_f64(secs).map_err(|_| {
+                KalshiError::UserInputError(format!(
+                    "Invalid Kalshi config file: {} must be a positive number of seconds, got {}",
+                    name, secs
+                ))
+            })
+        })
+        .transpose()
+    }
+
+    impl KalshiConfig {
+        /// Loads a config from a TOML file, see [`KalshiConfig::from_reader`] for its format.
+        ///
+        /// A relative `private_key_file` is resolved against the directory of the config file.
+        pub fn from_toml(path: impl AsRef<Path>) -> Result<KalshiConfig, KalshiError> {
+            let path = path.as_ref();
+            let text = std::fs::read_to_string(path).map_err(|e| {
+                KalshiError::UserInputError(format!("Could not read {}: {}", path.display(), e))
+            })?;
+            parse(&text, path.parent().unwrap_or(Path::new("")))
+        }
+
+        /// Loads a config from TOML, with a relative `private_key_file` resolved against the
+        /// working directory.
+        ///
+        /// Durations are given in seconds and environments as `demo`, `live` or `legacy_live`.
+        /// Only `environment` is required, anything left out keeps the defaults of
+        /// [`KalshiConfig::new`]. The private key is either read from `private_key_file` or given
+        /// inline as `private_key`. A `reconnect` table, even an empty one, turns reconnecting
+        /// websockets on. The config isn't validated, [`Kalshi::from_config`](crate::Kalshi::from_config)
+        /// does it when building the client.
+        ///
+        /// ```toml
+        /// environment = "demo"
+        /// request_timeout_secs = 10
+        /// dry_run = true
+        ///
+        /// [api_key]
+        /// key_id = "a952bcbe-ec3b-4b5b-b8f9-11dae589608c"
+        /// private_key_file = "keys/kalshi-demo.pem"
+        /// environment = "demo"
+        ///
+        /// [websocket]
+        /// command_timeout_secs = 5
+        /// command_rate_limit = { per_second = 10, burst = 20 }
+        /// reconnect = { initial_backoff_secs = 0.5, max_backoff_secs = 60, max_attempts = 20 }
+        /// ```
+        pub fn from_reader(mut reader: impl Read) -> Result<KalshiConfig, KalshiError> {
+            let mut text = String::new();
+            reader.read_to_string(&mut text).map_err(|e| {
+                KalshiError::UserInputError(format!("Could not read the Kalshi config: {}", e))
+            })?;
+            parse(&text, Path::new(""))
+        }
+    }
+
+    fn parse(text: &str, base_dir: &Path) -> Result<KalshiConfig, KalshiError> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| {
+            KalshiError::UserInputError(format!("Invalid Kalshi config file: {}", e))
+        })?;
+        let environment = file.environment.into();
+        let api_key = match file.api_key {
+            Some(api_key) => {
+                let private_key_pem = match (api_key.private_key_file, api_key.private_key) {
+                    (Some(key_file), None) => {
+                        let key_file = base_dir.join(key_file);
+                        std::fs::read_to_string(&key_file).map_err(|e| {
+                            KalshiError::UserInputError(format!(
+                                "Could not read the private key {}: {}",
+                                key_file.display(),
+                                e
+                            ))
+                        })?
+                    }
+                    (None, Some(private_key)) => private_key,
+                    _ => {
+                        return Err(KalshiError::UserInputError(
+                            "Invalid Kalshi config file: the api key needs exactly one of private_key_file and private_key"
+                                .to_string(),
+                        ))
+                    }
+                };
+                Some(ApiKeyConfig {
+                    key_id: api_key.key_id,
+                    private_key_pem,
+                    environment: api_key.environment.map(Into::into),
+                })
+            }
+            None => None,
+        };
+
+        #[cfg_attr(not(feature = "websockets"), allow(unused_mut))]
+        let mut config = KalshiConfig {
+            api_key,
+            base_url: file.base_url,
+            request_timeout: seconds(file.request_timeout_secs, "request_timeout_secs")?,
+            connect_timeout: seconds(file.connect_timeout_secs, "connect_timeout_secs")?,
+            dry_run: file.dry_run,
+            validate_orders: file.validate_orders,
+            ..KalshiConfig::new(environment)
+        };
+        #[cfg(feature = "websockets")]
+        {
+            use crate::websockets::rate_limit::KalshiCommandRateLimit;
+
+            config.ws_url = file.ws_url;
+            if let Some(websocket) = file.websocket {
+                let ws = &mut config.websocket;
+                if let Some(timeout) =
+                    seconds(websocket.command_timeout_secs, "command_timeout_secs")?
+                {
+                    ws.command_timeout = Some(timeout);
+                }
+                ws.command_rate_limit = websocket
+                    .command_rate_limit
+                    .map(|limit| KalshiCommandRateLimit::new(limit.per_second, limit.burst));
+                if let Some(reconnect) = websocket.reconnect {
+                    let policy = ws.reconnect.get_or_insert_with(Default::default);
+                    if let Some(backoff) =
+                        seconds(reconnect.initial_backoff_secs, "initial_backoff_secs")?
+                    {
+                        policy.initial_backoff = backoff;
+                    }
+                    if let Some(backoff) = seconds(reconnect.max_backoff_secs, "max_backoff_secs")?
+                    {
+                        policy.max_backoff = backoff;
+                    }
+                    policy.max_attempts = reconnect.max_attempts;
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::{testing::throwaway_private_key, Kalshi};
+
+        #[test]
+        fn test_config_loaded_from_toml() {
+            let dir = std::env::temp_dir().join(format!("kalshi-config-{}", std::process::id()));
+            std::fs::create_dir_all(dir.join("keys")).unwrap();
+            let key = throwaway_private_key();
+            std::fs::write(dir.join("keys/demo.pem"), &key).unwrap();
+            let path = dir.join("kalshi.toml");
+            std::fs::write(
+                &path,
+                r#"
+                environment = "demo"
+                request_timeout_secs = 10
+                connect_timeout_secs = 2.5
+                dry_run = true
+
+                [api_key]
+                key_id = "a952bcbe-ec3b-4b5b-b8f9-11dae589608c"
+                private_key_file = "keys/demo.pem"
+                environment = "demo"
+
+                [websocket]
+                command_rate_limit = { per_second = 10, burst = 20 }
+                reconnect = { max_attempts = 5 }
+                "#,
+            )
+            .unwrap();
+
+            let config = KalshiConfig::from_toml(&path).unwrap();
+            std::fs::remove_dir_all(&dir).unwrap();
+            assert_eq!(config.environment, TradingEnvironment::DemoMode);
+            assert_eq!(config.connect_timeout, Some(Duration::from_millis(2500)));
+            assert_eq!(config.api_key.as_ref().unwrap().private_key_pem, key);
+            #[cfg(feature = "websockets")]
+            {
+                assert_eq!(config.websocket.command_rate_limit.unwrap().burst, 20);
+                let reconnect = config.websocket.reconnect.unwrap();
+                assert_eq!(reconnect.max_attempts, Some(5));
+                assert_eq!(reconnect.initial_backoff, Duration::from_secs(1));
+            }
+            assert!(Kalshi::from_config(config).is_ok());
+
+            for invalid in [
+                "environment = \"staging\"",
+                "environment = \"demo\"\nrequest_timout_secs = 10",
+                "environment = \"demo\"\n[api_key]\nkey_id = \"a952bcbe-ec3b-4b5b-b8f9-11dae589608c\"",
+                "environment = \"demo\"\nrequest_timeout_secs = -1",
+            ] {
+                assert!(matches!(
+                    KalshiConfig::from_reader(invalid.as_bytes()),
+                    Err(KalshiError::UserInputError(_))
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::throwaway_private_key;
+
+    fn demo_config() -> KalshiConfig {
+        KalshiConfig {
+            api_key: Some(ApiKeyConfig {
+                key_id: "a952bcbe-ec3b-4b5b-b8f9-11dae589608c".to_string(),
+                private_key_pem: throwaway_private_key(),
+                environment: Some(TradingEnvironment::DemoMode),
+            }),
+            request_timeout: Some(Duration::from_secs(10)),
+            connect_timeout: Some(Duration::from_secs(2)),
+            dry_run: true,
+            ..KalshiConfig::new(TradingEnvironment::DemoMode)
+        }
+    }
+
+    #[test]
+    fn test_config_validated_and_built() {
+        let kalshi = Kalshi::from_config(demo_config()).unwrap();
+        assert_eq!(
+            kalshi.get_base_url(),
+            utils::build_base_url(TradingEnvironment::DemoMode)
+        );
+
+        let invalid_configs = [
+            // A demo key against the live environment
+            KalshiConfig {
+                environment: TradingEnvironment::LiveMarketMode,
+                ..demo_config()
+            },
+            KalshiConfig {
+                base_url: Some(
+                    utils::build_base_url(TradingEnvironment::LiveMarketMode).to_string(),
+                ),
+                ..demo_config()
+            },
+            KalshiConfig {
+                api_key: Some(ApiKeyConfig {
+                    private_key_pem: "not a key".to_string(),
+                    ..demo_config().api_key.unwrap()
+                }),
+                ..demo_config()
+            },
+            KalshiConfig {
+                connect_timeout: Some(Duration::from_secs(30)),
+                ..demo_config()
+            },
+        ];
+        for config in invalid_configs {
+            assert!(matches!(
+                Kalshi::from_config(config),
+                Err(KalshiError::UserInputError(_))
+            ));
+        }
+
+        // Proxies and mock servers aren't another environment
+        let proxied = KalshiConfig {
+            base_url: Some("http://127.0.0.1:8080/trade-api/v2".to_string()),
+            ..demo_config()
+        };
+        assert!(proxied.validate().is_ok());
+    }
+}
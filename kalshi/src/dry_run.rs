@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use uuid::Uuid;
+
+use crate::{
+    CanceledOrder, KalshiError, Order, OrderCreationField, OrderStatus, OrderType, TimeInForce,
+};
+
+/// The orders "placed" by a [`Kalshi`](crate::Kalshi) instance in dry-run mode.
+///
+/// Nothing is sent to the exchange: orders rest forever at their limit price, so they can be
+/// canceled and decreased like real ones. Clones share the same orders.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DryRun {
+    orders: Arc<Mutex<HashMap<String, Order>>>,
+}
+
+impl DryRun {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Order>> {
+        self.orders.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Records an order that passed local validation, returning what the exchange would have.
+    pub(crate) fn create_order(
+        &self,
+        order: &OrderCreationField,
+        client_order_id: String,
+    ) -> Order {
+        let (yes_price, no_price) = match (order.yes_price, order.no_price) {
+            (Some(yes), _) => (yes, 100 - yes),
+            (None, Some(no)) => (100 - no, no),
+            // Market orders have no price until they fill
+            (None, None) => (0, 0),
+        };
+        let order = Order {
+            order_id: format!("dry-run-{}", Uuid::new_v4()),
+            user_id: None,
+            ticker: order.ticker.clone(),
+            status: OrderStatus::Resting,
+            yes_price: yes_price as i32,
+            no_price: no_price as i32,
+            created_time: Some(chrono::Utc::now().to_rfc3339()),
+            taker_fill_count: Some(0),
+            taker_fill_cost: Some(0),
+            place_count: Some(order.count),
+            decrease_count: Some(0),
+            maker_fill_count: Some(0),
+            fcc_cancel_count: Some(0),
+            close_cancel_count: Some(0),
+            remaining_count: Some(order.count),
+            queue_position: None,
+            expiration_time: order
+                .expiration_ts
+                .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                .map(|time| time.to_rfc3339()),
+            time_in_force: Some(TimeInForce::GoodTillCanceled),
+            post_only: false,
+            taker_fees: Some(0),
+            action: order.action,
+            side: order.side,
+            r#type: match order.input_type {
+                OrderType::Market => "market".to_string(),
+                OrderType::Limit => "limit".to_string(),
+            },
+            last_update_time: None,
+            client_order_id,
+            order_group_id: String::new(),
+        };
+        self.lock().insert(order.order_id.clone(), order.clone());
+        order
+    }
+
+    /// Cancels a dry-run order, returning it and how many contracts were canceled.
+    pub(crate) fn cancel_order(&self, order_id: &str) -> Result<CanceledOrder, KalshiError> {
+        let mut orders = self.lock();
+        let order = Self::resting(&mut orders, order_id)?;
+        let reduced_by = order.remaining_count.unwrap_or(0);
+        order.remaining_count = Some(0);
+        order.status = OrderStatus::Canceled;
+        Ok(CanceledOrder {
+            order: order.clone(),
+            reduced_by,
+        })
+    }
+
+    /// Decreases a dry-run order by or to a number of contracts, checked by the caller.
+    pub(crate) fn decrease_order(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> Result<Order, KalshiError> {
+        let mut orders = self.lock();
+        let order = Self::resting(&mut orders, order_id)?;
+        let remaining = order.remaining_count.unwrap_or(0);
+        let new_remaining = match (reduce_by, reduce_to) {
+            (Some(by), _) => remaining - by,
+            (None, Some(to)) => to,
+            (None, None) => remaining,
+        }
+        .clamp(0, remaining);
+        order.remaining_count = Some(new_remaining);
+        order.decrease_count = Some(order.decrease_count.unwrap_or(0) + remaining - new_remaining);
+        if new_remaining == 0 {
+            order.status = OrderStatus::Canceled;
+        }
+        Ok(order.clone())
+    }
+
+    fn resting<'a>(
+        orders: &'a mut HashMap<String, Order>,
+        order_id: &str,
+    ) -> Result<&'a mut Order, KalshiError> {
+        match orders.get_mut(order_id) {
+            Some(order) if order.status == OrderStatus::Resting => Ok(order),
+            Some(_) => Err(KalshiError::UserInputError(format!(
+                "Dry-run order {} is no longer resting",
+                order_id
+            ))),
+            None => Err(KalshiError::UserInputError(format!(
+                "No dry-run order {}",
+                order_id
+            ))),
+        }
+    }
+
+    /// Every order placed in dry-run mode, in no particular order.
+    pub(crate) fn orders(&self) -> Vec<Order> {
+        self.lock().values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Action, Side};
+
+    #[test]
+    fn test_dry_run_orders_can_be_decreased_and_canceled() {
+        let dry_run = DryRun::default();
+        let order = dry_run.create_order(
+            &OrderCreationField {
+                action: Action::Buy,
+                client_order_id: None,
+                count: 10,
+                side: Side::No,
+                ticker: "KXHIGHNY-25OCT02-B80.5".to_string(),
+                input_type: OrderType::Limit,
+                buy_max_cost: None,
+                expiration_ts: None,
+                no_price: Some(62),
+                sell_position_floor: None,
+                yes_price: None,
+            },
+            "client".to_string(),
+        );
+        assert_eq!((order.yes_price, order.no_price), (38, 62));
+        assert_eq!(order.status, OrderStatus::Resting);
+
+        let decreased = dry_run
+            .decrease_order(&order.order_id, Some(4), None)
+            .unwrap();
+        assert_eq!(decreased.remaining_count, Some(6));
+
+        let canceled = dry_run.cancel_order(&order.order_id).unwrap();
+        assert_eq!(canceled.reduced_by, 6);
+        assert_eq!(canceled.order.status, OrderStatus::Canceled);
+        assert!(dry_run.cancel_order(&order.order_id).is_err());
+        assert!(dry_run.cancel_order("unknown").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_kalshi_never_reaches_the_exchange() {
+        let mut kalshi = crate::Kalshi::new(crate::TradingEnvironment::DemoMode);
+        kalshi.set_dry_run(true);
+        let order = kalshi
+            .create_order(
+                Action::Buy,
+                None,
+                5,
+                Side::Yes,
+                "KXHIGHNY-25OCT02-B80.5".to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                None,
+                None,
+                Some(40),
+            )
+            .await
+            .unwrap();
+        assert!(order.order_id.starts_with("dry-run-"));
+        // Local validation still applies
+        assert!(kalshi
+            .create_order(
+                Action::Buy,
+                None,
+                5,
+                Side::Yes,
+                "KXHIGHNY-25OCT02-B80.5".to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .is_err());
+        kalshi.cancel_order(&order.order_id).await.unwrap();
+        assert_eq!(kalshi.get_dry_run_orders()[0].status, OrderStatus::Canceled);
+    }
+}
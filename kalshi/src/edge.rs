@@ -0,0 +1,119 @@
+use crate::{FeeModel, FeeSchedule, Kalshi, KalshiError, Side};
+
+/// The expected value of buying a contract, computed by [`Kalshi::expected_value`].
+///
+/// Prices and values are in cents per contract, a contract paying 100 cents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpectedValue {
+    /// The estimated chance that the side bought pays out.
+    pub probability: f64,
+    /// The fee of a contract in cents. Fees are rounded up per order, this is the fee of a
+    /// contract in an order of 100.
+    pub fee_per_contract: f64,
+    /// The expected profit of a contract, net of fees, negative for a losing trade.
+    pub ev_per_contract: f64,
+    /// The chance of paying out the side needs for the trade to break even after fees.
+    pub breakeven_probability: f64,
+}
+
+impl ExpectedValue {
+    /// The expected value of buying `side` at `price` cents, given the estimated probability that
+    /// yes pays out, with `fees` charged on the trade.
+    pub fn compute(side: Side, price: i64, probability_yes: f64, fees: FeeModel) -> Self {
+        let probability = match side {
+            Side::Yes => probability_yes,
+            Side::No => 1.0 - probability_yes,
+        };
+        let fee_per_contract = fees.fee(100, price) as f64 / 100.0;
+        let cost = price as f64 + fee_per_contract;
+        ExpectedValue {
+            probability,
+            fee_per_contract,
+            ev_per_contract: probability * 100.0 - cost,
+            breakeven_probability: cost / 100.0,
+        }
+    }
+
+    /// How much the estimated probability exceeds the breakeven one, negative without an edge.
+    pub fn edge(&self) -> f64 {
+        self.probability - self.breakeven_probability
+    }
+
+    /// Whether the trade is expected to make money after fees.
+    pub fn is_profitable(&self) -> bool {
+        self.ev_per_contract > 0.0
+    }
+}
+
+impl Kalshi {
+    /// Computes the expected value of buying a side of a market, net of the series' taker fees.
+    ///
+    /// Fetches the market, its event and series for the fee schedule (cached when a metadata
+    /// cache is configured). Taker fees are the ones paid crossing the spread, see
+    /// [`ExpectedValue::compute`] for orders that rest on the book with the maker fees.
+    ///
+    /// # Arguments
+    /// * `ticker` - The market to trade.
+    /// * `side` - The side to buy.
+    /// * `price` - The price paid on `side`, in cents.
+    /// * `probability_yes` - The estimated probability that yes pays out, between 0 and 1.
+    ///
+    /// # Example
+    /// ```
+    /// // We think yes has a 62% chance, the yes ask is 55
+    /// let ev = kalshi_instance.expected_value("KXHIGHNY-25OCT02-B80.5", Side::Yes, 55, 0.62).await?;
+    /// if ev.is_profitable() {
+    ///     println!("{:.2} cents per contract, breakeven at {:.1}%", ev.ev_per_contract, ev.breakeven_probability * 100.0);
+    /// }
+    /// ```
+    pub async fn expected_value(
+        &self,
+        ticker: &str,
+        side: Side,
+        price: i64,
+        probability_yes: f64,
+    ) -> Result<ExpectedValue, KalshiError> {
+        if !(1..=99).contains(&price) || !(0.0..=1.0).contains(&probability_yes) {
+            return Err(KalshiError::UserInputError(format!(
+                "Can't value a contract at {} cents with a probability of {}, the price must be between 1 and 99 and the probability between 0 and 1",
+                price, probability_yes
+            )));
+        }
+        let market = self.get_single_market(&ticker.to_string()).await?;
+        let event = self.get_single_event(&market.event_ticker, None).await?;
+        let series = self.get_series(&event.series_ticker).await?;
+        Ok(ExpectedValue::compute(
+            side,
+            price,
+            probability_yes,
+            FeeSchedule::from_series(&series).taker,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_expected_value_net_of_fees() {
+        let ev = ExpectedValue::compute(Side::Yes, 50, 0.6, FeeModel::KALSHI_TAKER);
+        assert!((ev.fee_per_contract - 1.75).abs() < 1e-9);
+        assert!((ev.ev_per_contract - 8.25).abs() < 1e-9);
+        assert!((ev.breakeven_probability - 0.5175).abs() < 1e-9);
+        assert!(ev.is_profitable());
+
+        // The same trade on the no side
+        let no = ExpectedValue::compute(Side::No, 50, 0.4, FeeModel::KALSHI_TAKER);
+        assert!((no.ev_per_contract - ev.ev_per_contract).abs() < 1e-9);
+
+        // An edge smaller than the fees
+        let ev = ExpectedValue::compute(Side::Yes, 50, 0.51, FeeModel::KALSHI_TAKER);
+        assert!(!ev.is_profitable());
+        assert!(ev.edge() < 0.0);
+        assert_eq!(
+            ExpectedValue::compute(Side::Yes, 50, 0.51, FeeModel::NoFees).breakeven_probability,
+            0.5
+        );
+    }
+}
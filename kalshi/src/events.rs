@@ -0,0 +1,599 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::Stream;
+use tokio::{sync::mpsc, time::MissedTickBehavior};
+
+use crate::{
+    BalanceChange, ExchangeStatus, Fill, Kalshi, Market, SettlementResult, Ticker, TriggerQuote,
+};
+
+/// Fills remembered to drop the ones delivered by both transports.
+const REMEMBERED_FILLS: usize = 10_000;
+
+/// Fills fetched per page when polling.
+const FILLS_PAGE_SIZE: i32 = 100;
+
+/// Events buffered for a consumer that fell behind, the bus waits once it's full.
+const EVENT_BUFFER: usize = 1024;
+
+/// Something that happened on the exchange or in the account, whatever transport reported it.
+#[derive(Debug, Clone)]
+pub enum KalshiEvent {
+    /// New prices of a followed market.
+    Quote(TriggerQuote),
+    /// A fill of one of the account's orders, delivered once even if both transports saw it.
+    Fill(Fill),
+    /// A followed market changed status.
+    Lifecycle {
+        ticker: Ticker,
+        change: LifecycleChange,
+    },
+    /// The exchange opened, closed or paused trading.
+    ExchangeStatus(ExchangeStatus),
+    /// The account's balance changed.
+    Balance(BalanceChange),
+}
+
+/// How a market's status changed, see [`KalshiEvent::Lifecycle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleChange {
+    Opened,
+    Closed,
+    /// The outcome is known, `None` when it isn't one this crate parses.
+    Determined(Option<SettlementResult>),
+    Settled,
+}
+
+impl LifecycleChange {
+    /// The change a market's REST status means, `None` for statuses that don't map to one.
+    fn from_status(market: &Market) -> Option<Self> {
+        match market.status.as_str() {
+            "active" | "open" => Some(LifecycleChange::Opened),
+            "inactive" | "closed" => Some(LifecycleChange::Closed),
+            "determined" => Some(LifecycleChange::Determined(market.result)),
+            "settled" | "finalized" => Some(LifecycleChange::Settled),
+            _ => None,
+        }
+    }
+}
+
+/// Merges the REST api and the websocket feed into one stream of [`KalshiEvent`]s, so strategy
+/// code handles a single event type whatever delivers it.
+///
+/// Quotes, lifecycle changes and fills come from the websocket feed while it's connected and
+/// from polling the REST api every `poll_interval` otherwise, or when the bus was started without
+/// a websocket client. The exchange status and the balance have no websocket channel and are
+/// always polled. Events come out in the order the bus observed them; polled values are only
+/// reported when they changed, fills are reported once even when both transports deliver them.
+///
+/// ```
+/// let mut events = EventBus::new(kalshi_instance.clone())
+///     .markets(vec!["KXHIGHNY-25OCT02-B80.5".to_string()])
+///     .account(true)
+///     .start_with_websocket(&mut ws_client)
+///     .await?;
+/// while let Some(event) = events.recv().await {
+///     match event {
+///         KalshiEvent::Quote(quote) => println!("{} bid {}", quote.ticker, quote.yes_bid),
+///         KalshiEvent::Fill(fill) => println!("filled {} of {}", fill.count, fill.order_id),
+///         _ => {}
+///     }
+/// }
+/// ```
+pub struct EventBus {
+    kalshi: Kalshi,
+    markets: Vec<String>,
+    account: bool,
+    poll_interval: Duration,
+    exchange_status_interval: Duration,
+    balance_interval: Duration,
+}
+
+impl EventBus {
+    pub fn new(kalshi: Kalshi) -> Self {
+        EventBus {
+            kalshi,
+            markets: Vec::new(),
+            account: false,
+            poll_interval: Duration::from_secs(5),
+            exchange_status_interval: Duration::from_secs(60),
+            balance_interval: Duration::from_secs(30),
+        }
+    }
+
+    /// Markets whose quotes and lifecycle changes are reported.
+    pub fn markets(mut self, tickers: Vec<String>) -> Self {
+        self.markets = tickers;
+        self
+    }
+
+    /// Whether the account's fills and balance changes are reported, which requires an
+    /// authenticated instance. Off by default.
+    pub fn account(mut self, enabled: bool) -> Self {
+        self.account = enabled;
+        self
+    }
+
+    /// How often markets and fills are polled while no websocket delivers them, 5 seconds by default.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// How often the exchange status is polled, a minute by default.
+    pub fn exchange_status_interval(mut self, interval: Duration) -> Self {
+        self.exchange_status_interval = interval;
+        self
+    }
+
+    /// How often the balance is polled, 30 seconds by default.
+    pub fn balance_interval(mut self, interval: Duration) -> Self {
+        self.balance_interval = interval;
+        self
+    }
+
+    /// Starts reporting events polled from the REST api.
+    pub fn start(self) -> KalshiEvents {
+        self.spawn(None)
+    }
+
+    fn spawn(self, feed: Option<Feed>) -> KalshiEvents {
+        let (sender, receiver) = mpsc::channel(EVENT_BUFFER);
+        let shutdown = self.kalshi.shutdown_handle().clone();
+        shutdown.spawn("event bus", run_bus(self, feed, sender));
+        KalshiEvents { receiver }
+    }
+}
+
+/// The events reported by an [`EventBus`], until the instance shuts down. Dropping it stops the bus.
+#[derive(Debug)]
+pub struct KalshiEvents {
+    receiver: mpsc::Receiver<KalshiEvent>,
+}
+
+impl KalshiEvents {
+    /// The next event, `None` once the bus stopped.
+    pub async fn recv(&mut self) -> Option<KalshiEvent> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for KalshiEvents {
+    type Item = KalshiEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// The websocket messages the bus follows, there are none without the `websockets` feature.
+#[cfg(not(feature = "websockets"))]
+type Feed = std::convert::Infallible;
+#[cfg(feature = "websockets")]
+use ws::{next_feed_message, on_feed_message, Feed};
+
+/// What the bus reported last, to only report changes.
+struct BusState {
+    markets: HashSet<Ticker>,
+    quotes: HashMap<Ticker, TriggerQuote>,
+    statuses: HashMap<Ticker, LifecycleChange>,
+    exchange_status: Option<ExchangeStatus>,
+    balance: Option<i64>,
+    seen_fills: HashSet<String>,
+    /// Seen fills, oldest first
+    fill_order: VecDeque<String>,
+    /// Creation time of the newest fill seen, in seconds since the epoch
+    fills_since: i64,
+}
+
+impl BusState {
+    fn new(markets: &[String]) -> Self {
+        BusState {
+            markets: markets.iter().map(|ticker| Ticker::new(ticker)).collect(),
+            quotes: HashMap::new(),
+            statuses: HashMap::new(),
+            exchange_status: None,
+            balance: None,
+            seen_fills: HashSet::new(),
+            fill_order: VecDeque::new(),
+            fills_since: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn on_quote(&mut self, quote: TriggerQuote) -> Option<KalshiEvent> {
+        if !self.markets.contains(&quote.ticker) || self.quotes.get(&quote.ticker) == Some(&quote) {
+            return None;
+        }
+        self.quotes.insert(quote.ticker.clone(), quote.clone());
+        Some(KalshiEvent::Quote(quote))
+    }
+
+    fn on_lifecycle(&mut self, ticker: Ticker, change: LifecycleChange) -> Option<KalshiEvent> {
+        if !self.markets.contains(&ticker) {
+            return None;
+        }
+        match self.statuses.insert(ticker.clone(), change) {
+            Some(previous) if previous != change => Some(KalshiEvent::Lifecycle { ticker, change }),
+            // The first status seen only sets the one changes are reported from
+            _ => None,
+        }
+    }
+
+    fn on_market(&mut self, market: &Market) -> Vec<KalshiEvent> {
+        let mut events: Vec<KalshiEvent> = self.on_quote(market.into()).into_iter().collect();
+        if let Some(change) = LifecycleChange::from_status(market) {
+            events.extend(self.on_lifecycle(Ticker::new(&market.ticker), change));
+        }
+        events
+    }
+
+    fn on_fill(&mut self, fill: Fill) -> Option<KalshiEvent> {
+        if !self.seen_fills.insert(fill.trade_id.clone()) {
+            return None;
+        }
+        self.fill_order.push_back(fill.trade_id.clone());
+        if self.fill_order.len() > REMEMBERED_FILLS {
+            if let Some(oldest) = self.fill_order.pop_front() {
+                self.seen_fills.remove(&oldest);
+            }
+        }
+        if let Some(created) = fill_timestamp(&fill) {
+            self.fills_since = self.fills_since.max(created);
+        }
+        Some(KalshiEvent::Fill(fill))
+    }
+
+    /// The fills of a poll not seen yet, oldest first, ignoring the ones older than the bus.
+    fn on_polled_fills(&mut self, mut fills: Vec<Fill>) -> Vec<KalshiEvent> {
+        let since = self.fills_since;
+        fills.retain(|fill| fill_timestamp(fill).is_some_and(|created| created >= since));
+        fills.sort_by_key(fill_timestamp);
+        fills
+            .into_iter()
+            .filter_map(|fill| self.on_fill(fill))
+            .collect()
+    }
+
+    fn on_exchange_status(&mut self, status: ExchangeStatus) -> Option<KalshiEvent> {
+        if self.exchange_status.as_ref() == Some(&status) {
+            return None;
+        }
+        self.exchange_status = Some(status.clone());
+        Some(KalshiEvent::ExchangeStatus(status))
+    }
+
+    fn on_balance(&mut self, balance: i64) -> Option<KalshiEvent> {
+        let previous = self.balance.replace(balance)?;
+        (previous != balance).then_some(KalshiEvent::Balance(BalanceChange {
+            balance,
+            delta: balance - previous,
+        }))
+    }
+}
+
+fn fill_timestamp(fill: &Fill) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(&fill.created_time)
+        .ok()
+        .map(|created| created.timestamp())
+}
+
+async fn poll_markets(kalshi: &Kalshi, state: &mut BusState) -> Vec<KalshiEvent> {
+    let mut events = Vec::new();
+    let mut tickers: Vec<Ticker> = state.markets.iter().cloned().collect();
+    tickers.sort();
+    for ticker in tickers {
+        match kalshi.get_single_market(&ticker.to_string()).await {
+            Ok(market) => events.extend(state.on_market(&market)),
+            Err(e) => log::warn!("Could not poll {} for events: {}", ticker, e),
+        }
+    }
+    events
+}
+
+/// Fetches every page of fills since the latest one reported, a failed page keeps the fills
+/// fetched before it.
+async fn poll_fills(kalshi: &Kalshi, state: &mut BusState) -> Vec<KalshiEvent> {
+    let mut fills = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = kalshi
+            .get_multiple_fills(
+                None,
+                None,
+                Some(state.fills_since),
+                None,
+                Some(FILLS_PAGE_SIZE),
+                cursor,
+            )
+            .await;
+        match page {
+            Ok((next, page)) => {
+                let empty = page.is_empty();
+                fills.extend(page);
+                match next {
+                    Some(next) if !next.is_empty() && !empty => cursor = Some(next),
+                    _ => break,
+                }
+            }
+            Err(e) => {
+                log::warn!("Could not poll fills for events: {}", e);
+                break;
+            }
+        }
+    }
+    state.on_polled_fills(fills)
+}
+
+/// Reports events until the consumer drops [`KalshiEvents`].
+async fn run_bus(bus: EventBus, mut feed: Option<Feed>, sender: mpsc::Sender<KalshiEvent>) {
+    let kalshi = &bus.kalshi;
+    let mut state = BusState::new(&bus.markets);
+    let ticker = |interval| {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticks
+    };
+    let mut poll_ticks = ticker(bus.poll_interval);
+    let mut exchange_ticks = ticker(bus.exchange_status_interval);
+    let mut balance_ticks = ticker(bus.balance_interval);
+    // Whether the websocket delivers market data and fills right now
+    let mut feed_live = feed.is_some();
+
+    loop {
+        let events = tokio::select! {
+            _ = poll_ticks.tick(), if !feed_live => {
+                let mut events = poll_markets(kalshi, &mut state).await;
+                if bus.account {
+                    events.extend(poll_fills(kalshi, &mut state).await);
+                }
+                events
+            }
+            _ = exchange_ticks.tick() => match kalshi.get_exchange_status().await {
+                Ok(status) => state.on_exchange_status(status).into_iter().collect(),
+                Err(e) => {
+                    log::warn!("Could not poll the exchange status for events: {}", e);
+                    Vec::new()
+                }
+            },
+            _ = balance_ticks.tick(), if bus.account => match kalshi.get_balance().await {
+                Ok(balance) => state.on_balance(balance).into_iter().collect(),
+                Err(e) => {
+                    log::warn!("Could not poll the balance for events: {}", e);
+                    Vec::new()
+                }
+            },
+            msg = next_feed_message(&mut feed), if feed.is_some() => {
+                on_feed_message(msg, &mut feed, &mut feed_live, &mut state)
+            }
+        };
+        for event in events {
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "websockets"))]
+async fn next_feed_message(_feed: &mut Option<Feed>) {
+    std::future::pending().await
+}
+
+#[cfg(not(feature = "websockets"))]
+fn on_feed_message(
+    _msg: (),
+    _feed: &mut Option<Feed>,
+    _feed_live: &mut bool,
+    _state: &mut BusState,
+) -> Vec<KalshiEvent> {
+    Vec::new()
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::{
+        client::{KalshiWebsocketClient, KalshiWebsocketError},
+        responses::{KalshiMarketLifecycleMessage, KalshiWebsocketResponse},
+    };
+    use crate::{KalshiChannel, KalshiError};
+    use tokio::sync::broadcast::{self, error::RecvError};
+
+    pub(super) type Feed =
+        broadcast::Receiver<Result<KalshiWebsocketResponse, KalshiWebsocketError>>;
+    type FeedMessage = Result<Result<KalshiWebsocketResponse, KalshiWebsocketError>, RecvError>;
+
+    impl EventBus {
+        /// Starts reporting events from `ws_client`'s feed, polling the REST api while it's
+        /// disconnected.
+        ///
+        /// Subscribes to the `ticker` channel of the markets, the `market_lifecycle_v2` channel,
+        /// and the `fill` channel when following the account.
+        pub async fn start_with_websocket(
+            self,
+            ws_client: &mut KalshiWebsocketClient,
+        ) -> Result<KalshiEvents, KalshiError> {
+            let feed = ws_client.receiver();
+            let mut subscriptions = vec![(vec![KalshiChannel::MarketLifecycleV2], vec![])];
+            if !self.markets.is_empty() {
+                subscriptions.push((vec![KalshiChannel::Ticker], self.markets.clone()));
+            }
+            if self.account {
+                subscriptions.push((vec![KalshiChannel::Fill], vec![]));
+            }
+            for (channels, tickers) in subscriptions {
+                ws_client
+                    .ensure_subscribed(channels, tickers)
+                    .await
+                    .map_err(|e| KalshiError::InternalError(e.to_string()))?;
+            }
+            Ok(self.spawn(Some(feed)))
+        }
+    }
+
+    pub(super) async fn next_feed_message(feed: &mut Option<Feed>) -> FeedMessage {
+        match feed {
+            Some(feed) => feed.recv().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    pub(super) fn on_feed_message(
+        msg: FeedMessage,
+        feed: &mut Option<Feed>,
+        feed_live: &mut bool,
+        state: &mut BusState,
+    ) -> Vec<KalshiEvent> {
+        let msg = match msg {
+            Ok(Ok(msg)) => msg,
+            Ok(Err(KalshiWebsocketError::ConnectionClosed))
+            | Ok(Err(KalshiWebsocketError::WebSocketError(_))) => {
+                // Polled until the client reconnected
+                *feed_live = false;
+                return Vec::new();
+            }
+            Ok(Err(_)) => return Vec::new(),
+            Err(RecvError::Lagged(skipped)) => {
+                log::warn!("Event bus lagged, skipped {} messages", skipped);
+                return Vec::new();
+            }
+            Err(RecvError::Closed) => {
+                // The client is gone for good, poll from now on
+                *feed = None;
+                *feed_live = false;
+                return Vec::new();
+            }
+        };
+        *feed_live = true;
+        match msg {
+            KalshiWebsocketResponse::Ticker { msg, .. } => {
+                state.on_quote((&msg).into()).into_iter().collect()
+            }
+            KalshiWebsocketResponse::Fill { msg, .. } => match Fill::try_from(&msg) {
+                Ok(fill) => state.on_fill(fill).into_iter().collect(),
+                Err(e) => {
+                    log::warn!("Ignoring fill {}: {}", msg.trade_id, e);
+                    Vec::new()
+                }
+            },
+            KalshiWebsocketResponse::MarketLifecycleV2 { msg, .. } => {
+                let change = match &msg {
+                    KalshiMarketLifecycleMessage::Activated { .. } => LifecycleChange::Opened,
+                    KalshiMarketLifecycleMessage::Deactivated { .. } => LifecycleChange::Closed,
+                    KalshiMarketLifecycleMessage::Determined { result, .. } => {
+                        LifecycleChange::Determined(
+                            serde_json::from_value(serde_json::Value::String(result.clone())).ok(),
+                        )
+                    }
+                    KalshiMarketLifecycleMessage::Settled { .. } => LifecycleChange::Settled,
+                    _ => return Vec::new(),
+                };
+                let ticker = Ticker::new(msg.get_market_ticker());
+                state.on_lifecycle(ticker, change).into_iter().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use reqwest::Method;
+    use serde_json::json;
+
+    async fn next(events: &mut KalshiEvents) -> KalshiEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_polled_events_reported_once() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let mut fill = fixtures::fills_page();
+        fill["fills"][0]["created_time"] = json!(chrono::Utc::now().to_rfc3339());
+        server.respond(Method::GET, "/portfolio/fills", 200, fill);
+
+        let mut events = EventBus::new(kalshi)
+            .markets(vec![fixtures::MARKET_TICKER.to_string()])
+            .account(true)
+            .poll_interval(Duration::from_millis(20))
+            .balance_interval(Duration::from_millis(20))
+            .start();
+        let mut first = Vec::new();
+        for _ in 0..3 {
+            first.push(next(&mut events).await);
+        }
+        assert!(first.iter().any(|e| matches!(e,
+            KalshiEvent::Quote(quote) if quote.ticker == fixtures::MARKET_TICKER)));
+        assert!(first
+            .iter()
+            .any(|e| matches!(e, KalshiEvent::ExchangeStatus(status) if status.can_trade())));
+        assert!(first.iter().any(|e| matches!(e,
+            KalshiEvent::Fill(fill) if fill.trade_id == "5a1f0c3e-92d4-4c1e-8c55-0d7b0f2e6c90")));
+
+        // The balance only changes once its first poll set the baseline
+        while server
+            .requests_to(Method::GET, "/portfolio/balance")
+            .is_empty()
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        // Unchanged values and fills seen before are not reported again
+        server.respond(
+            Method::GET,
+            "/portfolio/balance",
+            200,
+            fixtures::balance(9_000),
+        );
+        match next(&mut events).await {
+            KalshiEvent::Balance(change) => assert_eq!(change.delta, -1_000),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_polled_fills_follow_cursor() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let mut state = BusState::new(&[]);
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut first = fixtures::fills_page();
+        first["fills"][0]["created_time"] = json!(now);
+        first["cursor"] = json!("page-2");
+        let mut last = first.clone();
+        last["fills"][0]["trade_id"] = json!("page-2-trade");
+        last["cursor"] = json!("");
+        server.respond(Method::GET, "/portfolio/fills", 200, first);
+        server.respond_to_query(
+            Method::GET,
+            "/portfolio/fills",
+            &[("cursor", "page-2")],
+            200,
+            last,
+        );
+
+        let events = poll_fills(&kalshi, &mut state).await;
+        let trades: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                KalshiEvent::Fill(fill) => Some(fill.trade_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            trades,
+            vec!["5a1f0c3e-92d4-4c1e-8c55-0d7b0f2e6c90", "page-2-trade"]
+        );
+        assert_eq!(server.requests_to(Method::GET, "/portfolio/fills").len(), 2);
+    }
+}
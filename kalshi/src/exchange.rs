@@ -1,6 +1,12 @@
 use super::Kalshi;
 use crate::kalshi_error::*;
+use crate::utils;
+use crate::wire::SendLogged;
+use chrono::{DateTime, Utc};
+use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 impl Kalshi {
     /// Asynchronously retrieves the current status of the exchange.
@@ -18,17 +24,105 @@ impl Kalshi {
     pub async fn get_exchange_status(&self) -> Result<ExchangeStatus, KalshiError> {
         let exchange_status_url: &str = &format!("{}/exchange/status", self.base_url.to_string());
 
-        let result: ExchangeStatus = self
-            .client
-            .get(exchange_status_url)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: ExchangeStatus =
+            utils::parse_json(self.client.get(exchange_status_url).send_logged().await?).await?;
 
         return Ok(result);
     }
 
+    /// Polls the exchange status every `interval` and yields it whenever it changes.
+    ///
+    /// The first status is always yielded, after that only the polls where `trading_active` or
+    /// `exchange_active` toggled, so bots can pause and resume around exchange halts. Failed polls
+    /// are yielded as errors and polling carries on.
+    ///
+    /// # Example
+    /// ```
+    /// let mut statuses = Box::pin(kalshi_instance.watch_exchange_status(Duration::from_secs(5)));
+    /// while let Some(status) = statuses.next().await {
+    ///     if let Ok(status) = status {
+    ///         paused = !status.can_trade();
+    ///     }
+    /// }
+    /// ```
+    pub fn watch_exchange_status(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<ExchangeStatus, KalshiError>> + '_ {
+        async_stream::stream! {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last: Option<ExchangeStatus> = None;
+            loop {
+                ticker.tick().await;
+                match self.get_exchange_status().await {
+                    Ok(status) if last.as_ref().is_some_and(|last| last.same_activity(&status)) => {}
+                    Ok(status) => {
+                        last = Some(status.clone());
+                        yield Ok(status);
+                    }
+                    Err(e) => yield Err(e),
+                }
+            }
+        }
+    }
+
+    /// Retrieves the announcements the exchange currently publishes, such as degraded operations
+    /// or upcoming maintenance.
+    ///
+    /// # Returns
+    /// - `Ok(Vec<Announcement>)`: The announcements on successful retrieval.
+    /// - `Err(KalshiError)`: Error in case of a failure in the HTTP request or response parsing.
+    /// ```
+    /// let announcements = kalshi_instance.get_exchange_announcements().await.unwrap();
+    /// ```
+    pub async fn get_exchange_announcements(&self) -> Result<Vec<Announcement>, KalshiError> {
+        let announcements_url = format!("{}/exchange/announcements", self.base_url);
+        let result: AnnouncementsResponse =
+            utils::parse_json(self.client.get(announcements_url).send_logged().await?).await?;
+        Ok(result.announcements)
+    }
+
+    /// Polls the exchange announcements every `interval` and yields each one once.
+    ///
+    /// The announcements already published are yielded on the first poll, after that only the
+    /// ones that appeared since, so bots can react to degraded operations, e.g. by flattening
+    /// their positions. Failed polls are yielded as errors and polling carries on.
+    ///
+    /// # Example
+    /// ```
+    /// let mut announcements = Box::pin(kalshi_instance.watch_announcements(Duration::from_secs(30)));
+    /// while let Some(announcement) = announcements.next().await {
+    ///     let announcement = announcement?;
+    ///     if announcement.is_active() && announcement.is_warning() {
+    ///         flatten_positions().await?;
+    ///     }
+    /// }
+    /// ```
+    pub fn watch_announcements(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<Announcement, KalshiError>> + '_ {
+        async_stream::stream! {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut seen: HashSet<String> = HashSet::new();
+            loop {
+                ticker.tick().await;
+                match self.get_exchange_announcements().await {
+                    Ok(announcements) => {
+                        for announcement in announcements {
+                            if seen.insert(announcement.key()) {
+                                yield Ok(announcement);
+                            }
+                        }
+                    }
+                    Err(e) => yield Err(e),
+                }
+            }
+        }
+    }
+
     /// Asynchronously retrieves the exchange's trading schedule.
     ///
     /// Sends a GET request to the Kalshi exchange schedule endpoint to obtain
@@ -45,15 +139,50 @@ impl Kalshi {
         let exchange_schedule_url: &str =
             &format!("{}/exchange/schedule", self.base_url.to_string());
 
-        let result: ExchangeScheduleResponse = self
-            .client
-            .get(exchange_schedule_url)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: ExchangeScheduleResponse =
+            utils::parse_json(self.client.get(exchange_schedule_url).send_logged().await?).await?;
         return Ok(result.schedule);
     }
+
+    /// Asynchronously estimates the offset between the local clock and the exchange clock.
+    ///
+    /// Reads the `Date` header of an exchange status request and compares it to the local
+    /// time halfway through the round trip. The header only has second precision, so the
+    /// estimate is accurate to within about half a second.
+    ///
+    /// # Returns
+    /// - `Ok(i64)`: Milliseconds to add to local time to get exchange time.
+    /// - `Err(KalshiError)`: Error in case of a failure in the HTTP request or a missing `Date` header.
+    /// ```
+    /// let offset_ms = kalshi_instance.estimate_clock_offset().await.unwrap();
+    /// ```
+    pub async fn estimate_clock_offset(&self) -> Result<i64, KalshiError> {
+        let exchange_status_url: &str = &format!("{}/exchange/status", self.base_url);
+
+        let sent_at = SystemTime::now();
+        let started = Instant::now();
+        let response = self.client.get(exchange_status_url).send_logged().await?;
+        let midpoint = sent_at + started.elapsed() / 2;
+
+        let exchange_time = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|date| date.to_str().ok())
+            .and_then(|date| httpdate::parse_http_date(date).ok())
+            .ok_or_else(|| {
+                KalshiError::InternalError("Exchange response has no valid Date header".to_string())
+            })?;
+
+        // The Date header is truncated to whole seconds, compare against the middle of that second
+        let exchange_ms = unix_millis(exchange_time) + 500;
+        Ok(exchange_ms - unix_millis(midpoint))
+    }
+}
+
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
 }
 
 /// Represents the standard trading hours and maintenance windows of the exchange.
@@ -70,10 +199,83 @@ struct ExchangeScheduleResponse {
 }
 
 /// Represents the status of the exchange, including trading and exchange activity.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExchangeStatus {
     pub trading_active: bool,
     pub exchange_active: bool,
+    /// When the exchange expects to be back up, only set while it's down.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange_estimated_resume_time: Option<String>,
+}
+
+impl ExchangeStatus {
+    /// Whether orders can be placed, the exchange is up and trading isn't paused.
+    pub fn can_trade(&self) -> bool {
+        self.exchange_active && self.trading_active
+    }
+
+    /// Whether the exchange is up, it may still be outside trading hours.
+    pub fn is_open(&self) -> bool {
+        self.exchange_active
+    }
+
+    /// Whether the exchange is down, typically for its weekly maintenance.
+    pub fn is_under_maintenance(&self) -> bool {
+        !self.exchange_active
+    }
+
+    /// The parsed [`ExchangeStatus::exchange_estimated_resume_time`].
+    pub fn estimated_resume_time(&self) -> Option<DateTime<Utc>> {
+        self.exchange_estimated_resume_time
+            .as_deref()
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.with_timezone(&Utc))
+    }
+
+    /// Whether `other` has the same activity flags, ignoring the estimated resume time.
+    fn same_activity(&self, other: &ExchangeStatus) -> bool {
+        self.trading_active == other.trading_active && self.exchange_active == other.exchange_active
+    }
+}
+
+/// A message published by the exchange, see [`Kalshi::get_exchange_announcements`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Announcement {
+    /// Identifier of the announcement, when the exchange provides one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The severity of the announcement: `info`, `warning` or `error`.
+    pub r#type: String,
+    pub message: String,
+    /// When the announcement was published.
+    pub delivery_time: String,
+    /// `active` while the announcement applies, `inactive` after.
+    pub status: String,
+}
+
+impl Announcement {
+    /// Whether the announcement reports a warning or an error, such as degraded operations.
+    pub fn is_warning(&self) -> bool {
+        matches!(self.r#type.as_str(), "warning" | "error")
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == "active"
+    }
+
+    /// Identifies the announcement across polls, by id or by publication time and message.
+    fn key(&self) -> String {
+        match &self.id {
+            Some(id) => id.clone(),
+            None => format!("{}|{}", self.delivery_time, self.message),
+        }
+    }
+}
+
+/// Internal struct used for deserializing the response from the exchange announcements endpoint.
+#[derive(Debug, Deserialize, Serialize)]
+struct AnnouncementsResponse {
+    announcements: Vec<Announcement>,
 }
 
 /// Contains the daily schedule for each day of the week.
@@ -94,3 +296,76 @@ pub struct DaySchedule {
     pub open_time: String,
     pub close_time: String,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::MockHttpServer;
+    use futures::StreamExt;
+    use reqwest::Method;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_watch_exchange_status_yields_changes_only() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let mut statuses = Box::pin(kalshi.watch_exchange_status(Duration::from_millis(10)));
+
+        assert!(statuses.next().await.unwrap().unwrap().can_trade());
+        let halted = json!({ "trading_active": false, "exchange_active": true });
+        server.respond(Method::GET, "/exchange/status", 200, halted);
+        let status = statuses.next().await.unwrap().unwrap();
+        assert!(!status.can_trade());
+        assert!(status.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_watch_announcements_yields_new_ones_once() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let announcement = |time: &str, message: &str| {
+            json!({
+                "type": "warning",
+                "message": message,
+                "delivery_time": time,
+                "status": "active",
+            })
+        };
+        let degraded = announcement("2025-09-18T08:00:00Z", "Degraded order entry");
+        server.respond(
+            Method::GET,
+            "/exchange/announcements",
+            200,
+            json!({ "announcements": [degraded.clone()] }),
+        );
+        let mut announcements = Box::pin(kalshi.watch_announcements(Duration::from_millis(10)));
+
+        let first = announcements.next().await.unwrap().unwrap();
+        assert!(first.is_warning() && first.is_active());
+        let restored = announcement("2025-09-18T09:00:00Z", "Order entry restored");
+        server.respond(
+            Method::GET,
+            "/exchange/announcements",
+            200,
+            json!({ "announcements": [degraded, restored] }),
+        );
+        let next = announcements.next().await.unwrap().unwrap();
+        assert_eq!(next.message, "Order entry restored");
+    }
+
+    #[test]
+    fn test_exchange_status_during_maintenance() {
+        let status: ExchangeStatus = serde_json::from_value(json!({
+            "trading_active": false,
+            "exchange_active": false,
+            "exchange_estimated_resume_time": "2025-09-18T08:00:00Z",
+        }))
+        .unwrap();
+        assert!(status.is_under_maintenance());
+        assert!(!status.can_trade());
+        assert_eq!(
+            status.estimated_resume_time().unwrap().to_rfc3339(),
+            "2025-09-18T08:00:00+00:00"
+        );
+    }
+}
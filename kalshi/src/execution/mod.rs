@@ -0,0 +1,24 @@
+//! Algorithms that work a large order over time or across price levels instead of sending it at once,
+//! and order types the exchange doesn't support natively.
+
+#[cfg(feature = "websockets")]
+mod oco;
+mod queue;
+mod report;
+mod submit;
+mod sweep;
+// The store needs serde_json
+#[cfg(feature = "websockets")]
+mod ttl;
+#[cfg(feature = "websockets")]
+mod twap;
+
+#[cfg(feature = "websockets")]
+pub use oco::*;
+pub use queue::*;
+pub use report::*;
+pub use sweep::*;
+#[cfg(feature = "websockets")]
+pub use ttl::*;
+#[cfg(feature = "websockets")]
+pub use twap::*;
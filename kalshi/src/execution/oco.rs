@@ -0,0 +1,323 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
+
+use crate::{
+    store,
+    websockets::{client::KalshiWebsocketClient, responses::KalshiWebsocketResponse},
+    Kalshi, KalshiError, Order, OrderStatus, RestartPolicy,
+};
+
+/// Two resting orders where a fill on either cancels the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OcoLink {
+    pub id: String,
+    pub first_order_id: String,
+    pub second_order_id: String,
+}
+
+impl OcoLink {
+    /// The other leg of the link, `None` if `order_id` isn't one of its legs.
+    pub fn other_leg(&self, order_id: &str) -> Option<&str> {
+        if self.first_order_id == order_id {
+            Some(&self.second_order_id)
+        } else if self.second_order_id == order_id {
+            Some(&self.first_order_id)
+        } else {
+            None
+        }
+    }
+}
+
+/// Emulates one-cancels-the-other orders, which the exchange doesn't support natively.
+///
+/// Link two resting orders, typically a take-profit and a stop on the same position, and the first
+/// fill on either leg cancels the other one, even a partial fill. Fills are read from the websocket
+/// `fill` channel with [`OcoManager::follow_fills`].
+///
+/// With a store file the links are saved on every change, so a restarted bot picks them up again
+/// with [`OcoManager::with_store`]. Fills missed while it was down are caught up on with
+/// [`OcoManager::resume`], which checks every leg through the REST api.
+///
+/// The manager is a cheap handle, clones share the same links.
+///
+/// ```
+/// let oco = OcoManager::with_store("oco_links.json")?;
+/// oco.resume(&kalshi_instance).await?;
+/// let link = oco.link(&take_profit.order_id, &stop.order_id)?;
+/// oco.follow_fills(kalshi_instance.clone(), &mut ws_client).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OcoManager {
+    links: Arc<Mutex<Vec<OcoLink>>>,
+    store: Option<PathBuf>,
+}
+
+impl OcoManager {
+    /// Creates a manager that only keeps links in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a manager saving its links to `path`, loading the links already saved there.
+    pub fn with_store(path: impl AsRef<Path>) -> Result<Self, KalshiError> {
+        let path = path.as_ref().to_path_buf();
+        let links = store::load_json(&path, "OCO store")?;
+        Ok(OcoManager {
+            links: Arc::new(Mutex::new(links)),
+            store: Some(path),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<OcoLink>> {
+        self.links.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    fn save(&self, links: &[OcoLink]) -> Result<(), KalshiError> {
+        match &self.store {
+            Some(path) => store::save_json(path, "OCO store", links),
+            None => Ok(()),
+        }
+    }
+
+    /// Links two resting orders, returning the id of the link.
+    ///
+    /// Returns an error if either order is already linked or the link can't be saved.
+    pub fn link(&self, first_order_id: &str, second_order_id: &str) -> Result<String, KalshiError> {
+        let mut links = self.lock();
+        if first_order_id == second_order_id
+            || links.iter().any(|link| {
+                link.other_leg(first_order_id).is_some()
+                    || link.other_leg(second_order_id).is_some()
+            })
+        {
+            return Err(KalshiError::UserInputError(format!(
+                "Orders {} and {} can't be linked, an order can only be in one OCO link",
+                first_order_id, second_order_id
+            )));
+        }
+        let link = OcoLink {
+            id: uuid::Uuid::new_v4().to_string(),
+            first_order_id: first_order_id.to_string(),
+            second_order_id: second_order_id.to_string(),
+        };
+        links.push(link.clone());
+        if let Err(e) = self.save(&links) {
+            links.pop();
+            return Err(e);
+        }
+        Ok(link.id)
+    }
+
+    /// Removes a link without canceling either leg, returning it if it was found.
+    pub fn unlink(&self, id: &str) -> Option<OcoLink> {
+        let mut links = self.lock();
+        let index = links.iter().position(|link| link.id == id)?;
+        let link = links.remove(index);
+        if let Err(e) = self.save(&links) {
+            log::warn!("{}", e);
+        }
+        Some(link)
+    }
+
+    pub fn links(&self) -> Vec<OcoLink> {
+        self.lock().clone()
+    }
+
+    /// Removes the link `order_id` is a leg of.
+    fn take_link(&self, order_id: &str) -> Option<OcoLink> {
+        let id = self
+            .lock()
+            .iter()
+            .find(|link| link.other_leg(order_id).is_some())?
+            .id
+            .clone();
+        self.unlink(&id)
+    }
+
+    /// Cancels the other leg of the link a filled order belongs to, returning the link.
+    ///
+    /// Returns `Ok(None)` if the order isn't linked. The link is removed before canceling, so a
+    /// failed cancel isn't retried, the other leg may already be filled or canceled too.
+    pub async fn on_fill(
+        &self,
+        kalshi: &Kalshi,
+        order_id: &str,
+    ) -> Result<Option<OcoLink>, KalshiError> {
+        let Some(link) = self.take_link(order_id) else {
+            return Ok(None);
+        };
+        let other = link.other_leg(order_id).unwrap_or_default();
+        kalshi.cancel_order(other).await?;
+        Ok(Some(link))
+    }
+
+    /// Checks every leg through the REST api and resolves the links with a leg that was filled or canceled.
+    ///
+    /// A filled leg cancels the other one, a leg canceled without fills only removes the link.
+    /// Returns the links that were resolved. A link whose legs can't be read or whose cancel fails
+    /// is logged and kept for the next resume, the other links are still checked and the first
+    /// error is returned at the end.
+    pub async fn resume(&self, kalshi: &Kalshi) -> Result<Vec<OcoLink>, KalshiError> {
+        let mut resolved = Vec::new();
+        let mut first_error = None;
+        for link in self.links() {
+            match self.resume_link(kalshi, &link).await {
+                Ok(true) => resolved.push(link),
+                Ok(false) => {}
+                Err(e) => {
+                    log::warn!("Could not resume OCO link {}: {}", link.id, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(resolved),
+        }
+    }
+
+    /// Resolves one link, returns whether it was unlinked.
+    async fn resume_link(&self, kalshi: &Kalshi, link: &OcoLink) -> Result<bool, KalshiError> {
+        let first = kalshi.get_single_order(&link.first_order_id).await?;
+        let second = kalshi.get_single_order(&link.second_order_id).await?;
+        let (done, other) = match (is_filled(&first), is_filled(&second)) {
+            (true, _) => (&first, &second),
+            (_, true) => (&second, &first),
+            _ if first.status != OrderStatus::Resting => (&first, &second),
+            _ if second.status != OrderStatus::Resting => (&second, &first),
+            _ => return Ok(false),
+        };
+        if is_filled(done) && other.status == OrderStatus::Resting {
+            kalshi.cancel_order(&other.order_id).await?;
+        }
+        self.unlink(&link.id);
+        Ok(true)
+    }
+
+    /// Cancels the other leg of every fill delivered by `ws_client` from now on, until the client shuts down.
+    ///
+    /// Subscribes to the `fill` channel first. Messages missed because the manager fell behind are
+    /// caught up on with [`OcoManager::resume`].
+    pub async fn follow_fills(
+        &self,
+        kalshi: Kalshi,
+        ws_client: &mut KalshiWebsocketClient,
+    ) -> Result<JoinHandle<()>, KalshiError> {
+        let receiver = Arc::new(tokio::sync::Mutex::new(ws_client.receiver()));
+        ws_client
+            .ensure_subscribed(vec![crate::KalshiChannel::Fill], vec![])
+            .await
+            .map_err(|e| KalshiError::InternalError(e.to_string()))?;
+
+        let oco = self.clone();
+        let shutdown = ws_client.kalshi().shutdown_handle().clone();
+        Ok(
+            shutdown.supervise("oco fills", RestartPolicy::default(), move || {
+                let oco = oco.clone();
+                let kalshi = kalshi.clone();
+                let receiver = Arc::clone(&receiver);
+                async move {
+                    // Restarts pick up where the panicked run stopped
+                    let mut receiver = receiver.lock_owned().await;
+                    loop {
+                        match receiver.recv().await {
+                            Ok(Ok(KalshiWebsocketResponse::Fill { msg, .. })) => {
+                                if let Err(e) = oco.on_fill(&kalshi, &msg.order_id).await {
+                                    log::warn!(
+                                        "Could not cancel the OCO leg of {}: {}",
+                                        msg.order_id,
+                                        e
+                                    );
+                                }
+                            }
+                            Ok(_) => {}
+                            Err(RecvError::Lagged(skipped)) => {
+                                log::warn!("OCO manager lagged, skipped {} messages", skipped);
+                                if let Err(e) = oco.resume(&kalshi).await {
+                                    log::warn!("Could not resume OCO links: {}", e);
+                                }
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            }),
+        )
+    }
+}
+
+fn is_filled(order: &Order) -> bool {
+    order.status == OrderStatus::Executed
+        || order.taker_fill_count.unwrap_or_default() + order.maker_fill_count.unwrap_or_default()
+            > 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use reqwest::Method;
+
+    #[test]
+    fn test_links_persist() {
+        let path = std::env::temp_dir().join(format!("oco-{}.json", uuid::Uuid::new_v4()));
+        let oco = OcoManager::with_store(&path).unwrap();
+        let id = oco.link("order-a", "order-b").unwrap();
+        assert!(oco.link("order-b", "order-c").is_err());
+
+        let restarted = OcoManager::with_store(&path).unwrap();
+        assert_eq!(restarted.links(), oco.links());
+        assert_eq!(restarted.take_link("order-b").unwrap().id, id);
+
+        assert!(OcoManager::with_store(&path).unwrap().links().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resume_continues_after_failed_link() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let mut filled = fixtures::order("filled", 64, 0);
+        filled["status"] = serde_json::json!("executed");
+        filled["taker_fill_count"] = serde_json::json!(10);
+        server.respond(
+            Method::GET,
+            "/portfolio/orders/filled",
+            200,
+            serde_json::json!({ "order": filled }),
+        );
+        server.respond(
+            Method::GET,
+            "/portfolio/orders/resting",
+            200,
+            serde_json::json!({ "order": fixtures::order("resting", 30, 10) }),
+        );
+        server.respond(
+            Method::DELETE,
+            "/portfolio/orders/resting",
+            200,
+            fixtures::canceled_order(),
+        );
+        let kalshi = server.kalshi().await.unwrap();
+
+        let oco = OcoManager::default();
+        let broken = oco.link("missing", "other").unwrap();
+        let id = oco.link("filled", "resting").unwrap();
+
+        assert!(oco.resume(&kalshi).await.is_err());
+        assert_eq!(
+            server
+                .requests_to(Method::DELETE, "/portfolio/orders/resting")
+                .len(),
+            1
+        );
+        let links = oco.links();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].id, broken);
+        assert_ne!(links[0].id, id);
+    }
+}
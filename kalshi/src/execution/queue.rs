@@ -0,0 +1,359 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::MissedTickBehavior,
+};
+
+use crate::{CanceledOrder, Kalshi, KalshiError, Order, OrderCreationField};
+
+type Responder<T> = oneshot::Sender<Result<T, KalshiError>>;
+
+enum Request {
+    Cancel {
+        order_id: String,
+        /// Everyone who asked for this cancel while it was queued
+        responders: Vec<Responder<CanceledOrder>>,
+    },
+    Decrease {
+        order_id: String,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+        responder: Responder<Order>,
+    },
+    Place {
+        order: Box<OrderCreationField>,
+        responder: Responder<Order>,
+    },
+}
+
+impl std::fmt::Debug for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Request::Cancel { order_id, .. } => write!(f, "Cancel({})", order_id),
+            Request::Decrease { order_id, .. } => write!(f, "Decrease({})", order_id),
+            Request::Place { order, .. } => write!(f, "Place({})", order.ticker),
+        }
+    }
+}
+
+/// Requests waiting for their turn, highest priority first: cancels, decreases, then new orders.
+#[derive(Default)]
+struct Pending {
+    cancels: VecDeque<Request>,
+    decreases: VecDeque<Request>,
+    places: VecDeque<Request>,
+}
+
+impl Pending {
+    /// Queues a request, returning how many requests it was merged into (one for a duplicate cancel).
+    fn push(&mut self, request: Request) -> usize {
+        match request {
+            Request::Cancel {
+                order_id,
+                mut responders,
+            } => {
+                let queued = self.cancels.iter_mut().find_map(|queued| match queued {
+                    Request::Cancel {
+                        order_id: queued_id,
+                        responders,
+                    } if *queued_id == order_id => Some(responders),
+                    _ => None,
+                });
+                match queued {
+                    Some(queued) => {
+                        queued.append(&mut responders);
+                        1
+                    }
+                    None => {
+                        self.cancels.push_back(Request::Cancel {
+                            order_id,
+                            responders,
+                        });
+                        0
+                    }
+                }
+            }
+            Request::Decrease { .. } => {
+                self.decreases.push_back(request);
+                0
+            }
+            Request::Place { .. } => {
+                self.places.push_back(request);
+                0
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<Request> {
+        self.cancels
+            .pop_front()
+            .or_else(|| self.decreases.pop_front())
+            .or_else(|| self.places.pop_front())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.cancels.is_empty() && self.decreases.is_empty() && self.places.is_empty()
+    }
+}
+
+/// Sends orders, decreases and cancels one at a time, under a rate limit, most urgent first.
+///
+/// A burst of new orders sent straight through [`Kalshi::create_order`] can exhaust the
+/// account's write rate limit just when a cancel has to go out. The queue paces every
+/// transactional request to `writes_per_second` and always sends queued cancels first, then
+/// decreases, then new orders. Cancels of an order already waiting to be canceled are merged
+/// into the queued one, every caller gets its result.
+///
+/// The queue is a cheap handle, clones submit to the same queue. The worker stops once every
+/// handle is dropped and the queue is drained.
+///
+/// ```
+/// let queue = SubmissionQueue::new(kalshi_instance.clone(), 10);
+/// let order = queue.place(order_fields).await?;
+///
+/// // Jumps ahead of any order still waiting
+/// let (canceled, reduced_by) = queue.cancel(&order.order_id).await?;
+/// println!("{} requests waiting", queue.depth());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubmissionQueue {
+    sender: mpsc::UnboundedSender<Request>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl SubmissionQueue {
+    /// Starts a queue sending at most `writes_per_second` requests through `kalshi`.
+    pub fn new(kalshi: Kalshi, writes_per_second: u32) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let depth = Arc::new(AtomicUsize::new(0));
+        let interval = Duration::from_secs(1) / writes_per_second.max(1);
+        let shutdown = kalshi.shutdown_handle().clone();
+        shutdown.spawn(
+            "submission queue",
+            run_queue(kalshi, receiver, depth.clone(), interval),
+        );
+        SubmissionQueue { sender, depth }
+    }
+
+    /// Requests waiting to be sent, merged cancels count once.
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+
+    async fn submit<T>(
+        &self,
+        request: Request,
+        receiver: oneshot::Receiver<Result<T, KalshiError>>,
+    ) -> Result<T, KalshiError> {
+        self.depth.fetch_add(1, Ordering::SeqCst);
+        if self.sender.send(request).is_err() {
+            self.depth.fetch_sub(1, Ordering::SeqCst);
+        }
+        receiver.await.unwrap_or_else(|_| {
+            Err(KalshiError::InternalError(
+                "Submission queue stopped before sending the request".to_string(),
+            ))
+        })
+    }
+
+    /// Queues a cancel, sent before any queued decrease or new order, see [`Kalshi::cancel_order`].
+    pub async fn cancel(&self, order_id: &str) -> Result<CanceledOrder, KalshiError> {
+        let (responder, receiver) = oneshot::channel();
+        let request = Request::Cancel {
+            order_id: order_id.to_string(),
+            responders: vec![responder],
+        };
+        self.submit(request, receiver).await
+    }
+
+    /// Queues a decrease, sent before any queued new order, see [`Kalshi::decrease_order`].
+    pub async fn decrease(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> Result<Order, KalshiError> {
+        let (responder, receiver) = oneshot::channel();
+        let request = Request::Decrease {
+            order_id: order_id.to_string(),
+            reduce_by,
+            reduce_to,
+            responder,
+        };
+        self.submit(request, receiver).await
+    }
+
+    /// Queues a new order, see [`Kalshi::create_order`].
+    pub async fn place(&self, order: OrderCreationField) -> Result<Order, KalshiError> {
+        let (responder, receiver) = oneshot::channel();
+        let request = Request::Place {
+            order: Box::new(order),
+            responder,
+        };
+        self.submit(request, receiver).await
+    }
+}
+
+async fn run_queue(
+    kalshi: Kalshi,
+    mut receiver: mpsc::UnboundedReceiver<Request>,
+    depth: Arc<AtomicUsize>,
+    interval: Duration,
+) {
+    let mut pending = Pending::default();
+    let mut pace = tokio::time::interval(interval);
+    pace.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        if pending.is_empty() {
+            match receiver.recv().await {
+                Some(request) => {
+                    let merged = pending.push(request);
+                    depth.fetch_sub(merged, Ordering::SeqCst);
+                }
+                None => return,
+            }
+        }
+        // Requests arriving while waiting for the rate limit can still jump the line
+        pace.tick().await;
+        while let Ok(request) = receiver.try_recv() {
+            let merged = pending.push(request);
+            depth.fetch_sub(merged, Ordering::SeqCst);
+        }
+        if let Some(request) = pending.pop() {
+            depth.fetch_sub(1, Ordering::SeqCst);
+            send(&kalshi, request).await;
+        }
+    }
+}
+
+async fn send(kalshi: &Kalshi, request: Request) {
+    match request {
+        Request::Cancel {
+            order_id,
+            responders,
+        } => {
+            let result = kalshi.cancel_order(&order_id).await;
+            let mut responders = responders.into_iter();
+            let first = responders.next();
+            for responder in responders {
+                let _ = responder.send(match &result {
+                    Ok(canceled) => Ok(canceled.clone()),
+                    Err(e) => Err(duplicate_error(e)),
+                });
+            }
+            if let Some(first) = first {
+                let _ = first.send(result);
+            }
+        }
+        Request::Decrease {
+            order_id,
+            reduce_by,
+            reduce_to,
+            responder,
+        } => {
+            let _ = responder.send(kalshi.decrease_order(&order_id, reduce_by, reduce_to).await);
+        }
+        Request::Place { order, responder } => {
+            let order = *order;
+            let result = kalshi
+                .create_order(
+                    order.action,
+                    order.client_order_id,
+                    order.count,
+                    order.side,
+                    order.ticker,
+                    order.input_type,
+                    order.buy_max_cost,
+                    order.expiration_ts,
+                    order.no_price,
+                    order.sell_position_floor,
+                    order.yes_price,
+                )
+                .await;
+            let _ = responder.send(result);
+        }
+    }
+}
+
+/// A copy of an error for callers sharing a merged request, request errors can't be cloned.
+fn duplicate_error(error: &KalshiError) -> KalshiError {
+    match error {
+        KalshiError::UserInputError(e) => KalshiError::UserInputError(e.clone()),
+        KalshiError::InternalError(e) => KalshiError::InternalError(e.clone()),
+        KalshiError::TradingError(e) => KalshiError::TradingError(e.clone()),
+        KalshiError::RequestError(e) => KalshiError::InternalError(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Action, OrderType, Side};
+
+    fn place(ticker: &str) -> Request {
+        Request::Place {
+            order: Box::new(OrderCreationField {
+                action: Action::Buy,
+                client_order_id: None,
+                count: 1,
+                side: Side::Yes,
+                ticker: ticker.to_string(),
+                input_type: OrderType::Limit,
+                buy_max_cost: None,
+                expiration_ts: None,
+                no_price: None,
+                sell_position_floor: None,
+                yes_price: Some(40),
+            }),
+            responder: oneshot::channel().0,
+        }
+    }
+
+    fn cancel(order_id: &str) -> Request {
+        Request::Cancel {
+            order_id: order_id.to_string(),
+            responders: vec![oneshot::channel().0],
+        }
+    }
+
+    #[test]
+    fn test_cancels_first_and_merged() {
+        let mut pending = Pending::default();
+        assert_eq!(pending.push(place("A")), 0);
+        assert_eq!(pending.push(cancel("x")), 0);
+        assert_eq!(pending.push(place("B")), 0);
+        assert_eq!(pending.push(cancel("x")), 1);
+        assert_eq!(pending.push(cancel("y")), 0);
+
+        let order: Vec<String> = std::iter::from_fn(|| pending.pop())
+            .map(|r| format!("{:?}", r))
+            .collect();
+        assert_eq!(order, ["Cancel(x)", "Cancel(y)", "Place(A)", "Place(B)"]);
+    }
+
+    #[tokio::test]
+    async fn test_queue_sends_through_kalshi() {
+        let mut kalshi = Kalshi::new(crate::TradingEnvironment::DemoMode);
+        kalshi.set_dry_run(true);
+        let queue = SubmissionQueue::new(kalshi, 100);
+        let Request::Place { order, .. } = place("KXHIGHNY-25OCT02-B80.5") else {
+            unreachable!()
+        };
+        let placed = queue.place(*order).await.unwrap();
+        let (first, second) = tokio::join!(
+            queue.cancel(&placed.order_id),
+            queue.cancel(&placed.order_id)
+        );
+        assert_eq!(first.unwrap().reduced_by, 1);
+        assert_eq!(second.unwrap().reduced_by, 1);
+        assert_eq!(queue.depth(), 0);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{Action, Side};
+
+/// The outcome of an execution algorithm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionReport {
+    pub ticker: String,
+    pub action: Action,
+    pub side: Side,
+    /// Contracts the algorithm was asked to trade.
+    pub requested: i32,
+    /// Contracts actually traded.
+    pub filled: i32,
+    /// What the fills cost (buys) or brought in (sells), in cents on the order's side.
+    pub notional: i64,
+    /// Price on the order's side when the algorithm started, in cents.
+    pub arrival_price: Option<i64>,
+    /// Ids of every order the algorithm placed.
+    pub child_orders: Vec<String>,
+}
+
+impl ExecutionReport {
+    pub(crate) fn new(ticker: &str, action: Action, side: Side, requested: i32) -> Self {
+        ExecutionReport {
+            ticker: ticker.to_string(),
+            action,
+            side,
+            requested,
+            filled: 0,
+            notional: 0,
+            arrival_price: None,
+            child_orders: Vec::new(),
+        }
+    }
+
+    /// Whether every requested contract was traded.
+    pub fn is_complete(&self) -> bool {
+        self.filled >= self.requested
+    }
+
+    /// Average fill price in cents on the order's side.
+    pub fn average_price(&self) -> Option<f64> {
+        (self.filled > 0).then(|| self.notional as f64 / self.filled as f64)
+    }
+
+    /// How much worse than the arrival price the fills were on average, in cents per contract.
+    ///
+    /// Positive when buys paid more or sells received less than the arrival price.
+    pub fn slippage(&self) -> Option<f64> {
+        let average = self.average_price()?;
+        let arrival = self.arrival_price? as f64;
+        Some(match self.action {
+            Action::Buy => average - arrival,
+            Action::Sell => arrival - average,
+        })
+    }
+
+    /// Only the websocket executors see fills
+    #[cfg_attr(not(feature = "websockets"), allow(dead_code))]
+    pub(crate) fn record_fill(&mut self, count: i32, price: i64) {
+        self.filled += count;
+        self.notional += count as i64 * price;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_slippage_from_fills() {
+        let mut report = ExecutionReport::new("KXHIGHNY-25OCT02-B80.5", Action::Buy, Side::Yes, 30);
+        report.arrival_price = Some(40);
+        report.record_fill(10, 40);
+        report.record_fill(10, 43);
+        assert!(!report.is_complete());
+        assert_eq!(report.average_price(), Some(41.5));
+        assert_eq!(report.slippage(), Some(1.5));
+
+        report.action = Action::Sell;
+        assert_eq!(report.slippage(), Some(-1.5));
+    }
+}
@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::{Kalshi, KalshiError, Order, OrderCreationField, RequestError};
+
+/// Orders created this long before the first attempt are still searched, for clocks that drift.
+const LOOKUP_MARGIN_SECS: i64 = 60;
+/// How long to wait before submitting the order again.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Orders fetched per page while looking an order up.
+const LOOKUP_PAGE_SIZE: i32 = 200;
+
+/// Whether the exchange answered and refused the order, so it certainly wasn't placed.
+fn is_rejection(error: &KalshiError) -> bool {
+    matches!(
+        error,
+        KalshiError::UserInputError(_)
+            | KalshiError::TradingError(_)
+            | KalshiError::RequestError(RequestError::ClientError(_))
+    )
+}
+
+impl Kalshi {
+    /// Finds the order of a market placed with `client_order_id`, among the orders created at or
+    /// after `min_ts` in seconds.
+    pub async fn get_order_by_client_id(
+        &self,
+        ticker: &str,
+        client_order_id: &str,
+        min_ts: Option<i64>,
+    ) -> Result<Option<Order>, KalshiError> {
+        let mut cursor = None;
+        loop {
+            let (next, orders) = self
+                .get_multiple_orders(
+                    Some(ticker.to_string()),
+                    None,
+                    min_ts,
+                    None,
+                    None,
+                    Some(LOOKUP_PAGE_SIZE),
+                    cursor,
+                )
+                .await?;
+            if let Some(order) = orders
+                .iter()
+                .find(|order| order.client_order_id == client_order_id)
+            {
+                return Ok(Some(order.clone()));
+            }
+            match next {
+                Some(next) if !next.is_empty() && !orders.is_empty() => cursor = Some(next),
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    /// Places an order at most once, retrying up to `attempts` times when the outcome of a
+    /// submission is unknown.
+    ///
+    /// A timeout or a server error doesn't mean the order wasn't placed: the exchange may have
+    /// accepted it before the response was lost. Retrying blindly then doubles the position. After
+    /// such a failure the order is looked up by its client order id and returned if it was placed,
+    /// otherwise it's submitted again with the same client order id, which the exchange refuses to
+    /// place twice. A rejection of the first attempt (an invalid order, insufficient balance) is
+    /// returned right away.
+    ///
+    /// The order gets a random client order id when it has none.
+    ///
+    /// # Example
+    /// ```
+    /// let order = kalshi_instance.create_order_once(OrderCreationField {
+    ///     action: Action::Buy,
+    ///     client_order_id: Some("hedge-42".to_string()),
+    ///     count: 10,
+    ///     side: Side::Yes,
+    ///     ticker: "KXHIGHNY-25OCT02-B80.5".to_string(),
+    ///     input_type: OrderType::Limit,
+    ///     buy_max_cost: None,
+    ///     expiration_ts: None,
+    ///     no_price: None,
+    ///     sell_position_floor: None,
+    ///     yes_price: Some(45),
+    /// }, 3).await?;
+    /// ```
+    pub async fn create_order_once(
+        &self,
+        order: OrderCreationField,
+        attempts: u32,
+    ) -> Result<Order, KalshiError> {
+        let client_order_id = order
+            .client_order_id
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let since = chrono::Utc::now().timestamp() - LOOKUP_MARGIN_SECS;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let error = match self
+                .create_order(
+                    order.action,
+                    Some(client_order_id.clone()),
+                    order.count,
+                    order.side,
+                    order.ticker.clone(),
+                    order.input_type,
+                    order.buy_max_cost,
+                    order.expiration_ts,
+                    order.no_price,
+                    order.sell_position_floor,
+                    order.yes_price,
+                )
+                .await
+            {
+                Ok(order) => return Ok(order),
+                Err(e) => e,
+            };
+            if attempt == 1 && is_rejection(&error) {
+                return Err(error);
+            }
+
+            // A rejection after an unknown outcome can be the exchange refusing the duplicate id
+            match self
+                .get_order_by_client_id(&order.ticker, &client_order_id, Some(since))
+                .await
+            {
+                Ok(Some(placed)) => {
+                    log::info!(
+                        "Order {} was placed despite failing with: {}",
+                        client_order_id,
+                        error
+                    );
+                    if let Some(tracker) = &self.order_tracker {
+                        tracker.on_order_created(&placed);
+                    }
+                    return Ok(placed);
+                }
+                Ok(None) if is_rejection(&error) => return Err(error),
+                Ok(None) => {}
+                // Submitting again is still safe, the exchange refuses a duplicate id
+                Err(e) => log::warn!("Could not look order {} up: {}", client_order_id, e),
+            }
+            if attempt >= attempts {
+                return Err(error);
+            }
+            log::warn!(
+                "Submitting order {} again after: {}",
+                client_order_id,
+                error
+            );
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use crate::{Action, OrderType, Side};
+    use reqwest::Method;
+    use serde_json::json;
+
+    fn order() -> OrderCreationField {
+        OrderCreationField {
+            action: Action::Buy,
+            client_order_id: Some("once".to_string()),
+            count: 10,
+            side: Side::Yes,
+            ticker: fixtures::MARKET_TICKER.to_string(),
+            input_type: OrderType::Limit,
+            buy_max_cost: None,
+            expiration_ts: None,
+            no_price: None,
+            sell_position_floor: None,
+            yes_price: Some(45),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_error_finds_placed_order() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        server.respond(Method::POST, "/portfolio/orders", 503, json!({}));
+        let mut placed = fixtures::order("placed", 45, 10);
+        placed["client_order_id"] = "once".into();
+        server.respond(
+            Method::GET,
+            "/portfolio/orders",
+            200,
+            json!({"orders": [fixtures::order("other", 45, 10), placed], "cursor": ""}),
+        );
+        let kalshi = server.kalshi().await.unwrap();
+
+        let order = kalshi.create_order_once(order(), 3).await.unwrap();
+        assert_eq!(order.order_id, "placed");
+        assert_eq!(
+            server.requests_to(Method::POST, "/portfolio/orders").len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retries_with_same_client_order_id() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        server.respond(Method::POST, "/portfolio/orders", 500, json!({}));
+        server.respond(
+            Method::GET,
+            "/portfolio/orders",
+            200,
+            json!({"orders": [], "cursor": ""}),
+        );
+        let kalshi = server.kalshi().await.unwrap();
+
+        assert!(kalshi.create_order_once(order(), 2).await.is_err());
+        let submissions = server.requests_to(Method::POST, "/portfolio/orders");
+        assert_eq!(submissions.len(), 2);
+        assert!(submissions
+            .iter()
+            .all(|request| request.body.as_ref().unwrap()["client_order_id"] == "once"));
+
+        // A rejection isn't retried
+        server.respond(Method::POST, "/portfolio/orders", 400, json!({}));
+        assert!(kalshi.create_order_once(order(), 2).await.is_err());
+        assert_eq!(
+            server.requests_to(Method::POST, "/portfolio/orders").len(),
+            3
+        );
+    }
+}
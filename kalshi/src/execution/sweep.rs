@@ -0,0 +1,194 @@
+use super::ExecutionReport;
+use crate::{Action, Book, Kalshi, KalshiError, OrderCreationField, OrderStatus, OrderType, Side};
+
+/// Takes liquidity level by level, one limit order per price level of the book.
+///
+/// Instead of sending a single order priced at the worst level it's willing to reach, the sweep
+/// reads the book and places one child order per level, each priced at that level and sized to
+/// what rests there. No contract trades worse than the level it was planned against, and the
+/// sweep stops at `limit_price` or once `max_cost` would be exceeded.
+///
+/// Children are sent together with [`Kalshi::batch_create_order`]. Whatever didn't fill because
+/// the book moved in the meantime is canceled, a sweep never leaves orders resting.
+///
+/// ```
+/// let report = LiquiditySweep::new("KXHIGHNY-25OCT02-B80.5", Action::Buy, Side::Yes, 200, 48)
+///     .max_cost(8_000)
+///     .run(&kalshi_instance)
+///     .await?;
+/// println!("filled {} at {:?}", report.filled, report.average_price());
+/// ```
+#[derive(Debug, Clone)]
+pub struct LiquiditySweep {
+    ticker: String,
+    action: Action,
+    side: Side,
+    count: i32,
+    limit_price: i64,
+    max_cost: Option<i64>,
+}
+
+impl LiquiditySweep {
+    /// Trades up to `count` contracts at `limit_price` cents on `side` or better.
+    pub fn new(ticker: &str, action: Action, side: Side, count: i32, limit_price: i64) -> Self {
+        LiquiditySweep {
+            ticker: ticker.to_string(),
+            action,
+            side,
+            count,
+            limit_price,
+            max_cost: None,
+        }
+    }
+
+    /// Caps what the buys may cost in total, in cents. Ignored for sells.
+    pub fn max_cost(mut self, max_cost: i64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// The child orders the sweep would place against `book`, best price first.
+    pub fn plan(&self, book: &Book) -> Vec<OrderCreationField> {
+        let opposite = match self.side {
+            Side::Yes => Side::No,
+            Side::No => Side::Yes,
+        };
+        // Buys take the opposite side's bids, sells take their own side's bids
+        let levels: Vec<(i64, i64)> = match self.action {
+            Action::Buy => {
+                let bids = match opposite {
+                    Side::Yes => &book.yes,
+                    Side::No => &book.no,
+                };
+                bids.iter()
+                    .rev()
+                    .map(|(bid, qty)| (100 - *bid as i64, *qty))
+                    .take_while(|(price, _)| *price <= self.limit_price)
+                    .collect()
+            }
+            Action::Sell => {
+                let bids = match self.side {
+                    Side::Yes => &book.yes,
+                    Side::No => &book.no,
+                };
+                bids.iter()
+                    .rev()
+                    .map(|(bid, qty)| (*bid as i64, *qty))
+                    .take_while(|(price, _)| *price >= self.limit_price)
+                    .collect()
+            }
+        };
+
+        let mut remaining = self.count as i64;
+        let mut budget = self.max_cost.filter(|_| self.action == Action::Buy);
+        let mut children = Vec::new();
+        for (price, available) in levels {
+            let mut count = remaining.min(available);
+            if let Some(budget) = budget.as_mut() {
+                count = count.min(*budget / price.max(1));
+                *budget -= count * price;
+            }
+            if count <= 0 {
+                break;
+            }
+            remaining -= count;
+            let (yes_price, no_price) = match self.side {
+                Side::Yes => (Some(price), None),
+                Side::No => (None, Some(price)),
+            };
+            children.push(OrderCreationField {
+                action: self.action,
+                client_order_id: None,
+                count: count as i32,
+                side: self.side,
+                ticker: self.ticker.clone(),
+                input_type: OrderType::Limit,
+                buy_max_cost: None,
+                expiration_ts: None,
+                no_price,
+                sell_position_floor: None,
+                yes_price,
+            });
+        }
+        children
+    }
+
+    /// Reads the book, places the planned children and cancels what didn't fill.
+    ///
+    /// Children that fail to place are logged and left out of the report, the error is only
+    /// returned when the book can't be read or no child could be placed.
+    pub async fn run(&self, kalshi: &Kalshi) -> Result<ExecutionReport, KalshiError> {
+        if self.count <= 0 || !(1..=99).contains(&self.limit_price) {
+            return Err(KalshiError::UserInputError(format!(
+                "Invalid sweep of {} contracts at {} cents",
+                self.count, self.limit_price
+            )));
+        }
+
+        let orderbook = kalshi.get_market_orderbook(&self.ticker, None).await?;
+        let book = Book::from_orderbook(&self.ticker, &orderbook);
+        let mut report = ExecutionReport::new(&self.ticker, self.action, self.side, self.count);
+        report.arrival_price = match (self.action, self.side) {
+            (Action::Buy, Side::Yes) => book.best_yes_ask(),
+            (Action::Buy, Side::No) => book.best_no_ask(),
+            (Action::Sell, Side::Yes) => book.best_yes_bid(),
+            (Action::Sell, Side::No) => book.best_no_bid(),
+        }
+        .map(|(price, _)| price as i64);
+
+        let children = self.plan(&book);
+        if children.is_empty() {
+            return Ok(report);
+        }
+
+        let mut first_error = None;
+        for result in kalshi.batch_create_order(children).await? {
+            let order = match result {
+                Ok(order) => order,
+                Err(e) => {
+                    log::warn!("Sweep child order on {} failed: {}", self.ticker, e);
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            if order.status == OrderStatus::Resting {
+                if let Err(e) = kalshi.cancel_order(&order.order_id).await {
+                    log::warn!("Could not cancel sweep remainder {}: {}", order.order_id, e);
+                }
+            }
+            report.filled += order.taker_fill_count.unwrap_or_default();
+            report.notional += order.taker_fill_cost.unwrap_or_default() as i64;
+            report.child_orders.push(order.order_id);
+        }
+
+        match first_error {
+            Some(e) if report.child_orders.is_empty() => Err(e),
+            _ => Ok(report),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plan_walks_levels_within_limits() {
+        let mut book = Book::new("KXHIGHNY-25OCT02-B80.5");
+        // Yes asks of 10 at 45, 20 at 47 and 50 at 50
+        book.apply_change(Side::No, 55, 10);
+        book.apply_change(Side::No, 53, 20);
+        book.apply_change(Side::No, 50, 50);
+
+        let sweep = LiquiditySweep::new("KXHIGHNY-25OCT02-B80.5", Action::Buy, Side::Yes, 100, 48);
+        let children = sweep.plan(&book);
+        let levels: Vec<(Option<i64>, i32)> =
+            children.iter().map(|c| (c.yes_price, c.count)).collect();
+        assert_eq!(levels, vec![(Some(45), 10), (Some(47), 20)]);
+
+        let capped = sweep.max_cost(1_000).plan(&book);
+        let levels: Vec<(Option<i64>, i32)> =
+            capped.iter().map(|c| (c.yes_price, c.count)).collect();
+        assert_eq!(levels, vec![(Some(45), 10), (Some(47), 11)]);
+    }
+}
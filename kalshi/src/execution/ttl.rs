@@ -0,0 +1,333 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::{store, Kalshi, KalshiError, Order, OrderCreationField, OrderStatus, TradingErrorKind};
+
+/// Cancels attempted before giving up on an expired order until the next [`TtlScheduler::resume`].
+const CANCEL_ATTEMPTS: u32 = 5;
+const CANCEL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// An order to cancel once its time to live is over.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TtlIntent {
+    pub order_id: String,
+    /// When to cancel the order, in milliseconds since the unix epoch.
+    pub cancel_at_ms: i64,
+}
+
+#[derive(Debug, Default)]
+struct TtlState {
+    intents: Vec<TtlIntent>,
+    /// Orders with a task waiting to cancel them
+    armed: HashSet<String>,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// Cancels orders after a time to live measured to the millisecond.
+///
+/// Orders placed with [`TtlScheduler::submit_with_ttl`] are canceled once their time to live is
+/// over, through the REST api so a reconnecting websocket doesn't delay it. Failed cancels are
+/// retried until the order is canceled or no longer resting. The exchange's own expiration only
+/// has a granularity of seconds, it's still set to the second after the deadline as a backstop
+/// in case the bot dies before canceling.
+///
+/// With a store file the pending cancels are saved on every change, so a restarted bot picks them
+/// up again with [`TtlScheduler::with_store`] and [`TtlScheduler::resume`], canceling right away
+/// the orders whose time to live ran out while it was down.
+///
+/// The scheduler is a cheap handle, clones share the same cancels.
+///
+/// ```
+/// let ttl = TtlScheduler::with_store(kalshi_instance.clone(), "ttl_orders.json")?;
+/// ttl.resume();
+/// let order = ttl.submit_with_ttl(quote, Duration::from_millis(1500)).await?;
+/// ```
+#[derive(Clone)]
+pub struct TtlScheduler {
+    kalshi: Kalshi,
+    state: Arc<Mutex<TtlState>>,
+    /// Wakes the waiting tasks when a deadline changes
+    rescheduled: Arc<Notify>,
+    store: Option<PathBuf>,
+}
+
+impl TtlScheduler {
+    /// Creates a scheduler that only keeps its pending cancels in memory.
+    pub fn new(kalshi: Kalshi) -> Self {
+        TtlScheduler {
+            kalshi,
+            state: Arc::new(Mutex::new(TtlState::default())),
+            rescheduled: Arc::new(Notify::new()),
+            store: None,
+        }
+    }
+
+    /// Creates a scheduler saving its pending cancels to `path`, loading the ones already saved
+    /// there. Loaded cancels only run once [`TtlScheduler::resume`] is called.
+    pub fn with_store(kalshi: Kalshi, path: impl AsRef<Path>) -> Result<Self, KalshiError> {
+        let path = path.as_ref().to_path_buf();
+        let intents = store::load_json(&path, "TTL store")?;
+        Ok(TtlScheduler {
+            kalshi,
+            state: Arc::new(Mutex::new(TtlState {
+                intents,
+                armed: HashSet::new(),
+            })),
+            rescheduled: Arc::new(Notify::new()),
+            store: Some(path),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TtlState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    fn save(&self, intents: &[TtlIntent]) -> Result<(), KalshiError> {
+        match &self.store {
+            Some(path) => store::save_json(path, "TTL store", intents),
+            None => Ok(()),
+        }
+    }
+
+    /// Places an order and cancels it once `ttl` is over, see [`Kalshi::create_order`].
+    ///
+    /// Orders that don't rest on the book aren't scheduled. An error saving the cancel is only
+    /// logged, the cancel still runs and the exchange's expiration backs it up.
+    pub async fn submit_with_ttl(
+        &self,
+        order: OrderCreationField,
+        ttl: Duration,
+    ) -> Result<Order, KalshiError> {
+        let cancel_at_ms = now_ms() + ttl.as_millis() as i64;
+        // The exchange expires orders at the start of a second
+        let backstop = (cancel_at_ms + 999) / 1000 + 1;
+        let placed = self
+            .kalshi
+            .create_order(
+                order.action,
+                order.client_order_id,
+                order.count,
+                order.side,
+                order.ticker,
+                order.input_type,
+                order.buy_max_cost,
+                Some(order.expiration_ts.map_or(backstop, |ts| ts.min(backstop))),
+                order.no_price,
+                order.sell_position_floor,
+                order.yes_price,
+            )
+            .await?;
+        if placed.status == OrderStatus::Resting {
+            if let Err(e) = self.schedule(TtlIntent {
+                order_id: placed.order_id.clone(),
+                cancel_at_ms,
+            }) {
+                log::warn!("{}", e);
+            }
+        }
+        Ok(placed)
+    }
+
+    /// Cancels an order already placed once `ttl` is over, replacing any cancel already scheduled
+    /// for it.
+    ///
+    /// Returns an error if the cancel can't be saved, it's still scheduled in memory.
+    pub fn cancel_after(&self, order_id: &str, ttl: Duration) -> Result<(), KalshiError> {
+        self.schedule(TtlIntent {
+            order_id: order_id.to_string(),
+            cancel_at_ms: now_ms() + ttl.as_millis() as i64,
+        })
+    }
+
+    fn schedule(&self, intent: TtlIntent) -> Result<(), KalshiError> {
+        let saved = {
+            let mut state = self.lock();
+            state.intents.retain(|i| i.order_id != intent.order_id);
+            state.intents.push(intent.clone());
+            self.save(&state.intents)
+        };
+        self.rescheduled.notify_waiters();
+        self.arm(&intent.order_id);
+        saved
+    }
+
+    /// Forgets the cancel scheduled for an order, returning it if there was one.
+    ///
+    /// A cancel already waiting for its deadline finds nothing to do once it's over.
+    pub fn forget(&self, order_id: &str) -> Option<TtlIntent> {
+        let mut state = self.lock();
+        let index = state.intents.iter().position(|i| i.order_id == order_id)?;
+        let intent = state.intents.remove(index);
+        if let Err(e) = self.save(&state.intents) {
+            log::warn!("{}", e);
+        }
+        Some(intent)
+    }
+
+    /// The cancels waiting for their deadline or to be retried.
+    pub fn pending(&self) -> Vec<TtlIntent> {
+        self.lock().intents.clone()
+    }
+
+    /// Starts waiting for the deadline of every pending cancel that isn't already, such as the
+    /// ones loaded from the store or the ones that failed. Expired ones are canceled right away.
+    pub fn resume(&self) {
+        let order_ids: Vec<String> = self
+            .lock()
+            .intents
+            .iter()
+            .map(|i| i.order_id.clone())
+            .collect();
+        for order_id in order_ids {
+            self.arm(&order_id);
+        }
+    }
+
+    /// Spawns the task canceling an order at its deadline, unless one is already waiting.
+    fn arm(&self, order_id: &str) {
+        if !self.lock().armed.insert(order_id.to_string()) {
+            return;
+        }
+        let scheduler = self.clone();
+        let order_id = order_id.to_string();
+        let shutdown = self.kalshi.shutdown_handle();
+        shutdown.spawn("order ttl", async move {
+            loop {
+                // The deadline may have been moved while waiting
+                let cancel_at_ms = {
+                    let state = scheduler.lock();
+                    match state.intents.iter().find(|i| i.order_id == order_id) {
+                        Some(intent) => intent.cancel_at_ms,
+                        None => break,
+                    }
+                };
+                let wait = cancel_at_ms - now_ms();
+                if wait <= 0 {
+                    match scheduler.expire(&order_id).await {
+                        Ok(()) => {
+                            scheduler.forget(&order_id);
+                        }
+                        Err(e) => log::warn!("Could not cancel expired order {}: {}", order_id, e),
+                    }
+                    break;
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_millis(wait as u64)) => {}
+                    _ = scheduler.rescheduled.notified() => {}
+                }
+            }
+            scheduler.lock().armed.remove(&order_id);
+        });
+    }
+
+    /// Cancels an order, retrying until it's canceled or no longer resting.
+    async fn expire(&self, order_id: &str) -> Result<(), KalshiError> {
+        let mut attempts = 0;
+        loop {
+            let error = match self.kalshi.cancel_order(order_id).await {
+                Ok(_) => return Ok(()),
+                Err(e) if e.trading_error_kind() == Some(TradingErrorKind::OrderAlreadyClosed) => {
+                    return Ok(())
+                }
+                Err(e) => e,
+            };
+            // Filled or canceled in the meantime
+            if let Ok(order) = self.kalshi.get_single_order(&order_id.to_string()).await {
+                if order.status != OrderStatus::Resting {
+                    return Ok(());
+                }
+            }
+            attempts += 1;
+            if attempts >= CANCEL_ATTEMPTS {
+                return Err(error);
+            }
+            tokio::time::sleep(CANCEL_RETRY_DELAY).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use crate::{Action, OrderType, Side};
+    use reqwest::Method;
+
+    fn quote() -> OrderCreationField {
+        OrderCreationField {
+            action: Action::Buy,
+            client_order_id: None,
+            count: 10,
+            side: Side::Yes,
+            ticker: fixtures::MARKET_TICKER.to_string(),
+            input_type: OrderType::Limit,
+            buy_max_cost: None,
+            expiration_ts: None,
+            no_price: None,
+            sell_position_floor: None,
+            yes_price: Some(40),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order_canceled_after_ttl() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let ttl = TtlScheduler::new(kalshi);
+        let cancel = format!("/portfolio/orders/{}", fixtures::ORDER_ID);
+
+        let order = ttl
+            .submit_with_ttl(quote(), Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert_eq!(order.order_id, fixtures::ORDER_ID);
+        let placed = server.requests_to(Method::POST, "/portfolio/orders");
+        let expiration = placed[0].body.as_ref().unwrap()["expiration_ts"]
+            .as_i64()
+            .unwrap();
+        assert!(expiration * 1000 > now_ms());
+        assert_eq!(ttl.pending().len(), 1);
+        assert!(server.requests_to(Method::DELETE, &cancel).is_empty());
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(server.requests_to(Method::DELETE, &cancel).len(), 1);
+        assert!(ttl.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pending_cancels_persist() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let path = std::env::temp_dir().join(format!("ttl-{}.json", uuid::Uuid::new_v4()));
+        let cancel = format!("/portfolio/orders/{}", fixtures::ORDER_ID);
+
+        let ttl = TtlScheduler::with_store(kalshi.clone(), &path).unwrap();
+        ttl.cancel_after(fixtures::ORDER_ID, Duration::from_millis(50))
+            .unwrap();
+        // A restarted bot picks up the cancel, even past its deadline
+        let restarted = TtlScheduler::with_store(kalshi, &path).unwrap();
+        assert_eq!(restarted.pending(), ttl.pending());
+        ttl.forget(fixtures::ORDER_ID);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(server.requests_to(Method::DELETE, &cancel).is_empty());
+
+        restarted.resume();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(server.requests_to(Method::DELETE, &cancel).len(), 1);
+        assert!(restarted.pending().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
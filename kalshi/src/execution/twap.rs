@@ -0,0 +1,414 @@
+use std::{collections::HashSet, time::Duration};
+
+use tokio::{
+    sync::broadcast::error::{RecvError, TryRecvError},
+    time::Instant,
+};
+
+use super::ExecutionReport;
+use crate::{
+    websockets::{client::KalshiWebsocketClient, responses::KalshiWebsocketResponse},
+    Action, Kalshi, KalshiChannel, KalshiError, OrderType, Side,
+};
+
+/// Works an order evenly over a time window, one limit order per slice.
+///
+/// The window is split into `slices` equal intervals. At the start of each interval the unfilled
+/// part of the previous child order is canceled and a new child order is placed for whatever
+/// is needed to be back on schedule. Child orders are limit orders at `limit_price`, so they
+/// never trade worse than it.
+///
+/// With [`TwapExecutor::max_participation`] set, each child order is capped to a share of the
+/// contracts the market traded during the previous interval. The first slice isn't capped since
+/// no volume has been observed yet.
+///
+/// Fills are tracked from the websocket `fill` channel and traded volume from the `trade`
+/// channel, both are subscribed to when the execution starts.
+///
+/// ```
+/// let twap = TwapExecutor::new("KXHIGHNY-25OCT02-B80.5", Action::Buy, Side::Yes, 500, 45, Duration::from_secs(600))
+///     .slices(20)
+///     .max_participation(0.1);
+/// let report = twap.run(&kalshi_instance, &mut ws_client).await?;
+/// println!("filled {}/{} slippage {:?}", report.filled, report.requested, report.slippage());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TwapExecutor {
+    ticker: String,
+    action: Action,
+    side: Side,
+    count: i32,
+    limit_price: i64,
+    duration: Duration,
+    slices: u32,
+    max_participation: Option<f64>,
+}
+
+impl TwapExecutor {
+    /// Trades `count` contracts over `duration`, at `limit_price` cents on `side` or better.
+    pub fn new(
+        ticker: &str,
+        action: Action,
+        side: Side,
+        count: i32,
+        limit_price: i64,
+        duration: Duration,
+    ) -> Self {
+        TwapExecutor {
+            ticker: ticker.to_string(),
+            action,
+            side,
+            count,
+            limit_price,
+            duration,
+            slices: 10,
+            max_participation: None,
+        }
+    }
+
+    /// Number of child orders the window is split into, 10 by default.
+    pub fn slices(mut self, slices: u32) -> Self {
+        self.slices = slices;
+        self
+    }
+
+    /// Caps each child order to `share` (0.1 for 10%) of the volume traded during the previous slice.
+    pub fn max_participation(mut self, share: f64) -> Self {
+        self.max_participation = Some(share);
+        self
+    }
+
+    /// Runs the execution to the end of the window, or until every contract is filled.
+    ///
+    /// Returns an error if the parameters are invalid, a child order can't be placed or the
+    /// websocket client shuts down. Whatever child order is resting is canceled first.
+    pub async fn run(
+        &self,
+        kalshi: &Kalshi,
+        ws_client: &mut KalshiWebsocketClient,
+    ) -> Result<ExecutionReport, KalshiError> {
+        if self.count <= 0 || self.slices == 0 || !(1..=99).contains(&self.limit_price) {
+            return Err(KalshiError::UserInputError(format!(
+                "Invalid TWAP of {} contracts in {} slices at {} cents",
+                self.count, self.slices, self.limit_price
+            )));
+        }
+
+        // Subscribe before placing anything so no fill is missed
+        let mut receiver = ws_client.receiver();
+        let mut channels = vec![(KalshiChannel::Fill, vec![])];
+        if self.max_participation.is_some() {
+            channels.push((KalshiChannel::Trade, vec![self.ticker.clone()]));
+        }
+        for (channel, tickers) in channels {
+            ws_client
+                .ensure_subscribed(vec![channel], tickers)
+                .await
+                .map_err(|e| KalshiError::InternalError(e.to_string()))?;
+        }
+
+        let mut report = ExecutionReport::new(&self.ticker, self.action, self.side, self.count);
+        report.arrival_price = kalshi
+            .get_single_market(&self.ticker)
+            .await
+            .ok()
+            .map(|market| match (self.action, self.side) {
+                (Action::Buy, Side::Yes) => market.yes_ask,
+                (Action::Buy, Side::No) => market.no_ask,
+                (Action::Sell, Side::Yes) => market.yes_bid,
+                (Action::Sell, Side::No) => market.no_bid,
+            })
+            .filter(|price| *price > 0);
+
+        let run_id = uuid::Uuid::new_v4();
+        let mut client_order_ids = HashSet::new();
+        let mut resting: Option<String> = None;
+        let mut traded_volume = 0i64;
+        let interval = self.duration / self.slices;
+        let started = Instant::now();
+
+        for slice in 0..self.slices {
+            if let Some(order_id) = resting.take() {
+                cancel_child(kalshi, &order_id).await;
+            }
+            if report.is_complete() {
+                break;
+            }
+
+            let scheduled = (self.count as i64 * (slice as i64 + 1) / self.slices as i64) as i32;
+            let mut size = scheduled - report.filled;
+            if let (Some(share), true) = (self.max_participation, slice > 0) {
+                size = size.min((traded_volume as f64 * share).floor() as i32);
+            }
+            traded_volume = 0;
+
+            if size > 0 {
+                let client_order_id = format!("twap-{}-{}", run_id, slice);
+                client_order_ids.insert(client_order_id.clone());
+                let (yes_price, no_price) = match self.side {
+                    Side::Yes => (Some(self.limit_price), None),
+                    Side::No => (None, Some(self.limit_price)),
+                };
+                let order = kalshi
+                    .create_order(
+                        self.action,
+                        Some(client_order_id),
+                        size,
+                        self.side,
+                        self.ticker.clone(),
+                        OrderType::Limit,
+                        None,
+                        None,
+                        no_price,
+                        None,
+                        yes_price,
+                    )
+                    .await?;
+                report.child_orders.push(order.order_id.clone());
+                resting = Some(order.order_id);
+            }
+
+            let deadline = started + interval * (slice + 1);
+            while !report.is_complete() {
+                tokio::select! {
+                    _ = tokio::time::sleep_until(deadline) => break,
+                    msg = receiver.recv() => match msg {
+                        Ok(Ok(msg)) => self.on_message(&msg, &client_order_ids, &mut report, &mut traded_volume),
+                        Ok(Err(_)) => {}
+                        Err(RecvError::Lagged(skipped)) => {
+                            log::warn!("TWAP on {} lagged, skipped {} messages", self.ticker, skipped);
+                        }
+                        Err(RecvError::Closed) => {
+                            if let Some(order_id) = resting.take() {
+                                cancel_child(kalshi, &order_id).await;
+                            }
+                            return Err(KalshiError::InternalError(
+                                "Websocket client shut down during TWAP execution".to_string(),
+                            ));
+                        }
+                    },
+                }
+            }
+        }
+
+        if let Some(order_id) = resting.take() {
+            cancel_child(kalshi, &order_id).await;
+        }
+        // Fills that arrived while the last child was being canceled
+        loop {
+            match receiver.try_recv() {
+                Ok(Ok(msg)) => {
+                    self.on_message(&msg, &client_order_ids, &mut report, &mut traded_volume)
+                }
+                Ok(Err(_)) | Err(TryRecvError::Lagged(_)) => {}
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+            }
+        }
+        Ok(report)
+    }
+
+    fn on_message(
+        &self,
+        msg: &KalshiWebsocketResponse,
+        client_order_ids: &HashSet<String>,
+        report: &mut ExecutionReport,
+        traded_volume: &mut i64,
+    ) {
+        match msg {
+            KalshiWebsocketResponse::Fill { msg, .. }
+                if msg
+                    .client_order_id
+                    .as_ref()
+                    .is_some_and(|id| client_order_ids.contains(id))
+                    || report.child_orders.contains(&msg.order_id) =>
+            {
+                let price = match self.side {
+                    Side::Yes => msg.yes_price,
+                    Side::No => msg.no_price,
+                };
+                report.record_fill(msg.count as i32, price as i64);
+            }
+            KalshiWebsocketResponse::Trade { msg, .. } if msg.market_ticker == self.ticker => {
+                *traded_volume += msg.count as i64;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Cancels a child order, which may already be filled.
+async fn cancel_child(kalshi: &Kalshi, order_id: &str) {
+    if let Err(e) = kalshi.cancel_order(order_id).await {
+        log::debug!("Could not cancel child order {}: {}", order_id, e);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer, MockWsServer};
+    use reqwest::Method;
+    use serde_json::json;
+
+    fn fill(
+        order_id: &str,
+        client_order_id: &str,
+        count: u32,
+        yes_price: u32,
+    ) -> KalshiWebsocketResponse {
+        let msg = json!({
+            "type": "fill",
+            "sid": 1,
+            "msg": {
+                "trade_id": format!("{}-{}", order_id, count),
+                "order_id": order_id,
+                "market_ticker": fixtures::MARKET_TICKER,
+                "is_taker": false,
+                "side": "yes",
+                "yes_price": yes_price,
+                "no_price": 100 - yes_price,
+                "count": count,
+                "action": "buy",
+                "ts": 1758132400,
+                "client_order_id": client_order_id,
+                "post_position": count,
+                "purchased_side": "yes",
+            },
+        });
+        KalshiWebsocketResponse::from_text(&msg.to_string()).unwrap()
+    }
+
+    fn trade(market_ticker: &str, count: u32) -> KalshiWebsocketResponse {
+        let msg = json!({
+            "type": "trade",
+            "sid": 2,
+            "msg": {
+                "trade_id": format!("{}-{}", market_ticker, count),
+                "market_ticker": market_ticker,
+                "yes_price": 44,
+                "no_price": 56,
+                "count": count,
+                "taker_side": "yes",
+                "ts": 1758132400,
+            },
+        });
+        KalshiWebsocketResponse::from_text(&msg.to_string()).unwrap()
+    }
+
+    async fn servers() -> (MockHttpServer, MockWsServer, Kalshi, KalshiWebsocketClient) {
+        let http = MockHttpServer::with_fixtures().await.unwrap();
+        let mut market = fixtures::market();
+        market["yes_ask"] = 40.into();
+        http.respond(
+            Method::GET,
+            &format!("/markets/{}", fixtures::MARKET_TICKER),
+            200,
+            json!({ "market": market }),
+        );
+        let ws = MockWsServer::start().await.unwrap();
+        let mut kalshi = http.kalshi().await.unwrap();
+        kalshi.set_ws_url(&ws.url());
+        let ws_client = kalshi.connect_ws().await.unwrap();
+        (http, ws, kalshi, ws_client)
+    }
+
+    /// The child orders placed so far, once there are at least `count`.
+    async fn wait_for_children(http: &MockHttpServer, count: usize) -> Vec<serde_json::Value> {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let placed = http.requests_to(Method::POST, "/portfolio/orders");
+                if placed.len() >= count {
+                    return placed.into_iter().filter_map(|r| r.body).collect();
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_slices_filled_and_reported() {
+        let (http, ws, kalshi, mut ws_client) = servers().await;
+        let twap = TwapExecutor::new(
+            fixtures::MARKET_TICKER,
+            Action::Buy,
+            Side::Yes,
+            10,
+            45,
+            Duration::from_millis(600),
+        )
+        .slices(2);
+
+        let fills = async {
+            let children = wait_for_children(&http, 1).await;
+            let client_order_id = children[0]["client_order_id"].as_str().unwrap();
+            // Matched by client order id, the order id isn't known yet
+            ws.send(&fill("not-yet-known", client_order_id, 3, 42));
+            ws.send(&fill("someone-else", "other-client-order", 5, 42));
+            wait_for_children(&http, 2).await;
+            ws.send(&fill(fixtures::ORDER_ID, "", 7, 44));
+        };
+        let (report, _) = tokio::join!(twap.run(&kalshi, &mut ws_client), fills);
+        let report = report.unwrap();
+
+        let children = wait_for_children(&http, 2).await;
+        let sizes: Vec<_> = children.iter().map(|c| c["count"].clone()).collect();
+        assert_eq!(sizes, vec![json!(5), json!(7)]);
+        assert_eq!(children[0]["yes_price"], 45);
+        assert_eq!(report.filled, 10);
+        assert_eq!(report.notional, 3 * 42 + 7 * 44);
+        assert_eq!(report.arrival_price, Some(40));
+        assert!((report.slippage().unwrap() - 3.4).abs() < 1e-9);
+        // Each child is canceled when the next slice starts or the execution ends
+        assert_eq!(
+            http.requests_to(
+                Method::DELETE,
+                &format!("/portfolio/orders/{}", fixtures::ORDER_ID)
+            )
+            .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slices_capped_by_participation() {
+        let (http, ws, kalshi, mut ws_client) = servers().await;
+        let twap = TwapExecutor::new(
+            fixtures::MARKET_TICKER,
+            Action::Buy,
+            Side::Yes,
+            20,
+            45,
+            Duration::from_millis(400),
+        )
+        .slices(2)
+        .max_participation(0.5);
+
+        let trades = async {
+            wait_for_children(&http, 1).await;
+            ws.send(&trade(fixtures::MARKET_TICKER, 7));
+            ws.send(&trade("KXHIGHNY-25OCT02-B80.5", 100));
+        };
+        let (report, _) = tokio::join!(twap.run(&kalshi, &mut ws_client), trades);
+        let report = report.unwrap();
+
+        ws.assert_subscribed(&["fill"], &[]);
+        ws.assert_subscribed(&["trade"], &[fixtures::MARKET_TICKER]);
+        let children = wait_for_children(&http, 2).await;
+        let sizes: Vec<_> = children.iter().map(|c| c["count"].clone()).collect();
+        // The first slice isn't capped, the second trades half of the 7 contracts traded since
+        assert_eq!(sizes, vec![json!(10), json!(3)]);
+        assert_eq!(report.filled, 0);
+        assert_eq!(report.child_orders.len(), 2);
+        assert_eq!(report.slippage(), None);
+        assert_eq!(
+            http.requests_to(
+                Method::DELETE,
+                &format!("/portfolio/orders/{}", fixtures::ORDER_ID)
+            )
+            .len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,394 @@
+use std::{fs::File, io::Write, path::Path, sync::Arc};
+
+use arrow_array::{
+    ArrayRef, Int32Array, Int64Array, RecordBatch, StringArray, TimestampMillisecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use futures::{Stream, StreamExt};
+use parquet::arrow::ArrowWriter;
+
+use crate::{KalshiError, Market, Snapshot, Trade};
+
+/// Types that can be converted to Arrow record batches, one row per value.
+pub trait ArrowRecord: Sized {
+    /// The schema of the batches, the same for every batch.
+    fn schema() -> SchemaRef;
+
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, KalshiError>;
+}
+
+fn arrow_error(e: impl std::fmt::Display) -> KalshiError {
+    KalshiError::InternalError(format!("Could not write Arrow data: {}", e))
+}
+
+fn timestamp_field(name: &str) -> Field {
+    Field::new(
+        name,
+        DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+        true,
+    )
+}
+
+/// Builds a timestamp column from RFC 3339 times, times that don't parse are null.
+fn timestamps<'a>(times: impl Iterator<Item = Option<&'a str>>) -> ArrayRef {
+    let millis = times.map(|time| {
+        time.and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.timestamp_millis())
+    });
+    Arc::new(TimestampMillisecondArray::from_iter(millis).with_timezone("UTC"))
+}
+
+fn batch(schema: SchemaRef, columns: Vec<ArrayRef>) -> Result<RecordBatch, KalshiError> {
+    RecordBatch::try_new(schema, columns).map_err(arrow_error)
+}
+
+impl ArrowRecord for Trade {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("trade_id", DataType::Utf8, false),
+            Field::new("taker_side", DataType::Utf8, false),
+            Field::new("ticker", DataType::Utf8, false),
+            Field::new("count", DataType::Int32, false),
+            Field::new("yes_price", DataType::Int32, false),
+            Field::new("no_price", DataType::Int32, false),
+            timestamp_field("created_time"),
+        ]))
+    }
+
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, KalshiError> {
+        batch(
+            Self::schema(),
+            vec![
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|t| &t.trade_id),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|t| &t.taker_side),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|t| &t.ticker),
+                )),
+                Arc::new(Int32Array::from_iter_values(rows.iter().map(|t| t.count))),
+                Arc::new(Int32Array::from_iter_values(
+                    rows.iter().map(|t| t.yes_price),
+                )),
+                Arc::new(Int32Array::from_iter_values(
+                    rows.iter().map(|t| t.no_price),
+                )),
+                timestamps(rows.iter().map(|t| Some(t.created_time.as_str()))),
+            ],
+        )
+    }
+}
+
+impl ArrowRecord for Snapshot {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("yes_price", DataType::Int32, false),
+            Field::new("yes_bid", DataType::Int32, false),
+            Field::new("yes_ask", DataType::Int32, false),
+            Field::new("no_bid", DataType::Int32, false),
+            Field::new("no_ask", DataType::Int32, false),
+            Field::new("volume", DataType::Int32, false),
+            Field::new("open_interest", DataType::Int32, false),
+            timestamp_field("ts"),
+        ]))
+    }
+
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, KalshiError> {
+        let int = |field: fn(&Snapshot) -> i32| -> ArrayRef {
+            Arc::new(Int32Array::from_iter_values(rows.iter().map(field)))
+        };
+        batch(
+            Self::schema(),
+            vec![
+                int(|s| s.yes_price),
+                int(|s| s.yes_bid),
+                int(|s| s.yes_ask),
+                int(|s| s.no_bid),
+                int(|s| s.no_ask),
+                int(|s| s.volume),
+                int(|s| s.open_interest),
+                Arc::new(
+                    TimestampMillisecondArray::from_iter_values(rows.iter().map(|s| s.ts * 1000))
+                        .with_timezone("UTC"),
+                ),
+            ],
+        )
+    }
+}
+
+/// Only the main fields of a market are written: identifiers, times, prices and activity.
+impl ArrowRecord for Market {
+    fn schema() -> SchemaRef {
+        let mut fields = vec![
+            Field::new("ticker", DataType::Utf8, false),
+            Field::new("event_ticker", DataType::Utf8, false),
+            Field::new("market_type", DataType::Utf8, false),
+            Field::new("title", DataType::Utf8, false),
+            Field::new("subtitle", DataType::Utf8, false),
+            Field::new("category", DataType::Utf8, false),
+            Field::new("status", DataType::Utf8, false),
+            timestamp_field("open_time"),
+            timestamp_field("close_time"),
+        ];
+        fields.extend(
+            [
+                "yes_bid",
+                "yes_ask",
+                "no_bid",
+                "no_ask",
+                "last_price",
+                "previous_price",
+                "volume",
+                "volume_24h",
+                "liquidity",
+                "open_interest",
+            ]
+            .map(|name| Field::new(name, DataType::Int64, false)),
+        );
+        fields.push(Field::new("result", DataType::Utf8, true));
+        Arc::new(Schema::new(fields))
+    }
+
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, KalshiError> {
+        let string = |field: fn(&Market) -> &str| -> ArrayRef {
+            Arc::new(StringArray::from_iter_values(rows.iter().map(field)))
+        };
+        let int = |field: fn(&Market) -> i64| -> ArrayRef {
+            Arc::new(Int64Array::from_iter_values(rows.iter().map(field)))
+        };
+        batch(
+            Self::schema(),
+            vec![
+                string(|m| &m.ticker),
+                string(|m| &m.event_ticker),
+                string(|m| &m.market_type),
+                string(|m| &m.title),
+                string(|m| &m.subtitle),
+                string(|m| m.category.as_str()),
+                string(|m| &m.status),
+                timestamps(rows.iter().map(|m| Some(m.open_time.as_str()))),
+                timestamps(rows.iter().map(|m| Some(m.close_time.as_str()))),
+                int(|m| m.yes_bid),
+                int(|m| m.yes_ask),
+                int(|m| m.no_bid),
+                int(|m| m.no_ask),
+                int(|m| m.last_price),
+                int(|m| m.previous_price),
+                int(|m| m.volume),
+                int(|m| m.volume_24h),
+                int(|m| m.liquidity),
+                int(|m| m.open_interest),
+                Arc::new(StringArray::from_iter(rows.iter().map(|m| {
+                    m.result
+                        .map(|result| format!("{:?}", result).to_lowercase())
+                }))),
+            ],
+        )
+    }
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::responses::KalshiTickerMessage;
+
+    impl ArrowRecord for KalshiTickerMessage {
+        fn schema() -> SchemaRef {
+            let mut fields = vec![Field::new("market_ticker", DataType::Utf8, false)];
+            fields.extend(
+                [
+                    "price",
+                    "yes_bid",
+                    "yes_ask",
+                    "volume",
+                    "open_interest",
+                    "dollar_volume",
+                    "dollar_open_interest",
+                ]
+                .map(|name| Field::new(name, DataType::Int64, false)),
+            );
+            fields.push(timestamp_field("ts"));
+            Arc::new(Schema::new(fields))
+        }
+
+        fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, KalshiError> {
+            let int = |field: fn(&KalshiTickerMessage) -> u32| -> ArrayRef {
+                Arc::new(Int64Array::from_iter_values(
+                    rows.iter().map(|row| field(row) as i64),
+                ))
+            };
+            batch(
+                Self::schema(),
+                vec![
+                    Arc::new(StringArray::from_iter_values(
+                        rows.iter().map(|t| &t.market_ticker),
+                    )),
+                    int(|t| t.price),
+                    int(|t| t.yes_bid),
+                    int(|t| t.yes_ask),
+                    int(|t| t.volume),
+                    int(|t| t.open_interest),
+                    int(|t| t.dollar_volume),
+                    int(|t| t.dollar_open_interest),
+                    Arc::new(
+                        TimestampMillisecondArray::from_iter_values(
+                            rows.iter().map(|t| t.ts as i64 * 1000),
+                        )
+                        .with_timezone("UTC"),
+                    ),
+                ],
+            )
+        }
+    }
+}
+
+/// Writes records to a Parquet file, batching them into Arrow record batches.
+///
+/// Records are buffered until a batch of `batch_size` rows (10 000 by default) is full, so
+/// streamed data can be written as it arrives without holding it all in memory. The file is only
+/// valid once [`ParquetWriter::close`] has written its footer.
+///
+/// ```
+/// let mut writer = ParquetWriter::<Trade>::create("trades.parquet")?;
+/// let trades = kalshi_instance.get_trades(None, None, None, Some(ticker), None, None).await;
+/// writer.write_stream(trades).await?;
+/// writer.close()?;
+/// ```
+pub struct ParquetWriter<T: ArrowRecord, W: Write + Send = File> {
+    writer: ArrowWriter<W>,
+    buffer: Vec<T>,
+    batch_size: usize,
+    rows: usize,
+}
+
+impl<T: ArrowRecord> ParquetWriter<T, File> {
+    /// Creates the file at `path`, replacing it if it exists.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, KalshiError> {
+        let file = File::create(path.as_ref()).map_err(|e| {
+            KalshiError::InternalError(format!(
+                "Could not create {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })?;
+        Self::new(file)
+    }
+}
+
+impl<T: ArrowRecord, W: Write + Send> ParquetWriter<T, W> {
+    pub fn new(writer: W) -> Result<Self, KalshiError> {
+        Ok(ParquetWriter {
+            writer: ArrowWriter::try_new(writer, T::schema(), None).map_err(arrow_error)?,
+            buffer: Vec::new(),
+            batch_size: 10_000,
+            rows: 0,
+        })
+    }
+
+    /// Number of rows per record batch.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn write(&mut self, record: T) -> Result<(), KalshiError> {
+        self.buffer.push(record);
+        self.rows += 1;
+        if self.buffer.len() >= self.batch_size {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered records as a record batch.
+    fn write_batch(&mut self) -> Result<(), KalshiError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let batch = T::to_record_batch(&self.buffer)?;
+        self.buffer.clear();
+        self.writer.write(&batch).map_err(arrow_error)
+    }
+
+    /// Number of rows written, including those still buffered.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Writes every record of a stream such as `get_trades`, returning the number of rows written.
+    ///
+    /// Stops at the first error of the stream, the rows before it are kept.
+    pub async fn write_stream<S>(&mut self, stream: S) -> Result<usize, KalshiError>
+    where
+        S: Stream<Item = Result<T, KalshiError>>,
+    {
+        let mut stream = Box::pin(stream);
+        let before = self.rows;
+        while let Some(record) = stream.next().await {
+            self.write(record?)?;
+        }
+        Ok(self.rows - before)
+    }
+
+    /// Writes every page of a paginated stream such as `get_multiple_markets`, returning the number of rows written.
+    ///
+    /// Stops at the first error of the stream, the rows before it are kept.
+    pub async fn write_pages<S>(&mut self, pages: S) -> Result<usize, KalshiError>
+    where
+        S: Stream<Item = Result<Vec<T>, KalshiError>>,
+    {
+        let mut pages = Box::pin(pages);
+        let before = self.rows;
+        while let Some(page) = pages.next().await {
+            for record in page? {
+                self.write(record)?;
+            }
+        }
+        Ok(self.rows - before)
+    }
+
+    /// Writes the buffered records and the file footer, returning the number of rows in the file.
+    pub fn close(mut self) -> Result<usize, KalshiError> {
+        self.write_batch()?;
+        self.writer.close().map_err(arrow_error)?;
+        Ok(self.rows)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_trades_to_parquet() {
+        let trade = Trade {
+            trade_id: "trade-1".to_string(),
+            taker_side: "yes".to_string(),
+            ticker: "KXHIGHNY-25OCT02-B80.5".to_string(),
+            count: 10,
+            yes_price: 45,
+            no_price: 55,
+            created_time: "2025-10-02T14:00:00Z".to_string(),
+        };
+        let batch = Trade::to_record_batch(std::slice::from_ref(&trade)).unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        let created = batch
+            .column(6)
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(created.value(0), 1_759_413_600_000);
+
+        let mut out = Vec::new();
+        let mut writer = ParquetWriter::<Trade, _>::new(&mut out)
+            .unwrap()
+            .batch_size(2);
+        for _ in 0..5 {
+            writer.write(trade.clone()).unwrap();
+        }
+        assert_eq!(writer.close().unwrap(), 5);
+        assert_eq!(&out[..4], b"PAR1");
+    }
+}
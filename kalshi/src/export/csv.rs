@@ -0,0 +1,160 @@
+use std::io::Write;
+
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use crate::{Fill, KalshiError, Market, MarketPosition, Order, Settlement, Snapshot, Trade};
+
+/// Types written as one CSV row each, with a column per field.
+///
+/// Only flat types implement it, nested ones like `Candlestick` can't be represented as a single row.
+pub trait CsvRecord: Serialize {}
+
+impl CsvRecord for Market {}
+impl CsvRecord for Trade {}
+impl CsvRecord for Snapshot {}
+impl CsvRecord for Order {}
+impl CsvRecord for Fill {}
+impl CsvRecord for Settlement {}
+impl CsvRecord for MarketPosition {}
+
+fn csv_error(e: ::csv::Error) -> KalshiError {
+    KalshiError::InternalError(format!("Could not write CSV: {}", e))
+}
+
+/// Writes records as CSV rows, preceded by a header row named after their fields.
+///
+/// Empty options are written as empty cells and enums as their api names, so the output reads
+/// back into spreadsheets and dataframes as-is.
+///
+/// ```
+/// let file = std::fs::File::create("fills.csv")?;
+/// let mut writer = CsvWriter::new(file);
+/// for fill in fills {
+///     writer.write(&fill)?;
+/// }
+/// writer.flush()?;
+/// ```
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+    writer: ::csv::Writer<W>,
+    rows: usize,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(writer: W) -> Self {
+        CsvWriter {
+            writer: ::csv::Writer::from_writer(writer),
+            rows: 0,
+        }
+    }
+
+    pub fn write<T: CsvRecord>(&mut self, record: &T) -> Result<(), KalshiError> {
+        self.writer.serialize(record).map_err(csv_error)?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Number of rows written, not counting the header.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn flush(&mut self) -> Result<(), KalshiError> {
+        self.writer
+            .flush()
+            .map_err(|e| KalshiError::InternalError(format!("Could not write CSV: {}", e)))
+    }
+
+    /// Flushes the buffered rows and returns the underlying writer.
+    pub fn into_inner(self) -> Result<W, KalshiError> {
+        self.writer
+            .into_inner()
+            .map_err(|e| KalshiError::InternalError(format!("Could not write CSV: {}", e)))
+    }
+
+    /// Writes every record of a stream such as `get_trades`, returning the number of rows written.
+    ///
+    /// Stops at the first error of the stream, the rows before it are written and flushed.
+    pub async fn write_stream<T, S>(&mut self, stream: S) -> Result<usize, KalshiError>
+    where
+        T: CsvRecord,
+        S: Stream<Item = Result<T, KalshiError>>,
+    {
+        let mut stream = Box::pin(stream);
+        let before = self.rows;
+        while let Some(record) = stream.next().await {
+            match record {
+                Ok(record) => self.write(&record)?,
+                Err(e) => {
+                    self.flush()?;
+                    return Err(e);
+                }
+            }
+        }
+        self.flush()?;
+        Ok(self.rows - before)
+    }
+
+    /// Writes every page of a paginated stream such as `get_multiple_markets`, returning the number of rows written.
+    ///
+    /// Stops at the first error of the stream, the rows before it are written and flushed.
+    pub async fn write_pages<T, S>(&mut self, pages: S) -> Result<usize, KalshiError>
+    where
+        T: CsvRecord,
+        S: Stream<Item = Result<Vec<T>, KalshiError>>,
+    {
+        let records = pages.flat_map(|page| {
+            futures::stream::iter(match page {
+                Ok(records) => records.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            })
+        });
+        self.write_stream(records).await
+    }
+}
+
+/// Writes a slice of records as CSV, with a header row.
+pub trait ToCsv {
+    fn to_csv<W: Write>(&self, writer: W) -> Result<(), KalshiError>;
+}
+
+impl<T: CsvRecord> ToCsv for [T] {
+    fn to_csv<W: Write>(&self, writer: W) -> Result<(), KalshiError> {
+        let mut writer = CsvWriter::new(writer);
+        for record in self {
+            writer.write(record)?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fills_to_csv() {
+        let fill: Fill = serde_json::from_value(serde_json::json!({
+            "action": "buy",
+            "count": 10,
+            "created_time": "2025-10-02T14:00:00Z",
+            "is_taker": true,
+            "no_price": 55,
+            "order_id": "order-1",
+            "side": "yes",
+            "ticker": "KXHIGHNY-25OCT02-B80.5",
+            "trade_id": "trade-1",
+            "yes_price": 45,
+        }))
+        .unwrap();
+
+        let mut out = Vec::new();
+        [fill.clone(), fill].to_csv(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("action,count,created_time,is_taker"));
+        assert!(lines[1].starts_with("buy,10,2025-10-02T14:00:00Z,true,55,order-1,yes"));
+    }
+}
@@ -0,0 +1,11 @@
+//! Writers that dump api results into files for analysis in other tools.
+
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "csv")]
+mod csv;
+
+#[cfg(feature = "arrow")]
+pub use self::arrow::*;
+#[cfg(feature = "csv")]
+pub use self::csv::*;
@@ -0,0 +1,251 @@
+use crate::KalshiError;
+
+/// The field delimiter of the FIX tag-value encoding.
+pub const SOH: u8 = 0x01;
+
+/// Tags used by the session and order entry messages.
+pub mod tag {
+    pub const BEGIN_STRING: u32 = 8;
+    pub const BODY_LENGTH: u32 = 9;
+    pub const CHECK_SUM: u32 = 10;
+    pub const BEGIN_SEQ_NO: u32 = 7;
+    pub const CL_ORD_ID: u32 = 11;
+    pub const CUM_QTY: u32 = 14;
+    pub const END_SEQ_NO: u32 = 16;
+    pub const EXEC_ID: u32 = 17;
+    pub const LAST_PX: u32 = 31;
+    pub const LAST_QTY: u32 = 32;
+    pub const MSG_SEQ_NUM: u32 = 34;
+    pub const MSG_TYPE: u32 = 35;
+    pub const NEW_SEQ_NO: u32 = 36;
+    pub const ORDER_ID: u32 = 37;
+    pub const ORDER_QTY: u32 = 38;
+    pub const ORD_STATUS: u32 = 39;
+    pub const ORD_TYPE: u32 = 40;
+    pub const ORIG_CL_ORD_ID: u32 = 41;
+    pub const POSS_DUP_FLAG: u32 = 43;
+    pub const PRICE: u32 = 44;
+    pub const REF_SEQ_NUM: u32 = 45;
+    pub const SENDER_COMP_ID: u32 = 49;
+    pub const SENDING_TIME: u32 = 52;
+    pub const SIDE: u32 = 54;
+    pub const SYMBOL: u32 = 55;
+    pub const TARGET_COMP_ID: u32 = 56;
+    pub const TEXT: u32 = 58;
+    pub const TIME_IN_FORCE: u32 = 59;
+    pub const TRANSACT_TIME: u32 = 60;
+    pub const RAW_DATA_LENGTH: u32 = 95;
+    pub const RAW_DATA: u32 = 96;
+    pub const ENCRYPT_METHOD: u32 = 98;
+    pub const HEART_BT_INT: u32 = 108;
+    pub const TEST_REQ_ID: u32 = 112;
+    pub const ORIG_SENDING_TIME: u32 = 122;
+    pub const GAP_FILL_FLAG: u32 = 123;
+    pub const RESET_SEQ_NUM_FLAG: u32 = 141;
+    pub const EXEC_TYPE: u32 = 150;
+    pub const LEAVES_QTY: u32 = 151;
+    pub const DEFAULT_APPL_VER_ID: u32 = 1137;
+}
+
+/// Message types used by the session and order entry.
+pub mod msg_type {
+    pub const HEARTBEAT: &str = "0";
+    pub const TEST_REQUEST: &str = "1";
+    pub const RESEND_REQUEST: &str = "2";
+    pub const REJECT: &str = "3";
+    pub const SEQUENCE_RESET: &str = "4";
+    pub const LOGOUT: &str = "5";
+    pub const EXECUTION_REPORT: &str = "8";
+    pub const ORDER_CANCEL_REJECT: &str = "9";
+    pub const LOGON: &str = "A";
+    pub const NEW_ORDER_SINGLE: &str = "D";
+    pub const ORDER_CANCEL_REQUEST: &str = "F";
+
+    /// Whether messages of this type belong to the session layer rather than the application.
+    pub fn is_admin(msg_type: &str) -> bool {
+        matches!(
+            msg_type,
+            HEARTBEAT | TEST_REQUEST | RESEND_REQUEST | REJECT | SEQUENCE_RESET | LOGOUT | LOGON
+        )
+    }
+}
+
+/// A FIX message, its fields in order without the `BeginString`, `BodyLength` and `CheckSum`
+/// framing fields, which are computed on [`FixMessage::encode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixMessage {
+    fields: Vec<(u32, String)>,
+}
+
+impl FixMessage {
+    /// An empty message of type `msg_type`, see [`msg_type`].
+    pub fn new(msg_type: &str) -> Self {
+        FixMessage {
+            fields: vec![(tag::MSG_TYPE, msg_type.to_string())],
+        }
+    }
+
+    /// Appends a field.
+    pub fn with(mut self, tag: u32, value: impl ToString) -> Self {
+        self.fields.push((tag, value.to_string()));
+        self
+    }
+
+    /// Replaces the first field with this tag, or appends it.
+    pub fn set(&mut self, tag: u32, value: impl ToString) {
+        match self.fields.iter_mut().find(|(t, _)| *t == tag) {
+            Some(field) => field.1 = value.to_string(),
+            None => self.fields.push((tag, value.to_string())),
+        }
+    }
+
+    /// The value of the first field with this tag.
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The value of the first field with this tag, parsed.
+    pub fn get_parsed<T: std::str::FromStr>(&self, tag: u32) -> Option<T> {
+        self.get(tag).and_then(|v| v.parse().ok())
+    }
+
+    pub fn msg_type(&self) -> &str {
+        self.get(tag::MSG_TYPE).unwrap_or_default()
+    }
+
+    pub fn seq_num(&self) -> Option<u64> {
+        self.get_parsed(tag::MSG_SEQ_NUM)
+    }
+
+    pub fn fields(&self) -> &[(u32, String)] {
+        &self.fields
+    }
+
+    /// Encodes the message with its framing fields.
+    pub fn encode(&self, begin_string: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (tag, value) in &self.fields {
+            body.extend_from_slice(format!("{}={}", tag, value).as_bytes());
+            body.push(SOH);
+        }
+        let mut message = format!(
+            "{}={}\u{1}{}={}\u{1}",
+            tag::BEGIN_STRING,
+            begin_string,
+            tag::BODY_LENGTH,
+            body.len()
+        )
+        .into_bytes();
+        message.append(&mut body);
+        let check_sum = checksum(&message);
+        message.extend_from_slice(format!("{}={:03}\u{1}", tag::CHECK_SUM, check_sum).as_bytes());
+        message
+    }
+
+    /// Decodes one complete message, framing included, checking its checksum.
+    pub fn decode(bytes: &[u8]) -> Result<Self, KalshiError> {
+        let invalid = |reason: &str| {
+            KalshiError::InternalError(format!(
+                "Invalid FIX message ({}): {}",
+                reason,
+                String::from_utf8_lossy(bytes).replace('\u{1}', "|")
+            ))
+        };
+        let trailer = bytes
+            .len()
+            .checked_sub(7)
+            .filter(|&start| bytes[start..].starts_with(b"10="))
+            .ok_or_else(|| invalid("missing checksum"))?;
+        let expected: u32 = std::str::from_utf8(&bytes[trailer + 3..bytes.len() - 1])
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| invalid("malformed checksum"))?;
+        if checksum(&bytes[..trailer]) != expected {
+            return Err(invalid("checksum mismatch"));
+        }
+
+        let mut fields = Vec::new();
+        for field in bytes[..trailer]
+            .split(|b| *b == SOH)
+            .filter(|f| !f.is_empty())
+        {
+            let field = std::str::from_utf8(field).map_err(|_| invalid("not utf-8"))?;
+            let (tag, value) = field.split_once('=').ok_or_else(|| invalid("field"))?;
+            let tag: u32 = tag.parse().map_err(|_| invalid("tag"))?;
+            if tag != tag::BEGIN_STRING && tag != tag::BODY_LENGTH {
+                fields.push((tag, value.to_string()));
+            }
+        }
+        if fields.first().map(|(tag, _)| *tag) != Some(tag::MSG_TYPE) {
+            return Err(invalid("MsgType isn't the first body field"));
+        }
+        Ok(FixMessage { fields })
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().map(|b| *b as u32).sum::<u32>() % 256
+}
+
+/// The length of the first complete message in `buffer`, `None` if more bytes are needed.
+pub(crate) fn frame_length(buffer: &[u8]) -> Result<Option<usize>, KalshiError> {
+    // 8=...<SOH>9=<length><SOH>
+    let Some(begin_end) = buffer.iter().position(|b| *b == SOH) else {
+        return Ok(None);
+    };
+    if !buffer.starts_with(b"8=") {
+        return Err(KalshiError::InternalError(
+            "FIX stream out of sync, expected BeginString".to_string(),
+        ));
+    }
+    let rest = &buffer[begin_end + 1..];
+    let Some(length_end) = rest.iter().position(|b| *b == SOH) else {
+        return Ok(None);
+    };
+    let body_length: usize = std::str::from_utf8(&rest[..length_end])
+        .ok()
+        .and_then(|field| field.strip_prefix("9="))
+        .and_then(|length| length.parse().ok())
+        .ok_or_else(|| {
+            KalshiError::InternalError("FIX stream out of sync, expected BodyLength".to_string())
+        })?;
+    // The body, then "10=nnn<SOH>"
+    let total = begin_end + 1 + length_end + 1 + body_length + 7;
+    Ok((buffer.len() >= total).then_some(total))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let message = FixMessage::new(msg_type::NEW_ORDER_SINGLE)
+            .with(tag::MSG_SEQ_NUM, 2)
+            .with(tag::CL_ORD_ID, "abc")
+            .with(tag::PRICE, 64);
+        let encoded = message.encode("FIXT.1.1");
+        let text = String::from_utf8(encoded.clone())
+            .unwrap()
+            .replace('\u{1}', "|");
+        assert!(text.starts_with("8=FIXT.1.1|9=23|35=D|34=2|11=abc|44=64|10="));
+
+        // Split across reads
+        let mut buffer = encoded[..10].to_vec();
+        assert_eq!(frame_length(&buffer).unwrap(), None);
+        buffer.extend_from_slice(&encoded[10..]);
+        buffer.extend_from_slice(b"8=FIX");
+        assert_eq!(frame_length(&buffer).unwrap(), Some(encoded.len()));
+
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(decoded.seq_num(), Some(2));
+
+        let mut corrupted = encoded.clone();
+        corrupted[20] = b'E';
+        assert!(FixMessage::decode(&corrupted).is_err());
+    }
+}
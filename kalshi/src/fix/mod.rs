@@ -0,0 +1,10 @@
+//! A client for Kalshi's FIX order entry gateway, enabled with the `fix` feature.
+//!
+//! The session logs on with the same RSA key as the REST API, see [`FixClient`].
+//! [`FixMessage`] covers the tag-value encoding for messages the client doesn't build itself.
+
+mod message;
+mod session;
+
+pub use message::{msg_type, tag, FixMessage, SOH};
+pub use session::{FixClient, FixConfig, FixEvent, FixExecutionReport, FixOrder, FixSide};
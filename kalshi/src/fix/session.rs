@@ -0,0 +1,818 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf},
+    net::TcpStream,
+    sync::{broadcast, mpsc},
+    time::{Instant, MissedTickBehavior},
+};
+use uuid::Uuid;
+
+use super::message::{frame_length, msg_type, tag, FixMessage, SOH};
+use crate::{Kalshi, KalshiError};
+
+/// Header fields rebuilt on every send, never copied from a stored message.
+const HEADER_TAGS: [u32; 6] = [
+    tag::SENDER_COMP_ID,
+    tag::TARGET_COMP_ID,
+    tag::MSG_SEQ_NUM,
+    tag::SENDING_TIME,
+    tag::POSS_DUP_FLAG,
+    tag::ORIG_SENDING_TIME,
+];
+
+/// Application messages kept for resend requests, older ones are gap filled instead.
+const MAX_STORED_MESSAGES: usize = 10_000;
+
+/// Settings of a FIX session, see [`FixClient`].
+#[derive(Debug, Clone)]
+pub struct FixConfig {
+    host: String,
+    port: u16,
+    target_comp_id: String,
+    begin_string: String,
+    heartbeat_interval: Duration,
+    logon_timeout: Duration,
+    reset_seq_num: bool,
+}
+
+impl FixConfig {
+    /// A session with the gateway at `host:port`, as given by Kalshi when FIX access is granted.
+    pub fn new(host: &str, port: u16) -> Self {
+        FixConfig {
+            host: host.to_string(),
+            port,
+            target_comp_id: "KalshiNR".to_string(),
+            begin_string: "FIXT.1.1".to_string(),
+            heartbeat_interval: Duration::from_secs(30),
+            logon_timeout: Duration::from_secs(10),
+            reset_seq_num: true,
+        }
+    }
+
+    /// The gateway's comp id, `KalshiNR` (order entry without retransmission) by default.
+    pub fn target_comp_id(mut self, target_comp_id: &str) -> Self {
+        self.target_comp_id = target_comp_id.to_string();
+        self
+    }
+
+    /// Heartbeats are sent after this long without sending anything, 30 seconds by default.
+    pub fn heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    /// How long to wait for the gateway to acknowledge the logon, 10 seconds by default.
+    pub fn logon_timeout(mut self, timeout: Duration) -> Self {
+        self.logon_timeout = timeout;
+        self
+    }
+
+    /// Whether sequence numbers are reset to 1 on logon, on by default.
+    pub fn reset_seq_num(mut self, reset: bool) -> Self {
+        self.reset_seq_num = reset;
+        self
+    }
+}
+
+/// The side of a FIX order, on the yes contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixSide {
+    Buy,
+    Sell,
+}
+
+impl FixSide {
+    fn code(self) -> &'static str {
+        match self {
+            FixSide::Buy => "1",
+            FixSide::Sell => "2",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "1" => Some(FixSide::Buy),
+            "2" => Some(FixSide::Sell),
+            _ => None,
+        }
+    }
+}
+
+/// A limit order sent with [`FixClient::new_order`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixOrder {
+    /// Generated when `None`.
+    pub cl_ord_id: Option<String>,
+    pub symbol: String,
+    pub side: FixSide,
+    pub quantity: i32,
+    /// The limit price of the yes contract, in cents.
+    pub price: i64,
+}
+
+impl FixOrder {
+    pub fn limit(symbol: &str, side: FixSide, quantity: i32, price: i64) -> Self {
+        FixOrder {
+            cl_ord_id: None,
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            price,
+        }
+    }
+
+    pub fn cl_ord_id(mut self, cl_ord_id: &str) -> Self {
+        self.cl_ord_id = Some(cl_ord_id.to_string());
+        self
+    }
+}
+
+/// An execution report (`35=8`), sent by the gateway on every change of an order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixExecutionReport {
+    pub order_id: Option<String>,
+    pub cl_ord_id: Option<String>,
+    pub exec_id: Option<String>,
+    /// `0` new, `4` canceled, `5` replaced, `8` rejected, `F` trade...
+    pub exec_type: Option<String>,
+    /// `0` new, `1` partially filled, `2` filled, `4` canceled, `8` rejected...
+    pub ord_status: Option<String>,
+    pub symbol: Option<String>,
+    pub side: Option<FixSide>,
+    pub price: Option<i64>,
+    pub last_qty: Option<i64>,
+    pub last_px: Option<i64>,
+    pub leaves_qty: Option<i64>,
+    pub cum_qty: Option<i64>,
+    pub text: Option<String>,
+}
+
+impl FixExecutionReport {
+    pub fn from_message(message: &FixMessage) -> Self {
+        let text = |t| message.get(t).map(str::to_string);
+        FixExecutionReport {
+            order_id: text(tag::ORDER_ID),
+            cl_ord_id: text(tag::CL_ORD_ID),
+            exec_id: text(tag::EXEC_ID),
+            exec_type: text(tag::EXEC_TYPE),
+            ord_status: text(tag::ORD_STATUS),
+            symbol: text(tag::SYMBOL),
+            side: message.get(tag::SIDE).and_then(FixSide::from_code),
+            price: message.get_parsed(tag::PRICE),
+            last_qty: message.get_parsed(tag::LAST_QTY),
+            last_px: message.get_parsed(tag::LAST_PX),
+            leaves_qty: message.get_parsed(tag::LEAVES_QTY),
+            cum_qty: message.get_parsed(tag::CUM_QTY),
+            text: text(tag::TEXT),
+        }
+    }
+}
+
+/// Application and session events received by a [`FixClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixEvent {
+    ExecutionReport(FixExecutionReport),
+    /// A cancel request was rejected (`35=9`).
+    CancelReject {
+        cl_ord_id: Option<String>,
+        orig_cl_ord_id: Option<String>,
+        text: Option<String>,
+    },
+    /// A message was rejected at the session level (`35=3`).
+    Reject {
+        ref_seq_num: Option<u64>,
+        text: Option<String>,
+    },
+    /// The session ended, the client can't be used anymore.
+    LoggedOut {
+        text: Option<String>,
+    },
+    /// Any other application message.
+    Message(FixMessage),
+}
+
+/// An order entry session with Kalshi's FIX gateway.
+///
+/// The session logs on with the API key of a [`Kalshi`] instance: the key id is the
+/// `SenderCompID` and the logon carries an RSA-PSS signature of its `SendingTime`, `MsgType`,
+/// `MsgSeqNum`, `SenderCompID` and `TargetCompID`, joined by SOH. Once logged on, a background
+/// task keeps the session alive with heartbeats, answers test requests, resends messages the
+/// gateway missed and asks for the ones this side missed. Orders are sent with
+/// [`FixClient::new_order`], their execution reports arrive on [`FixClient::events`].
+///
+/// The client is a cheap handle, clones share the session. It's logged out when every handle is
+/// dropped.
+///
+/// ```
+/// let fix = FixClient::connect(FixConfig::new(gateway_host, gateway_port), &kalshi_instance).await?;
+/// let mut events = fix.events();
+/// let cl_ord_id = fix.new_order(FixOrder::limit("KXHIGHNY-25OCT02-B80.5", FixSide::Buy, 10, 40))?;
+///
+/// while let Ok(event) = events.recv().await {
+///     if let FixEvent::ExecutionReport(report) = event {
+///         println!("{:?} is now {:?}", report.cl_ord_id, report.ord_status);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixClient {
+    outgoing: mpsc::UnboundedSender<FixMessage>,
+    events: broadcast::Sender<FixEvent>,
+}
+
+impl FixClient {
+    /// Opens a TLS connection to the gateway and logs on.
+    pub async fn connect(config: FixConfig, kalshi: &Kalshi) -> Result<Self, KalshiError> {
+        let io_error =
+            |e: std::io::Error| KalshiError::InternalError(format!("FIX connection failed: {}", e));
+        let tls_error = |e: tokio_native_tls::native_tls::Error| {
+            KalshiError::InternalError(format!("FIX TLS handshake failed: {}", e))
+        };
+        let tcp = TcpStream::connect((config.host.as_str(), config.port))
+            .await
+            .map_err(io_error)?;
+        tcp.set_nodelay(true).map_err(io_error)?;
+        let connector = match &kalshi.tls {
+            Some(tls) => tls.connector()?,
+            None => tokio_native_tls::native_tls::TlsConnector::new().map_err(tls_error)?,
+        };
+        let stream = tokio_native_tls::TlsConnector::from(connector)
+            .connect(&config.host, tcp)
+            .await
+            .map_err(tls_error)?;
+        Self::start(stream, config, kalshi).await
+    }
+
+    /// Logs on over an already established connection, for example a TLS tunnel or a test stream.
+    pub async fn start<S>(
+        stream: S,
+        config: FixConfig,
+        kalshi: &Kalshi,
+    ) -> Result<Self, KalshiError>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::split(stream);
+        let (sender_comp_id, _) = kalshi.sign_with_api_key("")?;
+        let mut session = Session {
+            writer,
+            config,
+            sender_comp_id,
+            next_out: 1,
+            next_in: 1,
+            sent: BTreeMap::new(),
+            last_sent: Instant::now(),
+        };
+        let mut reader = FrameReader {
+            reader,
+            buffer: Vec::new(),
+        };
+        session.logon(kalshi).await?;
+
+        let timeout = session.config.logon_timeout;
+        let logon = tokio::time::timeout(timeout, async {
+            loop {
+                match reader.next().await? {
+                    Some(message) if message.msg_type() == msg_type::LOGON => return Ok(message),
+                    Some(message) if message.msg_type() == msg_type::LOGOUT => {
+                        return Err(KalshiError::UserInputError(format!(
+                            "FIX logon refused: {}",
+                            message.get(tag::TEXT).unwrap_or("no reason given")
+                        )))
+                    }
+                    Some(_) => continue,
+                    None => {
+                        return Err(KalshiError::InternalError(
+                            "FIX connection closed during logon".to_string(),
+                        ))
+                    }
+                }
+            }
+        })
+        .await
+        .map_err(|_| KalshiError::InternalError("FIX logon timed out".to_string()))??;
+        session.next_in = logon.seq_num().unwrap_or(1) + 1;
+
+        let (outgoing, commands) = mpsc::unbounded_channel();
+        let (events, _) = broadcast::channel(1024);
+        tokio::spawn(run_session(session, reader, commands, events.clone()));
+        Ok(FixClient { outgoing, events })
+    }
+
+    /// Subscribes to the events received from now on.
+    pub fn events(&self) -> broadcast::Receiver<FixEvent> {
+        self.events.subscribe()
+    }
+
+    /// Whether the session is still running.
+    pub fn is_connected(&self) -> bool {
+        !self.outgoing.is_closed()
+    }
+
+    /// Sends an application message, the header is filled in by the session.
+    pub fn send(&self, message: FixMessage) -> Result<(), KalshiError> {
+        self.outgoing
+            .send(message)
+            .map_err(|_| KalshiError::InternalError("FIX session has ended".to_string()))
+    }
+
+    /// Sends a limit order (`35=D`), returning its `ClOrdID`.
+    pub fn new_order(&self, order: FixOrder) -> Result<String, KalshiError> {
+        let cl_ord_id = order
+            .cl_ord_id
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        self.send(
+            FixMessage::new(msg_type::NEW_ORDER_SINGLE)
+                .with(tag::CL_ORD_ID, &cl_ord_id)
+                .with(tag::SYMBOL, order.symbol)
+                .with(tag::SIDE, order.side.code())
+                .with(tag::ORDER_QTY, order.quantity)
+                .with(tag::ORD_TYPE, "2")
+                .with(tag::PRICE, order.price)
+                .with(tag::TRANSACT_TIME, sending_time()),
+        )?;
+        Ok(cl_ord_id)
+    }
+
+    /// Cancels the order sent as `orig_cl_ord_id` (`35=F`), returning the cancel's `ClOrdID`.
+    pub fn cancel_order(
+        &self,
+        orig_cl_ord_id: &str,
+        symbol: &str,
+        side: FixSide,
+    ) -> Result<String, KalshiError> {
+        let cl_ord_id = Uuid::new_v4().to_string();
+        self.send(
+            FixMessage::new(msg_type::ORDER_CANCEL_REQUEST)
+                .with(tag::ORIG_CL_ORD_ID, orig_cl_ord_id)
+                .with(tag::CL_ORD_ID, &cl_ord_id)
+                .with(tag::SYMBOL, symbol)
+                .with(tag::SIDE, side.code())
+                .with(tag::TRANSACT_TIME, sending_time()),
+        )?;
+        Ok(cl_ord_id)
+    }
+
+    /// Ends the session, [`FixEvent::LoggedOut`] follows once the gateway confirmed.
+    pub fn logout(&self) -> Result<(), KalshiError> {
+        self.send(FixMessage::new(msg_type::LOGOUT))
+    }
+}
+
+fn sending_time() -> String {
+    chrono::Utc::now().format("%Y%m%d-%H:%M:%S%.3f").to_string()
+}
+
+struct FrameReader<S> {
+    reader: ReadHalf<S>,
+    buffer: Vec<u8>,
+}
+
+impl<S: AsyncRead> FrameReader<S> {
+    /// The next message, `None` once the connection is closed. Cancel safe.
+    async fn next(&mut self) -> Result<Option<FixMessage>, KalshiError> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            if let Some(length) = frame_length(&self.buffer)? {
+                let frame: Vec<u8> = self.buffer.drain(..length).collect();
+                return FixMessage::decode(&frame).map(Some);
+            }
+            let read = self
+                .reader
+                .read(&mut chunk)
+                .await
+                .map_err(|e| KalshiError::InternalError(format!("FIX read failed: {}", e)))?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+}
+
+struct Session<S> {
+    writer: WriteHalf<S>,
+    config: FixConfig,
+    sender_comp_id: String,
+    next_out: u64,
+    next_in: u64,
+    /// The last [`MAX_STORED_MESSAGES`] application messages sent, kept for resend requests
+    sent: BTreeMap<u64, FixMessage>,
+    last_sent: Instant,
+}
+
+impl<S: AsyncWrite> Session<S> {
+    /// Rebuilds `message` with a header, keeping its type first and dropping old header fields.
+    fn with_header(&self, message: &FixMessage, seq: u64, extra: &[(u32, String)]) -> FixMessage {
+        let mut stamped = FixMessage::new(message.msg_type())
+            .with(tag::SENDER_COMP_ID, &self.sender_comp_id)
+            .with(tag::TARGET_COMP_ID, &self.config.target_comp_id)
+            .with(tag::MSG_SEQ_NUM, seq);
+        for (t, value) in extra {
+            stamped = stamped.with(*t, value);
+        }
+        if !extra.iter().any(|(t, _)| *t == tag::SENDING_TIME) {
+            stamped = stamped.with(tag::SENDING_TIME, sending_time());
+        }
+        for (t, value) in &message.fields()[1..] {
+            if !HEADER_TAGS.contains(t) {
+                stamped = stamped.with(*t, value);
+            }
+        }
+        stamped
+    }
+
+    async fn write(&mut self, message: &FixMessage) -> Result<(), KalshiError> {
+        log::debug!(
+            "FIX out: {}",
+            String::from_utf8_lossy(&message.encode(&self.config.begin_string))
+                .replace('\u{1}', "|")
+        );
+        self.writer
+            .write_all(&message.encode(&self.config.begin_string))
+            .await
+            .map_err(|e| KalshiError::InternalError(format!("FIX write failed: {}", e)))?;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    /// Sends a new message with the next sequence number.
+    async fn send(&mut self, message: FixMessage) -> Result<(), KalshiError> {
+        let seq = self.next_out;
+        let stamped = self.with_header(&message, seq, &[]);
+        self.next_out += 1;
+        if !msg_type::is_admin(stamped.msg_type()) {
+            self.sent.insert(seq, stamped.clone());
+            if self.sent.len() > MAX_STORED_MESSAGES {
+                self.sent.pop_first();
+            }
+        }
+        self.write(&stamped).await
+    }
+
+    async fn logon(&mut self, kalshi: &Kalshi) -> Result<(), KalshiError> {
+        let time = sending_time();
+        let seq = self.next_out;
+        let payload = [
+            time.as_str(),
+            msg_type::LOGON,
+            &seq.to_string(),
+            &self.sender_comp_id,
+            &self.config.target_comp_id,
+        ]
+        .join(&(SOH as char).to_string());
+        let (_, signature) = kalshi.sign_with_api_key(&payload)?;
+
+        let mut logon = FixMessage::new(msg_type::LOGON)
+            .with(tag::ENCRYPT_METHOD, 0)
+            .with(tag::HEART_BT_INT, self.config.heartbeat_interval.as_secs())
+            .with(tag::DEFAULT_APPL_VER_ID, 9)
+            .with(tag::RAW_DATA_LENGTH, signature.len())
+            .with(tag::RAW_DATA, signature);
+        if self.config.reset_seq_num {
+            logon = logon.with(tag::RESET_SEQ_NUM_FLAG, "Y");
+        }
+        let stamped = self.with_header(&logon, seq, &[(tag::SENDING_TIME, time)]);
+        self.next_out += 1;
+        self.write(&stamped).await
+    }
+
+    /// Answers a resend request: application messages are sent again as possible duplicates,
+    /// the gaps left by session messages are filled with sequence resets.
+    async fn resend(&mut self, begin: u64, end: u64) -> Result<(), KalshiError> {
+        let last = self.next_out - 1;
+        let end = if end == 0 || end > last { last } else { end };
+        let mut gap_start = None;
+        for seq in begin..=end {
+            match self.sent.get(&seq).cloned() {
+                Some(original) => {
+                    if let Some(start) = gap_start.take() {
+                        self.gap_fill(start, seq).await?;
+                    }
+                    let orig_time = original.get(tag::SENDING_TIME).unwrap_or_default();
+                    let extra = [
+                        (tag::POSS_DUP_FLAG, "Y".to_string()),
+                        (tag::SENDING_TIME, sending_time()),
+                        (tag::ORIG_SENDING_TIME, orig_time.to_string()),
+                    ];
+                    let duplicate = self.with_header(&original, seq, &extra);
+                    self.write(&duplicate).await?;
+                }
+                None => {
+                    gap_start.get_or_insert(seq);
+                }
+            }
+        }
+        if let Some(start) = gap_start {
+            self.gap_fill(start, end + 1).await?;
+        }
+        Ok(())
+    }
+
+    async fn gap_fill(&mut self, seq: u64, new_seq: u64) -> Result<(), KalshiError> {
+        let reset = FixMessage::new(msg_type::SEQUENCE_RESET)
+            .with(tag::GAP_FILL_FLAG, "Y")
+            .with(tag::NEW_SEQ_NO, new_seq);
+        let reset = self.with_header(&reset, seq, &[(tag::POSS_DUP_FLAG, "Y".to_string())]);
+        self.write(&reset).await
+    }
+}
+
+async fn run_session<S: AsyncRead + AsyncWrite>(
+    mut session: Session<S>,
+    mut reader: FrameReader<S>,
+    mut commands: mpsc::UnboundedReceiver<FixMessage>,
+    events: broadcast::Sender<FixEvent>,
+) {
+    let text = match session_loop(&mut session, &mut reader, &mut commands, &events).await {
+        Ok(text) => text,
+        Err(e) => {
+            log::warn!("FIX session failed: {}", e);
+            Some(e.to_string())
+        }
+    };
+    let _ = events.send(FixEvent::LoggedOut { text });
+}
+
+/// Runs the session until it's logged out or fails, returning the logout text.
+async fn session_loop<S: AsyncRead + AsyncWrite>(
+    session: &mut Session<S>,
+    reader: &mut FrameReader<S>,
+    commands: &mut mpsc::UnboundedReceiver<FixMessage>,
+    events: &broadcast::Sender<FixEvent>,
+) -> Result<Option<String>, KalshiError> {
+    let interval = session.config.heartbeat_interval;
+    let mut tick = tokio::time::interval(interval / 2);
+    tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut last_received = Instant::now();
+    let mut test_request_sent = false;
+    let mut resend_requested = false;
+    let mut logging_out = false;
+    let mut commands_closed = false;
+
+    loop {
+        tokio::select! {
+            command = commands.recv(), if !commands_closed => match command {
+                Some(message) => {
+                    logging_out |= message.msg_type() == msg_type::LOGOUT;
+                    session.send(message).await?;
+                }
+                // Every handle was dropped, wait for the gateway's logout only
+                None => {
+                    commands_closed = true;
+                    if !logging_out {
+                        logging_out = true;
+                        session.send(FixMessage::new(msg_type::LOGOUT)).await?;
+                    }
+                }
+            },
+            message = reader.next() => {
+                let Some(message) = message? else {
+                    return Err(KalshiError::InternalError("FIX connection closed".to_string()));
+                };
+                last_received = Instant::now();
+                test_request_sent = false;
+
+                let seq = message.seq_num().unwrap_or(0);
+                let is_reset = message.msg_type() == msg_type::SEQUENCE_RESET;
+                if !is_reset && seq < session.next_in {
+                    if message.get(tag::POSS_DUP_FLAG) == Some("Y") {
+                        continue;
+                    }
+                    return Err(KalshiError::InternalError(format!(
+                        "FIX MsgSeqNum too low, expected {} got {}",
+                        session.next_in, seq
+                    )));
+                }
+                if !is_reset && seq > session.next_in {
+                    // Everything from the gap on is sent again
+                    if !resend_requested {
+                        resend_requested = true;
+                        let request = FixMessage::new(msg_type::RESEND_REQUEST)
+                            .with(tag::BEGIN_SEQ_NO, session.next_in)
+                            .with(tag::END_SEQ_NO, 0);
+                        session.send(request).await?;
+                    }
+                    continue;
+                }
+                if is_reset {
+                    if let Some(new_seq) = message.get_parsed::<u64>(tag::NEW_SEQ_NO) {
+                        session.next_in = new_seq.max(session.next_in);
+                    }
+                    continue;
+                }
+                session.next_in += 1;
+                resend_requested = false;
+
+                match message.msg_type() {
+                    msg_type::HEARTBEAT | msg_type::LOGON => {}
+                    msg_type::TEST_REQUEST => {
+                        let mut heartbeat = FixMessage::new(msg_type::HEARTBEAT);
+                        if let Some(id) = message.get(tag::TEST_REQ_ID) {
+                            heartbeat = heartbeat.with(tag::TEST_REQ_ID, id);
+                        }
+                        session.send(heartbeat).await?;
+                    }
+                    msg_type::RESEND_REQUEST => {
+                        let begin = message.get_parsed(tag::BEGIN_SEQ_NO).unwrap_or(1);
+                        let end = message.get_parsed(tag::END_SEQ_NO).unwrap_or(0);
+                        session.resend(begin, end).await?;
+                    }
+                    msg_type::LOGOUT => {
+                        if !logging_out {
+                            session.send(FixMessage::new(msg_type::LOGOUT)).await?;
+                        }
+                        return Ok(message.get(tag::TEXT).map(str::to_string));
+                    }
+                    msg_type::REJECT => {
+                        let _ = events.send(FixEvent::Reject {
+                            ref_seq_num: message.get_parsed(tag::REF_SEQ_NUM),
+                            text: message.get(tag::TEXT).map(str::to_string),
+                        });
+                    }
+                    msg_type::EXECUTION_REPORT => {
+                        let report = FixExecutionReport::from_message(&message);
+                        let _ = events.send(FixEvent::ExecutionReport(report));
+                    }
+                    msg_type::ORDER_CANCEL_REJECT => {
+                        let _ = events.send(FixEvent::CancelReject {
+                            cl_ord_id: message.get(tag::CL_ORD_ID).map(str::to_string),
+                            orig_cl_ord_id: message.get(tag::ORIG_CL_ORD_ID).map(str::to_string),
+                            text: message.get(tag::TEXT).map(str::to_string),
+                        });
+                    }
+                    _ => {
+                        let _ = events.send(FixEvent::Message(message));
+                    }
+                }
+            },
+            _ = tick.tick() => {
+                if last_received.elapsed() > interval * 2 {
+                    return Err(KalshiError::InternalError("FIX gateway stopped responding".to_string()));
+                }
+                if last_received.elapsed() > interval + interval / 5 && !test_request_sent {
+                    test_request_sent = true;
+                    let request = FixMessage::new(msg_type::TEST_REQUEST)
+                        .with(tag::TEST_REQ_ID, sending_time());
+                    session.send(request).await?;
+                } else if session.last_sent.elapsed() >= interval {
+                    session.send(FixMessage::new(msg_type::HEARTBEAT)).await?;
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TradingEnvironment;
+    use tokio::io::DuplexStream;
+
+    /// Reads the next message sent by the client.
+    async fn receive(gateway: &mut DuplexStream, buffer: &mut Vec<u8>) -> FixMessage {
+        let mut chunk = [0u8; 4096];
+        loop {
+            if let Some(length) = frame_length(buffer).unwrap() {
+                let frame: Vec<u8> = buffer.drain(..length).collect();
+                return FixMessage::decode(&frame).unwrap();
+            }
+            let read = gateway.read(&mut chunk).await.unwrap();
+            assert!(read > 0, "client closed the connection");
+            buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    async fn reply(gateway: &mut DuplexStream, message: FixMessage) {
+        gateway
+            .write_all(&message.encode("FIXT.1.1"))
+            .await
+            .unwrap();
+    }
+
+    /// Starts a session over an in-memory stream, answering its logon.
+    async fn logged_on(buffer: &mut Vec<u8>) -> (FixClient, DuplexStream) {
+        let kalshi = Kalshi::new_with_api_key(
+            TradingEnvironment::DemoMode,
+            "key-id".to_string(),
+            crate::testing::throwaway_private_key(),
+        );
+        let (client_end, mut gateway) = tokio::io::duplex(64 * 1024);
+
+        let config = FixConfig::new("localhost", 0).heartbeat_interval(Duration::from_secs(60));
+        let fix = tokio::spawn(async move { FixClient::start(client_end, config, &kalshi).await });
+        let logon = receive(&mut gateway, buffer).await;
+        assert_eq!(logon.msg_type(), msg_type::LOGON);
+        assert_eq!(logon.get(tag::SENDER_COMP_ID), Some("key-id"));
+        assert_eq!(logon.seq_num(), Some(1));
+        assert!(logon.get(tag::RAW_DATA).is_some());
+        reply(
+            &mut gateway,
+            FixMessage::new(msg_type::LOGON).with(tag::MSG_SEQ_NUM, 1),
+        )
+        .await;
+        (fix.await.unwrap().unwrap(), gateway)
+    }
+
+    #[tokio::test]
+    async fn test_logon_order_and_resend() {
+        let mut buffer = Vec::new();
+        let (fix, mut gateway) = logged_on(&mut buffer).await;
+        let mut events = fix.events();
+
+        let cl_ord_id = fix
+            .new_order(FixOrder::limit(
+                "KXHIGHNY-25OCT02-B80.5",
+                FixSide::Buy,
+                10,
+                40,
+            ))
+            .unwrap();
+        let order = receive(&mut gateway, &mut buffer).await;
+        assert_eq!(order.msg_type(), msg_type::NEW_ORDER_SINGLE);
+        assert_eq!(order.seq_num(), Some(2));
+        assert_eq!(order.get(tag::CL_ORD_ID), Some(cl_ord_id.as_str()));
+
+        // The gateway missed the order
+        reply(
+            &mut gateway,
+            FixMessage::new(msg_type::RESEND_REQUEST)
+                .with(tag::MSG_SEQ_NUM, 2)
+                .with(tag::BEGIN_SEQ_NO, 1)
+                .with(tag::END_SEQ_NO, 0),
+        )
+        .await;
+        let gap_fill = receive(&mut gateway, &mut buffer).await;
+        assert_eq!(gap_fill.msg_type(), msg_type::SEQUENCE_RESET);
+        assert_eq!(gap_fill.get(tag::NEW_SEQ_NO), Some("2"));
+        let duplicate = receive(&mut gateway, &mut buffer).await;
+        assert_eq!(duplicate.seq_num(), Some(2));
+        assert_eq!(duplicate.get(tag::POSS_DUP_FLAG), Some("Y"));
+        assert_eq!(duplicate.get(tag::CL_ORD_ID), Some(cl_ord_id.as_str()));
+
+        reply(
+            &mut gateway,
+            FixMessage::new(msg_type::EXECUTION_REPORT)
+                .with(tag::MSG_SEQ_NUM, 3)
+                .with(tag::ORDER_ID, "o-1")
+                .with(tag::CL_ORD_ID, &cl_ord_id)
+                .with(tag::EXEC_TYPE, "0")
+                .with(tag::ORD_STATUS, "0")
+                .with(tag::SIDE, "1")
+                .with(tag::LEAVES_QTY, 10),
+        )
+        .await;
+        let FixEvent::ExecutionReport(report) = events.recv().await.unwrap() else {
+            panic!("expected an execution report");
+        };
+        assert_eq!(report.order_id.as_deref(), Some("o-1"));
+        assert_eq!(report.side, Some(FixSide::Buy));
+        assert_eq!(report.leaves_qty, Some(10));
+
+        fix.logout().unwrap();
+        assert_eq!(
+            receive(&mut gateway, &mut buffer).await.msg_type(),
+            msg_type::LOGOUT
+        );
+        reply(
+            &mut gateway,
+            FixMessage::new(msg_type::LOGOUT).with(tag::MSG_SEQ_NUM, 4),
+        )
+        .await;
+        assert_eq!(
+            events.recv().await.unwrap(),
+            FixEvent::LoggedOut { text: None }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dropped_client_logs_out() {
+        let mut buffer = Vec::new();
+        let (fix, mut gateway) = logged_on(&mut buffer).await;
+        let mut events = fix.events();
+
+        drop(fix);
+        assert_eq!(
+            receive(&mut gateway, &mut buffer).await.msg_type(),
+            msg_type::LOGOUT
+        );
+        reply(
+            &mut gateway,
+            FixMessage::new(msg_type::LOGOUT)
+                .with(tag::MSG_SEQ_NUM, 2)
+                .with(tag::TEXT, "bye"),
+        )
+        .await;
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("the session never logged out")
+            .unwrap();
+        assert_eq!(
+            event,
+            FixEvent::LoggedOut {
+                text: Some("bye".to_string())
+            }
+        );
+    }
+}
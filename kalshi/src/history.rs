@@ -0,0 +1,487 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::{stream, StreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{store, Candlestick, Kalshi, KalshiError, Market, Trade};
+
+/// Most candlesticks Kalshi returns for a single request.
+const MAX_CANDLESTICKS_PER_REQUEST: i64 = 5000;
+
+/// Length of the candlesticks a [`HistoryDownloader`] fetches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandlestickPeriod {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl CandlestickPeriod {
+    /// The period in minutes, as the `period_interval` of `get_market_candlesticks`.
+    pub fn minutes(&self) -> i32 {
+        match self {
+            CandlestickPeriod::Minute => 1,
+            CandlestickPeriod::Hour => 60,
+            CandlestickPeriod::Day => 1440,
+        }
+    }
+}
+
+/// How far a download got, passed to the progress callback after every market.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Markets matching the download.
+    pub markets_total: usize,
+    /// Markets downloaded by this run.
+    pub markets_done: usize,
+    /// Markets already downloaded by a previous run.
+    pub markets_skipped: usize,
+    /// Markets that failed and will be retried by the next run.
+    pub markets_failed: usize,
+    /// Candlesticks downloaded by this run.
+    pub candlesticks: usize,
+    /// Trades downloaded by this run.
+    pub trades: usize,
+}
+
+type ProgressCallback = Arc<dyn Fn(&DownloadProgress) + Send + Sync>;
+
+/// Markets already downloaded, saved in `progress.json` so an interrupted download can resume.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    completed: BTreeSet<String>,
+}
+
+/// Downloads the candlesticks, trades and metadata of many markets into a local dataset.
+///
+/// Markets are selected by series with [`HistoryDownloader::series`], by close time with
+/// [`HistoryDownloader::closing_between`], or both. Their history is fetched from their open time
+/// to their close time, several markets at a time.
+///
+/// The dataset is a directory of JSON lines files, read back with [`HistoryDataset`]:
+/// - `markets.jsonl`: every selected market
+/// - `candlesticks/<ticker>.jsonl`: the candlesticks of each market
+/// - `trades/<ticker>.jsonl`: the trades of each market
+/// - `progress.json`: the markets already downloaded
+///
+/// Downloads resume where they left off: markets listed in `progress.json` are skipped, a market
+/// interrupted halfway is downloaded again from the start.
+///
+/// ```
+/// let progress = HistoryDownloader::new("data/highny")
+///     .series("KXHIGHNY")
+///     .period(CandlestickPeriod::Hour)
+///     .concurrency(8)
+///     .on_progress(|p| println!("{}/{} markets", p.markets_done + p.markets_skipped, p.markets_total))
+///     .download(&kalshi_instance)
+///     .await?;
+///
+/// let dataset = HistoryDataset::open("data/highny");
+/// for market in dataset.markets()? {
+///     let candlesticks = dataset.candlesticks(&market.ticker)?;
+/// }
+/// ```
+#[derive(Clone)]
+pub struct HistoryDownloader {
+    dir: PathBuf,
+    series_ticker: Option<String>,
+    min_close_ts: Option<i64>,
+    max_close_ts: Option<i64>,
+    period: CandlestickPeriod,
+    concurrency: usize,
+    trades: bool,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl fmt::Debug for HistoryDownloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HistoryDownloader")
+            .field("dir", &self.dir)
+            .field("series_ticker", &self.series_ticker)
+            .field("min_close_ts", &self.min_close_ts)
+            .field("max_close_ts", &self.max_close_ts)
+            .field("period", &self.period)
+            .field("concurrency", &self.concurrency)
+            .field("trades", &self.trades)
+            .finish()
+    }
+}
+
+impl HistoryDownloader {
+    /// Downloads into `dir`, hourly candlesticks and trades of 4 markets at a time by default.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        HistoryDownloader {
+            dir: dir.as_ref().to_path_buf(),
+            series_ticker: None,
+            min_close_ts: None,
+            max_close_ts: None,
+            period: CandlestickPeriod::Hour,
+            concurrency: 4,
+            trades: true,
+            on_progress: None,
+        }
+    }
+
+    /// Only downloads the markets of a series.
+    pub fn series(mut self, series_ticker: &str) -> Self {
+        self.series_ticker = Some(series_ticker.to_string());
+        self
+    }
+
+    /// Only downloads the markets closing between two timestamps.
+    pub fn closing_between(mut self, min_close_ts: i64, max_close_ts: i64) -> Self {
+        self.min_close_ts = Some(min_close_ts);
+        self.max_close_ts = Some(max_close_ts);
+        self
+    }
+
+    pub fn period(mut self, period: CandlestickPeriod) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Number of markets downloaded at the same time.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Skips trades, which are by far the largest part of a dataset.
+    pub fn without_trades(mut self) -> Self {
+        self.trades = false;
+        self
+    }
+
+    /// Called after every market, downloaded or failed.
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(&DownloadProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Downloads every selected market not downloaded yet.
+    ///
+    /// Markets that fail are logged and counted in `markets_failed`, the next run retries them.
+    /// Returns an error if no market filter is set, the markets can't be listed or the dataset
+    /// can't be written.
+    pub async fn download(&self, kalshi: &Kalshi) -> Result<DownloadProgress, KalshiError> {
+        if self.series_ticker.is_none() && self.max_close_ts.is_none() {
+            return Err(KalshiError::UserInputError(
+                "A series or close time range is required to download history".to_string(),
+            ));
+        }
+        for dir in [
+            self.dir.clone(),
+            self.dir.join("candlesticks"),
+            self.dir.join("trades"),
+        ] {
+            tokio::fs::create_dir_all(&dir).await.map_err(io_error)?;
+        }
+
+        let mut markets = Vec::new();
+        {
+            let mut pages = Box::pin(
+                kalshi
+                    .get_multiple_markets(
+                        None,
+                        None,
+                        None,
+                        None,
+                        self.series_ticker.clone(),
+                        self.max_close_ts,
+                        self.min_close_ts,
+                        &[],
+                        Vec::new(),
+                    )
+                    .await,
+            );
+            while let Some(page) = pages.next().await {
+                markets.extend(page?);
+            }
+        }
+        write_lines(&self.dir.join("markets.jsonl"), &markets).await?;
+
+        let manifest_path = self.dir.join("progress.json");
+        let mut manifest: Manifest = store::load_json(&manifest_path, "download progress")?;
+
+        let mut progress = DownloadProgress {
+            markets_total: markets.len(),
+            ..DownloadProgress::default()
+        };
+        let pending: Vec<&Market> = markets
+            .iter()
+            .filter(|market| !manifest.completed.contains(&market.ticker))
+            .collect();
+        progress.markets_skipped = markets.len() - pending.len();
+
+        let kalshi: &Kalshi = kalshi;
+        let mut results = stream::iter(pending)
+            .map(|market| async move { (market, self.download_market(kalshi, market).await) })
+            .buffer_unordered(self.concurrency);
+        while let Some((market, result)) = results.next().await {
+            match result {
+                Ok((candlesticks, trades)) => {
+                    progress.markets_done += 1;
+                    progress.candlesticks += candlesticks;
+                    progress.trades += trades;
+                    manifest.completed.insert(market.ticker.clone());
+                    store::save_json(&manifest_path, "download progress", &manifest)?;
+                }
+                Err(e) => {
+                    log::warn!("Could not download history of {}: {}", market.ticker, e);
+                    progress.markets_failed += 1;
+                }
+            }
+            if let Some(callback) = &self.on_progress {
+                callback(&progress);
+            }
+        }
+        Ok(progress)
+    }
+
+    /// Downloads the candlesticks and trades of a market, returning how many of each were written.
+    async fn download_market(
+        &self,
+        kalshi: &Kalshi,
+        market: &Market,
+    ) -> Result<(usize, usize), KalshiError> {
+        let parse = |time: &str| {
+            chrono::DateTime::parse_from_rfc3339(time)
+                .map(|time| time.timestamp())
+                .map_err(|e| KalshiError::InternalError(format!("Invalid time {}: {}", time, e)))
+        };
+        let open_ts = parse(&market.open_time)?;
+        let close_ts = parse(&market.close_time)?.min(chrono::Utc::now().timestamp());
+        // Series tickers are the part of the event ticker before the first `-`
+        let series_ticker = match &self.series_ticker {
+            Some(series_ticker) => series_ticker.as_str(),
+            None => market
+                .event_ticker
+                .split('-')
+                .next()
+                .unwrap_or(&market.event_ticker),
+        };
+
+        let mut candlesticks: Vec<Candlestick> = Vec::new();
+        for (start_ts, end_ts) in candlestick_windows(open_ts, close_ts, self.period) {
+            candlesticks.extend(
+                kalshi
+                    .get_market_candlesticks(
+                        series_ticker,
+                        &market.ticker,
+                        start_ts,
+                        end_ts,
+                        self.period.minutes(),
+                    )
+                    .await?,
+            );
+        }
+        write_lines(
+            &self
+                .dir
+                .join("candlesticks")
+                .join(format!("{}.jsonl", market.ticker)),
+            &candlesticks,
+        )
+        .await?;
+
+        let mut trades: Vec<Trade> = Vec::new();
+        if self.trades {
+            let mut stream = Box::pin(
+                kalshi
+                    .get_trades(None, None, None, Some(market.ticker.clone()), None, None)
+                    .await,
+            );
+            while let Some(trade) = stream.next().await {
+                trades.push(trade?);
+            }
+            // Trades are returned newest first
+            trades.reverse();
+            write_lines(
+                &self
+                    .dir
+                    .join("trades")
+                    .join(format!("{}.jsonl", market.ticker)),
+                &trades,
+            )
+            .await?;
+        }
+
+        Ok((candlesticks.len(), trades.len()))
+    }
+}
+
+/// Splits `[start_ts, end_ts]` into windows that each fit in a single candlesticks request.
+fn candlestick_windows(start_ts: i64, end_ts: i64, period: CandlestickPeriod) -> Vec<(i64, i64)> {
+    let span = period.minutes() as i64 * 60 * MAX_CANDLESTICKS_PER_REQUEST;
+    let mut windows = Vec::new();
+    let mut start = start_ts;
+    while start < end_ts {
+        let end = (start + span).min(end_ts);
+        windows.push((start, end));
+        start = end + 1;
+    }
+    windows
+}
+
+fn io_error(e: std::io::Error) -> KalshiError {
+    KalshiError::InternalError(format!("Could not write history dataset: {}", e))
+}
+
+async fn write_lines<T: Serialize>(path: &Path, items: &[T]) -> Result<(), KalshiError> {
+    let mut bytes = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut bytes, item)
+            .map_err(|e| KalshiError::InternalError(e.to_string()))?;
+        bytes.push(b'\n');
+    }
+    tokio::fs::write(path, bytes).await.map_err(io_error)
+}
+
+/// A dataset written by [`HistoryDownloader`].
+#[derive(Debug, Clone)]
+pub struct HistoryDataset {
+    dir: PathBuf,
+}
+
+impl HistoryDataset {
+    pub fn open(dir: impl AsRef<Path>) -> Self {
+        HistoryDataset {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn markets(&self) -> Result<Vec<Market>, KalshiError> {
+        read_lines(&self.dir.join("markets.jsonl"))
+    }
+
+    /// The candlesticks of a market, oldest first.
+    pub fn candlesticks(&self, ticker: &str) -> Result<Vec<Candlestick>, KalshiError> {
+        read_lines(
+            &self
+                .dir
+                .join("candlesticks")
+                .join(format!("{}.jsonl", ticker)),
+        )
+    }
+
+    /// The trades of a market, oldest first.
+    pub fn trades(&self, ticker: &str) -> Result<Vec<Trade>, KalshiError> {
+        read_lines(&self.dir.join("trades").join(format!("{}.jsonl", ticker)))
+    }
+}
+
+fn read_lines<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, KalshiError> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        KalshiError::UserInputError(format!("Could not read {}: {}", path.display(), e))
+    })?;
+    content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| {
+                KalshiError::InternalError(format!("Invalid line in {}: {}", path.display(), e))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use reqwest::Method;
+    use serde_json::json;
+
+    #[test]
+    fn test_candlestick_windows_fit_requests() {
+        let day = 86_400;
+        assert_eq!(
+            candlestick_windows(0, 2 * day, CandlestickPeriod::Hour),
+            vec![(0, 2 * day)]
+        );
+
+        let windows = candlestick_windows(0, 7 * day, CandlestickPeriod::Minute);
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0], (0, 300_000));
+        assert_eq!(windows[1].0, 300_001);
+        assert_eq!(windows[2].1, 7 * day);
+        assert!(candlestick_windows(day, day, CandlestickPeriod::Day).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_download_resumes() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let mut other = fixtures::market();
+        other["ticker"] = "KXWNBAGAME-25SEP17PHXNYL-PHX".into();
+        server.respond(
+            Method::GET,
+            "/markets",
+            200,
+            json!({ "markets": [fixtures::market(), other], "cursor": "" }),
+        );
+        let candlesticks =
+            |ticker: &str| format!("/series/KXWNBAGAME/markets/{}/candlesticks", ticker);
+        server.respond(
+            Method::GET,
+            &candlesticks(fixtures::MARKET_TICKER),
+            200,
+            json!({ "ticker": fixtures::MARKET_TICKER, "candlesticks": [] }),
+        );
+        server.respond(
+            Method::GET,
+            "/markets/trades",
+            200,
+            json!({ "trades": [], "cursor": "" }),
+        );
+        let kalshi = server.kalshi().await.unwrap();
+        let dir = std::env::temp_dir().join(format!("kalshi-history-{}", uuid::Uuid::new_v4()));
+        let downloader = HistoryDownloader::new(&dir).series("KXWNBAGAME");
+
+        // The candlesticks of the second market can't be fetched yet
+        let first = downloader.download(&kalshi).await.unwrap();
+        assert_eq!((first.markets_done, first.markets_failed), (1, 1));
+        let manifest: Manifest = store::load_json(&dir.join("progress.json"), "test").unwrap();
+        assert_eq!(
+            manifest.completed.into_iter().collect::<Vec<_>>(),
+            vec![fixtures::MARKET_TICKER.to_string()]
+        );
+        assert!(!dir.join("progress.tmp").exists());
+
+        server.respond(
+            Method::GET,
+            &candlesticks("KXWNBAGAME-25SEP17PHXNYL-PHX"),
+            200,
+            json!({ "ticker": "KXWNBAGAME-25SEP17PHXNYL-PHX", "candlesticks": [] }),
+        );
+        let resumed = downloader.download(&kalshi).await.unwrap();
+        assert_eq!(
+            (
+                resumed.markets_skipped,
+                resumed.markets_done,
+                resumed.markets_failed
+            ),
+            (1, 1, 0)
+        );
+        assert_eq!(
+            server
+                .requests_to(Method::GET, &candlesticks(fixtures::MARKET_TICKER))
+                .len(),
+            1
+        );
+        let dataset = HistoryDataset::open(&dir);
+        assert_eq!(dataset.markets().unwrap().len(), 2);
+        assert!(dataset
+            .candlesticks("KXWNBAGAME-25SEP17PHXNYL-PHX")
+            .unwrap()
+            .is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
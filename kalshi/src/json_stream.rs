@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use futures::stream::Stream;
+use serde::de::DeserializeOwned;
+
+use crate::KalshiError;
+
+/// Splits the elements of one array out of a JSON object as its bytes arrive.
+///
+/// Only the array under `array_key` at the top level of the object is split, elements are
+/// returned raw so they can be deserialized one at a time. String values of the other top-level
+/// keys, such as the pagination cursor, are kept. The scanner doesn't validate the JSON, malformed
+/// input surfaces when the elements are deserialized.
+#[derive(Debug)]
+pub(crate) struct JsonArrayScanner {
+    array_key: &'static str,
+    buffer: Vec<u8>,
+    /// Next byte of `buffer` to scan
+    pos: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Start of the top-level string being scanned, after its opening quote
+    string_start: usize,
+    /// Whether the object is between a `:` and the next `,`
+    expect_value: bool,
+    /// The last top-level string, a key if a `:` follows it
+    pending_string: Option<String>,
+    last_key: Option<String>,
+    in_array: bool,
+    element_start: Option<usize>,
+    values: HashMap<String, String>,
+}
+
+impl JsonArrayScanner {
+    pub(crate) fn new(array_key: &'static str) -> Self {
+        JsonArrayScanner {
+            array_key,
+            buffer: Vec::new(),
+            pos: 0,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            string_start: 0,
+            expect_value: false,
+            pending_string: None,
+            last_key: None,
+            in_array: false,
+            element_start: None,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Scans the next chunk of the body, returning the array elements it completed.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Vec<u8>>, KalshiError> {
+        self.buffer.extend_from_slice(chunk);
+        let mut elements = Vec::new();
+        for i in self.pos..self.buffer.len() {
+            let c = self.buffer[i];
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if c == b'\\' {
+                    self.escaped = true;
+                } else if c == b'"' {
+                    self.in_string = false;
+                    if self.depth == 1 {
+                        let s = String::from_utf8_lossy(&self.buffer[self.string_start..i])
+                            .into_owned();
+                        match (&self.last_key, self.expect_value) {
+                            (Some(key), true) => {
+                                self.values.insert(key.clone(), s);
+                            }
+                            _ => self.pending_string = Some(s),
+                        }
+                    }
+                }
+                continue;
+            }
+            let in_elements = self.in_array && self.depth == 2;
+            match c {
+                b'"' => {
+                    self.in_string = true;
+                    if self.depth == 1 {
+                        self.string_start = i + 1;
+                    }
+                    if in_elements && self.element_start.is_none() {
+                        self.element_start = Some(i);
+                    }
+                }
+                b'{' | b'[' => {
+                    if in_elements && self.element_start.is_none() {
+                        self.element_start = Some(i);
+                    }
+                    if c == b'['
+                        && self.depth == 1
+                        && self.expect_value
+                        && self.last_key.as_deref() == Some(self.array_key)
+                    {
+                        self.in_array = true;
+                    }
+                    self.depth += 1;
+                }
+                b'}' | b']' => {
+                    if self.depth == 0 {
+                        return Err(KalshiError::InternalError(
+                            "Unbalanced JSON response".to_string(),
+                        ));
+                    }
+                    if c == b']' && in_elements {
+                        // The end of the array, completing a scalar element if there is one
+                        if let Some(start) = self.element_start.take() {
+                            elements.push(trim_end(&self.buffer[start..i]).to_vec());
+                        }
+                        self.in_array = false;
+                    }
+                    self.depth -= 1;
+                    if self.in_array && self.depth == 2 {
+                        if let Some(start) = self.element_start.take() {
+                            elements.push(self.buffer[start..=i].to_vec());
+                        }
+                    }
+                }
+                b':' if self.depth == 1 => {
+                    self.last_key = self.pending_string.take();
+                    self.expect_value = true;
+                }
+                b',' => {
+                    if self.depth == 1 {
+                        self.expect_value = false;
+                    }
+                    if in_elements {
+                        if let Some(start) = self.element_start.take() {
+                            elements.push(trim_end(&self.buffer[start..i]).to_vec());
+                        }
+                    }
+                }
+                b' ' | b'\n' | b'\r' | b'\t' => {}
+                _ => {
+                    if in_elements && self.element_start.is_none() {
+                        self.element_start = Some(i);
+                    }
+                }
+            }
+        }
+
+        // Drop the bytes no longer needed
+        let mut cut = self.buffer.len();
+        if let Some(start) = self.element_start {
+            cut = cut.min(start);
+        }
+        if self.in_string && self.depth == 1 {
+            cut = cut.min(self.string_start);
+        }
+        self.buffer.drain(..cut);
+        self.pos = self.buffer.len();
+        self.element_start = self.element_start.map(|start| start - cut);
+        self.string_start = self.string_start.saturating_sub(cut);
+        Ok(elements)
+    }
+
+    /// The string value of a top-level key seen so far.
+    pub(crate) fn value(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+}
+
+fn trim_end(bytes: &[u8]) -> &[u8] {
+    let end = bytes
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    &bytes[..end]
+}
+
+/// An item of a list response streamed by [`stream_json_array`].
+#[derive(Debug)]
+pub(crate) enum JsonArrayItem<T> {
+    Element(T),
+    /// The body ended, with the response's cursor if it had a non-empty one.
+    End {
+        cursor: Option<String>,
+    },
+}
+
+/// Deserializes the elements of the array under `array_key` as the response body arrives.
+pub(crate) fn stream_json_array<T: DeserializeOwned>(
+    mut response: reqwest::Response,
+    array_key: &'static str,
+) -> impl Stream<Item = Result<JsonArrayItem<T>, KalshiError>> {
+    async_stream::stream! {
+        if let Err(e) = response.error_for_status_ref() {
+            yield Err(KalshiError::from(e));
+            return;
+        }
+        let mut scanner = JsonArrayScanner::new(array_key);
+        loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) => {
+                    yield Err(KalshiError::from(e));
+                    return;
+                }
+            };
+            let elements = match scanner.feed(&chunk) {
+                Ok(elements) => elements,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+            for element in elements {
+                match serde_json::from_slice(&element) {
+                    Ok(element) => yield Ok(JsonArrayItem::Element(element)),
+                    Err(e) => {
+                        yield Err(KalshiError::InternalError(format!(
+                            "Failed to decode JSON response: {}",
+                            e
+                        )));
+                        return;
+                    }
+                }
+            }
+        }
+        let cursor = scanner
+            .value("cursor")
+            .filter(|cursor| !cursor.is_empty())
+            .map(str::to_string);
+        yield Ok(JsonArrayItem::End { cursor });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_elements_split_across_chunks() {
+        let body = br#"{"trades": [{"trade_id": "a", "note": "}],\"["}, {"trade_id": "b", "nested": {"x": [1, 2]}}], "cursor": "abc=="}"#;
+        for chunk_size in [1, 3, 7, body.len()] {
+            let mut scanner = JsonArrayScanner::new("trades");
+            let elements: Vec<serde_json::Value> = body
+                .chunks(chunk_size)
+                .flat_map(|chunk| scanner.feed(chunk).unwrap())
+                .map(|raw| serde_json::from_slice(&raw).unwrap())
+                .collect();
+            assert_eq!(elements.len(), 2);
+            assert_eq!(elements[0]["note"], "}],\"[");
+            assert_eq!(elements[1]["nested"]["x"][1], 2);
+            assert_eq!(scanner.value("cursor"), Some("abc=="));
+        }
+
+        // Scalars, and arrays under other keys are left alone
+        let mut scanner = JsonArrayScanner::new("ids");
+        let elements = scanner
+            .feed(br#"{"cursor": "", "other": [9], "ids": [1, "two" ,3]}"#)
+            .unwrap();
+        assert_eq!(
+            elements,
+            vec![b"1".to_vec(), b"\"two\"".to_vec(), b"3".to_vec()]
+        );
+        assert_eq!(scanner.value("cursor"), Some(""));
+    }
+}
@@ -18,6 +18,8 @@ pub enum KalshiError {
     UserInputError(String),
     /// Errors representing unexpected internal issues or situations that are not supposed to happen.
     InternalError(String),
+    /// An order request the exchange refused, with the reason it gave.
+    TradingError(TradingError),
     // TODO: add error type specifically for joining threads together.
 }
 
@@ -26,6 +28,7 @@ impl fmt::Display for KalshiError {
         match self {
             KalshiError::RequestError(e) => write!(f, "HTTP Error: {}", e),
             KalshiError::UserInputError(e) => write!(f, "User Input Error: {}", e),
+            KalshiError::TradingError(e) => write!(f, "Trading Error: {}", e),
             KalshiError::InternalError(e) => write!(f, "INTERNAL ERROR, PLEASE EMAIL DEVELOPER OR MAKE A NEW ISSUE ON THE CRATE'S REPOSITORY: https://github.com/dpeachpeach/kalshi-rust. Specific Error: {}", e)
         }
     }
@@ -37,6 +40,26 @@ impl Error for KalshiError {
             KalshiError::RequestError(e) => Some(e),
             KalshiError::UserInputError(_) => None,
             KalshiError::InternalError(_) => None,
+            KalshiError::TradingError(_) => None,
+        }
+    }
+}
+
+impl KalshiError {
+    /// Why the exchange refused an order request, `None` for other errors.
+    ///
+    /// ```
+    /// match kalshi_instance.create_order(...).await {
+    ///     Err(e) if e.trading_error_kind() == Some(TradingErrorKind::InsufficientBalance) => {
+    ///         // Try again with fewer contracts
+    ///     }
+    ///     result => handle(result),
+    /// }
+    /// ```
+    pub fn trading_error_kind(&self) -> Option<TradingErrorKind> {
+        match self {
+            KalshiError::TradingError(e) => Some(e.kind),
+            _ => None,
         }
     }
 }
@@ -115,3 +138,207 @@ impl Error for RequestError {
         }
     }
 }
+
+/// Why the exchange refused an order request, parsed from the `code` of its error payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TradingErrorKind {
+    /// The balance can't cover the order.
+    InsufficientBalance,
+    /// The market or the exchange isn't open for trading.
+    MarketClosed,
+    /// The order doesn't exist or isn't the account's.
+    OrderNotFound,
+    /// The order already filled or was canceled, so it can't be canceled or changed.
+    OrderAlreadyClosed,
+    /// The order would have traded against one of the account's own orders.
+    SelfTradePrevented,
+    /// The order would take the position over the market's limit.
+    PositionLimitExceeded,
+    /// Too many requests, the order can be sent again later.
+    RateLimited,
+    /// The order's parameters were refused, e.g. an out of range price.
+    InvalidOrder,
+    /// Any other refusal, see the error's code and message.
+    Other,
+}
+
+impl TradingErrorKind {
+    /// The kind of a Kalshi error code, answered with `status`.
+    pub fn from_code(code: &str, status: u16) -> Self {
+        match code.to_ascii_lowercase().as_str() {
+            "insufficient_balance" | "insufficient_funds" => TradingErrorKind::InsufficientBalance,
+            "market_closed" | "market_not_open" | "market_inactive" | "market_not_active"
+            | "exchange_closed" | "exchange_inactive" | "trading_is_paused" => {
+                TradingErrorKind::MarketClosed
+            }
+            "order_not_found" | "not_found" => TradingErrorKind::OrderNotFound,
+            "order_already_filled"
+            | "order_already_executed"
+            | "order_already_canceled"
+            | "order_already_cancelled"
+            | "order_not_resting"
+            | "order_closed" => TradingErrorKind::OrderAlreadyClosed,
+            "self_trade" | "self_trade_prevented" | "self_trade_prevention" => {
+                TradingErrorKind::SelfTradePrevented
+            }
+            "position_limit_exceeded"
+            | "exceeds_position_limit"
+            | "max_position_exceeded"
+            | "risk_limit_exceeded" => TradingErrorKind::PositionLimitExceeded,
+            "too_many_requests" | "rate_limited" | "rate_limit_exceeded" => {
+                TradingErrorKind::RateLimited
+            }
+            "invalid_order" | "invalid_parameters" | "invalid_price" | "bad_request" => {
+                TradingErrorKind::InvalidOrder
+            }
+            _ => match status {
+                404 => TradingErrorKind::OrderNotFound,
+                409 => TradingErrorKind::OrderAlreadyClosed,
+                429 => TradingErrorKind::RateLimited,
+                _ => TradingErrorKind::Other,
+            },
+        }
+    }
+}
+
+/// An order request the exchange refused, see [`KalshiError::TradingError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradingError {
+    pub kind: TradingErrorKind,
+    /// The HTTP status of the response.
+    pub status: u16,
+    /// Kalshi's error code, e.g. `insufficient_balance`, empty when the response had none.
+    pub code: String,
+    pub message: String,
+}
+
+impl fmt::Display for TradingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}, status {} {}: {}",
+            self.kind, self.status, self.code, self.message
+        )
+    }
+}
+
+impl Error for TradingError {}
+
+#[derive(serde::Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+/// The error Kalshi sends for a refused request, also the per-order error of a batch.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ErrorBody {
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    details: Option<String>,
+}
+
+impl ErrorBody {
+    pub(crate) fn into_trading_error(self, status: u16) -> TradingError {
+        let message = match self.details.filter(|d| !d.is_empty()) {
+            Some(details) => format!("{} ({})", self.message, details),
+            None => self.message,
+        };
+        TradingError {
+            kind: TradingErrorKind::from_code(&self.code, status),
+            status,
+            code: self.code,
+            message,
+        }
+    }
+}
+
+/// Passes successful responses of order endpoints through, turning client errors into
+/// [`KalshiError::TradingError`] and server errors into request errors.
+pub(crate) async fn check_trading_response(
+    response: reqwest::Response,
+) -> Result<reqwest::Response, KalshiError> {
+    let status = response.status();
+    if !status.is_client_error() {
+        return response.error_for_status().map_err(KalshiError::from);
+    }
+    let error = match response.json::<ErrorResponse>().await {
+        Ok(ErrorResponse { error }) => error,
+        Err(_) => ErrorBody {
+            code: String::new(),
+            message: status.canonical_reason().unwrap_or_default().to_string(),
+            details: None,
+        },
+    };
+    Err(KalshiError::TradingError(
+        error.into_trading_error(status.as_u16()),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use crate::{Action, OrderType, Side};
+    use reqwest::Method;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_order_errors_parsed() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        server.respond(
+            Method::POST,
+            "/portfolio/orders",
+            400,
+            json!({"error": {"code": "insufficient_balance", "message": "Insufficient balance"}}),
+        );
+        server.respond(
+            Method::DELETE,
+            &format!("/portfolio/orders/{}", fixtures::ORDER_ID),
+            404,
+            json!({"error": {"code": "not_found", "message": "order not found"}}),
+        );
+        let kalshi = server.kalshi().await.unwrap();
+
+        let error = kalshi
+            .create_order(
+                Action::Buy,
+                None,
+                10,
+                Side::Yes,
+                fixtures::MARKET_TICKER.to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                None,
+                None,
+                Some(45),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.trading_error_kind(),
+            Some(TradingErrorKind::InsufficientBalance)
+        );
+        match error {
+            KalshiError::TradingError(e) => assert_eq!(e.message, "Insufficient balance"),
+            e => panic!("Unexpected error {}", e),
+        }
+
+        let error = kalshi.cancel_order(fixtures::ORDER_ID).await.unwrap_err();
+        assert_eq!(
+            error.trading_error_kind(),
+            Some(TradingErrorKind::OrderNotFound)
+        );
+        assert_eq!(
+            TradingErrorKind::from_code("ORDER_ALREADY_CANCELED", 400),
+            TradingErrorKind::OrderAlreadyClosed
+        );
+        assert_eq!(
+            TradingErrorKind::from_code("something_new", 429),
+            TradingErrorKind::RateLimited
+        );
+    }
+}
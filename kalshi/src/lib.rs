@@ -102,7 +102,7 @@
 //! use kalshi::TradingEnvironment;
 //! let kalshi_instance = Kalshi::new(TradingEnvironment::DemoMode);
 //!
-//! kalshi_instance.get_multiple_events(Some(5), None, None, None, None).await.unwrap();
+//! kalshi_instance.get_multiple_events(Some(5), None, None, None, None, None).await.unwrap();
 //! ```
 //! #### Checking the User's balance
 //! Returns an i64 representing the user's balance in cents.
@@ -115,22 +115,86 @@
 //! ```
 //!
 
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex, RwLock},
+};
 use url::Url;
 
 #[macro_use]
 mod utils;
+mod api;
 mod auth;
+mod bars;
+mod book;
+mod book_check;
+mod builder;
+mod cache;
+mod candles;
+mod checkpoint;
+mod config;
+mod dry_run;
+mod edge;
+mod events;
 mod exchange;
+mod execution;
+#[cfg(any(feature = "csv", feature = "arrow"))]
+mod export;
+#[cfg(feature = "fix")]
+mod fix;
+#[cfg(feature = "history")]
+mod history;
+#[cfg(feature = "streaming")]
+mod json_stream;
 mod kalshi_error;
 mod market;
+mod money;
 mod portfolio;
+mod preview;
+mod registry;
+mod risk;
+mod rolling;
+mod scanner;
+mod shutdown;
+mod sim;
+mod sizing;
+mod store;
+#[cfg(feature = "websockets")]
+mod strategy;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod ticker;
+mod timing;
+mod tls;
+mod tracking;
+mod triggers;
+mod validation;
 #[cfg(feature = "websockets")]
 mod websockets;
+mod wire;
 
+pub use api::*;
+pub use bars::*;
+pub use book::*;
+pub use book_check::*;
+pub use builder::*;
+pub use cache::*;
+pub use candles::*;
+pub use checkpoint::*;
+pub use config::*;
+pub use edge::*;
+pub use events::*;
 pub use exchange::*;
+pub use execution::*;
+#[cfg(any(feature = "csv", feature = "arrow"))]
+pub use export::*;
+#[cfg(feature = "fix")]
+pub use fix::*;
+#[cfg(feature = "history")]
+pub use history::*;
 pub use kalshi_error::*;
 pub use market::*;
+pub use money::*;
 use openssl::{
     hash::MessageDigest,
     pkey::{PKey, Private},
@@ -138,9 +202,26 @@ use openssl::{
     sign::{RsaPssSaltlen, Signer},
 };
 pub use portfolio::*;
+pub use preview::*;
+pub use registry::*;
+pub use risk::*;
+pub use rolling::*;
+pub use scanner::*;
+pub use shutdown::*;
+pub use sim::*;
+pub use sizing::*;
+#[cfg(feature = "websockets")]
+pub use strategy::*;
+pub use ticker::*;
+pub use timing::*;
+pub use tls::*;
+pub use tracking::*;
+pub use triggers::*;
 
 #[cfg(feature = "websockets")]
 pub use websockets::*;
+#[cfg(feature = "wire-logging")]
+pub use wire::*;
 
 // imports
 use reqwest;
@@ -158,21 +239,50 @@ use reqwest;
 /// let kalshi_instance = Kalshi::new(TradingEnvironment::DemoMode);
 /// ```
 ///
+/// ## Sharing an instance between tasks
 ///
+/// `Kalshi` is `Send + Sync` and every request method takes `&self`, so a single instance behind
+/// an `Arc` can serve any number of tokio tasks. The login token is behind a lock and the API key
+/// signer is locked only while signing. Clones share the login session and reuse the parsed key.
+///
+/// ```
+/// let kalshi = Arc::new(Kalshi::new_with_api_key(TradingEnvironment::DemoMode, key_id, pem));
+/// for ticker in tickers {
+///     let kalshi = kalshi.clone();
+///     tokio::spawn(async move { kalshi.get_market_orderbook(&ticker, Some(5)).await });
+/// }
+/// ```
 #[derive(Clone)]
 pub struct Kalshi {
     /// - `base_url`: The base URL for the API, determined by the trading environment.
     base_url: String,
     #[cfg(feature = "websockets")]
     ws_url: String,
-    /// - `curr_token`: A field for storing the current authentication token.
-    curr_token: Option<String>,
-    /// - `member_id`: A field for storing the member ID.
-    member_id: Option<String>,
+    /// - `ws_config`: Settings of the websocket clients connected through this instance
+    #[cfg(feature = "websockets")]
+    ws_config: websockets::client::KalshiWebsocketConfig,
+    /// - `curr_token`: A field for storing the current authentication token, shared by clones.
+    curr_token: Arc<RwLock<Option<String>>>,
+    /// - `member_id`: A field for storing the member ID, shared by clones.
+    member_id: Arc<RwLock<Option<String>>>,
     /// - `client`: The HTTP client used for making requests to the marketplace.
     client: reqwest::Client,
     /// - `auth`: Stores the method of authentication to use and any required inputs (key for example)
     auth: KalshiAuth,
+    /// - `order_tracker`: Notified of every order placed, decreased or canceled through this instance
+    order_tracker: Option<OrderTracker>,
+    /// - `risk_manager`: Checks every order placed through this instance before it's sent
+    risk_manager: Option<RiskManager>,
+    /// - `metadata_cache`: Serves markets, events and series fetched through this instance until they expire
+    metadata_cache: Option<MetadataCache>,
+    /// - `dry_run`: When set, order-routing methods are validated and logged but never sent
+    dry_run: Option<dry_run::DryRun>,
+    /// - `validate_orders`: When set, orders are checked against their market before they're sent
+    validate_orders: bool,
+    /// - `tls`: TLS settings of the websocket and FIX connections, the REST client's are applied when it's built
+    tls: Option<TlsConfig>,
+    /// - `shutdown`: Stops the background tasks started through this instance, shared by clones
+    shutdown: Shutdown,
 }
 
 pub enum KalshiAuth {
@@ -187,17 +297,23 @@ pub enum KalshiAuth {
         key: String,
         /// - `p_key`: The private key loaded
         p_key: Arc<PKey<Private>>,
-        /// - `signer`: If using apiKey auth, stores the RSA signer for the passed key
-        signer: Signer<'static>,
+        /// - `signer`: If using apiKey auth, stores the RSA signer for the passed key, locked while
+        ///   signing so requests can be authenticated through a shared reference
+        signer: Mutex<Signer<'static>>,
     },
 }
 
 impl Clone for KalshiAuth {
     fn clone(&self) -> Self {
         match &self {
-            KalshiAuth::ApiKey { key_id, key, .. } => {
-                KalshiAuth::build_api_key(key_id.clone(), key.clone())
-            }
+            KalshiAuth::ApiKey {
+                key_id, key, p_key, ..
+            } => KalshiAuth::ApiKey {
+                key_id: key_id.clone(),
+                key: key.clone(),
+                p_key: p_key.clone(),
+                signer: Mutex::new(KalshiAuth::build_signer(p_key)),
+            },
             KalshiAuth::EmailPassword => KalshiAuth::EmailPassword,
         }
     }
@@ -205,9 +321,22 @@ impl Clone for KalshiAuth {
 
 impl KalshiAuth {
     fn build_api_key(key_id: String, key: String) -> Self {
-        let p_key = PKey::private_key_from_pem(key.as_bytes())
-            .expect("Unable to load private key from pem string provided");
-        let mut signer = Signer::new(MessageDigest::sha256(), &p_key)
+        let p_key = Arc::new(
+            PKey::private_key_from_pem(key.as_bytes())
+                .expect("Unable to load private key from pem string provided"),
+        );
+        let signer = Self::build_signer(&p_key);
+        KalshiAuth::ApiKey {
+            key_id,
+            key,
+            p_key,
+            signer: Mutex::new(signer),
+        }
+    }
+
+    /// A signer for the already parsed key, cheap compared to parsing the PEM again.
+    fn build_signer(p_key: &PKey<Private>) -> Signer<'static> {
+        let mut signer = Signer::new(MessageDigest::sha256(), p_key)
             .expect("Unable to load signer from private key");
         signer
             .set_rsa_padding(Padding::PKCS1_PSS)
@@ -215,12 +344,7 @@ impl KalshiAuth {
         signer
             .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
             .expect("Unable to set rsa pss salt length for signer");
-        KalshiAuth::ApiKey {
-            key_id,
-            key,
-            p_key: Arc::new(p_key),
-            signer,
-        }
+        signer
     }
 }
 
@@ -251,10 +375,19 @@ impl Kalshi {
             base_url: utils::build_base_url(trading_env).to_string(),
             #[cfg(feature = "websockets")]
             ws_url: utils::build_ws_url(trading_env).to_string(),
-            curr_token: None,
-            member_id: None,
+            #[cfg(feature = "websockets")]
+            ws_config: Default::default(),
+            curr_token: Arc::default(),
+            member_id: Arc::default(),
             client: reqwest::Client::new(),
             auth: KalshiAuth::EmailPassword,
+            order_tracker: None,
+            risk_manager: None,
+            metadata_cache: None,
+            dry_run: None,
+            validate_orders: false,
+            tls: None,
+            shutdown: Shutdown::new(),
         };
     }
 
@@ -288,10 +421,19 @@ impl Kalshi {
             base_url: utils::build_base_url(trading_env).to_string(),
             #[cfg(feature = "websockets")]
             ws_url: utils::build_ws_url(trading_env).to_string(),
-            curr_token: None,
-            member_id: None,
+            #[cfg(feature = "websockets")]
+            ws_config: Default::default(),
+            curr_token: Arc::default(),
+            member_id: Arc::default(),
             client: reqwest::Client::new(),
             auth: KalshiAuth::build_api_key(key_id, key),
+            order_tracker: None,
+            risk_manager: None,
+            metadata_cache: None,
+            dry_run: None,
+            validate_orders: false,
+            tls: None,
+            shutdown: Shutdown::new(),
         };
     }
 
@@ -316,10 +458,10 @@ impl Kalshi {
     /// ```
     ///
     pub fn get_user_token(&self) -> Option<String> {
-        match &self.curr_token {
-            Some(val) => return Some(val.clone()),
-            _ => return None,
-        }
+        self.curr_token
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
     }
 
     /// Retrieves the currently set base url
@@ -332,6 +474,124 @@ impl Kalshi {
         &self.base_url
     }
 
+    /// Overrides the REST base url picked from the trading environment,
+    /// for example to send requests through a proxy or to a `testing::MockHttpServer`.
+    pub fn set_base_url(&mut self, base_url: &str) {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+    }
+
+    /// Attaches an [`OrderTracker`] that records every order placed, decreased or canceled through this instance.
+    ///
+    /// Clones of this instance made afterwards report to the same tracker.
+    ///
+    /// # Example
+    /// ```
+    /// let tracker = OrderTracker::new();
+    /// kalshi_instance.set_order_tracker(tracker.clone());
+    /// ```
+    pub fn set_order_tracker(&mut self, tracker: OrderTracker) {
+        self.order_tracker = Some(tracker);
+    }
+
+    /// Retrieves the attached [`OrderTracker`], if any.
+    pub fn get_order_tracker(&self) -> Option<&OrderTracker> {
+        self.order_tracker.as_ref()
+    }
+
+    /// Attaches a [`RiskManager`] that checks every order placed through this instance before it's sent.
+    ///
+    /// Clones of this instance made afterwards are checked by the same manager.
+    ///
+    /// # Example
+    /// ```
+    /// let risk = RiskManager::new(RiskLimits {
+    ///     max_position_per_market: Some(500),
+    ///     ..Default::default()
+    /// });
+    /// kalshi_instance.set_risk_manager(risk.clone());
+    /// ```
+    pub fn set_risk_manager(&mut self, risk_manager: RiskManager) {
+        self.risk_manager = Some(risk_manager);
+    }
+
+    /// Retrieves the attached [`RiskManager`], if any.
+    pub fn get_risk_manager(&self) -> Option<&RiskManager> {
+        self.risk_manager.as_ref()
+    }
+
+    /// Attaches a [`MetadataCache`] that serves `get_single_market`, `get_single_event` and `get_series`
+    /// from memory until the cached value expires.
+    ///
+    /// Clones of this instance made afterwards share the same cache.
+    ///
+    /// # Example
+    /// ```
+    /// kalshi_instance.set_metadata_cache(MetadataCache::new(CacheTtls::default()));
+    /// ```
+    pub fn set_metadata_cache(&mut self, cache: MetadataCache) {
+        self.metadata_cache = Some(cache);
+    }
+
+    /// Retrieves the attached [`MetadataCache`], if any.
+    pub fn get_metadata_cache(&self) -> Option<&MetadataCache> {
+        self.metadata_cache.as_ref()
+    }
+
+    /// Turns dry-run mode on or off.
+    ///
+    /// In dry-run mode `create_order`, `cancel_order`, `decrease_order` and their batch variants
+    /// run the same local validation and risk checks as usual, log the request they would send and
+    /// return a synthetic response instead of reaching the exchange. Dry-run orders rest until
+    /// they're canceled and never fill, and attached trackers are notified of them as if they were
+    /// real. Market data and account requests are unaffected, so a strategy can run against live
+    /// data without trading.
+    ///
+    /// Clones of this instance made afterwards share the same dry-run orders. Turning the mode
+    /// off forgets them.
+    ///
+    /// # Example
+    /// ```
+    /// kalshi_instance.set_dry_run(true);
+    /// let order = kalshi_instance.create_order(Action::Buy, None, 10, Side::Yes, ticker, OrderType::Limit, None, None, None, None, Some(40)).await?;
+    /// assert!(order.order_id.starts_with("dry-run-"));
+    /// ```
+    pub fn set_dry_run(&mut self, enabled: bool) {
+        match (enabled, &self.dry_run) {
+            (true, None) => self.dry_run = Some(dry_run::DryRun::default()),
+            (false, _) => self.dry_run = None,
+            _ => {}
+        }
+    }
+
+    /// Turns local order validation on or off.
+    ///
+    /// When on, `create_order` and its batch variant fetch the order's market (from the
+    /// [`MetadataCache`] when one is attached) and check the order with [`Market::validate_order`]
+    /// before sending it, so orders the exchange would reject fail fast with a descriptive
+    /// [`KalshiError::UserInputError`]. Prices outside of 1 to 99 cents are always rejected locally.
+    ///
+    /// # Example
+    /// ```
+    /// kalshi_instance.set_metadata_cache(MetadataCache::new(CacheTtls::default()));
+    /// kalshi_instance.set_validate_orders(true);
+    /// ```
+    pub fn set_validate_orders(&mut self, enabled: bool) {
+        self.validate_orders = enabled;
+    }
+
+    /// Whether dry-run mode is on, see [`Kalshi::set_dry_run`].
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// The orders placed in dry-run mode, with their current status.
+    pub fn get_dry_run_orders(&self) -> Vec<Order> {
+        self.dry_run
+            .as_ref()
+            .map(|dry_run| dry_run.orders())
+            .unwrap_or_default()
+    }
+
     /// Constructs the full API path for use in authentication signatures.
     ///
     /// This method takes a relative path (e.g., "markets", "events") and combines it
@@ -395,7 +655,7 @@ impl Kalshi {
 /// This enum is used to specify whether the interaction with the Kalshi API should be in a demo (simulated) environment
 /// or in the live market with real financial transactions.
 ///
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TradingEnvironment {
     /// The demo mode represents a simulated environment where trades do not involve real money.
     /// This mode is typically used for testing and practice purposes.
@@ -408,3 +668,63 @@ pub enum TradingEnvironment {
     // Legacy only markets
     LegacyLiveMarketMode,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[tokio::test]
+    async fn test_shared_instance_used_from_many_tasks() {
+        assert_send_sync::<Kalshi>();
+
+        let mut kalshi = Kalshi::new_with_api_key(
+            TradingEnvironment::DemoMode,
+            "key-id".to_string(),
+            testing::throwaway_private_key(),
+        );
+        kalshi.set_dry_run(true);
+        let kalshi = Arc::new(kalshi);
+
+        let tasks: Vec<_> = (0..8)
+            .map(|i| {
+                let kalshi = kalshi.clone();
+                tokio::spawn(async move {
+                    for _ in 0..10 {
+                        let headers = kalshi
+                            .generate_auth_headers("/trade-api/v2/markets", reqwest::Method::GET)
+                            .unwrap();
+                        assert!(headers.contains_key("KALSHI-ACCESS-SIGNATURE"));
+                        kalshi
+                            .create_order(
+                                Action::Buy,
+                                None,
+                                1,
+                                Side::Yes,
+                                format!("KXTEST-{}", i),
+                                OrderType::Limit,
+                                None,
+                                None,
+                                None,
+                                None,
+                                Some(40),
+                            )
+                            .await
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(kalshi.get_dry_run_orders().len(), 80);
+
+        // Clones reuse the parsed key and still sign
+        let clone = (*kalshi).clone();
+        assert!(clone
+            .generate_auth_headers("/trade-api/v2/markets", reqwest::Method::GET)
+            .is_ok());
+    }
+}
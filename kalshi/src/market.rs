@@ -1,11 +1,16 @@
 use super::Kalshi;
 use crate::kalshi_error::*;
-use futures::stream::Stream;
+use crate::money::Money;
+use crate::utils;
+use crate::wire::SendLogged;
+use futures::stream::{Stream, StreamExt};
 use log;
 use reqwest::Method;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 
-fn empty_string_as_none<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+pub(crate) fn empty_string_as_none<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -42,7 +47,10 @@ where
     }
 }
 
-fn update_cursor_param(params: &mut Vec<(&str, String)>, cursor: &Option<String>) -> bool {
+pub(crate) fn update_cursor_param(
+    params: &mut Vec<(&str, String)>,
+    cursor: &Option<String>,
+) -> bool {
     match cursor {
         Some(c) => {
             // Check if cursor is already in params
@@ -59,6 +67,76 @@ fn update_cursor_param(params: &mut Vec<(&str, String)>, cursor: &Option<String>
     }
 }
 
+pub(crate) fn set_param<'a>(params: &mut Vec<(&'a str, String)>, key: &'a str, value: String) {
+    match params.iter_mut().find(|(k, _)| *k == key) {
+        Some(param) => param.1 = value,
+        None => params.push((key, value)),
+    }
+}
+
+/// Joins the values of a list filter into its comma separated query value, `None` when empty.
+fn join_filter(name: &str, values: &[String]) -> Result<Option<String>, KalshiError> {
+    if let Some(value) = values
+        .iter()
+        .find(|v| v.trim().is_empty() || v.contains(','))
+    {
+        return Err(KalshiError::UserInputError(format!(
+            "Invalid {} filter value {:?}, values can't be empty or contain commas",
+            name, value
+        )));
+    }
+    Ok((!values.is_empty()).then(|| values.join(",")))
+}
+
+/// Joins market statuses into their comma separated query value, `None` when empty.
+fn join_statuses(statuses: &[MarketStatus]) -> Option<String> {
+    let statuses: Vec<&str> = statuses.iter().map(|status| status.as_str()).collect();
+    (!statuses.is_empty()).then(|| statuses.join(","))
+}
+
+/// Sizes the pages of a paginated listing.
+///
+/// `limit` caps the items returned over every page, `None` fetches everything. Each request asks
+/// for `page_size` items, or the default, fewer once the limit is close, so memory use is bounded
+/// by the page size however many items are fetched.
+#[derive(Debug, Clone)]
+pub(crate) struct Pager {
+    limit: Option<usize>,
+    page_size: usize,
+    fetched: usize,
+}
+
+impl Pager {
+    pub(crate) fn new(
+        limit: Option<i64>,
+        page_size: Option<i64>,
+        default_page_size: usize,
+    ) -> Self {
+        Pager {
+            limit: limit.map(|limit| limit.max(0) as usize),
+            page_size: page_size.map_or(default_page_size, |size| size.max(1) as usize),
+            fetched: 0,
+        }
+    }
+
+    /// The size of the next page to request, `None` once the limit is reached.
+    pub(crate) fn next_page_size(&self) -> Option<usize> {
+        match self.limit {
+            Some(limit) if self.fetched >= limit => None,
+            Some(limit) => Some(self.page_size.min(limit - self.fetched)),
+            None => Some(self.page_size),
+        }
+    }
+
+    pub(crate) fn record(&mut self, count: usize) {
+        self.fetched += count;
+    }
+
+    pub(crate) fn fetched(&self) -> usize {
+        self.fetched
+    }
+}
+
 impl Kalshi {
     /// Retrieves detailed information about a specific event from the Kalshi exchange.
     ///
@@ -80,6 +158,15 @@ impl Kalshi {
         event_ticker: &String,
         with_nested_markets: Option<bool>,
     ) -> Result<Event, KalshiError> {
+        let nested = with_nested_markets.unwrap_or(false);
+        if let Some(event) = self
+            .metadata_cache
+            .as_ref()
+            .and_then(|cache| cache.event(event_ticker, nested))
+        {
+            return Ok(event);
+        }
+
         let single_event_url: &str =
             &format!("{}/events/{}", self.base_url.to_string(), event_ticker);
 
@@ -93,14 +180,12 @@ impl Kalshi {
                 panic!("Internal Parse Error, please contact developer!");
             });
 
-        let result: SingleEventResponse = self
-            .client
-            .get(single_event_url)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: SingleEventResponse =
+            utils::parse_json(self.client.get(single_event_url).send_logged().await?).await?;
 
+        if let Some(cache) = &self.metadata_cache {
+            cache.insert_event(result.event.clone(), nested);
+        }
         return Ok(result.event);
     }
 
@@ -119,16 +204,22 @@ impl Kalshi {
     /// let market = kalshi_instance.get_single_event(market_ticker).await.unwrap();
     /// ```
     pub async fn get_single_market(&self, ticker: &String) -> Result<Market, KalshiError> {
+        if let Some(market) = self
+            .metadata_cache
+            .as_ref()
+            .and_then(|cache| cache.market(ticker))
+        {
+            return Ok(market);
+        }
+
         let single_market_url: &str = &format!("{}/markets/{}", self.base_url.to_string(), ticker);
 
-        let result: SingleMarketResponse = self
-            .client
-            .get(single_market_url)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: SingleMarketResponse =
+            utils::parse_json(self.client.get(single_market_url).send_logged().await?).await?;
 
+        if let Some(cache) = &self.metadata_cache {
+            cache.insert_market(result.market.clone());
+        }
         return Ok(result.market);
     }
     /// Asynchronously retrieves information about multiple markets from the Kalshi exchange.
@@ -137,14 +228,15 @@ impl Kalshi {
     /// It supports pagination, time-based filtering, and selection by specific tickers or statuses.
     ///
     /// # Arguments
-    /// * `limit` - An optional integer to limit the number of markets returned.
-    /// * `cursor` - An optional string for pagination cursor.
+    /// * `limit` - An optional total number of markets to return, every page is fetched when `None`.
+    /// * `page_size` - An optional number of markets requested per page.
+    /// * `cursor` - An optional cursor to resume from, as returned by the `*_page` variants.
     /// * `event_ticker` - An optional string to filter markets by event ticker.
     /// * `series_ticker` - An optional string to filter markets by series ticker.
     /// * `max_close_ts` - An optional timestamp for the maximum close time.
     /// * `min_close_ts` - An optional timestamp for the minimum close time.
-    /// * `status` - An optional string to filter markets by their status.
-    /// * `tickers` - An optional string to filter markets by specific tickers.
+    /// * `status` - Statuses to filter markets by, markets of any status are returned when empty.
+    /// * `tickers` - Tickers of the markets to return, no ticker filter when empty.
     ///
     /// # Returns
     /// - `Ok((Option<String>, Vec<Market>))`: A tuple containing an optional pagination cursor and a vector of `Market` objects on success.
@@ -157,33 +249,44 @@ impl Kalshi {
     /// let markets_result = kalshi_instance.get_multiple_markets(
     ///     Some(10),
     ///     None,
-    ///     Some("event_ticker"),
     ///     None,
+    ///     Some("event_ticker"),
     ///     None,
     ///     None,
     ///     None,
-    ///     None
+    ///     &[MarketStatus::Open],
+    ///     Vec::new()
     /// ).await.unwrap();
     /// ```
+    #[allow(clippy::too_many_arguments)]
     pub async fn get_multiple_markets(
-        &mut self,
+        &self,
         limit: Option<i64>,
+        page_size: Option<i64>,
+        cursor: Option<String>,
         event_ticker: Option<String>,
         series_ticker: Option<String>,
         max_close_ts: Option<i64>,
         min_close_ts: Option<i64>,
-        status: Option<String>,
-        tickers: Option<String>,
+        status: &[MarketStatus],
+        tickers: Vec<String>,
     ) -> impl Stream<Item = Result<Vec<Market>, KalshiError>> + '_ {
+        let status = join_statuses(status);
+        let tickers = join_filter("tickers", &tickers);
         async_stream::stream! {
+            let tickers = match tickers {
+                Ok(tickers) => tickers,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
             let markets_url = format!("{}/markets", self.base_url);
             let mut params: Vec<(&str, String)> = Vec::with_capacity(10);
-            let retrieve_all = limit.is_none();
-            let mut total_market_count = 0;
+            let mut pager = Pager::new(limit, page_size, 200);
 
-            let req_limit = Some(limit.unwrap_or(200));
+            add_param!(params, "cursor", cursor);
 
-            add_param!(params, "limit", req_limit);
             add_param!(params, "event_ticker", event_ticker);
             add_param!(params, "series_ticker", series_ticker);
             add_param!(params, "status", status);
@@ -191,7 +294,8 @@ impl Kalshi {
             add_param!(params, "max_close_ts", max_close_ts);
             add_param!(params, "tickers", tickers);
 
-            loop {
+            while let Some(page_size) = pager.next_page_size() {
+                set_param(&mut params, "limit", page_size.to_string());
                 let markets_url = reqwest::Url::parse_with_params(&markets_url, &params)
                     .unwrap_or_else(|err| {
                         eprintln!("{:?}", err);
@@ -212,11 +316,11 @@ impl Kalshi {
                     request = request.header(key, value);
                 }
 
-                let result: PublicMarketsResponse = match request.send().await {
-                    Ok(response) => match response.json().await {
+                let result: PublicMarketsResponse = match request.send_logged().await {
+                    Ok(response) => match utils::parse_json(response).await {
                         Ok(data) => data,
                         Err(e) => {
-                            yield Err(KalshiError::from(e));
+                            yield Err(e);
                             break;
                         }
                     },
@@ -227,18 +331,14 @@ impl Kalshi {
                 };
 
                 let market_count = result.markets.len();
-                total_market_count += market_count;
+                pager.record(market_count);
 
                 // for market in result.markets {
                 //     yield Ok(market);
                 // }
                 yield Ok(result.markets);
 
-                if !retrieve_all {
-                    break;
-                }
-
-                log::debug!("Fetched {} markets ({} new)", total_market_count, market_count);
+                log::debug!("Fetched {} markets ({} new)", pager.fetched(), market_count);
 
                 if !update_cursor_param(&mut params, &result.cursor) {
                     break;
@@ -253,8 +353,9 @@ impl Kalshi {
     /// and time-based filtering.
     ///
     /// # Arguments
-    /// * `limit` - An optional integer to limit the number of events returned.
-    /// * `cursor` - An optional string for pagination cursor.
+    /// * `limit` - An optional total number of events to return, every page is fetched when `None`.
+    /// * `page_size` - An optional number of events requested per page.
+    /// * `cursor` - An optional cursor to resume from, as returned by the `*_page` variants.
     /// * `status` - An optional string to filter events by their status.
     /// * `series_ticker` - An optional string to filter events by series ticker.
     /// * `with_nested_markets` - An optional boolean to include nested market data.
@@ -270,6 +371,7 @@ impl Kalshi {
     /// let events_result = kalshi_instance.get_multiple_events(
     ///     Some(10),
     ///     None,
+    ///     None,
     ///     Some("active"),
     ///     None,
     ///     Some(true)
@@ -280,6 +382,8 @@ impl Kalshi {
     pub async fn get_multiple_events(
         &self,
         limit: Option<i64>,
+        page_size: Option<i64>,
+        cursor: Option<String>,
         status: Option<String>,
         series_ticker: Option<String>,
         with_nested_markets: Option<bool>,
@@ -287,28 +391,27 @@ impl Kalshi {
         async_stream::stream! {
             let events_url = format!("{}/events", self.base_url);
             let mut params: Vec<(&str, String)> = Vec::with_capacity(6);
-            let retrieve_all = limit.is_none();
-            let mut total_event_count = 0;
+            let mut pager = Pager::new(limit, page_size, 200);
 
-            let req_limit = Some(limit.unwrap_or(200));
+            add_param!(params, "cursor", cursor);
 
-            add_param!(params, "limit", req_limit);
             add_param!(params, "status", status);
             add_param!(params, "series_ticker", series_ticker);
             add_param!(params, "with_nested_markets", with_nested_markets);
 
-            loop {
+            while let Some(page_size) = pager.next_page_size() {
+                set_param(&mut params, "limit", page_size.to_string());
                 let events_url = reqwest::Url::parse_with_params(&events_url, &params)
                     .unwrap_or_else(|err| {
                         eprintln!("{:?}", err);
                         panic!("Internal Parse Error, please contact developer!");
                     });
 
-                let result: PublicEventsResponse = match self.client.get(events_url).send().await {
-                    Ok(response) => match response.json().await {
+                let result: PublicEventsResponse = match self.client.get(events_url).send_logged().await {
+                    Ok(response) => match utils::parse_json(response).await {
                         Ok(data) => data,
                         Err(e) => {
-                            yield Err(KalshiError::from(e));
+                            yield Err(e);
                             break;
                         }
                     },
@@ -319,18 +422,14 @@ impl Kalshi {
                 };
 
                 let event_count = result.events.len();
-                total_event_count += event_count;
+                pager.record(event_count);
 
                 // for event in result.events {
                 //     yield Ok(event);
                 // }
                 yield Ok(result.events);
 
-                if !retrieve_all {
-                    break;
-                }
-
-                log::debug!("Fetched {} events ({} new)", total_event_count, event_count);
+                log::debug!("Fetched {} events ({} new)", pager.fetched(), event_count);
 
                 if !update_cursor_param(&mut params, &result.cursor) {
                     break;
@@ -356,10 +455,22 @@ impl Kalshi {
     /// let series = kalshi_instance.get_series(series_ticker).await.unwrap();
     /// ```
     pub async fn get_series(&self, ticker: &String) -> Result<Series, KalshiError> {
+        if let Some(series) = self
+            .metadata_cache
+            .as_ref()
+            .and_then(|cache| cache.series(ticker))
+        {
+            return Ok(series);
+        }
+
         let series_url: &str = &format!("{}/series/{}", self.base_url.to_string(), ticker);
 
-        let result: SeriesResponse = self.client.get(series_url).send().await?.json().await?;
+        let result: SeriesResponse =
+            utils::parse_json(self.client.get(series_url).send_logged().await?).await?;
 
+        if let Some(cache) = &self.metadata_cache {
+            cache.insert_series(result.series.clone());
+        }
         return Ok(result.series);
     }
     /// Asynchronously retrieves detailed information multiple series from the Kalshi exchange.
@@ -369,9 +480,9 @@ impl Kalshi {
     /// and related contract URLs.
     ///
     /// # Arguments
-    /// * `category` - A reference to a string representing the series category.
+    /// * `category` - The category of the series.
     /// * `include_product_metadata` - A boolean to include product metadata in the response.
-    /// * `tags` - A string, comma separated list of tags, to filter series with.
+    /// * `tags` - Tags to filter series with, every series is returned when empty.
     ///
     /// # Returns
     /// - `Ok(Vec<Series>)`: Vector of `Series` object on successful retrieval.
@@ -379,20 +490,20 @@ impl Kalshi {
     /// # Example
     /// ```
     /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
-    /// let category = "some_series_category";
-    /// let series = kalshi_instance.get_series_list(category, None, None).await.unwrap();
+    /// let series = kalshi_instance.get_series_list(&Category::Sports, None, vec!["Football".to_string()]).await.unwrap();
     /// ```
     pub async fn get_series_list(
         &self,
-        category: &String,
+        category: &Category,
         include_product_metadata: Option<bool>,
-        tags: Option<String>,
+        tags: Vec<String>,
     ) -> Result<Vec<Series>, KalshiError> {
+        let tags = join_filter("tags", &tags)?;
         let series_url: &str = &format!("{}/series/", self.base_url.to_string());
 
         let mut params: Vec<(&str, String)> = Vec::with_capacity(3);
 
-        add_param!(params, "category", Some(category));
+        add_param!(params, "category", Some(category.as_str()));
         add_param!(params, "include_product_metadata", include_product_metadata);
         add_param!(params, "tags", tags);
 
@@ -402,9 +513,38 @@ impl Kalshi {
                 panic!("Internal Parse Error, please contact developer!");
             });
 
-        let result: SeriesList = self.client.get(series_url).send().await?.json().await?;
+        let result: SeriesList =
+            utils::parse_json(self.client.get(series_url).send_logged().await?).await?;
         return Ok(result.series);
     }
+    /// Retrieves every series listed on the exchange, whatever their category.
+    ///
+    /// # Arguments
+    /// * `include_product_metadata` - A boolean to include product metadata in the response.
+    ///
+    /// # Example
+    /// ```
+    /// let series = kalshi_instance.get_all_series(None).await.unwrap();
+    /// ```
+    pub async fn get_all_series(
+        &self,
+        include_product_metadata: Option<bool>,
+    ) -> Result<Vec<Series>, KalshiError> {
+        let series_url: &str = &format!("{}/series", self.base_url);
+
+        let mut params: Vec<(&str, String)> = Vec::with_capacity(1);
+        add_param!(params, "include_product_metadata", include_product_metadata);
+
+        let series_url =
+            reqwest::Url::parse_with_params(series_url, &params).unwrap_or_else(|err| {
+                eprintln!("{:?}", err);
+                panic!("Internal Parse Error, please contact developer!");
+            });
+
+        let result: SeriesList =
+            utils::parse_json(self.client.get(series_url).send_logged().await?).await?;
+        Ok(result.series)
+    }
     /// Asynchronously retrieves the order book for a specific market in the Kalshi exchange.
     ///
     /// This method fetches the order book for a market, which includes the bid and ask prices
@@ -426,7 +566,7 @@ impl Kalshi {
     /// let orderbook = kalshi_instance.get_market_orderbook(market_ticker, Some(10)).await.unwrap();
     /// ```
     pub async fn get_market_orderbook(
-        &mut self,
+        &self,
         ticker: &String,
         depth: Option<i32>,
     ) -> Result<Orderbook, KalshiError> {
@@ -449,11 +589,85 @@ impl Kalshi {
         for (key, value) in &auth_headers {
             request = request.header(key, value);
         }
-        let result: OrderBookResponse = request.send().await?.json().await?;
+        let result: OrderBookResponse = utils::parse_json(request.send_logged().await?).await?;
 
         return Ok(result.orderbook);
     }
 
+    /// Fetches the order books of many markets concurrently, see [`Kalshi::get_market_orderbook`].
+    ///
+    /// At most `concurrency` requests are in flight at once, each through its own clone of this
+    /// instance. Books arrive in the order their requests complete; a failed request only fails
+    /// its own ticker.
+    ///
+    /// # Arguments
+    /// * `tickers` - The markets to fetch the order books of.
+    /// * `depth` - An optional integer specifying the depth of each order book.
+    /// * `concurrency` - How many requests to run at once, at least 1.
+    ///
+    /// # Example
+    /// ```
+    /// let tickers = vec!["KXHIGHNY-25OCT02-B80.5".to_string(), "KXHIGHNY-25OCT02-B82.5".to_string()];
+    /// let mut books = kalshi_instance.stream_orderbooks(tickers, Some(5), 8);
+    /// while let Some((ticker, orderbook)) = books.next().await {
+    ///     println!("{}: {:?}", ticker, orderbook?.yes);
+    /// }
+    /// ```
+    pub fn stream_orderbooks(
+        &self,
+        tickers: Vec<String>,
+        depth: Option<i32>,
+        concurrency: usize,
+    ) -> impl Stream<Item = (String, Result<Orderbook, KalshiError>)> {
+        let queue = Arc::new(Mutex::new(VecDeque::from(tickers)));
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        for _ in 0..concurrency.max(1) {
+            let kalshi = self.clone();
+            let queue = queue.clone();
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                loop {
+                    let next = queue.lock().unwrap_or_else(|p| p.into_inner()).pop_front();
+                    let Some(ticker) = next else { break };
+                    let orderbook = kalshi.get_market_orderbook(&ticker, depth).await;
+                    if sender.send((ticker, orderbook)).is_err() {
+                        // The stream was dropped
+                        break;
+                    }
+                }
+            });
+        }
+        async_stream::stream! {
+            while let Some(result) = receiver.recv().await {
+                yield result;
+            }
+        }
+    }
+
+    /// Fetches the order books of many markets concurrently and collects them by ticker.
+    ///
+    /// See [`Kalshi::stream_orderbooks`], every ticker maps to its own result.
+    ///
+    /// # Example
+    /// ```
+    /// let books = kalshi_instance.get_orderbooks(tickers, None, 8).await;
+    /// for (ticker, orderbook) in books {
+    ///     if let Ok(orderbook) = orderbook {
+    ///         println!("{}: {:?}", ticker, orderbook.yes);
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_orderbooks(
+        &self,
+        tickers: Vec<String>,
+        depth: Option<i32>,
+        concurrency: usize,
+    ) -> HashMap<String, Result<Orderbook, KalshiError>> {
+        self.stream_orderbooks(tickers, depth, concurrency)
+            .collect()
+            .await
+    }
+
     /// Asynchronously retrieves the market history for a given market on the Kalshi exchange.
     ///
     /// This method fetches historical data for a specific market, which can include
@@ -462,8 +676,9 @@ impl Kalshi {
     ///
     /// # Arguments
     /// * `ticker` - A reference to a string representing the market's ticker.
-    /// * `limit` - An optional integer to limit the number of history records returned.
-    /// * `cursor` - An optional string for pagination cursor.
+    /// * `limit` - An optional total number of history records to return, every page is fetched when `None`.
+    /// * `page_size` - An optional number of history records requested per page.
+    /// * `cursor` - An optional cursor to resume from, as returned by the `*_page` variants.
     /// * `min_ts` - An optional timestamp to specify the minimum time for history records.
     /// * `max_ts` - An optional timestamp to specify the maximum time for history records.
     ///
@@ -479,13 +694,16 @@ impl Kalshi {
     ///     Some(10),
     ///     None,
     ///     None,
+    ///     None,
     ///     None
     /// ).await.unwrap();
     /// ```
     pub async fn get_market_history(
-        &mut self,
+        &self,
         ticker: &String,
         limit: Option<i32>,
+        page_size: Option<i32>,
+        cursor: Option<String>,
         min_ts: Option<i64>,
         max_ts: Option<i64>,
     ) -> impl Stream<Item = Result<Snapshot, KalshiError>> + '_ {
@@ -493,14 +711,15 @@ impl Kalshi {
         async_stream::stream! {
             let market_history_url = format!("{}/markets/{}/history", self.base_url, ticker);
             let mut params: Vec<(&str, String)> = Vec::with_capacity(5);
-            let retrieve_all = limit.is_none();
-            let mut total_history_count = 0;
+            let mut pager = Pager::new(limit.map(i64::from), page_size.map(i64::from), 100);
+
+            add_param!(params, "cursor", cursor);
 
-            add_param!(params, "limit", limit);
             add_param!(params, "min_ts", min_ts);
             add_param!(params, "max_ts", max_ts);
 
-            loop {
+            while let Some(page_size) = pager.next_page_size() {
+                set_param(&mut params, "limit", page_size.to_string());
                 let market_history_url = reqwest::Url::parse_with_params(&market_history_url, &params)
                     .unwrap_or_else(|err| {
                         eprintln!("{:?}", err);
@@ -521,11 +740,11 @@ impl Kalshi {
                     request = request.header(key, value);
                 }
 
-                let result: MarketHistoryResponse = match request.send().await {
-                    Ok(response) => match response.json().await {
+                let result: MarketHistoryResponse = match request.send_logged().await {
+                    Ok(response) => match utils::parse_json(response).await {
                         Ok(data) => data,
                         Err(e) => {
-                            yield Err(KalshiError::from(e));
+                            yield Err(e);
                             break;
                         }
                     },
@@ -536,17 +755,13 @@ impl Kalshi {
                 };
 
                 let history_count = result.history.len();
-                total_history_count += history_count;
+                pager.record(history_count);
 
                 for snapshot in result.history {
                     yield Ok(snapshot);
                 }
 
-                if !retrieve_all {
-                    break;
-                }
-
-                log::debug!("Fetched {} history ({} new)", total_history_count, history_count);
+                log::debug!("Fetched {} history ({} new)", pager.fetched(), history_count);
 
                 if !update_cursor_param(&mut params, &result.cursor) {
                     break;
@@ -562,8 +777,9 @@ impl Kalshi {
     /// such as time, ticker, and pagination options.
     ///
     /// # Arguments
-    /// * `cursor` - An optional string for pagination cursor.
-    /// * `limit` - An optional integer to limit the number of trades returned.
+    /// * `limit` - An optional total number of trades to return, every page is fetched when `None`.
+    /// * `page_size` - An optional number of trades requested per page.
+    /// * `cursor` - An optional cursor to resume from, as returned by the `*_page` variants.
     /// * `ticker` - An optional string representing the market's ticker for which trades are to be fetched.
     /// * `min_ts` - An optional timestamp to specify the minimum time for trade records.
     /// * `max_ts` - An optional timestamp to specify the maximum time for trade records.
@@ -574,8 +790,9 @@ impl Kalshi {
     /// ```
     /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
     /// let trades = kalshi_instance.get_trades(
-    ///     None,
     ///     Some(10),
+    ///     None,
+    ///     None,
     ///     Some("ticker_name"),
     ///     None,
     ///     None
@@ -584,6 +801,8 @@ impl Kalshi {
     pub async fn get_trades(
         &self,
         limit: Option<i32>,
+        page_size: Option<i32>,
+        cursor: Option<String>,
         ticker: Option<String>,
         min_ts: Option<i64>,
         max_ts: Option<i64>,
@@ -591,26 +810,27 @@ impl Kalshi {
         async_stream::stream! {
             let trades_url = format!("{}/markets/trades", self.base_url);
             let mut params: Vec<(&str, String)> = Vec::with_capacity(7);
-            let retrieve_all = limit.is_none();
-            let mut total_trade_count = 0;
+            let mut pager = Pager::new(limit.map(i64::from), page_size.map(i64::from), 100);
+
+            add_param!(params, "cursor", cursor);
 
-            add_param!(params, "limit", limit);
             add_param!(params, "min_ts", min_ts);
             add_param!(params, "max_ts", max_ts);
             add_param!(params, "ticker", ticker);
 
-            loop {
+            while let Some(page_size) = pager.next_page_size() {
+                set_param(&mut params, "limit", page_size.to_string());
                 let trades_url = reqwest::Url::parse_with_params(&trades_url, &params)
                     .unwrap_or_else(|err| {
                         eprintln!("{:?}", err);
                         panic!("Internal Parse Error, please contact developer!");
                     });
 
-                let result: PublicTradesResponse = match self.client.get(trades_url).send().await {
-                    Ok(response) => match response.json().await {
+                let result: PublicTradesResponse = match self.client.get(trades_url).send_logged().await {
+                    Ok(response) => match utils::parse_json(response).await {
                         Ok(data) => data,
                         Err(e) => {
-                            yield Err(KalshiError::from(e));
+                            yield Err(e);
                             break;
                         }
                     },
@@ -621,17 +841,13 @@ impl Kalshi {
                 };
 
                 let trade_count = result.trades.len();
-                total_trade_count += trade_count;
+                pager.record(trade_count);
 
                 for trade in result.trades {
                     yield Ok(trade);
                 }
 
-                if !retrieve_all {
-                    break;
-                }
-
-                log::debug!("Fetched {} trades ({} new)", total_trade_count, trade_count);
+                log::debug!("Fetched {} trades ({} new)", pager.fetched(), trade_count);
 
                 if !update_cursor_param(&mut params, &result.cursor) {
                     break;
@@ -639,6 +855,267 @@ impl Kalshi {
             }
         }
     }
+
+    /// Retrieves a single page of markets, see [`Kalshi::get_multiple_markets`] for the filters.
+    ///
+    /// Unlike the streaming variant, the cursor of the next page is returned so pagination can be
+    /// checkpointed and resumed later, even from another process.
+    ///
+    /// # Arguments
+    /// * `limit` - An optional number of markets in the page.
+    /// * `cursor` - The cursor returned with the previous page, `None` for the first page.
+    ///
+    /// # Returns
+    /// - `Ok((Vec<Market>, Option<String>))`: The markets of the page and the cursor of the next one, `None` after the last page.
+    /// - `Err(KalshiError)`: Error in case of a failure in the HTTP request or response parsing.
+    ///
+    /// # Example
+    /// ```
+    /// let mut cursor = load_checkpoint();
+    /// loop {
+    ///     let (markets, next) = kalshi_instance.get_markets_page(
+    ///         Some(200), cursor, None, Some("KXHIGHNY".to_string()), None, None, &[], Vec::new()
+    ///     ).await.unwrap();
+    ///     process(markets);
+    ///     save_checkpoint(&next);
+    ///     match next {
+    ///         Some(next) => cursor = Some(next),
+    ///         None => break,
+    ///     }
+    /// }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_markets_page(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        event_ticker: Option<String>,
+        series_ticker: Option<String>,
+        max_close_ts: Option<i64>,
+        min_close_ts: Option<i64>,
+        status: &[MarketStatus],
+        tickers: Vec<String>,
+    ) -> Result<(Vec<Market>, Option<String>), KalshiError> {
+        let tickers = join_filter("tickers", &tickers)?;
+        let markets_url = format!("{}/markets", self.base_url);
+        let mut params: Vec<(&str, String)> = Vec::with_capacity(8);
+
+        add_param!(params, "limit", limit);
+        add_param!(params, "cursor", cursor);
+        add_param!(params, "event_ticker", event_ticker);
+        add_param!(params, "series_ticker", series_ticker);
+        add_param!(params, "status", join_statuses(status));
+        add_param!(params, "min_close_ts", min_close_ts);
+        add_param!(params, "max_close_ts", max_close_ts);
+        add_param!(params, "tickers", tickers);
+
+        let markets_url =
+            reqwest::Url::parse_with_params(&markets_url, &params).unwrap_or_else(|err| {
+                eprintln!("{:?}", err);
+                panic!("Internal Parse Error, please contact developer!");
+            });
+
+        let api_path = self.get_api_path("markets");
+        let mut request = self.client.get(markets_url);
+        for (key, value) in &self.generate_auth_headers(&api_path, Method::GET)? {
+            request = request.header(key, value);
+        }
+
+        let result: PublicMarketsResponse = utils::parse_json(request.send_logged().await?).await?;
+        Ok((result.markets, result.cursor))
+    }
+
+    /// Retrieves a single page of events, see [`Kalshi::get_multiple_events`] for the filters.
+    ///
+    /// # Arguments
+    /// * `limit` - An optional number of events in the page.
+    /// * `cursor` - The cursor returned with the previous page, `None` for the first page.
+    ///
+    /// # Returns
+    /// - `Ok((Vec<Event>, Option<String>))`: The events of the page and the cursor of the next one, `None` after the last page.
+    /// - `Err(KalshiError)`: Error in case of a failure in the HTTP request or response parsing.
+    ///
+    /// # Example
+    /// ```
+    /// let (events, next_cursor) = kalshi_instance.get_events_page(
+    ///     Some(100), saved_cursor, Some("open".to_string()), None, None
+    /// ).await.unwrap();
+    /// ```
+    pub async fn get_events_page(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        status: Option<String>,
+        series_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+    ) -> Result<(Vec<Event>, Option<String>), KalshiError> {
+        let events_url = format!("{}/events", self.base_url);
+        let mut params: Vec<(&str, String)> = Vec::with_capacity(5);
+
+        add_param!(params, "limit", limit);
+        add_param!(params, "cursor", cursor);
+        add_param!(params, "status", status);
+        add_param!(params, "series_ticker", series_ticker);
+        add_param!(params, "with_nested_markets", with_nested_markets);
+
+        let events_url =
+            reqwest::Url::parse_with_params(&events_url, &params).unwrap_or_else(|err| {
+                eprintln!("{:?}", err);
+                panic!("Internal Parse Error, please contact developer!");
+            });
+
+        let result: PublicEventsResponse =
+            utils::parse_json(self.client.get(events_url).send_logged().await?).await?;
+        Ok((result.events, result.cursor))
+    }
+
+    /// Retrieves a single page of a market's history, see [`Kalshi::get_market_history`] for the filters.
+    ///
+    /// # Arguments
+    /// * `limit` - An optional number of history records in the page.
+    /// * `cursor` - The cursor returned with the previous page, `None` for the first page.
+    ///
+    /// # Returns
+    /// - `Ok((Vec<Snapshot>, Option<String>))`: The records of the page and the cursor of the next one, `None` after the last page.
+    /// - `Err(KalshiError)`: Error in case of a failure in the HTTP request or response parsing.
+    ///
+    /// # Example
+    /// ```
+    /// let (history, next_cursor) = kalshi_instance.get_market_history_page(
+    ///     &"KXHIGHNY-25OCT02-B80.5".to_string(), Some(100), saved_cursor, None, None
+    /// ).await.unwrap();
+    /// ```
+    pub async fn get_market_history_page(
+        &self,
+        ticker: &String,
+        limit: Option<i32>,
+        cursor: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Vec<Snapshot>, Option<String>), KalshiError> {
+        let market_history_url = format!("{}/markets/{}/history", self.base_url, ticker);
+        let mut params: Vec<(&str, String)> = Vec::with_capacity(4);
+
+        add_param!(params, "limit", limit);
+        add_param!(params, "cursor", cursor);
+        add_param!(params, "min_ts", min_ts);
+        add_param!(params, "max_ts", max_ts);
+
+        let market_history_url = reqwest::Url::parse_with_params(&market_history_url, &params)
+            .unwrap_or_else(|err| {
+                eprintln!("{:?}", err);
+                panic!("Internal Parse Error, please contact developer!");
+            });
+
+        let api_path = self.get_api_path(&format!("markets/{}/history", ticker));
+        let mut request = self.client.get(market_history_url);
+        for (key, value) in &self.generate_auth_headers(&api_path, Method::GET)? {
+            request = request.header(key, value);
+        }
+
+        let result: MarketHistoryResponse = utils::parse_json(request.send_logged().await?).await?;
+        Ok((result.history, result.cursor))
+    }
+
+    /// Retrieves a single page of trades, see [`Kalshi::get_trades`] for the filters.
+    ///
+    /// # Arguments
+    /// * `limit` - An optional number of trades in the page.
+    /// * `cursor` - The cursor returned with the previous page, `None` for the first page.
+    ///
+    /// # Returns
+    /// - `Ok((Vec<Trade>, Option<String>))`: The trades of the page and the cursor of the next one, `None` after the last page.
+    /// - `Err(KalshiError)`: Error in case of a failure in the HTTP request or response parsing.
+    ///
+    /// # Example
+    /// ```
+    /// let (trades, next_cursor) = kalshi_instance.get_trades_page(
+    ///     Some(1000), saved_cursor, Some("KXHIGHNY-25OCT02-B80.5".to_string()), None, None
+    /// ).await.unwrap();
+    /// ```
+    pub async fn get_trades_page(
+        &self,
+        limit: Option<i32>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Vec<Trade>, Option<String>), KalshiError> {
+        let trades_url = format!("{}/markets/trades", self.base_url);
+        let mut params: Vec<(&str, String)> = Vec::with_capacity(5);
+
+        add_param!(params, "limit", limit);
+        add_param!(params, "cursor", cursor);
+        add_param!(params, "ticker", ticker);
+        add_param!(params, "min_ts", min_ts);
+        add_param!(params, "max_ts", max_ts);
+
+        let trades_url =
+            reqwest::Url::parse_with_params(&trades_url, &params).unwrap_or_else(|err| {
+                eprintln!("{:?}", err);
+                panic!("Internal Parse Error, please contact developer!");
+            });
+
+        let result: PublicTradesResponse =
+            utils::parse_json(self.client.get(trades_url).send_logged().await?).await?;
+        Ok((result.trades, result.cursor))
+    }
+
+    /// Asynchronously retrieves the candlesticks of a market on the Kalshi exchange.
+    ///
+    /// This method fetches OHLC data for a market's bid, ask and traded prices, aggregated over
+    /// periods of `period_interval` minutes that end between `start_ts` and `end_ts`.
+    ///
+    /// # Arguments
+    /// * `series_ticker` - A reference to a string representing the ticker of the market's series.
+    /// * `ticker` - A reference to a string representing the market's ticker.
+    /// * `start_ts` - The timestamp of the earliest period end to return.
+    /// * `end_ts` - The timestamp of the latest period end to return.
+    /// * `period_interval` - The length of each candlestick, in minutes. Kalshi supports 1, 60 and 1440.
+    ///
+    /// # Returns
+    /// - `Ok(Vec<Candlestick>)`: Vector of `Candlestick` objects, oldest first, on successful retrieval.
+    /// - `Err(KalshiError)`: Error in case of a failure in the HTTP request or response parsing.
+    /// # Example
+    /// ```
+    /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
+    /// let candlesticks = kalshi_instance.get_market_candlesticks(
+    ///     "KXHIGHNY",
+    ///     "KXHIGHNY-25OCT02-B80.5",
+    ///     1759334400,
+    ///     1759420800,
+    ///     60
+    /// ).await.unwrap();
+    /// ```
+    pub async fn get_market_candlesticks(
+        &self,
+        series_ticker: &str,
+        ticker: &str,
+        start_ts: i64,
+        end_ts: i64,
+        period_interval: i32,
+    ) -> Result<Vec<Candlestick>, KalshiError> {
+        let candlesticks_url: &str = &format!(
+            "{}/series/{}/markets/{}/candlesticks",
+            self.base_url, series_ticker, ticker
+        );
+
+        let mut params: Vec<(&str, String)> = Vec::with_capacity(3);
+
+        add_param!(params, "start_ts", Some(start_ts));
+        add_param!(params, "end_ts", Some(end_ts));
+        add_param!(params, "period_interval", Some(period_interval));
+
+        let candlesticks_url = reqwest::Url::parse_with_params(candlesticks_url, &params)
+            .unwrap_or_else(|err| {
+                eprintln!("{:?}", err);
+                panic!("Internal Parse Error, please contact developer!");
+            });
+
+        let result: CandlesticksResponse =
+            utils::parse_json(self.client.get(candlesticks_url).send_logged().await?).await?;
+        Ok(result.candlesticks)
+    }
 }
 
 // PRIVATE STRUCTS
@@ -694,6 +1171,12 @@ struct PublicTradesResponse {
     trades: Vec<Trade>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+struct CandlesticksResponse {
+    ticker: String,
+    candlesticks: Vec<Candlestick>,
+}
+
 // PUBLIC STRUCTS
 
 /// A market in the Kalshi exchange.
@@ -701,7 +1184,7 @@ struct PublicTradesResponse {
 /// Contains detailed information about the market including its ticker,
 /// type, status, and other relevant data.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Market {
     /// Unique identifier for the market.
     pub ticker: String,
@@ -797,18 +1280,125 @@ pub struct Market {
     /// Value at expiration.
     pub expiration_value: String,
     /// Category of the market.
-    pub category: String,
+    pub category: Category,
     /// Risk limit in cents.
     pub risk_limit_cents: i64,
     /// Primary rules for the market.
     pub rules_primary: String,
     /// Secondary rules for the market.
     pub rules_secondary: String,
-    /// Settlement value for the market.
+    /// Settlement value for the market, what a yes contract paid out in cents.
     pub settlement_value: Option<i64>,
     /// Settlement value for the market in dollars.
     #[serde(default)]
     pub settlement_value_dollars: Option<String>,
+    /// What the market is struck on, from its `strike_type` and strike fields.
+    #[serde(flatten)]
+    pub strike: Strike,
+}
+
+/// An amount reported in both cents and dollars, the cents when the two disagree.
+fn reconciled(cents: i64, dollars: &Option<String>) -> Money {
+    Money::reconcile(cents, dollars.as_deref()).unwrap_or_else(|e| {
+        log::warn!("{}, using the cents", e);
+        Money::from_cents(cents)
+    })
+}
+
+impl Market {
+    /// Whether the market is open for trading, the exchange reports these as `active` or `open`.
+    pub fn is_open(&self) -> bool {
+        matches!(self.status.as_str(), "active" | "open")
+    }
+
+    /// The yes bid, with the sub-cent precision of `yes_bid_dollars` when reported.
+    pub fn yes_bid_money(&self) -> Money {
+        reconciled(self.yes_bid, &self.yes_bid_dollars)
+    }
+
+    /// The yes ask, with the sub-cent precision of `yes_ask_dollars` when reported.
+    pub fn yes_ask_money(&self) -> Money {
+        reconciled(self.yes_ask, &self.yes_ask_dollars)
+    }
+
+    /// The no bid, with the sub-cent precision of `no_bid_dollars` when reported.
+    pub fn no_bid_money(&self) -> Money {
+        reconciled(self.no_bid, &self.no_bid_dollars)
+    }
+
+    /// The no ask, with the sub-cent precision of `no_ask_dollars` when reported.
+    pub fn no_ask_money(&self) -> Money {
+        reconciled(self.no_ask, &self.no_ask_dollars)
+    }
+
+    /// The last traded price, with the sub-cent precision of `last_price_dollars` when reported.
+    pub fn last_price_money(&self) -> Money {
+        reconciled(self.last_price, &self.last_price_dollars)
+    }
+
+    pub fn liquidity_money(&self) -> Money {
+        reconciled(self.liquidity, &self.liquidity_dollars)
+    }
+
+    pub fn notional_value_money(&self) -> Money {
+        reconciled(self.notional_value, &self.notional_value_dollars)
+    }
+
+    /// What a yes contract paid out, `None` before settlement.
+    pub fn settlement_value_money(&self) -> Option<Money> {
+        self.settlement_value
+            .map(|cents| reconciled(cents, &self.settlement_value_dollars))
+    }
+
+    /// Midpoint of the yes bid and ask in cents, `None` when either side isn't quoted.
+    pub fn mid_price(&self) -> Option<f64> {
+        if self.yes_bid > 0 && self.yes_ask > 0 {
+            Some((self.yes_bid + self.yes_ask) as f64 / 2.0)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the market is scalar: a yes contract pays out in proportion to where the
+    /// underlying value lands between the floor and cap strikes, instead of all or nothing.
+    pub fn is_scalar(&self) -> bool {
+        self.market_type == "scalar"
+    }
+
+    /// The floor and cap strikes of a scalar market, where a yes contract is worth nothing and
+    /// 100 cents respectively.
+    pub fn scalar_range(&self) -> Option<(f64, f64)> {
+        match (self.is_scalar(), self.strike.floor(), self.strike.cap()) {
+            (true, Some(floor), Some(cap)) if cap > floor => Some((floor, cap)),
+            _ => None,
+        }
+    }
+
+    /// The underlying value implied by a yes price in cents, for scalar markets.
+    ///
+    /// # Example
+    /// ```
+    /// // A market on CPI between 2.0 and 4.0, with yes trading at 40 cents
+    /// assert_eq!(market.implied_value(40.0), Some(2.8));
+    /// ```
+    pub fn implied_value(&self, yes_price: f64) -> Option<f64> {
+        let (floor, cap) = self.scalar_range()?;
+        Some(floor + yes_price.clamp(0.0, 100.0) / 100.0 * (cap - floor))
+    }
+
+    /// The yes price in cents a scalar market settles at if the underlying lands on `value`.
+    pub fn price_for_value(&self, value: f64) -> Option<f64> {
+        let (floor, cap) = self.scalar_range()?;
+        Some(((value - floor) / (cap - floor)).clamp(0.0, 1.0) * 100.0)
+    }
+
+    /// The underlying value a scalar market settled at, from its settlement value.
+    pub fn settled_value(&self) -> Option<f64> {
+        match self.result {
+            Some(SettlementResult::Scalar) => self.implied_value(self.settlement_value? as f64),
+            _ => None,
+        }
+    }
 }
 
 /// An event in the Kalshi exchange.
@@ -816,7 +1406,7 @@ pub struct Market {
 /// This struct contains information about a specific event, including its identifier,
 /// title, and other relevant details. It may also include associated markets.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Event {
     /// Unique identifier for the event.
     pub event_ticker: String,
@@ -829,7 +1419,7 @@ pub struct Event {
     /// Indicates if the event's outcomes are mutually exclusive.
     pub mutually_exclusive: bool,
     /// Category of the event.
-    pub category: String,
+    pub category: Category,
     /// Optional list of markets associated with this event.
     pub markets: Option<Vec<Market>>,
     /// Optional date of the event's occurrence.
@@ -838,13 +1428,57 @@ pub struct Event {
     pub strike_period: Option<String>,
 }
 
+impl Event {
+    /// The event's markets that are open for trading.
+    ///
+    /// Events only carry their markets when fetched with nested markets, otherwise this is empty.
+    pub fn open_markets(&self) -> Vec<&Market> {
+        self.markets
+            .iter()
+            .flatten()
+            .filter(|market| market.is_open())
+            .collect()
+    }
+
+    /// Probability of each open market resolving yes, from the yes mid prices scaled to sum to one.
+    ///
+    /// Empty unless the event's markets are mutually exclusive, markets without both a bid and an
+    /// ask are left out. Like [`EventBook::implied_distribution`](crate::EventBook::implied_distribution)
+    /// but from the prices the markets were fetched with.
+    ///
+    /// # Example
+    /// ```
+    /// let event = kalshi_instance.get_single_event(&"KXHIGHNY-25OCT02".to_string(), Some(true)).await?;
+    /// for (ticker, probability) in event.implied_distribution() {
+    ///     println!("{}: {:.1}%", ticker, probability * 100.0);
+    /// }
+    /// ```
+    pub fn implied_distribution(&self) -> Vec<(String, f64)> {
+        if !self.mutually_exclusive {
+            return Vec::new();
+        }
+        let mids: Vec<(String, f64)> = self
+            .open_markets()
+            .into_iter()
+            .filter_map(|market| market.mid_price().map(|mid| (market.ticker.clone(), mid)))
+            .collect();
+        let total: f64 = mids.iter().map(|(_, mid)| mid).sum();
+        if total <= 0.0 {
+            return Vec::new();
+        }
+        mids.into_iter()
+            .map(|(ticker, mid)| (ticker, mid / total))
+            .collect()
+    }
+}
+
 /// Series on the Kalshi exchange.
 ///
 /// This struct includes details about a specific series, such as its frequency,
 /// title, and category. It also includes information on settlement sources and
 /// related contract URLs.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Series {
     /// Unique ticker identifying the series.
     pub ticker: String,
@@ -853,7 +1487,7 @@ pub struct Series {
     /// Title of the series.
     pub title: String,
     /// Category of the series.
-    pub category: String,
+    pub category: Category,
     /// Tags associated with the series.
     #[serde(default)]
     pub tags: Option<Vec<String>>,
@@ -877,7 +1511,7 @@ pub struct SeriesList {
 }
 
 /// Product metadata for a series
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ProductMetadata {
     pub scope: String,
 }
@@ -886,7 +1520,7 @@ pub struct ProductMetadata {
 ///
 /// This struct contains information about a source used for settling a series, including the source's URL and name.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SettlementSource {
     /// URL of the settlement source.
     pub url: String,
@@ -912,7 +1546,7 @@ pub struct Orderbook {
 ///
 /// This struct provides a snapshot of the market at a specific time, including prices, bids, asks, volume, and open interest.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Snapshot {
     /// Last traded price for the 'Yes' option.
     pub yes_price: i32,
@@ -938,7 +1572,7 @@ pub struct Snapshot {
 ///
 /// Used in methods for retrieving user fills and specific trade details.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Trade {
     /// Unique identifier of the trade.
     pub trade_id: String,
@@ -956,12 +1590,59 @@ pub struct Trade {
     pub created_time: String,
 }
 
+/// Prices of a market aggregated over a period, in the Kalshi exchange.
+///
+/// This struct contains the open, high, low and close of the market's bid, ask and traded prices,
+/// along with the volume traded during the period and the open interest at its end.
+///
+/// Used in `get_market_candlesticks`.
+///
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Candlestick {
+    /// Timestamp of the end of the period.
+    pub end_period_ts: i64,
+    /// OHLC of the highest bid for the 'Yes' option.
+    pub yes_bid: QuoteOhlc,
+    /// OHLC of the lowest ask for the 'Yes' option.
+    pub yes_ask: QuoteOhlc,
+    /// OHLC of the traded price, empty if nothing traded during the period.
+    pub price: TradeOhlc,
+    /// Number of contracts traded during the period.
+    pub volume: i64,
+    /// Open interest at the end of the period.
+    pub open_interest: i64,
+}
+
+/// Open, high, low and close of a quoted price over a candlestick's period, in cents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct QuoteOhlc {
+    pub open: i64,
+    pub high: i64,
+    pub low: i64,
+    pub close: i64,
+}
+
+/// Open, high, low and close of the traded price over a candlestick's period, in cents.
+///
+/// Every price is `None` when nothing traded during the period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TradeOhlc {
+    pub open: Option<i64>,
+    pub high: Option<i64>,
+    pub low: Option<i64>,
+    pub close: Option<i64>,
+    /// Average traded price during the period.
+    pub mean: Option<i64>,
+    /// Close of the last period anything traded in.
+    pub previous: Option<i64>,
+}
+
 /// Possible outcomes of a market settlement on the Kalshi exchange.
 ///
 /// This enum represents the different results that can be assigned to a market
 /// upon its conclusion.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SettlementResult {
     /// The outcome of the market is affirmative.
@@ -970,7 +1651,7 @@ pub enum SettlementResult {
     No,
     /// The market is voided, usually due to specific conditions not being met.
     Void,
-    /// scalar market settled at a specific value
+    /// A scalar market settled, a yes contract paid its `settlement_value`, see [`Market::settled_value`].
     Scalar,
 }
 
@@ -978,9 +1659,12 @@ pub enum SettlementResult {
 ///
 /// This enum is used to represent the current operational state of a market.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MarketStatus {
+    /// The market hasn't opened yet.
+    Unopened,
+
     /// The market is open for trading.
     Open,
 
@@ -991,11 +1675,728 @@ pub enum MarketStatus {
     Settled,
 }
 
+impl MarketStatus {
+    /// The value of this status in query strings.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketStatus::Unopened => "unopened",
+            MarketStatus::Open => "open",
+            MarketStatus::Closed => "closed",
+            MarketStatus::Settled => "settled",
+        }
+    }
+}
+
+/// What a market is struck on, parsed from its `strike_type` along with the `floor_strike`,
+/// `cap_strike`, `custom_strike` and `functional_strike` fields.
+///
+/// Used by both [`Market`] and the market lifecycle websocket messages. Strike types this crate
+/// doesn't know, or known types missing their strikes, are kept as [`Strike::Other`].
+///
+/// # Example
+/// ```
+/// match &market.strike {
+///     Strike::Between { floor, cap } => println!("{} to {}", floor, cap),
+///     Strike::Structured(targets) => println!("on {:?}", targets),
+///     _ => {}
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(from = "RawStrike", into = "RawStrike")]
+pub enum Strike {
+    /// Resolves yes if the value is above `floor`.
+    Greater { floor: f64 },
+    /// Resolves yes if the value is at least `floor`.
+    GreaterOrEqual { floor: f64 },
+    /// Resolves yes if the value is below `cap`.
+    Less { cap: f64 },
+    /// Resolves yes if the value is at most `cap`.
+    LessOrEqual { cap: f64 },
+    /// Resolves yes if the value lands between `floor` and `cap`.
+    Between { floor: f64, cap: f64 },
+    /// Resolves on structured targets, e.g. `basketball_team` to the team's id.
+    Structured(HashMap<String, String>),
+    /// Resolves on custom targets, keyed like [`Strike::Structured`].
+    Custom(HashMap<String, String>),
+    /// Resolves on the outcome of an expression.
+    Functional(String),
+    /// A strike type without a variant, with whatever strikes came along.
+    Other {
+        strike_type: String,
+        floor: Option<f64>,
+        cap: Option<f64>,
+    },
+    /// The market has no strike.
+    #[default]
+    None,
+}
+
+impl Strike {
+    /// The lower strike, if the strike has one.
+    pub fn floor(&self) -> Option<f64> {
+        match self {
+            Strike::Greater { floor }
+            | Strike::GreaterOrEqual { floor }
+            | Strike::Between { floor, .. } => Some(*floor),
+            Strike::Other { floor, .. } => *floor,
+            _ => None,
+        }
+    }
+
+    /// The upper strike, if the strike has one.
+    pub fn cap(&self) -> Option<f64> {
+        match self {
+            Strike::Less { cap } | Strike::LessOrEqual { cap } | Strike::Between { cap, .. } => {
+                Some(*cap)
+            }
+            Strike::Other { cap, .. } => *cap,
+            _ => None,
+        }
+    }
+
+    /// Whether `value` resolves the market yes, for strikes on a number.
+    ///
+    /// `Between` includes both ends.
+    pub fn resolves_yes(&self, value: f64) -> Option<bool> {
+        match *self {
+            Strike::Greater { floor } => Some(value > floor),
+            Strike::GreaterOrEqual { floor } => Some(value >= floor),
+            Strike::Less { cap } => Some(value < cap),
+            Strike::LessOrEqual { cap } => Some(value <= cap),
+            Strike::Between { floor, cap } => Some(value >= floor && value <= cap),
+            _ => None,
+        }
+    }
+}
+
+/// The strike fields as they're sent by the exchange.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RawStrike {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    strike_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    floor_strike: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cap_strike: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    custom_strike: Option<HashMap<String, RawStrikeTarget>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    functional_strike: Option<String>,
+}
+
+/// A value of `custom_strike`, usually a string id but sometimes sent as a number.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawStrikeTarget {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<RawStrikeTarget> for String {
+    fn from(target: RawStrikeTarget) -> Self {
+        match target {
+            RawStrikeTarget::String(value) => value,
+            RawStrikeTarget::Integer(value) => value.to_string(),
+            RawStrikeTarget::Float(value) => value.to_string(),
+            RawStrikeTarget::Bool(value) => value.to_string(),
+        }
+    }
+}
+
+impl From<RawStrike> for Strike {
+    fn from(raw: RawStrike) -> Self {
+        let targets = |custom: Option<HashMap<String, RawStrikeTarget>>| {
+            custom
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect()
+        };
+        let Some(strike_type) = raw.strike_type else {
+            return Strike::None;
+        };
+        match (strike_type.as_str(), raw.floor_strike, raw.cap_strike) {
+            ("greater", Some(floor), _) => Strike::Greater { floor },
+            ("greater_or_equal", Some(floor), _) => Strike::GreaterOrEqual { floor },
+            ("less", _, Some(cap)) => Strike::Less { cap },
+            ("less_or_equal", _, Some(cap)) => Strike::LessOrEqual { cap },
+            ("between", Some(floor), Some(cap)) => Strike::Between { floor, cap },
+            ("structured", _, _) => Strike::Structured(targets(raw.custom_strike)),
+            ("custom", _, _) => Strike::Custom(targets(raw.custom_strike)),
+            ("functional", _, _) if raw.functional_strike.is_some() => {
+                Strike::Functional(raw.functional_strike.unwrap_or_default())
+            }
+            _ => Strike::Other {
+                strike_type,
+                floor: raw.floor_strike,
+                cap: raw.cap_strike,
+            },
+        }
+    }
+}
+
+impl From<Strike> for RawStrike {
+    fn from(strike: Strike) -> Self {
+        let (floor_strike, cap_strike) = (strike.floor(), strike.cap());
+        let typed = |strike_type: &str| RawStrike {
+            strike_type: Some(strike_type.to_string()),
+            floor_strike,
+            cap_strike,
+            ..RawStrike::default()
+        };
+        let targets = |targets: HashMap<String, String>| {
+            Some(
+                targets
+                    .into_iter()
+                    .map(|(key, value)| (key, RawStrikeTarget::String(value)))
+                    .collect(),
+            )
+        };
+        match strike {
+            Strike::Greater { .. } => typed("greater"),
+            Strike::GreaterOrEqual { .. } => typed("greater_or_equal"),
+            Strike::Less { .. } => typed("less"),
+            Strike::LessOrEqual { .. } => typed("less_or_equal"),
+            Strike::Between { .. } => typed("between"),
+            Strike::Structured(custom) => RawStrike {
+                custom_strike: targets(custom),
+                ..typed("structured")
+            },
+            Strike::Custom(custom) => RawStrike {
+                custom_strike: targets(custom),
+                ..typed("custom")
+            },
+            Strike::Functional(expression) => RawStrike {
+                functional_strike: Some(expression),
+                ..typed("functional")
+            },
+            Strike::Other { strike_type, .. } => typed(&strike_type),
+            Strike::None => RawStrike::default(),
+        }
+    }
+}
+
+/// The category of a market, event or series.
+///
+/// Parsed from Kalshi's category names ignoring case, categories without a variant are kept as
+/// [`Category::Other`], which is also how markets without a category (an empty string) come back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Category {
+    Politics,
+    Elections,
+    Sports,
+    Culture,
+    Crypto,
+    /// `Climate and Weather`, also parsed from `Climate`.
+    Climate,
+    Economics,
+    Financials,
+    Companies,
+    Commodities,
+    Health,
+    /// `Science and Technology`.
+    Science,
+    World,
+    Mentions,
+    Transportation,
+    Social,
+    Other(String),
+}
+
+impl Category {
+    /// Kalshi's name of the category, as used in query strings.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Category::Politics => "Politics",
+            Category::Elections => "Elections",
+            Category::Sports => "Sports",
+            Category::Culture => "Culture",
+            Category::Crypto => "Crypto",
+            Category::Climate => "Climate and Weather",
+            Category::Economics => "Economics",
+            Category::Financials => "Financials",
+            Category::Companies => "Companies",
+            Category::Commodities => "Commodities",
+            Category::Health => "Health",
+            Category::Science => "Science and Technology",
+            Category::World => "World",
+            Category::Mentions => "Mentions",
+            Category::Transportation => "Transportation",
+            Category::Social => "Social",
+            Category::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for Category {
+    fn from(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "politics" => Category::Politics,
+            "elections" => Category::Elections,
+            "sports" => Category::Sports,
+            "culture" => Category::Culture,
+            "crypto" => Category::Crypto,
+            "climate and weather" | "climate" => Category::Climate,
+            "economics" => Category::Economics,
+            "financials" => Category::Financials,
+            "companies" => Category::Companies,
+            "commodities" => Category::Commodities,
+            "health" => Category::Health,
+            "science and technology" => Category::Science,
+            "world" => Category::World,
+            "mentions" => Category::Mentions,
+            "transportation" => Category::Transportation,
+            "social" => Category::Social,
+            _ => Category::Other(name.to_string()),
+        }
+    }
+}
+
+impl From<String> for Category {
+    fn from(name: String) -> Self {
+        Category::from(name.as_str())
+    }
+}
+
+impl From<Category> for String {
+    fn from(category: Category) -> Self {
+        category.as_str().to_string()
+    }
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "streaming")]
+mod streaming {
+    use super::*;
+    use crate::json_stream::{stream_json_array, JsonArrayItem};
+
+    impl Kalshi {
+        /// Streams markets one at a time, each yielded as soon as it's parsed.
+        ///
+        /// Unlike [`Kalshi::get_multiple_markets`], pages are never buffered whole: each market
+        /// is deserialized as its bytes arrive, so the first market is available before the page
+        /// finished downloading and memory stays bounded by a single market. Takes the same
+        /// filters, and fetches every page when `limit` is `None`, starting from `cursor` when
+        /// given. Larger pages mean fewer round trips, the default is 1000 markets per page.
+        ///
+        /// # Example
+        /// ```
+        /// let markets = kalshi_instance.stream_markets(None, Some(500), None, None, None, None, None, &[MarketStatus::Open], Vec::new());
+        /// pin_mut!(markets);
+        /// while let Some(market) = markets.next().await {
+        ///     println!("{}", market?.ticker);
+        /// }
+        /// ```
+        #[allow(clippy::too_many_arguments)]
+        pub fn stream_markets(
+            &self,
+            limit: Option<i64>,
+            page_size: Option<i64>,
+            cursor: Option<String>,
+            event_ticker: Option<String>,
+            series_ticker: Option<String>,
+            max_close_ts: Option<i64>,
+            min_close_ts: Option<i64>,
+            status: &[MarketStatus],
+            tickers: Vec<String>,
+        ) -> impl Stream<Item = Result<Market, KalshiError>> + '_ {
+            let status = join_statuses(status);
+            let tickers = join_filter("tickers", &tickers);
+            async_stream::stream! {
+                let tickers = match tickers {
+                    Ok(tickers) => tickers,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+                let markets_url = format!("{}/markets", self.base_url);
+                let mut params: Vec<(&str, String)> = Vec::with_capacity(10);
+                let mut pager = Pager::new(limit, page_size, 1000);
+
+                add_param!(params, "cursor", cursor);
+
+                add_param!(params, "event_ticker", event_ticker);
+                add_param!(params, "series_ticker", series_ticker);
+                add_param!(params, "status", status);
+                add_param!(params, "min_close_ts", min_close_ts);
+                add_param!(params, "max_close_ts", max_close_ts);
+                add_param!(params, "tickers", tickers);
+
+                while let Some(page_size) = pager.next_page_size() {
+                    set_param(&mut params, "limit", page_size.to_string());
+                    let markets_url = reqwest::Url::parse_with_params(&markets_url, &params)
+                        .unwrap_or_else(|err| {
+                            eprintln!("{:?}", err);
+                            panic!("Internal Parse Error, please contact developer!");
+                        });
+
+                    let api_path = self.get_api_path("markets");
+                    let auth_headers = match self.generate_auth_headers(&api_path, Method::GET) {
+                        Ok(headers) => headers,
+                        Err(e) => {
+                            yield Err(e);
+                            break;
+                        }
+                    };
+                    let mut request = self.client.get(markets_url);
+                    for (key, value) in &auth_headers {
+                        request = request.header(key, value);
+                    }
+                    let response = match request.send_logged().await {
+                        Ok(response) => response,
+                        Err(e) => {
+                            yield Err(KalshiError::from(e));
+                            break;
+                        }
+                    };
+
+                    let mut cursor = None;
+                    let page = stream_json_array::<Market>(response, "markets");
+                    futures::pin_mut!(page);
+                    while let Some(item) = page.next().await {
+                        match item {
+                            Ok(JsonArrayItem::Element(market)) => {
+                                pager.record(1);
+                                yield Ok(market);
+                            }
+                            Ok(JsonArrayItem::End { cursor: next }) => cursor = next,
+                            Err(e) => {
+                                yield Err(e);
+                                return;
+                            }
+                        }
+                    }
+
+                    if !update_cursor_param(&mut params, &cursor) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        /// Streams trades one at a time, each yielded as soon as it's parsed.
+        ///
+        /// The incremental counterpart of [`Kalshi::get_trades`], see [`Kalshi::stream_markets`].
+        ///
+        /// # Example
+        /// ```
+        /// let trades = kalshi_instance.stream_trades(None, None, None, Some("KXHIGHNY-25OCT02-B80.5".to_string()), None, None);
+        /// pin_mut!(trades);
+        /// while let Some(trade) = trades.next().await {
+        ///     println!("{:?}", trade?);
+        /// }
+        /// ```
+        pub fn stream_trades(
+            &self,
+            limit: Option<i32>,
+            page_size: Option<i32>,
+            cursor: Option<String>,
+            ticker: Option<String>,
+            min_ts: Option<i64>,
+            max_ts: Option<i64>,
+        ) -> impl Stream<Item = Result<Trade, KalshiError>> + '_ {
+            async_stream::stream! {
+                let trades_url = format!("{}/markets/trades", self.base_url);
+                let mut params: Vec<(&str, String)> = Vec::with_capacity(7);
+                let mut pager = Pager::new(limit.map(i64::from), page_size.map(i64::from), 1000);
+
+                add_param!(params, "cursor", cursor);
+
+                add_param!(params, "min_ts", min_ts);
+                add_param!(params, "max_ts", max_ts);
+                add_param!(params, "ticker", ticker);
+
+                while let Some(page_size) = pager.next_page_size() {
+                    set_param(&mut params, "limit", page_size.to_string());
+                    let trades_url = reqwest::Url::parse_with_params(&trades_url, &params)
+                        .unwrap_or_else(|err| {
+                            eprintln!("{:?}", err);
+                            panic!("Internal Parse Error, please contact developer!");
+                        });
+                    let response = match self.client.get(trades_url).send_logged().await {
+                        Ok(response) => response,
+                        Err(e) => {
+                            yield Err(KalshiError::from(e));
+                            break;
+                        }
+                    };
+
+                    let mut cursor = None;
+                    let page = stream_json_array::<Trade>(response, "trades");
+                    futures::pin_mut!(page);
+                    while let Some(item) = page.next().await {
+                        match item {
+                            Ok(JsonArrayItem::Element(trade)) => {
+                                pager.record(1);
+                                yield Ok(trade);
+                            }
+                            Ok(JsonArrayItem::End { cursor: next }) => cursor = next,
+                            Err(e) => {
+                                yield Err(e);
+                                return;
+                            }
+                        }
+                    }
+
+                    if !update_cursor_param(&mut params, &cursor) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
 
     use super::*;
 
+    #[test]
+    fn test_pager_stops_at_limit() {
+        let mut pager = Pager::new(Some(250), Some(100), 200);
+        let mut sizes = Vec::new();
+        while let Some(size) = pager.next_page_size() {
+            sizes.push(size);
+            pager.record(size);
+        }
+        assert_eq!(sizes, [100, 100, 50]);
+
+        let pager = Pager::new(None, None, 200);
+        assert_eq!(pager.next_page_size(), Some(200));
+        assert_eq!(Pager::new(Some(0), None, 200).next_page_size(), None);
+    }
+
+    #[tokio::test]
+    async fn test_markets_page_returns_next_cursor() {
+        use crate::testing::{fixtures, MockHttpServer};
+
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let page = serde_json::json!({ "markets": [fixtures::market()], "cursor": "page-3" });
+        server.respond(Method::GET, "/markets", 200, page);
+
+        let (markets, cursor) = kalshi
+            .get_markets_page(
+                Some(1),
+                Some("page-2".to_string()),
+                None,
+                None,
+                None,
+                None,
+                &[MarketStatus::Open, MarketStatus::Closed],
+                vec![fixtures::MARKET_TICKER.to_string(), "KXOTHER-1".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(markets[0].ticker, fixtures::MARKET_TICKER);
+        assert_eq!(cursor.as_deref(), Some("page-3"));
+        let request = &server.requests_to(Method::GET, "/markets")[0];
+        assert_eq!(request.query_param("cursor"), Some("page-2"));
+        assert_eq!(request.query_param("limit"), Some("1"));
+        assert_eq!(request.query_param("status"), Some("open,closed"));
+        let tickers = format!("{},KXOTHER-1", fixtures::MARKET_TICKER);
+        assert_eq!(request.query_param("tickers"), Some(tickers.as_str()));
+
+        server.respond(Method::GET, "/markets", 200, fixtures::markets_page());
+        let (_, cursor) = kalshi
+            .get_markets_page(None, cursor, None, None, None, None, &[], Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(cursor, None);
+        assert_eq!(
+            server.requests_to(Method::GET, "/markets")[1].query_param("status"),
+            None
+        );
+
+        let joined = vec!["A,B".to_string()];
+        let result = kalshi
+            .get_markets_page(None, None, None, None, None, None, &[], joined)
+            .await;
+        assert!(matches!(result, Err(KalshiError::UserInputError(_))));
+    }
+
+    #[test]
+    fn test_category_parsing() {
+        assert_eq!(Category::from("sports"), Category::Sports);
+        assert_eq!(Category::from("Climate"), Category::Climate);
+        assert_eq!(Category::from("Climate and Weather"), Category::Climate);
+        assert_eq!(
+            Category::from("Entertainment"),
+            Category::Other("Entertainment".to_string())
+        );
+        let category: Category = serde_json::from_str("\"Science and Technology\"").unwrap();
+        assert_eq!(category, Category::Science);
+        assert_eq!(
+            serde_json::to_string(&category).unwrap(),
+            "\"Science and Technology\""
+        );
+    }
+
+    #[test]
+    fn test_scalar_implied_values() {
+        let mut json = crate::testing::fixtures::market();
+        json["market_type"] = serde_json::json!("scalar");
+        json["strike_type"] = serde_json::json!("between");
+        json["floor_strike"] = serde_json::json!(2.0);
+        json["cap_strike"] = serde_json::json!(4.0);
+        json["result"] = serde_json::json!("scalar");
+        json["settlement_value"] = serde_json::json!(25);
+        let market: Market = serde_json::from_value(json).unwrap();
+
+        assert!(market.is_scalar());
+        assert_eq!(market.scalar_range(), Some((2.0, 4.0)));
+        assert_eq!(market.implied_value(40.0), Some(2.8));
+        assert_eq!(market.price_for_value(3.0), Some(50.0));
+        assert_eq!(market.price_for_value(5.0), Some(100.0));
+        assert_eq!(market.settled_value(), Some(2.5));
+
+        let binary: Market = serde_json::from_value(crate::testing::fixtures::market()).unwrap();
+        assert_eq!(binary.implied_value(40.0), None);
+    }
+
+    #[test]
+    fn test_event_implied_distribution() {
+        let market = |ticker: &str, status: &str, yes_bid: i64, yes_ask: i64| {
+            let mut json = crate::testing::fixtures::market();
+            json["ticker"] = serde_json::json!(ticker);
+            json["status"] = serde_json::json!(status);
+            json["yes_bid"] = serde_json::json!(yes_bid);
+            json["yes_ask"] = serde_json::json!(yes_ask);
+            serde_json::from_value::<Market>(json).unwrap()
+        };
+        let mut event = Event {
+            event_ticker: "KXWNBAGAME-25SEP17PHXNYL".to_string(),
+            series_ticker: "KXWNBAGAME".to_string(),
+            sub_title: String::new(),
+            title: "Phoenix vs New York (Game 2) Winner?".to_string(),
+            mutually_exclusive: true,
+            category: Category::Sports,
+            markets: Some(vec![
+                market("NYL", "active", 64, 66),
+                market("PHX", "active", 34, 36),
+                market("TIE", "active", 0, 2),
+                market("OLD", "closed", 50, 52),
+            ]),
+            strike_date: None,
+            strike_period: None,
+        };
+
+        let open: Vec<&str> = event
+            .open_markets()
+            .iter()
+            .map(|market| market.ticker.as_str())
+            .collect();
+        assert_eq!(open, vec!["NYL", "PHX", "TIE"]);
+        assert_eq!(
+            event.implied_distribution(),
+            vec![("NYL".to_string(), 0.65), ("PHX".to_string(), 0.35)]
+        );
+
+        event.mutually_exclusive = false;
+        assert!(event.implied_distribution().is_empty());
+    }
+
+    #[test]
+    fn test_prices_as_money() {
+        let mut json = crate::testing::fixtures::market();
+        json["yes_bid"] = serde_json::json!(27);
+        json["yes_bid_dollars"] = serde_json::json!("0.2750");
+        json["yes_ask"] = serde_json::json!(30);
+        json["yes_ask_dollars"] = serde_json::json!("0.9000");
+        json["no_bid"] = serde_json::json!(70);
+        json["no_bid_dollars"] = serde_json::json!("");
+        let market: Market = serde_json::from_value(json).unwrap();
+
+        assert_eq!(
+            market.yes_bid_money(),
+            Money::from_dollars("0.275").unwrap()
+        );
+        // Inconsistent dollars fall back to the cents
+        assert_eq!(market.yes_ask_money(), Money::from_cents(30));
+        assert_eq!(market.no_bid_money(), Money::from_cents(70));
+    }
+
+    #[test]
+    fn test_strike_parsing() {
+        let market: Market = serde_json::from_value(crate::testing::fixtures::market()).unwrap();
+        match &market.strike {
+            Strike::Structured(targets) => assert!(targets.contains_key("basketball_team")),
+            strike => panic!("Expected a structured strike, got {:?}", strike),
+        }
+        let json = serde_json::to_value(&market).unwrap();
+        assert_eq!(json["strike_type"], "structured");
+        assert!(json.get("floor_strike").is_none());
+
+        let strike: Strike = serde_json::from_value(serde_json::json!({
+            "strike_type": "between",
+            "floor_strike": 80.5,
+            "cap_strike": 81.5,
+        }))
+        .unwrap();
+        assert_eq!(
+            strike,
+            Strike::Between {
+                floor: 80.5,
+                cap: 81.5
+            }
+        );
+        assert_eq!(strike.resolves_yes(81.0), Some(true));
+        assert_eq!(strike.resolves_yes(82.0), Some(false));
+
+        let strike: Strike =
+            serde_json::from_value(serde_json::json!({ "strike_type": "greater" })).unwrap();
+        assert_eq!(
+            strike,
+            Strike::Other {
+                strike_type: "greater".to_string(),
+                floor: None,
+                cap: None
+            }
+        );
+        let strike: Strike = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(strike, Strike::None);
+
+        let strike: Strike = serde_json::from_value(serde_json::json!({
+            "strike_type": "custom",
+            "custom_strike": { "rank": 3 },
+        }))
+        .unwrap();
+        match strike {
+            Strike::Custom(targets) => assert_eq!(targets["rank"], "3"),
+            strike => panic!("Expected a custom strike, got {:?}", strike),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streams_resume_from_cursor() {
+        use crate::testing::MockHttpServer;
+
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let empty = serde_json::json!({ "trades": [], "cursor": "" });
+        server.respond(Method::GET, "/markets/trades", 200, empty);
+
+        let saved = Some("saved-cursor".to_string());
+        let trades: Vec<_> = kalshi
+            .get_trades(None, None, saved, None, None, None)
+            .await
+            .collect()
+            .await;
+        assert!(trades.is_empty());
+        let request = &server.requests_to(Method::GET, "/markets/trades")[0];
+        assert_eq!(request.query_param("cursor"), Some("saved-cursor"));
+    }
+
     fn display_json_error_context(error_msg: &str, json_data: &str) {
         // Check if this is a variant error with location information
         if error_msg
@@ -1094,7 +2495,7 @@ mod test {
                 );
                 assert!(!first_series.title.is_empty(), "Title should not be empty");
                 assert!(
-                    !first_series.category.is_empty(),
+                    !first_series.category.as_str().is_empty(),
                     "Category should not be empty"
                 );
 
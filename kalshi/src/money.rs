@@ -0,0 +1,274 @@
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::KalshiError;
+
+/// Ten-thousandths of a dollar in a cent, the precision of the API's `*_dollars` strings.
+const UNITS_PER_CENT: i64 = 100;
+const UNITS_PER_DOLLAR: i64 = 100 * UNITS_PER_CENT;
+
+/// An amount of money, exact to the ten-thousandth of a dollar.
+///
+/// The API reports amounts both as integer cents (`yes_bid`) and as dollar strings with four
+/// decimals (`yes_bid_dollars`), the latter being the only one able to carry sub-cent prices.
+/// `Money` deserializes from either, a number being cents and a string dollars, and serializes to
+/// the dollar string. [`Money::reconcile`] combines both fields of a response into one amount.
+///
+/// ```
+/// let price = Money::from_dollars("0.2750")?;
+/// assert_eq!(price.cents(), 28);
+/// assert_eq!(price.to_string(), "$0.2750");
+/// assert_eq!(Money::from_cents(150).to_string(), "$1.50");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money {
+    /// Ten-thousandths of a dollar
+    units: i64,
+}
+
+impl Money {
+    pub const ZERO: Money = Money { units: 0 };
+
+    pub const fn from_cents(cents: i64) -> Self {
+        Money {
+            units: cents * UNITS_PER_CENT,
+        }
+    }
+
+    /// Parses a dollar amount such as `"0.2700"`, `"-12.5"` or `"3"`, with at most four decimals.
+    pub fn from_dollars(dollars: &str) -> Result<Self, KalshiError> {
+        let invalid =
+            || KalshiError::UserInputError(format!("Invalid dollar amount {:?}", dollars));
+        let trimmed = dollars.trim();
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, trimmed),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let fraction = fraction.trim_end_matches('0');
+        if !digits.chars().any(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        if fraction.len() > 4
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let whole: i64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| invalid())?
+        };
+        let fraction: i64 = format!("{:0<4}", fraction).parse().map_err(|_| invalid())?;
+        let units = whole
+            .checked_mul(UNITS_PER_DOLLAR)
+            .and_then(|units| units.checked_add(fraction))
+            .ok_or_else(invalid)?;
+        Ok(Money {
+            units: if negative { -units } else { units },
+        })
+    }
+
+    /// Combines the cents and dollars fields reporting the same amount.
+    ///
+    /// The dollar string is used when present since it keeps sub-cent precision, and must round
+    /// to the cents. An empty string, which the API sends for unset amounts, counts as absent.
+    pub fn reconcile(cents: i64, dollars: Option<&str>) -> Result<Self, KalshiError> {
+        let Some(dollars) = dollars.filter(|dollars| !dollars.trim().is_empty()) else {
+            return Ok(Money::from_cents(cents));
+        };
+        let money = Money::from_dollars(dollars)?;
+        // The cents are either rounded or truncated depending on the field
+        if (money.units - cents * UNITS_PER_CENT).abs() >= UNITS_PER_CENT {
+            return Err(KalshiError::InternalError(format!(
+                "Inconsistent amounts, {} cents and {} dollars",
+                cents, dollars
+            )));
+        }
+        Ok(money)
+    }
+
+    /// The amount in cents, rounded half away from zero.
+    pub fn cents(&self) -> i64 {
+        let half = UNITS_PER_CENT / 2 * self.units.signum();
+        (self.units + half) / UNITS_PER_CENT
+    }
+
+    /// Whether the amount is a whole number of cents.
+    pub fn is_whole_cents(&self) -> bool {
+        self.units % UNITS_PER_CENT == 0
+    }
+
+    /// The amount in dollars, for display or math that tolerates rounding.
+    pub fn as_dollars(&self) -> f64 {
+        self.units as f64 / UNITS_PER_DOLLAR as f64
+    }
+
+    /// The amount as the API writes it, a dollar string with four decimals like `"0.2700"`.
+    pub fn to_dollars_string(&self) -> String {
+        let sign = if self.units < 0 { "-" } else { "" };
+        let units = self.units.unsigned_abs();
+        format!(
+            "{}{}.{:04}",
+            sign,
+            units / UNITS_PER_DOLLAR as u64,
+            units % UNITS_PER_DOLLAR as u64
+        )
+    }
+}
+
+impl fmt::Display for Money {
+    /// Dollars with two decimals, or four for sub-cent amounts: `$1.50`, `-$0.2750`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.units < 0 { "-" } else { "" };
+        let units = self.units.unsigned_abs();
+        let dollars = units / UNITS_PER_DOLLAR as u64;
+        let fraction = units % UNITS_PER_DOLLAR as u64;
+        if self.is_whole_cents() {
+            write!(
+                f,
+                "{}${}.{:02}",
+                sign,
+                dollars,
+                fraction / UNITS_PER_CENT as u64
+            )
+        } else {
+            write!(f, "{}${}.{:04}", sign, dollars, fraction)
+        }
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, other: Money) -> Money {
+        Money {
+            units: self.units + other.units,
+        }
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.units += other.units;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, other: Money) -> Money {
+        Money {
+            units: self.units - other.units,
+        }
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.units -= other.units;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money { units: -self.units }
+    }
+}
+
+/// The cost of a number of contracts at a price.
+impl Mul<i64> for Money {
+    type Output = Money;
+    fn mul(self, count: i64) -> Money {
+        Money {
+            units: self.units * count,
+        }
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_dollars_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Cents(i64),
+            Dollars(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Cents(cents) => Ok(Money::from_cents(cents)),
+            Raw::Dollars(dollars) => {
+                Money::from_dollars(&dollars).map_err(serde::de::Error::custom)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dollars_parse_and_format() {
+        assert_eq!(
+            Money::from_dollars("0.2700").unwrap(),
+            Money::from_cents(27)
+        );
+        assert_eq!(Money::from_dollars("3").unwrap(), Money::from_cents(300));
+        assert_eq!(Money::from_dollars(".5").unwrap(), Money::from_cents(50));
+        assert_eq!(Money::from_dollars("0").unwrap(), Money::ZERO);
+        assert_eq!(Money::from_dollars("-12.25").unwrap().cents(), -1225);
+        for invalid in ["", "-", ".", "1.23456", "1,00", "$1", "1.2.3"] {
+            assert!(Money::from_dollars(invalid).is_err(), "{:?}", invalid);
+        }
+
+        let sub_cent = Money::from_dollars("0.2750").unwrap();
+        assert_eq!(sub_cent.cents(), 28);
+        assert_eq!((-sub_cent).cents(), -28);
+        assert!(!sub_cent.is_whole_cents());
+        assert_eq!(sub_cent.to_string(), "$0.2750");
+        assert_eq!(sub_cent.to_dollars_string(), "0.2750");
+        assert_eq!(Money::from_cents(-150).to_string(), "-$1.50");
+        assert_eq!(Money::from_cents(-150).to_dollars_string(), "-1.5000");
+        assert_eq!(Money::from_cents(27) * 10, Money::from_cents(270));
+    }
+
+    #[test]
+    fn test_deserializes_from_cents_or_dollars() {
+        let amounts: Vec<Money> = serde_json::from_str(r#"[27, "0.2700", "0.0050"]"#).unwrap();
+        assert_eq!(amounts[0], amounts[1]);
+        assert_eq!(serde_json::to_string(&amounts[2]).unwrap(), r#""0.0050""#);
+    }
+
+    #[test]
+    fn test_reconcile() {
+        assert_eq!(
+            Money::reconcile(27, Some("0.2750")).unwrap(),
+            Money::from_dollars("0.2750").unwrap()
+        );
+        assert_eq!(
+            Money::reconcile(27, Some("")).unwrap(),
+            Money::from_cents(27)
+        );
+        assert_eq!(Money::reconcile(27, None).unwrap(), Money::from_cents(27));
+        assert!(Money::reconcile(27, Some("0.3000")).is_err());
+    }
+}
@@ -1,7 +1,14 @@
 use super::Kalshi;
 use crate::kalshi_error::*;
+use crate::market::{empty_string_as_none, Pager};
+use crate::utils;
+use crate::validation::validate_price;
+use crate::wire::SendLogged;
+use crate::RiskDecision;
+use futures::stream::{Stream, StreamExt};
 use std::fmt;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use tokio::task;
 use uuid::Uuid;
 
@@ -26,7 +33,7 @@ impl Kalshi {
     /// ```
     ///
     pub async fn get_balance(&self) -> Result<i64, KalshiError> {
-        if self.curr_token == None {
+        if self.get_user_token().is_none() {
             return Err(KalshiError::UserInputError(
                 "Not logged in, a valid token is required for requests that require authentication"
                     .to_string(),
@@ -35,18 +42,57 @@ impl Kalshi {
 
         let balance_url: &str = &format!("{}/portfolio/balance", self.base_url.to_string());
 
-        let result: BalanceResponse = self
-            .client
-            .get(balance_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: BalanceResponse = utils::parse_json(
+            self.client
+                .get(balance_url)
+                .header("Authorization", self.get_user_token().unwrap())
+                .send_logged()
+                .await?,
+        )
+        .await?;
 
         Ok(result.balance)
     }
 
+    /// Polls the balance every `interval` and yields it whenever it changed, with the change.
+    ///
+    /// The first poll only sets the balance changes are measured from. Failed polls are yielded as
+    /// errors and polling carries on. Useful to notice fills, settlements or transfers the bot
+    /// didn't make itself, e.g. to alert on unexpected drawdowns.
+    ///
+    /// # Example
+    /// ```
+    /// let mut changes = Box::pin(kalshi_instance.watch_balance(Duration::from_secs(30)));
+    /// while let Some(change) = changes.next().await {
+    ///     let change = change?;
+    ///     if change.delta < -10_000 {
+    ///         log::warn!("Balance dropped by {} cents to {}", -change.delta, change.balance);
+    ///     }
+    /// }
+    /// ```
+    pub fn watch_balance(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<BalanceChange, KalshiError>> + '_ {
+        async_stream::stream! {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last: Option<i64> = None;
+            loop {
+                ticker.tick().await;
+                match self.get_balance().await {
+                    Ok(balance) => {
+                        if let Some(previous) = last.filter(|previous| *previous != balance) {
+                            yield Ok(BalanceChange { balance, delta: balance - previous });
+                        }
+                        last = Some(balance);
+                    }
+                    Err(e) => yield Err(e),
+                }
+            }
+        }
+    }
+
     /// Retrieves a list of orders from the Kalshi exchange based on specified criteria.
     ///
     /// This method fetches multiple orders, allowing for filtering by ticker, event ticker, time range,
@@ -88,7 +134,7 @@ impl Kalshi {
         limit: Option<i32>,
         cursor: Option<String>,
     ) -> Result<(Option<String>, Vec<Order>), KalshiError> {
-        if self.curr_token == None {
+        if self.get_user_token().is_none() {
             return Err(KalshiError::UserInputError(
                 "Not logged in, a valid token is required for requests that require authentication"
                     .to_string(),
@@ -112,14 +158,14 @@ impl Kalshi {
                 panic!("Internal Parse Error, please contact developer!");
             });
 
-        let result: MultipleOrderResponse = self
-            .client
-            .get(user_orders_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: MultipleOrderResponse = utils::parse_json(
+            self.client
+                .get(user_orders_url)
+                .header("Authorization", self.get_user_token().unwrap())
+                .send_logged()
+                .await?,
+        )
+        .await?;
 
         return Ok((result.cursor, result.orders));
     }
@@ -147,7 +193,7 @@ impl Kalshi {
     /// ```
     ///
     pub async fn get_single_order(&self, order_id: &String) -> Result<Order, KalshiError> {
-        if self.curr_token == None {
+        if self.get_user_token().is_none() {
             return Err(KalshiError::UserInputError(
                 "Not logged in, a valid token is required for requests that require authentication"
                     .to_string(),
@@ -159,14 +205,17 @@ impl Kalshi {
             order_id
         );
 
-        let result: SingleOrderResponse = self
-            .client
-            .get(user_order_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: SingleOrderResponse = utils::parse_json(
+            check_trading_response(
+                self.client
+                    .get(user_order_url)
+                    .header("Authorization", self.get_user_token().unwrap())
+                    .send_logged()
+                    .await?,
+            )
+            .await?,
+        )
+        .await?;
 
         return Ok(result.order);
     }
@@ -183,20 +232,35 @@ impl Kalshi {
     ///
     /// # Returns
     ///
-    /// - `Ok((Order, i32))`: A tuple containing the updated `Order` object after cancellation
-    ///   and an integer indicating the amount by which the order was reduced on successful cancellation.
+    /// - `Ok(CanceledOrder)`: The order after cancellation and the number of contracts the
+    ///   cancellation removed from the book.
     /// - `Err(KalshiError)`: An error if the user is not authenticated or if there is an issue with the request.
+    ///   Orders the exchange refuses to cancel because they're already filled or canceled come
+    ///   back as [`TradingErrorKind::OrderAlreadyClosed`](crate::TradingErrorKind::OrderAlreadyClosed).
     ///
     /// # Example
     ///
     /// ```
     /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
     /// let order_id = "some_order_id";
-    /// let (order, reduced_by) = kalshi_instance.cancel_order(order_id).await.unwrap();
+    /// let canceled = kalshi_instance.cancel_order(order_id).await.unwrap();
+    /// match canceled.outcome() {
+    ///     CancelOutcome::Canceled => println!("removed {} contracts", canceled.reduced_by),
+    ///     CancelOutcome::PartiallyFilled => println!("filled in part before the cancel"),
+    ///     CancelOutcome::AlreadyClosed => println!("nothing was left resting"),
+    /// }
     /// ```
     ///
-    pub async fn cancel_order(&self, order_id: &str) -> Result<(Order, i32), KalshiError> {
-        if self.curr_token == None {
+    pub async fn cancel_order(&self, order_id: &str) -> Result<CanceledOrder, KalshiError> {
+        if let Some(dry_run) = &self.dry_run {
+            log::info!("Dry run, not canceling order {}", order_id);
+            let canceled = dry_run.cancel_order(order_id)?;
+            if let Some(tracker) = &self.order_tracker {
+                tracker.on_order_canceled(&canceled.order);
+            }
+            return Ok(canceled);
+        }
+        if self.get_user_token().is_none() {
             return Err(KalshiError::UserInputError(
                 "Not logged in, a valid token is required for requests that require authentication"
                     .to_string(),
@@ -208,16 +272,22 @@ impl Kalshi {
             order_id
         );
 
-        let result: DeleteOrderResponse = self
-            .client
-            .delete(cancel_order_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: CanceledOrder = utils::parse_json(
+            check_trading_response(
+                self.client
+                    .delete(cancel_order_url)
+                    .header("Authorization", self.get_user_token().unwrap())
+                    .send_logged()
+                    .await?,
+            )
+            .await?,
+        )
+        .await?;
 
-        Ok((result.order, result.reduced_by))
+        if let Some(tracker) = &self.order_tracker {
+            tracker.on_order_canceled(&result.order);
+        }
+        Ok(result)
     }
     /// Decreases the size of an existing order on the Kalshi exchange.
     ///
@@ -252,7 +322,7 @@ impl Kalshi {
         reduce_by: Option<i32>,
         reduce_to: Option<i32>,
     ) -> Result<Order, KalshiError> {
-        if self.curr_token == None {
+        if self.get_user_token().is_none() && self.dry_run.is_none() {
             return Err(KalshiError::UserInputError(
                 "Not logged in, a valid token is required for requests that require authentication"
                     .to_string(),
@@ -280,25 +350,244 @@ impl Kalshi {
             _ => {}
         }
 
+        if let Some(dry_run) = &self.dry_run {
+            log::info!(
+                "Dry run, not decreasing order {} (reduce_by: {:?}, reduce_to: {:?})",
+                order_id,
+                reduce_by,
+                reduce_to
+            );
+            let order = dry_run.decrease_order(order_id, reduce_by, reduce_to)?;
+            if let Some(tracker) = &self.order_tracker {
+                tracker.on_order_decreased(&order);
+            }
+            return Ok(order);
+        }
+
         let decrease_payload = DecreaseOrderPayload {
             reduce_by: reduce_by,
             reduce_to: reduce_to,
         };
 
-        let result: SingleOrderResponse = self
+        let result: SingleOrderResponse = utils::parse_json(
+            check_trading_response(
+                self.client
+                    .post(decrease_order_url)
+                    .header("Authorization", self.get_user_token().unwrap())
+                    .header("content-type", "application/json".to_string())
+                    .json(&decrease_payload)
+                    .send_logged()
+                    .await?,
+            )
+            .await?,
+        )
+        .await?;
+
+        if let Some(tracker) = &self.order_tracker {
+            tracker.on_order_decreased(&result.order);
+        }
+        Ok(result.order)
+    }
+
+    /// Amends the price and size of a resting order on the Kalshi exchange, keeping its place
+    /// in the order id.
+    ///
+    /// The order keeps its ticker, side and action. A valid authentication token is required,
+    /// amending isn't supported in dry run mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The order to amend, as last fetched or returned by the exchange.
+    /// * `new_price` - The new limit price in cents, on the order's own side.
+    /// * `new_count` - The new number of contracts of the order.
+    /// * `updated_client_order_id` - The client order id of the amended order, a replacement of
+    ///   `order`'s client order id is generated when `None`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Order)`: The amended `Order` object.
+    /// - `Err(KalshiError)`: An error if the user is not authenticated, the price is out of range,
+    ///   or if there is an issue with the request.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
+    /// let order = kalshi_instance.get_single_order(&order_id).await.unwrap();
+    /// let amended = kalshi_instance.amend_order(&order, 45, 10, None).await.unwrap();
+    /// ```
+    ///
+    pub async fn amend_order(
+        &self,
+        order: &Order,
+        new_price: i64,
+        new_count: i32,
+        updated_client_order_id: Option<String>,
+    ) -> Result<Order, KalshiError> {
+        if self.dry_run.is_some() {
+            return Err(KalshiError::UserInputError(
+                "Amending orders isn't supported in dry run mode".to_string(),
+            ));
+        }
+        if self.get_user_token().is_none() {
+            return Err(KalshiError::UserInputError(
+                "Not logged in, a valid token is required for requests that require authentication"
+                    .to_string(),
+            ));
+        }
+        validate_price(new_price)?;
+        let amend_order_url: &str = &format!(
+            "{}/portfolio/orders/{}/amend",
+            self.base_url, order.order_id
+        );
+
+        let (yes_price, no_price) = match order.side {
+            Side::Yes => (Some(new_price), None),
+            Side::No => (None, Some(new_price)),
+        };
+        let amend_payload = AmendOrderPayload {
+            ticker: order.ticker.clone(),
+            side: order.side,
+            action: order.action,
+            client_order_id: order.client_order_id.clone(),
+            updated_client_order_id: updated_client_order_id
+                .unwrap_or_else(|| next_client_order_id(&order.client_order_id)),
+            count: new_count,
+            yes_price,
+            no_price,
+        };
+
+        let response = self
             .client
-            .post(decrease_order_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
+            .post(amend_order_url)
+            .header("Authorization", self.get_user_token().unwrap())
             .header("content-type", "application/json".to_string())
-            .json(&decrease_payload)
-            .send()
-            .await?
-            .json()
+            .json(&amend_payload)
+            .send_logged()
             .await?;
-
+        let result: AmendOrderResponse =
+            utils::parse_json(check_trading_response(response).await?).await?;
+
+        if let Some(tracker) = &self.order_tracker {
+            if result.old_order.order_id == result.order.order_id {
+                tracker.on_order_amended(&result.order);
+            } else {
+                tracker.on_order_canceled(&result.old_order);
+                tracker.on_order_created(&result.order);
+            }
+        }
         Ok(result.order)
     }
 
+    /// Reprices and resizes a resting order, amending it when possible and otherwise canceling it
+    /// and placing a new one.
+    ///
+    /// The order is only canceled and replaced when amending isn't supported: in dry run mode, or
+    /// when the exchange has no amend endpoint for it (`404` without an error code, `405` or
+    /// `501`). Any other refusal of the amend is returned as is, without touching the order.
+    ///
+    /// The new order has the same ticker, side and action as a limit order. Its client order id
+    /// continues the lineage of the replaced one: `{client_order_id}-r1`, then `-r2` and so on,
+    /// so fills of every version of an order can be traced back to the first. The fallback only
+    /// places the new order once the cancel succeeded, and fails without placing anything if
+    /// nothing was left resting. The replaced order's expiration isn't carried over by the
+    /// fallback.
+    ///
+    /// # Arguments
+    ///
+    /// * `order_id` - The ID of the order to replace.
+    /// * `new_price` - The new limit price in cents, on the order's own side.
+    /// * `new_count` - The number of contracts of the new order.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(OrderReplacement)`: The resting order and whether it was amended or cancel-replaced.
+    /// - `Err(KalshiError)`: An error if the order can't be fetched, the amend is refused, the cancel
+    ///   fails, or the new order can't be placed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
+    /// let replaced = kalshi_instance.replace_order(&order_id, 45, 10).await?;
+    /// if replaced.method == ReplaceMethod::CancelReplaced {
+    ///     println!("replaced by {}", replaced.order.order_id);
+    /// }
+    /// ```
+    ///
+    pub async fn replace_order(
+        &self,
+        order_id: &str,
+        new_price: i64,
+        new_count: i32,
+    ) -> Result<OrderReplacement, KalshiError> {
+        validate_price(new_price)?;
+        let order = match &self.dry_run {
+            Some(dry_run) => dry_run
+                .orders()
+                .into_iter()
+                .find(|order| order.order_id == order_id)
+                .ok_or_else(|| {
+                    KalshiError::UserInputError(format!("Unknown dry run order {}", order_id))
+                })?,
+            None => self.get_single_order(&order_id.to_string()).await?,
+        };
+        let client_order_id = next_client_order_id(&order.client_order_id);
+
+        if self.dry_run.is_none() {
+            match self
+                .amend_order(&order, new_price, new_count, Some(client_order_id.clone()))
+                .await
+            {
+                Ok(amended) => {
+                    return Ok(OrderReplacement {
+                        order: amended,
+                        method: ReplaceMethod::Amended,
+                        canceled: None,
+                    })
+                }
+                Err(e) if amend_unsupported(&e) => log::info!(
+                    "Amending order {} isn't supported, canceling and replacing it: {}",
+                    order_id,
+                    e
+                ),
+                Err(e) => return Err(e),
+            }
+        }
+
+        let canceled = self.cancel_order(order_id).await?;
+        if canceled.outcome() == CancelOutcome::AlreadyClosed {
+            return Err(KalshiError::UserInputError(format!(
+                "Order {} had nothing left resting to replace",
+                order_id
+            )));
+        }
+        let (yes_price, no_price) = match order.side {
+            Side::Yes => (Some(new_price), None),
+            Side::No => (None, Some(new_price)),
+        };
+        let replacement = self
+            .create_order(
+                order.action,
+                Some(client_order_id),
+                new_count,
+                order.side,
+                order.ticker.clone(),
+                OrderType::Limit,
+                None,
+                None,
+                no_price,
+                None,
+                yes_price,
+            )
+            .await?;
+        Ok(OrderReplacement {
+            order: replacement,
+            method: ReplaceMethod::CancelReplaced,
+            canceled: Some(canceled.order),
+        })
+    }
+
     /// Retrieves a list of fills from the Kalshi exchange based on specified criteria.
     ///
     /// This method fetches multiple fills, allowing for filtering by ticker, order ID, time range,
@@ -338,7 +627,7 @@ impl Kalshi {
         limit: Option<i32>,
         cursor: Option<String>,
     ) -> Result<(Option<String>, Vec<Fill>), KalshiError> {
-        if self.curr_token == None {
+        if self.get_user_token().is_none() {
             return Err(KalshiError::UserInputError(
                 "Not logged in, a valid token is required for requests that require authentication"
                     .to_string(),
@@ -361,21 +650,22 @@ impl Kalshi {
                 panic!("Internal Parse Error, please contact developer!");
             });
 
-        let result: MultipleFillsResponse = self
-            .client
-            .get(user_fills_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: MultipleFillsResponse = utils::parse_json(
+            self.client
+                .get(user_fills_url)
+                .header("Authorization", self.get_user_token().unwrap())
+                .send_logged()
+                .await?,
+        )
+        .await?;
 
         return Ok((result.cursor, result.fills));
     }
 
     /// Retrieves a list of portfolio settlements from the Kalshi exchange.
     ///
-    /// This method fetches settlements in the user's portfolio, with options for pagination using limit and cursor.
+    /// This method fetches settlements in the user's portfolio, with options for filtering by market,
+    /// event and settlement time, as well as pagination using limit and cursor.
     /// A valid authentication token is required to access this information.
     /// If the user is not logged in or the token is missing, it returns an error.
     ///
@@ -383,6 +673,10 @@ impl Kalshi {
     ///
     /// * `limit` - An optional integer to limit the number of settlements returned.
     /// * `cursor` - An optional string for pagination cursor.
+    /// * `ticker` - An optional string to filter settlements by market ticker.
+    /// * `event_ticker` - An optional string to filter settlements by event ticker.
+    /// * `min_ts` - An optional minimum timestamp for the settlement time.
+    /// * `max_ts` - An optional maximum timestamp for the settlement time.
     ///
     /// # Returns
     ///
@@ -394,15 +688,22 @@ impl Kalshi {
     ///
     /// ```
     /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
-    /// let settlements = kalshi_instance.get_portfolio_settlements(None, None).await.unwrap();
+    /// let settlements = kalshi_instance
+    ///     .get_portfolio_settlements(None, None, Some("KXHIGHNY-25OCT02-B80.5".to_string()), None, None, None)
+    ///     .await
+    ///     .unwrap();
     /// ```
     ///
     pub async fn get_portfolio_settlements(
         &self,
         limit: Option<i64>,
         cursor: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
     ) -> Result<(Option<String>, Vec<Settlement>), KalshiError> {
-        if self.curr_token == None {
+        if self.get_user_token().is_none() {
             return Err(KalshiError::UserInputError(
                 "Not logged in, a valid token is required for requests that require authentication"
                     .to_string(),
@@ -414,6 +715,10 @@ impl Kalshi {
 
         add_param!(params, "limit", limit);
         add_param!(params, "cursor", cursor);
+        add_param!(params, "ticker", ticker);
+        add_param!(params, "event_ticker", event_ticker);
+        add_param!(params, "min_ts", min_ts);
+        add_param!(params, "max_ts", max_ts);
 
         let settlements_url = reqwest::Url::parse_with_params(settlements_url, &params)
             .unwrap_or_else(|err| {
@@ -421,14 +726,14 @@ impl Kalshi {
                 panic!("Internal Parse Error, please contact developer!");
             });
 
-        let result: PortfolioSettlementResponse = self
-            .client
-            .get(settlements_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: PortfolioSettlementResponse = utils::parse_json(
+            self.client
+                .get(settlements_url)
+                .header("Authorization", self.get_user_token().unwrap())
+                .send_logged()
+                .await?,
+        )
+        .await?;
 
         Ok((result.cursor, result.settlements))
     }
@@ -436,46 +741,126 @@ impl Kalshi {
     /// Retrieves the user's positions in events and markets from the Kalshi exchange.
     ///
     /// This method fetches the user's positions, providing options for filtering by settlement status,
-    /// specific ticker, and event ticker, as well as pagination using limit and cursor. A valid
-    /// authentication token is required to access this information. If the user is not logged in
-    /// or the token is missing, it returns an error.
+    /// specific ticker, and event ticker. Pages are requested as the stream is polled, following the
+    /// cursors until `limit` market positions were fetched or the last page is reached, so accounts
+    /// with more positions than fit in a page aren't cut short. A valid authentication token is
+    /// required to access this information. If the user is not logged in or the token is missing,
+    /// the stream yields an error.
     ///
     /// # Arguments
     ///
-    /// * `limit` - An optional integer to limit the number of positions returned.
-    /// * `cursor` - An optional string for pagination cursor.
+    /// * `limit` - An optional total number of market positions to return, every page is fetched when `None`.
+    /// * `page_size` - An optional number of positions requested per page.
+    /// * `cursor` - An optional cursor to resume from, as returned by [`Kalshi::get_positions_page`].
     /// * `settlement_status` - An optional string to filter positions by their settlement status.
     /// * `ticker` - An optional string to filter positions by market ticker.
     /// * `event_ticker` - An optional string to filter positions by event ticker.
     ///
     /// # Returns
     ///
-    /// - `Ok((Option<String>, Vec<EventPosition>, Vec<MarketPosition>))`: A tuple containing an optional pagination cursor,
-    ///   a vector of `EventPosition` objects, and a vector of `MarketPosition` objects on successful retrieval.
-    /// - `Err(KalshiError)`: An error if the user is not authenticated or if there is an issue with the request.
+    /// A stream yielding, for each page:
+    /// - `Ok((Vec<EventPosition>, Vec<MarketPosition>))`: The page's `EventPosition` and `MarketPosition` objects.
+    /// - `Err(KalshiError)`: An error if the user is not authenticated or if there is an issue with the request,
+    ///   the stream ends after it.
     ///
     /// # Example
     ///
     /// ```
     /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
-    /// let user_positions = kalshi_instance.get_user_positions(None, None, None, None, None).await.unwrap();
+    /// let positions = kalshi_instance
+    ///     .get_user_positions(None, None, None, None, None, None)
+    ///     .await;
+    /// pin_mut!(positions);
+    /// while let Some(page) = positions.next().await {
+    ///     let (event_positions, market_positions) = page?;
+    /// }
     /// ```
     ///
     pub async fn get_user_positions(
+        &self,
+        limit: Option<i64>,
+        page_size: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+    ) -> impl Stream<Item = Result<(Vec<EventPosition>, Vec<MarketPosition>), KalshiError>> + '_
+    {
+        async_stream::stream! {
+            let mut pager = Pager::new(limit, page_size, 100);
+            let mut cursor = cursor;
+
+            while let Some(page_size) = pager.next_page_size() {
+                let page = self
+                    .get_positions_page(
+                        Some(page_size as i64),
+                        cursor.take(),
+                        settlement_status.clone(),
+                        ticker.clone(),
+                        event_ticker.clone(),
+                    )
+                    .await;
+                let (event_positions, market_positions, next_cursor) = match page {
+                    Ok(page) => page,
+                    Err(e) => {
+                        yield Err(e);
+                        break;
+                    }
+                };
+
+                let position_count = market_positions.len();
+                pager.record(position_count);
+
+                yield Ok((event_positions, market_positions));
+
+                log::debug!("Fetched {} positions ({} new)", pager.fetched(), position_count);
+
+                match next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    /// Retrieves a single page of the user's positions, see [`Kalshi::get_user_positions`] for the filters.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - An optional number of positions in the page.
+    /// * `cursor` - The cursor returned with the previous page, `None` for the first page.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok((Vec<EventPosition>, Vec<MarketPosition>, Option<String>))`: The positions of the page and
+    ///   the cursor of the next one, `None` after the last page.
+    /// - `Err(KalshiError)`: An error if the user is not authenticated or if there is an issue with the request.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
+    /// let (event_positions, market_positions, next_cursor) = kalshi_instance
+    ///     .get_positions_page(Some(1000), saved_cursor, None, None, None)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    ///
+    pub async fn get_positions_page(
         &self,
         limit: Option<i64>,
         cursor: Option<String>,
         settlement_status: Option<String>,
         ticker: Option<String>,
         event_ticker: Option<String>,
-    ) -> Result<(Option<String>, Vec<EventPosition>, Vec<MarketPosition>), KalshiError> {
-        if self.curr_token == None {
+    ) -> Result<(Vec<EventPosition>, Vec<MarketPosition>, Option<String>), KalshiError> {
+        if self.get_user_token().is_none() {
             return Err(KalshiError::UserInputError(
                 "Not logged in, a valid token is required for requests that require authentication"
                     .to_string(),
             ));
         }
-        let positions_url: &str = &format!("{}/portfolio/positions", self.base_url.to_string());
+        let positions_url: &str = &format!("{}/portfolio/positions", self.base_url);
 
         let mut params: Vec<(&str, String)> = Vec::with_capacity(6);
 
@@ -491,22 +876,104 @@ impl Kalshi {
                 panic!("Internal Parse Error, please contact developer!");
             });
 
-        let result: GetPositionsResponse = self
-            .client
-            .get(positions_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
-            .send()
-            .await?
-            .json()
-            .await?;
+        let result: GetPositionsResponse = utils::parse_json(
+            self.client
+                .get(positions_url)
+                .header("Authorization", self.get_user_token().unwrap())
+                .send_logged()
+                .await?,
+        )
+        .await?;
 
         Ok((
-            result.cursor,
             result.event_positions,
             result.market_positions,
+            result.cursor,
         ))
     }
 
+    /// Retrieves the balance, positions, resting orders and recent fills of the account at once.
+    ///
+    /// The requests are made concurrently and every page of positions and resting orders is
+    /// fetched, fills are limited to the latest [`SNAPSHOT_FILLS`]. Useful to load the account's
+    /// state on startup or to audit it periodically. The whole snapshot fails if any request does.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(PortfolioSnapshot)`: The account's state, stamped with the time it was requested.
+    /// - `Err(KalshiError)`: An error if the user is not authenticated or if there is an issue with a request.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
+    /// let snapshot = kalshi_instance.get_portfolio_snapshot().await?;
+    /// println!(
+    ///     "{:?}: {} cents, {} positions, {} resting orders",
+    ///     snapshot.taken_at,
+    ///     snapshot.balance,
+    ///     snapshot.market_positions.len(),
+    ///     snapshot.resting_orders.len()
+    /// );
+    /// ```
+    ///
+    pub async fn get_portfolio_snapshot(&self) -> Result<PortfolioSnapshot, KalshiError> {
+        let taken_at = SystemTime::now();
+        let (balance, (event_positions, market_positions), resting_orders, (_, recent_fills)) = futures::try_join!(
+            self.get_balance(),
+            self.get_all_positions(),
+            self.get_all_resting_orders(),
+            self.get_multiple_fills(None, None, None, None, Some(SNAPSHOT_FILLS), None),
+        )?;
+        Ok(PortfolioSnapshot {
+            taken_at,
+            balance,
+            event_positions,
+            market_positions,
+            resting_orders,
+            recent_fills,
+        })
+    }
+
+    async fn get_all_positions(
+        &self,
+    ) -> Result<(Vec<EventPosition>, Vec<MarketPosition>), KalshiError> {
+        let (mut event_positions, mut market_positions) = (Vec::new(), Vec::new());
+        let pages = self
+            .get_user_positions(None, Some(1000), None, None, None, None)
+            .await;
+        futures::pin_mut!(pages);
+        while let Some(page) = pages.next().await {
+            let (mut events, mut markets) = page?;
+            event_positions.append(&mut events);
+            market_positions.append(&mut markets);
+        }
+        Ok((event_positions, market_positions))
+    }
+
+    async fn get_all_resting_orders(&self) -> Result<Vec<Order>, KalshiError> {
+        let mut resting = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next_cursor, mut page) = self
+                .get_multiple_orders(
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some("resting".to_string()),
+                    Some(1000),
+                    cursor,
+                )
+                .await?;
+            resting.append(&mut page);
+            match next_cursor {
+                Some(next) if !next.is_empty() => cursor = Some(next),
+                _ => return Ok(resting),
+            }
+        }
+    }
+
     /// Submits an order to the Kalshi exchange.
     ///
     /// This method allows placing an order in the market, requiring details such as action, count, side,
@@ -571,7 +1038,7 @@ impl Kalshi {
         sell_position_floor: Option<i32>,
         yes_price: Option<i64>,
     ) -> Result<Order, KalshiError> {
-        if self.curr_token == None {
+        if self.get_user_token().is_none() && self.dry_run.is_none() {
             return Err(KalshiError::UserInputError(
                 "Not logged in, a valid token is required for requests that require authentication"
                     .to_string(),
@@ -579,23 +1046,30 @@ impl Kalshi {
         }
         let order_url: &str = &format!("{}/portfolio/orders", self.base_url.to_string());
 
-        match input_type {
-            OrderType::Limit => match (no_price, yes_price) {
-                (Some(_), Some(_)) => {
-                    return Err(KalshiError::UserInputError(
-                        "Can only provide no_price exclusive or yes_price, can't provide both"
-                            .to_string(),
-                    ));
+        self.check_order(input_type, side, &ticker, count, yes_price, no_price)
+            .await?;
+        let price = crate::risk::side_price(side, yes_price, no_price);
+
+        let mut count = count;
+        if let Some(risk_manager) = &self.risk_manager {
+            match risk_manager.check_order(&ticker, action, side, count, price) {
+                RiskDecision::Accept => {}
+                RiskDecision::Shrink(shrunk) => {
+                    log::info!(
+                        "Risk manager shrunk order on {} from {} to {} contracts",
+                        ticker,
+                        count,
+                        shrunk
+                    );
+                    count = shrunk;
                 }
-                (None, None) => {
-                    return Err(KalshiError::UserInputError(
-                            "Must provide either no_price exclusive or yes_price, can't provide neither"
-                                .to_string(),
-                        ));
+                RiskDecision::Reject(reason) => {
+                    return Err(KalshiError::UserInputError(format!(
+                        "Order rejected by risk manager: {}",
+                        reason
+                    )));
                 }
-                _ => {}
-            },
-            _ => {}
+            }
         }
 
         let unwrapped_id = match client_order_id {
@@ -603,6 +1077,28 @@ impl Kalshi {
             _ => String::from(Uuid::new_v4()),
         };
 
+        if let Some(dry_run) = &self.dry_run {
+            let order = OrderCreationField {
+                action,
+                client_order_id: None,
+                count,
+                side,
+                ticker,
+                input_type,
+                buy_max_cost,
+                expiration_ts,
+                no_price,
+                sell_position_floor,
+                yes_price,
+            };
+            log::info!("Dry run, not placing order {:?}", order);
+            let order = dry_run.create_order(&order, unwrapped_id);
+            if let Some(tracker) = &self.order_tracker {
+                tracker.on_order_created(&order);
+            }
+            return Ok(order);
+        }
+
         let order_payload = CreateOrderPayload {
             action: action,
             client_order_id: unwrapped_id,
@@ -620,17 +1116,22 @@ impl Kalshi {
         let response = self
             .client
             .post(order_url)
-            .header("Authorization", self.curr_token.clone().unwrap())
+            .header("Authorization", self.get_user_token().unwrap())
             .header("content-type", "application/json".to_string())
             .json(&order_payload)
-            .send()
+            .send_logged()
             .await;
 
         match response {
-            Ok(resp) => {
-                if resp.status().is_success() {
-                    match resp.json::<SingleOrderResponse>().await {
-                        Ok(order_response) => Ok(order_response.order),
+            Ok(resp) => match check_trading_response(resp).await {
+                Ok(resp) => {
+                    match utils::parse_json::<SingleOrderResponse>(resp).await {
+                        Ok(order_response) => {
+                            if let Some(tracker) = &self.order_tracker {
+                                tracker.on_order_created(&order_response.order);
+                            }
+                            Ok(order_response.order)
+                        }
                         Err(json_err) => {
                             // Handle JSON decoding error
                             let error_message =
@@ -639,13 +1140,13 @@ impl Kalshi {
                             Err(KalshiError::InternalError(error_message))
                         }
                     }
-                } else {
-                    // Handle non-success HTTP status codes
-                    let error_message = format!("HTTP Error: {}", resp.status());
-                    eprintln!("{}", error_message);
-                    Err(KalshiError::InternalError(error_message))
                 }
-            }
+                Err(e) => {
+                    // A refused order or a server error, whose outcome is unknown
+                    eprintln!("HTTP Error: {}", e);
+                    Err(e)
+                }
+            },
             Err(request_err) => {
                 // Handle errors in sending the request
                 let error_message = format!("Failed to send request: {}", request_err);
@@ -655,10 +1156,54 @@ impl Kalshi {
         }
     }
 
+    /// The checks every order goes through before the risk manager sees it: a limit order needs
+    /// exactly one price, prices must be valid, and with order validation on the market's own
+    /// limits are checked too.
+    async fn check_order(
+        &self,
+        input_type: OrderType,
+        side: Side,
+        ticker: &str,
+        count: i32,
+        yes_price: Option<i64>,
+        no_price: Option<i64>,
+    ) -> Result<(), KalshiError> {
+        match input_type {
+            OrderType::Limit => match (no_price, yes_price) {
+                (Some(_), Some(_)) => {
+                    return Err(KalshiError::UserInputError(
+                        "Can only provide no_price exclusive or yes_price, can't provide both"
+                            .to_string(),
+                    ));
+                }
+                (None, None) => {
+                    return Err(KalshiError::UserInputError(
+                            "Must provide either no_price exclusive or yes_price, can't provide neither"
+                                .to_string(),
+                        ));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+
+        for price in yes_price.iter().chain(no_price.iter()) {
+            validate_price(*price)?;
+        }
+        // Prices are checked on the order's own side
+        let price = crate::risk::side_price(side, yes_price, no_price);
+        if self.validate_orders {
+            self.get_single_market(&ticker.to_string())
+                .await?
+                .validate_order(count, price)?;
+        }
+        Ok(())
+    }
+
     pub async fn batch_cancel_order(
-        &mut self,
+        &self,
         batch: Vec<String>,
-    ) -> Result<Vec<Result<(Order, i32), KalshiError>>, KalshiError> {
+    ) -> Result<Vec<Result<CanceledOrder, KalshiError>>, KalshiError> {
         let temp_instance = Arc::new(self.clone());
         let mut futures = Vec::new();
 
@@ -687,11 +1232,179 @@ impl Kalshi {
         Ok(outputs)
     }
 
+    /// Places several orders through the batch endpoint, returning the outcome of each in the order they were given.
+    ///
+    /// Every order goes through the same checks as [`Kalshi::create_order`], including an attached
+    /// order tracker. An attached risk manager checks the batch as a whole with
+    /// [`RiskManager::check_batch`](crate::RiskManager::check_batch) before anything is sent.
+    /// Batches larger than the exchange accepts in one request are split into several requests, a
+    /// request that fails as a whole fails every order in it.
     pub async fn batch_create_order(
-        &mut self,
+        &self,
         batch: Vec<OrderCreationField>,
-    ) -> Result<Vec<Result<(Order, i32), KalshiError>>, KalshiError> {
-        todo!()
+    ) -> Result<Vec<Result<Order, KalshiError>>, KalshiError> {
+        if self.get_user_token().is_none() && self.dry_run.is_none() {
+            return Err(KalshiError::UserInputError(
+                "Not logged in, a valid token is required for requests that require authentication"
+                    .to_string(),
+            ));
+        }
+        let decisions = match &self.risk_manager {
+            Some(risk_manager) => risk_manager.check_batch(&batch),
+            None => vec![RiskDecision::Accept; batch.len()],
+        };
+
+        let mut outputs: Vec<Option<Result<Order, KalshiError>>> = Vec::new();
+        let mut payloads = Vec::new();
+        for (index, (order, decision)) in batch.into_iter().zip(decisions).enumerate() {
+            let (
+                action,
+                client_order_id,
+                mut count,
+                side,
+                ticker,
+                input_type,
+                buy_max_cost,
+                expiration_ts,
+                no_price,
+                sell_position_floor,
+                yes_price,
+            ) = order.get_params();
+            if let Err(e) = self
+                .check_order(input_type, side, &ticker, count, yes_price, no_price)
+                .await
+            {
+                outputs.push(Some(Err(e)));
+                continue;
+            }
+            match decision {
+                RiskDecision::Accept => {}
+                RiskDecision::Shrink(shrunk) => {
+                    log::info!(
+                        "Risk manager shrunk order on {} from {} to {} contracts",
+                        ticker,
+                        count,
+                        shrunk
+                    );
+                    count = shrunk;
+                }
+                RiskDecision::Reject(reason) => {
+                    outputs.push(Some(Err(KalshiError::UserInputError(format!(
+                        "Order rejected by risk manager: {}",
+                        reason
+                    )))));
+                    continue;
+                }
+            }
+
+            let unwrapped_id = client_order_id.unwrap_or_else(|| String::from(Uuid::new_v4()));
+            if let Some(dry_run) = &self.dry_run {
+                let order = OrderCreationField {
+                    action,
+                    client_order_id: None,
+                    count,
+                    side,
+                    ticker,
+                    input_type,
+                    buy_max_cost,
+                    expiration_ts,
+                    no_price,
+                    sell_position_floor,
+                    yes_price,
+                };
+                log::info!("Dry run, not placing order {:?}", order);
+                let order = dry_run.create_order(&order, unwrapped_id);
+                if let Some(tracker) = &self.order_tracker {
+                    tracker.on_order_created(&order);
+                }
+                outputs.push(Some(Ok(order)));
+                continue;
+            }
+
+            outputs.push(None);
+            payloads.push((
+                index,
+                CreateOrderPayload {
+                    action,
+                    client_order_id: unwrapped_id,
+                    count,
+                    side,
+                    ticker,
+                    r#type: input_type,
+                    buy_max_cost,
+                    expiration_ts,
+                    no_price,
+                    sell_position_floor,
+                    yes_price,
+                },
+            ));
+        }
+
+        for chunk in payloads.chunks(BATCH_CREATE_LIMIT) {
+            let orders: Vec<&CreateOrderPayload> = chunk.iter().map(|(_, order)| order).collect();
+            match self.post_order_batch(&orders).await {
+                Ok(results) => {
+                    let mut results = results.into_iter();
+                    for (index, _) in chunk {
+                        outputs[*index] = Some(match results.next() {
+                            Some(BatchCreatedOrder {
+                                order: Some(order), ..
+                            }) => {
+                                if let Some(tracker) = &self.order_tracker {
+                                    tracker.on_order_created(&order);
+                                }
+                                Ok(order)
+                            }
+                            Some(BatchCreatedOrder {
+                                error: Some(error), ..
+                            }) => Err(KalshiError::TradingError(error.into_trading_error(400))),
+                            _ => Err(KalshiError::InternalError(
+                                "Batch response had no result for the order".to_string(),
+                            )),
+                        });
+                    }
+                }
+                Err(e) => {
+                    eprintln!("HTTP Error: {}", e);
+                    for (index, _) in chunk {
+                        outputs[*index] = Some(Err(match &e {
+                            KalshiError::TradingError(error) => {
+                                KalshiError::TradingError(error.clone())
+                            }
+                            other => KalshiError::InternalError(format!(
+                                "Batch order request failed: {}",
+                                other
+                            )),
+                        }));
+                    }
+                }
+            }
+        }
+        Ok(outputs.into_iter().flatten().collect())
+    }
+
+    /// Sends one request to the batch create endpoint, the results come back in the order sent.
+    async fn post_order_batch(
+        &self,
+        orders: &[&CreateOrderPayload],
+    ) -> Result<Vec<BatchCreatedOrder>, KalshiError> {
+        let batch_url: &str = &format!("{}/portfolio/orders/batched", self.base_url);
+        let response = self
+            .client
+            .post(batch_url)
+            .header("Authorization", self.get_user_token().unwrap())
+            .header("content-type", "application/json".to_string())
+            .json(&BatchCreateOrdersPayload { orders })
+            .send_logged()
+            .await
+            .map_err(|e| KalshiError::InternalError(format!("Failed to send request: {}", e)))?;
+        let response = check_trading_response(response).await?;
+        utils::parse_json::<BatchCreateOrdersResponse>(response)
+            .await
+            .map(|response| response.orders)
+            .map_err(|e| {
+                KalshiError::InternalError(format!("Failed to decode JSON response: {}", e))
+            })
     }
 }
 
@@ -727,16 +1440,48 @@ where
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-struct DeleteOrderResponse {
-    order: Order,
-    reduced_by: i32,
+struct AmendOrderPayload {
+    ticker: String,
+    side: Side,
+    action: Action,
+    client_order_id: String,
+    updated_client_order_id: String,
+    count: i32,
+    yes_price: Option<i64>,
+    no_price: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-struct DecreaseOrderResponse {
+struct AmendOrderResponse {
+    old_order: Order,
     order: Order,
 }
 
+/// Whether an amend failed because the exchange can't amend the order, rather than refusing it.
+fn amend_unsupported(error: &KalshiError) -> bool {
+    match error {
+        // A missing order comes with an error code, a missing endpoint doesn't
+        KalshiError::TradingError(e) => e.status == 405 || (e.status == 404 && e.code.is_empty()),
+        KalshiError::RequestError(RequestError::ServerError(e)) => {
+            e.status() == Some(reqwest::StatusCode::NOT_IMPLEMENTED)
+        }
+        _ => false,
+    }
+}
+
+/// The client order id of the order replacing one, `{root}-r{n}` for the nth replacement.
+fn next_client_order_id(client_order_id: &str) -> String {
+    if client_order_id.is_empty() {
+        return String::from(Uuid::new_v4());
+    }
+    match client_order_id.rsplit_once("-r") {
+        Some((root, n)) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => {
+            format!("{}-r{}", root, n.parse::<u64>().map_or(1, |n| n + 1))
+        }
+        _ => format!("{}-r1", client_order_id),
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct DecreaseOrderPayload {
     reduce_by: Option<i32>,
@@ -757,11 +1502,34 @@ struct PortfolioSettlementResponse {
 
 #[derive(Debug, Deserialize, Serialize)]
 struct GetPositionsResponse {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
     cursor: Option<String>,
     event_positions: Vec<EventPosition>,
     market_positions: Vec<MarketPosition>,
 }
 
+/// The most orders the batch create endpoint takes in one request.
+const BATCH_CREATE_LIMIT: usize = 20;
+
+#[derive(Debug, Serialize)]
+struct BatchCreateOrdersPayload<'a> {
+    orders: &'a [&'a CreateOrderPayload],
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchCreateOrdersResponse {
+    orders: Vec<BatchCreatedOrder>,
+}
+
+/// One order's outcome in a batch, either the created order or why it was refused.
+#[derive(Debug, Deserialize)]
+struct BatchCreatedOrder {
+    #[serde(default)]
+    order: Option<Order>,
+    #[serde(default)]
+    error: Option<ErrorBody>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct CreateOrderPayload {
     action: Action,
@@ -784,7 +1552,7 @@ struct CreateOrderPayload {
 ///
 /// This struct details an individual order, including its identification, status, prices, and various metrics related to its lifecycle.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Order {
     /// Unique identifier for the order.
     pub order_id: String,
@@ -820,6 +1588,12 @@ pub struct Order {
     pub queue_position: Option<i32>,
     /// Expiration time of the order. Optional.
     pub expiration_time: Option<String>,
+    /// How long the order works before the exchange cancels it. Optional.
+    #[serde(default)]
+    pub time_in_force: Option<TimeInForce>,
+    /// Whether the order is canceled rather than taking liquidity.
+    #[serde(default)]
+    pub post_only: bool,
     /// Fees incurred as a taker. Optional.
     pub taker_fees: Option<i32>,
     /// The action (buy/sell) of the order.
@@ -836,12 +1610,107 @@ pub struct Order {
     pub order_group_id: String,
 }
 
+impl Order {
+    /// The expiration time of the order as a unix timestamp in seconds, `None` for orders working
+    /// until canceled or with an expiration time that can't be parsed.
+    pub fn expiration_ts(&self) -> Option<i64> {
+        self.expiration_time
+            .as_deref()
+            .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.timestamp())
+    }
+}
+
+/// The order repriced by [`Kalshi::replace_order`].
+#[derive(Debug, Clone)]
+pub struct OrderReplacement {
+    /// The order now resting, amended or newly placed.
+    pub order: Order,
+    pub method: ReplaceMethod,
+    /// The replaced order as canceled, when it was cancel-replaced.
+    pub canceled: Option<Order>,
+}
+
+/// An order canceled by [`Kalshi::cancel_order`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CanceledOrder {
+    /// The order after the cancellation.
+    pub order: Order,
+    /// Contracts the cancellation removed from the book, 0 when none were left resting.
+    pub reduced_by: i32,
+}
+
+impl CanceledOrder {
+    /// Contracts filled before the cancellation.
+    pub fn filled_count(&self) -> i32 {
+        self.order.taker_fill_count.unwrap_or_default()
+            + self.order.maker_fill_count.unwrap_or_default()
+    }
+
+    pub fn outcome(&self) -> CancelOutcome {
+        if self.reduced_by <= 0 {
+            CancelOutcome::AlreadyClosed
+        } else if self.filled_count() > 0 {
+            CancelOutcome::PartiallyFilled
+        } else {
+            CancelOutcome::Canceled
+        }
+    }
+}
+
+/// What a cancellation did to an order, see [`CanceledOrder::outcome`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelOutcome {
+    /// The whole order was resting and is now canceled.
+    Canceled,
+    /// The order filled in part, the rest is now canceled.
+    PartiallyFilled,
+    /// Nothing was left resting, the order had already filled or been canceled.
+    AlreadyClosed,
+}
+
+/// How [`Kalshi::replace_order`] repriced an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaceMethod {
+    /// The order was amended in place.
+    Amended,
+    /// The order was canceled and a new one placed.
+    CancelReplaced,
+}
+
+/// How many of the latest fills [`Kalshi::get_portfolio_snapshot`] fetches.
+pub const SNAPSHOT_FILLS: i32 = 100;
+
+/// The state of the account at one point in time, see [`Kalshi::get_portfolio_snapshot`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortfolioSnapshot {
+    /// When the snapshot was requested.
+    pub taken_at: SystemTime,
+    /// The balance in cents.
+    pub balance: i64,
+    pub event_positions: Vec<EventPosition>,
+    pub market_positions: Vec<MarketPosition>,
+    /// Orders still resting on the book.
+    pub resting_orders: Vec<Order>,
+    /// The latest fills, most recent first.
+    pub recent_fills: Vec<Fill>,
+}
+
+/// A change of the account balance seen by [`Kalshi::watch_balance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceChange {
+    /// The new balance in cents.
+    pub balance: i64,
+    /// The change since the previous poll in cents, negative when the balance dropped.
+    pub delta: i64,
+}
+
 /// A completed transaction (a 'fill') in the Kalshi exchange.
 ///
 /// This struct details a single fill instance, including the action taken, the quantity,
 /// the involved prices, and the identifiers of the order and trade.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Fill {
     /// The action (buy/sell) of the fill.
     pub action: Action,
@@ -888,6 +1757,9 @@ pub struct Settlement {
     pub yes_count: i64,
     /// The total cost associated with the 'Yes' position, in cents.
     pub yes_total_cost: i64,
+    /// What a yes contract paid out in cents, for scalar markets.
+    #[serde(default)]
+    pub value: Option<i64>,
 }
 
 /// A user's position in a specific event on the Kalshi exchange.
@@ -938,7 +1810,7 @@ pub struct MarketPosition {
 /// This struct is used to encapsulate all the data needed to create a new order. It includes details about the order type,
 /// the action being taken (buy/sell), the market ticker, and various other optional parameters that can be specified
 /// to fine-tune the order according to the user's needs.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OrderCreationField {
     /// The action (buy/sell) of the order.
     pub action: Action,
@@ -1000,7 +1872,7 @@ impl OrderParams for OrderCreationField {
 ///
 /// This enum is used to indicate whether a market position, order, or trade is associated with the 'Yes' or 'No' outcome of a market event.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Side {
     /// Represents a position, order, or trade associated with the 'Yes' outcome of a market event.
@@ -1011,7 +1883,7 @@ pub enum Side {
 
 /// This enum is used to specify the type of action a user wants to take in an order, either buying or selling.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Action {
     /// Represents a buy action.
@@ -1033,7 +1905,7 @@ impl fmt::Display for Action {
 ///
 /// This enum categorizes an order's lifecycle state, from creation to completion or cancellation.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderStatus {
     /// The order is active but not yet filled or partially filled and still in the order book.
@@ -1061,7 +1933,7 @@ impl fmt::Display for OrderStatus {
 ///
 /// This enum is used to specify the nature of the order, particularly how it interacts with the market.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderType {
     /// A market order is executed immediately at the current market price.
@@ -1070,6 +1942,25 @@ pub enum OrderType {
     Limit,
 }
 
+/// How long an order works before the exchange cancels what's left of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInForce {
+    /// The order rests until canceled, or until its expiration time if it has one.
+    GoodTillCanceled,
+    /// Whatever can't fill immediately is canceled.
+    ImmediateOrCancel,
+    /// The order fills completely and immediately or not at all.
+    FillOrKill,
+}
+
+impl TimeInForce {
+    /// Whether orders with this time in force can rest on the book.
+    pub fn can_rest(&self) -> bool {
+        *self == TimeInForce::GoodTillCanceled
+    }
+}
+
 trait OrderParams {
     fn get_params(
         self,
@@ -1126,7 +2017,10 @@ impl OrderParams
 
 #[cfg(test)]
 mod test {
+    use super::*;
     use crate::portfolio::MultipleOrderResponse;
+    use crate::testing::{fixtures, MockHttpServer};
+    use reqwest::Method;
 
     #[test]
     fn test_serialize_multiple_order_response() -> serde_json::Result<()> {
@@ -1136,4 +2030,347 @@ mod test {
         assert!(result.cursor.is_none());
         Ok(())
     }
+
+    #[test]
+    fn test_client_order_id_lineage() {
+        assert_eq!(next_client_order_id("mm-1"), "mm-1-r1");
+        assert_eq!(next_client_order_id("mm-1-r1"), "mm-1-r2");
+        assert_eq!(next_client_order_id("mm-r9"), "mm-r10");
+        assert_eq!(next_client_order_id("mm-rx"), "mm-rx-r1");
+    }
+
+    #[tokio::test]
+    async fn test_portfolio_snapshot() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+
+        let snapshot = kalshi.get_portfolio_snapshot().await.unwrap();
+        assert_eq!(snapshot.balance, 10_000);
+        assert_eq!(snapshot.event_positions.len(), 1);
+        assert_eq!(snapshot.market_positions[0].position, 4);
+        assert_eq!(snapshot.resting_orders[0].order_id, fixtures::ORDER_ID);
+        assert_eq!(snapshot.recent_fills[0].count, 4);
+        let orders = server.requests_to(Method::GET, "/portfolio/orders");
+        assert_eq!(orders[0].query_param("status"), Some("resting"));
+
+        server.respond(
+            Method::GET,
+            "/portfolio/balance",
+            500,
+            serde_json::json!({}),
+        );
+        assert!(kalshi.get_portfolio_snapshot().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_user_positions_follow_cursors() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let mut page = fixtures::positions();
+        page["cursor"] = "next".into();
+        server.respond(Method::GET, "/portfolio/positions", 200, page);
+
+        let pages: Vec<_> = kalshi
+            .get_user_positions(Some(3), Some(2), None, None, None, None)
+            .await
+            .collect()
+            .await;
+        assert_eq!(pages.len(), 3);
+        assert!(pages.iter().all(|page| page.as_ref().unwrap().1.len() == 1));
+
+        let requests = server.requests_to(Method::GET, "/portfolio/positions");
+        let params: Vec<_> = requests
+            .iter()
+            .map(|r| (r.query_param("limit"), r.query_param("cursor")))
+            .collect();
+        assert_eq!(
+            params,
+            vec![
+                (Some("2"), None),
+                (Some("2"), Some("next")),
+                (Some("1"), Some("next"))
+            ]
+        );
+
+        // The last page ends the stream
+        server.respond(
+            Method::GET,
+            "/portfolio/positions",
+            200,
+            fixtures::positions(),
+        );
+        let (_, positions) = kalshi.get_all_positions().await.unwrap();
+        assert_eq!(positions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_settlements_filters() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+
+        let (_, settlements) = kalshi
+            .get_portfolio_settlements(
+                None,
+                None,
+                Some(fixtures::MARKET_TICKER.to_string()),
+                None,
+                Some(1_700_000_000),
+                Some(1_800_000_000),
+            )
+            .await
+            .unwrap();
+        assert!(!settlements.is_empty());
+
+        let request = &server.requests_to(Method::GET, "/portfolio/settlements")[0];
+        assert_eq!(request.query_param("ticker"), Some(fixtures::MARKET_TICKER));
+        assert_eq!(request.query_param("event_ticker"), None);
+        assert_eq!(request.query_param("min_ts"), Some("1700000000"));
+        assert_eq!(request.query_param("max_ts"), Some("1800000000"));
+    }
+
+    #[tokio::test]
+    async fn test_watch_balance_yields_changes() {
+        use futures::StreamExt;
+
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let mut changes = Box::pin(kalshi.watch_balance(Duration::from_millis(10)));
+
+        let (change, _) = tokio::join!(changes.next(), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            server.respond(
+                Method::GET,
+                "/portfolio/balance",
+                200,
+                fixtures::balance(9_250),
+            );
+        });
+        let change = change.unwrap().unwrap();
+        assert_eq!(
+            change,
+            BalanceChange {
+                balance: 9_250,
+                delta: -750
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replace_order_falls_back_to_cancel_replace() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let amend = format!("/portfolio/orders/{}/amend", fixtures::ORDER_ID);
+
+        let replaced = kalshi
+            .replace_order(fixtures::ORDER_ID, 60, 10)
+            .await
+            .unwrap();
+        assert_eq!(replaced.method, ReplaceMethod::Amended);
+        assert_eq!(replaced.order.yes_price, 60);
+        let body = server.requests_to(Method::POST, &amend)[0]
+            .body
+            .clone()
+            .unwrap();
+        assert_eq!(body["updated_client_order_id"], "mock-client-order-r1");
+        assert_eq!(body["yes_price"], 60);
+
+        server.respond(Method::POST, &amend, 404, serde_json::json!({}));
+        let replaced = kalshi
+            .replace_order(fixtures::ORDER_ID, 60, 10)
+            .await
+            .unwrap();
+        assert_eq!(replaced.method, ReplaceMethod::CancelReplaced);
+        assert!(replaced.canceled.is_some());
+        let created = server.requests_to(Method::POST, "/portfolio/orders");
+        let body = created[0].body.as_ref().unwrap();
+        assert_eq!(body["client_order_id"], "mock-client-order-r1");
+        assert_eq!(
+            (body["count"].clone(), body["side"].clone()),
+            (10.into(), "yes".into())
+        );
+        assert_eq!(
+            server
+                .requests_to(
+                    Method::DELETE,
+                    &format!("/portfolio/orders/{}", fixtures::ORDER_ID)
+                )
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replace_order_returns_refused_amends() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let amend = format!("/portfolio/orders/{}/amend", fixtures::ORDER_ID);
+
+        server.respond(
+            Method::POST,
+            &amend,
+            400,
+            serde_json::json!({"error": {"code": "invalid_price", "message": "invalid price"}}),
+        );
+        let error = kalshi
+            .replace_order(fixtures::ORDER_ID, 60, 10)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.trading_error_kind(),
+            Some(TradingErrorKind::InvalidOrder)
+        );
+
+        server.respond(
+            Method::POST,
+            &amend,
+            404,
+            serde_json::json!({"error": {"code": "not_found", "message": "order not found"}}),
+        );
+        let error = kalshi
+            .replace_order(fixtures::ORDER_ID, 60, 10)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.trading_error_kind(),
+            Some(TradingErrorKind::OrderNotFound)
+        );
+
+        assert!(server
+            .requests_to(
+                Method::DELETE,
+                &format!("/portfolio/orders/{}", fixtures::ORDER_ID)
+            )
+            .is_empty());
+        assert!(server
+            .requests_to(Method::POST, "/portfolio/orders")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_checked_by_risk_manager_as_a_whole() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let mut kalshi = server.kalshi().await.unwrap();
+        kalshi.set_risk_manager(crate::RiskManager::new(crate::RiskLimits {
+            max_position_per_market: Some(100),
+            shrink_orders: true,
+            ..Default::default()
+        }));
+        let order = |count| OrderCreationField {
+            action: Action::Buy,
+            client_order_id: None,
+            count,
+            side: Side::Yes,
+            ticker: fixtures::MARKET_TICKER.to_string(),
+            input_type: OrderType::Limit,
+            buy_max_cost: None,
+            expiration_ts: None,
+            no_price: None,
+            sell_position_floor: None,
+            yes_price: Some(40),
+        };
+
+        let results = kalshi
+            .batch_create_order(vec![order(60), order(60), order(60)])
+            .await
+            .unwrap();
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(matches!(results[2], Err(KalshiError::UserInputError(_))));
+        let requests = server.requests_to(Method::POST, "/portfolio/orders/batched");
+        assert_eq!(requests.len(), 1);
+        let counts: Vec<i64> = requests[0].body.as_ref().unwrap()["orders"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|order| order["count"].as_i64().unwrap())
+            .collect();
+        assert_eq!(counts, vec![60, 40]);
+        assert!(server
+            .requests_to(Method::POST, "/portfolio/orders")
+            .is_empty());
+    }
+
+    fn batch_order(count: i32) -> OrderCreationField {
+        OrderCreationField {
+            action: Action::Buy,
+            client_order_id: None,
+            count,
+            side: Side::Yes,
+            ticker: fixtures::MARKET_TICKER.to_string(),
+            input_type: OrderType::Limit,
+            buy_max_cost: None,
+            expiration_ts: None,
+            no_price: None,
+            sell_position_floor: None,
+            yes_price: Some(40),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_split_at_limit() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let mut batch: Vec<OrderCreationField> = (1..=25).map(batch_order).collect();
+        batch[3].yes_price = None;
+
+        let results = kalshi.batch_create_order(batch).await.unwrap();
+        assert_eq!(results.len(), 25);
+        assert!(matches!(results[3], Err(KalshiError::UserInputError(_))));
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 24);
+
+        let sizes: Vec<usize> = server
+            .requests_to(Method::POST, "/portfolio/orders/batched")
+            .iter()
+            .map(|request| {
+                request.body.as_ref().unwrap()["orders"]
+                    .as_array()
+                    .unwrap()
+                    .len()
+            })
+            .collect();
+        assert_eq!(sizes, vec![20, 4]);
+        assert!(server
+            .requests_to(Method::POST, "/portfolio/orders")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_errors_returned_per_order() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let mut body = fixtures::batched_orders(2);
+        body["orders"][1] = serde_json::json!({
+            "client_order_id": "refused",
+            "order": null,
+            "error": {"code": "insufficient_balance", "message": "Insufficient balance"},
+        });
+        server.respond(Method::POST, "/portfolio/orders/batched", 201, body);
+        let kalshi = server.kalshi().await.unwrap();
+
+        let results = kalshi
+            .batch_create_order(vec![batch_order(1), batch_order(2)])
+            .await
+            .unwrap();
+        assert_eq!(results[0].as_ref().unwrap().order_id, "batched-0");
+        assert_eq!(
+            results[1].as_ref().unwrap_err().trading_error_kind(),
+            Some(TradingErrorKind::InsufficientBalance)
+        );
+
+        server.respond(
+            Method::POST,
+            "/portfolio/orders/batched",
+            400,
+            serde_json::json!({"error": {"code": "market_closed", "message": "Market closed"}}),
+        );
+        let results = kalshi
+            .batch_create_order(vec![batch_order(1), batch_order(2)])
+            .await
+            .unwrap();
+        for result in results {
+            assert_eq!(
+                result.unwrap_err().trading_error_kind(),
+                Some(TradingErrorKind::MarketClosed)
+            );
+        }
+    }
 }
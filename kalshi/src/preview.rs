@@ -0,0 +1,243 @@
+use crate::{Action, FeeSchedule, Kalshi, KalshiError, Orderbook, Side};
+
+/// What an order would cost, computed by [`Kalshi::preview_order`] before anything is submitted.
+///
+/// Prices are in cents on the order's side, the contracts crossing the book are priced at the
+/// levels they would take, the rest as resting at the limit price until filled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderPreview {
+    /// Contracts that would trade immediately against the book.
+    pub taker_count: i32,
+    /// Contracts that would rest on the book.
+    pub maker_count: i32,
+    /// Average price of the immediate fills, `None` if nothing crosses.
+    pub average_fill_price: Option<f64>,
+    /// Fees of the immediate fills, in cents.
+    pub taker_fees: i64,
+    /// Fees of the resting contracts once filled at the limit price, in cents.
+    pub maker_fees: i64,
+    /// The most the order can cost with its fees, in cents. Contracts a sell closes cost nothing,
+    /// the ones it opens on the other side cost `100 - price` each.
+    pub max_cost: i64,
+    /// The position in the market before the order, positive for yes and negative for no.
+    pub position_before: i32,
+    /// The position in the market once the order completely filled.
+    pub position_after: i32,
+}
+
+impl OrderPreview {
+    /// Previews an order of `count` contracts at `price` cents against `orderbook`, with the
+    /// current `position` in the market.
+    pub fn compute(
+        action: Action,
+        side: Side,
+        count: i32,
+        price: i64,
+        orderbook: &Orderbook,
+        fees: FeeSchedule,
+        position: i32,
+    ) -> Self {
+        let mut remaining = count.max(0) as i64;
+        let mut taker_count = 0;
+        let mut taker_notional = 0;
+        let mut taker_fees = 0;
+        for (level_price, quantity) in crossing_levels(orderbook, action, side, price) {
+            if remaining == 0 {
+                break;
+            }
+            let filled = quantity.min(remaining);
+            remaining -= filled;
+            taker_count += filled;
+            taker_notional += filled * level_price;
+            taker_fees += fees.taker.fee(filled, level_price);
+        }
+        let maker_fees = fees.maker.fee(remaining, price);
+
+        let delta = match (action, side) {
+            (Action::Buy, Side::Yes) | (Action::Sell, Side::No) => count,
+            (Action::Buy, Side::No) | (Action::Sell, Side::Yes) => -count,
+        };
+        let max_cost = match action {
+            Action::Buy => taker_notional + remaining * price,
+            Action::Sell => {
+                let held = match side {
+                    Side::Yes => position.max(0),
+                    Side::No => (-position).max(0),
+                };
+                (count - held).max(0) as i64 * (100 - price)
+            }
+        } + taker_fees
+            + maker_fees;
+
+        OrderPreview {
+            taker_count: taker_count as i32,
+            maker_count: remaining as i32,
+            average_fill_price: (taker_count > 0)
+                .then(|| taker_notional as f64 / taker_count as f64),
+            taker_fees,
+            maker_fees,
+            max_cost,
+            position_before: position,
+            position_after: position + delta,
+        }
+    }
+
+    /// Every fee of the order once completely filled, in cents.
+    pub fn total_fees(&self) -> i64 {
+        self.taker_fees + self.maker_fees
+    }
+}
+
+/// The levels an order would trade against, best first, as price on the order's side and quantity.
+///
+/// Buys cross the bids of the other side, a no bid at `q` being a yes ask at `100 - q`, sells
+/// cross the bids of their own side.
+fn crossing_levels(
+    orderbook: &Orderbook,
+    action: Action,
+    side: Side,
+    price: i64,
+) -> Vec<(i64, i64)> {
+    let bids = |levels: &Option<Vec<Vec<i32>>>| -> Vec<(i64, i64)> {
+        levels
+            .iter()
+            .flatten()
+            .filter_map(|level| match level.as_slice() {
+                [price, quantity, ..] => Some((*price as i64, *quantity as i64)),
+                _ => None,
+            })
+            .collect()
+    };
+    let mut levels: Vec<(i64, i64)> = match (action, side) {
+        (Action::Buy, Side::Yes) => bids(&orderbook.no),
+        (Action::Buy, Side::No) => bids(&orderbook.yes),
+        (Action::Sell, Side::Yes) => bids(&orderbook.yes),
+        (Action::Sell, Side::No) => bids(&orderbook.no),
+    };
+    match action {
+        Action::Buy => {
+            levels = levels
+                .into_iter()
+                .map(|(bid, quantity)| (100 - bid, quantity))
+                .filter(|(ask, _)| *ask <= price)
+                .collect();
+            levels.sort_by_key(|(ask, _)| *ask);
+        }
+        Action::Sell => {
+            levels.retain(|(bid, _)| *bid >= price);
+            levels.sort_by_key(|(bid, _)| -bid);
+        }
+    }
+    levels
+}
+
+impl Kalshi {
+    /// Previews the cost, fees and resulting position of a limit order without submitting it.
+    ///
+    /// Fetches the market, its event and series for the fee schedule (cached when a metadata
+    /// cache is configured), the current order book and, when logged in, the position in the
+    /// market. See [`OrderPreview::compute`] to preview against data already at hand.
+    ///
+    /// # Arguments
+    /// * `ticker` - The market of the order.
+    /// * `action` - Whether the order buys or sells.
+    /// * `side` - The side of the contracts.
+    /// * `count` - The number of contracts.
+    /// * `price` - The limit price on `side`, in cents.
+    ///
+    /// # Example
+    /// ```
+    /// let preview = kalshi_instance.preview_order("KXHIGHNY-25OCT02-B80.5", Action::Buy, Side::Yes, 100, 45).await?;
+    /// if preview.max_cost <= budget {
+    ///     kalshi_instance.create_order(Action::Buy, None, 100, Side::Yes, /* ... */).await?;
+    /// }
+    /// ```
+    pub async fn preview_order(
+        &self,
+        ticker: &str,
+        action: Action,
+        side: Side,
+        count: i32,
+        price: i64,
+    ) -> Result<OrderPreview, KalshiError> {
+        if count <= 0 || !(1..=99).contains(&price) {
+            return Err(KalshiError::UserInputError(format!(
+                "Can't preview {} contracts at {} cents, the count must be positive and the price between 1 and 99",
+                count, price
+            )));
+        }
+        let ticker = ticker.to_string();
+        let market = self.get_single_market(&ticker).await?;
+        let event = self.get_single_event(&market.event_ticker, None).await?;
+        let series = self.get_series(&event.series_ticker).await?;
+        let orderbook = self.get_market_orderbook(&ticker, None).await?;
+
+        let position = if self.get_user_token().is_some() {
+            let (_, positions, _) = self
+                .get_positions_page(None, None, None, Some(ticker.clone()), None)
+                .await?;
+            positions
+                .iter()
+                .find(|position| position.ticker == ticker)
+                .map_or(0, |position| position.position)
+        } else {
+            0
+        };
+
+        Ok(OrderPreview::compute(
+            action,
+            side,
+            count,
+            price,
+            &orderbook,
+            FeeSchedule::from_series(&series),
+            position,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FeeModel;
+
+    fn orderbook() -> Orderbook {
+        Orderbook {
+            yes: Some(vec![vec![60, 120], vec![62, 45], vec![64, 10]]),
+            no: Some(vec![vec![32, 80], vec![34, 25]]),
+        }
+    }
+
+    const FEES: FeeSchedule = FeeSchedule {
+        taker: FeeModel::KALSHI_TAKER,
+        maker: FeeModel::NoFees,
+    };
+
+    #[test]
+    fn test_buy_crosses_the_other_side() {
+        // The no bid at 34 is a yes ask at 66
+        let preview = OrderPreview::compute(Action::Buy, Side::Yes, 100, 67, &orderbook(), FEES, 0);
+        assert_eq!(preview.taker_count, 25);
+        assert_eq!(preview.maker_count, 75);
+        assert_eq!(preview.average_fill_price, Some(66.0));
+        assert_eq!(preview.taker_fees, 40);
+        assert_eq!(preview.maker_fees, 0);
+        assert_eq!(preview.max_cost, 25 * 66 + 75 * 67 + 40);
+        assert_eq!(preview.position_after, 100);
+    }
+
+    #[test]
+    fn test_sell_closes_before_opening() {
+        let preview =
+            OrderPreview::compute(Action::Sell, Side::Yes, 30, 61, &orderbook(), FEES, 10);
+        assert_eq!(preview.taker_count, 30);
+        assert_eq!(
+            preview.average_fill_price,
+            Some((10.0 * 64.0 + 20.0 * 62.0) / 30.0)
+        );
+        assert_eq!(preview.taker_fees, 17 + 33);
+        // 10 contracts close the position, 20 open a no position at 39
+        assert_eq!(preview.max_cost, 20 * 39 + 50);
+        assert_eq!(preview.position_after, -20);
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{FeeSchedule, Kalshi, KalshiError, Series, SettlementSource};
+
+#[derive(Debug, Default)]
+struct RegistryState {
+    series: HashMap<String, Series>,
+    /// Series of tickers that don't start with their series ticker, learned while fetching them
+    aliases: HashMap<String, String>,
+    loaded_at: Option<Instant>,
+}
+
+impl RegistryState {
+    fn resolve(&self, ticker: &str) -> Option<&Series> {
+        if let Some(series) = self.aliases.get(ticker) {
+            return self.series.get(series);
+        }
+        // Event tickers extend their series ticker with `-` suffixes, and market tickers their
+        // event ticker, so the longest prefix ending at a `-` is the series
+        std::iter::once(ticker.len())
+            .chain(ticker.rmatch_indices('-').map(|(index, _)| index))
+            .find_map(|cut| self.series.get(&ticker[..cut]))
+    }
+}
+
+/// Every series of the exchange, loaded once, resolving market and event tickers to their series.
+///
+/// Fee-aware logic needs the series of every market it touches, for its fee schedule. Rather than
+/// fetching the market, its event and series each time, the registry loads all series in one
+/// request and resolves tickers by prefix, `KXHIGHNY-25OCT02-B80.5` and `KXHIGHNY-25OCT02` both
+/// resolving to the `KXHIGHNY` series. Tickers it can't resolve are fetched by
+/// [`SeriesRegistry::series_for`] and remembered.
+///
+/// The registry is a cheap handle, clones share the same series.
+///
+/// ```
+/// let registry = SeriesRegistry::new();
+/// registry.load(&kalshi_instance).await?;
+///
+/// let fees = registry.fee_schedule("KXHIGHNY-25OCT02-B80.5").unwrap();
+/// println!("{}", fees.taker.fee(10, 45));
+///
+/// // Picks up series listed since the last load, at most every hour
+/// registry.refresh_if_older(&kalshi_instance, Duration::from_secs(3600)).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SeriesRegistry {
+    state: Arc<Mutex<RegistryState>>,
+}
+
+impl SeriesRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RegistryState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Replaces the registry's series with every series of the exchange, returns how many there
+    /// are.
+    pub async fn load(&self, kalshi: &Kalshi) -> Result<usize, KalshiError> {
+        let series = kalshi.get_all_series(None).await?;
+        let mut state = self.lock();
+        state.series = series
+            .into_iter()
+            .map(|series| (series.ticker.clone(), series))
+            .collect();
+        state.aliases.clear();
+        state.loaded_at = Some(Instant::now());
+        Ok(state.series.len())
+    }
+
+    /// Loads the series again if they were never loaded or were loaded more than `max_age` ago,
+    /// returns whether they were.
+    pub async fn refresh_if_older(
+        &self,
+        kalshi: &Kalshi,
+        max_age: Duration,
+    ) -> Result<bool, KalshiError> {
+        let stale = self
+            .loaded_at()
+            .map_or(true, |loaded| loaded.elapsed() >= max_age);
+        if stale {
+            self.load(kalshi).await?;
+        }
+        Ok(stale)
+    }
+
+    /// When the series were last loaded, `None` if they never were.
+    pub fn loaded_at(&self) -> Option<Instant> {
+        self.lock().loaded_at
+    }
+
+    /// Adds or replaces a series.
+    pub fn insert(&self, series: Series) {
+        self.lock().series.insert(series.ticker.clone(), series);
+    }
+
+    /// The series of a series, event or market ticker, `None` if no loaded series matches it.
+    pub fn resolve(&self, ticker: &str) -> Option<Series> {
+        self.lock().resolve(ticker).cloned()
+    }
+
+    /// The fee schedule of the series of `ticker`.
+    pub fn fee_schedule(&self, ticker: &str) -> Option<FeeSchedule> {
+        self.lock().resolve(ticker).map(FeeSchedule::from_series)
+    }
+
+    /// The settlement sources of the series of `ticker`.
+    pub fn settlement_sources(&self, ticker: &str) -> Option<Vec<SettlementSource>> {
+        self.lock()
+            .resolve(ticker)
+            .map(|series| series.settlement_sources.clone())
+    }
+
+    /// The series of a market or event ticker, fetching it when the registry can't resolve it.
+    ///
+    /// The ticker is fetched as a market, then as an event, for its series ticker, and the series
+    /// is added to the registry so later lookups of the ticker resolve.
+    pub async fn series_for(&self, kalshi: &Kalshi, ticker: &str) -> Result<Series, KalshiError> {
+        if let Some(series) = self.resolve(ticker) {
+            return Ok(series);
+        }
+        let event_ticker = match kalshi.get_single_market(&ticker.to_string()).await {
+            Ok(market) => market.event_ticker,
+            Err(_) => ticker.to_string(),
+        };
+        let event = kalshi.get_single_event(&event_ticker, None).await?;
+        let series = kalshi.get_series(&event.series_ticker).await?;
+
+        let mut state = self.lock();
+        state
+            .aliases
+            .insert(ticker.to_string(), series.ticker.clone());
+        state.series.insert(series.ticker.clone(), series.clone());
+        Ok(series)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().series.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().series.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::MockHttpServer;
+    use crate::FeeModel;
+    use reqwest::Method;
+    use serde_json::json;
+
+    fn series(ticker: &str, fee_type: &str) -> serde_json::Value {
+        json!({
+            "ticker": ticker,
+            "frequency": "daily",
+            "title": ticker,
+            "category": "Climate and Weather",
+            "settlement_sources": [{"url": "https://www.weather.gov", "name": "NWS"}],
+            "contract_url": "",
+            "contract_terms_url": "",
+            "fee_type": fee_type,
+            "fee_multiplier": 1.0
+        })
+    }
+
+    #[tokio::test]
+    async fn test_registry_resolves_tickers_by_prefix() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        server.respond(
+            Method::GET,
+            "/series",
+            200,
+            json!({"series": [
+                series("KXHIGH", "quadratic"),
+                series("KXHIGHNY", "quadratic_with_maker_fees"),
+            ]}),
+        );
+        let kalshi = server.kalshi().await.unwrap();
+        let registry = SeriesRegistry::new();
+
+        assert_eq!(registry.load(&kalshi).await.unwrap(), 2);
+        assert!(!registry
+            .refresh_if_older(&kalshi, Duration::from_secs(60))
+            .await
+            .unwrap());
+
+        let resolved = registry.resolve("KXHIGHNY-25OCT02-B80.5").unwrap();
+        assert_eq!(resolved.ticker, "KXHIGHNY");
+        assert_eq!(
+            registry.resolve("KXHIGHNY-25OCT02").unwrap().ticker,
+            "KXHIGHNY"
+        );
+        assert_eq!(registry.resolve("KXHIGH").unwrap().ticker, "KXHIGH");
+        // A prefix that doesn't end at a `-` isn't the series
+        assert!(registry.resolve("KXHIGHCHI-25OCT02").is_none());
+
+        assert_eq!(
+            registry.fee_schedule("KXHIGH-25OCT02").unwrap().maker,
+            FeeModel::NoFees
+        );
+        assert_ne!(
+            registry.fee_schedule("KXHIGHNY-25OCT02").unwrap().maker,
+            FeeModel::NoFees
+        );
+        assert_eq!(
+            registry.settlement_sources("KXHIGHNY-25OCT02").unwrap()[0].name,
+            "NWS"
+        );
+        assert_eq!(server.requests_to(Method::GET, "/series").len(), 1);
+    }
+}
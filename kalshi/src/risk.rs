@@ -0,0 +1,510 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use chrono::{NaiveDate, Utc};
+
+use crate::{Action, FeeModel, Market, OrderCreationField, OrderTracker, PositionTracker, Side};
+
+/// The limits enforced by a [`RiskManager`], every limit is disabled when `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RiskLimits {
+    /// Most contracts held in a single market, on either side.
+    pub max_position_per_market: Option<i64>,
+    /// Most contracts held across all markets of an event, on either side.
+    pub max_position_per_event: Option<i64>,
+    /// Most cents resting in open buy orders across all markets.
+    pub max_open_notional: Option<i64>,
+    /// Most cents lost since midnight UTC before every order is rejected.
+    pub max_daily_loss: Option<i64>,
+    /// Markets that can't be traded. An event or series ticker bans all of its markets.
+    pub banned_tickers: HashSet<String>,
+    /// Shrink orders that would exceed a limit to the largest size that fits, instead of rejecting them.
+    pub shrink_orders: bool,
+}
+
+/// The outcome of [`RiskManager::check_order`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RiskDecision {
+    /// The order fits within every limit.
+    Accept,
+    /// The order only fits with the given number of contracts.
+    Shrink(i32),
+    /// The order can't be placed, with the reason why.
+    Reject(String),
+}
+
+/// Orders accepted earlier in a batch, counted as if they were resting.
+#[derive(Debug, Default)]
+struct Reserved {
+    /// Net contracts per market, positive for yes
+    positions: HashMap<String, i64>,
+    /// Cents committed by buys
+    notional: i64,
+}
+
+#[derive(Debug)]
+struct RiskState {
+    limits: RiskLimits,
+    day: NaiveDate,
+    /// Profit or loss reported with `record_pnl` today, in cents
+    daily_pnl: i64,
+    /// Realized pnl of the position tracker at the start of the day, in cents
+    realized_at_day_start: Option<i64>,
+}
+
+/// Checks orders against configurable limits before they reach the exchange.
+///
+/// Attach the manager to a [`Kalshi`](crate::Kalshi) instance with
+/// [`Kalshi::set_risk_manager`](crate::Kalshi::set_risk_manager) and every `create_order` call is
+/// checked first: orders breaking a limit are rejected with a [`KalshiError::UserInputError`](crate::KalshiError::UserInputError),
+/// or shrunk to fit when [`RiskLimits::shrink_orders`] is set.
+///
+/// Position limits need a [`PositionTracker`] to know what's held, and the open notional limit
+/// needs an [`OrderTracker`] to know what's resting. Without them those limits only apply to the
+/// order being checked. Attach the position tracker once it's seeded, so positions held before
+/// today don't count towards the daily loss.
+///
+/// The manager is a cheap handle, clones share the same limits and daily loss.
+///
+/// ```
+/// let mut risk = RiskManager::new(RiskLimits {
+///     max_position_per_market: Some(500),
+///     max_daily_loss: Some(10_000),
+///     shrink_orders: true,
+///     ..Default::default()
+/// });
+/// risk.set_position_tracker(positions.clone());
+/// risk.set_order_tracker(orders.clone());
+/// kalshi_instance.set_risk_manager(risk);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RiskManager {
+    state: Arc<Mutex<RiskState>>,
+    positions: Option<PositionTracker>,
+    orders: Option<OrderTracker>,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits) -> Self {
+        RiskManager {
+            state: Arc::new(Mutex::new(RiskState {
+                limits,
+                day: Utc::now().date_naive(),
+                daily_pnl: 0,
+                realized_at_day_start: None,
+            })),
+            positions: None,
+            orders: None,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RiskState> {
+        let mut state = self.state.lock().unwrap_or_else(|p| p.into_inner());
+        let today = Utc::now().date_naive();
+        if state.day != today {
+            state.day = today;
+            state.daily_pnl = 0;
+            state.realized_at_day_start = self.realized_pnl();
+        }
+        state
+    }
+
+    fn realized_pnl(&self) -> Option<i64> {
+        self.positions.as_ref().map(PositionTracker::realized_pnl)
+    }
+
+    /// Uses `positions` for the position limits and the realized part of the daily loss.
+    pub fn set_position_tracker(&mut self, positions: PositionTracker) {
+        self.lock().realized_at_day_start = None;
+        self.positions = Some(positions);
+        self.lock().realized_at_day_start = self.realized_pnl();
+    }
+
+    /// Uses `orders` for the open notional and position limits.
+    pub fn set_order_tracker(&mut self, orders: OrderTracker) {
+        self.orders = Some(orders);
+    }
+
+    pub fn limits(&self) -> RiskLimits {
+        self.lock().limits.clone()
+    }
+
+    /// Replaces the limits, the daily loss so far is kept.
+    pub fn set_limits(&self, limits: RiskLimits) {
+        self.lock().limits = limits;
+    }
+
+    /// Adds profit (positive) or loss (negative) in cents to today's total.
+    ///
+    /// Realized pnl of an attached [`PositionTracker`] is counted automatically, use this for
+    /// anything it can't see such as settlements or fees.
+    pub fn record_pnl(&self, pnl: i64) {
+        self.lock().daily_pnl += pnl;
+    }
+
+    /// Profit or loss since midnight UTC, in cents.
+    pub fn daily_pnl(&self) -> i64 {
+        let state = self.lock();
+        let realized = match (self.realized_pnl(), state.realized_at_day_start) {
+            (Some(now), Some(start)) => now - start,
+            _ => 0,
+        };
+        state.daily_pnl + realized
+    }
+
+    /// Checks an order of `count` contracts on `side` of `ticker` against every limit.
+    ///
+    /// `price` is the limit price in cents on the order's side, `None` for market orders
+    /// which are assumed to fill at the worst price.
+    pub fn check_order(
+        &self,
+        ticker: &str,
+        action: Action,
+        side: Side,
+        count: i32,
+        price: Option<i64>,
+    ) -> RiskDecision {
+        self.decide(ticker, action, side, count, price, &Reserved::default())
+    }
+
+    /// Checks the orders of a batch together, each one counting the orders accepted before it
+    /// as resting so the batch as a whole stays within the limits.
+    ///
+    /// Used by [`Kalshi::batch_create_order`](crate::Kalshi::batch_create_order), whose orders
+    /// are sent together and would otherwise all be checked against the same exposure.
+    pub fn check_batch(&self, orders: &[OrderCreationField]) -> Vec<RiskDecision> {
+        let mut reserved = Reserved::default();
+        orders
+            .iter()
+            .map(|order| {
+                let price = side_price(order.side, order.yes_price, order.no_price);
+                let decision = self.decide(
+                    &order.ticker,
+                    order.action,
+                    order.side,
+                    order.count,
+                    price,
+                    &reserved,
+                );
+                let count = match decision {
+                    RiskDecision::Accept => order.count as i64,
+                    RiskDecision::Shrink(count) => count as i64,
+                    RiskDecision::Reject(_) => 0,
+                };
+                *reserved.positions.entry(order.ticker.clone()).or_default() +=
+                    direction(order.side, order.action) * count;
+                if order.action == Action::Buy {
+                    reserved.notional += count * price.unwrap_or(100).max(1);
+                }
+                decision
+            })
+            .collect()
+    }
+
+    fn decide(
+        &self,
+        ticker: &str,
+        action: Action,
+        side: Side,
+        count: i32,
+        price: Option<i64>,
+        reserved: &Reserved,
+    ) -> RiskDecision {
+        if count <= 0 {
+            return RiskDecision::Reject(format!("Order count must be positive, got {}", count));
+        }
+        let allowed = match self.allowed_count(ticker, action, side, count as i64, price, reserved)
+        {
+            Ok(allowed) => allowed,
+            Err(reason) => return RiskDecision::Reject(reason),
+        };
+
+        if allowed >= count as i64 {
+            RiskDecision::Accept
+        } else if allowed > 0 && self.limits().shrink_orders {
+            RiskDecision::Shrink(allowed as i32)
+        } else {
+            RiskDecision::Reject(format!(
+                "Order of {} contracts on {} exceeds limits, at most {} allowed",
+                count,
+                ticker,
+                allowed.max(0)
+            ))
+        }
+    }
+
+    /// The most contracts a buy of `side` of `market` at `price` cents can get within every
+    /// limit, the `balance` and the market's `risk_limit_cents`, net of `fees`.
+    ///
+    /// Cents committed to resting buy orders are set aside from the balance when an
+    /// [`OrderTracker`] is attached. Returns 0 when no order would be accepted.
+    ///
+    /// ```
+    /// let market = kalshi_instance.get_single_market(&ticker).await?;
+    /// let balance = kalshi_instance.get_balance().await?;
+    /// let count = risk.max_buy(&market, Side::Yes, 45, balance, FeeModel::KALSHI_TAKER);
+    /// ```
+    pub fn max_buy(
+        &self,
+        market: &Market,
+        side: Side,
+        price: i64,
+        balance: i64,
+        fees: FeeModel,
+    ) -> i64 {
+        let affordable = market.max_contracts(price, balance, self.resting_notional(), fees);
+        if affordable <= 0 {
+            return 0;
+        }
+        self.allowed_count(
+            &market.ticker,
+            Action::Buy,
+            side,
+            affordable,
+            Some(price),
+            &Reserved::default(),
+        )
+        .map_or(0, |allowed| allowed.clamp(0, affordable))
+    }
+
+    /// Cents resting in open buy orders across all markets.
+    fn resting_notional(&self) -> i64 {
+        self.orders
+            .iter()
+            .flat_map(|orders| orders.exposure_by_market().into_values())
+            .sum()
+    }
+
+    /// How many of `count` contracts the limits allow, or why none are.
+    fn allowed_count(
+        &self,
+        ticker: &str,
+        action: Action,
+        side: Side,
+        count: i64,
+        price: Option<i64>,
+        reserved: &Reserved,
+    ) -> Result<i64, String> {
+        let limits = self.limits();
+        if let Some(banned) = limits
+            .banned_tickers
+            .iter()
+            .find(|banned| ticker == *banned || ticker.starts_with(&format!("{}-", banned)))
+        {
+            return Err(format!("{} is banned by {}", ticker, banned));
+        }
+
+        if let Some(max_loss) = limits.max_daily_loss {
+            let pnl = self.daily_pnl();
+            if -pnl >= max_loss {
+                return Err(format!(
+                    "Daily loss of {} cents reached the limit of {}",
+                    -pnl, max_loss
+                ));
+            }
+        }
+
+        let direction = direction(side, action);
+        let mut allowed = count;
+
+        if let Some(max_position) = limits.max_position_per_market {
+            let base = self.projected_position(ticker, reserved);
+            allowed = allowed.min(max_position - direction * base);
+        }
+
+        if let Some(max_event) = limits.max_position_per_event {
+            let event = event_ticker(ticker);
+            let mut others: HashMap<String, i64> = self
+                .positions
+                .iter()
+                .flat_map(|positions| positions.net_positions())
+                .collect();
+            for (other, position) in &reserved.positions {
+                *others.entry(other.clone()).or_default() += position;
+            }
+            let others: i64 = others
+                .iter()
+                .filter(|(other, _)| *other != ticker && event_ticker(other) == event)
+                .map(|(_, position)| position.abs())
+                .sum();
+            let base = self.projected_position(ticker, reserved);
+            allowed = allowed.min(max_event - others - direction * base);
+        }
+
+        if let (Some(max_notional), Action::Buy) = (limits.max_open_notional, action) {
+            let price = price.unwrap_or(100).max(1);
+            let resting = self.resting_notional() + reserved.notional;
+            allowed = allowed.min((max_notional - resting) / price);
+        }
+        Ok(allowed)
+    }
+
+    /// Net position in `ticker` if every open and reserved order in it filled, positive for yes.
+    fn projected_position(&self, ticker: &str, reserved: &Reserved) -> i64 {
+        let held = self
+            .positions
+            .as_ref()
+            .map(|positions| positions.position(ticker).position)
+            .unwrap_or_default();
+        let resting: i64 = self
+            .orders
+            .iter()
+            .flat_map(|orders| orders.open_orders())
+            .filter(|order| order.ticker == ticker)
+            .map(|order| direction(order.side, order.action) * order.remaining_count as i64)
+            .sum();
+        held + resting + reserved.positions.get(ticker).copied().unwrap_or_default()
+    }
+}
+
+/// Buying no is selling yes, positions are signed with yes positive.
+fn direction(side: Side, action: Action) -> i64 {
+    match (side, action) {
+        (Side::Yes, Action::Buy) | (Side::No, Action::Sell) => 1,
+        (Side::Yes, Action::Sell) | (Side::No, Action::Buy) => -1,
+    }
+}
+
+/// The limit price of an order on its own side, from whichever price it was given.
+pub(crate) fn side_price(side: Side, yes_price: Option<i64>, no_price: Option<i64>) -> Option<i64> {
+    match side {
+        Side::Yes => yes_price.or(no_price.map(|p| 100 - p)),
+        Side::No => no_price.or(yes_price.map(|p| 100 - p)),
+    }
+}
+
+/// The event a market belongs to, market tickers extend their event ticker with a final `-` suffix.
+fn event_ticker(market_ticker: &str) -> &str {
+    market_ticker
+        .rsplit_once('-')
+        .map(|(event, _)| event)
+        .unwrap_or(market_ticker)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_orders_checked_against_limits() {
+        let positions = PositionTracker::new();
+        positions.apply_fill("KXHIGHNY-25OCT02-B80.5", Side::Yes, Action::Buy, 80, 40);
+        positions.apply_fill("KXHIGHNY-25OCT02-B82.5", Side::No, Action::Buy, 50, 30);
+
+        let mut risk = RiskManager::new(RiskLimits {
+            max_position_per_market: Some(100),
+            max_position_per_event: Some(150),
+            banned_tickers: HashSet::from(["KXBTC".to_string()]),
+            shrink_orders: true,
+            ..Default::default()
+        });
+        risk.set_position_tracker(positions.clone());
+
+        assert_eq!(
+            risk.check_order(
+                "KXHIGHNY-25OCT02-B80.5",
+                Action::Buy,
+                Side::Yes,
+                10,
+                Some(40)
+            ),
+            RiskDecision::Accept
+        );
+        // Market limit leaves 20, event limit leaves 150 - 50 - 80 = 20
+        assert_eq!(
+            risk.check_order(
+                "KXHIGHNY-25OCT02-B80.5",
+                Action::Buy,
+                Side::Yes,
+                50,
+                Some(40)
+            ),
+            RiskDecision::Shrink(20)
+        );
+        // Buying no reduces the yes position
+        assert_eq!(
+            risk.check_order(
+                "KXHIGHNY-25OCT02-B80.5",
+                Action::Buy,
+                Side::No,
+                150,
+                Some(60)
+            ),
+            RiskDecision::Accept
+        );
+        assert!(matches!(
+            risk.check_order("KXBTC-25OCT02-T100000", Action::Buy, Side::Yes, 1, Some(50)),
+            RiskDecision::Reject(_)
+        ));
+
+        risk.set_limits(RiskLimits {
+            max_daily_loss: Some(100),
+            ..Default::default()
+        });
+        // Sell 80 yes at 38, realizing a loss of 160 cents
+        positions.apply_fill("KXHIGHNY-25OCT02-B80.5", Side::Yes, Action::Sell, 80, 38);
+        assert_eq!(risk.daily_pnl(), -160);
+        assert!(matches!(
+            risk.check_order(
+                "KXHIGHNY-25OCT02-B82.5",
+                Action::Sell,
+                Side::No,
+                1,
+                Some(70)
+            ),
+            RiskDecision::Reject(_)
+        ));
+    }
+
+    #[test]
+    fn test_max_buy_within_limits_and_balance() {
+        let orders = OrderTracker::new();
+        let order = serde_json::json!({
+            "order_id": "resting",
+            "ticker": "KXHIGHNY-25OCT02-B82.5",
+            "status": "resting",
+            "yes_price": 50,
+            "no_price": 50,
+            "remaining_count": 100,
+            "action": "buy",
+            "side": "yes",
+            "type": "limit",
+            "client_order_id": "",
+            "order_group_id": "",
+        });
+        orders.on_order_created(&serde_json::from_value(order).unwrap());
+        let mut market: crate::Market =
+            serde_json::from_value(crate::testing::fixtures::market()).unwrap();
+        market.risk_limit_cents = 0;
+
+        let mut risk = RiskManager::new(RiskLimits::default());
+        // 5_000 cents of the balance rest in the other market
+        assert_eq!(
+            risk.max_buy(&market, Side::Yes, 40, 10_000, FeeModel::NoFees),
+            250
+        );
+        risk.set_order_tracker(orders);
+        assert_eq!(
+            risk.max_buy(&market, Side::Yes, 40, 10_000, FeeModel::NoFees),
+            125
+        );
+        risk.set_limits(RiskLimits {
+            max_position_per_market: Some(100),
+            ..Default::default()
+        });
+        assert_eq!(
+            risk.max_buy(&market, Side::Yes, 40, 10_000, FeeModel::NoFees),
+            100
+        );
+        risk.set_limits(RiskLimits {
+            banned_tickers: HashSet::from([market.ticker.clone()]),
+            ..Default::default()
+        });
+        assert_eq!(
+            risk.max_buy(&market, Side::Yes, 40, 10_000, FeeModel::NoFees),
+            0
+        );
+    }
+}
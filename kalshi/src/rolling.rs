@@ -0,0 +1,265 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::MarketTrade;
+
+/// Statistics of a market over the last window of a [`RollingStats`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketStats {
+    pub market_ticker: String,
+    /// When the statistics were computed, the time of the latest message in seconds since the unix epoch.
+    pub ts: i64,
+    /// Volume weighted average traded yes price in cents, `None` without trades in the window.
+    pub vwap: Option<f64>,
+    /// Standard deviation of the changes between consecutive yes prices in cents, `None` with
+    /// fewer than 3 prices in the window.
+    pub volatility: Option<f64>,
+    /// Contracts traded in the window.
+    pub volume: u64,
+    /// Contracts traded per minute over the window.
+    pub volume_rate: f64,
+    /// Trades in the window.
+    pub trades: usize,
+    /// Latest yes price in the window in cents, a trade price or quote mid.
+    pub last_price: Option<f64>,
+}
+
+/// Rolling VWAP, volatility and volume of markets over a time window.
+///
+/// VWAP and volume come from trades. Volatility comes from every price seen: trade prices and,
+/// from the websocket `ticker` channel, the mid of the yes bid and ask, so quote moves count even
+/// when nothing trades. Time is driven by the messages, observations older than the window as of
+/// the latest message of any market are dropped.
+///
+/// The stats are a shared handle, clones feed and query the same state, so a task can keep them
+/// current with [`KalshiWebsocketClient::rolling_stats`](crate::KalshiWebsocketClient::rolling_stats)
+/// while another queries them.
+///
+/// ```
+/// let stats = RollingStats::new(Duration::from_secs(300));
+/// let mut updates = Box::pin(ws_client.rolling_stats(stats.clone()));
+/// tokio::spawn(async move { while updates.next().await.is_some() {} });
+///
+/// if let Some(stats) = stats.stats("KXHIGHNY-25OCT02-B80.5") {
+///     println!("vwap {:?} vol {:?} {:.1}/min", stats.vwap, stats.volatility, stats.volume_rate);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RollingStats {
+    state: Arc<Mutex<RollingState>>,
+}
+
+#[derive(Debug)]
+struct RollingState {
+    window: i64,
+    now: i64,
+    markets: HashMap<String, MarketWindow>,
+}
+
+#[derive(Debug, Default)]
+struct MarketWindow {
+    /// Time, yes price and count of the trades in the window.
+    trades: VecDeque<(i64, u32, u32)>,
+    /// Time and yes price of every price seen in the window.
+    prices: VecDeque<(i64, f64)>,
+}
+
+impl MarketWindow {
+    fn evict(&mut self, since: i64) {
+        while self.trades.front().is_some_and(|(ts, _, _)| *ts <= since) {
+            self.trades.pop_front();
+        }
+        while self.prices.front().is_some_and(|(ts, _)| *ts <= since) {
+            self.prices.pop_front();
+        }
+    }
+}
+
+impl RollingStats {
+    /// Keeps statistics over the last `window`, rounded down to whole seconds and at least one second.
+    pub fn new(window: Duration) -> Self {
+        RollingStats {
+            state: Arc::new(Mutex::new(RollingState {
+                window: window.as_secs().max(1) as i64,
+                now: 0,
+                markets: HashMap::new(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RollingState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Adds a trade, returning the market's updated statistics.
+    pub fn on_trade(&self, trade: &MarketTrade) -> MarketStats {
+        let mut state = self.lock();
+        state.now = state.now.max(trade.ts);
+        let market = state
+            .markets
+            .entry(trade.market_ticker.clone())
+            .or_default();
+        market
+            .trades
+            .push_back((trade.ts, trade.yes_price, trade.count));
+        market.prices.push_back((trade.ts, trade.yes_price as f64));
+        state.compute(&trade.market_ticker)
+    }
+
+    /// Adds a quoted yes bid and ask at `ts`, returning the market's updated statistics.
+    ///
+    /// Only the mid is used, quotes missing a side (a price of 0) only move the clock.
+    pub fn on_quote(
+        &self,
+        market_ticker: &str,
+        yes_bid: u32,
+        yes_ask: u32,
+        ts: i64,
+    ) -> MarketStats {
+        let mut state = self.lock();
+        state.now = state.now.max(ts);
+        let market = state.markets.entry(market_ticker.to_string()).or_default();
+        if yes_bid > 0 && yes_ask > 0 {
+            market
+                .prices
+                .push_back((ts, (yes_bid + yes_ask) as f64 / 2.0));
+        }
+        state.compute(market_ticker)
+    }
+
+    /// The statistics of a market as of the latest message, `None` for markets never seen.
+    pub fn stats(&self, market_ticker: &str) -> Option<MarketStats> {
+        let mut state = self.lock();
+        state
+            .markets
+            .contains_key(market_ticker)
+            .then(|| state.compute(market_ticker))
+    }
+
+    /// The statistics of every market seen.
+    pub fn all(&self) -> Vec<MarketStats> {
+        let mut state = self.lock();
+        let mut tickers: Vec<String> = state.markets.keys().cloned().collect();
+        tickers.sort();
+        tickers.iter().map(|ticker| state.compute(ticker)).collect()
+    }
+}
+
+impl RollingState {
+    fn compute(&mut self, market_ticker: &str) -> MarketStats {
+        let since = self.now - self.window;
+        let minutes = self.window as f64 / 60.0;
+        let now = self.now;
+        let market = self.markets.entry(market_ticker.to_string()).or_default();
+        market.evict(since);
+
+        let volume: u64 = market
+            .trades
+            .iter()
+            .map(|(_, _, count)| *count as u64)
+            .sum();
+        let notional: f64 = market
+            .trades
+            .iter()
+            .map(|(_, price, count)| *price as f64 * *count as f64)
+            .sum();
+        let changes: Vec<f64> = market
+            .prices
+            .iter()
+            .zip(market.prices.iter().skip(1))
+            .map(|((_, a), (_, b))| b - a)
+            .collect();
+        let volatility = (changes.len() >= 2).then(|| {
+            let mean = changes.iter().sum::<f64>() / changes.len() as f64;
+            let variance = changes.iter().map(|c| (c - mean).powi(2)).sum::<f64>()
+                / (changes.len() - 1) as f64;
+            variance.sqrt()
+        });
+
+        MarketStats {
+            market_ticker: market_ticker.to_string(),
+            ts: now,
+            vwap: (volume > 0).then(|| notional / volume as f64),
+            volatility,
+            volume,
+            volume_rate: volume as f64 / minutes,
+            trades: market.trades.len(),
+            last_price: market.prices.back().map(|(_, price)| *price),
+        }
+    }
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::responses::KalshiWebsocketResponse;
+
+    impl RollingStats {
+        /// Adds a `trade`, backfilled trade or `ticker` message, returning the market's updated statistics.
+        ///
+        /// Messages of other channels are ignored.
+        pub fn on_message(&self, msg: &KalshiWebsocketResponse) -> Option<MarketStats> {
+            match msg {
+                KalshiWebsocketResponse::Trade { msg, .. }
+                | KalshiWebsocketResponse::TradeBackfill { msg } => {
+                    Some(self.on_trade(&MarketTrade::from(msg.clone())))
+                }
+                KalshiWebsocketResponse::Ticker { msg, .. } => {
+                    Some(self.on_quote(&msg.market_ticker, msg.yes_bid, msg.yes_ask, msg.ts as i64))
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Side;
+
+    fn trade(yes_price: u32, count: u32, ts: i64) -> MarketTrade {
+        MarketTrade {
+            trade_id: ts.to_string(),
+            market_ticker: "A".to_string(),
+            taker_side: Side::Yes,
+            count,
+            yes_price,
+            no_price: 100 - yes_price,
+            ts,
+        }
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let stats = RollingStats::new(Duration::from_secs(60));
+        stats.on_trade(&trade(40, 10, 0));
+        stats.on_trade(&trade(44, 30, 30));
+        let update = stats.on_quote("A", 41, 45, 45);
+
+        assert_eq!(update.vwap, Some(43.0));
+        assert_eq!(update.volume, 40);
+        assert_eq!(update.volume_rate, 40.0);
+        assert_eq!(update.trades, 2);
+        assert_eq!(update.last_price, Some(43.0));
+        // Changes of +4 and -1
+        assert!((update.volatility.unwrap() - 12.5f64.sqrt()).abs() < 1e-9);
+
+        // The first trade leaves the window
+        stats.on_quote("B", 10, 12, 70);
+        let a = stats.stats("A").unwrap();
+        assert_eq!((a.volume, a.vwap, a.volatility), (30, Some(44.0), None));
+        assert_eq!(a.ts, 70);
+
+        stats.on_quote("B", 0, 12, 200);
+        let a = stats.stats("A").unwrap();
+        assert_eq!((a.volume, a.vwap, a.last_price), (0, None, None));
+        assert!(stats.stats("C").is_none());
+        assert_eq!(stats.all().len(), 2);
+    }
+}
@@ -0,0 +1,432 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use futures::{Stream, StreamExt};
+use regex::Regex;
+
+use crate::{Category, Kalshi, KalshiError, Market, MarketStatus};
+
+type MarketFilter = Arc<dyn Fn(&Market) -> bool + Send + Sync>;
+
+/// Streams the open markets that pass every filter.
+///
+/// Filters are added with the builder methods and combined with a logical and. Anything not covered
+/// by the built-in filters can be expressed with [`MarketScanner::filter`], and scanners can be
+/// combined with an or through [`MarketScanner::matches`].
+///
+/// Filters that the exchange supports (series, event, close time) are also sent with the request
+/// so fewer markets have to be downloaded.
+///
+/// ```
+/// let scanner = MarketScanner::new()
+///     .min_volume(1_000)
+///     .max_spread(3)
+///     .closes_within(Duration::from_secs(6 * 3600))
+///     .title_matches(Regex::new("(?i)temperature").unwrap());
+///
+/// let mut matches = Box::pin(scanner.scan(&kalshi_instance).await);
+/// while let Some(market) = matches.next().await {
+///     println!("{}", market?.ticker);
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct MarketScanner {
+    filters: Vec<MarketFilter>,
+    series_ticker: Option<String>,
+    event_ticker: Option<String>,
+    closes_within: Option<Duration>,
+}
+
+impl fmt::Debug for MarketScanner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MarketScanner")
+            .field("filters", &self.filters.len())
+            .field("series_ticker", &self.series_ticker)
+            .field("event_ticker", &self.event_ticker)
+            .field("closes_within", &self.closes_within)
+            .finish()
+    }
+}
+
+impl MarketScanner {
+    /// Creates a scanner matching every open market.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps markets for which `predicate` returns true.
+    pub fn filter(mut self, predicate: impl Fn(&Market) -> bool + Send + Sync + 'static) -> Self {
+        self.filters.push(Arc::new(predicate));
+        self
+    }
+
+    /// Keeps markets that traded at least `volume` contracts.
+    pub fn min_volume(self, volume: i64) -> Self {
+        self.filter(move |market| market.volume >= volume)
+    }
+
+    /// Keeps markets quoted on both sides with a yes spread of at most `cents`.
+    pub fn max_spread(self, cents: i64) -> Self {
+        self.filter(move |market| {
+            market.yes_bid > 0 && market.yes_ask > 0 && market.yes_ask - market.yes_bid <= cents
+        })
+    }
+
+    /// Keeps markets closing between now and `within` from now.
+    pub fn closes_within(mut self, within: Duration) -> Self {
+        self.closes_within = Some(within);
+        self.filter(move |market| {
+            let close = match chrono::DateTime::parse_from_rfc3339(&market.close_time) {
+                Ok(close) => close.timestamp(),
+                Err(_) => return false,
+            };
+            let now = chrono::Utc::now().timestamp();
+            close >= now && close <= now + within.as_secs() as i64
+        })
+    }
+
+    /// Keeps markets of a category.
+    pub fn category(self, category: Category) -> Self {
+        self.filter(move |market| market.category == category)
+    }
+
+    /// Keeps markets whose title or subtitle matches `pattern`.
+    pub fn title_matches(self, pattern: Regex) -> Self {
+        self.filter(move |market| {
+            pattern.is_match(&market.title) || pattern.is_match(&market.subtitle)
+        })
+    }
+
+    /// Keeps markets mentioning every word of `query`, ignoring case, in their ticker, event
+    /// ticker, titles or rules.
+    pub fn search(self, query: &str) -> Self {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        self.filter(move |market| {
+            let text = [
+                &market.ticker,
+                &market.event_ticker,
+                &market.title,
+                &market.subtitle,
+                &market.yes_sub_title,
+                &market.no_sub_title,
+                &market.rules_primary,
+                &market.rules_secondary,
+            ]
+            .map(|field| field.to_lowercase())
+            .join("\n");
+            terms.iter().all(|term| text.contains(term.as_str()))
+        })
+    }
+
+    /// Only scans the markets of a series.
+    pub fn series(mut self, series_ticker: &str) -> Self {
+        self.series_ticker = Some(series_ticker.to_string());
+        self
+    }
+
+    /// Only scans the markets of an event.
+    pub fn event(mut self, event_ticker: &str) -> Self {
+        self.event_ticker = Some(event_ticker.to_string());
+        self
+    }
+
+    /// Whether `market` passes every filter.
+    pub fn matches(&self, market: &Market) -> bool {
+        self.series_ticker.as_ref().map_or(true, |series| {
+            market.event_ticker.starts_with(&format!("{}-", series))
+        }) && self
+            .event_ticker
+            .as_ref()
+            .map_or(true, |event| &market.event_ticker == event)
+            && self.filters.iter().all(|filter| filter(market))
+    }
+
+    /// Streams every open market that passes the filters, page by page as they're downloaded.
+    ///
+    /// Errors fetching a page are yielded and end the scan.
+    pub async fn scan<'a>(
+        &'a self,
+        kalshi: &'a Kalshi,
+    ) -> impl Stream<Item = Result<Market, KalshiError>> + 'a {
+        let max_close_ts = self
+            .closes_within
+            .map(|within| chrono::Utc::now().timestamp() + within.as_secs() as i64);
+        let pages = kalshi
+            .get_multiple_markets(
+                None,
+                None,
+                None,
+                self.event_ticker.clone(),
+                self.series_ticker.clone(),
+                max_close_ts,
+                None,
+                &[MarketStatus::Open],
+                Vec::new(),
+            )
+            .await;
+        pages
+            .map(|page| match page {
+                Ok(markets) => markets.into_iter().map(Ok).collect::<Vec<_>>(),
+                Err(e) => vec![Err(e)],
+            })
+            .flat_map(futures::stream::iter)
+            .filter(move |market| {
+                let keep = match market {
+                    Ok(market) => self.matches(market),
+                    Err(_) => true,
+                };
+                async move { keep }
+            })
+    }
+}
+
+impl Kalshi {
+    /// Streams the open markets mentioning every word of `query`, see [`MarketScanner::search`].
+    ///
+    /// The exchange has no text search, markets are matched as they're downloaded. Narrow the
+    /// scan with a [`MarketScanner`] when the series or event is known.
+    ///
+    /// # Example
+    /// ```
+    /// let mut markets = Box::pin(kalshi_instance.search_markets("highest temperature nyc"));
+    /// while let Some(market) = markets.next().await {
+    ///     let market = market?;
+    ///     println!("{}: {}", market.ticker, market.title);
+    /// }
+    /// ```
+    pub fn search_markets(
+        &self,
+        query: &str,
+    ) -> impl Stream<Item = Result<Market, KalshiError>> + '_ {
+        let scanner = MarketScanner::new().search(query);
+        async_stream::stream! {
+            let mut matches = Box::pin(scanner.scan(self).await);
+            while let Some(market) = matches.next().await {
+                yield market;
+            }
+        }
+    }
+
+    /// Streams the open markets of every series tagged with one of `tags`, series by series.
+    ///
+    /// Tags are matched ignoring case, `["Fed"]` streams the markets of every series tagged
+    /// `Fed`. The series are fetched once with [`Kalshi::get_all_series`], then the markets of
+    /// each matching series. Errors are yielded and end the stream.
+    ///
+    /// # Example
+    /// ```
+    /// let mut markets = Box::pin(kalshi_instance.get_markets_by_tags(vec!["Fed".to_string()]));
+    /// while let Some(market) = markets.next().await {
+    ///     println!("{}", market?.ticker);
+    /// }
+    /// ```
+    pub fn get_markets_by_tags(
+        &self,
+        tags: Vec<String>,
+    ) -> impl Stream<Item = Result<Market, KalshiError>> + '_ {
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_lowercase()).collect();
+        async_stream::stream! {
+            let series = match self.get_all_series(None).await {
+                Ok(series) => series,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+            let tagged = series.into_iter().filter(|series| {
+                series.tags.iter().flatten().any(|tag| tags.contains(&tag.to_lowercase()))
+            });
+            for series in tagged {
+                let mut pages = Box::pin(
+                    self.get_multiple_markets(
+                        None,
+                        None,
+                        None,
+                        None,
+                        Some(series.ticker),
+                        None,
+                        None,
+                        &[MarketStatus::Open],
+                        Vec::new(),
+                    )
+                    .await,
+                );
+                while let Some(page) = pages.next().await {
+                    match page {
+                        Ok(markets) => {
+                            for market in markets {
+                                yield Ok(market);
+                            }
+                        }
+                        Err(e) => {
+                            yield Err(e);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Retrieves the open markets closing between now and `within` from now, soonest first.
+    ///
+    /// Only markets passing `filters` are returned, pass `&MarketScanner::new()` for every market.
+    /// The close time is sent to the exchange as `max_close_ts`, so only the markets closing soon
+    /// are downloaded.
+    ///
+    /// # Example
+    /// ```
+    /// let filters = MarketScanner::new().series("KXHIGHNY").min_volume(100);
+    /// let markets = kalshi_instance.get_markets_closing_within(Duration::from_secs(3600), &filters).await?;
+    /// for market in markets {
+    ///     println!("{} closes at {}", market.ticker, market.close_time);
+    /// }
+    /// ```
+    pub async fn get_markets_closing_within(
+        &self,
+        within: Duration,
+        filters: &MarketScanner,
+    ) -> Result<Vec<Market>, KalshiError> {
+        let scanner = filters.clone().closes_within(within);
+        let mut markets = Box::pin(scanner.scan(self).await)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        // Markets that passed `closes_within` have a valid close time
+        markets.sort_by_cached_key(|market| {
+            chrono::DateTime::parse_from_rfc3339(&market.close_time)
+                .map(|close| close.timestamp())
+                .unwrap_or(i64::MAX)
+        });
+        Ok(markets)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use reqwest::Method;
+
+    fn market(title: &str, volume: i64, yes_bid: i64, yes_ask: i64, close_in: i64) -> Market {
+        let close_time = chrono::Utc::now() + chrono::Duration::seconds(close_in);
+        let mut json = fixtures::market();
+        json["ticker"] = serde_json::json!("KXHIGHNY-25OCT02-B80.5");
+        json["event_ticker"] = serde_json::json!("KXHIGHNY-25OCT02");
+        json["title"] = serde_json::json!(title);
+        json["category"] = serde_json::json!("Climate");
+        json["close_time"] = serde_json::json!(close_time.to_rfc3339());
+        json["volume"] = serde_json::json!(volume);
+        json["yes_bid"] = serde_json::json!(yes_bid);
+        json["yes_ask"] = serde_json::json!(yes_ask);
+        json["no_bid"] = serde_json::json!(100 - yes_ask);
+        json["no_ask"] = serde_json::json!(100 - yes_bid);
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_filters_compose() {
+        let scanner = MarketScanner::new()
+            .series("KXHIGHNY")
+            .category(Category::Climate)
+            .min_volume(100)
+            .max_spread(3)
+            .closes_within(Duration::from_secs(3600))
+            .title_matches(Regex::new("(?i)highest temp").unwrap());
+
+        assert!(scanner.matches(&market("Highest temperature in NYC", 500, 40, 42, 600)));
+        assert!(!scanner.matches(&market("Highest temperature in NYC", 50, 40, 42, 600)));
+        assert!(!scanner.matches(&market("Highest temperature in NYC", 500, 40, 45, 600)));
+        assert!(!scanner.matches(&market("Highest temperature in NYC", 500, 40, 42, 7200)));
+        assert!(!scanner.matches(&market("Rain in NYC", 500, 40, 42, 600)));
+    }
+
+    #[test]
+    fn test_search_matches_every_term() {
+        let scanner = MarketScanner::new().search("  HIGHEST nyc ");
+        assert!(scanner.matches(&market("Highest temperature in NYC", 0, 0, 0, 600)));
+        assert!(!scanner.matches(&market("Highest temperature in Chicago", 0, 0, 0, 600)));
+        // Matched against the ticker too
+        assert!(MarketScanner::new()
+            .search("b80.5")
+            .matches(&market("Rain", 0, 0, 0, 600)));
+    }
+
+    #[tokio::test]
+    async fn test_markets_by_tags() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let series = |ticker: &str, tags: &[&str]| {
+            serde_json::json!({
+                "ticker": ticker,
+                "frequency": "custom",
+                "title": ticker,
+                "category": "Economics",
+                "tags": tags,
+                "settlement_sources": [],
+                "contract_url": "",
+                "contract_terms_url": "",
+                "fee_type": "quadratic",
+                "fee_multiplier": 1.0
+            })
+        };
+        server.respond(
+            Method::GET,
+            "/series",
+            200,
+            serde_json::json!({"series": [
+                series("KXFED", &["Fed", "Interest rates"]),
+                series("KXCPI", &["Inflation"]),
+                series("KXFEDDECISION", &["fed"]),
+            ]}),
+        );
+        let kalshi = server.kalshi().await.unwrap();
+
+        let markets: Vec<_> = kalshi
+            .get_markets_by_tags(vec!["FED".to_string()])
+            .collect()
+            .await;
+        assert!(markets.iter().all(|market| market.is_ok()));
+
+        let series: Vec<_> = server
+            .requests_to(Method::GET, "/markets")
+            .iter()
+            .map(|request| request.query_param("series_ticker").unwrap().to_string())
+            .collect();
+        assert_eq!(series, vec!["KXFED", "KXFEDDECISION"]);
+    }
+
+    #[tokio::test]
+    async fn test_markets_closing_within_sorted() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let markets: Vec<_> = [("LATER", 1800), ("TOO-LATE", 7200), ("SOONER", 300)]
+            .into_iter()
+            .map(|(ticker, close_in)| {
+                let mut market =
+                    serde_json::to_value(market("Highest temperature", 500, 40, 42, close_in))
+                        .unwrap();
+                market["ticker"] = ticker.into();
+                market
+            })
+            .collect();
+        server.respond(
+            Method::GET,
+            "/markets",
+            200,
+            serde_json::json!({"markets": markets, "cursor": ""}),
+        );
+        let kalshi = server.kalshi().await.unwrap();
+
+        let closing = kalshi
+            .get_markets_closing_within(Duration::from_secs(3600), &MarketScanner::new())
+            .await
+            .unwrap();
+        let tickers: Vec<_> = closing
+            .iter()
+            .map(|market| market.ticker.as_str())
+            .collect();
+        assert_eq!(tickers, vec!["SOONER", "LATER"]);
+
+        let requests = server.requests_to(Method::GET, "/markets");
+        assert!(requests[0].query_param("max_close_ts").is_some());
+    }
+}
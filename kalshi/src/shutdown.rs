@@ -0,0 +1,650 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+use tokio::{
+    sync::{watch, Notify},
+    task::{AbortHandle, JoinHandle},
+};
+
+use crate::Kalshi;
+
+/// How long [`Kalshi::shutdown`] waits for background tasks, then for the shutdown hooks.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Hook = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// Supervises the background tasks started through a [`Kalshi`] instance and coordinates their
+/// shutdown.
+///
+/// Every clone of an instance shares one `Shutdown`. The trackers, watchers, executors and
+/// websocket clients started from it register their tasks here. A task that panics is logged and
+/// listed as failed in [`Shutdown::health`] instead of silently disappearing, and tasks started
+/// with [`Shutdown::supervise`] are restarted after a backoff. [`Shutdown::shutdown`]:
+///
+/// 1. signals every task, websocket clients close their connection and the others stop at their
+///    next await point,
+/// 2. waits for the tasks to finish, aborting the ones still running after the timeout,
+/// 3. runs the hooks registered with [`Shutdown::on_shutdown`] in order, e.g. to cancel resting
+///    quotes once nothing can place new ones, or to flush files.
+///
+/// ```
+/// let shutdown = kalshi_instance.shutdown_handle();
+/// shutdown.spawn("quoter", quote_forever(kalshi_instance.clone()));
+/// shutdown.on_shutdown("journal", move || async move { journal.flush().await });
+/// kalshi_instance.cancel_orders_on_shutdown();
+///
+/// tokio::signal::ctrl_c().await?;
+/// let report = kalshi_instance.shutdown().await;
+/// println!("aborted {:?}", report.aborted_tasks);
+/// ```
+#[derive(Clone)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    signal: watch::Sender<bool>,
+    state: Mutex<State>,
+    /// Notified whenever a task finishes and once the shutdown completes
+    changed: Notify,
+}
+
+#[derive(Default)]
+struct State {
+    next_task_id: u64,
+    /// Running tasks by id
+    tasks: HashMap<u64, (TaskHealth, AbortHandle)>,
+    /// Tasks that panicked and were not restarted
+    failed: Vec<TaskHealth>,
+    hooks: Vec<(String, Hook)>,
+    report: Option<ShutdownReport>,
+}
+
+/// When and how often [`Shutdown::supervise`] restarts a task that panicked.
+///
+/// The backoff doubles on every restart, and goes back to `initial_backoff` once the task ran
+/// for `max_backoff` without panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// `None` to restart forever.
+    pub max_restarts: Option<u32>,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_restarts: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    Running,
+    /// Panicked, waiting for the backoff before restarting.
+    Restarting,
+    /// Panicked and won't be restarted.
+    Failed,
+}
+
+/// The state of a background task, see [`Shutdown::health`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskHealth {
+    pub name: String,
+    pub status: TaskStatus,
+    /// Times the task was restarted after a panic.
+    pub restarts: u32,
+    /// The message of the latest panic.
+    pub last_panic: Option<String>,
+    /// When the task entered its current status.
+    pub since: Instant,
+}
+
+impl TaskHealth {
+    fn new(name: &str) -> Self {
+        TaskHealth {
+            name: name.to_string(),
+            status: TaskStatus::Running,
+            restarts: 0,
+            last_panic: None,
+            since: Instant::now(),
+        }
+    }
+
+    fn set_status(&mut self, status: TaskStatus) {
+        self.status = status;
+        self.since = Instant::now();
+    }
+}
+
+/// What [`Shutdown::shutdown`] did, tasks and hooks listed by the names they were registered with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Tasks that finished within the timeout.
+    pub joined_tasks: Vec<String>,
+    /// Tasks still running after the timeout, aborted.
+    pub aborted_tasks: Vec<String>,
+    /// Hooks that completed.
+    pub completed_hooks: Vec<String>,
+    /// Hooks still running after the timeout, dropped.
+    pub timed_out_hooks: Vec<String>,
+}
+
+impl ShutdownReport {
+    /// Whether every task and hook completed in time.
+    pub fn is_clean(&self) -> bool {
+        self.aborted_tasks.is_empty() && self.timed_out_hooks.is_empty()
+    }
+}
+
+/// Resolves once a shutdown starts, for tasks winding down by themselves.
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    pub fn is_shutting_down(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Waits for the shutdown to start.
+    pub async fn wait(&mut self) {
+        while !*self.0.borrow_and_update() {
+            if self.0.changed().await.is_err() {
+                // Every handle is gone, no shutdown will ever come
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+/// Removes a task from the running ones once it finishes, however it finishes.
+struct TaskGuard {
+    inner: Arc<Inner>,
+    id: u64,
+}
+
+impl TaskGuard {
+    fn update(&self, update: impl FnOnce(&mut TaskHealth)) {
+        if let Some((health, _)) = lock(&self.inner).tasks.get_mut(&self.id) {
+            update(health);
+        }
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        let mut state = lock(&self.inner);
+        if let Some((health, _)) = state.tasks.remove(&self.id) {
+            if health.status == TaskStatus::Failed {
+                state.failed.push(health);
+            }
+        }
+        drop(state);
+        self.inner.changed.notify_waiters();
+    }
+}
+
+/// Aborts a task when dropped, so aborting the task awaiting it aborts both.
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn lock(inner: &Inner) -> MutexGuard<'_, State> {
+    inner.state.lock().unwrap_or_else(|p| p.into_inner())
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+async fn until_shutdown(mut signal: ShutdownSignal, task: impl Future<Output = ()>) {
+    tokio::select! {
+        biased;
+        _ = signal.wait() => {}
+        _ = task => {}
+    }
+}
+
+/// Runs the task made by `make`, restarting it with backoff if it panics and a policy is given.
+async fn run_task<F, Fut>(
+    guard: TaskGuard,
+    policy: Option<RestartPolicy>,
+    mut make: F,
+    signal: ShutdownSignal,
+) where
+    F: FnMut(ShutdownSignal) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut backoff = policy.map(|p| p.initial_backoff).unwrap_or_default();
+    let mut restarts = 0;
+    loop {
+        let started = Instant::now();
+        let task = tokio::spawn(make(signal.clone()));
+        let _abort = AbortOnDrop(task.abort_handle());
+        let panic = match task.await {
+            Err(e) if e.is_panic() => panic_message(e.into_panic()),
+            _ => return,
+        };
+        let name = lock(&guard.inner)
+            .tasks
+            .get(&guard.id)
+            .map(|(health, _)| health.name.clone())
+            .unwrap_or_default();
+        let restart = policy.filter(|policy| {
+            !signal.is_shutting_down() && policy.max_restarts.map_or(true, |max| restarts < max)
+        });
+        let Some(policy) = restart else {
+            log::error!("Task {} panicked: {}", name, panic);
+            guard.update(|health| {
+                health.last_panic = Some(panic);
+                health.set_status(TaskStatus::Failed);
+            });
+            return;
+        };
+        if started.elapsed() >= policy.max_backoff {
+            backoff = policy.initial_backoff;
+        }
+        log::error!(
+            "Task {} panicked, restarting in {:?}: {}",
+            name,
+            backoff,
+            panic
+        );
+        guard.update(|health| {
+            health.last_panic = Some(panic);
+            health.set_status(TaskStatus::Restarting);
+        });
+        let mut stopped = signal.clone();
+        tokio::select! {
+            _ = stopped.wait() => return,
+            _ = tokio::time::sleep(backoff) => {}
+        }
+        backoff = (backoff * 2).min(policy.max_backoff);
+        restarts += 1;
+        guard.update(|health| {
+            health.restarts = restarts;
+            health.set_status(TaskStatus::Running);
+        });
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Shutdown::new()
+    }
+}
+
+impl std::fmt::Debug for Shutdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = lock(&self.inner);
+        f.debug_struct("Shutdown")
+            .field("shutting_down", &self.is_shutting_down())
+            .field("tasks", &state.tasks.len())
+            .field("hooks", &state.hooks.len())
+            .finish()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Shutdown {
+            inner: Arc::new(Inner {
+                signal: watch::channel(false).0,
+                state: Mutex::new(State::default()),
+                changed: Notify::new(),
+            }),
+        }
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        *self.inner.signal.borrow()
+    }
+
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal(self.inner.signal.subscribe())
+    }
+
+    /// Spawns a task dropped at its current await point when the shutdown starts.
+    pub fn spawn<F>(&self, name: &str, task: F) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_with_signal(name, |signal| until_shutdown(signal, task))
+    }
+
+    /// Spawns a task given the shutdown signal, expected to wind down by itself once it fires.
+    ///
+    /// Tasks still running when the shutdown times out are aborted.
+    pub fn spawn_with_signal<F, Fut>(&self, name: &str, task: F) -> JoinHandle<()>
+    where
+        F: FnOnce(ShutdownSignal) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        // Made once, never restarted without a policy
+        let mut task = Some(task(self.signal()));
+        self.start(name, None, move |_| {
+            task.take()
+                .expect("tasks without a restart policy are made once")
+        })
+    }
+
+    /// Spawns a task made by `make`, made again and restarted after a backoff when it panics.
+    ///
+    /// Like with [`Shutdown::spawn`], the task is dropped at its current await point when the
+    /// shutdown starts, and never restarted after. Aborting the returned handle stops it for good.
+    ///
+    /// ```
+    /// let shutdown = kalshi_instance.shutdown_handle();
+    /// shutdown.supervise("marker", RestartPolicy::default(), move || {
+    ///     let tracker = tracker.clone();
+    ///     async move { tracker.mark_forever().await }
+    /// });
+    /// ```
+    pub fn supervise<F, Fut>(
+        &self,
+        name: &str,
+        policy: RestartPolicy,
+        mut make: F,
+    ) -> JoinHandle<()>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.start(name, Some(policy), move |signal| {
+            until_shutdown(signal, make())
+        })
+    }
+
+    fn start<F, Fut>(&self, name: &str, policy: Option<RestartPolicy>, make: F) -> JoinHandle<()>
+    where
+        F: FnMut(ShutdownSignal) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        // Registered before the task can finish and unregister itself
+        let mut state = lock(&self.inner);
+        let id = state.next_task_id;
+        state.next_task_id += 1;
+        let guard = TaskGuard {
+            inner: Arc::clone(&self.inner),
+            id,
+        };
+        let handle = tokio::spawn(run_task(guard, policy, make, self.signal()));
+        state
+            .tasks
+            .insert(id, (TaskHealth::new(name), handle.abort_handle()));
+        handle
+    }
+
+    /// The running tasks, then the ones that panicked and won't be restarted, each by name.
+    pub fn health(&self) -> Vec<TaskHealth> {
+        let state = lock(&self.inner);
+        let mut running: Vec<TaskHealth> = state
+            .tasks
+            .values()
+            .map(|(health, _)| health.clone())
+            .collect();
+        running.sort_by(|a, b| a.name.cmp(&b.name));
+        running.extend(state.failed.iter().cloned());
+        running
+    }
+
+    /// Whether no task panicked without being restarted, or is waiting to be.
+    pub fn is_healthy(&self) -> bool {
+        let state = lock(&self.inner);
+        state.failed.is_empty()
+            && state
+                .tasks
+                .values()
+                .all(|(health, _)| health.status == TaskStatus::Running)
+    }
+
+    /// Runs `hook` during the shutdown, once the tasks are stopped. Hooks run one after the other
+    /// in the order they were registered, hooks registered once the shutdown started never run.
+    pub fn on_shutdown<F, Fut>(&self, name: &str, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if self.is_shutting_down() {
+            log::warn!(
+                "Shutdown hook {} registered after the shutdown started",
+                name
+            );
+            return;
+        }
+        let hook: Hook = Box::new(move || Box::pin(hook()));
+        lock(&self.inner).hooks.push((name.to_string(), hook));
+    }
+
+    /// Stops every task and runs the hooks, giving each phase `timeout`.
+    ///
+    /// Calling it again, or from another clone, waits for the first shutdown and returns its report.
+    pub async fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        if self.inner.signal.send_replace(true) {
+            loop {
+                let changed = self.inner.changed.notified();
+                if let Some(report) = lock(&self.inner).report.clone() {
+                    return report;
+                }
+                changed.await;
+            }
+        }
+
+        let mut report = ShutdownReport::default();
+        let mut running: HashMap<u64, String> = lock(&self.inner)
+            .tasks
+            .iter()
+            .map(|(id, (health, _))| (*id, health.name.clone()))
+            .collect();
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let changed = self.inner.changed.notified();
+            {
+                let state = lock(&self.inner);
+                running.retain(|id, name| {
+                    let done = !state.tasks.contains_key(id);
+                    if done {
+                        report.joined_tasks.push(std::mem::take(name));
+                    }
+                    !done
+                });
+                if state.tasks.is_empty() {
+                    break;
+                }
+            }
+            if tokio::time::timeout_at(deadline, changed).await.is_err() {
+                let mut state = lock(&self.inner);
+                for (_, (health, task)) in state.tasks.drain() {
+                    task.abort();
+                    report.aborted_tasks.push(health.name);
+                }
+                break;
+            }
+        }
+
+        let hooks = std::mem::take(&mut lock(&self.inner).hooks);
+        for (name, hook) in hooks {
+            match tokio::time::timeout(timeout, hook()).await {
+                Ok(()) => report.completed_hooks.push(name),
+                Err(_) => {
+                    log::warn!("Shutdown hook {} timed out", name);
+                    report.timed_out_hooks.push(name);
+                }
+            }
+        }
+
+        lock(&self.inner).report = Some(report.clone());
+        self.inner.changed.notify_waiters();
+        report
+    }
+}
+
+impl Kalshi {
+    /// The [`Shutdown`] shared by this instance and its clones.
+    pub fn shutdown_handle(&self) -> &Shutdown {
+        &self.shutdown
+    }
+
+    /// Stops the background tasks started through this instance and its clones, then runs the
+    /// shutdown hooks, see [`Shutdown`]. Waits at most [`DEFAULT_SHUTDOWN_TIMEOUT`] per phase.
+    pub async fn shutdown(&self) -> ShutdownReport {
+        self.shutdown.shutdown(DEFAULT_SHUTDOWN_TIMEOUT).await
+    }
+
+    /// Cancels every order resting on the exchange during the shutdown, after the tasks that could
+    /// place new ones are stopped.
+    pub fn cancel_orders_on_shutdown(&self) {
+        let kalshi = self.clone();
+        self.shutdown
+            .on_shutdown("cancel resting orders", move || async move {
+                let mut cursor = None;
+                loop {
+                    let page = kalshi
+                        .get_multiple_orders(
+                            None,
+                            None,
+                            None,
+                            None,
+                            Some("resting".to_string()),
+                            Some(ORDERS_PAGE_SIZE),
+                            cursor,
+                        )
+                        .await;
+                    let (next_cursor, orders) = match page {
+                        Ok(page) => page,
+                        Err(e) => {
+                            log::warn!("Could not list the resting orders to cancel: {}", e);
+                            return;
+                        }
+                    };
+                    for order in orders {
+                        if let Err(e) = kalshi.cancel_order(&order.order_id).await {
+                            log::warn!("Could not cancel {} on shutdown: {}", order.order_id, e);
+                        }
+                    }
+                    match next_cursor {
+                        Some(next) if !next.is_empty() => cursor = Some(next),
+                        _ => break,
+                    }
+                }
+            });
+    }
+}
+
+/// Orders fetched per page while listing the orders to cancel.
+const ORDERS_PAGE_SIZE: i32 = 200;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn test_tasks_stopped_then_hooks_run() {
+        let shutdown = Shutdown::new();
+        let hook_saw_tasks_stopped = Arc::new(AtomicBool::new(false));
+
+        // Stops at its next await point
+        shutdown.spawn("loop", std::future::pending());
+        // Winds down by itself
+        let wound_down = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&wound_down);
+        shutdown.spawn_with_signal("graceful", |mut signal| async move {
+            signal.wait().await;
+            flag.store(true, Ordering::SeqCst);
+        });
+        // Ignores the signal
+        shutdown.spawn_with_signal("stubborn", |_| std::future::pending());
+
+        let flag = Arc::clone(&wound_down);
+        let seen = Arc::clone(&hook_saw_tasks_stopped);
+        shutdown.on_shutdown("flush", move || async move {
+            seen.store(flag.load(Ordering::SeqCst), Ordering::SeqCst);
+        });
+
+        let clone = shutdown.clone();
+        let second = tokio::spawn(async move { clone.shutdown(Duration::from_secs(5)).await });
+        let mut report = shutdown.shutdown(Duration::from_millis(200)).await;
+        report.joined_tasks.sort();
+        assert_eq!(report.joined_tasks, vec!["graceful", "loop"]);
+        assert_eq!(report.aborted_tasks, vec!["stubborn"]);
+        assert_eq!(report.completed_hooks, vec!["flush"]);
+        assert!(!report.is_clean());
+        assert!(hook_saw_tasks_stopped.load(Ordering::SeqCst));
+
+        // Every caller gets the same report
+        let mut second = second.await.unwrap();
+        second.joined_tasks.sort();
+        assert_eq!(second, report);
+        assert!(shutdown.signal().is_shutting_down());
+    }
+
+    #[tokio::test]
+    async fn test_panicking_tasks_restarted_or_failed() {
+        let shutdown = Shutdown::new();
+        let policy = RestartPolicy {
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+            max_restarts: Some(5),
+        };
+        let runs = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let counter = Arc::clone(&runs);
+        shutdown.supervise("flaky", policy, move || {
+            let run = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if run < 2 {
+                    panic!("run {} failed", run);
+                }
+                std::future::pending::<()>().await;
+            }
+        });
+        shutdown.spawn("fragile", async { panic!("gone") });
+
+        let health = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let health = shutdown.health();
+                let settled = health.iter().all(|task| task.last_panic.is_some())
+                    && health
+                        .iter()
+                        .all(|task| task.status != TaskStatus::Restarting);
+                if settled && runs.load(Ordering::SeqCst) == 3 {
+                    return health;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(health[0].name, "flaky");
+        assert_eq!(health[0].status, TaskStatus::Running);
+        assert_eq!(health[0].restarts, 2);
+        assert_eq!(health[0].last_panic.as_deref(), Some("run 1 failed"));
+        assert_eq!(health[1].name, "fragile");
+        assert_eq!(health[1].status, TaskStatus::Failed);
+        assert_eq!(health[1].last_panic.as_deref(), Some("gone"));
+        assert!(!shutdown.is_healthy());
+
+        let report = shutdown.shutdown(Duration::from_secs(1)).await;
+        assert_eq!(report.joined_tasks, vec!["flaky"]);
+    }
+}
@@ -0,0 +1,646 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use chrono::{SecondsFormat, Utc};
+
+use crate::{
+    Action, Book, CanceledOrder, Fill, KalshiError, Order, OrderStatus, OrderType, PositionTracker,
+    Side, TimeInForce,
+};
+
+#[derive(Debug, Default)]
+struct SimState {
+    /// Cash in cents
+    balance: i64,
+    books: HashMap<String, Book>,
+    orders: Vec<Order>,
+    fills: Vec<Fill>,
+    next_id: u64,
+}
+
+/// A paper-trading stand-in for [`Kalshi`](crate::Kalshi) that matches orders locally.
+///
+/// `SimKalshi` has the same trading methods as `Kalshi` (`create_order`, `cancel_order`,
+/// `decrease_order`, `get_balance`, `get_single_order`, `get_multiple_orders` and
+/// `get_multiple_fills`) so a strategy can switch between the two, but orders are matched against
+/// books kept locally instead of being sent to the exchange.
+///
+/// Books are fed with [`SimKalshi::set_book`] and [`SimKalshi::apply_change`], or straight from
+/// a live or recorded websocket feed with `SimKalshi::feed`. Orders take liquidity from the book
+/// as soon as they're placed, and resting orders fill whenever the book moves through their price.
+/// Liquidity taken is removed from the local book until the feed sends the level again.
+///
+/// Fees, order expiration and queue position aren't simulated: a resting order fills as soon as
+/// the opposite side crosses it.
+///
+/// ```
+/// let sim = SimKalshi::new(100_000);
+/// tokio::spawn({
+///     let sim = sim.clone();
+///     async move { sim.feed(KalshiReplay::new("feed.jsonl").stream()).await }
+/// });
+///
+/// let order = sim.create_order(
+///     Action::Buy, None, 10, Side::Yes, "KXHIGHNY-25OCT02-B80.5".to_string(),
+///     OrderType::Limit, None, None, None, None, Some(40),
+/// ).await?;
+/// println!("{:?}, balance {}", order.status, sim.get_balance().await?);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SimKalshi {
+    state: Arc<Mutex<SimState>>,
+    positions: PositionTracker,
+}
+
+impl SimKalshi {
+    /// Creates a simulator holding `balance` cents and no positions.
+    pub fn new(balance: i64) -> Self {
+        SimKalshi {
+            state: Arc::new(Mutex::new(SimState {
+                balance,
+                ..Default::default()
+            })),
+            positions: PositionTracker::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SimState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// The simulated positions, updated with every fill.
+    pub fn positions(&self) -> PositionTracker {
+        self.positions.clone()
+    }
+
+    /// Replaces the book of a market, then fills any resting order it crosses.
+    pub fn set_book(&self, book: Book) {
+        let ticker = book.market_ticker.clone();
+        let mut state = self.lock();
+        state.books.insert(ticker.clone(), book);
+        self.match_resting(&mut state, &ticker);
+    }
+
+    /// Changes the quantity resting at `price` in a market's book, then fills any resting order it crosses.
+    pub fn apply_change(&self, ticker: &str, side: Side, price: u32, delta: i64) {
+        let mut state = self.lock();
+        state
+            .books
+            .entry(ticker.to_string())
+            .or_insert_with(|| Book::new(ticker))
+            .apply_change(side, price, delta);
+        self.match_resting(&mut state, ticker);
+    }
+
+    /// The local book of a market.
+    pub fn book(&self, ticker: &str) -> Option<Book> {
+        self.lock().books.get(ticker).cloned()
+    }
+
+    /// Settles a market: resting orders are canceled and every contract held on `result` pays 100 cents.
+    pub fn settle(&self, ticker: &str, result: Side) {
+        let mut state = self.lock();
+        for order in state.orders.iter_mut() {
+            if order.ticker == ticker && order.status == OrderStatus::Resting {
+                order.status = OrderStatus::Canceled;
+                order.remaining_count = Some(0);
+            }
+        }
+        let held = self.positions.position(ticker);
+        if let Some(side) = held.side() {
+            let count = held.position.abs();
+            let price = if side == result { 100 } else { 0 };
+            self.positions
+                .apply_fill(ticker, side, Action::Sell, count, price);
+            state.balance += count * price;
+        }
+    }
+
+    /// Retrieves the simulated balance in cents, see [`Kalshi::get_balance`](crate::Kalshi::get_balance).
+    pub async fn get_balance(&self) -> Result<i64, KalshiError> {
+        Ok(self.lock().balance)
+    }
+
+    /// Places an order against the local books, see [`Kalshi::create_order`](crate::Kalshi::create_order).
+    ///
+    /// Market orders take what they can up to `buy_max_cost` and the remainder is canceled.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_order(
+        &self,
+        action: Action,
+        client_order_id: Option<String>,
+        count: i32,
+        side: Side,
+        ticker: String,
+        input_type: OrderType,
+        buy_max_cost: Option<i64>,
+        _expiration_ts: Option<i64>,
+        no_price: Option<i64>,
+        _sell_position_floor: Option<i32>,
+        yes_price: Option<i64>,
+    ) -> Result<Order, KalshiError> {
+        if count <= 0 {
+            return Err(KalshiError::UserInputError(
+                "Order count must be positive".to_string(),
+            ));
+        }
+        let (is_market, price) = match input_type {
+            OrderType::Limit => match (side, yes_price, no_price) {
+                (_, Some(_), Some(_)) => {
+                    return Err(KalshiError::UserInputError(
+                        "Can only provide no_price exclusive or yes_price, can't provide both"
+                            .to_string(),
+                    ))
+                }
+                (_, None, None) => return Err(KalshiError::UserInputError(
+                    "Must provide either no_price exclusive or yes_price, can't provide neither"
+                        .to_string(),
+                )),
+                (Side::Yes, Some(p), None) | (Side::No, None, Some(p)) => (false, p),
+                (Side::Yes, None, Some(p)) | (Side::No, Some(p), None) => (false, 100 - p),
+            },
+            // Market orders accept any price
+            OrderType::Market => match action {
+                Action::Buy => (true, 99),
+                Action::Sell => (true, 1),
+            },
+        };
+        if !(1..=99).contains(&price) {
+            return Err(KalshiError::UserInputError(format!(
+                "Price must be between 1 and 99 cents, got {}",
+                price
+            )));
+        }
+
+        let mut state = self.lock();
+        state.next_id += 1;
+        let order_id = format!("sim-{}", state.next_id);
+        let order = Order {
+            order_id: order_id.clone(),
+            user_id: None,
+            ticker,
+            status: OrderStatus::Resting,
+            yes_price: match side {
+                Side::Yes => price as i32,
+                Side::No => 100 - price as i32,
+            },
+            no_price: match side {
+                Side::Yes => 100 - price as i32,
+                Side::No => price as i32,
+            },
+            created_time: Some(now()),
+            taker_fill_count: Some(0),
+            taker_fill_cost: Some(0),
+            place_count: Some(count),
+            decrease_count: Some(0),
+            maker_fill_count: Some(0),
+            fcc_cancel_count: Some(0),
+            close_cancel_count: Some(0),
+            remaining_count: Some(count),
+            queue_position: None,
+            expiration_time: None,
+            time_in_force: Some(TimeInForce::GoodTillCanceled),
+            post_only: false,
+            taker_fees: Some(0),
+            action,
+            side,
+            r#type: if is_market { "market" } else { "limit" }.to_string(),
+            last_update_time: None,
+            client_order_id: client_order_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            order_group_id: String::new(),
+        };
+
+        let opening_cost = {
+            let pay = match action {
+                Action::Buy => price,
+                Action::Sell => 100 - price,
+            };
+            let closable = closable(
+                self.positions.position(&order.ticker).position,
+                side,
+                action,
+            );
+            (count as i64 - closable).max(0) * pay
+        };
+        if !is_market && opening_cost > state.balance {
+            return Err(KalshiError::UserInputError(format!(
+                "Insufficient balance, order needs {} cents but only {} are available",
+                opening_cost, state.balance
+            )));
+        }
+
+        state.orders.push(order);
+        let index = state.orders.len() - 1;
+        let budget = buy_max_cost.filter(|_| is_market && action == Action::Buy);
+        self.execute(&mut state, index, true, budget);
+
+        let order = &mut state.orders[index];
+        if order.remaining_count == Some(0) {
+            order.status = OrderStatus::Executed;
+        } else if is_market {
+            order.status = OrderStatus::Canceled;
+            order.remaining_count = Some(0);
+        }
+        Ok(order.clone())
+    }
+
+    /// Cancels a resting order, see [`Kalshi::cancel_order`](crate::Kalshi::cancel_order).
+    pub async fn cancel_order(&self, order_id: &str) -> Result<CanceledOrder, KalshiError> {
+        let mut state = self.lock();
+        let order = find_resting(&mut state, order_id)?;
+        let reduced_by = order.remaining_count.unwrap_or_default();
+        order.remaining_count = Some(0);
+        order.status = OrderStatus::Canceled;
+        order.last_update_time = Some(now());
+        Ok(CanceledOrder {
+            order: order.clone(),
+            reduced_by,
+        })
+    }
+
+    /// Decreases a resting order, see [`Kalshi::decrease_order`](crate::Kalshi::decrease_order).
+    pub async fn decrease_order(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> Result<Order, KalshiError> {
+        let mut state = self.lock();
+        let order = find_resting(&mut state, order_id)?;
+        let remaining = order.remaining_count.unwrap_or_default();
+        let new_remaining = match (reduce_by, reduce_to) {
+            (Some(by), None) => remaining - by,
+            (None, Some(to)) => to,
+            _ => {
+                return Err(KalshiError::UserInputError(
+                    "Must provide either reduce_by exclusive or reduce_to, can't provide both or neither."
+                        .to_string(),
+                ))
+            }
+        };
+        if new_remaining < 0 || new_remaining > remaining {
+            return Err(KalshiError::UserInputError(format!(
+                "Can't decrease an order of {} remaining contracts to {}",
+                remaining, new_remaining
+            )));
+        }
+        order.remaining_count = Some(new_remaining);
+        order.decrease_count =
+            Some(order.decrease_count.unwrap_or_default() + remaining - new_remaining);
+        order.last_update_time = Some(now());
+        if new_remaining == 0 {
+            order.status = OrderStatus::Canceled;
+        }
+        Ok(order.clone())
+    }
+
+    /// Retrieves a simulated order by id, see [`Kalshi::get_single_order`](crate::Kalshi::get_single_order).
+    pub async fn get_single_order(&self, order_id: &String) -> Result<Order, KalshiError> {
+        self.lock()
+            .orders
+            .iter()
+            .find(|order| &order.order_id == order_id)
+            .cloned()
+            .ok_or_else(|| KalshiError::UserInputError(format!("Order {} not found", order_id)))
+    }
+
+    /// Lists simulated orders, see [`Kalshi::get_multiple_orders`](crate::Kalshi::get_multiple_orders).
+    ///
+    /// The cursor is the number of orders already returned.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_multiple_orders(
+        &self,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        _min_ts: Option<i64>,
+        _max_ts: Option<i64>,
+        status: Option<String>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> Result<(Option<String>, Vec<Order>), KalshiError> {
+        let state = self.lock();
+        let matching = state.orders.iter().filter(|order| {
+            ticker.as_ref().map_or(true, |t| &order.ticker == t)
+                && event_ticker
+                    .as_ref()
+                    .map_or(true, |e| order.ticker.starts_with(&format!("{}-", e)))
+                && status.as_ref().map_or(true, |s| {
+                    format!("{:?}", order.status).eq_ignore_ascii_case(s)
+                })
+        });
+        Ok(paginate(matching, limit, cursor))
+    }
+
+    /// Lists simulated fills, see [`Kalshi::get_multiple_fills`](crate::Kalshi::get_multiple_fills).
+    ///
+    /// The cursor is the number of fills already returned.
+    pub async fn get_multiple_fills(
+        &self,
+        ticker: Option<String>,
+        order_id: Option<String>,
+        _min_ts: Option<i64>,
+        _max_ts: Option<i64>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> Result<(Option<String>, Vec<Fill>), KalshiError> {
+        let state = self.lock();
+        let matching = state.fills.iter().filter(|fill| {
+            ticker.as_ref().map_or(true, |t| &fill.ticker == t)
+                && order_id.as_ref().map_or(true, |id| &fill.order_id == id)
+        });
+        Ok(paginate(matching, limit, cursor))
+    }
+
+    fn match_resting(&self, state: &mut SimState, ticker: &str) {
+        for index in 0..state.orders.len() {
+            let order = &state.orders[index];
+            if order.ticker == ticker && order.status == OrderStatus::Resting {
+                self.execute(state, index, false, None);
+                let order = &mut state.orders[index];
+                if order.remaining_count == Some(0) {
+                    order.status = OrderStatus::Executed;
+                }
+            }
+        }
+    }
+
+    /// Fills as much of an order as the book and the balance allow.
+    fn execute(&self, state: &mut SimState, index: usize, is_taker: bool, budget: Option<i64>) {
+        let order = &state.orders[index];
+        let (ticker, side, action) = (order.ticker.clone(), order.side, order.action);
+        let (order_id, limit) = (
+            order.order_id.clone(),
+            match side {
+                Side::Yes => order.yes_price as i64,
+                Side::No => order.no_price as i64,
+            },
+        );
+        let mut remaining = order.remaining_count.unwrap_or_default() as i64;
+        let mut budget = budget;
+
+        while remaining > 0 {
+            let book = match state.books.get_mut(&ticker) {
+                Some(book) => book,
+                None => return,
+            };
+            // Buys take the opposite side's bids, sells take their own side's bids
+            let (book_side, price, available) = match action {
+                Action::Buy => {
+                    let opposite = match side {
+                        Side::Yes => Side::No,
+                        Side::No => Side::Yes,
+                    };
+                    let best = match opposite {
+                        Side::Yes => book.best_yes_bid(),
+                        Side::No => book.best_no_bid(),
+                    };
+                    match best {
+                        Some((bid, qty)) if 100 - bid as i64 <= limit => {
+                            (opposite, 100 - bid as i64, qty)
+                        }
+                        _ => break,
+                    }
+                }
+                Action::Sell => {
+                    let best = match side {
+                        Side::Yes => book.best_yes_bid(),
+                        Side::No => book.best_no_bid(),
+                    };
+                    match best {
+                        Some((bid, qty)) if bid as i64 >= limit => (side, bid as i64, qty),
+                        _ => break,
+                    }
+                }
+            };
+
+            // Closing a position pays out the held side, opening one costs the side bought
+            let (receive, pay) = match action {
+                Action::Buy => (100 - price, price),
+                Action::Sell => (price, 100 - price),
+            };
+            let wanted = remaining.min(available);
+            let closing = wanted.min(closable(
+                self.positions.position(&ticker).position,
+                side,
+                action,
+            ));
+            let mut cash = state.balance + closing * receive;
+            if let Some(budget) = budget {
+                cash = cash.min(budget);
+            }
+            let opening = (wanted - closing).min(if pay > 0 { cash / pay } else { wanted });
+            let count = closing + opening;
+            if count == 0 {
+                break;
+            }
+
+            book.apply_change(
+                book_side,
+                match action {
+                    Action::Buy => (100 - price) as u32,
+                    Action::Sell => price as u32,
+                },
+                -count,
+            );
+            state.balance += closing * receive - opening * pay;
+            if let Some(budget) = budget.as_mut() {
+                *budget -= opening * pay;
+            }
+            self.positions
+                .apply_fill(&ticker, side, action, count, price);
+            remaining -= count;
+
+            state.next_id += 1;
+            let yes_price = match side {
+                Side::Yes => price,
+                Side::No => 100 - price,
+            };
+            state.fills.push(Fill {
+                action,
+                count: count as i32,
+                created_time: now(),
+                is_taker,
+                no_price: 100 - yes_price,
+                order_id: order_id.clone(),
+                side,
+                ticker: ticker.clone(),
+                trade_id: format!("sim-{}", state.next_id),
+                yes_price,
+            });
+
+            let order = &mut state.orders[index];
+            order.remaining_count = Some(remaining as i32);
+            order.last_update_time = Some(now());
+            if is_taker {
+                order.taker_fill_count =
+                    Some(order.taker_fill_count.unwrap_or_default() + count as i32);
+                order.taker_fill_cost =
+                    Some(order.taker_fill_cost.unwrap_or_default() + (count * price) as i32);
+            } else {
+                order.maker_fill_count =
+                    Some(order.maker_fill_count.unwrap_or_default() + count as i32);
+            }
+        }
+    }
+}
+
+/// How many contracts of an order would reduce the position instead of adding to it.
+fn closable(position: i64, side: Side, action: Action) -> i64 {
+    match (side, action) {
+        // Adds yes, closes a no position
+        (Side::Yes, Action::Buy) | (Side::No, Action::Sell) => (-position).max(0),
+        (Side::Yes, Action::Sell) | (Side::No, Action::Buy) => position.max(0),
+    }
+}
+
+fn find_resting<'a>(state: &'a mut SimState, order_id: &str) -> Result<&'a mut Order, KalshiError> {
+    match state
+        .orders
+        .iter_mut()
+        .find(|order| order.order_id == order_id)
+    {
+        Some(order) if order.status == OrderStatus::Resting => Ok(order),
+        Some(_) => Err(KalshiError::UserInputError(format!(
+            "Order {} is no longer resting",
+            order_id
+        ))),
+        None => Err(KalshiError::UserInputError(format!(
+            "Order {} not found",
+            order_id
+        ))),
+    }
+}
+
+fn paginate<'a, T: Clone + 'a>(
+    items: impl Iterator<Item = &'a T>,
+    limit: Option<i32>,
+    cursor: Option<String>,
+) -> (Option<String>, Vec<T>) {
+    let skip = cursor.and_then(|c| c.parse::<usize>().ok()).unwrap_or(0);
+    let limit = limit.map(|l| l.max(0) as usize).unwrap_or(100);
+    let mut items = items.skip(skip);
+    let page: Vec<T> = items.by_ref().take(limit).cloned().collect();
+    let next = items.next().map(|_| (skip + page.len()).to_string());
+    (next, page)
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::{client::KalshiWebsocketError, responses::KalshiWebsocketResponse};
+    use futures_util::{Stream, StreamExt};
+
+    impl SimKalshi {
+        /// Applies an orderbook snapshot or delta from the websocket feed, other messages are ignored.
+        pub fn on_message(&self, msg: &KalshiWebsocketResponse) {
+            match msg {
+                KalshiWebsocketResponse::OrderbookSnapshot { msg, .. } => self.set_book(msg.into()),
+                KalshiWebsocketResponse::OrderbookDelta { msg, .. } => self.apply_change(
+                    &msg.market_ticker,
+                    msg.side.into(),
+                    msg.price,
+                    msg.delta as i64,
+                ),
+                _ => {}
+            }
+        }
+
+        /// Applies every message of a live or recorded feed until it ends.
+        ///
+        /// Works with both [`KalshiWebsocketClient::stream`](crate::websockets::client::KalshiWebsocketClient::stream)
+        /// and [`KalshiReplay::stream`](crate::websockets::recording::KalshiReplay::stream), feed errors are logged and skipped.
+        pub async fn feed(
+            &self,
+            stream: impl Stream<Item = Result<KalshiWebsocketResponse, KalshiWebsocketError>>,
+        ) {
+            let mut stream = Box::pin(stream);
+            while let Some(msg) = stream.next().await {
+                match msg {
+                    Ok(msg) => self.on_message(&msg),
+                    Err(e) => log::warn!("Simulator skipped a feed error: {}", e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TICKER: &str = "KXHIGHNY-25OCT02-B80.5";
+
+    #[tokio::test]
+    async fn test_orders_matched_against_book() {
+        let sim = SimKalshi::new(10_000);
+        let mut book = Book::new(TICKER);
+        book.apply_change(Side::Yes, 40, 100);
+        // Yes asks at 45 and 47
+        book.apply_change(Side::No, 55, 5);
+        book.apply_change(Side::No, 53, 10);
+        sim.set_book(book);
+
+        let order = sim
+            .create_order(
+                Action::Buy,
+                None,
+                10,
+                Side::Yes,
+                TICKER.to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                None,
+                None,
+                Some(46),
+            )
+            .await
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Resting);
+        assert_eq!(order.remaining_count, Some(5));
+        assert_eq!(sim.get_balance().await.unwrap(), 10_000 - 5 * 45);
+
+        // The book moves through the resting order
+        sim.apply_change(TICKER, Side::No, 54, 20);
+        let order = sim.get_single_order(&order.order_id).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Executed);
+        assert_eq!(sim.get_balance().await.unwrap(), 10_000 - 5 * 45 - 5 * 46);
+        assert_eq!(sim.positions().position(TICKER).position, 10);
+
+        // Selling into the yes bid closes the position
+        let order = sim
+            .create_order(
+                Action::Sell,
+                None,
+                10,
+                Side::Yes,
+                TICKER.to_string(),
+                OrderType::Market,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Executed);
+        assert_eq!(
+            sim.get_balance().await.unwrap(),
+            10_000 - 5 * 45 - 5 * 46 + 10 * 40
+        );
+        assert_eq!(sim.positions().position(TICKER).position, 0);
+
+        let (_, fills) = sim
+            .get_multiple_fills(Some(TICKER.to_string()), None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(fills.len(), 3);
+    }
+}
@@ -0,0 +1,335 @@
+use crate::{Action, Market, RiskDecision, RiskManager, Series, Side};
+
+/// How trading fees are charged, used to size positions net of fees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeModel {
+    NoFees,
+    /// `rate * count * price * (1 - price)` dollars, rounded up to the cent. Kalshi's taker fee
+    /// uses a rate of 0.07, see [`FeeModel::KALSHI_TAKER`].
+    Quadratic {
+        rate: f64,
+    },
+    /// A fixed fee per contract, in cents.
+    PerContract(f64),
+}
+
+impl FeeModel {
+    /// Kalshi's general taker fee schedule.
+    pub const KALSHI_TAKER: FeeModel = FeeModel::Quadratic { rate: 0.07 };
+
+    /// The fee of trading `count` contracts at `price` cents, in cents.
+    pub fn fee(&self, count: i64, price: i64) -> i64 {
+        let fee = match self {
+            FeeModel::NoFees => 0.0,
+            FeeModel::Quadratic { rate } => {
+                let p = price as f64 / 100.0;
+                rate * count as f64 * p * (1.0 - p) * 100.0
+            }
+            FeeModel::PerContract(cents) => cents * count as f64,
+        };
+        // Guard against 7.000000001 rounding up to 8
+        (fee - 1e-9).ceil().max(0.0) as i64
+    }
+}
+
+/// The fees of a series, for orders that take and orders that rest on the book.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeSchedule {
+    pub taker: FeeModel,
+    pub maker: FeeModel,
+}
+
+impl FeeSchedule {
+    /// The schedule of a series from its `fee_type` and `fee_multiplier`.
+    ///
+    /// Takers pay `0.07` times the multiplier, makers only pay on `quadratic_with_maker_fees`
+    /// series, `0.0175` times the multiplier. Unknown fee types are priced like `quadratic`.
+    pub fn from_series(series: &Series) -> Self {
+        let multiplier = series.fee_multiplier;
+        let taker = FeeModel::Quadratic {
+            rate: 0.07 * multiplier,
+        };
+        let maker = match series.fee_type.as_str() {
+            "quadratic_with_maker_fees" => FeeModel::Quadratic {
+                rate: 0.0175 * multiplier,
+            },
+            _ => FeeModel::NoFees,
+        };
+        FeeSchedule { taker, maker }
+    }
+}
+
+/// The most contracts a buy at `price` cents can get with `budget` cents, fees included.
+pub fn affordable_contracts(budget: i64, price: i64, fees: FeeModel) -> i64 {
+    if budget <= 0 || price <= 0 {
+        return 0;
+    }
+    // Fees only grow with the count, search the largest count that fits
+    let (mut low, mut high) = (0, budget / price);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if mid * price + fees.fee(mid, price) <= budget {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+impl Market {
+    /// The most contracts a buy at `price` cents in this market can get.
+    ///
+    /// The contracts and their fees must fit in the `balance` left once `resting_exposure`, the
+    /// cents committed to resting buy orders, is set aside, and their cost within the market's
+    /// `risk_limit_cents` when it has one. Both amounts are in cents.
+    ///
+    /// # Example
+    /// ```
+    /// let market = kalshi_instance.get_single_market(&ticker).await?;
+    /// let resting: i64 = order_tracker.exposure_by_market().values().sum();
+    /// let count = market.max_contracts(45, kalshi_instance.get_balance().await?, resting, FeeModel::KALSHI_TAKER);
+    /// ```
+    pub fn max_contracts(
+        &self,
+        price: i64,
+        balance: i64,
+        resting_exposure: i64,
+        fees: FeeModel,
+    ) -> i64 {
+        let mut count = affordable_contracts(balance - resting_exposure, price, fees);
+        if self.risk_limit_cents > 0 && price > 0 {
+            count = count.min(self.risk_limit_cents / price);
+        }
+        count
+    }
+}
+
+/// The Kelly fraction of a bankroll to stake on a contract.
+///
+/// `probability` is the estimated chance that the contract pays out, `cost` what a contract costs
+/// in cents including fees. A contract pays 100 cents, so the optimal fraction is
+/// `(probability * 100 - cost) / (100 - cost)`. Returns 0 when the bet has no edge.
+pub fn kelly_fraction(probability: f64, cost: f64) -> f64 {
+    if !(0.0..100.0).contains(&cost) || !(0.0..=1.0).contains(&probability) {
+        return 0.0;
+    }
+    ((probability * 100.0 - cost) / (100.0 - cost)).max(0.0)
+}
+
+/// A position size computed by [`PositionSizer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sizing {
+    /// Contracts to buy, after every cap.
+    pub count: i32,
+    /// The Kelly fraction before the multiplier and caps.
+    pub kelly_fraction: f64,
+    /// Expected profit per contract in cents, net of fees.
+    pub edge: f64,
+    /// What the contracts cost in cents, excluding fees.
+    pub cost: i64,
+    /// Fees of the contracts, in cents.
+    pub fees: i64,
+}
+
+/// Turns a probability estimate into a number of contracts to buy, with the Kelly criterion.
+///
+/// The stake is the Kelly fraction of the bankroll times a multiplier (0.5 for half Kelly is a
+/// common choice, since full Kelly is very sensitive to overestimated edges), net of fees. The
+/// resulting count is then capped by `max_contracts`, `max_cost`, the bankroll itself and, when
+/// one is attached, the [`RiskManager`]'s limits.
+///
+/// ```
+/// let sizer = PositionSizer::new(kalshi_instance.get_balance().await?)
+///     .kelly_multiplier(0.5)
+///     .fees(FeeModel::KALSHI_TAKER)
+///     .max_contracts(500)
+///     .risk_manager(risk.clone());
+///
+/// // We think yes has a 62% chance, the yes ask is 55
+/// let sizing = sizer.size("KXHIGHNY-25OCT02-B80.5", Side::Yes, 0.62, 55);
+/// if sizing.count > 0 {
+///     kalshi_instance.create_order(Action::Buy, None, sizing.count, Side::Yes, /* ... */).await?;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PositionSizer {
+    bankroll: i64,
+    resting_exposure: i64,
+    kelly_multiplier: f64,
+    fees: FeeModel,
+    max_contracts: Option<i32>,
+    max_cost: Option<i64>,
+    risk_manager: Option<RiskManager>,
+}
+
+impl PositionSizer {
+    /// Sizes full Kelly stakes of `bankroll` cents, without fees or caps.
+    pub fn new(bankroll: i64) -> Self {
+        PositionSizer {
+            bankroll,
+            resting_exposure: 0,
+            kelly_multiplier: 1.0,
+            fees: FeeModel::NoFees,
+            max_contracts: None,
+            max_cost: None,
+            risk_manager: None,
+        }
+    }
+
+    /// Stakes `multiplier` times the Kelly fraction, 0.5 for half Kelly.
+    pub fn kelly_multiplier(mut self, multiplier: f64) -> Self {
+        self.kelly_multiplier = multiplier.max(0.0);
+        self
+    }
+
+    /// Sets aside `resting_exposure` cents of the bankroll, committed to resting buy orders.
+    pub fn resting_exposure(mut self, resting_exposure: i64) -> Self {
+        self.resting_exposure = resting_exposure.max(0);
+        self
+    }
+
+    pub fn fees(mut self, fees: FeeModel) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    pub fn max_contracts(mut self, max_contracts: i32) -> Self {
+        self.max_contracts = Some(max_contracts);
+        self
+    }
+
+    /// Caps what the contracts and their fees may cost, in cents.
+    pub fn max_cost(mut self, max_cost: i64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// Also caps sizes to what `risk_manager` accepts.
+    pub fn risk_manager(mut self, risk_manager: RiskManager) -> Self {
+        self.risk_manager = Some(risk_manager);
+        self
+    }
+
+    /// Sizes a buy of `side` of `ticker` at `price` cents, given the estimated probability that yes pays out.
+    pub fn size(&self, ticker: &str, side: Side, probability_yes: f64, price: i64) -> Sizing {
+        let probability = match side {
+            Side::Yes => probability_yes,
+            Side::No => 1.0 - probability_yes,
+        };
+        // Fees are rounded per order, estimate them per contract from a large order
+        let fee_per_contract = self.fees.fee(100, price) as f64 / 100.0;
+        let cost_per_contract = price as f64 + fee_per_contract;
+        let kelly = kelly_fraction(probability, cost_per_contract);
+        let edge = probability * 100.0 - cost_per_contract;
+
+        let mut budget = (self.bankroll as f64 * kelly * self.kelly_multiplier).floor() as i64;
+        budget = budget.min(self.bankroll - self.resting_exposure);
+        if let Some(max_cost) = self.max_cost {
+            budget = budget.min(max_cost);
+        }
+        let count = affordable_contracts(budget, price, self.fees);
+        let mut count = count.min(i32::MAX as i64) as i32;
+        if let Some(max_contracts) = self.max_contracts {
+            count = count.min(max_contracts);
+        }
+        if let (Some(risk_manager), true) = (&self.risk_manager, count > 0) {
+            count = match risk_manager.check_order(ticker, Action::Buy, side, count, Some(price)) {
+                RiskDecision::Accept => count,
+                RiskDecision::Shrink(shrunk) => shrunk,
+                RiskDecision::Reject(_) => 0,
+            };
+        }
+
+        Sizing {
+            count,
+            kelly_fraction: kelly,
+            edge,
+            cost: count as i64 * price,
+            fees: self.fees.fee(count as i64, price),
+        }
+    }
+
+    /// Sizes a buy like [`PositionSizer::size`], also capped by the market's `risk_limit_cents`.
+    pub fn size_market(
+        &self,
+        market: &Market,
+        side: Side,
+        probability_yes: f64,
+        price: i64,
+    ) -> Sizing {
+        let mut sizing = self.size(&market.ticker, side, probability_yes, price);
+        let max = market.max_contracts(price, self.bankroll, self.resting_exposure, self.fees);
+        if (sizing.count as i64) > max {
+            sizing.count = max as i32;
+            sizing.cost = max * price;
+            sizing.fees = self.fees.fee(max, price);
+        }
+        sizing
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_kelly_sizing_net_of_fees() {
+        assert_eq!(FeeModel::KALSHI_TAKER.fee(100, 50), 175);
+        assert_eq!(FeeModel::KALSHI_TAKER.fee(1, 50), 2);
+        assert!((kelly_fraction(0.6, 50.0) - 0.2).abs() < 1e-9);
+        assert_eq!(kelly_fraction(0.4, 50.0), 0.0);
+
+        let sizer = PositionSizer::new(100_000);
+        // 20% of the bankroll at 50 cents
+        assert_eq!(sizer.size("TICKER", Side::Yes, 0.6, 50).count, 400);
+        // The same edge on the no side
+        assert_eq!(sizer.size("TICKER", Side::No, 0.4, 50).count, 400);
+
+        let sizing = sizer
+            .clone()
+            .kelly_multiplier(0.5)
+            .fees(FeeModel::KALSHI_TAKER)
+            .size("TICKER", Side::Yes, 0.6, 50);
+        assert!(sizing.count < 200 && sizing.count > 150);
+        assert!(sizing.cost + sizing.fees <= 100_000 * 2 / 10 / 2);
+
+        assert_eq!(
+            sizer
+                .max_contracts(50)
+                .size("TICKER", Side::Yes, 0.6, 50)
+                .count,
+            50
+        );
+    }
+
+    #[test]
+    fn test_max_contracts_within_balance_and_risk_limit() {
+        // 44 * 45 + 77 cents of fees fits in 2_100, 45 * 45 + 78 doesn't
+        assert_eq!(affordable_contracts(2_100, 45, FeeModel::KALSHI_TAKER), 44);
+        assert_eq!(affordable_contracts(2_100, 45, FeeModel::NoFees), 46);
+        assert_eq!(affordable_contracts(-5, 45, FeeModel::NoFees), 0);
+
+        let mut market: Market =
+            serde_json::from_value(crate::testing::fixtures::market()).unwrap();
+        market.risk_limit_cents = 0;
+        assert_eq!(
+            market.max_contracts(50, 10_000, 5_000, FeeModel::NoFees),
+            100
+        );
+        market.risk_limit_cents = 2_000;
+        assert_eq!(
+            market.max_contracts(50, 10_000, 5_000, FeeModel::NoFees),
+            40
+        );
+
+        // Kelly stakes 400 contracts, the risk limit allows 40
+        let sizer = PositionSizer::new(100_000);
+        let sizing = sizer.size_market(&market, Side::Yes, 0.6, 50);
+        assert_eq!((sizing.count, sizing.cost), (40, 2_000));
+        let sizing = sizer
+            .resting_exposure(99_500)
+            .size_market(&market, Side::Yes, 0.6, 50);
+        assert_eq!(sizing.count, 10);
+    }
+}
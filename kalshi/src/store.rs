@@ -0,0 +1,61 @@
+//! Files keeping state across restarts, replaced through a temporary file on every save so a
+//! crash never leaves one half written.
+
+use std::path::Path;
+
+use crate::KalshiError;
+
+/// The contents of the file at `path`, `None` if there is none yet. `what` names the file in errors.
+pub(crate) fn read(path: &Path, what: &str) -> Result<Option<Vec<u8>>, KalshiError> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(KalshiError::InternalError(format!(
+            "Could not read {} {}: {}",
+            what,
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Replaces the file at `path` with `bytes`, writing them to a temporary file first.
+pub(crate) fn write_atomic(path: &Path, what: &str, bytes: &[u8]) -> Result<(), KalshiError> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, bytes)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| {
+            KalshiError::InternalError(format!(
+                "Could not write {} {}: {}",
+                what,
+                path.display(),
+                e
+            ))
+        })
+}
+
+/// Loads the JSON file at `path`, the default value if there is none yet.
+// serde_json comes with the websockets and history features, like the stores saving JSON
+#[cfg(any(feature = "websockets", feature = "history"))]
+pub(crate) fn load_json<T>(path: &Path, what: &str) -> Result<T, KalshiError>
+where
+    T: serde::de::DeserializeOwned + Default,
+{
+    match read(path, what)? {
+        Some(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+            KalshiError::InternalError(format!("Invalid {} {}: {}", what, path.display(), e))
+        }),
+        None => Ok(T::default()),
+    }
+}
+
+/// Saves `value` as JSON to the file at `path`, see [`write_atomic`].
+#[cfg(any(feature = "websockets", feature = "history"))]
+pub(crate) fn save_json<T>(path: &Path, what: &str, value: &T) -> Result<(), KalshiError>
+where
+    T: serde::Serialize + ?Sized,
+{
+    let bytes =
+        serde_json::to_vec_pretty(value).map_err(|e| KalshiError::InternalError(e.to_string()))?;
+    write_atomic(path, what, &bytes)
+}
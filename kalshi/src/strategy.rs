@@ -0,0 +1,456 @@
+use std::{collections::HashMap, time::Duration};
+
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    websockets::{
+        client::KalshiWebsocketClient,
+        responses::{KalshiFillMessage, KalshiTickerMessage, KalshiWebsocketResponse},
+    },
+    Action, Book, BookVerifier, BookVerifierStats, Kalshi, KalshiChannel, KalshiError, Order,
+    OrderCreationField, OrderType, PositionTracker, RiskManager, Side, Ticker, TrackedPosition,
+};
+
+/// Trading logic driven by a [`Runner`].
+///
+/// Every hook has an empty default, implement the ones the strategy needs. Hooks are synchronous:
+/// they read the market through the [`StrategyContext`] and queue orders and cancels on it, which
+/// the runner sends once the hook returns. The outcome of each order comes back through
+/// [`Strategy::on_order`].
+///
+/// ```
+/// struct FadeTheSpread;
+///
+/// impl Strategy for FadeTheSpread {
+///     fn on_orderbook(&mut self, ctx: &mut StrategyContext<'_>, book: &Book) {
+///         if let (Some((bid, _)), Some((ask, _))) = (book.best_yes_bid(), book.best_yes_ask()) {
+///             if ask - bid >= 4 && ctx.position(&book.market_ticker).position == 0 {
+///                 ctx.buy(&book.market_ticker, Side::Yes, 10, bid as i64 + 1);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub trait Strategy {
+    /// Called once the runner is subscribed, before any message.
+    fn on_start(&mut self, _ctx: &mut StrategyContext<'_>) {}
+
+    /// Called for every `ticker` message of a watched market.
+    fn on_tick(&mut self, _ctx: &mut StrategyContext<'_>, _tick: &KalshiTickerMessage) {}
+
+    /// Called whenever the book of a watched market changes.
+    fn on_orderbook(&mut self, _ctx: &mut StrategyContext<'_>, _book: &Book) {}
+
+    /// Called for every fill of the account, in any market.
+    fn on_fill(&mut self, _ctx: &mut StrategyContext<'_>, _fill: &KalshiFillMessage) {}
+
+    /// Called every timer interval, see [`Runner::timer`].
+    fn on_timer(&mut self, _ctx: &mut StrategyContext<'_>) {}
+
+    /// Called with the outcome of every order the strategy queued, including risk rejections.
+    fn on_order(&mut self, _ctx: &mut StrategyContext<'_>, _result: &Result<Order, KalshiError>) {}
+
+    /// Called when the runner stops, whether the strategy asked to or the websocket closed.
+    fn on_stop(&mut self, _ctx: &mut StrategyContext<'_>) {}
+}
+
+#[derive(Debug, Clone)]
+enum Command {
+    Place(OrderCreationField),
+    Cancel(String),
+}
+
+/// What a [`Strategy`] hook sees of the market, and where it queues its orders.
+#[derive(Debug)]
+pub struct StrategyContext<'a> {
+    books: &'a HashMap<Ticker, Book>,
+    positions: &'a PositionTracker,
+    commands: Vec<Command>,
+    stop: bool,
+}
+
+impl<'a> StrategyContext<'a> {
+    fn new(books: &'a HashMap<Ticker, Book>, positions: &'a PositionTracker) -> Self {
+        StrategyContext {
+            books,
+            positions,
+            commands: Vec::new(),
+            stop: false,
+        }
+    }
+
+    /// The current book of a watched market.
+    pub fn book(&self, ticker: &str) -> Option<&Book> {
+        self.books.get(ticker)
+    }
+
+    /// The position in a market, from the fills seen by the runner.
+    pub fn position(&self, ticker: &str) -> TrackedPosition {
+        self.positions.position(ticker)
+    }
+
+    /// Queues a limit buy of `count` contracts of `side` at `price` cents.
+    pub fn buy(&mut self, ticker: &str, side: Side, count: i32, price: i64) {
+        self.limit_order(ticker, Action::Buy, side, count, price);
+    }
+
+    /// Queues a limit sell of `count` contracts of `side` at `price` cents.
+    pub fn sell(&mut self, ticker: &str, side: Side, count: i32, price: i64) {
+        self.limit_order(ticker, Action::Sell, side, count, price);
+    }
+
+    fn limit_order(&mut self, ticker: &str, action: Action, side: Side, count: i32, price: i64) {
+        let (yes_price, no_price) = match side {
+            Side::Yes => (Some(price), None),
+            Side::No => (None, Some(price)),
+        };
+        self.place(OrderCreationField {
+            action,
+            client_order_id: None,
+            count,
+            side,
+            ticker: ticker.to_string(),
+            input_type: OrderType::Limit,
+            buy_max_cost: None,
+            expiration_ts: None,
+            no_price,
+            sell_position_floor: None,
+            yes_price,
+        });
+    }
+
+    /// Queues any order.
+    pub fn place(&mut self, order: OrderCreationField) {
+        self.commands.push(Command::Place(order));
+    }
+
+    /// Queues the cancel of an order.
+    pub fn cancel(&mut self, order_id: &str) {
+        self.commands.push(Command::Cancel(order_id.to_string()));
+    }
+
+    /// Stops the runner once the queued commands are sent.
+    pub fn stop(&mut self) {
+        self.stop = true;
+    }
+}
+
+/// Runs a [`Strategy`] against the websocket feed.
+///
+/// The runner subscribes to the `ticker` and `orderbook_delta` channels of the watched markets and
+/// to the account's fills, keeps the books and positions up to date, calls the strategy's hooks
+/// and sends the orders and cancels they queue. Orders go through
+/// [`Kalshi::create_order`], so the risk manager attached with [`Runner::risk_manager`] (or
+/// already attached to the `Kalshi` instance) checks every one of them.
+///
+/// ```
+/// let mut runner = Runner::new(kalshi_instance, FadeTheSpread)
+///     .markets(vec!["KXHIGHNY-25OCT02-B80.5".to_string()])
+///     .timer(Duration::from_secs(30))
+///     .risk_manager(RiskManager::new(limits));
+/// runner.run(&mut ws_client).await?;
+/// ```
+pub struct Runner<S: Strategy> {
+    kalshi: Kalshi,
+    strategy: S,
+    tickers: Vec<String>,
+    timer: Option<Duration>,
+    books: HashMap<Ticker, Book>,
+    verifier: Option<BookVerifier>,
+    positions: PositionTracker,
+}
+
+impl<S: Strategy> Runner<S> {
+    pub fn new(kalshi: Kalshi, strategy: S) -> Self {
+        Runner {
+            kalshi,
+            strategy,
+            tickers: Vec::new(),
+            timer: None,
+            books: HashMap::new(),
+            verifier: None,
+            positions: PositionTracker::new(),
+        }
+    }
+
+    /// Markets whose ticks and books are delivered to the strategy.
+    pub fn markets(mut self, tickers: Vec<String>) -> Self {
+        self.tickers = tickers;
+        self
+    }
+
+    /// Calls [`Strategy::on_timer`] every `interval`.
+    pub fn timer(mut self, interval: Duration) -> Self {
+        self.timer = Some(interval);
+        self
+    }
+
+    /// Cross-checks the books with a REST snapshot every `interval`, repairing the divergent ones
+    /// and delivering them to [`Strategy::on_orderbook`] again, see [`BookVerifier`].
+    pub fn verify_books(mut self, interval: Duration) -> Self {
+        self.verifier = Some(BookVerifier::new(interval));
+        self
+    }
+
+    /// Totals of the book checks, `None` unless [`Runner::verify_books`] was called.
+    pub fn book_verifier_stats(&self) -> Option<&BookVerifierStats> {
+        self.verifier.as_ref().map(|verifier| verifier.stats())
+    }
+
+    /// Checks every order of the strategy against `risk_manager`, which also sees the runner's positions.
+    pub fn risk_manager(mut self, mut risk_manager: RiskManager) -> Self {
+        risk_manager.set_position_tracker(self.positions.clone());
+        self.kalshi.set_risk_manager(risk_manager);
+        self
+    }
+
+    /// Starts positions from an existing tracker, for instance one seeded from the exchange.
+    ///
+    /// Call before [`Runner::risk_manager`] for the risk manager to share it.
+    pub fn positions(mut self, positions: PositionTracker) -> Self {
+        self.positions = positions;
+        self
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    pub fn into_strategy(self) -> S {
+        self.strategy
+    }
+
+    /// Runs the strategy until it stops itself or the websocket client shuts down.
+    pub async fn run(&mut self, ws_client: &mut KalshiWebsocketClient) -> Result<(), KalshiError> {
+        let mut receiver = ws_client.receiver();
+        let mut subscriptions = vec![(KalshiChannel::Fill, vec![])];
+        if !self.tickers.is_empty() {
+            subscriptions.push((KalshiChannel::OrderbookDelta, self.tickers.clone()));
+            subscriptions.push((KalshiChannel::Ticker, self.tickers.clone()));
+        }
+        for (channel, tickers) in subscriptions {
+            ws_client
+                .ensure_subscribed(vec![channel], tickers)
+                .await
+                .map_err(|e| KalshiError::InternalError(e.to_string()))?;
+        }
+
+        let mut timer = self.timer.map(|interval| {
+            let mut timer =
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            timer
+        });
+
+        let mut verify = self.verifier.as_ref().map(|verifier| {
+            let mut verify = tokio::time::interval(verifier.interval());
+            verify.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            verify
+        });
+
+        let mut stop = self.call(|strategy, ctx| strategy.on_start(ctx)).await;
+        while !stop {
+            let timer_tick = async {
+                match timer.as_mut() {
+                    Some(timer) => timer.tick().await,
+                    None => std::future::pending().await,
+                }
+            };
+            let verify_tick = async {
+                match verify.as_mut() {
+                    Some(verify) => verify.tick().await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = timer_tick => {
+                    stop = self.call(|strategy, ctx| strategy.on_timer(ctx)).await;
+                }
+                _ = verify_tick => {
+                    stop = self.verify_books_due().await;
+                }
+                msg = receiver.recv() => match msg {
+                    Ok(Ok(msg)) => {
+                        let (commands, stopped) = self.dispatch(&msg);
+                        stop = self.execute(commands).await || stopped;
+                    }
+                    Ok(Err(_)) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Strategy runner lagged, skipped {} messages", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+        self.call(|strategy, ctx| strategy.on_stop(ctx)).await;
+        Ok(())
+    }
+
+    /// Calls a hook and sends what it queued, returning whether the strategy asked to stop.
+    async fn call(&mut self, hook: impl FnOnce(&mut S, &mut StrategyContext<'_>)) -> bool {
+        let (commands, stop) = {
+            let mut ctx = StrategyContext::new(&self.books, &self.positions);
+            hook(&mut self.strategy, &mut ctx);
+            (ctx.commands, ctx.stop)
+        };
+        self.execute(commands).await || stop
+    }
+
+    /// Checks the books due for verification, returning whether the strategy asked to stop.
+    async fn verify_books_due(&mut self) -> bool {
+        let Some(verifier) = self.verifier.as_mut() else {
+            return false;
+        };
+        let divergences = verifier
+            .verify_due(&self.kalshi, self.books.values_mut())
+            .await;
+        // The runner's verifier repairs every divergent book
+        let mut stop = false;
+        for divergence in divergences {
+            let (commands, stopped) = self.dispatch_book(&divergence.market_ticker);
+            stop |= self.execute(commands).await || stopped;
+        }
+        stop
+    }
+
+    /// Updates the books and positions with a message and calls the matching hook.
+    fn dispatch(&mut self, msg: &KalshiWebsocketResponse) -> (Vec<Command>, bool) {
+        match msg {
+            KalshiWebsocketResponse::OrderbookSnapshot { msg, .. }
+                if self.watches(&msg.market_ticker) =>
+            {
+                self.books
+                    .insert(msg.market_ticker.clone(), Book::from(msg));
+                self.dispatch_book(&msg.market_ticker)
+            }
+            KalshiWebsocketResponse::OrderbookDelta { msg, .. }
+                if self.watches(&msg.market_ticker) =>
+            {
+                self.books
+                    .entry(msg.market_ticker.clone())
+                    .or_insert_with(|| Book::new(&msg.market_ticker))
+                    .apply_delta(msg);
+                self.dispatch_book(&msg.market_ticker)
+            }
+            KalshiWebsocketResponse::Ticker { msg, .. } if self.watches(&msg.market_ticker) => {
+                let mut ctx = StrategyContext::new(&self.books, &self.positions);
+                self.strategy.on_tick(&mut ctx, msg);
+                (ctx.commands, ctx.stop)
+            }
+            KalshiWebsocketResponse::Fill { msg, .. } => {
+                self.positions.on_fill(msg);
+                let mut ctx = StrategyContext::new(&self.books, &self.positions);
+                self.strategy.on_fill(&mut ctx, msg);
+                (ctx.commands, ctx.stop)
+            }
+            _ => (Vec::new(), false),
+        }
+    }
+
+    fn watches(&self, ticker: &str) -> bool {
+        self.tickers.iter().any(|watched| watched == ticker)
+    }
+
+    fn dispatch_book(&mut self, ticker: &str) -> (Vec<Command>, bool) {
+        let mut ctx = StrategyContext::new(&self.books, &self.positions);
+        if let Some(book) = self.books.get(ticker) {
+            self.strategy.on_orderbook(&mut ctx, book);
+        }
+        (ctx.commands, ctx.stop)
+    }
+
+    /// Sends queued commands, and those queued by `on_order` in turn, returning whether the strategy asked to stop.
+    async fn execute(&mut self, mut commands: Vec<Command>) -> bool {
+        let mut stop = false;
+        while !commands.is_empty() {
+            let mut next = Vec::new();
+            for command in commands {
+                match command {
+                    Command::Place(order) => {
+                        let result = self
+                            .kalshi
+                            .create_order(
+                                order.action,
+                                order.client_order_id,
+                                order.count,
+                                order.side,
+                                order.ticker,
+                                order.input_type,
+                                order.buy_max_cost,
+                                order.expiration_ts,
+                                order.no_price,
+                                order.sell_position_floor,
+                                order.yes_price,
+                            )
+                            .await;
+                        let mut ctx = StrategyContext::new(&self.books, &self.positions);
+                        self.strategy.on_order(&mut ctx, &result);
+                        next.extend(ctx.commands);
+                        stop |= ctx.stop;
+                    }
+                    Command::Cancel(order_id) => {
+                        if let Err(e) = self.kalshi.cancel_order(&order_id).await {
+                            log::warn!("Strategy could not cancel {}: {}", order_id, e);
+                        }
+                    }
+                }
+            }
+            commands = next;
+        }
+        stop
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Quoter {
+        books_seen: usize,
+    }
+
+    impl Strategy for Quoter {
+        fn on_orderbook(&mut self, ctx: &mut StrategyContext<'_>, book: &Book) {
+            self.books_seen += 1;
+            if let Some((bid, _)) = book.best_yes_bid() {
+                ctx.buy(&book.market_ticker, Side::Yes, 5, bid as i64);
+            }
+        }
+
+        fn on_fill(&mut self, ctx: &mut StrategyContext<'_>, fill: &KalshiFillMessage) {
+            if ctx.position(&fill.market_ticker).position >= 5 {
+                ctx.stop();
+            }
+        }
+    }
+
+    #[test]
+    fn test_dispatch_updates_state_and_calls_hooks() {
+        let kalshi = Kalshi::new(crate::TradingEnvironment::DemoMode);
+        let mut runner = Runner::new(kalshi, Quoter::default())
+            .markets(vec!["KXHIGHNY-25OCT02-B80.5".to_string()]);
+
+        let snapshot = KalshiWebsocketResponse::from_text(
+            r#"{"type":"orderbook_snapshot","sid":1,"seq":1,"msg":{"market_ticker":"KXHIGHNY-25OCT02-B80.5","yes":[[40,10]],"no":[[55,10]]}}"#,
+        )
+        .unwrap();
+        let (commands, stop) = runner.dispatch(&snapshot);
+        assert!(!stop);
+        assert!(matches!(&commands[..], [Command::Place(order)] if order.yes_price == Some(40)));
+
+        let other_market = KalshiWebsocketResponse::from_text(
+            r#"{"type":"orderbook_snapshot","sid":1,"seq":2,"msg":{"market_ticker":"KXHIGHNY-25OCT02-B82.5","yes":[[40,10]]}}"#,
+        )
+        .unwrap();
+        assert!(runner.dispatch(&other_market).0.is_empty());
+        assert_eq!(runner.strategy().books_seen, 1);
+
+        let fill = KalshiWebsocketResponse::from_text(
+            r#"{"type":"fill","sid":2,"msg":{"trade_id":"t","order_id":"o","market_ticker":"KXHIGHNY-25OCT02-B80.5","is_taker":false,"side":"yes","yes_price":40,"no_price":60,"count":5,"action":"buy","ts":0,"client_order_id":null,"post_position":5,"purchased_side":"yes"}}"#,
+        )
+        .unwrap();
+        let (_, stop) = runner.dispatch(&fill);
+        assert!(stop);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    io,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+use super::http::{read_request, write_response, API_PREFIX};
+use super::MockRequest;
+use crate::wire::redact_value;
+
+/// Request headers not forwarded upstream, they describe the connection to the recorder.
+const HOP_HEADERS: [&str; 4] = ["host", "content-length", "connection", "transfer-encoding"];
+
+/// A request sent to the API and the response it got, as stored in a [`Cassette`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    /// The path below `/trade-api/v2`, e.g. `/portfolio/balance`.
+    pub path: String,
+    #[serde(default)]
+    pub query: Vec<(String, String)>,
+    #[serde(default)]
+    pub request_body: Option<Value>,
+    pub status: u16,
+    pub response_body: Value,
+}
+
+impl Interaction {
+    /// Whether `request` is a replay of this interaction. Login credentials are redacted from the
+    /// recording so they match any value.
+    pub(super) fn matches(&self, request: &MockRequest) -> bool {
+        let mut body = request.body.clone();
+        if let Some(body) = &mut body {
+            redact_value(body);
+        }
+        self.method == request.method.as_str()
+            && self.path == request.path
+            && self.query == request.query
+            && self.request_body == body
+    }
+}
+
+/// Interactions with the REST API recorded by a [`CassetteRecorder`], replayed by
+/// [`MockHttpServer::replay`](super::MockHttpServer::replay).
+///
+/// Cassettes are JSON files meant to be committed next to the tests that replay them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        serde_json::from_slice(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let bytes = serde_json::to_vec_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, bytes)
+    }
+}
+
+/// A local proxy in front of the REST API recording every request and response to a
+/// [`Cassette`].
+///
+/// Point a [`Kalshi`](crate::Kalshi) instance at [`CassetteRecorder::url`] and use it as usual,
+/// requests are signed for the same paths so they're accepted upstream. Credentials, signatures and
+/// login tokens aren't recorded. Save the cassette once done and replay it in tests with
+/// [`MockHttpServer::replay`](super::MockHttpServer::replay), no API needed.
+///
+/// ```
+/// let upstream = Kalshi::new(TradingEnvironment::DemoMode).get_base_url().to_string();
+/// let recorder = CassetteRecorder::start(&upstream).await?;
+///
+/// let mut kalshi = Kalshi::new_with_api_key(TradingEnvironment::DemoMode, key_id, pem);
+/// kalshi.set_base_url(&recorder.url());
+/// kalshi.get_balance().await?;
+///
+/// recorder.cassette().save("tests/cassettes/balance.json")?;
+/// ```
+pub struct CassetteRecorder {
+    addr: SocketAddr,
+    cassette: Arc<Mutex<Cassette>>,
+    acceptor: JoinHandle<()>,
+}
+
+impl CassetteRecorder {
+    /// Starts recording on a random local port, forwarding to `upstream`, a base url including
+    /// the `/trade-api/v2` prefix.
+    pub async fn start(upstream: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let cassette = Arc::new(Mutex::new(Cassette::default()));
+        let upstream = upstream.trim_end_matches('/').to_string();
+        let client = reqwest::Client::new();
+
+        let acceptor_cassette = Arc::clone(&cassette);
+        let acceptor = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(forward(
+                    stream,
+                    client.clone(),
+                    upstream.clone(),
+                    Arc::clone(&acceptor_cassette),
+                ));
+            }
+        });
+
+        Ok(CassetteRecorder {
+            addr,
+            cassette,
+            acceptor,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cassette> {
+        self.cassette.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// The base url of the recorder, including the `/trade-api/v2` prefix.
+    pub fn url(&self) -> String {
+        format!("http://{}{}", self.addr, API_PREFIX)
+    }
+
+    /// The interactions recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        self.lock().clone()
+    }
+}
+
+impl Drop for CassetteRecorder {
+    fn drop(&mut self) {
+        self.acceptor.abort();
+    }
+}
+
+/// Forwards a single request upstream and records it, upstream failures answer 502 unrecorded.
+async fn forward(
+    mut stream: TcpStream,
+    client: reqwest::Client,
+    upstream: String,
+    cassette: Arc<Mutex<Cassette>>,
+) {
+    let Some((request, headers)) = read_request(&mut stream).await else {
+        return;
+    };
+    let mut upstream_request = client
+        .request(
+            request.method.clone(),
+            format!("{}{}", upstream, request.path),
+        )
+        .query(&request.query);
+    for (name, value) in &headers {
+        if !HOP_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            upstream_request = upstream_request.header(name, value);
+        }
+    }
+    if let Some(body) = &request.body {
+        upstream_request = upstream_request.json(body);
+    }
+
+    let response = match upstream_request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            let body = json!({ "error": { "code": "bad_gateway", "message": e.to_string() } });
+            write_response(&mut stream, 502, &body).await;
+            return;
+        }
+    };
+    let status = response.status().as_u16();
+    let body = match response.bytes().await {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned())),
+        Err(e) => {
+            let body = json!({ "error": { "code": "bad_gateway", "message": e.to_string() } });
+            write_response(&mut stream, 502, &body).await;
+            return;
+        }
+    };
+    let mut request_body = request.body;
+    if let Some(body) = &mut request_body {
+        redact_value(body);
+    }
+    let mut response_body = body.clone();
+    redact_value(&mut response_body);
+    cassette
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+        .interactions
+        .push(Interaction {
+            method: request.method.to_string(),
+            path: request.path,
+            query: request.query,
+            request_body,
+            status,
+            response_body,
+        });
+    // The caller gets the real response, credentials are only redacted from the recording
+    write_response(&mut stream, status, &body).await;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::MockHttpServer;
+    use crate::{Kalshi, TradingEnvironment};
+    use reqwest::Method;
+
+    #[tokio::test]
+    async fn test_recorded_cassette_replays() {
+        let upstream = MockHttpServer::with_fixtures().await.unwrap();
+        let recorder = CassetteRecorder::start(&upstream.url()).await.unwrap();
+        let mut kalshi = Kalshi::new(TradingEnvironment::DemoMode);
+        kalshi.set_base_url(&recorder.url());
+        kalshi.login("me@example.com", "secret").await.unwrap();
+        assert_eq!(kalshi.get_balance().await.unwrap(), 10_000);
+
+        let path = std::env::temp_dir().join(format!("kalshi-{}.json", uuid::Uuid::new_v4()));
+        recorder.cassette().save(&path).unwrap();
+        let cassette = Cassette::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(cassette.interactions.len(), 2);
+        assert_eq!(
+            cassette.interactions[0].request_body.as_ref().unwrap()["password"],
+            "<redacted>"
+        );
+
+        let replay = MockHttpServer::replay(cassette).await.unwrap();
+        let mut kalshi = Kalshi::new(TradingEnvironment::DemoMode);
+        kalshi.set_base_url(&replay.url());
+        kalshi.login("other@example.com", "other").await.unwrap();
+        assert_eq!(kalshi.get_balance().await.unwrap(), 10_000);
+        // Every interaction is replayed once
+        assert!(kalshi.get_balance().await.is_err());
+        assert_eq!(
+            replay.requests_to(Method::GET, "/portfolio/balance").len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Canned JSON responses shaped like the Kalshi REST API's.
+//!
+//! Every fixture deserializes into the matching type of this crate, [`MockHttpServer::with_fixtures`](super::MockHttpServer::with_fixtures)
+//! serves them at the matching paths. Tweak the returned values to script other scenarios.
+
+use serde_json::{json, Value};
+
+/// The ticker of the market served by [`market`].
+pub const MARKET_TICKER: &str = "KXWNBAGAME-25SEP17PHXNYL-NYL";
+/// The event of the market served by [`market`].
+pub const EVENT_TICKER: &str = "KXWNBAGAME-25SEP17PHXNYL";
+/// The id of the resting order served by [`order`].
+pub const ORDER_ID: &str = "ee44f3d2-6b8c-4b6a-9d3e-6f0c3c1f8a11";
+
+/// A market as listed by the exchange, recorded from the live API.
+pub fn market() -> Value {
+    let markets: Value = serde_json::from_str(include_str!("../../test_data/sample_markets.json"))
+        .expect("Sample markets are valid JSON");
+    markets[0].clone()
+}
+
+/// `GET /markets/{ticker}`
+pub fn single_market() -> Value {
+    json!({ "market": market() })
+}
+
+/// `GET /markets`, a single page holding [`market`].
+pub fn markets_page() -> Value {
+    json!({ "markets": [market()], "cursor": "" })
+}
+
+/// `GET /markets/{ticker}/orderbook`, bids as `[price, quantity]` pairs.
+pub fn orderbook() -> Value {
+    json!({
+        "orderbook": {
+            "yes": [[60, 120], [62, 45], [64, 10]],
+            "no": [[32, 80], [34, 25]],
+        }
+    })
+}
+
+/// `GET /exchange/status`
+pub fn exchange_status() -> Value {
+    json!({ "trading_active": true, "exchange_active": true })
+}
+
+/// `POST /login`
+pub fn login() -> Value {
+    json!({ "member_id": "mock-member", "token": "mock-token" })
+}
+
+/// `GET /portfolio/balance`, in cents.
+pub fn balance(cents: i64) -> Value {
+    json!({ "balance": cents })
+}
+
+/// An order resting on [`MARKET_TICKER`], buying `remaining_count` yes contracts at `yes_price`.
+pub fn order(order_id: &str, yes_price: i64, remaining_count: i32) -> Value {
+    json!({
+        "order_id": order_id,
+        "user_id": "mock-member",
+        "ticker": MARKET_TICKER,
+        "status": "resting",
+        "yes_price": yes_price,
+        "no_price": 100 - yes_price,
+        "created_time": "2025-09-17T18:04:11.512Z",
+        "taker_fill_count": 0,
+        "taker_fill_cost": 0,
+        "place_count": remaining_count,
+        "decrease_count": 0,
+        "maker_fill_count": 0,
+        "fcc_cancel_count": 0,
+        "close_cancel_count": 0,
+        "remaining_count": remaining_count,
+        "queue_position": 3,
+        "expiration_time": null,
+        "taker_fees": 0,
+        "action": "buy",
+        "side": "yes",
+        "type": "limit",
+        "last_update_time": null,
+        "client_order_id": "mock-client-order",
+        "order_group_id": "",
+    })
+}
+
+/// `POST /portfolio/orders` and `GET /portfolio/orders/{order_id}`
+pub fn single_order() -> Value {
+    json!({ "order": order(ORDER_ID, 64, 10) })
+}
+
+/// `GET /portfolio/orders`
+pub fn orders_page() -> Value {
+    json!({ "orders": [order(ORDER_ID, 64, 10)], "cursor": "" })
+}
+
+/// `DELETE /portfolio/orders/{order_id}`
+pub fn canceled_order() -> Value {
+    let mut order = order(ORDER_ID, 64, 0);
+    order["status"] = json!("canceled");
+    json!({ "order": order, "reduced_by": 10 })
+}
+
+/// `POST /portfolio/orders/{order_id}/amend`, the fixture order repriced to `yes_price`.
+pub fn amended_order(yes_price: i64, remaining_count: i32) -> Value {
+    let mut amended = order(ORDER_ID, yes_price, remaining_count);
+    amended["client_order_id"] = json!("mock-client-order-r1");
+    json!({ "old_order": order(ORDER_ID, 64, 10), "order": amended })
+}
+
+/// `POST /portfolio/orders/batched`, `count` orders created.
+pub fn batched_orders(count: usize) -> Value {
+    let orders: Vec<Value> = (0..count)
+        .map(|i| {
+            let order = order(&format!("batched-{}", i), 64, 10);
+            json!({ "client_order_id": order["client_order_id"], "order": order, "error": null })
+        })
+        .collect();
+    json!({ "orders": orders })
+}
+
+/// `GET /portfolio/fills`
+pub fn fills_page() -> Value {
+    json!({
+        "fills": [{
+            "action": "buy",
+            "count": 4,
+            "created_time": "2025-09-17T18:06:40.031Z",
+            "is_taker": false,
+            "no_price": 36,
+            "order_id": ORDER_ID,
+            "side": "yes",
+            "ticker": MARKET_TICKER,
+            "trade_id": "5a1f0c3e-92d4-4c1e-8c55-0d7b0f2e6c90",
+            "yes_price": 64,
+        }],
+        "cursor": "",
+    })
+}
+
+/// `GET /portfolio/positions`
+pub fn positions() -> Value {
+    json!({
+        "cursor": "",
+        "event_positions": [{
+            "event_exposure": 256,
+            "event_ticker": EVENT_TICKER,
+            "fees_paid": 4,
+            "realized_pnl": 0,
+            "resting_order_count": 1,
+            "total_cost": 256,
+        }],
+        "market_positions": [{
+            "fees_paid": 4,
+            "market_exposure": 256,
+            "position": 4,
+            "realized_pnl": 0,
+            "resting_orders_count": 1,
+            "ticker": MARKET_TICKER,
+            "total_traded": 256,
+        }],
+    })
+}
+
+/// `GET /portfolio/settlements`
+pub fn settlements_page() -> Value {
+    json!({
+        "cursor": "",
+        "settlements": [{
+            "market_result": "yes",
+            "no_count": 0,
+            "no_total_cost": 0,
+            "revenue": 400,
+            "settled_time": "2025-09-18T03:05:00Z",
+            "ticker": MARKET_TICKER,
+            "yes_count": 4,
+            "yes_total_cost": 256,
+        }],
+    })
+}
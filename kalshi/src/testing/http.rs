@@ -0,0 +1,462 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use reqwest::Method;
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+use super::{fixtures, Cassette, Interaction};
+use crate::{Kalshi, TradingEnvironment};
+
+/// The path prefix of every REST endpoint, stripped from recorded and routed paths.
+pub(super) const API_PREFIX: &str = "/trade-api/v2";
+
+/// A request received by a [`MockHttpServer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockRequest {
+    pub method: Method,
+    /// The path below `/trade-api/v2`, e.g. `/markets/KXHIGHNY-25OCT02-B80.5`.
+    pub path: String,
+    /// The query parameters, in the order they were sent.
+    pub query: Vec<(String, String)>,
+    /// The JSON body, if the request had one.
+    pub body: Option<Value>,
+}
+
+impl MockRequest {
+    /// The value of a query parameter.
+    pub fn query_param(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockResponse {
+    status: u16,
+    body: Value,
+}
+
+#[derive(Debug)]
+struct QueryRoute {
+    method: Method,
+    path: String,
+    query: Vec<(String, String)>,
+    response: MockResponse,
+}
+
+impl QueryRoute {
+    fn matches(&self, request: &MockRequest) -> bool {
+        self.method == request.method
+            && self.path == request.path
+            && self
+                .query
+                .iter()
+                .all(|(key, value)| request.query_param(key) == Some(value.as_str()))
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    routes: HashMap<(Method, String), MockResponse>,
+    /// Responses to requests with some query parameters, checked before `routes`
+    query_routes: Vec<QueryRoute>,
+    /// Recorded interactions still to replay, in the order they were recorded
+    replay: Vec<Interaction>,
+    requests: Vec<MockRequest>,
+}
+
+/// An in-process HTTP server that imitates the Kalshi REST API.
+///
+/// Responses are scripted per method and path with [`MockHttpServer::respond`], or per query
+/// with [`MockHttpServer::respond_to_query`], unscripted paths answer 404. [`MockHttpServer::with_fixtures`] starts a server already answering the
+/// common market, order and portfolio endpoints with the canned [`fixtures`], and
+/// [`MockHttpServer::replay`] one replaying a [`Cassette`] recorded against the real API. Every
+/// request is recorded for assertions.
+///
+/// ```
+/// let server = MockHttpServer::with_fixtures().await?;
+/// server.respond(Method::GET, "/portfolio/balance", 200, fixtures::balance(250));
+///
+/// let kalshi = server.kalshi().await?;
+/// assert_eq!(kalshi.get_balance().await?, 250);
+/// assert_eq!(server.requests_to(Method::GET, "/portfolio/balance").len(), 1);
+/// ```
+pub struct MockHttpServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    acceptor: JoinHandle<()>,
+}
+
+impl MockHttpServer {
+    /// Starts a server without any scripted response on a random local port.
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let acceptor_state = Arc::clone(&state);
+        let acceptor = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(stream, Arc::clone(&acceptor_state)));
+            }
+        });
+
+        Ok(MockHttpServer {
+            addr,
+            state,
+            acceptor,
+        })
+    }
+
+    /// Starts a server answering login, the exchange status, the [`fixtures::market`] and its
+    /// orderbook, and the order and portfolio endpoints with the canned [`fixtures`].
+    pub async fn with_fixtures() -> io::Result<Self> {
+        let server = Self::start().await?;
+        let market = format!("/markets/{}", fixtures::MARKET_TICKER);
+        let order = format!("/portfolio/orders/{}", fixtures::ORDER_ID);
+        server.respond(Method::POST, "/login", 200, fixtures::login());
+        server.respond(Method::POST, "/logout", 200, json!({}));
+        server.respond(
+            Method::GET,
+            "/exchange/status",
+            200,
+            fixtures::exchange_status(),
+        );
+        server.respond(Method::GET, "/markets", 200, fixtures::markets_page());
+        server.respond(Method::GET, &market, 200, fixtures::single_market());
+        server.respond(
+            Method::GET,
+            &format!("{}/orderbook", market),
+            200,
+            fixtures::orderbook(),
+        );
+        server.respond(
+            Method::GET,
+            "/portfolio/balance",
+            200,
+            fixtures::balance(10_000),
+        );
+        server.respond(
+            Method::GET,
+            "/portfolio/orders",
+            200,
+            fixtures::orders_page(),
+        );
+        server.respond(
+            Method::POST,
+            "/portfolio/orders",
+            201,
+            fixtures::single_order(),
+        );
+        server.respond(Method::GET, &order, 200, fixtures::single_order());
+        server.respond(Method::DELETE, &order, 200, fixtures::canceled_order());
+        server.respond(
+            Method::POST,
+            &format!("{}/amend", order),
+            200,
+            fixtures::amended_order(60, 10),
+        );
+        server.respond(
+            Method::POST,
+            "/portfolio/orders/batched",
+            201,
+            fixtures::batched_orders(20),
+        );
+        server.respond(Method::GET, "/portfolio/fills", 200, fixtures::fills_page());
+        server.respond(
+            Method::GET,
+            "/portfolio/positions",
+            200,
+            fixtures::positions(),
+        );
+        server.respond(
+            Method::GET,
+            "/portfolio/settlements",
+            200,
+            fixtures::settlements_page(),
+        );
+        Ok(server)
+    }
+
+    /// Starts a server replaying the interactions of a cassette, see [`CassetteRecorder`](super::CassetteRecorder).
+    ///
+    /// A request is answered with the first interaction not replayed yet with the same method,
+    /// path, query and body, so a cassette replays the same way every time. Requests without one
+    /// fall back to the responses scripted with [`MockHttpServer::respond`].
+    ///
+    /// ```
+    /// let server = MockHttpServer::replay(Cassette::load("tests/cassettes/balance.json")?).await?;
+    /// let kalshi = server.kalshi().await?;
+    /// assert_eq!(kalshi.get_balance().await?, 250);
+    /// ```
+    pub async fn replay(cassette: Cassette) -> io::Result<Self> {
+        let server = Self::start().await?;
+        server.lock().replay = cassette.interactions;
+        Ok(server)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// The base url of the server, including the `/trade-api/v2` prefix.
+    pub fn url(&self) -> String {
+        format!("http://{}{}", self.addr, API_PREFIX)
+    }
+
+    /// A [`Kalshi`] instance pointed at this server and logged in through its `/login` endpoint.
+    pub async fn kalshi(&self) -> Result<Kalshi, crate::KalshiError> {
+        let mut kalshi = Kalshi::new(TradingEnvironment::DemoMode);
+        kalshi.set_base_url(&self.url());
+        kalshi.login("mock@example.com", "mock-password").await?;
+        Ok(kalshi)
+    }
+
+    /// Answers requests to `path`, below `/trade-api/v2` and without query, replacing any
+    /// previous response for the same method and path.
+    pub fn respond(&self, method: Method, path: &str, status: u16, body: Value) {
+        self.lock()
+            .routes
+            .insert((method, path.to_string()), MockResponse { status, body });
+    }
+
+    /// Answers requests to `path` carrying every parameter of `query`, whatever their other
+    /// parameters, e.g. the later pages of a listing by their `cursor`. Takes precedence over
+    /// [`MockHttpServer::respond`] and replaces any previous response for the same query.
+    ///
+    /// ```
+    /// server.respond(Method::GET, "/portfolio/fills", 200, first_page);
+    /// server.respond_to_query(Method::GET, "/portfolio/fills", &[("cursor", "page-2")], 200, last_page);
+    /// ```
+    pub fn respond_to_query(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        status: u16,
+        body: Value,
+    ) {
+        let route = QueryRoute {
+            method,
+            path: path.to_string(),
+            query: query
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            response: MockResponse { status, body },
+        };
+        let mut state = self.lock();
+        state.query_routes.retain(|existing| {
+            (&existing.method, &existing.path, &existing.query)
+                != (&route.method, &route.path, &route.query)
+        });
+        state.query_routes.push(route);
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// The requests received for one method and path.
+    pub fn requests_to(&self, method: Method, path: &str) -> Vec<MockRequest> {
+        self.lock()
+            .requests
+            .iter()
+            .filter(|r| r.method == method && r.path == path)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Drop for MockHttpServer {
+    fn drop(&mut self) {
+        self.acceptor.abort();
+    }
+}
+
+/// Serves a single request, the connection is closed after the response.
+async fn serve_connection(mut stream: TcpStream, state: Arc<Mutex<MockState>>) {
+    let Some((request, _)) = read_request(&mut stream).await else {
+        return;
+    };
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|p| p.into_inner());
+        let replayed = state
+            .replay
+            .iter()
+            .position(|interaction| interaction.matches(&request))
+            .map(|index| state.replay.remove(index))
+            .map(|interaction| MockResponse {
+                status: interaction.status,
+                body: interaction.response_body,
+            });
+        let response = replayed
+            .or_else(|| {
+                state
+                    .query_routes
+                    .iter()
+                    .find(|route| route.matches(&request))
+                    .map(|route| route.response.clone())
+            })
+            .or_else(|| {
+                state
+                    .routes
+                    .get(&(request.method.clone(), request.path.clone()))
+                    .cloned()
+            });
+        state.requests.push(request.clone());
+        response.unwrap_or_else(|| {
+            let message = format!("No mock response for {} {}", request.method, request.path);
+            MockResponse {
+                status: 404,
+                body: json!({ "error": { "code": "not_found", "message": message } }),
+            }
+        })
+    };
+    write_response(&mut stream, response.status, &response.body).await;
+}
+
+/// Writes a JSON response and closes the connection.
+pub(super) async fn write_response(stream: &mut TcpStream, status: u16, body: &Value) {
+    let body = body.to_string();
+    let head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Reads a request and its headers.
+pub(super) async fn read_request(
+    stream: &mut TcpStream,
+) -> Option<(MockRequest, Vec<(String, String)>)> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = Method::from_bytes(request_line.next()?.as_bytes()).ok()?;
+    let target = url::Url::parse(&format!("http://mock{}", request_line.next()?)).ok()?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let mut body = buffer.split_off(head_end + 4);
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+
+    let path = target.path();
+    let request = MockRequest {
+        method,
+        path: path.strip_prefix(API_PREFIX).unwrap_or(path).to_string(),
+        query: target
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect(),
+        body: serde_json::from_slice(&body).ok(),
+    };
+    Some((request, headers))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Action, CancelOutcome, OrderType, Side};
+
+    #[tokio::test]
+    async fn test_fixtures_deserialize_through_the_client() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+
+        assert!(kalshi.get_exchange_status().await.unwrap().can_trade());
+        let ticker = fixtures::MARKET_TICKER.to_string();
+        assert_eq!(
+            kalshi.get_single_market(&ticker).await.unwrap().ticker,
+            ticker
+        );
+        let book = kalshi.get_market_orderbook(&ticker, Some(3)).await.unwrap();
+        assert_eq!(book.yes.unwrap().len(), 3);
+        assert_eq!(kalshi.get_balance().await.unwrap(), 10_000);
+        let (_, positions, _) = kalshi
+            .get_positions_page(None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(positions[0].position, 4);
+        let (_, fills) = kalshi
+            .get_multiple_fills(None, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(fills[0].order_id, fixtures::ORDER_ID);
+
+        let order = kalshi
+            .create_order(
+                Action::Buy,
+                None,
+                10,
+                Side::Yes,
+                ticker.clone(),
+                OrderType::Limit,
+                None,
+                None,
+                None,
+                None,
+                Some(64),
+            )
+            .await
+            .unwrap();
+        let canceled = kalshi.cancel_order(&order.order_id).await.unwrap();
+        assert_eq!(canceled.reduced_by, 10);
+        assert_eq!(canceled.outcome(), CancelOutcome::Canceled);
+
+        let placed = server.requests_to(Method::POST, "/portfolio/orders");
+        assert_eq!(placed[0].body.as_ref().unwrap()["yes_price"], 64);
+        let books = server.requests_to(Method::GET, &format!("/markets/{}/orderbook", ticker));
+        assert_eq!(books[0].query_param("depth"), Some("3"));
+    }
+
+    #[tokio::test]
+    async fn test_unscripted_paths_fail() {
+        let server = MockHttpServer::start().await.unwrap();
+        let mut kalshi = Kalshi::new(TradingEnvironment::DemoMode);
+        kalshi.set_base_url(&server.url());
+        assert!(kalshi.get_exchange_status().await.is_err());
+        assert_eq!(server.requests()[0].path, "/exchange/status");
+    }
+}
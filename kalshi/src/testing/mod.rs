@@ -0,0 +1,25 @@
+//! Utilities for testing bots built on this crate without talking to Kalshi.
+//!
+//! Enabled with the `testing` feature.
+
+mod cassette;
+pub mod fixtures;
+mod http;
+#[cfg(feature = "websockets")]
+mod ws;
+
+pub use cassette::{Cassette, CassetteRecorder, Interaction};
+pub use http::{MockHttpServer, MockRequest};
+#[cfg(feature = "websockets")]
+pub use ws::{MockCommand, MockWsServer};
+
+/// Generates a fresh PEM encoded RSA private key, accepted by [`Kalshi::new_with_api_key`](crate::Kalshi::new_with_api_key).
+///
+/// Mock servers don't verify signatures so any key will do.
+pub fn throwaway_private_key() -> String {
+    let rsa = openssl::rsa::Rsa::generate(2048).expect("Unable to generate RSA key");
+    let pem = rsa
+        .private_key_to_pem()
+        .expect("Unable to encode RSA key as pem");
+    String::from_utf8(pem).expect("PEM encoding is always valid utf-8")
+}
@@ -0,0 +1,632 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{broadcast, Notify},
+    task::JoinHandle,
+};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+use crate::{websockets::responses::KalshiWebsocketResponse, Kalshi, TradingEnvironment};
+
+/// A command received by a [`MockWsServer`] from a connected client.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct MockCommand {
+    /// The command name, e.g. `subscribe` or `unsubscribe`.
+    pub cmd: String,
+    /// The command id, absent for commands that don't carry one.
+    #[serde(default)]
+    pub id: Option<u32>,
+    /// The command parameters as sent on the wire.
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl MockCommand {
+    fn string_list(&self, key: &str) -> Vec<String> {
+        self.params[key]
+            .as_array()
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The channels of a `subscribe` command.
+    pub fn channels(&self) -> Vec<String> {
+        self.string_list("channels")
+    }
+
+    /// The market tickers of a `subscribe` or `update_subscription` command.
+    pub fn market_tickers(&self) -> Vec<String> {
+        self.string_list("market_tickers")
+    }
+
+    /// The sids of an `unsubscribe` or `update_subscription` command.
+    pub fn sids(&self) -> Vec<u32> {
+        self.params["sids"]
+            .as_array()
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| v.as_u64().map(|sid| sid as u32))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Outgoing {
+    Text(String),
+    Close,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    commands: Vec<MockCommand>,
+    /// Frames sent while no client was connected, delivered to the next connection
+    queued: Vec<String>,
+    connections: usize,
+    active_connections: usize,
+    next_sid: u32,
+    auto_ack: bool,
+    /// Markets of every auto acknowledged subscription, keyed by sid
+    market_tickers: HashMap<u32, Vec<String>>,
+}
+
+struct Shared {
+    state: Mutex<MockState>,
+    command_received: Notify,
+    outgoing: broadcast::Sender<Outgoing>,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+/// An in-process websocket server that imitates the Kalshi websocket API.
+///
+/// Clients connect with the [`Kalshi`] instance returned by [`MockWsServer::kalshi`].
+/// Subscribe, unsubscribe and update commands are acknowledged automatically (disable with
+/// [`MockWsServer::set_auto_ack`]), every command is recorded for assertions, and scripted
+/// [`KalshiWebsocketResponse`]s can be pushed to connected clients at any time.
+/// [`MockWsServer::disconnect_all`] drops every connection so reconnection logic can be tested.
+///
+/// ```
+/// let server = MockWsServer::start().await?;
+/// let kalshi = server.kalshi();
+/// let mut ws = kalshi.connect_ws().await?;
+/// ws.subscribe(vec![KalshiChannel::Ticker], vec!["HIGHNY-23NOV13-T51".into()]).await?;
+///
+/// let cmds = server.wait_for_commands(1, Duration::from_secs(1)).await;
+/// assert_eq!(cmds[0].channels(), vec!["ticker"]);
+/// server.send(&ticker_message);
+/// ```
+pub struct MockWsServer {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    acceptor: JoinHandle<()>,
+}
+
+impl MockWsServer {
+    /// Starts the server on a random local port.
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let (outgoing, _) = broadcast::channel(1024);
+        let shared = Arc::new(Shared {
+            state: Mutex::new(MockState {
+                next_sid: 1,
+                auto_ack: true,
+                ..Default::default()
+            }),
+            command_received: Notify::new(),
+            outgoing,
+        });
+
+        let acceptor_shared = Arc::clone(&shared);
+        let acceptor = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(stream, Arc::clone(&acceptor_shared)));
+            }
+        });
+
+        Ok(MockWsServer {
+            addr,
+            shared,
+            acceptor,
+        })
+    }
+
+    /// The `ws://` url of the server.
+    pub fn url(&self) -> String {
+        format!("ws://{}/trade-api/ws/v2", self.addr)
+    }
+
+    /// A [`Kalshi`] instance pointed at this server, authenticated with a throwaway api key.
+    pub fn kalshi(&self) -> Kalshi {
+        let mut kalshi = Kalshi::new_with_api_key(
+            TradingEnvironment::DemoMode,
+            "mock-key-id".to_string(),
+            super::throwaway_private_key(),
+        );
+        kalshi.set_ws_url(&self.url());
+        kalshi
+    }
+
+    /// Enables or disables automatic acknowledgement of subscription commands (on by default).
+    pub fn set_auto_ack(&self, auto_ack: bool) {
+        self.shared.lock().auto_ack = auto_ack;
+    }
+
+    /// Sends a message to every connected client, or to the next client if none is connected.
+    pub fn send(&self, response: &KalshiWebsocketResponse) {
+        let text = response
+            .to_text()
+            .expect("Mock websocket responses are always serializable");
+        self.send_raw(text);
+    }
+
+    /// Sends each message in order, see [`MockWsServer::send`].
+    pub fn send_all<'a>(&self, responses: impl IntoIterator<Item = &'a KalshiWebsocketResponse>) {
+        for response in responses {
+            self.send(response);
+        }
+    }
+
+    /// Sends a raw text frame, useful for malformed or not yet modelled messages.
+    pub fn send_raw(&self, text: impl Into<String>) {
+        let text = text.into();
+        let mut state = self.shared.lock();
+        if state.active_connections == 0 {
+            state.queued.push(text);
+        } else {
+            let _ = self.shared.outgoing.send(Outgoing::Text(text));
+        }
+    }
+
+    /// Closes every open connection, clients are free to reconnect.
+    pub fn disconnect_all(&self) {
+        let _ = self.shared.outgoing.send(Outgoing::Close);
+    }
+
+    /// Total number of connections accepted so far.
+    pub fn connection_count(&self) -> usize {
+        self.shared.lock().connections
+    }
+
+    /// Every command received so far, in order.
+    pub fn commands(&self) -> Vec<MockCommand> {
+        self.shared.lock().commands.clone()
+    }
+
+    /// Waits until at least `count` commands were received or `timeout` elapses,
+    /// returning all commands received so far.
+    pub async fn wait_for_commands(&self, count: usize, timeout: Duration) -> Vec<MockCommand> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let notified = self.shared.command_received.notified();
+            let commands = self.commands();
+            if commands.len() >= count {
+                return commands;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return self.commands();
+            }
+        }
+    }
+
+    /// Panics unless a `subscribe` command for exactly these channels and markets was received.
+    pub fn assert_subscribed(&self, channels: &[&str], market_tickers: &[&str]) {
+        let commands = self.commands();
+        let found = commands.iter().any(|c| {
+            c.cmd == "subscribe" && c.channels() == channels && c.market_tickers() == market_tickers
+        });
+        assert!(
+            found,
+            "No subscribe command for channels {:?} and markets {:?}, received: {:?}",
+            channels, market_tickers, commands
+        );
+    }
+}
+
+impl Drop for MockWsServer {
+    fn drop(&mut self) {
+        self.acceptor.abort();
+        let _ = self.shared.outgoing.send(Outgoing::Close);
+    }
+}
+
+async fn serve_connection(stream: TcpStream, shared: Arc<Shared>) {
+    let Ok(ws) = accept_async(stream).await else {
+        return;
+    };
+    let (mut sink, mut source) = ws.split();
+    let mut outgoing = shared.outgoing.subscribe();
+
+    let queued = {
+        let mut state = shared.lock();
+        state.connections += 1;
+        state.active_connections += 1;
+        std::mem::take(&mut state.queued)
+    };
+    for text in queued {
+        if sink.send(Message::text(text)).await.is_err() {
+            break;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            frame = source.next() => {
+                let text = match frame {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let Ok(cmd) = serde_json::from_str::<MockCommand>(&text) else {
+                    continue;
+                };
+                let acks = {
+                    let mut state = shared.lock();
+                    state.commands.push(cmd.clone());
+                    if state.auto_ack { acknowledge(&mut state, &cmd) } else { Vec::new() }
+                };
+                shared.command_received.notify_waiters();
+                for ack in acks {
+                    if sink.send(Message::text(ack.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            out = outgoing.recv() => {
+                match out {
+                    Ok(Outgoing::Text(text)) => {
+                        if sink.send(Message::text(text)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(Outgoing::Close) => {
+                        let _ = sink.send(Message::Close(None)).await;
+                        break;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    shared.lock().active_connections -= 1;
+}
+
+/// Builds the acknowledgements Kalshi sends for a command.
+fn acknowledge(state: &mut MockState, cmd: &MockCommand) -> Vec<Value> {
+    match cmd.cmd.as_str() {
+        "subscribe" => cmd
+            .channels()
+            .into_iter()
+            .map(|channel| {
+                let sid = state.next_sid;
+                state.next_sid += 1;
+                state.market_tickers.insert(sid, cmd.market_tickers());
+                json!({"type": "subscribed", "id": cmd.id, "msg": {"channel": channel, "sid": sid}})
+            })
+            .collect(),
+        "unsubscribe" => cmd
+            .sids()
+            .into_iter()
+            .map(|sid| json!({"type": "unsubscribed", "id": cmd.id, "sid": sid}))
+            .collect(),
+        "update_subscription" => cmd
+            .sids()
+            .into_iter()
+            .map(|sid| {
+                let tickers = state.market_tickers.entry(sid).or_default();
+                if cmd.params["action"] == "delete_markets" {
+                    tickers.retain(|t| !cmd.market_tickers().contains(t));
+                } else {
+                    tickers.extend(cmd.market_tickers());
+                }
+                json!({"type": "ok", "id": cmd.id, "sid": sid, "seq": 0, "market_tickers": tickers})
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::websockets::{
+        client::{KalshiWebsocketConfig, KalshiWebsocketError},
+        KalshiChannel,
+    };
+
+    const TRADE: &str = r#"{"type":"trade","sid":1,"seq":16,"msg":{"trade_id":"5b0276ef-7715-46f2-56d8-a1c7b9e59e58","market_ticker":"KXHIGHCHI-25OCT02-B80.5","yes_price":27,"no_price":73,"count":7,"taker_side":"yes","ts":1759350609}}"#;
+
+    #[tokio::test]
+    async fn test_subscribe_ack_and_scripted_messages() {
+        let server = MockWsServer::start().await.unwrap();
+        let kalshi = server.kalshi();
+        let mut ws = kalshi.connect_ws().await.unwrap();
+        let mut stream = Box::pin(ws.stream());
+
+        ws.subscribe(
+            vec![KalshiChannel::Trade],
+            vec!["KXHIGHCHI-25OCT02-B80.5".to_string()],
+        )
+        .await
+        .unwrap();
+        let cmds = server.wait_for_commands(1, Duration::from_secs(5)).await;
+        assert_eq!(cmds.len(), 1);
+        server.assert_subscribed(&["trade"], &["KXHIGHCHI-25OCT02-B80.5"]);
+
+        let ack = stream.next().await.unwrap().unwrap();
+        assert!(matches!(ack, KalshiWebsocketResponse::Subscribed { .. }));
+        assert_eq!(ws.stats().subscriptions.len(), 1);
+
+        server.send(&KalshiWebsocketResponse::from_text(TRADE).unwrap());
+        let trade = stream.next().await.unwrap().unwrap();
+        assert!(matches!(
+            trade,
+            KalshiWebsocketResponse::Trade { sid: 1, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_shared_receivers_get_the_same_message() {
+        let server = MockWsServer::start().await.unwrap();
+        let kalshi = server.kalshi();
+        let ws = kalshi.connect_ws().await.unwrap();
+        let mut a = ws.shared_receiver();
+        let mut b = Box::pin(ws.shared_stream());
+        let mut owned = ws.receiver();
+
+        server.send(&KalshiWebsocketResponse::from_text(TRADE).unwrap());
+        let from_a = a.recv().await.unwrap();
+        let from_b = b.next().await.unwrap();
+        assert!(Arc::ptr_eq(&from_a, &from_b));
+        assert!(matches!(
+            from_a.as_ref(),
+            Ok(KalshiWebsocketResponse::Trade { sid: 1, .. })
+        ));
+        assert!(matches!(
+            owned.recv().await.unwrap(),
+            Ok(KalshiWebsocketResponse::Trade { sid: 1, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_subscribed_is_idempotent() {
+        let server = MockWsServer::start().await.unwrap();
+        let kalshi = server.kalshi();
+        let mut ws = kalshi.connect_ws().await.unwrap();
+        let mut stream = Box::pin(ws.stream());
+        let a = "KXHIGHNY-25OCT02-B80.5".to_string();
+        let b = "KXHIGHNY-25OCT02-B82.5".to_string();
+
+        let sent = ws
+            .ensure_subscribed(vec![KalshiChannel::Ticker], vec![a.clone()])
+            .await
+            .unwrap();
+        assert_eq!(sent.len(), 1);
+        // Not acknowledged yet but already pending
+        let sent = ws
+            .ensure_subscribed(vec![KalshiChannel::Ticker], vec![a.clone()])
+            .await
+            .unwrap();
+        assert!(sent.is_empty());
+
+        stream.next().await.unwrap().unwrap();
+        assert!(ws.is_subscribed(&KalshiChannel::Ticker, &a));
+        assert!(!ws.is_subscribed(&KalshiChannel::Ticker, &b));
+
+        ws.ensure_subscribed(vec![KalshiChannel::Ticker], vec![a.clone(), b.clone()])
+            .await
+            .unwrap();
+        let cmds = server.wait_for_commands(2, Duration::from_secs(5)).await;
+        assert_eq!(cmds.len(), 2);
+        assert_eq!(cmds[1].cmd, "update_subscription");
+        assert_eq!(cmds[1].market_tickers(), vec![b.clone()]);
+
+        stream.next().await.unwrap().unwrap();
+        assert!(ws.is_subscribed(&KalshiChannel::Ticker, &b));
+        assert_eq!(ws.active_subscriptions()[0].market_tickers, vec![a, b]);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_last_channel_stream_unsubscribes() {
+        let server = MockWsServer::start().await.unwrap();
+        let kalshi = server.kalshi();
+        let mut ws = kalshi.connect_ws().await.unwrap();
+        let a = "KXHIGHCHI-25OCT02-B80.5".to_string();
+        let b = "KXHIGHCHI-25OCT02-B82.5".to_string();
+
+        let mut first = ws
+            .channel_stream(KalshiChannel::Trade, vec![a.clone(), b.clone()])
+            .await
+            .unwrap();
+        let second = ws
+            .channel_stream(KalshiChannel::Trade, vec![b, a])
+            .await
+            .unwrap();
+        server.send(&KalshiWebsocketResponse::from_text(TRADE).unwrap());
+        let trade = first.recv().await.unwrap();
+        assert!(matches!(trade, KalshiWebsocketResponse::Trade { .. }));
+        assert_eq!(server.commands().len(), 1);
+
+        drop(first);
+        drop(second);
+        let cmds = server.wait_for_commands(2, Duration::from_secs(5)).await;
+        assert_eq!(cmds[1].cmd, "unsubscribe");
+        assert_eq!(cmds[1].sids(), vec![1]);
+
+        // Released before the acknowledgement
+        server.set_auto_ack(false);
+        let ticker = ws
+            .channel_stream(KalshiChannel::Ticker, vec![])
+            .await
+            .unwrap();
+        let cmds = server.wait_for_commands(3, Duration::from_secs(5)).await;
+        drop(ticker);
+        server.send_raw(
+            json!({"type": "subscribed", "id": cmds[2].id, "msg": {"channel": "ticker", "sid": 7}})
+                .to_string(),
+        );
+        let cmds = server.wait_for_commands(4, Duration::from_secs(5)).await;
+        assert_eq!(cmds[3].cmd, "unsubscribe");
+        assert_eq!(cmds[3].sids(), vec![7]);
+    }
+
+    #[tokio::test]
+    async fn test_drop_shuts_down_connection() {
+        let server = MockWsServer::start().await.unwrap();
+        let kalshi = server.kalshi();
+        let ws = kalshi.connect_ws().await.unwrap();
+        let shutdown = ws.shutdown_handle();
+        assert!(!shutdown.is_closed());
+
+        drop(ws);
+        tokio::time::timeout(Duration::from_secs(5), shutdown.closed())
+            .await
+            .unwrap();
+        assert!(shutdown.is_closed());
+        // Shutting down an already closed client is a no-op
+        shutdown.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_kalshi_shutdown_closes_connection() {
+        let server = MockWsServer::start().await.unwrap();
+        let kalshi = server.kalshi();
+        let ws = kalshi.connect_ws().await.unwrap();
+        let closed = ws.shutdown_handle();
+
+        let report = tokio::time::timeout(Duration::from_secs(5), kalshi.shutdown())
+            .await
+            .unwrap();
+        assert_eq!(report.joined_tasks, vec!["websocket"]);
+        assert!(report.is_clean());
+        assert!(closed.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_closed_connection_ends_client_by_default() {
+        let server = MockWsServer::start().await.unwrap();
+        let mut ws = server.kalshi().connect_ws().await.unwrap();
+        let mut stream = Box::pin(ws.stream());
+        ws.subscribe(vec![KalshiChannel::Fill], vec![])
+            .await
+            .unwrap();
+        stream.next().await.unwrap().unwrap();
+
+        server.disconnect_all();
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(KalshiWebsocketError::ConnectionClosed))
+        ));
+        tokio::time::timeout(Duration::from_secs(5), ws.shutdown_handle().closed())
+            .await
+            .unwrap();
+        assert_eq!(server.connection_count(), 1);
+        assert_eq!(ws.stats().reconnect_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_resubscribes() {
+        let server = MockWsServer::start().await.unwrap();
+        let mut kalshi = server.kalshi();
+        kalshi.set_ws_config(KalshiWebsocketConfig {
+            reconnect: Some(Default::default()),
+            ..Default::default()
+        });
+        let mut ws = kalshi.connect_ws().await.unwrap();
+
+        ws.subscribe(vec![KalshiChannel::Fill], vec![])
+            .await
+            .unwrap();
+        server.wait_for_commands(1, Duration::from_secs(5)).await;
+
+        server.disconnect_all();
+        let cmds = server.wait_for_commands(2, Duration::from_secs(10)).await;
+        assert_eq!(cmds.len(), 2);
+        assert_eq!(cmds[1].cmd, "subscribe");
+        assert_eq!(cmds[1].channels(), vec!["fill"]);
+        assert_eq!(server.connection_count(), 2);
+        assert_eq!(ws.stats().reconnect_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_backfills_missed_trades() {
+        use crate::testing::MockHttpServer;
+        use reqwest::Method;
+
+        let server = MockWsServer::start().await.unwrap();
+        let http = MockHttpServer::with_fixtures().await.unwrap();
+        let mut kalshi = server.kalshi();
+        kalshi.set_base_url(&http.url());
+        kalshi.set_ws_config(KalshiWebsocketConfig {
+            reconnect: Some(Default::default()),
+            ..Default::default()
+        });
+        let missed = json!({"trade_id": "missed", "taker_side": "no", "ticker": "KXHIGHCHI-25OCT02-B80.5", "count": 3, "yes_price": 30, "no_price": 70, "created_time": "2025-10-01T20:31:00Z"});
+        let received = json!({"trade_id": "5b0276ef-7715-46f2-56d8-a1c7b9e59e58", "taker_side": "yes", "ticker": "KXHIGHCHI-25OCT02-B80.5", "count": 7, "yes_price": 27, "no_price": 73, "created_time": "2025-10-01T20:30:09Z"});
+        http.respond(
+            Method::GET,
+            "/markets/trades",
+            200,
+            json!({"trades": [missed, received], "cursor": ""}),
+        );
+
+        let mut ws = kalshi.connect_ws().await.unwrap();
+        let mut stream = Box::pin(ws.stream());
+        ws.subscribe(
+            vec![KalshiChannel::Trade],
+            vec!["KXHIGHCHI-25OCT02-B80.5".to_string()],
+        )
+        .await
+        .unwrap();
+        stream.next().await.unwrap().unwrap();
+        server.send(&KalshiWebsocketResponse::from_text(TRADE).unwrap());
+        stream.next().await.unwrap().unwrap();
+
+        server.disconnect_all();
+        let backfilled = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Some(Ok(KalshiWebsocketResponse::TradeBackfill { msg })) =
+                    stream.next().await
+                {
+                    return msg;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(backfilled.trade_id, "missed");
+        assert_eq!(backfilled.count, 3);
+
+        let requests = http.requests_to(Method::GET, "/markets/trades");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].query_param("min_ts"), Some("1759350609"));
+        assert_eq!(
+            requests[0].query_param("ticker"),
+            Some("KXHIGHCHI-25OCT02-B80.5")
+        );
+    }
+}
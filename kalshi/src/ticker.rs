@@ -0,0 +1,197 @@
+use std::{
+    borrow::Borrow,
+    collections::HashSet,
+    fmt,
+    ops::Deref,
+    sync::{Arc, OnceLock, RwLock},
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Every ticker interned so far. Tickers are never evicted, markets are few compared to the
+/// messages mentioning them.
+fn interned() -> &'static RwLock<HashSet<Arc<str>>> {
+    static INTERNED: OnceLock<RwLock<HashSet<Arc<str>>>> = OnceLock::new();
+    INTERNED.get_or_init(Default::default)
+}
+
+/// An interned market ticker, cheap to clone and compare.
+///
+/// Every `Ticker` of the same market shares one allocation: parsing a message of a market seen
+/// before allocates nothing for its ticker, and cloning one to key a map is a reference count
+/// increment. It derefs to `str` and compares with strings, so most code can treat it as one.
+///
+/// ```
+/// let ticker = Ticker::new("KXHIGHNY-25OCT02-B80.5");
+/// assert_eq!(ticker, "KXHIGHNY-25OCT02-B80.5");
+/// assert!(ticker.starts_with("KXHIGHNY"));
+///
+/// let mut volumes: HashMap<Ticker, u64> = HashMap::new();
+/// *volumes.entry(ticker).or_default() += 10;
+/// assert_eq!(volumes["KXHIGHNY-25OCT02-B80.5"], 10);
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ticker(Arc<str>);
+
+impl Ticker {
+    /// The interned ticker equal to `ticker`, allocated the first time it's seen.
+    pub fn new(ticker: &str) -> Self {
+        if let Some(existing) = interned()
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(ticker)
+        {
+            return Ticker(Arc::clone(existing));
+        }
+        let mut interned = interned().write().unwrap_or_else(|p| p.into_inner());
+        // Another thread may have interned it in between
+        match interned.get(ticker) {
+            Some(existing) => Ticker(Arc::clone(existing)),
+            None => {
+                let new: Arc<str> = Arc::from(ticker);
+                interned.insert(Arc::clone(&new));
+                Ticker(new)
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Ticker {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Ticker {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Ticker {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Ticker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Ticker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Ticker {
+    fn from(ticker: &str) -> Self {
+        Ticker::new(ticker)
+    }
+}
+
+impl From<String> for Ticker {
+    fn from(ticker: String) -> Self {
+        Ticker::new(&ticker)
+    }
+}
+
+impl From<&String> for Ticker {
+    fn from(ticker: &String) -> Self {
+        Ticker::new(ticker)
+    }
+}
+
+impl From<Ticker> for String {
+    fn from(ticker: Ticker) -> Self {
+        ticker.0.to_string()
+    }
+}
+
+impl PartialEq<str> for Ticker {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Ticker {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Ticker {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl PartialEq<Ticker> for String {
+    fn eq(&self, other: &Ticker) -> bool {
+        **self == *other.0
+    }
+}
+
+impl PartialEq<Ticker> for &str {
+    fn eq(&self, other: &Ticker) -> bool {
+        **self == *other.0
+    }
+}
+
+impl Serialize for Ticker {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ticker {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TickerVisitor;
+
+        impl de::Visitor<'_> for TickerVisitor {
+            type Value = Ticker;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a market ticker")
+            }
+
+            // Borrowed or not, the string is only looked up, no String is built
+            fn visit_str<E: de::Error>(self, ticker: &str) -> Result<Ticker, E> {
+                Ok(Ticker::new(ticker))
+            }
+        }
+
+        deserializer.deserialize_str(TickerVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tickers_share_one_allocation() {
+        let parsed: Vec<Ticker> =
+            serde_json::from_str(r#"["KXTEST-INTERN-A", "KXTEST-INTERN-A", "KXTEST-INTERN-B"]"#)
+                .unwrap();
+        assert!(Arc::ptr_eq(&parsed[0].0, &parsed[1].0));
+        assert!(Arc::ptr_eq(&parsed[0].0, &Ticker::new("KXTEST-INTERN-A").0));
+        assert_ne!(parsed[0], parsed[2]);
+        assert_eq!(parsed[0], "KXTEST-INTERN-A");
+        assert_eq!("KXTEST-INTERN-B".to_string(), parsed[2]);
+        assert_eq!(
+            serde_json::to_string(&parsed[2]).unwrap(),
+            r#""KXTEST-INTERN-B""#
+        );
+
+        let set: HashSet<Ticker> = parsed.into_iter().collect();
+        assert!(set.contains("KXTEST-INTERN-B"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Timing of the phases of REST requests, reported to a hook set with [`set_request_timing_hook`].
+
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use reqwest::{Method, Url};
+
+type TimingHook = Arc<dyn Fn(&RequestTiming) + Send + Sync>;
+
+static HOOK: RwLock<Option<TimingHook>> = RwLock::new(None);
+
+/// How long each phase of a REST request took.
+///
+/// The connection phases aren't exposed by the HTTP client: for requests opening a new
+/// connection, DNS resolution, connecting and the TLS handshake are part of `ttfb`. Comparing
+/// `ttfb` between requests on fresh and reused connections tells them apart from server time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestTiming {
+    pub method: Method,
+    pub url: Url,
+    pub status: u16,
+    /// From sending the request to receiving the response headers.
+    pub ttfb: Duration,
+    /// Downloading the response body.
+    pub body: Duration,
+    /// Parsing the body, `None` when the response wasn't parsed as JSON.
+    pub deserialize: Option<Duration>,
+}
+
+impl RequestTiming {
+    /// Time spent on the network, waiting for and downloading the response.
+    pub fn network(&self) -> Duration {
+        self.ttfb + self.body
+    }
+
+    /// The whole request, parsing included.
+    pub fn total(&self) -> Duration {
+        self.network() + self.deserialize.unwrap_or_default()
+    }
+}
+
+/// Calls `hook` with the timing of every REST request sent from now on, by any client.
+///
+/// While a hook is set, responses are downloaded whole before being parsed, including the ones
+/// otherwise streamed, to tell the download from the parsing. The hook runs on the task that sent
+/// the request and should return quickly, e.g. by recording a metric.
+///
+/// ```
+/// kalshi::set_request_timing_hook(|timing| {
+///     metrics::histogram!("kalshi_ttfb", timing.ttfb.as_secs_f64(), "path" => timing.url.path().to_string());
+///     if let Some(deserialize) = timing.deserialize {
+///         metrics::histogram!("kalshi_deserialize", deserialize.as_secs_f64());
+///     }
+/// });
+/// ```
+pub fn set_request_timing_hook(hook: impl Fn(&RequestTiming) + Send + Sync + 'static) {
+    *HOOK.write().unwrap_or_else(|p| p.into_inner()) = Some(Arc::new(hook));
+}
+
+/// Stops reporting request timings, see [`set_request_timing_hook`].
+pub fn clear_request_timing_hook() {
+    *HOOK.write().unwrap_or_else(|p| p.into_inner()) = None;
+}
+
+pub(crate) fn timing_hook_set() -> bool {
+    HOOK.read().unwrap_or_else(|p| p.into_inner()).is_some()
+}
+
+/// The timing of a request whose response is yet to be parsed, stored in the response's
+/// extensions. Reported once the response is parsed or dropped.
+pub(crate) struct PendingTiming(Option<RequestTiming>);
+
+impl PendingTiming {
+    pub(crate) fn new(timing: RequestTiming) -> Self {
+        PendingTiming(Some(timing))
+    }
+
+    pub(crate) fn deserialized_in(mut self, duration: Duration) {
+        if let Some(timing) = &mut self.0 {
+            timing.deserialize = Some(duration);
+        }
+    }
+}
+
+impl Drop for PendingTiming {
+    fn drop(&mut self) {
+        let Some(timing) = self.0.take() else {
+            return;
+        };
+        // Cloned so a hook setting another hook doesn't deadlock
+        let hook = HOOK.read().unwrap_or_else(|p| p.into_inner()).clone();
+        if let Some(hook) = hook {
+            hook(&timing);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::MockHttpServer;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_timings_reported() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let timings = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&timings);
+        // The hook is global, other tests' requests are filtered out by port
+        let port = Url::parse(&server.url()).unwrap().port();
+        set_request_timing_hook(move |timing| {
+            if timing.url.port() == port {
+                recorded.lock().unwrap().push(timing.clone());
+            }
+        });
+        let balance = kalshi.get_balance().await;
+        clear_request_timing_hook();
+        assert!(balance.is_ok());
+
+        let timings = timings.lock().unwrap();
+        assert_eq!(timings.len(), 1);
+        assert_eq!(timings[0].method, Method::GET);
+        assert!(timings[0].url.path().ends_with("/portfolio/balance"));
+        assert_eq!(timings[0].status, 200);
+        assert!(timings[0].deserialize.is_some());
+        assert!(timings[0].total() >= timings[0].network());
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt;
+
+use crate::KalshiError;
+
+const BEGIN_CERTIFICATE: &str = "-----BEGIN CERTIFICATE-----";
+const END_CERTIFICATE: &str = "-----END CERTIFICATE-----";
+
+/// TLS settings of the connections to the exchange: REST requests, the websocket feed and FIX
+/// sessions.
+///
+/// By default connections trust the platform's root certificates. Extra roots let the client
+/// through a corporate proxy re-signing traffic, and disabling the built-in ones pins the
+/// connections to the given roots only. A client certificate can be presented for proxies or
+/// gateways requiring mutual TLS.
+///
+/// ```
+/// let tls = TlsConfig::new()
+///     .add_root_certificate_pem(std::fs::read("corporate-ca.pem")?)
+///     .built_in_roots(false)
+///     .identity_pem(std::fs::read("client.pem")?, std::fs::read("client.key")?);
+/// let kalshi = Kalshi::builder(TradingEnvironment::LiveMarketMode)
+///     .api_key(key_id, pem)
+///     .tls(tls)
+///     .build()?;
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// PEM certificates, one per entry.
+    root_certificates: Vec<Vec<u8>>,
+    built_in_roots: bool,
+    /// PEM certificate chain and PKCS#8 PEM private key.
+    identity: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        TlsConfig::new()
+    }
+}
+
+impl fmt::Debug for TlsConfig {
+    /// Leaves the client key out.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("root_certificates", &self.root_certificates.len())
+            .field("built_in_roots", &self.built_in_roots)
+            .field("identity", &self.identity.is_some())
+            .finish()
+    }
+}
+
+impl TlsConfig {
+    /// The platform's defaults: built-in roots and no client certificate.
+    pub fn new() -> Self {
+        TlsConfig {
+            root_certificates: Vec::new(),
+            built_in_roots: true,
+            identity: None,
+        }
+    }
+
+    /// Trusts every certificate of a PEM file, which may hold a whole bundle.
+    pub fn add_root_certificate_pem(mut self, pem: impl AsRef<[u8]>) -> Self {
+        let pem = String::from_utf8_lossy(pem.as_ref());
+        let mut rest = pem.as_ref();
+        while let Some(start) = rest.find(BEGIN_CERTIFICATE) {
+            let Some(end) = rest[start..].find(END_CERTIFICATE) else {
+                // Kept whole so the error is reported when the client is built
+                self.root_certificates
+                    .push(rest.as_bytes()[start..].to_vec());
+                return self;
+            };
+            let end = start + end + END_CERTIFICATE.len();
+            self.root_certificates
+                .push(rest.as_bytes()[start..end].to_vec());
+            rest = &rest[end..];
+        }
+        if !pem.contains(BEGIN_CERTIFICATE) {
+            self.root_certificates.push(pem.as_bytes().to_vec());
+        }
+        self
+    }
+
+    /// Whether the platform's root certificates are trusted, true by default.
+    pub fn built_in_roots(mut self, enabled: bool) -> Self {
+        self.built_in_roots = enabled;
+        self
+    }
+
+    /// Presents a client certificate, from a PEM certificate chain and a PKCS#8 PEM private key.
+    pub fn identity_pem(mut self, cert_chain: impl Into<Vec<u8>>, key: impl Into<Vec<u8>>) -> Self {
+        self.identity = Some((cert_chain.into(), key.into()));
+        self
+    }
+
+    /// Applies the settings to the REST client.
+    pub(crate) fn configure_http(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder, KalshiError> {
+        for pem in &self.root_certificates {
+            let certificate = reqwest::Certificate::from_pem(pem).map_err(invalid)?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        builder = builder.tls_built_in_root_certs(self.built_in_roots);
+        if let Some((cert_chain, key)) = &self.identity {
+            let identity = reqwest::Identity::from_pkcs8_pem(cert_chain, key).map_err(invalid)?;
+            builder = builder.identity(identity);
+        }
+        Ok(builder)
+    }
+
+    /// A connector with the settings, for the websocket and FIX connections.
+    #[cfg_attr(not(any(feature = "websockets", feature = "fix")), allow(dead_code))]
+    pub(crate) fn connector(&self) -> Result<native_tls::TlsConnector, KalshiError> {
+        let mut builder = native_tls::TlsConnector::builder();
+        for pem in &self.root_certificates {
+            builder.add_root_certificate(native_tls::Certificate::from_pem(pem).map_err(invalid)?);
+        }
+        builder.disable_built_in_roots(!self.built_in_roots);
+        if let Some((cert_chain, key)) = &self.identity {
+            builder.identity(native_tls::Identity::from_pkcs8(cert_chain, key).map_err(invalid)?);
+        }
+        builder.build().map_err(invalid)
+    }
+}
+
+fn invalid(e: impl fmt::Display) -> KalshiError {
+    KalshiError::UserInputError(format!("Invalid TLS configuration: {}", e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use openssl::{
+        asn1::Asn1Time,
+        hash::MessageDigest,
+        pkey::PKey,
+        rsa::Rsa,
+        x509::{X509Builder, X509NameBuilder},
+    };
+
+    /// A self-signed certificate and its PKCS#8 key, both PEM.
+    fn self_signed() -> (Vec<u8>, Vec<u8>) {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "kalshi-test").unwrap();
+        let name = name.build();
+        let mut cert = X509Builder::new().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+        (
+            cert.build().to_pem().unwrap(),
+            key.private_key_to_pem_pkcs8().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_certificates_loaded() {
+        let (cert, key) = self_signed();
+        let (other, _) = self_signed();
+        let bundle = [cert.clone(), other].concat();
+        let tls = TlsConfig::new()
+            .add_root_certificate_pem(&bundle)
+            .built_in_roots(false)
+            .identity_pem(cert, key);
+        assert_eq!(tls.root_certificates.len(), 2);
+        assert!(tls.configure_http(reqwest::Client::builder()).is_ok());
+        assert!(tls.connector().is_ok());
+
+        let invalid = TlsConfig::new().add_root_certificate_pem("not a certificate");
+        assert!(matches!(
+            invalid.connector(),
+            Err(KalshiError::UserInputError(_))
+        ));
+        assert!(invalid.configure_http(reqwest::Client::builder()).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{Fill, Kalshi, KalshiError};
+
+/// Fills fetched per page while syncing the journal.
+const FILLS_PAGE_SIZE: i32 = 100;
+
+/// A fill is the same whichever source reported it if both the trade and the order match, the
+/// trade alone isn't enough as the account can be on both sides of it.
+type FillKey = (String, String);
+
+/// Where in the log a fill sits, by creation time in milliseconds then by key.
+type LogKey = (i64, String, String);
+
+#[derive(Debug, Default)]
+struct JournalState {
+    log: BTreeMap<LogKey, Fill>,
+    seen: HashSet<FillKey>,
+}
+
+/// Merges the account's fills from the REST fills endpoint and the websocket `fill` channel into
+/// a single log.
+///
+/// The same fill is usually reported by both sources, so naively concatenating them counts it
+/// twice. The journal keeps a fill once per trade and order, whichever source reported it first,
+/// and orders the log by creation time so it reads the same whatever order the sources delivered
+/// the fills in. The websocket only reports fills to the second, fills first seen there sort
+/// before fills of the same second fetched from REST.
+///
+/// The journal is a cheap handle, clones share the same state.
+///
+/// ```
+/// let journal = FillJournal::new();
+/// journal.follow_fills(&ws_client);
+/// // Catches up on fills missed while disconnected
+/// journal.sync(&kalshi_instance).await?;
+///
+/// for fill in journal.fills() {
+///     println!("{} {} {:?} at {}", fill.created_time, fill.count, fill.side, fill.yes_price);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FillJournal {
+    state: Arc<Mutex<JournalState>>,
+}
+
+/// The creation time of a fill in milliseconds, 0 when it can't be parsed.
+fn created_ms(fill: &Fill) -> i64 {
+    chrono::DateTime::parse_from_rfc3339(&fill.created_time)
+        .map(|time| time.timestamp_millis())
+        .unwrap_or_else(|_| {
+            log::warn!(
+                "Fill {} has an invalid creation time {}",
+                fill.trade_id,
+                fill.created_time
+            );
+            0
+        })
+}
+
+impl FillJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, JournalState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Adds a fill to the journal, returns `false` if it was already there.
+    pub fn ingest(&self, fill: &Fill) -> bool {
+        let key = (fill.trade_id.clone(), fill.order_id.clone());
+        let mut state = self.lock();
+        if state.seen.contains(&key) {
+            return false;
+        }
+        let time = created_ms(fill);
+        state.seen.insert(key.clone());
+        state.log.insert((time, key.0, key.1), fill.clone());
+        true
+    }
+
+    /// Whether the fill of `order_id` in `trade_id` is in the journal.
+    pub fn contains(&self, trade_id: &str, order_id: &str) -> bool {
+        self.lock()
+            .seen
+            .contains(&(trade_id.to_string(), order_id.to_string()))
+    }
+
+    /// Every fill in the journal, oldest first.
+    pub fn fills(&self) -> Vec<Fill> {
+        self.lock().log.values().cloned().collect()
+    }
+
+    /// The fills created at or after `ts`, in seconds, oldest first.
+    pub fn fills_since(&self, ts: i64) -> Vec<Fill> {
+        let start = (ts.saturating_mul(1000), String::new(), String::new());
+        self.lock()
+            .log
+            .range(start..)
+            .map(|(_, fill)| fill.clone())
+            .collect()
+    }
+
+    /// The fills of a market, oldest first.
+    pub fn fills_for(&self, ticker: &str) -> Vec<Fill> {
+        self.lock()
+            .log
+            .values()
+            .filter(|fill| fill.ticker == ticker)
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().log.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().log.is_empty()
+    }
+
+    /// Fetches the fills created since the latest one in the journal, every fill when it's empty,
+    /// and returns how many were new.
+    ///
+    /// The second of the latest fill is fetched again so fills created in the same second aren't
+    /// missed, the journal drops the ones it already has.
+    pub async fn sync(&self, kalshi: &Kalshi) -> Result<usize, KalshiError> {
+        let min_ts = self.lock().log.keys().next_back().map(|key| key.0 / 1000);
+        let mut new = 0;
+        let mut cursor = None;
+        loop {
+            let (next, fills) = kalshi
+                .get_multiple_fills(None, None, min_ts, None, Some(FILLS_PAGE_SIZE), cursor)
+                .await?;
+            new += fills.iter().filter(|fill| self.ingest(fill)).count();
+            match next {
+                Some(next) if !next.is_empty() && !fills.is_empty() => cursor = Some(next),
+                _ => return Ok(new),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::{
+        client::KalshiWebsocketClient,
+        responses::{KalshiFillMessage, KalshiWebsocketResponse},
+    };
+    use crate::{Action, RestartPolicy, Side};
+    use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
+
+    impl TryFrom<&KalshiFillMessage> for Fill {
+        type Error = KalshiError;
+
+        fn try_from(fill: &KalshiFillMessage) -> Result<Self, Self::Error> {
+            let action = match fill.action.as_str() {
+                "buy" => Action::Buy,
+                "sell" => Action::Sell,
+                other => {
+                    return Err(KalshiError::InternalError(format!(
+                        "Unexpected fill action {}",
+                        other
+                    )))
+                }
+            };
+            let created_time = chrono::DateTime::from_timestamp(fill.ts as i64, 0)
+                .unwrap_or_default()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+            Ok(Fill {
+                action,
+                count: fill.count as i32,
+                created_time,
+                is_taker: fill.is_taker,
+                no_price: fill.no_price as i64,
+                order_id: fill.order_id.clone(),
+                side: Side::from(fill.side),
+                ticker: fill.market_ticker.to_string(),
+                trade_id: fill.trade_id.clone(),
+                yes_price: fill.yes_price as i64,
+            })
+        }
+    }
+
+    impl FillJournal {
+        /// Adds a websocket fill message to the journal, returns `false` if it was already there.
+        pub fn on_fill(&self, fill: &KalshiFillMessage) -> bool {
+            match Fill::try_from(fill) {
+                Ok(fill) => self.ingest(&fill),
+                Err(e) => {
+                    log::warn!("Ignoring fill {}: {}", fill.trade_id, e);
+                    false
+                }
+            }
+        }
+
+        /// Adds every fill delivered by `ws_client` from now on, until the client shuts down.
+        ///
+        /// The client must be subscribed to the `fill` channel.
+        pub fn follow_fills(&self, ws_client: &KalshiWebsocketClient) -> JoinHandle<()> {
+            let journal = self.clone();
+            let receiver = Arc::new(tokio::sync::Mutex::new(ws_client.receiver()));
+            let shutdown = ws_client.kalshi().shutdown_handle().clone();
+            shutdown.supervise("fill journal", RestartPolicy::default(), move || {
+                let journal = journal.clone();
+                let receiver = Arc::clone(&receiver);
+                async move {
+                    // Restarts pick up where the panicked run stopped
+                    let mut receiver = receiver.lock_owned().await;
+                    loop {
+                        match receiver.recv().await {
+                            Ok(Ok(KalshiWebsocketResponse::Fill { msg, .. })) => {
+                                journal.on_fill(&msg);
+                            }
+                            Ok(_) => {}
+                            Err(RecvError::Lagged(skipped)) => {
+                                log::warn!("Fill journal lagged, skipped {} messages", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use reqwest::Method;
+
+    fn fill(trade_id: &str, order_id: &str, created_time: &str) -> Fill {
+        let mut fill = fixtures::fills_page()["fills"][0].clone();
+        fill["trade_id"] = trade_id.into();
+        fill["order_id"] = order_id.into();
+        fill["created_time"] = created_time.into();
+        serde_json::from_value(fill).unwrap()
+    }
+
+    #[test]
+    fn test_journal_dedupes_and_orders_fills() {
+        let journal = FillJournal::new();
+        assert!(journal.ingest(&fill("t2", "a", "2025-09-17T18:06:41.000Z")));
+        assert!(journal.ingest(&fill("t1", "a", "2025-09-17T18:06:40.500Z")));
+        // The account on both sides of a trade
+        assert!(journal.ingest(&fill("t1", "b", "2025-09-17T18:06:40.500Z")));
+        assert!(!journal.ingest(&fill("t1", "a", "2025-09-17T18:06:40.000Z")));
+
+        let log: Vec<_> = journal
+            .fills()
+            .into_iter()
+            .map(|fill| (fill.trade_id, fill.order_id))
+            .collect();
+        assert_eq!(
+            log,
+            vec![
+                ("t1".to_string(), "a".to_string()),
+                ("t1".to_string(), "b".to_string()),
+                ("t2".to_string(), "a".to_string()),
+            ]
+        );
+        assert!(journal.contains("t1", "b"));
+        assert_eq!(journal.fills_since(1758132401).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sync_fetches_since_latest_fill() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let journal = FillJournal::new();
+
+        assert_eq!(journal.sync(&kalshi).await.unwrap(), 1);
+        assert_eq!(journal.sync(&kalshi).await.unwrap(), 0);
+        assert_eq!(journal.len(), 1);
+
+        let requests = server.requests_to(Method::GET, "/portfolio/fills");
+        assert_eq!(requests[0].query_param("min_ts"), None);
+        assert_eq!(requests[1].query_param("min_ts"), Some("1758132400"));
+    }
+}
@@ -0,0 +1,15 @@
+//! Local mirrors of the account's state, kept up to date from REST calls and the websocket feed.
+
+mod fills;
+mod orders;
+mod positions;
+mod queue;
+mod reconcile;
+mod settlements;
+
+pub use fills::*;
+pub use orders::*;
+pub use positions::*;
+pub use queue::*;
+pub use reconcile::*;
+pub use settlements::*;
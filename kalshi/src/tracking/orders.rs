@@ -0,0 +1,571 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::broadcast;
+
+use crate::{Action, Order, OrderStatus, Side, TimeInForce};
+
+/// How long a fill of an untracked order waits for the order, fills of orders placed elsewhere
+/// would otherwise pile up.
+const EARLY_FILL_TTL: Duration = Duration::from_secs(60);
+
+/// An order as seen by the [`OrderTracker`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedOrder {
+    pub order_id: String,
+    pub client_order_id: String,
+    pub ticker: String,
+    pub action: Action,
+    pub side: Side,
+    /// Limit price of the order in cents, on the order's own side.
+    pub price: i32,
+    /// Contracts still resting on the book.
+    pub remaining_count: i32,
+    /// Contracts filled since the order was tracked.
+    pub filled_count: i32,
+    pub status: OrderStatus,
+    pub time_in_force: Option<TimeInForce>,
+    pub post_only: bool,
+    /// When the exchange cancels the order, as a unix timestamp in seconds.
+    pub expiration_ts: Option<i64>,
+}
+
+impl TrackedOrder {
+    pub(crate) fn from_order(order: &Order) -> Self {
+        let price = match order.side {
+            Side::Yes => order.yes_price,
+            Side::No => order.no_price,
+        };
+        TrackedOrder {
+            order_id: order.order_id.clone(),
+            client_order_id: order.client_order_id.clone(),
+            ticker: order.ticker.clone(),
+            action: order.action,
+            side: order.side,
+            price,
+            remaining_count: order.remaining_count.unwrap_or_default(),
+            filled_count: 0,
+            status: order.status,
+            time_in_force: order.time_in_force,
+            post_only: order.post_only,
+            expiration_ts: order.expiration_ts(),
+        }
+    }
+
+    /// Whether the order is still working on the book.
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, OrderStatus::Resting | OrderStatus::Pending)
+    }
+
+    /// Whether the exchange has canceled the order for reaching its expiration time at `now`,
+    /// a unix timestamp in seconds.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiration_ts.is_some_and(|ts| ts <= now)
+    }
+}
+
+/// A change to a tracked order, see [`OrderTracker::events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderEvent {
+    /// An order was placed.
+    Placed(TrackedOrder),
+    /// Some contracts of an order were filled, the order reflects the fill.
+    Filled { order: TrackedOrder, count: i32 },
+    /// An order was decreased, the order reflects the new size.
+    Decreased(TrackedOrder),
+    /// An order was amended, the order reflects the new price and size.
+    Amended(TrackedOrder),
+    /// An order was canceled.
+    Canceled(TrackedOrder),
+    /// An order was overwritten with the exchange's view of it, see [`Reconciler`](crate::Reconciler).
+    Reconciled(TrackedOrder),
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    orders: HashMap<String, TrackedOrder>,
+    /// Fills received before the order they belong to was tracked, keyed by order id, with
+    /// when the first of them arrived
+    early_fills: HashMap<String, (i32, Instant)>,
+    /// Contracts the create response already counted as filled whose fill messages haven't
+    /// arrived yet, keyed by order id
+    counted_fills: HashMap<String, i32>,
+}
+
+impl TrackerState {
+    /// Keeps a fill of an untracked order, dropping the ones that waited too long.
+    fn buffer_fill(&mut self, order_id: &str, count: i32, now: Instant) {
+        self.early_fills
+            .retain(|_, (_, received_at)| now.duration_since(*received_at) < EARLY_FILL_TTL);
+        self.early_fills
+            .entry(order_id.to_string())
+            .or_insert((0, now))
+            .0 += count;
+    }
+
+    fn take_early_fill(&mut self, order_id: &str, now: Instant) -> Option<i32> {
+        self.early_fills
+            .remove(order_id)
+            .filter(|(_, received_at)| now.duration_since(*received_at) < EARLY_FILL_TTL)
+            .map(|(count, _)| count)
+    }
+
+    /// How many of `count` newly received filled contracts are already out of the order's
+    /// remaining count, because the create response counted them.
+    fn take_counted_fills(&mut self, order_id: &str, count: i32) -> i32 {
+        let Some(counted) = self.counted_fills.get_mut(order_id) else {
+            return 0;
+        };
+        let taken = count.min(*counted);
+        *counted -= taken;
+        if *counted == 0 {
+            self.counted_fills.remove(order_id);
+        }
+        taken
+    }
+}
+
+/// Mirrors the account's orders locally.
+///
+/// Attach the tracker to a [`Kalshi`](crate::Kalshi) instance with
+/// [`Kalshi::set_order_tracker`](crate::Kalshi::set_order_tracker) and every successful
+/// `create_order`, `decrease_order` and `cancel_order` call is recorded. Fills are applied from the
+/// websocket `fill` channel with [`OrderTracker::follow_fills`], or fed manually with [`OrderTracker::on_fill`].
+///
+/// The tracker is a cheap handle, clones share the same state.
+///
+/// ```
+/// let tracker = OrderTracker::new();
+/// kalshi.set_order_tracker(tracker.clone());
+/// ws_client.subscribe(vec![KalshiChannel::Fill], vec![]).await?;
+/// tracker.follow_fills(&ws_client);
+///
+/// let mut events = tracker.events();
+/// kalshi.create_order(/* ... */).await?;
+/// while let Ok(event) = events.recv().await {
+///     println!("{:?}, exposure {:?}", event, tracker.exposure_by_market());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderTracker {
+    state: Arc<Mutex<TrackerState>>,
+    events: broadcast::Sender<OrderEvent>,
+}
+
+impl Default for OrderTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(1024);
+        OrderTracker {
+            state: Arc::new(Mutex::new(TrackerState::default())),
+            events,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TrackerState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    fn emit(&self, event: OrderEvent) {
+        // No receivers is fine, events are optional
+        let _ = self.events.send(event);
+    }
+
+    /// Subscribes to order changes, every receiver sees every event from the moment it was created.
+    pub fn events(&self) -> broadcast::Receiver<OrderEvent> {
+        self.events.subscribe()
+    }
+
+    /// Records a newly placed order.
+    ///
+    /// The remaining count of the create response already accounts for the fills it reports, so
+    /// only fill messages beyond those take contracts off it, whether they arrived before the
+    /// response or arrive after.
+    pub fn on_order_created(&self, order: &Order) {
+        let mut tracked = TrackedOrder::from_order(order);
+        let early_fill = {
+            let mut state = self.lock();
+            if let Some(existing) = state.orders.get(&tracked.order_id) {
+                tracked.filled_count = existing.filled_count;
+            }
+            let early_fill = state.take_early_fill(&tracked.order_id, Instant::now());
+            let counted = order.taker_fill_count.unwrap_or_default()
+                + order.maker_fill_count.unwrap_or_default();
+            let early_count = early_fill.unwrap_or_default();
+            apply_fill(&mut tracked, early_count, early_count.min(counted));
+            if counted > early_count {
+                state
+                    .counted_fills
+                    .insert(tracked.order_id.clone(), counted - early_count);
+            }
+            state
+                .orders
+                .insert(tracked.order_id.clone(), tracked.clone());
+            early_fill
+        };
+        self.emit(OrderEvent::Placed(tracked.clone()));
+        if let Some(count) = early_fill {
+            self.emit(OrderEvent::Filled {
+                order: tracked,
+                count,
+            });
+        }
+    }
+
+    /// Records an order returned by a decrease.
+    pub fn on_order_decreased(&self, order: &Order) {
+        let tracked = {
+            let mut state = self.lock();
+            let tracked = state
+                .orders
+                .entry(order.order_id.clone())
+                .or_insert_with(|| TrackedOrder::from_order(order));
+            tracked.remaining_count = order.remaining_count.unwrap_or_default();
+            tracked.status = order.status;
+            tracked.clone()
+        };
+        self.emit(OrderEvent::Decreased(tracked));
+    }
+
+    /// Records an order returned by an amend, which can change its price, size and client order id.
+    pub fn on_order_amended(&self, order: &Order) {
+        let amended = TrackedOrder::from_order(order);
+        let tracked = {
+            let mut state = self.lock();
+            let tracked = state
+                .orders
+                .entry(order.order_id.clone())
+                .or_insert_with(|| amended.clone());
+            tracked.client_order_id = amended.client_order_id;
+            tracked.price = amended.price;
+            tracked.remaining_count = amended.remaining_count;
+            tracked.status = amended.status;
+            tracked.clone()
+        };
+        self.emit(OrderEvent::Amended(tracked));
+    }
+
+    /// Records an order returned by a cancel.
+    pub fn on_order_canceled(&self, order: &Order) {
+        let tracked = {
+            let mut state = self.lock();
+            let tracked = state
+                .orders
+                .entry(order.order_id.clone())
+                .or_insert_with(|| TrackedOrder::from_order(order));
+            tracked.remaining_count = 0;
+            tracked.status = OrderStatus::Canceled;
+            tracked.clone()
+        };
+        self.emit(OrderEvent::Canceled(tracked));
+    }
+
+    /// Overwrites an order's status and remaining count with the exchange's, tracking it if needed.
+    ///
+    /// The fill count is kept, it only counts fills seen by the tracker.
+    pub fn on_order_reconciled(&self, order: &Order) {
+        let tracked = {
+            let mut state = self.lock();
+            let tracked = state
+                .orders
+                .entry(order.order_id.clone())
+                .or_insert_with(|| TrackedOrder::from_order(order));
+            tracked.remaining_count = order.remaining_count.unwrap_or_default();
+            tracked.status = order.status;
+            tracked.clone()
+        };
+        self.emit(OrderEvent::Reconciled(tracked));
+    }
+
+    /// Stops tracking an order, returning it if it was tracked.
+    pub fn remove(&self, order_id: &str) -> Option<TrackedOrder> {
+        let mut state = self.lock();
+        state.counted_fills.remove(order_id);
+        state.orders.remove(order_id)
+    }
+
+    /// Applies a fill of `count` contracts to the order `order_id`.
+    ///
+    /// Fills of orders not tracked yet are kept for a minute and applied if the order is recorded
+    /// by then, the fill message can arrive before the response to `create_order`.
+    pub fn on_order_filled(&self, order_id: &str, count: i32) {
+        let tracked = {
+            let mut state = self.lock();
+            let counted = state.take_counted_fills(order_id, count);
+            match state.orders.get_mut(order_id) {
+                Some(tracked) => {
+                    apply_fill(tracked, count, counted);
+                    tracked.clone()
+                }
+                None => {
+                    state.buffer_fill(order_id, count, Instant::now());
+                    return;
+                }
+            }
+        };
+        self.emit(OrderEvent::Filled {
+            order: tracked,
+            count,
+        });
+    }
+
+    /// Orders still resting on the book.
+    pub fn open_orders(&self) -> Vec<TrackedOrder> {
+        self.lock()
+            .orders
+            .values()
+            .filter(|order| order.is_open())
+            .cloned()
+            .collect()
+    }
+
+    /// A tracked order by id, open or not.
+    pub fn get(&self, order_id: &str) -> Option<TrackedOrder> {
+        self.lock().orders.get(order_id).cloned()
+    }
+
+    /// The most each market could cost if every resting buy order filled, in cents.
+    ///
+    /// Resting sell orders only reduce existing positions and don't add exposure.
+    pub fn exposure_by_market(&self) -> HashMap<String, i64> {
+        let mut exposure = HashMap::new();
+        for order in self.lock().orders.values() {
+            if order.is_open() && order.action == Action::Buy {
+                *exposure.entry(order.ticker.clone()).or_default() +=
+                    order.remaining_count as i64 * order.price as i64;
+            }
+        }
+        exposure
+    }
+
+    /// Marks the open orders whose expiration time is at or before `now`, a unix timestamp in
+    /// seconds, as canceled like the exchange does, returning them.
+    ///
+    /// The exchange doesn't send anything when an order expires, call this periodically to keep
+    /// open orders in line with the book.
+    pub fn expire_orders(&self, now: i64) -> Vec<TrackedOrder> {
+        let expired: Vec<TrackedOrder> = {
+            let mut state = self.lock();
+            state
+                .orders
+                .values_mut()
+                .filter(|order| order.is_open() && order.is_expired(now))
+                .map(|order| {
+                    order.remaining_count = 0;
+                    order.status = OrderStatus::Canceled;
+                    order.clone()
+                })
+                .collect()
+        };
+        for order in &expired {
+            self.emit(OrderEvent::Canceled(order.clone()));
+        }
+        expired
+    }
+
+    /// Forgets orders that are no longer open.
+    pub fn prune_closed(&self) {
+        let mut state = self.lock();
+        state.orders.retain(|_, order| order.is_open());
+        let TrackerState {
+            orders,
+            counted_fills,
+            ..
+        } = &mut *state;
+        counted_fills.retain(|order_id, _| orders.contains_key(order_id));
+    }
+}
+
+/// Applies a fill of `count` contracts, `counted` of which are already out of the remaining count.
+fn apply_fill(order: &mut TrackedOrder, count: i32, counted: i32) {
+    if count == 0 {
+        return;
+    }
+    order.filled_count += count;
+    order.remaining_count = (order.remaining_count - (count - counted)).max(0);
+    if order.remaining_count == 0 {
+        order.status = OrderStatus::Executed;
+    }
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::{
+        client::KalshiWebsocketClient,
+        responses::{KalshiFillMessage, KalshiWebsocketResponse},
+    };
+    use crate::RestartPolicy;
+    use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
+
+    impl OrderTracker {
+        /// Applies a websocket fill message.
+        pub fn on_fill(&self, fill: &KalshiFillMessage) {
+            self.on_order_filled(&fill.order_id, fill.count as i32);
+        }
+
+        /// Applies every fill delivered by `ws_client` from now on, until the client shuts down.
+        ///
+        /// The client must be subscribed to the `fill` channel.
+        pub fn follow_fills(&self, ws_client: &KalshiWebsocketClient) -> JoinHandle<()> {
+            let tracker = self.clone();
+            let receiver = Arc::new(tokio::sync::Mutex::new(ws_client.receiver()));
+            let shutdown = ws_client.kalshi().shutdown_handle().clone();
+            shutdown.supervise("order fills", RestartPolicy::default(), move || {
+                let tracker = tracker.clone();
+                let receiver = Arc::clone(&receiver);
+                async move {
+                    // Restarts pick up where the panicked run stopped
+                    let mut receiver = receiver.lock_owned().await;
+                    loop {
+                        match receiver.recv().await {
+                            Ok(Ok(KalshiWebsocketResponse::Fill { msg, .. })) => {
+                                tracker.on_fill(&msg)
+                            }
+                            Ok(_) => {}
+                            Err(RecvError::Lagged(skipped)) => {
+                                log::warn!("Order tracker lagged, skipped {} messages", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn order(order_id: &str, remaining: i32) -> Order {
+        serde_json::from_value(serde_json::json!({
+            "order_id": order_id,
+            "ticker": "KXHIGHNY-25OCT02-B80.5",
+            "status": "resting",
+            "yes_price": 40,
+            "no_price": 60,
+            "remaining_count": remaining,
+            "action": "buy",
+            "side": "yes",
+            "type": "limit",
+            "client_order_id": "mm-1",
+            "order_group_id": "",
+        }))
+        .unwrap()
+    }
+
+    /// An order whose create response already counts `filled` contracts as filled.
+    fn filled_order(order_id: &str, remaining: i32, filled: i32) -> Order {
+        let mut order = serde_json::to_value(order(order_id, remaining)).unwrap();
+        order["taker_fill_count"] = filled.into();
+        serde_json::from_value(order).unwrap()
+    }
+
+    #[test]
+    fn test_expired_orders_canceled() {
+        let tracker = OrderTracker::new();
+        let mut expiring: serde_json::Value = serde_json::to_value(order("a", 10)).unwrap();
+        expiring["expiration_time"] = "2025-10-02T15:00:00Z".into();
+        expiring["time_in_force"] = "good_till_canceled".into();
+        expiring["post_only"] = true.into();
+        let expiring: Order = serde_json::from_value(expiring).unwrap();
+        assert_eq!(expiring.expiration_ts(), Some(1759417200));
+        tracker.on_order_created(&expiring);
+        tracker.on_order_created(&order("b", 5));
+
+        let tracked = tracker.get("a").unwrap();
+        assert_eq!(tracked.time_in_force, Some(TimeInForce::GoodTillCanceled));
+        assert!(tracked.post_only);
+        assert!(tracker.expire_orders(1759417199).is_empty());
+        let expired = tracker.expire_orders(1759417200);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].status, OrderStatus::Canceled);
+        assert_eq!(tracker.open_orders()[0].order_id, "b");
+    }
+
+    #[test]
+    fn test_orders_tracked_through_fills_and_cancels() {
+        let tracker = OrderTracker::new();
+        let mut events = tracker.events();
+
+        // The fill arrives before the create response, which counts it
+        tracker.on_order_filled("a", 4);
+        tracker.on_order_created(&filled_order("a", 6, 4));
+        tracker.on_order_created(&order("b", 5));
+        assert_eq!(tracker.get("a").unwrap().remaining_count, 6);
+        assert_eq!(tracker.get("a").unwrap().filled_count, 4);
+        assert_eq!(
+            tracker.exposure_by_market()["KXHIGHNY-25OCT02-B80.5"],
+            (6 + 5) * 40
+        );
+
+        tracker.on_order_filled("a", 6);
+        tracker.on_order_canceled(&order("b", 5));
+        assert!(tracker.open_orders().is_empty());
+        assert!(tracker.exposure_by_market().is_empty());
+        assert_eq!(tracker.get("a").unwrap().status, OrderStatus::Executed);
+
+        assert!(matches!(events.try_recv(), Ok(OrderEvent::Placed(_))));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(OrderEvent::Filled { count: 4, .. })
+        ));
+        assert!(matches!(events.try_recv(), Ok(OrderEvent::Placed(_))));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(OrderEvent::Filled { count: 6, .. })
+        ));
+        assert!(matches!(events.try_recv(), Ok(OrderEvent::Canceled(_))));
+    }
+
+    #[test]
+    fn test_fills_counted_by_create_response_applied_once() {
+        let tracker = OrderTracker::new();
+
+        // Fill messages after the response, the first 3 contracts are already counted
+        tracker.on_order_created(&filled_order("a", 7, 3));
+        tracker.on_order_filled("a", 2);
+        assert_eq!(tracker.get("a").unwrap().remaining_count, 7);
+        tracker.on_order_filled("a", 3);
+        let a = tracker.get("a").unwrap();
+        assert_eq!((a.remaining_count, a.filled_count), (5, 5));
+
+        // More fill messages before the response than it counts
+        tracker.on_order_filled("b", 5);
+        tracker.on_order_created(&filled_order("b", 8, 2));
+        let b = tracker.get("b").unwrap();
+        assert_eq!((b.remaining_count, b.filled_count), (5, 5));
+
+        // Tracking an order again keeps the fills seen so far
+        tracker.on_order_created(&order("b", 5));
+        assert_eq!(tracker.get("b").unwrap().filled_count, 5);
+    }
+
+    #[test]
+    fn test_early_fills_expire() {
+        let tracker = OrderTracker::new();
+        let start = Instant::now();
+        tracker.lock().buffer_fill("placed-elsewhere", 3, start);
+        tracker
+            .lock()
+            .buffer_fill("a", 4, start + EARLY_FILL_TTL / 2);
+        tracker
+            .lock()
+            .buffer_fill("b", 2, start + EARLY_FILL_TTL + Duration::from_secs(1));
+
+        let mut state = tracker.lock();
+        assert!(!state.early_fills.contains_key("placed-elsewhere"));
+        assert_eq!(state.take_early_fill("a", start + EARLY_FILL_TTL), Some(4));
+        assert_eq!(state.take_early_fill("b", start + EARLY_FILL_TTL * 3), None);
+    }
+}
@@ -0,0 +1,389 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use futures::StreamExt;
+use tokio::task::JoinHandle;
+
+use crate::{Action, Kalshi, KalshiError, MarketPosition, RestartPolicy, Side};
+
+/// The account's position in a single market, see [`PositionTracker`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackedPosition {
+    pub ticker: String,
+    /// Net contracts held, positive for yes and negative for no.
+    pub position: i64,
+    /// What the contracts held cost, in cents.
+    pub cost_basis: i64,
+    /// Profit or loss locked in by reducing the position, in cents.
+    pub realized_pnl: i64,
+}
+
+impl TrackedPosition {
+    /// The side held, `None` when flat.
+    pub fn side(&self) -> Option<Side> {
+        match self.position {
+            p if p > 0 => Some(Side::Yes),
+            p if p < 0 => Some(Side::No),
+            _ => None,
+        }
+    }
+
+    /// Average price paid per contract held, in cents on the side held.
+    pub fn average_entry_price(&self) -> Option<f64> {
+        (self.position != 0).then(|| self.cost_basis as f64 / self.position.abs() as f64)
+    }
+
+    /// Applies a fill of `count` contracts at `price` cents on `side`.
+    fn apply(&mut self, side: Side, action: Action, count: i64, price: i64) {
+        // Buying no is selling yes and the other way around
+        let delta = match (side, action) {
+            (Side::Yes, Action::Buy) | (Side::No, Action::Sell) => count,
+            (Side::Yes, Action::Sell) | (Side::No, Action::Buy) => -count,
+        };
+        // Prices are quoted on the side held, yes and no prices always sum to 100
+        let price_for = |held: i64| {
+            if (held > 0) == (side == Side::Yes) {
+                price
+            } else {
+                100 - price
+            }
+        };
+
+        if self.position == 0 || (self.position > 0) == (delta > 0) {
+            self.cost_basis += delta.abs() * price_for(delta);
+            self.position += delta;
+            return;
+        }
+
+        let closed = delta.abs().min(self.position.abs());
+        let closed_cost = self.cost_basis * closed / self.position.abs();
+        self.realized_pnl += closed * price_for(self.position) - closed_cost;
+        self.cost_basis -= closed_cost;
+        let opened = delta.abs() - closed;
+        self.position += delta;
+        if opened > 0 {
+            self.cost_basis = opened * price_for(self.position);
+        }
+    }
+}
+
+/// A difference between the local positions and the exchange found by [`PositionTracker::reconcile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionDrift {
+    pub ticker: String,
+    pub local_position: i64,
+    pub exchange_position: i64,
+}
+
+/// Tracks the account's positions in real time.
+///
+/// Seeded from [`Kalshi::get_user_positions`], then updated from fills as they arrive
+/// (see [`PositionTracker::follow_fills`]). Fills can be missed while the websocket reconnects,
+/// so positions are periodically overwritten with the exchange's view with
+/// [`PositionTracker::spawn_reconciliation`].
+///
+/// The tracker is a cheap handle, clones share the same state.
+///
+/// ```
+/// let tracker = PositionTracker::seed(&kalshi).await?;
+/// tracker.follow_fills(&ws_client);
+/// tracker.spawn_reconciliation(kalshi.clone(), Duration::from_secs(60));
+///
+/// let position = tracker.position("KXHIGHNY-25OCT02-B80.5");
+/// println!("{:?} @ {:?}, total exposure {}c", position.position, position.average_entry_price(), tracker.total_exposure());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PositionTracker {
+    positions: Arc<Mutex<HashMap<String, TrackedPosition>>>,
+}
+
+impl PositionTracker {
+    /// Creates an empty tracker, positions held before now are unknown until [`PositionTracker::reconcile`] runs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a tracker holding the account's current positions.
+    pub async fn seed(kalshi: &Kalshi) -> Result<Self, KalshiError> {
+        let tracker = Self::new();
+        tracker.reconcile(kalshi).await?;
+        Ok(tracker)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, TrackedPosition>> {
+        self.positions.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Applies a fill of `count` contracts bought or sold at `price` cents on `side`.
+    pub fn apply_fill(&self, ticker: &str, side: Side, action: Action, count: i64, price: i64) {
+        self.lock()
+            .entry(ticker.to_string())
+            .or_insert_with(|| TrackedPosition {
+                ticker: ticker.to_string(),
+                ..Default::default()
+            })
+            .apply(side, action, count, price);
+    }
+
+    /// Replaces the position in a market with one reported by the exchange.
+    pub fn set_position(&self, market_position: &MarketPosition) {
+        let mut positions = self.lock();
+        let position = positions
+            .entry(market_position.ticker.clone())
+            .or_insert_with(|| TrackedPosition {
+                ticker: market_position.ticker.clone(),
+                ..Default::default()
+            });
+        position.position = market_position.position as i64;
+        position.cost_basis = market_position.market_exposure;
+        position.realized_pnl = market_position.realized_pnl;
+    }
+
+    /// The position in a market, flat if nothing is held.
+    pub fn position(&self, ticker: &str) -> TrackedPosition {
+        self.lock()
+            .get(ticker)
+            .cloned()
+            .unwrap_or_else(|| TrackedPosition {
+                ticker: ticker.to_string(),
+                ..Default::default()
+            })
+    }
+
+    /// Every market with contracts held.
+    pub fn positions(&self) -> Vec<TrackedPosition> {
+        self.lock()
+            .values()
+            .filter(|p| p.position != 0)
+            .cloned()
+            .collect()
+    }
+
+    /// Net position per market, positive for yes and negative for no.
+    pub fn net_positions(&self) -> HashMap<String, i64> {
+        self.lock()
+            .values()
+            .filter(|p| p.position != 0)
+            .map(|p| (p.ticker.clone(), p.position))
+            .collect()
+    }
+
+    /// Profit or loss realized across all markets, in cents.
+    pub fn realized_pnl(&self) -> i64 {
+        self.lock().values().map(|p| p.realized_pnl).sum()
+    }
+
+    /// What all contracts held cost, in cents.
+    pub fn total_exposure(&self) -> i64 {
+        self.lock().values().map(|p| p.cost_basis).sum()
+    }
+
+    /// Overwrites the local positions with the exchange's, returning every market that had drifted.
+    pub async fn reconcile(&self, kalshi: &Kalshi) -> Result<Vec<PositionDrift>, KalshiError> {
+        let exchange_positions = fetch_positions(kalshi).await?;
+        let drifts = self.diff(&exchange_positions);
+        self.replace_positions(&exchange_positions);
+        Ok(drifts)
+    }
+
+    /// Compares the local positions with positions reported by the exchange, without changing them.
+    ///
+    /// Markets held locally but missing from `exchange_positions` count as flat on the exchange.
+    pub fn diff(&self, exchange_positions: &[MarketPosition]) -> Vec<PositionDrift> {
+        let positions = self.lock();
+        let mut drifts: Vec<PositionDrift> = positions
+            .values()
+            .filter(|local| {
+                local.position != 0 && !exchange_positions.iter().any(|p| p.ticker == local.ticker)
+            })
+            .map(|local| PositionDrift {
+                ticker: local.ticker.clone(),
+                local_position: local.position,
+                exchange_position: 0,
+            })
+            .collect();
+        for exchange in exchange_positions {
+            let local = positions
+                .get(&exchange.ticker)
+                .map(|p| p.position)
+                .unwrap_or_default();
+            if local != exchange.position as i64 {
+                drifts.push(PositionDrift {
+                    ticker: exchange.ticker.clone(),
+                    local_position: local,
+                    exchange_position: exchange.position as i64,
+                });
+            }
+        }
+        drifts
+    }
+
+    /// Overwrites the local positions with positions reported by the exchange.
+    ///
+    /// Markets held locally but missing from `exchange_positions` are flattened.
+    pub fn replace_positions(&self, exchange_positions: &[MarketPosition]) {
+        {
+            let mut positions = self.lock();
+            for local in positions.values_mut() {
+                if !exchange_positions.iter().any(|p| p.ticker == local.ticker) {
+                    local.position = 0;
+                    local.cost_basis = 0;
+                }
+            }
+        }
+        for exchange in exchange_positions {
+            self.set_position(exchange);
+        }
+    }
+
+    /// Reconciles with the exchange every `every` until the returned handle is aborted or the instance shuts down.
+    ///
+    /// Drifts are logged as warnings, failed reconciliations are logged and retried on the next tick.
+    pub fn spawn_reconciliation(&self, kalshi: Kalshi, every: Duration) -> JoinHandle<()> {
+        let tracker = self.clone();
+        let shutdown = kalshi.shutdown_handle().clone();
+        shutdown.supervise(
+            "position reconciliation",
+            RestartPolicy::default(),
+            move || {
+                let tracker = tracker.clone();
+                let kalshi = kalshi.clone();
+                async move {
+                    let mut interval = tokio::time::interval(every);
+                    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                    loop {
+                        interval.tick().await;
+                        match tracker.reconcile(&kalshi).await {
+                            Ok(drifts) => {
+                                for drift in drifts {
+                                    log::warn!(
+                                        "Position in {} drifted, local {} exchange {}",
+                                        drift.ticker,
+                                        drift.local_position,
+                                        drift.exchange_position
+                                    );
+                                }
+                            }
+                            Err(e) => log::warn!("Position reconciliation failed: {}", e),
+                        }
+                    }
+                }
+            },
+        )
+    }
+}
+
+/// Every position the exchange reports for the account.
+pub(crate) async fn fetch_positions(kalshi: &Kalshi) -> Result<Vec<MarketPosition>, KalshiError> {
+    let mut exchange_positions = Vec::new();
+    let pages = kalshi
+        .get_user_positions(None, Some(1000), None, None, None, None)
+        .await;
+    futures::pin_mut!(pages);
+    while let Some(page) = pages.next().await {
+        let (_, mut page) = page?;
+        exchange_positions.append(&mut page);
+    }
+    Ok(exchange_positions)
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::{
+        client::KalshiWebsocketClient,
+        responses::{KalshiFillMessage, KalshiWebsocketResponse},
+    };
+    use tokio::sync::broadcast::error::RecvError;
+
+    impl PositionTracker {
+        /// Applies a websocket fill message.
+        pub fn on_fill(&self, fill: &KalshiFillMessage) {
+            let action = match fill.action.as_str() {
+                "buy" => Action::Buy,
+                "sell" => Action::Sell,
+                other => {
+                    log::warn!("Ignoring fill {} with action {}", fill.trade_id, other);
+                    return;
+                }
+            };
+            let side: Side = fill.side.into();
+            let price = match side {
+                Side::Yes => fill.yes_price,
+                Side::No => fill.no_price,
+            };
+            self.apply_fill(
+                &fill.market_ticker,
+                side,
+                action,
+                fill.count as i64,
+                price as i64,
+            );
+        }
+
+        /// Applies every fill delivered by `ws_client` from now on, until the client shuts down.
+        ///
+        /// The client must be subscribed to the `fill` channel.
+        pub fn follow_fills(&self, ws_client: &KalshiWebsocketClient) -> JoinHandle<()> {
+            let tracker = self.clone();
+            let receiver = Arc::new(tokio::sync::Mutex::new(ws_client.receiver()));
+            let shutdown = ws_client.kalshi().shutdown_handle().clone();
+            shutdown.supervise("position fills", RestartPolicy::default(), move || {
+                let tracker = tracker.clone();
+                let receiver = Arc::clone(&receiver);
+                async move {
+                    // Restarts pick up where the panicked run stopped
+                    let mut receiver = receiver.lock_owned().await;
+                    loop {
+                        match receiver.recv().await {
+                            Ok(Ok(KalshiWebsocketResponse::Fill { msg, .. })) => {
+                                tracker.on_fill(&msg)
+                            }
+                            Ok(_) => {}
+                            Err(RecvError::Lagged(skipped)) => {
+                                log::warn!("Position tracker lagged, skipped {} messages", skipped);
+                            }
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TICKER: &str = "KXHIGHNY-25OCT02-B80.5";
+
+    #[test]
+    fn test_position_from_fills() {
+        let tracker = PositionTracker::new();
+        tracker.apply_fill(TICKER, Side::Yes, Action::Buy, 10, 40);
+        tracker.apply_fill(TICKER, Side::Yes, Action::Buy, 10, 50);
+        let position = tracker.position(TICKER);
+        assert_eq!(position.position, 20);
+        assert_eq!(position.cost_basis, 900);
+        assert_eq!(position.average_entry_price(), Some(45.0));
+
+        // Buying no at 40 sells yes at 60
+        tracker.apply_fill(TICKER, Side::No, Action::Buy, 5, 40);
+        let position = tracker.position(TICKER);
+        assert_eq!(position.position, 15);
+        assert_eq!(position.cost_basis, 675);
+        assert_eq!(position.realized_pnl, 5 * 60 - 225);
+
+        // Selling through zero flips to a no position
+        tracker.apply_fill(TICKER, Side::Yes, Action::Sell, 20, 70);
+        let position = tracker.position(TICKER);
+        assert_eq!(position.position, -5);
+        assert_eq!(position.side(), Some(Side::No));
+        assert_eq!(position.cost_basis, 5 * 30);
+        assert_eq!(tracker.total_exposure(), 150);
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{Action, Book, OrderTracker, Side, TrackedOrder};
+
+/// Estimates how many contracts rest ahead of the account's orders at their price level.
+///
+/// Keeps the markets' books from orderbook snapshots and deltas and takes the account's orders
+/// from an [`OrderTracker`]. An order is first seen with everything already resting at its level
+/// ahead of it, the account's other orders there excluded, and contracts joining the level later
+/// queue behind it. Contracts leaving the level, canceled or filled, are taken from the queue
+/// ahead of each order in proportion to its share of the level, as deltas don't tell where in the
+/// queue they left from. Once an order is filled nothing is ahead of it anymore.
+///
+/// Orders placed right before the delta adding them to the book arrived are seen with themselves
+/// ahead, so estimates err towards a longer queue.
+///
+/// The estimator is a cheap handle, clones share the same state.
+///
+/// ```
+/// let queue = QueueEstimator::new(tracker.clone());
+/// queue.follow_books(&ws_client);
+///
+/// let order = kalshi.create_order(/* ... */).await?;
+/// if let Some(ahead) = queue.estimated_queue_ahead(&order.order_id) {
+///     println!("{} contracts ahead", ahead);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueueEstimator {
+    tracker: OrderTracker,
+    state: Arc<Mutex<QueueState>>,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    books: HashMap<String, Book>,
+    /// Contracts of others resting at each level an order of the account rests at
+    levels: HashMap<Level, i64>,
+    orders: HashMap<String, QueuedOrder>,
+}
+
+/// A market, book side and price.
+type Level = (String, Side, u32);
+
+#[derive(Debug)]
+struct QueuedOrder {
+    level: Level,
+    ahead: f64,
+    /// Contracts filled when the order was first seen
+    filled_count: i32,
+}
+
+/// The book level an order rests at, sells rest as bids of the other side.
+fn level_of(order: &TrackedOrder) -> Level {
+    match (order.action, order.side) {
+        (Action::Buy, side) => (order.ticker.clone(), side, order.price as u32),
+        (Action::Sell, Side::Yes) => (order.ticker.clone(), Side::No, 100 - order.price as u32),
+        (Action::Sell, Side::No) => (order.ticker.clone(), Side::Yes, 100 - order.price as u32),
+    }
+}
+
+impl QueueEstimator {
+    pub fn new(tracker: OrderTracker) -> Self {
+        QueueEstimator {
+            tracker,
+            state: Arc::new(Mutex::new(QueueState::default())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Replaces a market's book, for example after an orderbook snapshot.
+    ///
+    /// Orders can't have more ahead of them than the level now holds.
+    pub fn on_book(&self, book: Book) {
+        let ticker = book.market_ticker.clone();
+        let mut state = self.lock();
+        state.books.insert(ticker.clone(), book);
+        self.sync_orders(&mut state, &ticker);
+        let open = self.tracker.open_orders();
+        let levels: Vec<Level> = state
+            .levels
+            .keys()
+            .filter(|level| level.0 == ticker)
+            .cloned()
+            .collect();
+        for level in levels {
+            let others = state.others_at(&level, &open);
+            state.levels.insert(level.clone(), others);
+            for order in state.orders.values_mut() {
+                if order.level == level {
+                    order.ahead = order.ahead.min(others as f64);
+                }
+            }
+        }
+    }
+
+    /// Changes the quantity resting at a level of a market's book by `delta`.
+    pub fn apply_change(&self, market_ticker: &str, side: Side, price: u32, delta: i64) {
+        let mut state = self.lock();
+        state
+            .books
+            .entry(market_ticker.to_string())
+            .or_insert_with(|| Book::new(market_ticker))
+            .apply_change(side, price, delta);
+        self.sync_orders(&mut state, market_ticker);
+
+        let level = (market_ticker.to_string(), side, price);
+        let Some(before) = state.levels.get(&level).copied() else {
+            return;
+        };
+        let others = state.others_at(&level, &self.tracker.open_orders());
+        state.levels.insert(level.clone(), others);
+        if others >= before || before <= 0 {
+            return;
+        }
+        let left = (before - others) as f64;
+        for order in state.orders.values_mut() {
+            if order.level == level {
+                order.ahead = (order.ahead - left * order.ahead / before as f64).max(0.0);
+            }
+        }
+    }
+
+    /// The estimated number of contracts resting ahead of an open order, `None` for orders that
+    /// aren't open or resting in a market without a book.
+    pub fn estimated_queue_ahead(&self, order_id: &str) -> Option<i64> {
+        let tracked = self.tracker.get(order_id).filter(TrackedOrder::is_open)?;
+        let mut state = self.lock();
+        if !state.books.contains_key(&tracked.ticker) {
+            return None;
+        }
+        self.sync_orders(&mut state, &tracked.ticker);
+        let order = state.orders.get(order_id)?;
+        if tracked.filled_count > order.filled_count {
+            return Some(0);
+        }
+        Some(order.ahead.round() as i64)
+    }
+
+    /// The book of a market as built from the messages seen.
+    pub fn book(&self, market_ticker: &str) -> Option<Book> {
+        self.lock().books.get(market_ticker).cloned()
+    }
+
+    /// Starts estimating the open orders of a market not seen before and forgets closed ones.
+    fn sync_orders(&self, state: &mut QueueState, market_ticker: &str) {
+        let open = self.tracker.open_orders();
+        state.orders.retain(|order_id, order| {
+            order.level.0 != market_ticker || open.iter().any(|o| &o.order_id == order_id)
+        });
+        let known: Vec<&TrackedOrder> = open
+            .iter()
+            .filter(|order| order.ticker == market_ticker)
+            .filter(|order| state.orders.contains_key(&order.order_id))
+            .collect();
+        let new: Vec<&TrackedOrder> = open
+            .iter()
+            .filter(|order| order.ticker == market_ticker)
+            .filter(|order| !state.orders.contains_key(&order.order_id))
+            .collect();
+        for order in new {
+            let level = level_of(order);
+            let (book_side, price) = (level.1, level.2);
+            let resting = state
+                .books
+                .get(market_ticker)
+                .and_then(|book| match book_side {
+                    Side::Yes => book.yes.get(&price),
+                    Side::No => book.no.get(&price),
+                })
+                .copied()
+                .unwrap_or_default();
+            // The order itself isn't counted as resting yet
+            let own: i64 = known
+                .iter()
+                .filter(|known| level_of(known) == level)
+                .map(|known| known.remaining_count as i64)
+                .sum();
+            let ahead = (resting - own).max(0);
+            state.levels.entry(level.clone()).or_insert(ahead);
+            state.orders.insert(
+                order.order_id.clone(),
+                QueuedOrder {
+                    level,
+                    ahead: ahead as f64,
+                    filled_count: order.filled_count,
+                },
+            );
+        }
+        let orders = &state.orders;
+        state.levels.retain(|level, _| {
+            level.0 != market_ticker || orders.values().any(|o| &o.level == level)
+        });
+    }
+}
+
+impl QueueState {
+    /// Contracts resting at a level that aren't the account's.
+    fn others_at(&self, level: &Level, open: &[TrackedOrder]) -> i64 {
+        let resting = self
+            .books
+            .get(&level.0)
+            .and_then(|book| match level.1 {
+                Side::Yes => book.yes.get(&level.2),
+                Side::No => book.no.get(&level.2),
+            })
+            .copied()
+            .unwrap_or_default();
+        let own: i64 = open
+            .iter()
+            .filter(|order| self.orders.contains_key(&order.order_id) && level_of(order) == *level)
+            .map(|order| order.remaining_count as i64)
+            .sum();
+        (resting - own).max(0)
+    }
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::{client::KalshiWebsocketClient, responses::KalshiWebsocketResponse};
+    use crate::RestartPolicy;
+    use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
+
+    impl QueueEstimator {
+        /// Applies an orderbook snapshot or delta, other messages are ignored.
+        pub fn on_message(&self, msg: &KalshiWebsocketResponse) {
+            match msg {
+                KalshiWebsocketResponse::OrderbookSnapshot { msg, .. } => {
+                    self.on_book(Book::from(msg))
+                }
+                KalshiWebsocketResponse::OrderbookDelta { msg, .. } => self.apply_change(
+                    &msg.market_ticker,
+                    msg.side.into(),
+                    msg.price,
+                    msg.delta as i64,
+                ),
+                _ => {}
+            }
+        }
+
+        /// Applies every orderbook message delivered by `ws_client` from now on, until the
+        /// client shuts down.
+        ///
+        /// The client must be subscribed to the `orderbook_delta` channel of the markets the
+        /// account's orders rest in.
+        pub fn follow_books(&self, ws_client: &KalshiWebsocketClient) -> JoinHandle<()> {
+            let estimator = self.clone();
+            let receiver = Arc::new(tokio::sync::Mutex::new(ws_client.receiver()));
+            let shutdown = ws_client.kalshi().shutdown_handle().clone();
+            shutdown.supervise(
+                "queue position books",
+                RestartPolicy::default(),
+                move || {
+                    let estimator = estimator.clone();
+                    let receiver = Arc::clone(&receiver);
+                    async move {
+                        // Restarts pick up where the panicked run stopped
+                        let mut receiver = receiver.lock_owned().await;
+                        loop {
+                            match receiver.recv().await {
+                                Ok(Ok(msg)) => estimator.on_message(&msg),
+                                Ok(Err(_)) => {}
+                                Err(RecvError::Lagged(skipped)) => {
+                                    log::warn!(
+                                        "Queue estimator lagged, skipped {} messages",
+                                        skipped
+                                    );
+                                }
+                                Err(RecvError::Closed) => break,
+                            }
+                        }
+                    }
+                },
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::fixtures;
+    use crate::Order;
+
+    fn order(order_id: &str, yes_price: i64, remaining_count: i32) -> Order {
+        serde_json::from_value(fixtures::order(order_id, yes_price, remaining_count)).unwrap()
+    }
+
+    #[test]
+    fn test_queue_ahead_shrinks_with_level() {
+        let tracker = OrderTracker::new();
+        let queue = QueueEstimator::new(tracker.clone());
+        let ticker = fixtures::MARKET_TICKER;
+        let mut book = Book::new(ticker);
+        book.yes.insert(40, 100);
+        queue.on_book(book);
+
+        tracker.on_order_created(&order("a", 40, 10));
+        assert_eq!(queue.estimated_queue_ahead("a"), Some(100));
+
+        // Our own order joins the level, then others queue behind it
+        queue.apply_change(ticker, Side::Yes, 40, 10);
+        queue.apply_change(ticker, Side::Yes, 40, 50);
+        assert_eq!(queue.estimated_queue_ahead("a"), Some(100));
+
+        // 30 of the 150 others leave, 20 of them from ahead
+        queue.apply_change(ticker, Side::Yes, 40, -30);
+        assert_eq!(queue.estimated_queue_ahead("a"), Some(80));
+
+        // A snapshot caps what can be ahead
+        let mut book = Book::new(ticker);
+        book.yes.insert(40, 40);
+        queue.on_book(book);
+        assert_eq!(queue.estimated_queue_ahead("a"), Some(30));
+
+        tracker.on_order_filled("a", 2);
+        assert_eq!(queue.estimated_queue_ahead("a"), Some(0));
+        tracker.on_order_filled("a", 8);
+        assert_eq!(queue.estimated_queue_ahead("a"), None);
+        assert_eq!(queue.estimated_queue_ahead("unknown"), None);
+    }
+}
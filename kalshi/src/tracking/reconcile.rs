@@ -0,0 +1,271 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use super::positions::fetch_positions;
+use crate::{
+    Kalshi, KalshiError, Order, OrderTracker, PositionDrift, PositionTracker, RestartPolicy,
+    TrackedOrder,
+};
+
+/// Orders fetched per page while listing resting orders.
+const ORDERS_PAGE_SIZE: i32 = 1000;
+
+/// A difference between the local trackers and the exchange, found by [`Reconciler::reconcile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// An order resting on the exchange that the order tracker doesn't know.
+    UntrackedOrder(TrackedOrder),
+    /// An order whose status or remaining count differs from the exchange's.
+    OrderMismatch {
+        local: TrackedOrder,
+        exchange: TrackedOrder,
+    },
+    /// An order open locally that the exchange doesn't know.
+    UnknownOrder(TrackedOrder),
+    /// A position that differs from the exchange's.
+    Position(PositionDrift),
+}
+
+/// Diffs the local [`OrderTracker`] and [`PositionTracker`] against the exchange.
+///
+/// Trackers fed from the websocket drift when messages are missed, typically around a reconnect.
+/// A reconciliation fetches the resting orders and positions from the exchange, reports every
+/// discrepancy and, with [`Reconciler::repair`], overwrites the local state with the exchange's.
+/// Orders open locally but no longer resting are fetched one by one to learn how they ended.
+///
+/// ```
+/// let reconciler = Reconciler::new()
+///     .orders(order_tracker.clone())
+///     .positions(position_tracker.clone())
+///     .repair(true);
+///
+/// // After the websocket reconnected
+/// for discrepancy in reconciler.reconcile(&kalshi_instance).await? {
+///     println!("Repaired {:?}", discrepancy);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Reconciler {
+    orders: Option<OrderTracker>,
+    positions: Option<PositionTracker>,
+    repair: bool,
+}
+
+impl Reconciler {
+    /// A reconciler checking nothing, attach trackers with [`Reconciler::orders`] and [`Reconciler::positions`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn orders(mut self, tracker: OrderTracker) -> Self {
+        self.orders = Some(tracker);
+        self
+    }
+
+    pub fn positions(mut self, tracker: PositionTracker) -> Self {
+        self.positions = Some(tracker);
+        self
+    }
+
+    /// Overwrites the local state with the exchange's when they differ, off by default.
+    pub fn repair(mut self, repair: bool) -> Self {
+        self.repair = repair;
+        self
+    }
+
+    /// Compares the attached trackers with the exchange, returning every discrepancy.
+    pub async fn reconcile(&self, kalshi: &Kalshi) -> Result<Vec<Discrepancy>, KalshiError> {
+        let mut discrepancies = Vec::new();
+        if let Some(tracker) = &self.orders {
+            discrepancies.append(&mut self.reconcile_orders(kalshi, tracker).await?);
+        }
+        if let Some(tracker) = &self.positions {
+            let exchange_positions = fetch_positions(kalshi).await?;
+            let drifts = tracker.diff(&exchange_positions);
+            if self.repair {
+                tracker.replace_positions(&exchange_positions);
+            }
+            discrepancies.extend(drifts.into_iter().map(Discrepancy::Position));
+        }
+        Ok(discrepancies)
+    }
+
+    async fn reconcile_orders(
+        &self,
+        kalshi: &Kalshi,
+        tracker: &OrderTracker,
+    ) -> Result<Vec<Discrepancy>, KalshiError> {
+        let mut resting = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next_cursor, mut page) = kalshi
+                .get_multiple_orders(
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some("resting".to_string()),
+                    Some(ORDERS_PAGE_SIZE),
+                    cursor,
+                )
+                .await?;
+            resting.append(&mut page);
+            match next_cursor {
+                Some(next) if !next.is_empty() => cursor = Some(next),
+                _ => break,
+            }
+        }
+
+        let (mut discrepancies, closed) = diff_orders(tracker, &resting);
+        if self.repair {
+            for order in &resting {
+                tracker.on_order_reconciled(order);
+            }
+        }
+        for local in closed {
+            match kalshi.get_single_order(&local.order_id).await {
+                Ok(order) => {
+                    let exchange = TrackedOrder::from_order(&order);
+                    if self.repair {
+                        tracker.on_order_reconciled(&order);
+                    }
+                    discrepancies.push(Discrepancy::OrderMismatch { local, exchange });
+                }
+                Err(e) => {
+                    log::debug!("Could not fetch order {}: {}", local.order_id, e);
+                    if self.repair {
+                        tracker.remove(&local.order_id);
+                    }
+                    discrepancies.push(Discrepancy::UnknownOrder(local));
+                }
+            }
+        }
+        Ok(discrepancies)
+    }
+
+    /// Reconciles every `every` until the returned handle is aborted or the instance shuts down.
+    ///
+    /// Discrepancies are logged as warnings, failed reconciliations are logged and retried on the next tick.
+    pub fn spawn(self, kalshi: Kalshi, every: Duration) -> JoinHandle<()> {
+        let shutdown = kalshi.shutdown_handle().clone();
+        shutdown.supervise("reconciliation", RestartPolicy::default(), move || {
+            let reconciler = self.clone();
+            let kalshi = kalshi.clone();
+            async move {
+                let mut interval = tokio::time::interval(every);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    interval.tick().await;
+                    match reconciler.reconcile(&kalshi).await {
+                        Ok(discrepancies) => {
+                            for discrepancy in discrepancies {
+                                log::warn!(
+                                    "Local state differs from the exchange: {:?}",
+                                    discrepancy
+                                );
+                            }
+                        }
+                        Err(e) => log::warn!("Reconciliation failed: {}", e),
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Compares tracked orders with the orders resting on the exchange.
+///
+/// Returns the discrepancies found and the orders open locally but not resting, whose fate has
+/// to be fetched.
+fn diff_orders(tracker: &OrderTracker, resting: &[Order]) -> (Vec<Discrepancy>, Vec<TrackedOrder>) {
+    let mut discrepancies = Vec::new();
+    for order in resting {
+        let exchange = TrackedOrder::from_order(order);
+        match tracker.get(&order.order_id) {
+            None => discrepancies.push(Discrepancy::UntrackedOrder(exchange)),
+            Some(local)
+                if local.status != exchange.status
+                    || local.remaining_count != exchange.remaining_count =>
+            {
+                discrepancies.push(Discrepancy::OrderMismatch { local, exchange })
+            }
+            Some(_) => {}
+        }
+    }
+    let closed = tracker
+        .open_orders()
+        .into_iter()
+        .filter(|local| !resting.iter().any(|o| o.order_id == local.order_id))
+        .collect();
+    (discrepancies, closed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Action, MarketPosition, Side};
+
+    fn order(order_id: &str, remaining: i32) -> Order {
+        serde_json::from_value(serde_json::json!({
+            "order_id": order_id,
+            "ticker": "KXHIGHNY-25OCT02-B80.5",
+            "status": "resting",
+            "yes_price": 40,
+            "no_price": 60,
+            "remaining_count": remaining,
+            "action": "buy",
+            "side": "yes",
+            "type": "limit",
+            "client_order_id": "mm-1",
+            "order_group_id": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diff_finds_missed_orders_and_fills() {
+        let tracker = OrderTracker::new();
+        tracker.on_order_created(&order("a", 10));
+        tracker.on_order_created(&order("b", 5));
+        tracker.on_order_created(&order("c", 5));
+
+        // A fill of a was missed, b is gone, d was placed elsewhere
+        let (discrepancies, closed) =
+            diff_orders(&tracker, &[order("a", 7), order("c", 5), order("d", 1)]);
+        assert!(matches!(
+            &discrepancies[..],
+            [
+                Discrepancy::OrderMismatch { local, exchange },
+                Discrepancy::UntrackedOrder(untracked),
+            ] if local.remaining_count == 10 && exchange.remaining_count == 7 && untracked.order_id == "d"
+        ));
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].order_id, "b");
+
+        tracker.on_order_reconciled(&order("a", 7));
+        assert_eq!(tracker.get("a").unwrap().remaining_count, 7);
+
+        let positions = PositionTracker::new();
+        positions.apply_fill("KXHIGHNY-25OCT02-B80.5", Side::Yes, Action::Buy, 3, 40);
+        let exchange = MarketPosition {
+            fees_paid: 0,
+            market_exposure: 200,
+            position: 5,
+            realized_pnl: 0,
+            resting_orders_count: 0,
+            ticker: "KXHIGHNY-25OCT02-B80.5".to_string(),
+            total_traded: 200,
+        };
+        assert_eq!(
+            positions.diff(std::slice::from_ref(&exchange)),
+            vec![PositionDrift {
+                ticker: "KXHIGHNY-25OCT02-B80.5".to_string(),
+                local_position: 3,
+                exchange_position: 5,
+            }]
+        );
+        positions.replace_positions(&[exchange]);
+        assert_eq!(positions.position("KXHIGHNY-25OCT02-B80.5").position, 5);
+    }
+}
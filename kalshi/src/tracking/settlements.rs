@@ -0,0 +1,409 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use tokio::sync::broadcast;
+
+use crate::{Kalshi, KalshiError, PositionTracker, Settlement};
+
+/// Settlements fetched per page while looking for new ones.
+const SETTLEMENTS_PAGE_SIZE: i64 = 100;
+
+/// How a position in a market was settled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionSettlement {
+    pub ticker: String,
+    /// The outcome of the market, `yes`, `no` or `void`.
+    pub result: String,
+    pub yes_count: i64,
+    pub no_count: i64,
+    /// What the position paid out, in cents.
+    pub revenue: i64,
+    /// What the position cost, in cents.
+    pub cost: i64,
+    pub settled_time: String,
+}
+
+impl PositionSettlement {
+    /// Revenue minus cost, in cents.
+    pub fn pnl(&self) -> i64 {
+        self.revenue - self.cost
+    }
+}
+
+impl From<&Settlement> for PositionSettlement {
+    fn from(settlement: &Settlement) -> Self {
+        PositionSettlement {
+            ticker: settlement.ticker.clone(),
+            result: settlement.market_result.clone(),
+            yes_count: settlement.yes_count,
+            no_count: settlement.no_count,
+            revenue: settlement.revenue,
+            cost: settlement.yes_total_cost + settlement.no_total_cost,
+            settled_time: settlement.settled_time.clone(),
+        }
+    }
+}
+
+/// A settlement of a market the account holds, see [`SettlementWatcher::events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettlementEvent {
+    /// The outcome of a market was determined, it pays out once it's settled.
+    ///
+    /// `position` is the net yes position from the attached [`PositionTracker`], negative for no.
+    Determined {
+        ticker: String,
+        result: String,
+        position: Option<i64>,
+    },
+    /// A position was settled and paid out.
+    Settled(PositionSettlement),
+}
+
+#[derive(Debug, Default)]
+struct WatcherState {
+    /// Markets whose settlement was already reported
+    reported: HashSet<String>,
+}
+
+/// Reports settlements of the account's positions as soon as they happen.
+///
+/// The websocket `market_lifecycle_v2` channel announces when markets are determined and settled
+/// but knows nothing about the account, the portfolio settlements endpoint knows what the account
+/// was paid but has to be polled. The watcher combines both: determinations are reported right
+/// away, and each settlement announcement fetches the new portfolio settlements.
+///
+/// With a [`PositionTracker`] attached, determinations and settlements of markets the account has
+/// no position in are skipped without a request.
+///
+/// The watcher is a cheap handle, clones share the same state.
+///
+/// ```
+/// let watcher = SettlementWatcher::new();
+/// watcher.set_position_tracker(positions.clone());
+/// watcher.seed(&kalshi_instance).await?;
+/// watcher.follow_lifecycle(kalshi_instance.clone(), &mut ws_client).await?;
+///
+/// let mut events = watcher.events();
+/// while let Ok(SettlementEvent::Settled(settlement)) = events.recv().await {
+///     println!("{} settled {} for {} cents", settlement.ticker, settlement.result, settlement.revenue);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SettlementWatcher {
+    state: Arc<Mutex<WatcherState>>,
+    positions: Arc<Mutex<Option<PositionTracker>>>,
+    events: broadcast::Sender<SettlementEvent>,
+}
+
+impl Default for SettlementWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SettlementWatcher {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(1024);
+        SettlementWatcher {
+            state: Arc::new(Mutex::new(WatcherState::default())),
+            positions: Arc::new(Mutex::new(None)),
+            events,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, WatcherState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    fn emit(&self, event: SettlementEvent) {
+        // No receivers is fine, events are optional
+        let _ = self.events.send(event);
+    }
+
+    /// Subscribes to settlements, every receiver sees every event from the moment it was created.
+    pub fn events(&self) -> broadcast::Receiver<SettlementEvent> {
+        self.events.subscribe()
+    }
+
+    /// Uses `tracker` to skip markets the account has no position in.
+    pub fn set_position_tracker(&self, tracker: PositionTracker) {
+        *self.positions.lock().unwrap_or_else(|p| p.into_inner()) = Some(tracker);
+    }
+
+    /// The net yes position in a market, `None` without a position tracker.
+    fn position(&self, ticker: &str) -> Option<i64> {
+        self.positions
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .as_ref()
+            .map(|tracker| tracker.position(ticker).position)
+    }
+
+    /// Marks the settlements already in the portfolio as reported, so only later ones are.
+    pub async fn seed(&self, kalshi: &Kalshi) -> Result<(), KalshiError> {
+        let mut cursor = None;
+        loop {
+            let (next, settlements) = kalshi
+                .get_portfolio_settlements(
+                    Some(SETTLEMENTS_PAGE_SIZE),
+                    cursor,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            self.lock()
+                .reported
+                .extend(settlements.iter().map(|s| s.ticker.clone()));
+            match next {
+                Some(next) if !next.is_empty() && !settlements.is_empty() => cursor = Some(next),
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Reports a market's determination, returning whether it was reported.
+    ///
+    /// Skipped when the attached position tracker has no position in the market.
+    pub fn on_determined(&self, ticker: &str, result: &str) -> bool {
+        let position = self.position(ticker);
+        if position == Some(0) {
+            return false;
+        }
+        self.emit(SettlementEvent::Determined {
+            ticker: ticker.to_string(),
+            result: result.to_string(),
+            position,
+        });
+        true
+    }
+
+    /// Fetches the portfolio settlements and reports those not reported yet, newest first.
+    ///
+    /// Pages are fetched until one only contains reported settlements.
+    pub async fn poll(&self, kalshi: &Kalshi) -> Result<Vec<PositionSettlement>, KalshiError> {
+        let mut new = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next, settlements) = kalshi
+                .get_portfolio_settlements(
+                    Some(SETTLEMENTS_PAGE_SIZE),
+                    cursor,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            let mut any_new = false;
+            {
+                let mut state = self.lock();
+                for settlement in &settlements {
+                    if state.reported.insert(settlement.ticker.clone()) {
+                        any_new = true;
+                        new.push(PositionSettlement::from(settlement));
+                    }
+                }
+            }
+            match next {
+                Some(next) if !next.is_empty() && any_new => cursor = Some(next),
+                _ => break,
+            }
+        }
+        for settlement in &new {
+            self.emit(SettlementEvent::Settled(settlement.clone()));
+        }
+        Ok(new)
+    }
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::websockets::{
+        client::KalshiWebsocketClient,
+        responses::{KalshiMarketLifecycleMessage, KalshiWebsocketResponse},
+    };
+    use crate::{KalshiChannel, RestartPolicy};
+    use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
+
+    /// Attempts at finding a settled market in the portfolio settlements, which can lag the announcement.
+    const SETTLEMENT_POLL_ATTEMPTS: u32 = 3;
+
+    impl SettlementWatcher {
+        /// Reports settlements announced by the `market_lifecycle_v2` channel until the client shuts down.
+        ///
+        /// Subscribes to the channel first. Messages missed because the watcher fell behind are
+        /// caught up on by fetching the portfolio settlements.
+        pub async fn follow_lifecycle(
+            &self,
+            kalshi: Kalshi,
+            ws_client: &mut KalshiWebsocketClient,
+        ) -> Result<JoinHandle<()>, KalshiError> {
+            let receiver = Arc::new(tokio::sync::Mutex::new(ws_client.receiver()));
+            ws_client
+                .ensure_subscribed(vec![KalshiChannel::MarketLifecycleV2], vec![])
+                .await
+                .map_err(|e| KalshiError::InternalError(e.to_string()))?;
+
+            let watcher = self.clone();
+            let shutdown = ws_client.kalshi().shutdown_handle().clone();
+            Ok(shutdown.supervise(
+                "settlement lifecycle",
+                RestartPolicy::default(),
+                move || {
+                    let watcher = watcher.clone();
+                    let kalshi = kalshi.clone();
+                    let receiver = Arc::clone(&receiver);
+                    async move {
+                        // Restarts pick up where the panicked run stopped
+                        let mut receiver = receiver.lock_owned().await;
+                        loop {
+                            match receiver.recv().await {
+                                Ok(Ok(KalshiWebsocketResponse::MarketLifecycleV2 {
+                                    msg, ..
+                                })) => match msg {
+                                    KalshiMarketLifecycleMessage::Determined {
+                                        market_ticker,
+                                        result,
+                                        ..
+                                    } => {
+                                        watcher.on_determined(&market_ticker, &result);
+                                    }
+                                    KalshiMarketLifecycleMessage::Settled {
+                                        market_ticker, ..
+                                    } if watcher.is_relevant(&market_ticker) => {
+                                        watcher.poll_until_settled(&kalshi, market_ticker.into());
+                                    }
+                                    _ => {}
+                                },
+                                Ok(_) => {}
+                                Err(RecvError::Lagged(skipped)) => {
+                                    log::warn!(
+                                        "Settlement watcher lagged, skipped {} messages",
+                                        skipped
+                                    );
+                                    if let Err(e) = watcher.poll(&kalshi).await {
+                                        log::warn!("Could not fetch settlements: {}", e);
+                                    }
+                                }
+                                Err(RecvError::Closed) => break,
+                            }
+                        }
+                    }
+                },
+            ))
+        }
+
+        /// Whether a settled market needs the portfolio settlements to be fetched.
+        fn is_relevant(&self, ticker: &str) -> bool {
+            !self.lock().reported.contains(ticker) && self.position(ticker) != Some(0)
+        }
+
+        /// Polls in the background until the settlement of `ticker` shows up in the portfolio.
+        fn poll_until_settled(&self, kalshi: &Kalshi, ticker: String) {
+            let watcher = self.clone();
+            let kalshi = kalshi.clone();
+            let shutdown = kalshi.shutdown_handle().clone();
+            shutdown.spawn("settlement poll", async move {
+                for attempt in 0..SETTLEMENT_POLL_ATTEMPTS {
+                    tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+                    if let Err(e) = watcher.poll(&kalshi).await {
+                        log::warn!("Could not fetch settlements: {}", e);
+                    }
+                    if watcher.lock().reported.contains(&ticker) {
+                        return;
+                    }
+                }
+                log::debug!("No settlement of {} in the portfolio", ticker);
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use crate::{Action, Side};
+    use reqwest::Method;
+
+    #[test]
+    fn test_determinations_skip_flat_markets() {
+        let watcher = SettlementWatcher::new();
+        let positions = PositionTracker::new();
+        positions.apply_fill("KXHIGHNY-25OCT02-B80.5", Side::No, Action::Buy, 5, 60);
+        watcher.set_position_tracker(positions);
+        let mut events = watcher.events();
+
+        assert!(!watcher.on_determined("KXHIGHNY-25OCT02-B82.5", "no"));
+        assert!(watcher.on_determined("KXHIGHNY-25OCT02-B80.5", "no"));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SettlementEvent::Determined {
+                ticker: "KXHIGHNY-25OCT02-B80.5".to_string(),
+                result: "no".to_string(),
+                position: Some(-5),
+            }
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_seed_stops_at_last_page() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let watcher = SettlementWatcher::new();
+
+        watcher.seed(&kalshi).await.unwrap();
+        assert!(watcher.lock().reported.contains(fixtures::MARKET_TICKER));
+        assert_eq!(
+            server
+                .requests_to(Method::GET, "/portfolio/settlements")
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_poll_reports_new_settlements_once() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let watcher = SettlementWatcher::new();
+        watcher.seed(&kalshi).await.unwrap();
+        let mut events = watcher.events();
+
+        assert!(watcher.poll(&kalshi).await.unwrap().is_empty());
+
+        let mut page = fixtures::settlements_page();
+        let mut settled = page["settlements"][0].clone();
+        settled["ticker"] = "KXHIGHNY-25OCT02-B80.5".into();
+        page["settlements"]
+            .as_array_mut()
+            .unwrap()
+            .insert(0, settled);
+        server.respond(Method::GET, "/portfolio/settlements", 200, page);
+
+        let new = watcher.poll(&kalshi).await.unwrap();
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].ticker, "KXHIGHNY-25OCT02-B80.5");
+        assert_eq!(new[0].pnl(), 144);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SettlementEvent::Settled(new[0].clone())
+        );
+        assert!(watcher.poll(&kalshi).await.unwrap().is_empty());
+        // Seed and three polls, each a single page
+        assert_eq!(
+            server
+                .requests_to(Method::GET, "/portfolio/settlements")
+                .len(),
+            4
+        );
+    }
+}
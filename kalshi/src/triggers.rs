@@ -0,0 +1,731 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use tokio::task::JoinHandle;
+
+use crate::{
+    Kalshi, KalshiError, Market, OrderCreationField, OrderType, RestartPolicy, Side, Ticker,
+};
+
+/// The price of a market a [`TriggerCondition`] watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PriceField {
+    LastPrice,
+    YesBid,
+    YesAsk,
+}
+
+/// When a [`TriggerCondition`] is met, thresholds and distances are in cents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Crossing {
+    /// The price is at or above the threshold.
+    AtOrAbove(i64),
+    /// The price is at or below the threshold.
+    AtOrBelow(i64),
+    /// The price fell at least this far below the highest price seen since the trigger was
+    /// registered, a trailing stop on a long position.
+    TrailingBelow(i64),
+    /// The price rose at least this far above the lowest price seen since the trigger was
+    /// registered, a trailing stop on a short position.
+    TrailingAbove(i64),
+}
+
+/// A price of a market crossing a threshold.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TriggerCondition {
+    pub ticker: String,
+    pub field: PriceField,
+    pub crossing: Crossing,
+}
+
+impl TriggerCondition {
+    pub fn new(ticker: &str, field: PriceField, crossing: Crossing) -> Self {
+        TriggerCondition {
+            ticker: ticker.to_string(),
+            field,
+            crossing,
+        }
+    }
+
+    /// The watched price in `quote`, `None` for other markets or when nothing is quoted (a price of 0).
+    fn price(&self, quote: &TriggerQuote) -> Option<i64> {
+        if quote.ticker != self.ticker {
+            return None;
+        }
+        let price = match self.field {
+            PriceField::LastPrice => quote.last_price,
+            PriceField::YesBid => quote.yes_bid,
+            PriceField::YesAsk => quote.yes_ask,
+        };
+        Some(price).filter(|price| *price > 0)
+    }
+
+    /// The threshold the price is compared to, given the extreme price seen so far for trailing crossings.
+    fn threshold(&self, extreme: Option<i64>) -> Option<i64> {
+        match self.crossing {
+            Crossing::AtOrAbove(threshold) | Crossing::AtOrBelow(threshold) => Some(threshold),
+            Crossing::TrailingBelow(distance) => extreme.map(|high| high - distance),
+            Crossing::TrailingAbove(distance) => extreme.map(|low| low + distance),
+        }
+    }
+
+    /// Whether `quote` meets the condition. Prices of 0 mean nothing is quoted and never match.
+    ///
+    /// Trailing crossings are compared to `extreme`, the highest (`TrailingBelow`) or lowest
+    /// (`TrailingAbove`) price seen so far, and are never met without one.
+    pub fn is_met(&self, quote: &TriggerQuote, extreme: Option<i64>) -> bool {
+        let (Some(price), Some(threshold)) = (self.price(quote), self.threshold(extreme)) else {
+            return false;
+        };
+        match self.crossing {
+            Crossing::AtOrAbove(_) | Crossing::TrailingAbove(_) => price >= threshold,
+            Crossing::AtOrBelow(_) | Crossing::TrailingBelow(_) => price <= threshold,
+        }
+    }
+}
+
+/// The prices of a market a trigger is evaluated against, in cents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerQuote {
+    pub ticker: Ticker,
+    pub last_price: i64,
+    pub yes_bid: i64,
+    pub yes_ask: i64,
+}
+
+impl From<&Market> for TriggerQuote {
+    fn from(market: &Market) -> Self {
+        TriggerQuote {
+            ticker: Ticker::new(&market.ticker),
+            last_price: market.last_price,
+            yes_bid: market.yes_bid,
+            yes_ask: market.yes_ask,
+        }
+    }
+}
+
+/// A trigger that fired, passed to [`TriggerAction::Callback`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerFired {
+    pub id: u64,
+    pub condition: TriggerCondition,
+    pub quote: TriggerQuote,
+}
+
+/// What happens when a trigger fires.
+#[derive(Clone)]
+pub enum TriggerAction {
+    /// Sells `count` contracts of `side` at market, the usual stop-loss or take-profit.
+    MarketSell { side: Side, count: i32 },
+    /// Places an order, for exits that shouldn't cross the whole book.
+    Order(OrderCreationField),
+    /// Cancels every resting order in the market, to pull quotes.
+    CancelOrders,
+    /// Calls a function, for anything else.
+    Callback(Arc<dyn Fn(&TriggerFired) + Send + Sync>),
+}
+
+impl fmt::Debug for TriggerAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerAction::MarketSell { side, count } => f
+                .debug_struct("MarketSell")
+                .field("side", side)
+                .field("count", count)
+                .finish(),
+            TriggerAction::Order(order) => f.debug_tuple("Order").field(order).finish(),
+            TriggerAction::CancelOrders => write!(f, "CancelOrders"),
+            TriggerAction::Callback(_) => write!(f, "Callback"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Trigger {
+    id: u64,
+    condition: TriggerCondition,
+    action: TriggerAction,
+    /// Most favorable price seen, for trailing crossings
+    extreme: Option<i64>,
+}
+
+impl Trigger {
+    /// Ratchets the extreme of a trailing crossing with `quote`, then checks the condition.
+    fn observe(&mut self, quote: &TriggerQuote) -> bool {
+        if let Some(price) = self.condition.price(quote) {
+            self.extreme = match (self.condition.crossing, self.extreme) {
+                (Crossing::TrailingBelow(_), Some(high)) => Some(high.max(price)),
+                (Crossing::TrailingAbove(_), Some(low)) => Some(low.min(price)),
+                (Crossing::TrailingBelow(_) | Crossing::TrailingAbove(_), None) => Some(price),
+                _ => None,
+            };
+        }
+        self.condition.is_met(quote, self.extreme)
+    }
+}
+
+#[derive(Debug, Default)]
+struct TriggerState {
+    triggers: Vec<Trigger>,
+    next_id: u64,
+    /// When each market last received a quote from the websocket
+    last_streamed: HashMap<Ticker, Instant>,
+    /// Subscribes the markets of triggers registered once spawned on a websocket
+    #[cfg(feature = "websockets")]
+    subscriber: Option<crate::websockets::client::WsSubscriber>,
+}
+
+/// Runs actions once market prices cross thresholds, for stop-losses, take-profits and kill switches.
+///
+/// Triggers are one-shot: once a trigger's condition is met its action runs and the trigger is
+/// removed. A trigger whose action fails is put back and fires again on the next quote meeting
+/// its condition. Trailing crossings ratchet their threshold as the market moves in the position's favor
+/// and never move it back, see [`TriggerManager::threshold`]. Conditions are evaluated against the websocket `ticker` channel with
+/// `TriggerManager::spawn`, which also polls the REST api for markets the websocket hasn't
+/// quoted recently. Without a websocket, [`TriggerManager::spawn_polling`] only polls.
+///
+/// The manager is a cheap handle, clones share the same triggers.
+///
+/// ```
+/// let triggers = TriggerManager::new();
+/// // Stop-loss: sell the 100 yes contracts held once the bid drops to 35
+/// triggers.register(
+///     TriggerCondition::new("KXHIGHNY-25OCT02-B80.5", PriceField::YesBid, Crossing::AtOrBelow(35)),
+///     TriggerAction::MarketSell { side: Side::Yes, count: 100 },
+/// );
+/// // Trailing stop: exit with a limit order once the bid gives back 5 cents from its high
+/// triggers.register(
+///     TriggerCondition::new("KXHIGHNY-25OCT02-B80.5", PriceField::YesBid, Crossing::TrailingBelow(5)),
+///     TriggerAction::Order(exit_order),
+/// );
+/// triggers.spawn(kalshi_instance.clone(), &mut ws_client, Duration::from_secs(10)).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TriggerManager {
+    state: Arc<Mutex<TriggerState>>,
+}
+
+impl TriggerManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TriggerState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Adds a trigger, returning the id to cancel it with.
+    ///
+    /// Once the manager is spawned on a websocket, the trigger's market is subscribed right away.
+    pub fn register(&self, condition: TriggerCondition, action: TriggerAction) -> u64 {
+        let mut state = self.lock();
+        state.next_id += 1;
+        let id = state.next_id;
+        #[cfg(feature = "websockets")]
+        let ticker = condition.ticker.clone();
+        state.triggers.push(Trigger {
+            id,
+            condition,
+            action,
+            extreme: None,
+        });
+        drop(state);
+        #[cfg(feature = "websockets")]
+        self.subscribe_ticker(ticker);
+        id
+    }
+
+    /// Removes a trigger that hasn't fired yet, returning whether it was found.
+    pub fn cancel(&self, id: u64) -> bool {
+        let mut state = self.lock();
+        let before = state.triggers.len();
+        state.triggers.retain(|trigger| trigger.id != id);
+        state.triggers.len() != before
+    }
+
+    /// Triggers that haven't fired yet, with their ids.
+    pub fn pending(&self) -> Vec<(u64, TriggerCondition)> {
+        self.lock()
+            .triggers
+            .iter()
+            .map(|trigger| (trigger.id, trigger.condition.clone()))
+            .collect()
+    }
+
+    /// The price a pending trigger fires at, which moves with the market for trailing crossings.
+    ///
+    /// `None` if the trigger isn't pending, or is trailing and hasn't seen a quote yet.
+    pub fn threshold(&self, id: u64) -> Option<i64> {
+        let state = self.lock();
+        let trigger = state.triggers.iter().find(|trigger| trigger.id == id)?;
+        trigger.condition.threshold(trigger.extreme)
+    }
+
+    /// Markets watched by at least one pending trigger.
+    pub fn tickers(&self) -> Vec<String> {
+        let mut tickers: Vec<String> = self
+            .lock()
+            .triggers
+            .iter()
+            .map(|trigger| trigger.condition.ticker.clone())
+            .collect();
+        tickers.sort();
+        tickers.dedup();
+        tickers
+    }
+
+    /// Evaluates every pending trigger against `quote` and runs the actions of those that fire,
+    /// returning the triggers whose action succeeded.
+    ///
+    /// Failed actions are logged and their trigger is put back, to fire again on the next quote
+    /// meeting its condition.
+    pub async fn on_quote(&self, kalshi: &Kalshi, quote: &TriggerQuote) -> Vec<TriggerFired> {
+        let fired: Vec<Trigger> = {
+            let mut state = self.lock();
+            let mut fired = Vec::new();
+            for mut trigger in std::mem::take(&mut state.triggers) {
+                if trigger.observe(quote) {
+                    fired.push(trigger);
+                } else {
+                    state.triggers.push(trigger);
+                }
+            }
+            fired
+        };
+
+        let mut events = Vec::new();
+        for trigger in fired {
+            let event = TriggerFired {
+                id: trigger.id,
+                condition: trigger.condition,
+                quote: quote.clone(),
+            };
+            match run_action(kalshi, &trigger.action, &event).await {
+                Ok(()) => events.push(event),
+                Err(e) => {
+                    log::warn!(
+                        "Action of trigger {} on {} failed, retrying on the next quote: {}",
+                        event.id,
+                        event.condition.ticker,
+                        e
+                    );
+                    self.lock().triggers.push(Trigger {
+                        condition: event.condition,
+                        ..trigger
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    /// Fetches the markets the websocket hasn't quoted within `max_age` and evaluates the triggers on them.
+    pub async fn poll(&self, kalshi: &Kalshi, max_age: Duration) -> Vec<TriggerFired> {
+        let tickers = self.tickers();
+        let stale: Vec<String> = {
+            let state = self.lock();
+            tickers
+                .into_iter()
+                .filter(|ticker| {
+                    state
+                        .last_streamed
+                        .get(ticker.as_str())
+                        .map_or(true, |at| at.elapsed() > max_age)
+                })
+                .collect()
+        };
+
+        let mut events = Vec::new();
+        for ticker in stale {
+            match kalshi.get_single_market(&ticker).await {
+                Ok(market) => events.extend(self.on_quote(kalshi, &(&market).into()).await),
+                Err(e) => log::warn!("Could not poll {} for triggers: {}", ticker, e),
+            }
+        }
+        events
+    }
+
+    /// Polls every watched market through the REST api every `interval`, until the handle is
+    /// aborted or the instance shuts down.
+    pub fn spawn_polling(&self, kalshi: Kalshi, interval: Duration) -> JoinHandle<()> {
+        let triggers = self.clone();
+        let shutdown = kalshi.shutdown_handle().clone();
+        shutdown.supervise("trigger polling", RestartPolicy::default(), move || {
+            let triggers = triggers.clone();
+            let kalshi = kalshi.clone();
+            async move {
+                let mut ticks = tokio::time::interval(interval);
+                ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    ticks.tick().await;
+                    triggers.poll(&kalshi, Duration::ZERO).await;
+                }
+            }
+        })
+    }
+}
+
+/// Orders fetched per page while listing the orders to cancel.
+const ORDERS_PAGE_SIZE: i32 = 200;
+
+/// Runs a trigger's action, failing if any part of it failed.
+async fn run_action(
+    kalshi: &Kalshi,
+    action: &TriggerAction,
+    event: &TriggerFired,
+) -> Result<(), KalshiError> {
+    let ticker = &event.condition.ticker;
+    match action {
+        TriggerAction::MarketSell { side, count } => {
+            kalshi
+                .create_order(
+                    crate::Action::Sell,
+                    None,
+                    *count,
+                    *side,
+                    ticker.clone(),
+                    OrderType::Market,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+        }
+        TriggerAction::Order(order) => {
+            let order = order.clone();
+            kalshi
+                .create_order(
+                    order.action,
+                    order.client_order_id,
+                    order.count,
+                    order.side,
+                    order.ticker,
+                    order.input_type,
+                    order.buy_max_cost,
+                    order.expiration_ts,
+                    order.no_price,
+                    order.sell_position_floor,
+                    order.yes_price,
+                )
+                .await?;
+        }
+        TriggerAction::CancelOrders => {
+            // Listed before canceling, canceled orders would shift the later pages
+            let mut resting = Vec::new();
+            let mut cursor = None;
+            loop {
+                let (next, orders) = kalshi
+                    .get_multiple_orders(
+                        Some(ticker.clone()),
+                        None,
+                        None,
+                        None,
+                        Some("resting".to_string()),
+                        Some(ORDERS_PAGE_SIZE),
+                        cursor,
+                    )
+                    .await?;
+                resting.extend(orders);
+                match next {
+                    Some(next) if !next.is_empty() => cursor = Some(next),
+                    _ => break,
+                }
+            }
+            let mut failed = None;
+            for order in resting {
+                if let Err(e) = kalshi.cancel_order(&order.order_id).await {
+                    log::warn!("Could not cancel {} for a trigger: {}", order.order_id, e);
+                    failed.get_or_insert(e);
+                }
+            }
+            if let Some(e) = failed {
+                return Err(e);
+            }
+        }
+        TriggerAction::Callback(callback) => callback(event),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "websockets")]
+mod ws {
+    use super::*;
+    use crate::websockets::{
+        client::KalshiWebsocketClient,
+        responses::{KalshiTickerMessage, KalshiWebsocketResponse},
+    };
+    use crate::KalshiChannel;
+    use tokio::sync::broadcast::error::RecvError;
+
+    impl From<&KalshiTickerMessage> for TriggerQuote {
+        fn from(msg: &KalshiTickerMessage) -> Self {
+            TriggerQuote {
+                ticker: msg.market_ticker.clone(),
+                last_price: msg.price as i64,
+                yes_bid: msg.yes_bid as i64,
+                yes_ask: msg.yes_ask as i64,
+            }
+        }
+    }
+
+    impl TriggerManager {
+        /// Subscribes the ticker channel for `ticker` once spawned on a websocket.
+        pub(super) fn subscribe_ticker(&self, ticker: String) {
+            let Some(subscriber) = self.lock().subscriber.clone() else {
+                return;
+            };
+            if let Err(e) = subscriber.ensure_subscribed(vec![KalshiChannel::Ticker], vec![ticker])
+            {
+                log::warn!("Could not subscribe a trigger's market, polling it: {}", e);
+            }
+        }
+
+        /// Evaluates the triggers against the websocket `ticker` channel until the client shuts down.
+        ///
+        /// Subscribes the ticker channel for the markets watched now, and for the markets of
+        /// triggers registered later as they're registered. Every `poll_interval`, markets
+        /// without a websocket quote for that long are fetched through the REST api.
+        pub async fn spawn(
+            &self,
+            kalshi: Kalshi,
+            ws_client: &mut KalshiWebsocketClient,
+            poll_interval: Duration,
+        ) -> Result<JoinHandle<()>, KalshiError> {
+            let receiver = Arc::new(tokio::sync::Mutex::new(ws_client.receiver()));
+            self.lock().subscriber = Some(ws_client.subscriber());
+            let tickers = self.tickers();
+            if !tickers.is_empty() {
+                ws_client
+                    .ensure_subscribed(vec![KalshiChannel::Ticker], tickers)
+                    .await
+                    .map_err(|e| KalshiError::InternalError(e.to_string()))?;
+            }
+
+            let triggers = self.clone();
+            let shutdown = ws_client.kalshi().shutdown_handle().clone();
+            Ok(shutdown.supervise("triggers", RestartPolicy::default(), move || {
+                let triggers = triggers.clone();
+                let kalshi = kalshi.clone();
+                let receiver = Arc::clone(&receiver);
+                async move {
+                    // Restarts pick up where the panicked run stopped
+                    let mut receiver = receiver.lock_owned().await;
+                    let mut ticks = tokio::time::interval(poll_interval);
+                    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                    loop {
+                        tokio::select! {
+                            _ = ticks.tick() => {
+                                triggers.poll(&kalshi, poll_interval).await;
+                            }
+                            msg = receiver.recv() => match msg {
+                                Ok(Ok(KalshiWebsocketResponse::Ticker { msg, .. })) => {
+                                    triggers
+                                        .lock()
+                                        .last_streamed
+                                        .insert(msg.market_ticker.clone(), Instant::now());
+                                    triggers.on_quote(&kalshi, &(&msg).into()).await;
+                                }
+                                Ok(_) => {}
+                                Err(RecvError::Lagged(skipped)) => {
+                                    log::warn!("Trigger manager lagged, skipped {} messages", skipped);
+                                }
+                                Err(RecvError::Closed) => break,
+                            },
+                        }
+                    }
+                }
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{fixtures, MockHttpServer};
+    use reqwest::Method;
+
+    #[tokio::test]
+    async fn test_triggers_fire_once() {
+        let kalshi = Kalshi::new(crate::TradingEnvironment::DemoMode);
+        let triggers = TriggerManager::new();
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let callback = {
+            let fired = fired.clone();
+            TriggerAction::Callback(Arc::new(move |event: &TriggerFired| {
+                fired.lock().unwrap().push(event.id)
+            }))
+        };
+        let stop = triggers.register(
+            TriggerCondition::new(
+                "KXHIGHNY-25OCT02-B80.5",
+                PriceField::YesBid,
+                Crossing::AtOrBelow(35),
+            ),
+            callback.clone(),
+        );
+        let take = triggers.register(
+            TriggerCondition::new(
+                "KXHIGHNY-25OCT02-B80.5",
+                PriceField::LastPrice,
+                Crossing::AtOrAbove(60),
+            ),
+            callback,
+        );
+
+        let mut quote = TriggerQuote {
+            ticker: "KXHIGHNY-25OCT02-B80.5".into(),
+            last_price: 45,
+            yes_bid: 0,
+            yes_ask: 47,
+        };
+        // An empty bid isn't a price of 0
+        assert!(triggers.on_quote(&kalshi, &quote).await.is_empty());
+
+        quote.yes_bid = 34;
+        triggers.on_quote(&kalshi, &quote).await;
+        triggers.on_quote(&kalshi, &quote).await;
+        assert_eq!(*fired.lock().unwrap(), vec![stop]);
+        assert_eq!(triggers.pending().len(), 1);
+
+        assert!(triggers.cancel(take));
+        assert!(triggers.tickers().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trailing_stop_ratchets() {
+        let kalshi = Kalshi::new(crate::TradingEnvironment::DemoMode);
+        let triggers = TriggerManager::new();
+        let id = triggers.register(
+            TriggerCondition::new(
+                "KXHIGHNY-25OCT02-B80.5",
+                PriceField::YesBid,
+                Crossing::TrailingBelow(5),
+            ),
+            TriggerAction::Callback(Arc::new(|_: &TriggerFired| {})),
+        );
+        assert_eq!(triggers.threshold(id), None);
+
+        let quote = |yes_bid| TriggerQuote {
+            ticker: "KXHIGHNY-25OCT02-B80.5".into(),
+            last_price: yes_bid,
+            yes_bid,
+            yes_ask: yes_bid + 2,
+        };
+        for bid in [40, 44, 42, 48, 45] {
+            assert!(triggers.on_quote(&kalshi, &quote(bid)).await.is_empty());
+        }
+        // The high of 48 sets the stop, the pullback to 45 doesn't lower it
+        assert_eq!(triggers.threshold(id), Some(43));
+
+        let fired = triggers.on_quote(&kalshi, &quote(43)).await;
+        assert_eq!(fired.len(), 1);
+        assert!(triggers.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_cancels_retried() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        let mut first = fixtures::orders_page();
+        first["cursor"] = "page-2".into();
+        server.respond(Method::GET, "/portfolio/orders", 200, first);
+        server.respond_to_query(
+            Method::GET,
+            "/portfolio/orders",
+            &[("cursor", "page-2")],
+            200,
+            serde_json::json!({"orders": [fixtures::order("filled-order", 64, 4)], "cursor": ""}),
+        );
+        let triggers = TriggerManager::new();
+        triggers.register(
+            TriggerCondition::new(
+                fixtures::MARKET_TICKER,
+                PriceField::YesBid,
+                Crossing::AtOrBelow(35),
+            ),
+            TriggerAction::CancelOrders,
+        );
+        let quote = TriggerQuote {
+            ticker: fixtures::MARKET_TICKER.into(),
+            last_price: 36,
+            yes_bid: 34,
+            yes_ask: 38,
+        };
+
+        // Canceling the order of the second page fails, the first one is canceled anyway
+        assert!(triggers.on_quote(&kalshi, &quote).await.is_empty());
+        assert_eq!(triggers.pending().len(), 1);
+        let order = format!("/portfolio/orders/{}", fixtures::ORDER_ID);
+        assert_eq!(server.requests_to(Method::DELETE, &order).len(), 1);
+        assert_eq!(
+            server
+                .requests_to(Method::DELETE, "/portfolio/orders/filled-order")
+                .len(),
+            1
+        );
+
+        server.respond(
+            Method::DELETE,
+            "/portfolio/orders/filled-order",
+            200,
+            fixtures::canceled_order(),
+        );
+        assert_eq!(triggers.on_quote(&kalshi, &quote).await.len(), 1);
+        assert!(triggers.pending().is_empty());
+        assert_eq!(server.requests_to(Method::DELETE, &order).len(), 2);
+    }
+
+    #[cfg(feature = "websockets")]
+    #[tokio::test]
+    async fn test_triggers_registered_after_spawn_subscribed() {
+        use crate::testing::MockWsServer;
+
+        let server = MockWsServer::start().await.unwrap();
+        let http = MockHttpServer::with_fixtures().await.unwrap();
+        let mut kalshi = server.kalshi();
+        kalshi.set_base_url(&http.url());
+        let mut ws = kalshi.connect_ws().await.unwrap();
+        let triggers = TriggerManager::new();
+        let condition = |ticker: &str| {
+            TriggerCondition::new(ticker, PriceField::YesBid, Crossing::AtOrBelow(35))
+        };
+        triggers.register(
+            condition(fixtures::MARKET_TICKER),
+            TriggerAction::CancelOrders,
+        );
+        triggers
+            .spawn(kalshi.clone(), &mut ws, Duration::from_secs(3600))
+            .await
+            .unwrap();
+        server.wait_for_commands(1, Duration::from_secs(5)).await;
+        server.assert_subscribed(&["ticker"], &[fixtures::MARKET_TICKER]);
+
+        triggers.register(
+            condition("KXHIGHNY-25OCT02-B80.5"),
+            TriggerAction::CancelOrders,
+        );
+        let commands = server.wait_for_commands(2, Duration::from_secs(5)).await;
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[1].market_tickers(), vec!["KXHIGHNY-25OCT02-B80.5"]);
+        // Already subscribed
+        triggers.register(
+            condition(fixtures::MARKET_TICKER),
+            TriggerAction::CancelOrders,
+        );
+        assert_eq!(
+            server
+                .wait_for_commands(3, Duration::from_millis(300))
+                .await
+                .len(),
+            2
+        );
+        ws.close().await;
+    }
+}
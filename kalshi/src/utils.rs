@@ -1,13 +1,13 @@
 use std::{
     error::Error,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
 use base64::{prelude::BASE64_STANDARD, Engine};
 use openssl::sign::Signer;
 use reqwest::Method;
 
-use crate::TradingEnvironment;
+use crate::{timing::PendingTiming, KalshiError, TradingEnvironment};
 // MACROS
 
 #[macro_export]
@@ -58,3 +58,28 @@ pub(super) fn api_key_headers(
     headers.push(("KALSHI-ACCESS-TIMESTAMP", ts.to_string()));
     Ok(headers)
 }
+
+/// Deserializes a JSON response body, with simd-json when the `simd-json` feature is enabled.
+///
+/// Used for the endpoints returning large pages, where parsing dominates the cost of a request,
+/// and for every response whose parsing is timed, see [`set_request_timing_hook`](crate::set_request_timing_hook).
+pub(crate) async fn parse_json<T: serde::de::DeserializeOwned>(
+    mut response: reqwest::Response,
+) -> Result<T, KalshiError> {
+    let timing = response.extensions_mut().remove::<PendingTiming>();
+    // With timing the body is already downloaded, only parsing is left
+    let started_at = Instant::now();
+    #[cfg(feature = "simd-json")]
+    let result = {
+        let mut body = response.bytes().await?.to_vec();
+        simd_json::serde::from_slice(&mut body).map_err(|e| {
+            KalshiError::InternalError(format!("Failed to decode JSON response: {}", e))
+        })
+    };
+    #[cfg(not(feature = "simd-json"))]
+    let result = response.json().await.map_err(KalshiError::from);
+    if let Some(timing) = timing {
+        timing.deserialized_in(started_at.elapsed());
+    }
+    result
+}
@@ -0,0 +1,137 @@
+use crate::{KalshiError, Market};
+
+/// Checks that a price is within the 1 to 99 cents a contract can trade at.
+pub(crate) fn validate_price(price: i64) -> Result<(), KalshiError> {
+    if !(1..=99).contains(&price) {
+        return Err(KalshiError::UserInputError(format!(
+            "Price {} is out of bounds, contracts trade between 1 and 99 cents",
+            price
+        )));
+    }
+    Ok(())
+}
+
+impl Market {
+    /// Checks an order against this market's constraints, without reaching the exchange.
+    ///
+    /// The market must be active with its close time ahead, the price (on the order's side, in
+    /// cents) between 1 and 99 and a multiple of the tick size, and the order's cost within the
+    /// market's `risk_limit_cents` when it has one. Market orders have no price to check.
+    ///
+    /// # Example
+    /// ```
+    /// let market = kalshi_instance.get_single_market(&ticker).await?;
+    /// market.validate_order(10, Some(45))?;
+    /// ```
+    pub fn validate_order(&self, count: i32, price: Option<i64>) -> Result<(), KalshiError> {
+        if count <= 0 {
+            return Err(KalshiError::UserInputError(format!(
+                "Order count must be positive, got {}",
+                count
+            )));
+        }
+        if !self.is_open() {
+            return Err(KalshiError::UserInputError(format!(
+                "Market {} is {}, orders are only accepted while it's active",
+                self.ticker, self.status
+            )));
+        }
+        if let Ok(close_time) = chrono::DateTime::parse_from_rfc3339(&self.close_time) {
+            if close_time <= chrono::Utc::now() {
+                return Err(KalshiError::UserInputError(format!(
+                    "Market {} closed at {}",
+                    self.ticker, self.close_time
+                )));
+            }
+        }
+
+        let Some(price) = price else {
+            return Ok(());
+        };
+        validate_price(price)?;
+        if self.tick_size > 1 && price % self.tick_size != 0 {
+            return Err(KalshiError::UserInputError(format!(
+                "Price {} isn't a multiple of the tick size {} of market {}",
+                price, self.tick_size, self.ticker
+            )));
+        }
+        let cost = count as i64 * price;
+        if self.risk_limit_cents > 0 && cost > self.risk_limit_cents {
+            return Err(KalshiError::UserInputError(format!(
+                "Order cost of {} cents exceeds the risk limit of {} cents of market {}",
+                cost, self.risk_limit_cents, self.ticker
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn market() -> Market {
+        let mut market: Market =
+            serde_json::from_value(crate::testing::fixtures::market()).unwrap();
+        market.close_time = (chrono::Utc::now() + chrono::Duration::days(1)).to_rfc3339();
+        market
+    }
+
+    fn rejection(result: Result<(), KalshiError>) -> String {
+        match result {
+            Err(KalshiError::UserInputError(message)) => message,
+            other => panic!("expected a user input error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_order() {
+        let mut market = market();
+        assert!(market.validate_order(10, Some(45)).is_ok());
+        assert!(market.validate_order(10, None).is_ok());
+        assert!(rejection(market.validate_order(10, Some(100))).contains("out of bounds"));
+        assert!(rejection(market.validate_order(0, Some(45))).contains("positive"));
+
+        market.tick_size = 5;
+        assert!(rejection(market.validate_order(10, Some(42))).contains("tick size"));
+        market.risk_limit_cents = 400;
+        assert!(rejection(market.validate_order(10, Some(45))).contains("risk limit"));
+
+        let mut closed = self::market();
+        closed.close_time = "2025-10-02T00:00:00Z".to_string();
+        assert!(rejection(closed.validate_order(10, Some(45))).contains("closed at"));
+        closed.status = "finalized".to_string();
+        assert!(rejection(closed.validate_order(10, Some(45))).contains("is finalized"));
+    }
+
+    #[tokio::test]
+    async fn test_create_order_validates_before_sending() {
+        use crate::testing::{fixtures, MockHttpServer};
+        use crate::{Action, OrderType, Side};
+        use reqwest::Method;
+
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let mut kalshi = server.kalshi().await.unwrap();
+        kalshi.set_validate_orders(true);
+        // The fixture market closed in the past
+        let result = kalshi
+            .create_order(
+                Action::Buy,
+                None,
+                10,
+                Side::Yes,
+                fixtures::MARKET_TICKER.to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                None,
+                None,
+                Some(45),
+            )
+            .await;
+        assert!(rejection(result.map(|_| ())).contains("closed at"));
+        assert!(server
+            .requests_to(Method::POST, "/portfolio/orders")
+            .is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::{atomic::AtomicU32, Arc, Mutex};
+
+use futures_util::Stream;
+use tokio::sync::{
+    broadcast::{error::RecvError, Receiver},
+    mpsc::UnboundedSender,
+};
+
+use super::{
+    client::{lock_state, KalshiWebsocketError},
+    commands::KalshiCommand,
+    responses::KalshiWebsocketResponse,
+    state::{StreamKey, WsState},
+    KalshiChannel,
+};
+
+/// The messages of one channel for a set of markets, holding the subscription delivering them.
+///
+/// Created with [`KalshiWebsocketClient::channel_stream`](super::client::KalshiWebsocketClient::channel_stream).
+/// Handles asking for the same channel and markets share a single subscription, which is
+/// unsubscribed when the last of them is dropped so subscriptions don't pile up against the
+/// exchange's limits. Dropping a handle before the subscription was acknowledged unsubscribes
+/// as soon as the acknowledgement arrives.
+///
+/// ```
+/// let mut trades = ws_client
+///     .channel_stream(KalshiChannel::Trade, vec!["KXHIGHNY-25OCT02-B80.5".to_string()])
+///     .await?;
+/// while let Some(msg) = trades.recv().await {
+///     println!("{:?}", msg);
+/// }
+/// // Unsubscribes
+/// drop(trades);
+/// ```
+pub struct KalshiChannelStream {
+    key: StreamKey,
+    receiver: Receiver<Result<KalshiWebsocketResponse, KalshiWebsocketError>>,
+    state: Arc<Mutex<WsState>>,
+    next_cmd_id: Arc<AtomicU32>,
+    to_kalshi: UnboundedSender<KalshiCommand>,
+}
+
+impl KalshiChannelStream {
+    pub(super) fn new(
+        key: StreamKey,
+        receiver: Receiver<Result<KalshiWebsocketResponse, KalshiWebsocketError>>,
+        state: Arc<Mutex<WsState>>,
+        next_cmd_id: Arc<AtomicU32>,
+        to_kalshi: UnboundedSender<KalshiCommand>,
+    ) -> Self {
+        KalshiChannelStream {
+            key,
+            receiver,
+            state,
+            next_cmd_id,
+            to_kalshi,
+        }
+    }
+
+    pub fn channel(&self) -> &KalshiChannel {
+        &self.key.0
+    }
+
+    /// The markets of the subscription, sorted, empty for all markets.
+    pub fn market_tickers(&self) -> &[String] {
+        &self.key.1
+    }
+
+    /// Waits for the next message of the channel for the markets, `None` once the client has shut down.
+    ///
+    /// Messages missed because the consumer fell too far behind are skipped with a warning.
+    pub async fn recv(&mut self) -> Option<KalshiWebsocketResponse> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(Ok(msg)) if self.matches(&msg) => return Some(msg),
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("Channel stream lagged, skipped {} messages", skipped);
+                    lock_state(&self.state).on_lagged(skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Converts the handle into a stream of messages, unsubscribing when the stream is dropped.
+    pub fn into_stream(mut self) -> impl Stream<Item = KalshiWebsocketResponse> {
+        async_stream::stream! {
+            while let Some(msg) = self.recv().await {
+                yield msg;
+            }
+        }
+    }
+
+    fn matches(&self, msg: &KalshiWebsocketResponse) -> bool {
+        let (channel, market_tickers) = &self.key;
+        msg.channel().as_ref() == Some(channel)
+            && (market_tickers.is_empty()
+                || msg
+                    .market_ticker()
+                    .is_some_and(|ticker| market_tickers.iter().any(|t| t == ticker)))
+    }
+}
+
+impl Drop for KalshiChannelStream {
+    fn drop(&mut self) {
+        let cmd = lock_state(&self.state).release_stream(&self.key, &self.next_cmd_id);
+        if let Some(cmd) = cmd {
+            // Nothing is left to unsubscribe once the client has shut down
+            let _ = self.to_kalshi.send(cmd);
+        }
+    }
+}
@@ -1,37 +1,58 @@
 #![allow(unused)]
 
-use futures_util::{select_biased, FutureExt, SinkExt, Stream, StreamExt};
+use futures_util::{select_biased, FutureExt, Sink, SinkExt, Stream, StreamExt};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
-use std::{error::Error, str::FromStr, time::Duration, vec};
+use std::{
+    error::Error,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+    vec,
+};
 use tokio::{
     net::TcpStream,
     sync::{
-        broadcast::{channel, Receiver, Sender},
+        broadcast::{channel, error::RecvError, Receiver, Sender},
         mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+        watch,
     },
     task::JoinHandle,
     time::{interval, MissedTickBehavior},
 };
 use tokio_tungstenite::{
-    connect_async,
+    connect_async, connect_async_tls_with_config, tungstenite,
     tungstenite::{
         client::IntoClientRequest,
         handshake,
         http::{HeaderMap, HeaderValue, Request, Uri},
         Message,
     },
-    MaybeTlsStream, WebSocketStream,
+    Connector, MaybeTlsStream, WebSocketStream,
 };
 
-use crate::{Kalshi, KalshiAuth};
+use crate::{
+    Bar, BarAggregator, Kalshi, KalshiAuth, MarketStats, MarketTrade, RollingStats, ShutdownSignal,
+    Side,
+};
 
 use super::{
+    channel_stream::KalshiChannelStream,
     commands::{
         KalshiCommand, KalshiSubscribeCommandParams, KalshiUnsubscribeCommandParams,
         KalshiUpdateSubscriptionAction, KalshiUpdateSubscriptionCommandParams,
     },
-    responses::KalshiWebsocketResponse,
+    demux::KalshiMarketDemux,
+    fills::KalshiFillFilter,
+    rate_limit::{CommandQueue, KalshiCommandRateLimit},
+    recording::KalshiRecorder,
+    responses::{KalshiFillMessage, KalshiSide, KalshiTradeMessage, KalshiWebsocketResponse},
+    state::{restored_on_reconnect, TradeBackfill, WsState},
+    stats::{KalshiPendingCommand, KalshiSubscription, KalshiWebsocketStats},
     KalshiChannel,
 };
 
@@ -40,6 +61,12 @@ pub enum KalshiWebsocketError {
     WebSocketError(String),
     SerializationError(String),
     ConnectionClosed,
+    /// Trades missed while disconnected couldn't be fetched through the REST api
+    BackfillFailed(String),
+    /// The command wasn't acknowledged within the command timeout
+    CommandTimeout {
+        id: u32,
+    },
 }
 
 impl std::fmt::Display for KalshiWebsocketError {
@@ -50,68 +77,162 @@ impl std::fmt::Display for KalshiWebsocketError {
                 write!(f, "Serialization error: {}", msg)
             }
             KalshiWebsocketError::ConnectionClosed => write!(f, "Connection closed"),
+            KalshiWebsocketError::BackfillFailed(msg) => {
+                write!(f, "Trade backfill failed: {}", msg)
+            }
+            KalshiWebsocketError::CommandTimeout { id } => {
+                write!(f, "Command {} was not acknowledged in time", id)
+            }
         }
     }
 }
 
 impl std::error::Error for KalshiWebsocketError {}
 
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How long commands wait for their acknowledgement unless changed with
+/// [`KalshiWebsocketClient::set_command_timeout`].
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How a lost connection is re-established, see [`KalshiWebsocketConfig::reconnect`].
+///
+/// Attempts are spaced by a backoff doubling from `initial_backoff` up to `max_backoff`. Once
+/// `max_attempts` attempts in a row failed the client gives up and reports
+/// [`KalshiWebsocketError::ConnectionClosed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KalshiReconnectPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// `None` to retry forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for KalshiReconnectPolicy {
+    fn default() -> Self {
+        KalshiReconnectPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: MAX_RECONNECT_BACKOFF,
+            max_attempts: None,
+        }
+    }
+}
+
+/// Settings of the websocket clients connected through a [`Kalshi`] instance, see
+/// [`Kalshi::set_ws_config`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KalshiWebsocketConfig {
+    /// See [`KalshiWebsocketClient::set_command_timeout`].
+    pub command_timeout: Option<Duration>,
+    /// See [`KalshiWebsocketClient::set_command_rate_limit`].
+    pub command_rate_limit: Option<KalshiCommandRateLimit>,
+    /// Re-establishes lost connections and restores their subscriptions when set. By default
+    /// the client ends with its connection, reporting [`KalshiWebsocketError::ConnectionClosed`].
+    pub reconnect: Option<KalshiReconnectPolicy>,
+}
+
+impl Default for KalshiWebsocketConfig {
+    fn default() -> Self {
+        KalshiWebsocketConfig {
+            command_timeout: Some(DEFAULT_COMMAND_TIMEOUT),
+            command_rate_limit: None,
+            reconnect: None,
+        }
+    }
+}
+
 pub struct KalshiWebsocketClient {
     _ws: JoinHandle<()>,
-    next_cmd_id: u32,
+    /// Closed once the handler task has exited
+    handler_done: watch::Receiver<()>,
+    next_cmd_id: Arc<AtomicU32>,
     to_kalshi: UnboundedSender<KalshiCommand>,
-    from_kalshi: Receiver<Result<KalshiWebsocketResponse, KalshiWebsocketError>>,
+    from_kalshi: FanOut,
+    state: Arc<Mutex<WsState>>,
+    recorder: Option<KalshiRecorder>,
+    kalshi: Kalshi,
 }
 
 impl Kalshi {
-    pub async fn connect_ws(&mut self) -> Result<KalshiWebsocketClient, Box<dyn Error>> {
+    pub async fn connect_ws(&self) -> Result<KalshiWebsocketClient, Box<dyn Error>> {
         KalshiWebsocketClient::connect(self).await
     }
 
     pub fn get_ws_url(&self) -> &str {
         &self.ws_url
     }
+
+    /// Overrides the websocket url picked from the trading environment,
+    /// for example to connect to a proxy or a `testing::MockWsServer`.
+    pub fn set_ws_url(&mut self, ws_url: &str) {
+        self.ws_url = ws_url.to_string();
+    }
+
+    /// Settings applied to websocket clients connected from now on.
+    ///
+    /// ```
+    /// kalshi_instance.set_ws_config(KalshiWebsocketConfig {
+    ///     command_rate_limit: Some(KalshiCommandRateLimit::new(10.0, 20)),
+    ///     reconnect: Some(KalshiReconnectPolicy {
+    ///         max_attempts: Some(10),
+    ///         ..Default::default()
+    ///     }),
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn set_ws_config(&mut self, config: KalshiWebsocketConfig) {
+        self.ws_config = config;
+    }
+
+    pub fn get_ws_config(&self) -> &KalshiWebsocketConfig {
+        &self.ws_config
+    }
 }
 
 impl<'a> KalshiWebsocketClient {
-    pub async fn connect(kalshi: &mut Kalshi) -> Result<Self, Box<dyn Error>> {
-        let mut req = Uri::from_str(kalshi.get_ws_url())?.into_client_request()?;
-        let ws_api_path = kalshi.extract_url_path(kalshi.get_ws_url());
-        let auth_headers = kalshi
-            .generate_auth_headers(&ws_api_path, Method::GET)
-            .map_err(|e| format!("Auth header generation failed: {}", e))?;
-        let headers = req.headers_mut();
-        for (key, val) in &auth_headers {
-            let ws_header_name = tokio_tungstenite::tungstenite::http::HeaderName::from_bytes(
-                key.as_str().as_bytes(),
-            )?;
-            let ws_header_value =
-                tokio_tungstenite::tungstenite::http::HeaderValue::from_str(val.to_str()?)?;
-            headers.insert(ws_header_name, ws_header_value);
-        }
-        let req_clone = req.clone();
-        let (ws_stream, res) = connect_async(req).await.inspect_err(|e| match e {
-            tokio_tungstenite::tungstenite::Error::Http(res) => {
-                if let Some(body) = res.body() {
-                    if let Ok(error_body) = String::from_utf8(body.to_vec()) {
-                        eprintln!("Request was {:?}", req_clone);
-                        eprintln!("Kalshi error response was {}", error_body);
-                    }
-                }
-            }
-            _ => {}
-        })?;
+    pub async fn connect(kalshi: &Kalshi) -> Result<Self, Box<dyn Error>> {
+        let ws_stream = open_ws_stream(kalshi)
+            .await
+            .map_err(|e| e as Box<dyn Error>)?;
 
         let (to_kalshi_tx, to_kalshi_rx) = unbounded_channel::<KalshiCommand>();
-        let (from_kalshi_tx, from_kalshi_rx) =
-            channel::<Result<KalshiWebsocketResponse, KalshiWebsocketError>>(1024);
+        let from_kalshi_tx = FanOut::new(1024);
+        let next_cmd_id = Arc::new(AtomicU32::new(1));
+        let state = Arc::new(Mutex::new(WsState::default()));
+        {
+            let mut state = lock_state(&state);
+            state.on_connected(false);
+            state.set_command_timeout(kalshi.ws_config.command_timeout);
+            state.set_command_rate_limit(kalshi.ws_config.command_rate_limit);
+        }
 
-        let _ws = tokio::spawn(kalshi_ws_handler(ws_stream, from_kalshi_tx, to_kalshi_rx));
+        // The sender lives as long as the handler task, even if it panics
+        let (handler_alive, handler_done) = watch::channel(());
+        let _ws = kalshi
+            .shutdown_handle()
+            .spawn_with_signal("websocket", |signal| {
+                kalshi_ws_handler(
+                    kalshi.clone(),
+                    ws_stream,
+                    from_kalshi_tx.clone(),
+                    to_kalshi_rx,
+                    Arc::clone(&state),
+                    Arc::clone(&next_cmd_id),
+                    signal,
+                )
+                .map(move |_| drop(handler_alive))
+            });
 
         Ok(KalshiWebsocketClient {
-            next_cmd_id: 1,
+            next_cmd_id,
             to_kalshi: to_kalshi_tx,
-            from_kalshi: from_kalshi_rx,
+            from_kalshi: from_kalshi_tx,
+            state,
+            recorder: None,
+            kalshi: kalshi.clone(),
+            handler_done,
             _ws,
         })
     }
@@ -130,10 +251,10 @@ impl<'a> KalshiWebsocketClient {
         channels: Vec<KalshiChannel>,
         market_tickers: Vec<String>,
     ) -> Result<u32, Box<dyn Error>> {
-        let cmd_id = self.next_cmd_id;
         if channels.contains(&KalshiChannel::OrderbookDelta) && market_tickers.len() == 0 {
             return Err("Cannot subscribe to orderbook deltas for all market tickers, provide at least one market ticker".to_string().into());
         }
+        let cmd_id = self.next_cmd_id.fetch_add(1, Ordering::SeqCst);
         let msg = KalshiCommand::Subscribe {
             id: cmd_id,
             params: KalshiSubscribeCommandParams {
@@ -142,10 +263,214 @@ impl<'a> KalshiWebsocketClient {
             },
         };
         self.to_kalshi.send(msg)?;
-        self.next_cmd_id += 1;
         Ok(cmd_id)
     }
 
+    /// Subscribe to one or more channels on every market of an event
+    ///
+    /// The event's markets are fetched over REST and subscribed to in a single command.
+    /// Markets added to the event later are added to the subscription automatically when their
+    /// `created` lifecycle message arrives, which is why this also subscribes to the
+    /// `market_lifecycle_v2` channel for all markets unless such a subscription already exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_ticker` - The ticker of the event to follow.
+    /// * `channels` - The channels to subscribe to for each market of the event.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result<u32, Box<dyn Error>>` where the unsigned integer is the command id
+    /// of the subscription covering the event's markets.
+    ///
+    /// ```
+    /// ws_client
+    ///     .subscribe_event("KXHIGHNY-25OCT02", vec![KalshiChannel::Ticker, KalshiChannel::Trade])
+    ///     .await?;
+    /// ```
+    ///
+    pub async fn subscribe_event(
+        &mut self,
+        event_ticker: &str,
+        channels: Vec<KalshiChannel>,
+    ) -> Result<u32, Box<dyn Error>> {
+        let event = self
+            .kalshi
+            .get_single_event(&event_ticker.to_string(), Some(true))
+            .await?;
+        let market_tickers: Vec<String> = event
+            .markets
+            .unwrap_or_default()
+            .into_iter()
+            .map(|market| market.ticker)
+            .collect();
+        if market_tickers.is_empty() {
+            return Err(format!("Event {} has no markets to subscribe to", event_ticker).into());
+        }
+
+        let watch_lifecycles = !lock_state(&self.state).watches_all_lifecycles();
+        if watch_lifecycles {
+            self.subscribe(vec![KalshiChannel::MarketLifecycleV2], vec![])
+                .await?;
+        }
+        let cmd_id = self.next_cmd_id.fetch_add(1, Ordering::SeqCst);
+        // Follow before sending so the ack can't arrive before the event is known
+        lock_state(&self.state).follow_event(event_ticker, cmd_id, &market_tickers);
+        let msg = KalshiCommand::Subscribe {
+            id: cmd_id,
+            params: KalshiSubscribeCommandParams {
+                channels,
+                market_tickers,
+            },
+        };
+        self.to_kalshi.send(msg)?;
+        Ok(cmd_id)
+    }
+
+    /// Make sure every channel delivers messages for every given market, subscribing only to what's missing
+    ///
+    /// Calling this repeatedly with the same arguments only sends commands the first time, subscriptions
+    /// that are still waiting for their acknowledgement count as subscribed. Missing markets are added to an
+    /// existing subscription of the channel when there is one, otherwise a new subscription is created.
+    /// An empty `market_tickers` asks for all markets.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result<Vec<u32>, Box<dyn Error>>` with the ids of the commands sent, empty if everything
+    /// was already subscribed
+    ///
+    /// ```
+    /// let tickers = vec!["KXHIGHNY-25OCT02-B80.5".to_string()];
+    /// ws_client.ensure_subscribed(vec![KalshiChannel::Ticker], tickers.clone()).await?;
+    /// // Sends nothing
+    /// ws_client.ensure_subscribed(vec![KalshiChannel::Ticker], tickers).await?;
+    /// ```
+    ///
+    pub async fn ensure_subscribed(
+        &mut self,
+        channels: Vec<KalshiChannel>,
+        market_tickers: Vec<String>,
+    ) -> Result<Vec<u32>, Box<dyn Error>> {
+        self.subscriber()
+            .ensure_subscribed(channels, market_tickers)
+    }
+
+    /// A handle subscribing through this client from background tasks, see [`WsSubscriber`].
+    pub(crate) fn subscriber(&self) -> WsSubscriber {
+        WsSubscriber {
+            next_cmd_id: Arc::clone(&self.next_cmd_id),
+            to_kalshi: self.to_kalshi.clone(),
+            state: Arc::clone(&self.state),
+        }
+    }
+
+    /// Subscribe to a channel on one, more, or all markets, getting a handle on its messages
+    ///
+    /// Handles for the same channel and markets, in any order, share one subscription that is
+    /// unsubscribed when the last handle is dropped, see [`KalshiChannelStream`]. Subscriptions
+    /// made with [`subscribe`](Self::subscribe) are never shared with or released by handles.
+    ///
+    /// ```
+    /// let tickers = vec!["KXHIGHNY-25OCT02-B80.5".to_string()];
+    /// let book = ws_client.channel_stream(KalshiChannel::OrderbookDelta, tickers.clone()).await?;
+    /// // Shares the subscription of `book`
+    /// let other = ws_client.channel_stream(KalshiChannel::OrderbookDelta, tickers).await?;
+    /// ```
+    ///
+    pub async fn channel_stream(
+        &mut self,
+        channel: KalshiChannel,
+        mut market_tickers: Vec<String>,
+    ) -> Result<KalshiChannelStream, Box<dyn Error>> {
+        if channel == KalshiChannel::OrderbookDelta && market_tickers.is_empty() {
+            return Err("Cannot subscribe to orderbook deltas for all market tickers, provide at least one market ticker".to_string().into());
+        }
+        market_tickers.sort();
+        market_tickers.dedup();
+        let key = (channel, market_tickers);
+        // Listen before subscribing so no message is missed
+        let receiver = self.receiver();
+        let cmd = lock_state(&self.state).acquire_stream(&key, &self.next_cmd_id);
+        let stream = KalshiChannelStream::new(
+            key,
+            receiver,
+            Arc::clone(&self.state),
+            Arc::clone(&self.next_cmd_id),
+            self.to_kalshi.clone(),
+        );
+        if let Some(cmd) = cmd {
+            self.to_kalshi.send(cmd)?;
+        }
+        Ok(stream)
+    }
+
+    /// Get the subscriptions acknowledged by the exchange and still active
+    ///
+    /// ```
+    /// for sub in ws_client.active_subscriptions() {
+    ///     println!("sid {} {:?} on {:?}", sub.sid, sub.channel, sub.market_tickers);
+    /// }
+    /// ```
+    ///
+    pub fn active_subscriptions(&self) -> Vec<KalshiSubscription> {
+        lock_state(&self.state).active_subscriptions()
+    }
+
+    /// Get the commands sent to the exchange that weren't acknowledged yet
+    ///
+    /// Commands not acknowledged within the command timeout are reported on the stream as
+    /// [`KalshiWebsocketError::CommandTimeout`] and dropped from this list, see
+    /// [`set_command_timeout`](Self::set_command_timeout). Commands queued during a reconnect
+    /// or held back by the command rate limit are only listed once they're sent.
+    ///
+    /// ```
+    /// for cmd in ws_client.pending_commands() {
+    ///     println!("{} {} waiting for {:?}", cmd.cmd, cmd.id, cmd.sent_at.elapsed());
+    /// }
+    /// ```
+    ///
+    pub fn pending_commands(&self) -> Vec<KalshiPendingCommand> {
+        lock_state(&self.state).pending_commands()
+    }
+
+    /// Set how long a command waits for its `subscribed`, `ok` or `error` acknowledgement before
+    /// a [`KalshiWebsocketError::CommandTimeout`] is sent on the stream, `None` to wait forever
+    ///
+    /// Defaults to [`DEFAULT_COMMAND_TIMEOUT`]. Timeouts are checked every second.
+    ///
+    /// ```
+    /// ws_client.set_command_timeout(Some(Duration::from_secs(5)));
+    /// ```
+    ///
+    pub fn set_command_timeout(&self, timeout: Option<Duration>) {
+        lock_state(&self.state).set_command_timeout(timeout);
+    }
+
+    /// Limit the rate of commands written to the connection, `None` to send them right away
+    ///
+    /// Commands over the limit are queued and sent in order as it allows, restored subscriptions
+    /// first after a reconnect. Disabled by default.
+    ///
+    /// ```
+    /// ws_client.set_command_rate_limit(Some(KalshiCommandRateLimit::new(10.0, 20)));
+    /// ```
+    ///
+    pub fn set_command_rate_limit(&self, limit: Option<KalshiCommandRateLimit>) {
+        lock_state(&self.state).set_command_rate_limit(limit);
+    }
+
+    /// Check whether an acknowledged subscription delivers `channel` messages for `market_ticker`
+    ///
+    /// ```
+    /// if !ws_client.is_subscribed(&KalshiChannel::Trade, "KXHIGHNY-25OCT02-B80.5") {
+    ///     println!("not receiving trades yet");
+    /// }
+    /// ```
+    ///
+    pub fn is_subscribed(&self, channel: &KalshiChannel, market_ticker: &str) -> bool {
+        lock_state(&self.state).is_subscribed(channel, market_ticker)
+    }
+
     /// Unsubscribe one or more existing subscriptions
     ///
     /// # Returns
@@ -155,13 +480,12 @@ impl<'a> KalshiWebsocketClient {
     /// ```
     ///
     pub async fn unsubscribe(&mut self, sids: Vec<u32>) -> Result<u32, Box<dyn Error>> {
-        let cmd_id = self.next_cmd_id;
+        let cmd_id = self.next_cmd_id.fetch_add(1, Ordering::SeqCst);
         let msg = KalshiCommand::Unsubscribe {
             id: cmd_id,
             params: KalshiUnsubscribeCommandParams { sids },
         };
         self.to_kalshi.send(msg)?;
-        self.next_cmd_id += 1;
         Ok(cmd_id)
     }
 
@@ -179,7 +503,7 @@ impl<'a> KalshiWebsocketClient {
         market_tickers: Vec<String>,
         action: KalshiUpdateSubscriptionAction,
     ) -> Result<u32, Box<dyn Error>> {
-        let cmd_id = self.next_cmd_id;
+        let cmd_id = self.next_cmd_id.fetch_add(1, Ordering::SeqCst);
         let msg = KalshiCommand::UpdateSubscription {
             id: cmd_id,
             params: KalshiUpdateSubscriptionCommandParams {
@@ -189,7 +513,6 @@ impl<'a> KalshiWebsocketClient {
             },
         };
         self.to_kalshi.send(msg)?;
-        self.next_cmd_id += 1;
         Ok(cmd_id)
     }
 
@@ -203,81 +526,900 @@ impl<'a> KalshiWebsocketClient {
     /// ```
     ///
     pub fn receiver(&self) -> Receiver<Result<KalshiWebsocketResponse, KalshiWebsocketError>> {
-        self.from_kalshi.resubscribe()
+        self.from_kalshi.owned.subscribe()
     }
 
-    /// Gracefully closes the websocket connection consuming the client
+    /// The instance the client connected through, background tasks following the client register
+    /// with its [`Shutdown`](crate::Shutdown).
+    pub(crate) fn kalshi(&self) -> &Kalshi {
+        &self.kalshi
+    }
+
+    /// Get a broadcast receiver of messages shared behind an `Arc`
+    ///
+    /// Every [`receiver`](Self::receiver) gets its own deep copy of each message, which adds up
+    /// with many consumers of a busy orderbook feed. Shared receivers all get the same allocation,
+    /// messages are copied at most once whatever the number of consumers.
+    ///
+    /// ```
+    /// let mut receiver = ws_client.shared_receiver();
+    /// while let Ok(msg) = receiver.recv().await {
+    ///     if let Ok(KalshiWebsocketResponse::OrderbookDelta { msg, .. }) = msg.as_ref() {
+    ///         println!("{:?}", msg);
+    ///     }
+    /// }
+    /// ```
+    ///
+    pub fn shared_receiver(&self) -> Receiver<Arc<KalshiWebsocketItem>> {
+        self.from_kalshi.shared.subscribe()
+    }
+
+    /// Get the websocket feed as a stream of parsed messages
+    ///
+    /// Messages missed because the consumer fell too far behind are skipped with a warning.
+    ///
+    /// ```
+    /// let mut stream = Box::pin(ws_client.stream());
+    /// while let Some(msg) = stream.next().await {
+    ///     println!("{:?}", msg);
+    /// }
+    /// ```
+    ///
+    pub fn stream(
+        &self,
+    ) -> impl Stream<Item = Result<KalshiWebsocketResponse, KalshiWebsocketError>> {
+        let mut receiver = self.receiver();
+        let state = Arc::clone(&self.state);
+        async_stream::stream! {
+            loop {
+                match receiver.recv().await {
+                    Ok(msg) => yield msg,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Websocket consumer lagged, skipped {} messages", skipped);
+                        lock_state(&state).on_lagged(skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    /// Get the websocket feed as a stream of messages shared behind an `Arc`, see
+    /// [`shared_receiver`](Self::shared_receiver)
+    ///
+    /// Messages missed because the consumer fell too far behind are skipped with a warning.
+    ///
+    /// ```
+    /// let mut stream = Box::pin(ws_client.shared_stream());
+    /// while let Some(msg) = stream.next().await {
+    ///     println!("{:?}", msg);
+    /// }
+    /// ```
+    ///
+    pub fn shared_stream(&self) -> impl Stream<Item = Arc<KalshiWebsocketItem>> {
+        let mut receiver = self.shared_receiver();
+        let state = Arc::clone(&self.state);
+        async_stream::stream! {
+            loop {
+                match receiver.recv().await {
+                    Ok(msg) => yield msg,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Websocket consumer lagged, skipped {} messages", skipped);
+                        lock_state(&state).on_lagged(skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    /// Get the fills of the orders selected by `filter`, ignoring fills of any other order on the account
+    ///
+    /// Requires a subscription to the `fill` channel. Ids added to the filter after the stream was
+    /// created are taken into account for every following fill, see [`KalshiFillFilter`].
+    ///
+    /// ```
+    /// let filter = KalshiFillFilter::for_client_order_ids(my_client_order_ids);
+    /// let mut fills = Box::pin(ws_client.fill_stream(filter));
+    /// while let Some(fill) = fills.next().await {
+    ///     println!("{} filled {}", fill.order_id, fill.count);
+    /// }
+    /// ```
+    ///
+    pub fn fill_stream(&self, filter: KalshiFillFilter) -> impl Stream<Item = KalshiFillMessage> {
+        let mut receiver = self.receiver();
+        let state = Arc::clone(&self.state);
+        async_stream::stream! {
+            loop {
+                match receiver.recv().await {
+                    Ok(Ok(KalshiWebsocketResponse::Fill { msg, .. })) if filter.matches(&msg) => {
+                        yield msg
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Fill consumer lagged, skipped {} messages", skipped);
+                        lock_state(&state).on_lagged(skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    /// Get bars of `length` built from the `trade` channel, as they complete
+    ///
+    /// Requires a subscription to the `trade` channel. A market's bar completes on its next trade
+    /// in a later bar, or a couple of seconds after the bar ended so markets that stopped trading
+    /// still get their bars. Open bars are completed when the connection closes.
+    /// See [`BarAggregator`].
+    ///
+    /// ```
+    /// let mut bars = Box::pin(ws_client.trade_bars(Duration::from_secs(60)));
+    /// while let Some(bar) = bars.next().await {
+    ///     println!("{} {} o{} h{} l{} c{} v{}", bar.market_ticker, bar.start_ts, bar.open, bar.high, bar.low, bar.close, bar.volume);
+    /// }
+    /// ```
+    ///
+    pub fn trade_bars(&self, length: Duration) -> impl Stream<Item = Bar> {
+        // Trades can arrive a little after the end of their bar
+        const GRACE_SECS: i64 = 2;
+
+        let mut receiver = self.receiver();
+        let state = Arc::clone(&self.state);
+        async_stream::stream! {
+            let mut aggregator = BarAggregator::new(length);
+            let mut ticks = interval(Duration::from_secs(1));
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                let msg = tokio::select! {
+                    msg = receiver.recv() => Some(msg),
+                    _ = ticks.tick() => None,
+                };
+                match msg {
+                    Some(Ok(Ok(
+                        KalshiWebsocketResponse::Trade { msg, .. }
+                        | KalshiWebsocketResponse::TradeBackfill { msg },
+                    ))) => {
+                        if let Some(bar) = aggregator.on_trade(&MarketTrade::from(msg)) {
+                            yield bar;
+                        }
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(RecvError::Lagged(skipped))) => {
+                        log::warn!("Bar consumer lagged, skipped {} messages", skipped);
+                        lock_state(&state).on_lagged(skipped);
+                    }
+                    Some(Err(RecvError::Closed)) => break,
+                    None => {
+                        let now = chrono::Utc::now().timestamp();
+                        for bar in aggregator.close_until(now - GRACE_SECS) {
+                            yield bar;
+                        }
+                    }
+                }
+            }
+            for bar in aggregator.finish() {
+                yield bar;
+            }
+        }
+    }
+
+    /// Keep `stats` current with the `trade` and `ticker` channels, yielding a market's statistics
+    /// every time they're updated
+    ///
+    /// Requires a subscription to the `trade` channel, the `ticker` channel, or both.
+    /// `stats` can be queried at any time through a clone, see [`RollingStats`].
+    ///
+    /// ```
+    /// let stats = RollingStats::new(Duration::from_secs(300));
+    /// let mut updates = Box::pin(ws_client.rolling_stats(stats.clone()));
+    /// while let Some(update) = updates.next().await {
+    ///     println!("{} vwap {:?}", update.market_ticker, update.vwap);
+    /// }
+    /// ```
+    ///
+    pub fn rolling_stats(&self, stats: RollingStats) -> impl Stream<Item = MarketStats> {
+        let mut receiver = self.receiver();
+        let state = Arc::clone(&self.state);
+        async_stream::stream! {
+            loop {
+                match receiver.recv().await {
+                    Ok(Ok(msg)) => {
+                        if let Some(update) = stats.on_message(&msg) {
+                            yield update;
+                        }
+                    }
+                    Ok(Err(_)) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Rolling stats consumer lagged, skipped {} messages", skipped);
+                        lock_state(&state).on_lagged(skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    /// Split the websocket feed into one ordered channel per market
+    ///
+    /// Every market gets its own unbounded queue, so heavy activity in one market can't delay
+    /// the processing of another while messages within a market stay in order.
+    /// See [`KalshiMarketDemux`] for details.
+    ///
+    /// ```
+    /// let mut demux = ws_client.demux_by_market();
+    /// while let Some(feed) = demux.next_market().await {
+    ///     tokio::spawn(handle_market(feed));
+    /// }
+    /// ```
+    ///
+    pub fn demux_by_market(&self) -> KalshiMarketDemux {
+        KalshiMarketDemux::new(self.receiver())
+    }
+
+    /// Start recording every raw frame received to a JSONL file at `path`
+    /// Recordings can be played back with [`KalshiReplay`](super::recording::KalshiReplay)
+    /// Starting a new recording finishes the previous one
     ///
     /// ```
+    /// ws_client.record_to("feed.jsonl").await?;
+    /// ```
     ///
-    fn close(self) -> Result<(), Box<dyn Error>> {
-        self.to_kalshi.send(KalshiCommand::End)?;
+    pub async fn record_to(&mut self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        self.stop_recording().await?;
+        let recorder = KalshiRecorder::create(path).await?;
+        lock_state(&self.state).set_recorder(Some(recorder.sender()));
+        self.recorder = Some(recorder);
         Ok(())
     }
+
+    /// Stop the active recording, flushing it to disk
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of frames written, 0 if no recording was active
+    ///
+    pub async fn stop_recording(&mut self) -> std::io::Result<u64> {
+        lock_state(&self.state).set_recorder(None);
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish().await,
+            None => Ok(0),
+        }
+    }
+
+    /// Returns a snapshot of the connection's health: message counts per channel,
+    /// bytes received, the last message time, reconnects, active subscriptions and the
+    /// throughput of each of them.
+    ///
+    /// Messages skipped by lagging consumers are only counted for the streams of this client
+    /// ([`stream`](Self::stream), [`fill_stream`](Self::fill_stream)...), not for raw
+    /// [`receiver`](Self::receiver)s.
+    ///
+    /// ```
+    /// let stats = ws_client.stats();
+    /// if let Some(last) = stats.last_message_at {
+    ///     println!("last message {:?} ago", last.elapsed());
+    /// }
+    /// for (sid, throughput) in &stats.throughput_by_sid {
+    ///     println!("sid {}: {:.0} msg/s, {} dropped", sid, throughput.messages_per_sec, throughput.dropped);
+    /// }
+    /// ```
+    ///
+    pub fn stats(&self) -> KalshiWebsocketStats {
+        lock_state(&self.state).snapshot()
+    }
+
+    /// Sets the offset between the local clock and the exchange clock used for latency measurements.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset_ms` - Milliseconds to add to local time to get exchange time,
+    ///   positive when the local clock runs behind. Usually obtained from [`Kalshi::estimate_clock_offset`].
+    ///
+    /// ```
+    /// let offset_ms = kalshi.estimate_clock_offset().await?;
+    /// ws_client.set_clock_offset(offset_ms);
+    ///
+    /// let stats = ws_client.stats();
+    /// if let Some(ticker) = stats.latency_by_channel.get(&KalshiChannel::Ticker) {
+    ///     println!("ticker latency {}ms (avg {:.0}ms)", ticker.last_ms, ticker.ewma_ms);
+    /// }
+    /// ```
+    ///
+    pub fn set_clock_offset(&self, offset_ms: i64) {
+        lock_state(&self.state).set_clock_offset_ms(offset_ms);
+    }
+
+    /// Get a handle that can shut the connection down and wait for it to be closed
+    ///
+    /// The handle is independent of the client, it keeps working after the client was moved or dropped.
+    ///
+    /// ```
+    /// let shutdown = ws_client.shutdown_handle();
+    /// tokio::spawn(run_strategy(ws_client));
+    /// // ...
+    /// shutdown.shutdown().await;
+    /// ```
+    ///
+    pub fn shutdown_handle(&self) -> KalshiShutdownHandle {
+        KalshiShutdownHandle {
+            to_kalshi: self.to_kalshi.clone(),
+            handler_done: self.handler_done.clone(),
+        }
+    }
+
+    /// Gracefully closes the websocket connection consuming the client
+    ///
+    /// Resolves once the close frame was sent and the background task has exited.
+    ///
+    /// ```
+    /// ws_client.close().await;
+    /// ```
+    ///
+    pub async fn close(self) {
+        self.shutdown_handle().shutdown().await;
+    }
+}
+
+impl Drop for KalshiWebsocketClient {
+    /// Asks the background task to close the connection without waiting for it.
+    ///
+    /// Never panics: if the task is already gone there is nothing left to close.
+    fn drop(&mut self) {
+        let _ = self.to_kalshi.send(KalshiCommand::End);
+    }
+}
+
+/// Shuts down a [`KalshiWebsocketClient`] from anywhere, see [`KalshiWebsocketClient::shutdown_handle`].
+#[derive(Clone)]
+pub struct KalshiShutdownHandle {
+    to_kalshi: UnboundedSender<KalshiCommand>,
+    handler_done: watch::Receiver<()>,
+}
+
+impl KalshiShutdownHandle {
+    /// Asks the connection to close without waiting, safe to call any number of times.
+    pub fn request_shutdown(&self) {
+        let _ = self.to_kalshi.send(KalshiCommand::End);
+    }
+
+    /// Asks the connection to close and waits until it is.
+    pub async fn shutdown(&self) {
+        self.request_shutdown();
+        self.closed().await;
+    }
+
+    /// Waits until the connection is closed, whatever the reason.
+    pub async fn closed(&self) {
+        let mut handler_done = self.handler_done.clone();
+        while handler_done.changed().await.is_ok() {}
+    }
+
+    /// Whether the connection is closed.
+    pub fn is_closed(&self) -> bool {
+        self.handler_done.has_changed().is_err()
+    }
+}
+
+/// Opens an authenticated websocket connection to the exchange.
+async fn open_ws_stream(kalshi: &Kalshi) -> Result<WsStream, Box<dyn Error + Send + Sync>> {
+    let mut req = Uri::from_str(kalshi.get_ws_url())?.into_client_request()?;
+    let ws_api_path = kalshi.extract_url_path(kalshi.get_ws_url());
+    let auth_headers = kalshi
+        .generate_auth_headers(&ws_api_path, Method::GET)
+        .map_err(|e| format!("Auth header generation failed: {}", e))?;
+    let headers = req.headers_mut();
+    for (key, val) in &auth_headers {
+        let ws_header_name =
+            tokio_tungstenite::tungstenite::http::HeaderName::from_bytes(key.as_str().as_bytes())?;
+        let ws_header_value =
+            tokio_tungstenite::tungstenite::http::HeaderValue::from_str(val.to_str()?)?;
+        headers.insert(ws_header_name, ws_header_value);
+    }
+    let req_clone = req.clone();
+    let connector = match &kalshi.tls {
+        Some(tls) => Some(Connector::NativeTls(tls.connector()?)),
+        None => None,
+    };
+    let (ws_stream, res) = connect_async_tls_with_config(req, None, false, connector)
+        .await
+        .inspect_err(|e| match e {
+            tokio_tungstenite::tungstenite::Error::Http(res) => {
+                if let Some(body) = res.body() {
+                    if let Ok(error_body) = String::from_utf8(body.to_vec()) {
+                        eprintln!("Request was {:?}", req_clone);
+                        eprintln!("Kalshi error response was {}", error_body);
+                    }
+                }
+            }
+            _ => {}
+        })?;
+    Ok(ws_stream)
+}
+
+/// A message of the websocket feed, as received from [`KalshiWebsocketClient::receiver`].
+pub type KalshiWebsocketItem = Result<KalshiWebsocketResponse, KalshiWebsocketError>;
+
+/// Broadcasts the feed to owned and shared receivers, each channel only paying for the
+/// receivers it has.
+#[derive(Clone)]
+struct FanOut {
+    owned: Sender<KalshiWebsocketItem>,
+    shared: Sender<Arc<KalshiWebsocketItem>>,
+}
+
+impl FanOut {
+    fn new(capacity: usize) -> Self {
+        FanOut {
+            owned: channel(capacity).0,
+            shared: channel(capacity).0,
+        }
+    }
+
+    /// Sends to every receiver, a message nobody receives is dropped.
+    fn send(&self, item: KalshiWebsocketItem) {
+        let owned = self.owned.receiver_count() > 0;
+        if self.shared.receiver_count() > 0 {
+            if owned {
+                let _ = self.owned.send(item.clone());
+            }
+            let _ = self.shared.send(Arc::new(item));
+        } else if owned {
+            let _ = self.owned.send(item);
+        }
+    }
+}
+
+/// Subscribes through a [`KalshiWebsocketClient`] without borrowing it, for tasks that watch
+/// markets chosen after they were spawned.
+#[derive(Debug, Clone)]
+pub(crate) struct WsSubscriber {
+    next_cmd_id: Arc<AtomicU32>,
+    to_kalshi: UnboundedSender<KalshiCommand>,
+    state: Arc<Mutex<WsState>>,
+}
+
+impl WsSubscriber {
+    /// See [`KalshiWebsocketClient::ensure_subscribed`].
+    pub(crate) fn ensure_subscribed(
+        &self,
+        channels: Vec<KalshiChannel>,
+        market_tickers: Vec<String>,
+    ) -> Result<Vec<u32>, Box<dyn Error>> {
+        if channels.contains(&KalshiChannel::OrderbookDelta) && market_tickers.is_empty() {
+            return Err("Cannot subscribe to orderbook deltas for all market tickers, provide at least one market ticker".to_string().into());
+        }
+
+        let mut cmd_ids = Vec::new();
+        for channel in channels {
+            let mut state = lock_state(&self.state);
+            let missing: Vec<String> = market_tickers
+                .iter()
+                .filter(|ticker| !state.covers(&channel, Some(ticker)))
+                .cloned()
+                .collect();
+            let covered = if market_tickers.is_empty() {
+                state.covers(&channel, None)
+            } else {
+                missing.is_empty()
+            };
+            if covered {
+                continue;
+            }
+
+            let cmd_id = self.next_cmd_id.fetch_add(1, Ordering::SeqCst);
+            let extendable = state
+                .extendable_subscription(&channel)
+                .filter(|_| !missing.is_empty());
+            let msg = match extendable {
+                Some(sid) => KalshiCommand::UpdateSubscription {
+                    id: cmd_id,
+                    params: KalshiUpdateSubscriptionCommandParams {
+                        market_tickers: missing,
+                        action: KalshiUpdateSubscriptionAction::AddMarkets,
+                        sids: [sid],
+                    },
+                },
+                None => KalshiCommand::Subscribe {
+                    id: cmd_id,
+                    params: KalshiSubscribeCommandParams {
+                        channels: vec![channel],
+                        market_tickers: missing,
+                    },
+                },
+            };
+            state.expect_ack(&msg);
+            drop(state);
+            self.to_kalshi.send(msg)?;
+            cmd_ids.push(cmd_id);
+        }
+        Ok(cmd_ids)
+    }
+}
+
+pub(super) fn lock_state(state: &Mutex<WsState>) -> MutexGuard<'_, WsState> {
+    // The state is only ever mutated in small synchronous sections, a poisoned lock
+    // still holds consistent data
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+enum SessionEnd {
+    /// The client asked to close the connection or was dropped
+    Shutdown,
+    /// The exchange closed the connection, already reported as `ConnectionClosed`
+    Closed,
+    /// The connection failed, already reported as a `WebSocketError`
+    Failed,
 }
 
 async fn kalshi_ws_handler(
-    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
-    from_kalshi_tx: Sender<Result<KalshiWebsocketResponse, KalshiWebsocketError>>,
+    kalshi: Kalshi,
+    stream: WsStream,
+    from_kalshi_tx: FanOut,
     mut to_kalshi_rx: UnboundedReceiver<KalshiCommand>,
+    state: Arc<Mutex<WsState>>,
+    next_cmd_id: Arc<AtomicU32>,
+    mut shutdown: ShutdownSignal,
 ) {
+    let mut stream = stream;
+    let mut queue = CommandQueue::default();
+    loop {
+        match kalshi_ws_session(
+            stream,
+            &from_kalshi_tx,
+            &mut to_kalshi_rx,
+            &mut queue,
+            &state,
+            &next_cmd_id,
+            &mut shutdown,
+        )
+        .await
+        {
+            SessionEnd::Shutdown => break,
+            // Without a reconnect policy the client ends with its connection
+            SessionEnd::Closed if kalshi.ws_config.reconnect.is_none() => break,
+            SessionEnd::Failed if kalshi.ws_config.reconnect.is_none() => {
+                from_kalshi_tx.send(Err(KalshiWebsocketError::ConnectionClosed));
+                break;
+            }
+            SessionEnd::Closed | SessionEnd::Failed => {
+                // The state restores them again along with the others
+                queue.drop_restored();
+                let backfill = lock_state(&state).trade_backfill();
+                match reconnect(
+                    &kalshi,
+                    &from_kalshi_tx,
+                    &mut to_kalshi_rx,
+                    &mut queue,
+                    &state,
+                    &next_cmd_id,
+                    &mut shutdown,
+                )
+                .await
+                {
+                    Some(new_stream) => {
+                        stream = new_stream;
+                        if let Some(backfill) = backfill {
+                            kalshi.shutdown_handle().spawn(
+                                "websocket trade backfill",
+                                backfill_trades(kalshi.clone(), backfill, from_kalshi_tx.clone()),
+                            );
+                        }
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+}
+
+/// Re-establishes the connection with exponential backoff and queues the commands restoring all
+/// subscriptions, ahead of the ones issued while disconnected.
+///
+/// Returns `None` if the client shut down while waiting or the reconnect policy gave up.
+async fn reconnect(
+    kalshi: &Kalshi,
+    from_kalshi_tx: &FanOut,
+    to_kalshi_rx: &mut UnboundedReceiver<KalshiCommand>,
+    queue: &mut CommandQueue,
+    state: &Mutex<WsState>,
+    next_cmd_id: &AtomicU32,
+    shutdown: &mut ShutdownSignal,
+) -> Option<WsStream> {
+    let policy = kalshi.ws_config.reconnect.unwrap_or_default();
+    let mut backoff = policy.initial_backoff;
+    let mut attempts = 0;
+
+    loop {
+        let mut wait = Box::pin(tokio::time::sleep(backoff).fuse());
+        loop {
+            select_biased! {
+                cmd = to_kalshi_rx.recv().fuse() => {
+                    match cmd {
+                        Some(KalshiCommand::End) | None => return None,
+                        Some(cmd) => queue.push(cmd),
+                    }
+                }
+                _ = shutdown.wait().fuse() => {
+                    from_kalshi_tx.send(Err(KalshiWebsocketError::ConnectionClosed));
+                    return None;
+                }
+                _ = wait => break,
+            }
+        }
+
+        let attempt = open_ws_stream(kalshi)
+            .await
+            .map_err(|e| KalshiWebsocketError::WebSocketError(e.to_string()));
+        match attempt {
+            Ok(stream) => {
+                let cmds = {
+                    let mut state = lock_state(state);
+                    state.on_connected(true);
+                    state.resubscribe_commands(next_cmd_id)
+                };
+                queue.restore(cmds);
+                return Some(stream);
+            }
+            Err(e) => {
+                from_kalshi_tx.send(Err(e));
+                attempts += 1;
+                if policy.max_attempts.is_some_and(|max| attempts >= max) {
+                    from_kalshi_tx.send(Err(KalshiWebsocketError::ConnectionClosed));
+                    return None;
+                }
+                backoff = (backoff * 2).min(policy.max_backoff);
+            }
+        }
+    }
+}
+
+/// Fetches the trades missed while disconnected and sends them as
+/// [`KalshiWebsocketResponse::TradeBackfill`], market by market in time order.
+async fn backfill_trades(kalshi: Kalshi, backfill: TradeBackfill, from_kalshi_tx: FanOut) {
+    let until_ts = chrono::Utc::now().timestamp();
+    for market_ticker in backfill.market_tickers {
+        let mut trades = Vec::new();
+        let mut pages = Box::pin(
+            kalshi
+                .get_trades(
+                    None,
+                    None,
+                    None,
+                    market_ticker,
+                    Some(backfill.since_ts),
+                    Some(until_ts),
+                )
+                .await,
+        );
+        while let Some(trade) = pages.next().await {
+            match trade.and_then(MarketTrade::try_from) {
+                Ok(trade) if !backfill.received.contains(&trade.trade_id) => trades.push(trade),
+                Ok(_) => {}
+                Err(e) => {
+                    from_kalshi_tx.send(Err(KalshiWebsocketError::BackfillFailed(e.to_string())));
+                    return;
+                }
+            }
+        }
+        // Trades come newest first
+        trades.reverse();
+        trades.sort_by_key(|trade| trade.ts);
+        for trade in trades {
+            let msg = KalshiTradeMessage {
+                trade_id: trade.trade_id,
+                market_ticker: trade.market_ticker.into(),
+                yes_price: trade.yes_price,
+                no_price: trade.no_price,
+                count: trade.count,
+                taker_side: match trade.taker_side {
+                    Side::Yes => KalshiSide::Yes,
+                    Side::No => KalshiSide::No,
+                },
+                ts: trade.ts as u32,
+            };
+            from_kalshi_tx.send(Ok(KalshiWebsocketResponse::TradeBackfill { msg }));
+        }
+    }
+}
+
+/// Sends the queued commands the rate limit allows, returns the error if the connection is lost.
+///
+/// A command that couldn't be written stays queued for the next connection, unless restoring
+/// the subscriptions already sends it again.
+async fn flush_commands(
+    stream: &mut (impl Sink<Message, Error = tungstenite::Error> + Unpin),
+    from_kalshi_tx: &FanOut,
+    queue: &mut CommandQueue,
+    state: &Mutex<WsState>,
+) -> Result<(), tungstenite::Error> {
+    queue.set_limit(lock_state(state).command_rate_limit());
+    while let Some(mut cmd) = queue.pop_ready(Instant::now()) {
+        lock_state(state).on_command(&mut cmd);
+        match serde_json::to_string(&cmd) {
+            Ok(msg) => {
+                if let Err(e) = stream.send(Message::text(msg)).await {
+                    if !restored_on_reconnect(&cmd) {
+                        queue.requeue(cmd);
+                    }
+                    return Err(e);
+                }
+            }
+            Err(e) => {
+                from_kalshi_tx.send(Err(KalshiWebsocketError::SerializationError(e.to_string())));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn kalshi_ws_session(
+    stream: WsStream,
+    from_kalshi_tx: &FanOut,
+    to_kalshi_rx: &mut UnboundedReceiver<KalshiCommand>,
+    queue: &mut CommandQueue,
+    state: &Mutex<WsState>,
+    next_cmd_id: &AtomicU32,
+    shutdown: &mut ShutdownSignal,
+) -> SessionEnd {
     let mut stream = Box::pin(stream.fuse());
     let mut heartbeat = interval(Duration::from_secs(10));
     heartbeat.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut ack_check = interval(Duration::from_secs(1));
+    ack_check.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    loop {
+        // A failed send means the connection is gone
+        if let Err(e) = flush_commands(&mut stream, from_kalshi_tx, queue, state).await {
+            from_kalshi_tx.send(Err(KalshiWebsocketError::WebSocketError(e.to_string())));
+            return SessionEnd::Failed;
+        }
+        let next_ready_at = queue.next_ready_at();
+        let command_ready = async move {
+            match next_ready_at {
+                Some(at) => tokio::time::sleep_until(at.into()).await,
+                None => futures_util::future::pending().await,
+            }
+        };
 
-    'out: loop {
         select_biased! {
             cmd = to_kalshi_rx.recv().fuse() => {
                 match cmd {
-                    Some(cmd) => {
-                        match serde_json::to_string(&cmd) {
-                            Ok(msg) => {
-                                stream.send(Message::text(msg)).await.unwrap();
-                            },
-                            Err(e) => {
-                                from_kalshi_tx.send(Err(KalshiWebsocketError::SerializationError(e.to_string())));
-                            }
-                        }
-
-                    },
-                    _ => {
+                    Some(KalshiCommand::End) => {
+                        let _ = stream.close().await;
+                        return SessionEnd::Shutdown;
+                    }
+                    Some(cmd) => queue.push(cmd),
+                    None => {
                         from_kalshi_tx.send(Err(KalshiWebsocketError::ConnectionClosed));
-                        break 'out;
+                        return SessionEnd::Shutdown;
                     }
                 }
             }
+            _ = shutdown.wait().fuse() => {
+                let _ = stream.close().await;
+                from_kalshi_tx.send(Err(KalshiWebsocketError::ConnectionClosed));
+                return SessionEnd::Shutdown;
+            }
+            _ = command_ready.fuse() => {}
+            _ = ack_check.tick().fuse() => {
+                for id in lock_state(state).expired_commands(Instant::now()) {
+                    from_kalshi_tx.send(Err(KalshiWebsocketError::CommandTimeout { id }));
+                }
+            }
             _ = heartbeat.tick().fuse() => {
-                match stream.send(Message::Ping(vec![])).await {
-                    Err(e) => {
-                        from_kalshi_tx.send(Err(KalshiWebsocketError::WebSocketError(e.to_string())));
-                    },
-                    _ => {}
+                if let Err(e) = stream.send(Message::Ping(vec![])).await {
+                    from_kalshi_tx.send(Err(KalshiWebsocketError::WebSocketError(e.to_string())));
+                    return SessionEnd::Failed;
                 }
             }
-            item = stream.select_next_some() => {
+            item = stream.next() => {
                 match item {
-                    Ok(msg) => {
+                    Some(Ok(msg)) => {
                         match msg {
                             Message::Text(text) => {
-                                match serde_json::from_str::<KalshiWebsocketResponse>(&text) {
-                                    Ok(res) => from_kalshi_tx.send(Ok(res)),
-                                    Err(e) => from_kalshi_tx.send(Err(KalshiWebsocketError::SerializationError(e.to_string()))),
+                                let received_at = Instant::now();
+                                lock_state(state).on_frame(&text);
+                                match KalshiWebsocketResponse::from_text(&text) {
+                                    Ok(res) => {
+                                        let follow_ups = {
+                                            let mut state = lock_state(state);
+                                            state.on_response(&res, text.len(), received_at);
+                                            let mut follow_ups = state.followed_event_updates(&res, next_cmd_id);
+                                            follow_ups.extend(state.stream_unsubscribes(&res, next_cmd_id));
+                                            follow_ups
+                                        };
+                                        from_kalshi_tx.send(Ok(res));
+                                        for cmd in follow_ups {
+                                            queue.push(cmd);
+                                        }
+                                    },
+                                    Err(e) => {
+                                        from_kalshi_tx.send(Err(KalshiWebsocketError::SerializationError(e.to_string())));
+                                    },
                                 };
                             },
                             Message::Close(_) => {
                                 from_kalshi_tx.send(Err(KalshiWebsocketError::ConnectionClosed));
-                                break 'out;
+                                return SessionEnd::Closed;
                             }
                             // Pings should be automatically handled by tokio_tungstenite
                             // All other messages are unhandled
                             _ => {}
                         }
                     },
-                    Err(e) => {
-                       from_kalshi_tx.send(Err(KalshiWebsocketError::WebSocketError(e.to_string())));
+                    Some(Err(e)) => {
+                        from_kalshi_tx.send(Err(KalshiWebsocketError::WebSocketError(e.to_string())));
+                        return SessionEnd::Failed;
+                    }
+                    None => {
+                        from_kalshi_tx.send(Err(KalshiWebsocketError::ConnectionClosed));
+                        return SessionEnd::Closed;
                     }
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::MockWsServer;
+
+    #[tokio::test]
+    async fn test_commands_kept_when_send_fails() {
+        let server = MockWsServer::start().await.unwrap();
+        let mut stream = open_ws_stream(&server.kalshi()).await.unwrap();
+        while server.connection_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        server.disconnect_all();
+        while let Some(Ok(msg)) = stream.next().await {
+            if msg.is_close() {
+                break;
+            }
+        }
+
+        let from_kalshi_tx = FanOut::new(16);
+        let state = Mutex::new(WsState::default());
+        let mut queue = CommandQueue::default();
+        let subscribe = KalshiCommand::Subscribe {
+            id: 1,
+            params: KalshiSubscribeCommandParams {
+                channels: vec![KalshiChannel::Fill],
+                market_tickers: vec![],
+            },
+        };
+        lock_state(&state).expect_ack(&subscribe);
+        queue.push(subscribe);
+        queue.push(KalshiCommand::Unsubscribe {
+            id: 2,
+            params: KalshiUnsubscribeCommandParams { sids: vec![7] },
+        });
+
+        // The subscribe is restored with the others, the unsubscribe waits for the next connection
+        assert!(
+            flush_commands(&mut stream, &from_kalshi_tx, &mut queue, &state)
+                .await
+                .is_err()
+        );
+        assert!(
+            flush_commands(&mut stream, &from_kalshi_tx, &mut queue, &state)
+                .await
+                .is_err()
+        );
+        assert!(matches!(
+            queue.pop_ready(Instant::now()),
+            Some(KalshiCommand::Unsubscribe { id: 2, .. })
+        ));
+        assert!(queue.pop_ready(Instant::now()).is_none());
+        let restored = lock_state(&state).resubscribe_commands(&AtomicU32::new(3));
+        assert!(matches!(
+            restored[..],
+            [KalshiCommand::Subscribe { id: 3, .. }]
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use futures_util::Stream;
+use tokio::{
+    sync::{
+        broadcast::{error::RecvError, Receiver},
+        mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    },
+    task::JoinHandle,
+};
+
+use super::{client::KalshiWebsocketError, responses::KalshiWebsocketResponse};
+
+type FeedItem = Result<KalshiWebsocketResponse, KalshiWebsocketError>;
+
+/// The websocket feed split into one ordered channel per market.
+///
+/// Created with [`KalshiWebsocketClient::demux_by_market`](super::client::KalshiWebsocketClient::demux_by_market).
+/// A background task routes every message that belongs to a market into that market's
+/// [`KalshiMarketFeed`], so a slow consumer of one busy market never holds up another market.
+/// Messages of a single market are always delivered in the order they were received.
+///
+/// Messages that don't belong to a market (acks, errors, event lifecycle and unknown messages)
+/// are delivered through [`KalshiMarketDemux::next_other`].
+///
+/// ```
+/// let mut demux = ws_client.demux_by_market();
+/// while let Some(mut feed) = demux.next_market().await {
+///     tokio::spawn(async move {
+///         while let Some(msg) = feed.recv().await {
+///             println!("{}: {:?}", feed.market_ticker(), msg);
+///         }
+///     });
+/// }
+/// ```
+pub struct KalshiMarketDemux {
+    new_markets: UnboundedReceiver<KalshiMarketFeed>,
+    other: UnboundedReceiver<FeedItem>,
+    router: JoinHandle<()>,
+}
+
+impl KalshiMarketDemux {
+    pub(super) fn new(receiver: Receiver<FeedItem>) -> Self {
+        let (markets_tx, new_markets) = unbounded_channel();
+        let (other_tx, other) = unbounded_channel();
+        let router = tokio::spawn(route(receiver, markets_tx, other_tx));
+        KalshiMarketDemux {
+            new_markets,
+            other,
+            router,
+        }
+    }
+
+    /// Waits for the first message of a market not seen before and returns that market's feed.
+    ///
+    /// Returns `None` once the websocket client has shut down.
+    pub async fn next_market(&mut self) -> Option<KalshiMarketFeed> {
+        self.new_markets.recv().await
+    }
+
+    /// Waits for the next message that doesn't belong to a market, or a feed error.
+    ///
+    /// Returns `None` once the websocket client has shut down.
+    pub async fn next_other(&mut self) -> Option<FeedItem> {
+        self.other.recv().await
+    }
+}
+
+impl Drop for KalshiMarketDemux {
+    fn drop(&mut self) {
+        self.router.abort();
+    }
+}
+
+/// The ordered messages of a single market, see [`KalshiMarketDemux`].
+///
+/// Dropping a feed discards all further messages of its market.
+pub struct KalshiMarketFeed {
+    market_ticker: String,
+    rx: UnboundedReceiver<KalshiWebsocketResponse>,
+}
+
+impl KalshiMarketFeed {
+    pub fn market_ticker(&self) -> &str {
+        &self.market_ticker
+    }
+
+    /// Waits for the next message of this market, `None` once the client has shut down.
+    pub async fn recv(&mut self) -> Option<KalshiWebsocketResponse> {
+        self.rx.recv().await
+    }
+
+    /// Converts the feed into a stream of messages.
+    pub fn into_stream(mut self) -> impl Stream<Item = KalshiWebsocketResponse> {
+        async_stream::stream! {
+            while let Some(msg) = self.rx.recv().await {
+                yield msg;
+            }
+        }
+    }
+}
+
+async fn route(
+    mut receiver: Receiver<FeedItem>,
+    new_markets: UnboundedSender<KalshiMarketFeed>,
+    other: UnboundedSender<FeedItem>,
+) {
+    let mut markets: HashMap<String, UnboundedSender<KalshiWebsocketResponse>> = HashMap::new();
+
+    loop {
+        let item = match receiver.recv().await {
+            Ok(item) => item,
+            Err(RecvError::Lagged(skipped)) => {
+                log::warn!("Market demux lagged, skipped {} messages", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        let msg = match item {
+            Ok(msg) if msg.market_ticker().is_some() => msg,
+            item => {
+                let _ = other.send(item);
+                continue;
+            }
+        };
+
+        let ticker = msg.market_ticker().unwrap_or_default();
+        if let Some(tx) = markets.get(ticker) {
+            // A closed feed means the consumer isn't interested in this market anymore
+            let _ = tx.send(msg);
+            continue;
+        }
+
+        let (tx, rx) = unbounded_channel();
+        let market_ticker = ticker.to_string();
+        let _ = tx.send(msg);
+        let _ = new_markets.send(KalshiMarketFeed {
+            market_ticker: market_ticker.clone(),
+            rx,
+        });
+        markets.insert(market_ticker, tx);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::websockets::responses::{KalshiSide, KalshiTradeMessage};
+    use tokio::sync::broadcast::channel;
+
+    fn trade(market_ticker: &str, count: u32) -> FeedItem {
+        Ok(KalshiWebsocketResponse::Trade {
+            sid: 1,
+            msg: KalshiTradeMessage {
+                trade_id: "5b0276ef-7715-46f2-56d8-a1c7b9e59e58".to_string(),
+                market_ticker: market_ticker.into(),
+                yes_price: 40,
+                no_price: 60,
+                count,
+                taker_side: KalshiSide::Yes,
+                ts: 1759350609,
+            },
+        })
+    }
+
+    fn count(msg: KalshiWebsocketResponse) -> u32 {
+        match msg {
+            KalshiWebsocketResponse::Trade { msg, .. } => msg.count,
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_messages_routed_per_market_in_order() {
+        let (tx, rx) = channel(16);
+        let mut demux = KalshiMarketDemux::new(rx);
+
+        tx.send(trade("KXHIGHNY-A", 1)).unwrap();
+        tx.send(trade("KXHIGHNY-B", 1)).unwrap();
+        tx.send(Ok(KalshiWebsocketResponse::Unsubscribed { sid: 3 }))
+            .unwrap();
+        tx.send(trade("KXHIGHNY-A", 2)).unwrap();
+        drop(tx);
+
+        let mut a = demux.next_market().await.unwrap();
+        let mut b = demux.next_market().await.unwrap();
+        assert_eq!(a.market_ticker(), "KXHIGHNY-A");
+        assert_eq!(b.market_ticker(), "KXHIGHNY-B");
+        assert!(demux.next_market().await.is_none());
+
+        assert_eq!(count(a.recv().await.unwrap()), 1);
+        assert_eq!(count(a.recv().await.unwrap()), 2);
+        assert!(a.recv().await.is_none());
+        assert_eq!(count(b.recv().await.unwrap()), 1);
+        assert!(matches!(
+            demux.next_other().await,
+            Some(Ok(KalshiWebsocketResponse::Unsubscribed { sid: 3 }))
+        ));
+    }
+}
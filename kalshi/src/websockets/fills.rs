@@ -0,0 +1,152 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::Order;
+
+use super::responses::KalshiFillMessage;
+
+/// Selects the fills that belong to a set of orders.
+///
+/// A fill matches when its `client_order_id` or its `order_id` was added to the filter.
+/// Fills don't say which order group they belong to, so order groups are resolved to the
+/// ids of their orders with [`KalshiFillFilter::add_order_group`].
+///
+/// The filter is a shared handle, cloning it and adding ids to the clone also affects
+/// streams already created with it. This lets an execution algorithm start listening
+/// before placing its orders and register every order as it goes.
+///
+/// ```
+/// let filter = KalshiFillFilter::new();
+/// let mut fills = Box::pin(ws_client.fill_stream(filter.clone()));
+///
+/// let client_order_id = uuid::Uuid::new_v4().to_string();
+/// filter.add_client_order_id(&client_order_id);
+/// kalshi.create_order(/* ... */ Some(client_order_id), /* ... */).await?;
+///
+/// while let Some(fill) = fills.next().await {
+///     println!("filled {} @ {}", fill.count, fill.yes_price);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KalshiFillFilter {
+    inner: Arc<Mutex<FillFilterInner>>,
+}
+
+#[derive(Debug, Default)]
+struct FillFilterInner {
+    client_order_ids: HashSet<String>,
+    order_ids: HashSet<String>,
+}
+
+impl KalshiFillFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a filter matching the given client order ids.
+    pub fn for_client_order_ids<I, S>(client_order_ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let filter = Self::new();
+        filter
+            .lock()
+            .client_order_ids
+            .extend(client_order_ids.into_iter().map(Into::into));
+        filter
+    }
+
+    /// Creates a filter matching the orders of `order_group_id` among `orders`.
+    pub fn for_order_group(order_group_id: &str, orders: &[Order]) -> Self {
+        let filter = Self::new();
+        filter.add_order_group(order_group_id, orders);
+        filter
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FillFilterInner> {
+        self.inner.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    pub fn add_client_order_id(&self, client_order_id: &str) {
+        self.lock()
+            .client_order_ids
+            .insert(client_order_id.to_string());
+    }
+
+    pub fn remove_client_order_id(&self, client_order_id: &str) {
+        self.lock().client_order_ids.remove(client_order_id);
+    }
+
+    pub fn add_order_id(&self, order_id: &str) {
+        self.lock().order_ids.insert(order_id.to_string());
+    }
+
+    pub fn remove_order_id(&self, order_id: &str) {
+        self.lock().order_ids.remove(order_id);
+    }
+
+    /// Adds every order of `orders` that belongs to `order_group_id`, returning how many were added.
+    pub fn add_order_group(&self, order_group_id: &str, orders: &[Order]) -> usize {
+        let mut inner = self.lock();
+        let before = inner.order_ids.len();
+        inner.order_ids.extend(
+            orders
+                .iter()
+                .filter(|order| order.order_group_id == order_group_id)
+                .map(|order| order.order_id.clone()),
+        );
+        inner.order_ids.len() - before
+    }
+
+    /// Whether the fill belongs to one of the filtered orders.
+    pub fn matches(&self, fill: &KalshiFillMessage) -> bool {
+        let inner = self.lock();
+        inner.order_ids.contains(&fill.order_id)
+            || fill
+                .client_order_id
+                .as_ref()
+                .is_some_and(|id| inner.client_order_ids.contains(id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::websockets::responses::KalshiSide;
+
+    fn fill(order_id: &str, client_order_id: Option<&str>) -> KalshiFillMessage {
+        KalshiFillMessage {
+            trade_id: "d91bc706-ee49-470d-82d8-11418bda6fed".to_string(),
+            order_id: order_id.to_string(),
+            market_ticker: "HIGHNY-22DEC23-B53.5".into(),
+            is_taker: true,
+            side: KalshiSide::Yes,
+            yes_price: 75,
+            no_price: 25,
+            count: 278,
+            action: "buy".to_string(),
+            ts: 1671899397,
+            client_order_id: client_order_id.map(str::to_string),
+            post_position: 500,
+            purchased_side: KalshiSide::Yes,
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_registered_orders_only() {
+        let filter = KalshiFillFilter::for_client_order_ids(["mm-1"]);
+        let shared = filter.clone();
+        shared.add_order_id("ee587a1c-8b87-4dcf-b721-9f6f790619fa");
+
+        assert!(filter.matches(&fill("a", Some("mm-1"))));
+        assert!(filter.matches(&fill("ee587a1c-8b87-4dcf-b721-9f6f790619fa", None)));
+        assert!(!filter.matches(&fill("b", Some("other-strategy"))));
+        assert!(!filter.matches(&fill("c", None)));
+
+        shared.remove_client_order_id("mm-1");
+        assert!(!filter.matches(&fill("a", Some("mm-1"))));
+    }
+}
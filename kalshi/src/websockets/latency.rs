@@ -0,0 +1,125 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Smoothing factor of [`KalshiFeedLatency::ewma_ms`], higher values react faster to changes.
+const EWMA_ALPHA: f64 = 0.1;
+
+/// Latency between the exchange timestamp of a message and the moment it was received.
+///
+/// Latencies are in milliseconds and already corrected by the clock offset configured with
+/// [`KalshiWebsocketClient::set_clock_offset`](super::client::KalshiWebsocketClient::set_clock_offset).
+/// Kalshi timestamps messages with whole seconds, so individual samples carry up to a second of
+/// truncation error (always making the latency look larger), the trend and the spikes are what matter.
+/// Samples can be negative when the local clock runs behind the exchange's.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct KalshiFeedLatency {
+    /// Number of messages measured.
+    pub samples: u64,
+    /// Latency of the most recent message.
+    pub last_ms: i64,
+    /// Lowest latency seen.
+    pub min_ms: i64,
+    /// Highest latency seen.
+    pub max_ms: i64,
+    /// Mean latency over all samples.
+    pub mean_ms: f64,
+    /// Exponentially weighted moving average, tracks the recent latency.
+    pub ewma_ms: f64,
+}
+
+impl KalshiFeedLatency {
+    pub(super) fn record(&mut self, latency_ms: i64) {
+        if self.samples == 0 {
+            self.min_ms = latency_ms;
+            self.max_ms = latency_ms;
+            self.ewma_ms = latency_ms as f64;
+        } else {
+            self.min_ms = self.min_ms.min(latency_ms);
+            self.max_ms = self.max_ms.max(latency_ms);
+            self.ewma_ms += EWMA_ALPHA * (latency_ms as f64 - self.ewma_ms);
+        }
+        self.samples += 1;
+        self.last_ms = latency_ms;
+        self.mean_ms += (latency_ms as f64 - self.mean_ms) / self.samples as f64;
+    }
+}
+
+/// Wall clock derived from a monotonic clock.
+///
+/// The wall time is read once and then advanced with [`Instant`], so adjustments of the system
+/// clock (NTP steps, manual changes) can't produce latency spikes on their own.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct FeedClock {
+    anchor: Instant,
+    anchor_unix_ms: i64,
+    /// Milliseconds to add to local time to get exchange time
+    offset_ms: i64,
+}
+
+impl Default for FeedClock {
+    fn default() -> Self {
+        let anchor_unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default();
+        FeedClock {
+            anchor: Instant::now(),
+            anchor_unix_ms,
+            offset_ms: 0,
+        }
+    }
+}
+
+impl FeedClock {
+    pub(super) fn offset_ms(&self) -> i64 {
+        self.offset_ms
+    }
+
+    pub(super) fn set_offset_ms(&mut self, offset_ms: i64) {
+        self.offset_ms = offset_ms;
+    }
+
+    /// Exchange time in unix milliseconds at the local instant `at`.
+    pub(super) fn exchange_ms_at(&self, at: Instant) -> i64 {
+        let elapsed = at.saturating_duration_since(self.anchor).as_millis() as i64;
+        self.anchor_unix_ms + elapsed + self.offset_ms
+    }
+
+    /// Latency of a message stamped `exchange_ts` (unix seconds) received at `received_at`.
+    pub(super) fn latency_ms(&self, exchange_ts: u32, received_at: Instant) -> i64 {
+        self.exchange_ms_at(received_at) - exchange_ts as i64 * 1000
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_latency_uses_offset_and_monotonic_clock() {
+        let mut clock = FeedClock {
+            anchor: Instant::now(),
+            anchor_unix_ms: 1_759_350_609_000,
+            offset_ms: 0,
+        };
+        let received_at = clock.anchor + Duration::from_millis(250);
+        assert_eq!(clock.latency_ms(1_759_350_609, received_at), 250);
+
+        // Local clock 400ms behind the exchange
+        clock.set_offset_ms(400);
+        assert_eq!(clock.latency_ms(1_759_350_609, received_at), 650);
+    }
+
+    #[test]
+    fn test_latency_aggregates() {
+        let mut latency = KalshiFeedLatency::default();
+        for sample in [100, 300, -20, 120] {
+            latency.record(sample);
+        }
+        assert_eq!(latency.samples, 4);
+        assert_eq!(latency.last_ms, 120);
+        assert_eq!(latency.min_ms, -20);
+        assert_eq!(latency.max_ms, 300);
+        assert_eq!(latency.mean_ms, 125.0);
+    }
+}
@@ -1,13 +1,21 @@
 use serde::{Deserialize, Serialize};
 
 mod commands;
+mod state;
 
+pub mod channel_stream;
 pub mod client;
+pub mod demux;
+pub mod fills;
+pub mod latency;
+pub mod rate_limit;
+pub mod recording;
+pub mod stats;
 
 #[allow(dead_code)]
 pub mod responses;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum KalshiChannel {
     OrderbookDelta,
@@ -0,0 +1,184 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use super::commands::KalshiCommand;
+
+/// A token bucket limiting the commands written to the websocket connection.
+///
+/// Up to `burst` commands are sent at once, then `per_second` a second. Commands over the limit
+/// are queued in order and sent as the bucket refills, the feed keeps being read meanwhile.
+/// Subscriptions restored after a reconnect go through the limit as well.
+///
+/// ```
+/// // Subscribe to hundreds of markets without tripping the exchange's command limits
+/// ws_client.set_command_rate_limit(Some(KalshiCommandRateLimit::new(10.0, 20)));
+/// for ticker in tickers {
+///     ws_client.subscribe(vec![KalshiChannel::OrderbookDelta], vec![ticker]).await?;
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KalshiCommandRateLimit {
+    /// Commands sent per second once the burst is spent.
+    pub per_second: f64,
+    /// Commands sent at once after an idle period.
+    pub burst: u32,
+}
+
+impl KalshiCommandRateLimit {
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        KalshiCommandRateLimit {
+            per_second: per_second.max(f64::MIN_POSITIVE),
+            burst: burst.max(1),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct QueuedCommand {
+    cmd: KalshiCommand,
+    /// Restores a subscription after a reconnect, the state recreates it if the connection is
+    /// lost again before it's sent
+    restore: bool,
+}
+
+/// The commands waiting to be written, in order, with the rate limit's bucket.
+#[derive(Debug, Default)]
+pub(super) struct CommandQueue {
+    queue: VecDeque<QueuedCommand>,
+    limit: Option<KalshiCommandRateLimit>,
+    tokens: f64,
+    refilled_at: Option<Instant>,
+}
+
+impl CommandQueue {
+    /// Changes the limit, starting over with a full bucket.
+    pub(super) fn set_limit(&mut self, limit: Option<KalshiCommandRateLimit>) {
+        if self.limit != limit {
+            self.limit = limit;
+            self.tokens = limit.map_or(0.0, |limit| limit.burst as f64);
+            self.refilled_at = None;
+        }
+    }
+
+    pub(super) fn push(&mut self, cmd: KalshiCommand) {
+        self.queue.push_back(QueuedCommand {
+            cmd,
+            restore: false,
+        });
+    }
+
+    /// Queues the commands restoring the subscriptions ahead of every other command.
+    pub(super) fn restore(&mut self, cmds: Vec<KalshiCommand>) {
+        for cmd in cmds.into_iter().rev() {
+            self.queue.push_front(QueuedCommand { cmd, restore: true });
+        }
+    }
+
+    /// Puts back a command that couldn't be written, ahead of every other command, and gives
+    /// back its token.
+    pub(super) fn requeue(&mut self, cmd: KalshiCommand) {
+        if let Some(limit) = self.limit {
+            self.tokens = (self.tokens + 1.0).min(limit.burst as f64);
+        }
+        self.queue.push_front(QueuedCommand {
+            cmd,
+            restore: false,
+        });
+    }
+
+    /// Drops the restoring commands not sent before the connection was lost.
+    pub(super) fn drop_restored(&mut self) {
+        self.queue.retain(|queued| !queued.restore);
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let Some(limit) = self.limit else {
+            return;
+        };
+        if let Some(refilled_at) = self.refilled_at {
+            let elapsed = now.saturating_duration_since(refilled_at).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * limit.per_second).min(limit.burst as f64);
+        }
+        self.refilled_at = Some(now);
+    }
+
+    /// The next command if the limit allows sending it now.
+    pub(super) fn pop_ready(&mut self, now: Instant) -> Option<KalshiCommand> {
+        if self.queue.is_empty() {
+            return None;
+        }
+        if self.limit.is_some() {
+            self.refill(now);
+            if self.tokens < 1.0 {
+                return None;
+            }
+            self.tokens -= 1.0;
+        }
+        self.queue.pop_front().map(|queued| queued.cmd)
+    }
+
+    /// When the next queued command may be sent, `None` when nothing is waiting for the limit.
+    pub(super) fn next_ready_at(&self) -> Option<Instant> {
+        let limit = self.limit?;
+        if self.queue.is_empty() || self.tokens >= 1.0 {
+            return None;
+        }
+        let wait = (1.0 - self.tokens) / limit.per_second;
+        Some(self.refilled_at? + Duration::from_secs_f64(wait))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::websockets::commands::KalshiUnsubscribeCommandParams;
+
+    fn unsubscribe(id: u32) -> KalshiCommand {
+        KalshiCommand::Unsubscribe {
+            id,
+            params: KalshiUnsubscribeCommandParams { sids: vec![id] },
+        }
+    }
+
+    fn id(cmd: KalshiCommand) -> u32 {
+        match cmd {
+            KalshiCommand::Unsubscribe { id, .. } => id,
+            other => panic!("Unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_commands_released_by_the_bucket() {
+        let mut queue = CommandQueue::default();
+        queue.set_limit(Some(KalshiCommandRateLimit::new(2.0, 2)));
+        for i in 1..=4 {
+            queue.push(unsubscribe(i));
+        }
+        let start = Instant::now();
+        assert_eq!(queue.pop_ready(start).map(id), Some(1));
+        assert_eq!(queue.pop_ready(start).map(id), Some(2));
+        assert!(queue.pop_ready(start).is_none());
+
+        let next = queue.next_ready_at().unwrap();
+        assert_eq!(next - start, Duration::from_millis(500));
+        assert_eq!(queue.pop_ready(next).map(id), Some(3));
+        assert!(queue.pop_ready(next).is_none());
+
+        // Restored subscriptions go first and are dropped on a disconnect
+        queue.restore(vec![unsubscribe(10), unsubscribe(11)]);
+        let later = next + Duration::from_secs(1);
+        assert_eq!(queue.pop_ready(later).map(id), Some(10));
+        queue.drop_restored();
+        assert_eq!(queue.pop_ready(later).map(id), Some(4));
+
+        // Without a limit everything is sent right away
+        queue.set_limit(None);
+        for i in 1..=100 {
+            queue.push(unsubscribe(i));
+        }
+        assert_eq!((0..100).filter_map(|_| queue.pop_ready(start)).count(), 100);
+        assert!(queue.next_ready_at().is_none());
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    io,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter},
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+    task::JoinHandle,
+    time::Instant,
+};
+
+use super::{client::KalshiWebsocketError, responses::KalshiWebsocketResponse};
+
+/// A single raw websocket text frame together with the time it was received.
+///
+/// Recordings are stored as JSON lines, one `RecordedFrame` per line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordedFrame {
+    /// Local receive time in microseconds since the unix epoch.
+    pub ts: u64,
+    /// The raw text frame exactly as sent by the exchange.
+    pub frame: String,
+}
+
+impl RecordedFrame {
+    pub fn now(frame: &str) -> Self {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or_default();
+        RecordedFrame {
+            ts,
+            frame: frame.to_string(),
+        }
+    }
+}
+
+/// Writes raw websocket frames to a JSONL file from a background task.
+///
+/// Usually driven by [`KalshiWebsocketClient::record_to`](super::client::KalshiWebsocketClient::record_to),
+/// but frames can also be recorded manually with [`KalshiRecorder::record`].
+pub struct KalshiRecorder {
+    tx: UnboundedSender<RecordedFrame>,
+    writer: JoinHandle<io::Result<u64>>,
+}
+
+impl KalshiRecorder {
+    /// Creates (or truncates) the file at `path` and starts the writer task.
+    pub async fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::create(path).await?;
+        let (tx, mut rx) = unbounded_channel::<RecordedFrame>();
+
+        let writer = tokio::spawn(async move {
+            let mut out = BufWriter::new(file);
+            let mut written = 0;
+            while let Some(frame) = rx.recv().await {
+                write_frame(&mut out, &frame).await?;
+                written += 1;
+                while let Ok(frame) = rx.try_recv() {
+                    write_frame(&mut out, &frame).await?;
+                    written += 1;
+                }
+                // Flush once the backlog is drained so a crash loses as little as possible
+                out.flush().await?;
+            }
+            out.flush().await?;
+            Ok(written)
+        });
+
+        Ok(KalshiRecorder { tx, writer })
+    }
+
+    /// Records a frame, timestamped with the current time.
+    pub fn record(&self, frame: &str) {
+        let _ = self.tx.send(RecordedFrame::now(frame));
+    }
+
+    pub(super) fn sender(&self) -> UnboundedSender<RecordedFrame> {
+        self.tx.clone()
+    }
+
+    /// Flushes all recorded frames to disk, returning how many were written.
+    ///
+    /// Every other handle to this recorder must have been released for this to complete.
+    pub async fn finish(self) -> io::Result<u64> {
+        drop(self.tx);
+        self.writer
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+    }
+}
+
+async fn write_frame(out: &mut BufWriter<File>, frame: &RecordedFrame) -> io::Result<()> {
+    let mut line = serde_json::to_vec(frame)?;
+    line.push(b'\n');
+    out.write_all(&line).await
+}
+
+/// How fast a recording is played back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Frames are delivered with the same spacing they were recorded with.
+    Realtime,
+    /// Frames are delivered this many times faster than they were recorded.
+    Multiplier(f64),
+    /// Frames are delivered as fast as they can be read.
+    Unthrottled,
+}
+
+/// Plays back a recording made by [`KalshiRecorder`] as the same typed stream a live
+/// [`KalshiWebsocketClient`](super::client::KalshiWebsocketClient) produces.
+///
+/// ```
+/// let replay = KalshiReplay::new("feed.jsonl").speed(ReplaySpeed::Multiplier(10.0));
+/// let mut stream = Box::pin(replay.stream());
+/// while let Some(msg) = stream.next().await {
+///     strategy.on_message(msg?);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct KalshiReplay {
+    path: std::path::PathBuf,
+    speed: ReplaySpeed,
+}
+
+impl KalshiReplay {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        KalshiReplay {
+            path: path.as_ref().to_path_buf(),
+            speed: ReplaySpeed::Realtime,
+        }
+    }
+
+    pub fn speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Streams the recorded messages.
+    ///
+    /// Unreadable files and corrupt lines are reported as `WebSocketError`s,
+    /// frames that fail to parse are reported as `SerializationError`s just like on a live feed.
+    pub fn stream(
+        self,
+    ) -> impl Stream<Item = Result<KalshiWebsocketResponse, KalshiWebsocketError>> {
+        async_stream::stream! {
+            let file = match File::open(&self.path).await {
+                Ok(file) => file,
+                Err(e) => {
+                    yield Err(KalshiWebsocketError::WebSocketError(e.to_string()));
+                    return;
+                }
+            };
+            let mut lines = BufReader::new(file).lines();
+            let started = Instant::now();
+            let mut first_ts = None;
+
+            loop {
+                let line = match lines.next_line().await {
+                    Ok(Some(line)) => line,
+                    Ok(None) => break,
+                    Err(e) => {
+                        yield Err(KalshiWebsocketError::WebSocketError(e.to_string()));
+                        break;
+                    }
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let recorded = match serde_json::from_str::<RecordedFrame>(&line) {
+                    Ok(recorded) => recorded,
+                    Err(e) => {
+                        yield Err(KalshiWebsocketError::WebSocketError(format!("Corrupt recording line: {}", e)));
+                        continue;
+                    }
+                };
+
+                let base = *first_ts.get_or_insert(recorded.ts);
+                let offset = Duration::from_micros(recorded.ts.saturating_sub(base));
+                let due = match self.speed {
+                    ReplaySpeed::Realtime => Some(offset),
+                    ReplaySpeed::Multiplier(m) if m > 0.0 && m.is_finite() => Some(offset.div_f64(m)),
+                    _ => None,
+                };
+                if let Some(due) = due {
+                    tokio::time::sleep_until(started + due).await;
+                }
+
+                yield KalshiWebsocketResponse::from_text(&recorded.frame)
+                    .map_err(|e| KalshiWebsocketError::SerializationError(e.to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_record_and_replay_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("kalshi-recording-{}.jsonl", uuid::Uuid::new_v4()));
+
+        let recorder = KalshiRecorder::create(&path).await.unwrap();
+        recorder.record(r#"{"type":"trade","sid":1,"seq":16,"msg":{"trade_id":"5b0276ef-7715-46f2-56d8-a1c7b9e59e58","market_ticker":"KXHIGHCHI-25OCT02-B80.5","yes_price":27,"no_price":73,"count":7,"taker_side":"yes","ts":1759350609}}"#);
+        recorder.record(r#"{"type":"unsubscribed","sid":1}"#);
+        assert_eq!(recorder.finish().await.unwrap(), 2);
+
+        let replayed: Vec<_> = KalshiReplay::new(&path)
+            .speed(ReplaySpeed::Unthrottled)
+            .stream()
+            .collect()
+            .await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(replayed.len(), 2);
+        assert!(matches!(
+            replayed[0],
+            Ok(KalshiWebsocketResponse::Trade { sid: 1, .. })
+        ));
+        assert!(matches!(
+            replayed[1],
+            Ok(KalshiWebsocketResponse::Unsubscribed { sid: 1 })
+        ));
+    }
+}
@@ -1,8 +1,9 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use super::KalshiChannel;
+use crate::{Strike, Ticker};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type")]
 #[serde(rename_all = "snake_case")]
 pub enum KalshiWebsocketResponse {
@@ -37,8 +38,13 @@ pub enum KalshiWebsocketResponse {
         msg: KalshiMarketLifecycleMessage,
     },
     Subscribed {
+        #[serde(default)]
+        id: Option<u32>,
         msg: KalshiOrderbookSubscribedMessage,
     },
+    Unsubscribed {
+        sid: u32,
+    },
     Error {
         id: u32,
         msg: KalshiOrderbookErrorMessage,
@@ -49,51 +55,187 @@ pub enum KalshiWebsocketResponse {
         seq: u32,
         market_tickers: Vec<String>,
     },
+    /// A trade missed while the connection was down, fetched through the REST api after
+    /// reconnecting. Never sent by the exchange, the client emits these for markets with a
+    /// `trade` subscription, in time order.
+    TradeBackfill {
+        msg: KalshiTradeMessage,
+    },
+    /// A message whose `type` this crate does not know about yet.
+    /// The full frame is kept so newly introduced Kalshi messages are not lost.
+    #[serde(skip)]
+    Unknown {
+        #[serde(rename = "unknown_type")]
+        r#type: String,
+        raw: serde_json::Value,
+    },
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl KalshiWebsocketResponse {
+    /// Parses a raw websocket text frame.
+    ///
+    /// Frames with a `type` that isn't modelled by this crate are returned as
+    /// [`KalshiWebsocketResponse::Unknown`] instead of failing, frames with a known
+    /// `type` but an unexpected shape still return the serialization error.
+    ///
+    /// With the `simd-json` feature, frames are parsed with simd-json first, falling back to
+    /// serde_json for unknown and malformed frames.
+    pub fn from_text(text: &str) -> Result<Self, serde_json::Error> {
+        #[cfg(feature = "simd-json")]
+        {
+            let mut frame = text.as_bytes().to_vec();
+            if let Ok(res) = simd_json::serde::from_slice::<KalshiWebsocketResponse>(&mut frame) {
+                return Ok(res);
+            }
+        }
+        match serde_json::from_str::<KalshiWebsocketResponse>(text) {
+            Ok(res) => Ok(res),
+            Err(e) => {
+                let raw = match serde_json::from_str::<serde_json::Value>(text) {
+                    Ok(raw) => raw,
+                    Err(_) => return Err(e),
+                };
+                match raw.get("type").and_then(|t| t.as_str()) {
+                    Some(msg_type) if !Self::is_known_type(msg_type) => {
+                        Ok(KalshiWebsocketResponse::Unknown {
+                            r#type: msg_type.to_string(),
+                            raw,
+                        })
+                    }
+                    _ => Err(e),
+                }
+            }
+        }
+    }
+
+    /// Serializes the message back into the text frame the exchange would send.
+    pub fn to_text(&self) -> Result<String, serde_json::Error> {
+        match self {
+            Self::Unknown { raw, .. } => serde_json::to_string(raw),
+            _ => serde_json::to_string(self),
+        }
+    }
+
+    /// The channel a data message was delivered on, `None` for control messages
+    /// (acks, errors) and unknown message types.
+    pub fn channel(&self) -> Option<KalshiChannel> {
+        match self {
+            Self::OrderbookSnapshot { .. } | Self::OrderbookDelta { .. } => {
+                Some(KalshiChannel::OrderbookDelta)
+            }
+            Self::Ticker { .. } => Some(KalshiChannel::Ticker),
+            Self::Trade { .. } | Self::TradeBackfill { .. } => Some(KalshiChannel::Trade),
+            Self::Fill { .. } => Some(KalshiChannel::Fill),
+            // Event lifecycle messages are sent on the market lifecycle channel
+            Self::EventLifecycle { .. } | Self::MarketLifecycleV2 { .. } => {
+                Some(KalshiChannel::MarketLifecycleV2)
+            }
+            _ => None,
+        }
+    }
+
+    /// The market a data message is about, `None` for control messages, event lifecycle
+    /// messages and unknown message types.
+    pub fn market_ticker(&self) -> Option<&str> {
+        match self {
+            Self::OrderbookSnapshot { msg, .. } => Some(&msg.market_ticker),
+            Self::OrderbookDelta { msg, .. } => Some(&msg.market_ticker),
+            Self::Ticker { msg, .. } => Some(&msg.market_ticker),
+            Self::Trade { msg, .. } | Self::TradeBackfill { msg } => Some(&msg.market_ticker),
+            Self::Fill { msg, .. } => Some(&msg.market_ticker),
+            Self::MarketLifecycleV2 { msg, .. } => Some(msg.get_market_ticker()),
+            _ => None,
+        }
+    }
+
+    /// The subscription a data message was delivered on, `None` for control messages,
+    /// backfilled trades and unknown message types.
+    pub fn sid(&self) -> Option<u32> {
+        match self {
+            Self::OrderbookSnapshot { sid, .. }
+            | Self::OrderbookDelta { sid, .. }
+            | Self::Ticker { sid, .. }
+            | Self::Trade { sid, .. }
+            | Self::Fill { sid, .. }
+            | Self::EventLifecycle { sid, .. }
+            | Self::MarketLifecycleV2 { sid, .. } => Some(*sid),
+            _ => None,
+        }
+    }
+
+    /// The exchange timestamp (unix seconds) of ticker, trade and fill messages.
+    pub fn exchange_ts(&self) -> Option<u32> {
+        match self {
+            Self::Ticker { msg, .. } => Some(msg.ts),
+            Self::Trade { msg, .. } | Self::TradeBackfill { msg } => Some(msg.ts),
+            Self::Fill { msg, .. } => Some(msg.ts),
+            _ => None,
+        }
+    }
+
+    fn is_known_type(msg_type: &str) -> bool {
+        matches!(
+            msg_type,
+            "orderbook_snapshot"
+                | "orderbook_delta"
+                | "ticker"
+                | "trade"
+                | "fill"
+                | "event_lifecycle"
+                | "market_lifecycle_v2"
+                | "subscribed"
+                | "unsubscribed"
+                | "error"
+                | "ok"
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KalshiOrderbookSubscribedMessage {
-    channel: KalshiChannel,
-    sid: u32,
+    pub channel: KalshiChannel,
+    pub sid: u32,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KalshiOrderbookErrorMessage {
-    code: u32,
-    msg: String,
+    pub code: u32,
+    pub msg: String,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KalshiOrderbookSnapshotMessage {
-    market_ticker: String,
-    yes: Option<Vec<(u32, i32)>>,
-    no: Option<Vec<(u32, i32)>>,
+    pub market_ticker: Ticker,
+    pub yes: Option<Vec<(u32, i32)>>,
+    pub no: Option<Vec<(u32, i32)>>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KalshiOrderbookDeltaMessage {
-    delta: i32,
-    price: u32,
-    side: String,
-    client_order_id: Option<String>,
+    pub market_ticker: Ticker,
+    pub delta: i32,
+    pub price: u32,
+    pub side: KalshiSide,
+    pub client_order_id: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KalshiTickerMessage {
-    market_ticker: String,
-    price: u32,
-    yes_bid: u32,
-    yes_ask: u32,
-    volume: u32,
-    open_interest: u32,
-    dollar_volume: u32,
-    dollar_open_interest: u32,
-    ts: u32,
+    pub market_ticker: Ticker,
+    pub price: u32,
+    pub yes_bid: u32,
+    pub yes_ask: u32,
+    pub volume: u32,
+    pub open_interest: u32,
+    pub dollar_volume: u32,
+    pub dollar_open_interest: u32,
+    pub ts: u32,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KalshiTradeMessage {
-    pub market_ticker: String,
+    pub trade_id: String,
+    pub market_ticker: Ticker,
     pub yes_price: u32,
     pub no_price: u32,
     pub count: u32,
@@ -101,52 +243,52 @@ pub struct KalshiTradeMessage {
     pub ts: u32,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KalshiFillMessage {
-    trade_id: String,
-    order_id: String,
-    market_ticker: String,
-    is_taker: bool,
-    side: KalshiSide,
-    yes_price: u32,
-    no_price: u32,
-    count: u32,
-    action: String,
-    ts: u32,
-    client_order_id: Option<String>,
-    post_position: u32,
-    purchased_side: KalshiSide,
+    pub trade_id: String,
+    pub order_id: String,
+    pub market_ticker: Ticker,
+    pub is_taker: bool,
+    pub side: KalshiSide,
+    pub yes_price: u32,
+    pub no_price: u32,
+    pub count: u32,
+    pub action: String,
+    pub ts: u32,
+    pub client_order_id: Option<String>,
+    pub post_position: u32,
+    pub purchased_side: KalshiSide,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "event_type")]
 #[serde(rename_all = "snake_case")]
 pub enum KalshiMarketLifecycleMessage {
     Created {
-        market_ticker: String,
+        market_ticker: Ticker,
         open_ts: u32,
         close_ts: u32,
         additional_metadata: MarketLifecycleAdditionalMetadata,
     },
     Activated {
-        market_ticker: String,
+        market_ticker: Ticker,
         is_deactivated: bool,
     },
     Deactivated {
-        market_ticker: String,
+        market_ticker: Ticker,
         is_deactivated: bool,
     },
     CloseDateUpdated {
-        market_ticker: String,
+        market_ticker: Ticker,
         close_ts: u32,
     },
     Determined {
-        market_ticker: String,
+        market_ticker: Ticker,
         result: String,
         determination_ts: u32,
     },
     Settled {
-        market_ticker: String,
+        market_ticker: Ticker,
         settled_ts: u32,
     },
 }
@@ -165,7 +307,7 @@ impl KalshiMarketLifecycleMessage {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MarketLifecycleAdditionalMetadata {
     pub name: String,
     pub title: String,
@@ -177,35 +319,29 @@ pub struct MarketLifecycleAdditionalMetadata {
     #[serde(default)]
     pub event_ticker: Option<String>,
     pub expected_expiration_ts: u32,
-    #[serde(default)]
-    pub strike_type: Option<String>,
-    #[serde(default)]
-    pub floor_strike: Option<f64>,
-    #[serde(default)]
-    pub cap_strike: Option<bool>,
-    #[serde(default)]
-    pub custom_strike: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub strike: Strike,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KalshiEventLifecycleMessage {
-    event_ticker: String,
-    title: String,
-    subtitle: String,
-    collateral_return_type: String,
-    series_ticker: String,
-    strike_date: Option<u32>,
-    strike_period: Option<String>,
+    pub event_ticker: String,
+    pub title: String,
+    pub subtitle: String,
+    pub collateral_return_type: String,
+    pub series_ticker: String,
+    pub strike_date: Option<u32>,
+    pub strike_period: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum KalshiSide {
     Yes,
     No,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum KalshiAction {
     Buy,
@@ -354,11 +490,19 @@ mod test {
                         market_ticker,
                         open_ts,
                         close_ts,
-                        ..
+                        additional_metadata,
                     } => {
                         assert_eq!(market_ticker, expected_ticker);
                         assert_eq!(open_ts, expected_open_ts);
                         assert_eq!(close_ts, expected_close_ts);
+                        if market_ticker == "KXFDVLIGHTER-25DEC31-8" {
+                            assert_eq!(
+                                additional_metadata.strike,
+                                Strike::Greater {
+                                    floor: 7999999999.99
+                                }
+                            );
+                        }
                     }
                     _ => panic!("Expected Created variant"),
                 }
@@ -412,4 +556,26 @@ mod test {
             _ => panic!("Expected Ticker variant"),
         }
     }
+
+    #[test]
+    fn test_unknown_message_type() {
+        let raw = r#"{"type":"multivariate_lookup","sid":3,"msg":{"collection_ticker":"KXMVE"}}"#;
+        let parsed = KalshiWebsocketResponse::from_text(raw);
+        assert!(parsed.is_ok());
+
+        match parsed.unwrap() {
+            KalshiWebsocketResponse::Unknown { r#type, raw } => {
+                assert_eq!(r#type, "multivariate_lookup");
+                assert_eq!(raw["sid"], 3);
+                assert_eq!(raw["msg"]["collection_ticker"], "KXMVE");
+            }
+            _ => panic!("Expected Unknown variant"),
+        }
+    }
+
+    #[test]
+    fn test_malformed_known_message_type() {
+        let raw = r#"{"type":"trade","sid":1,"msg":{"market_ticker":"KXHIGHCHI-25OCT02-B80.5"}}"#;
+        assert!(KalshiWebsocketResponse::from_text(raw).is_err());
+    }
 }
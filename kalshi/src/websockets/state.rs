@@ -0,0 +1,991 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::atomic::{AtomicU32, Ordering},
+    time::{Duration, Instant, SystemTime},
+};
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{
+    commands::{
+        KalshiCommand, KalshiSubscribeCommandParams, KalshiUnsubscribeCommandParams,
+        KalshiUpdateSubscriptionAction, KalshiUpdateSubscriptionCommandParams,
+    },
+    latency::FeedClock,
+    rate_limit::KalshiCommandRateLimit,
+    recording::RecordedFrame,
+    responses::{KalshiMarketLifecycleMessage, KalshiWebsocketResponse},
+    stats::{KalshiPendingCommand, KalshiSubscription, KalshiWebsocketStats, ThroughputCounter},
+};
+
+/// State shared between a [`KalshiWebsocketClient`](super::client::KalshiWebsocketClient)
+/// and its background handler task.
+#[derive(Debug, Default)]
+pub(super) struct WsState {
+    stats: KalshiWebsocketStats,
+    /// Acknowledged subscriptions keyed by sid
+    subscriptions: BTreeMap<u32, KalshiSubscription>,
+    /// Subscribe commands sent but not (fully) acknowledged, keyed by command id
+    pending_subscribes: HashMap<u32, PendingSubscribe>,
+    /// Sids handed out before a reconnect mapped to the sid now serving that subscription
+    sid_aliases: HashMap<u32, u32>,
+    /// Where raw frames are sent while a recording is active
+    recorder: Option<UnboundedSender<RecordedFrame>>,
+    clock: FeedClock,
+    /// Events whose subscriptions grow as markets are added, keyed by event ticker
+    followed_events: HashMap<String, FollowedEvent>,
+    /// Markets being added to subscriptions but not yet acknowledged, keyed by command id
+    pending_updates: HashMap<u32, PendingUpdate>,
+    /// Exchange time of the latest ticker, trade or fill message
+    last_exchange_ts: Option<u32>,
+    /// Time of the latest trade and the ids of the trades received at that time
+    latest_trades: (u32, HashSet<String>),
+    /// Messages received on each subscription, keyed by sid
+    throughput: HashMap<u32, ThroughputCounter>,
+    /// Commands sent on the current connection and not acknowledged yet, keyed by command id
+    sent_commands: BTreeMap<u32, KalshiPendingCommand>,
+    /// How long to wait for a command's acknowledgement, `None` to wait forever
+    command_timeout: Option<Duration>,
+    /// Limit of the commands written to the connection, `None` to send them right away
+    command_rate_limit: Option<KalshiCommandRateLimit>,
+    /// Subscriptions owned by channel stream handles, keyed by channel and sorted market tickers
+    stream_subscriptions: HashMap<StreamKey, StreamSubscription>,
+}
+
+/// The channel and sorted market tickers a channel stream handle subscribes to.
+pub(super) type StreamKey = (super::KalshiChannel, Vec<String>);
+
+/// The trades to fetch through the REST api after a reconnect, see [`WsState::trade_backfill`].
+#[derive(Debug)]
+pub(super) struct TradeBackfill {
+    /// Markets to fetch, `None` for all markets
+    pub(super) market_tickers: Vec<Option<String>>,
+    pub(super) since_ts: i64,
+    /// Trades at `since_ts` that were already received
+    pub(super) received: HashSet<String>,
+}
+
+#[derive(Debug)]
+struct PendingSubscribe {
+    params: KalshiSubscribeCommandParams,
+    /// The sid this subscribe re-establishes after a reconnect
+    replaces: Option<u32>,
+}
+
+#[derive(Debug)]
+struct PendingUpdate {
+    sid: u32,
+    market_tickers: Vec<String>,
+}
+
+#[derive(Debug)]
+struct StreamSubscription {
+    /// Live handles, the subscription is released once the last one is dropped
+    handles: usize,
+    /// Id of the subscribe command creating the subscription
+    subscribe_id: u32,
+    /// Sid acknowledged for the subscribe command
+    sid: Option<u32>,
+}
+
+#[derive(Debug)]
+struct FollowedEvent {
+    /// Id of the subscribe command covering the event's markets
+    subscribe_id: u32,
+    /// Sids acknowledged for that subscribe command
+    sids: Vec<u32>,
+    market_tickers: HashSet<String>,
+}
+
+impl WsState {
+    pub(super) fn snapshot(&self) -> KalshiWebsocketStats {
+        let mut stats = self.stats.clone();
+        stats.subscriptions = self.subscriptions.values().cloned().collect();
+        stats.clock_offset_ms = self.clock.offset_ms();
+        let now = Instant::now();
+        stats.throughput_by_sid = self
+            .throughput
+            .iter()
+            .filter(|(sid, _)| self.subscriptions.contains_key(sid))
+            .map(|(sid, counter)| (*sid, counter.snapshot(now)))
+            .collect();
+        stats
+    }
+
+    /// Records messages a consumer skipped, split between subscriptions by their share of the
+    /// messages received.
+    pub(super) fn on_lagged(&mut self, skipped: u64) {
+        self.stats.dropped_messages += skipped;
+        let total: u64 = self
+            .throughput
+            .values()
+            .map(ThroughputCounter::messages)
+            .sum();
+        if total == 0 {
+            return;
+        }
+        let mut assigned = 0;
+        for counter in self.throughput.values_mut() {
+            let share = skipped * counter.messages() / total;
+            counter.add_dropped(share);
+            assigned += share;
+        }
+        // What rounding left over goes to the busiest subscription
+        if let Some(busiest) = self.throughput.values_mut().max_by_key(|c| c.messages()) {
+            busiest.add_dropped(skipped - assigned);
+        }
+    }
+
+    pub(super) fn on_connected(&mut self, is_reconnect: bool) {
+        self.stats.connected_at = Some(SystemTime::now());
+        if is_reconnect {
+            self.stats.reconnect_count += 1;
+        }
+    }
+
+    pub(super) fn set_clock_offset_ms(&mut self, offset_ms: i64) {
+        self.clock.set_offset_ms(offset_ms);
+    }
+
+    pub(super) fn set_recorder(&mut self, recorder: Option<UnboundedSender<RecordedFrame>>) {
+        self.recorder = recorder;
+    }
+
+    /// Counts a raw text frame, parsed or not, and forwards it to the active recording if any.
+    pub(super) fn on_frame(&mut self, frame: &str) {
+        self.stats.messages_received += 1;
+        self.stats.bytes_received += frame.len() as u64;
+        self.stats.last_message_at = Some(SystemTime::now());
+        if let Some(recorder) = &self.recorder {
+            if recorder.send(RecordedFrame::now(frame)).is_err() {
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// Records an outgoing command, translating sids from before a reconnect to current ones.
+    pub(super) fn on_command(&mut self, cmd: &mut KalshiCommand) {
+        match cmd {
+            KalshiCommand::Unsubscribe { params, .. } => {
+                for sid in params.sids.iter_mut() {
+                    *sid = self.current_sid(*sid);
+                }
+            }
+            KalshiCommand::UpdateSubscription { params, .. } => {
+                for sid in params.sids.iter_mut() {
+                    *sid = self.current_sid(*sid);
+                }
+            }
+            KalshiCommand::Subscribe { .. } | KalshiCommand::End => {}
+        }
+        self.expect_ack(cmd);
+        if let Some(pending) = pending_command(cmd) {
+            self.sent_commands.insert(pending.id, pending);
+        }
+    }
+
+    pub(super) fn set_command_timeout(&mut self, timeout: Option<Duration>) {
+        self.command_timeout = timeout;
+    }
+
+    pub(super) fn set_command_rate_limit(&mut self, limit: Option<KalshiCommandRateLimit>) {
+        self.command_rate_limit = limit;
+    }
+
+    pub(super) fn command_rate_limit(&self) -> Option<KalshiCommandRateLimit> {
+        self.command_rate_limit
+    }
+
+    pub(super) fn pending_commands(&self) -> Vec<KalshiPendingCommand> {
+        self.sent_commands.values().cloned().collect()
+    }
+
+    /// Ids of the commands sent more than the command timeout ago without an acknowledgement.
+    ///
+    /// They stop being pending, so each is only reported once and subscribing to the same
+    /// markets again isn't blocked by a subscription that will never be acknowledged. Channel
+    /// streams waiting on an expired subscribe are dropped like refused ones, so the next handle
+    /// subscribes again.
+    pub(super) fn expired_commands(&mut self, now: Instant) -> Vec<u32> {
+        let Some(timeout) = self.command_timeout else {
+            return Vec::new();
+        };
+        let expired: Vec<u32> = self
+            .sent_commands
+            .values()
+            .filter(|cmd| now.saturating_duration_since(cmd.sent_at) >= timeout)
+            .map(|cmd| cmd.id)
+            .collect();
+        for id in &expired {
+            self.sent_commands.remove(id);
+            self.pending_subscribes.remove(id);
+            self.pending_updates.remove(id);
+        }
+        self.stream_subscriptions
+            .retain(|_, owned| !expired.contains(&owned.subscribe_id));
+        expired
+    }
+
+    /// Registers the subscriptions a command will create or extend once acknowledged.
+    ///
+    /// Called by the client as soon as a command is queued so that checks made before
+    /// the handler gets to send it already account for it, registering twice is harmless.
+    pub(super) fn expect_ack(&mut self, cmd: &KalshiCommand) {
+        match cmd {
+            KalshiCommand::Subscribe { id, params } => {
+                self.pending_subscribes
+                    .entry(*id)
+                    .or_insert_with(|| PendingSubscribe {
+                        params: params.clone(),
+                        replaces: None,
+                    });
+            }
+            KalshiCommand::UpdateSubscription { id, params } => {
+                if let KalshiUpdateSubscriptionAction::AddMarkets = params.action {
+                    self.pending_updates.insert(
+                        *id,
+                        PendingUpdate {
+                            sid: params.sids[0],
+                            market_tickers: params.market_tickers.clone(),
+                        },
+                    );
+                }
+            }
+            KalshiCommand::Unsubscribe { .. } | KalshiCommand::End => {}
+        }
+    }
+
+    pub(super) fn active_subscriptions(&self) -> Vec<KalshiSubscription> {
+        self.subscriptions.values().cloned().collect()
+    }
+
+    /// Whether an acknowledged subscription delivers `channel` messages for `market_ticker`.
+    pub(super) fn is_subscribed(
+        &self,
+        channel: &super::KalshiChannel,
+        market_ticker: &str,
+    ) -> bool {
+        self.subscriptions.values().any(|sub| {
+            sub.channel == *channel
+                && (sub.market_tickers.is_empty()
+                    || sub.market_tickers.iter().any(|t| t == market_ticker))
+        })
+    }
+
+    /// Like [`WsState::is_subscribed`] but also counting commands not acknowledged yet.
+    /// `None` asks for a subscription covering all markets.
+    pub(super) fn covers(
+        &self,
+        channel: &super::KalshiChannel,
+        market_ticker: Option<&str>,
+    ) -> bool {
+        let matches = |channels: &[super::KalshiChannel], tickers: &[String]| {
+            channels.contains(channel)
+                && (tickers.is_empty()
+                    || market_ticker.is_some_and(|ticker| tickers.iter().any(|t| t == ticker)))
+        };
+        self.subscriptions
+            .values()
+            .any(|sub| matches(std::slice::from_ref(&sub.channel), &sub.market_tickers))
+            || self
+                .pending_subscribes
+                .values()
+                .any(|pending| matches(&pending.params.channels, &pending.params.market_tickers))
+            || self.pending_updates.values().any(|update| {
+                let sub = self.subscriptions.get(&self.current_sid(update.sid));
+                sub.is_some_and(|sub| {
+                    sub.channel == *channel
+                        && market_ticker
+                            .is_some_and(|ticker| update.market_tickers.iter().any(|t| t == ticker))
+                })
+            })
+    }
+
+    /// An acknowledged subscription on `channel` limited to specific markets, which markets can be added to.
+    pub(super) fn extendable_subscription(&self, channel: &super::KalshiChannel) -> Option<u32> {
+        self.subscriptions
+            .values()
+            .find(|sub| sub.channel == *channel && !sub.market_tickers.is_empty())
+            .map(|sub| sub.sid)
+    }
+
+    pub(super) fn on_response(
+        &mut self,
+        res: &KalshiWebsocketResponse,
+        bytes: usize,
+        received_at: Instant,
+    ) {
+        if let Some(exchange_ts) = res.exchange_ts() {
+            self.last_exchange_ts = self.last_exchange_ts.max(Some(exchange_ts));
+        }
+        if let KalshiWebsocketResponse::Trade { msg, .. } = res {
+            let (latest_ts, ids) = &mut self.latest_trades;
+            if msg.ts > *latest_ts {
+                *latest_ts = msg.ts;
+                ids.clear();
+            }
+            if msg.ts == *latest_ts {
+                ids.insert(msg.trade_id.clone());
+            }
+        }
+        if let Some(channel) = res.channel() {
+            if let Some(exchange_ts) = res.exchange_ts() {
+                let latency_ms = self.clock.latency_ms(exchange_ts, received_at);
+                self.stats
+                    .latency_by_channel
+                    .entry(channel.clone())
+                    .or_default()
+                    .record(latency_ms);
+            }
+            *self.stats.messages_by_channel.entry(channel).or_default() += 1;
+        }
+        if let Some(sid) = res.sid() {
+            self.throughput
+                .entry(sid)
+                .or_insert_with(|| ThroughputCounter::new(received_at))
+                .record(bytes, received_at);
+        }
+
+        match res {
+            KalshiWebsocketResponse::Subscribed { id: Some(id), .. }
+            | KalshiWebsocketResponse::Ok { id, .. }
+            | KalshiWebsocketResponse::Error { id, .. } => {
+                self.sent_commands.remove(id);
+            }
+            KalshiWebsocketResponse::Unsubscribed { sid } => {
+                self.sent_commands
+                    .retain(|_, cmd| cmd.cmd != "unsubscribe" || !cmd.sids.contains(sid));
+            }
+            _ => {}
+        }
+
+        match res {
+            KalshiWebsocketResponse::Subscribed { id: Some(id), msg } => {
+                for follow in self.followed_events.values_mut() {
+                    if follow.subscribe_id == *id {
+                        follow.sids.push(msg.sid);
+                    }
+                }
+                let Some(pending) = self.pending_subscribes.get_mut(id) else {
+                    return;
+                };
+                self.subscriptions.insert(
+                    msg.sid,
+                    KalshiSubscription {
+                        sid: msg.sid,
+                        channel: msg.channel.clone(),
+                        market_tickers: pending.params.market_tickers.clone(),
+                    },
+                );
+                if let Some(old_sid) = pending.replaces {
+                    for current in self.sid_aliases.values_mut() {
+                        if *current == old_sid {
+                            *current = msg.sid;
+                        }
+                    }
+                    self.sid_aliases.insert(old_sid, msg.sid);
+                }
+                pending.params.channels.retain(|c| *c != msg.channel);
+                if pending.params.channels.is_empty() {
+                    self.pending_subscribes.remove(id);
+                }
+            }
+            KalshiWebsocketResponse::Ok {
+                id,
+                sid,
+                market_tickers,
+                ..
+            } => {
+                self.pending_updates.remove(id);
+                if let Some(sub) = self.subscriptions.get_mut(sid) {
+                    sub.market_tickers = market_tickers.clone();
+                }
+            }
+            KalshiWebsocketResponse::Unsubscribed { sid } => {
+                // Stop following an event once all of its subscriptions are gone
+                let aliases = &self.sid_aliases;
+                self.followed_events.retain(|_, follow| {
+                    let acked = follow.sids.len();
+                    follow
+                        .sids
+                        .retain(|s| aliases.get(s).copied().unwrap_or(*s) != *sid);
+                    acked == 0 || !follow.sids.is_empty()
+                });
+                self.subscriptions.remove(sid);
+                self.throughput.remove(sid);
+                self.sid_aliases.retain(|_, current| current != sid);
+            }
+            KalshiWebsocketResponse::Error { id, .. } => {
+                self.pending_subscribes.remove(id);
+                self.pending_updates.remove(id);
+            }
+            _ => {}
+        }
+    }
+
+    /// The trades missed since the last message, for the markets with an acknowledged `trade`
+    /// subscription, `None` without one or before any message was received.
+    ///
+    /// Must be called before [`WsState::resubscribe_commands`] hands the subscriptions over to
+    /// the new connection.
+    pub(super) fn trade_backfill(&self) -> Option<TradeBackfill> {
+        let since_ts = match self.last_exchange_ts {
+            Some(ts) => ts as i64,
+            None => self
+                .stats
+                .last_message_at?
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()?
+                .as_secs() as i64,
+        };
+        let subscriptions: Vec<&KalshiSubscription> = self
+            .subscriptions
+            .values()
+            .filter(|sub| sub.channel == super::KalshiChannel::Trade)
+            .collect();
+        if subscriptions.is_empty() {
+            return None;
+        }
+        let market_tickers = if subscriptions
+            .iter()
+            .any(|sub| sub.market_tickers.is_empty())
+        {
+            vec![None]
+        } else {
+            let mut tickers: Vec<Option<String>> = subscriptions
+                .iter()
+                .flat_map(|sub| sub.market_tickers.iter().cloned().map(Some))
+                .collect();
+            tickers.sort();
+            tickers.dedup();
+            tickers
+        };
+        let (latest_ts, ids) = &self.latest_trades;
+        let received = if *latest_ts as i64 >= since_ts {
+            ids.clone()
+        } else {
+            HashSet::new()
+        };
+        Some(TradeBackfill {
+            market_tickers,
+            since_ts,
+            received,
+        })
+    }
+
+    /// Builds the commands needed to re-establish every known subscription on a new connection.
+    ///
+    /// Acknowledged subscriptions are re-sent one channel at a time so each new sid can be
+    /// matched to the sid it replaces, unacknowledged subscribes are simply re-sent.
+    pub(super) fn resubscribe_commands(&mut self, next_cmd_id: &AtomicU32) -> Vec<KalshiCommand> {
+        let mut pending = HashMap::new();
+        let mut cmds = Vec::new();
+
+        // Markets being added when the connection dropped are folded into the new subscriptions
+        for (_, update) in std::mem::take(&mut self.pending_updates) {
+            let sid = self.current_sid(update.sid);
+            if let Some(sub) = self.subscriptions.get_mut(&sid) {
+                for ticker in update.market_tickers {
+                    if !sub.market_tickers.contains(&ticker) {
+                        sub.market_tickers.push(ticker);
+                    }
+                }
+            }
+        }
+
+        self.throughput.clear();
+        // Acknowledgements of commands sent on the lost connection will never arrive
+        self.sent_commands.clear();
+        for (old_sid, sub) in std::mem::take(&mut self.subscriptions) {
+            let id = next_cmd_id.fetch_add(1, Ordering::SeqCst);
+            let params = KalshiSubscribeCommandParams {
+                channels: vec![sub.channel],
+                market_tickers: sub.market_tickers,
+            };
+            pending.insert(
+                id,
+                PendingSubscribe {
+                    params: params.clone(),
+                    replaces: Some(old_sid),
+                },
+            );
+            cmds.push(KalshiCommand::Subscribe { id, params });
+        }
+
+        for (old_id, unacked) in self.pending_subscribes.drain() {
+            let id = next_cmd_id.fetch_add(1, Ordering::SeqCst);
+            for follow in self.followed_events.values_mut() {
+                if follow.subscribe_id == old_id {
+                    follow.subscribe_id = id;
+                }
+            }
+            for owned in self.stream_subscriptions.values_mut() {
+                if owned.subscribe_id == old_id {
+                    owned.subscribe_id = id;
+                }
+            }
+            cmds.push(KalshiCommand::Subscribe {
+                id,
+                params: unacked.params.clone(),
+            });
+            pending.insert(id, unacked);
+        }
+
+        self.pending_subscribes = pending;
+        cmds
+    }
+
+    /// Starts adding markets created for `event_ticker` to the subscription made by command `subscribe_id`.
+    pub(super) fn follow_event(
+        &mut self,
+        event_ticker: &str,
+        subscribe_id: u32,
+        market_tickers: &[String],
+    ) {
+        self.followed_events.insert(
+            event_ticker.to_string(),
+            FollowedEvent {
+                subscribe_id,
+                sids: Vec::new(),
+                market_tickers: market_tickers.iter().cloned().collect(),
+            },
+        );
+    }
+
+    /// Whether lifecycle messages of all markets are (being) subscribed to.
+    pub(super) fn watches_all_lifecycles(&self) -> bool {
+        let is_watch = |channel: &super::KalshiChannel, market_tickers: &[String]| {
+            *channel == super::KalshiChannel::MarketLifecycleV2 && market_tickers.is_empty()
+        };
+        self.subscriptions
+            .values()
+            .any(|sub| is_watch(&sub.channel, &sub.market_tickers))
+            || self.pending_subscribes.values().any(|pending| {
+                pending
+                    .params
+                    .channels
+                    .iter()
+                    .any(|c| is_watch(c, &pending.params.market_tickers))
+            })
+    }
+
+    /// Builds the commands adding a newly created market to the subscriptions of its followed event.
+    pub(super) fn followed_event_updates(
+        &mut self,
+        res: &KalshiWebsocketResponse,
+        next_cmd_id: &AtomicU32,
+    ) -> Vec<KalshiCommand> {
+        let KalshiWebsocketResponse::MarketLifecycleV2 {
+            msg:
+                KalshiMarketLifecycleMessage::Created {
+                    market_ticker,
+                    additional_metadata,
+                    ..
+                },
+            ..
+        } = res
+        else {
+            return Vec::new();
+        };
+        let Some(follow) = additional_metadata
+            .event_ticker
+            .as_ref()
+            .and_then(|event_ticker| self.followed_events.get_mut(event_ticker))
+        else {
+            return Vec::new();
+        };
+        if !follow.market_tickers.insert(market_ticker.to_string()) {
+            return Vec::new();
+        }
+
+        follow
+            .sids
+            .iter()
+            .map(|sid| KalshiCommand::UpdateSubscription {
+                id: next_cmd_id.fetch_add(1, Ordering::SeqCst),
+                params: KalshiUpdateSubscriptionCommandParams {
+                    action: KalshiUpdateSubscriptionAction::AddMarkets,
+                    market_tickers: vec![market_ticker.to_string()],
+                    sids: [*sid],
+                },
+            })
+            .collect()
+    }
+
+    /// Adds a channel stream handle for `key`, returning the subscribe command to send when no
+    /// other handle holds that subscription.
+    pub(super) fn acquire_stream(
+        &mut self,
+        key: &StreamKey,
+        next_cmd_id: &AtomicU32,
+    ) -> Option<KalshiCommand> {
+        if let Some(owned) = self.stream_subscriptions.get_mut(key) {
+            owned.handles += 1;
+            return None;
+        }
+        let id = next_cmd_id.fetch_add(1, Ordering::SeqCst);
+        self.stream_subscriptions.insert(
+            key.clone(),
+            StreamSubscription {
+                handles: 1,
+                subscribe_id: id,
+                sid: None,
+            },
+        );
+        let cmd = KalshiCommand::Subscribe {
+            id,
+            params: KalshiSubscribeCommandParams {
+                channels: vec![key.0.clone()],
+                market_tickers: key.1.clone(),
+            },
+        };
+        self.expect_ack(&cmd);
+        Some(cmd)
+    }
+
+    /// Removes a channel stream handle for `key`, returning the unsubscribe command to send once
+    /// it was the last one.
+    ///
+    /// A subscription released before its acknowledgement is unsubscribed when the
+    /// acknowledgement arrives, see [`WsState::stream_unsubscribes`].
+    pub(super) fn release_stream(
+        &mut self,
+        key: &StreamKey,
+        next_cmd_id: &AtomicU32,
+    ) -> Option<KalshiCommand> {
+        let owned = self.stream_subscriptions.get_mut(key)?;
+        owned.handles = owned.handles.saturating_sub(1);
+        if owned.handles > 0 {
+            return None;
+        }
+        let sid = owned.sid?;
+        self.stream_subscriptions.remove(key);
+        Some(KalshiCommand::Unsubscribe {
+            id: next_cmd_id.fetch_add(1, Ordering::SeqCst),
+            params: KalshiUnsubscribeCommandParams { sids: vec![sid] },
+        })
+    }
+
+    /// Records the sid of subscriptions owned by channel stream handles, building the unsubscribe
+    /// command of those whose handles were all dropped before the acknowledgement.
+    pub(super) fn stream_unsubscribes(
+        &mut self,
+        res: &KalshiWebsocketResponse,
+        next_cmd_id: &AtomicU32,
+    ) -> Vec<KalshiCommand> {
+        match res {
+            KalshiWebsocketResponse::Subscribed { id: Some(id), msg } => {
+                let mut released = Vec::new();
+                for (key, owned) in self.stream_subscriptions.iter_mut() {
+                    if owned.subscribe_id == *id {
+                        owned.sid = Some(msg.sid);
+                        if owned.handles == 0 {
+                            released.push(key.clone());
+                        }
+                    }
+                }
+                released
+                    .iter()
+                    .filter_map(|key| self.release_stream(key, next_cmd_id))
+                    .collect()
+            }
+            KalshiWebsocketResponse::Error { id, .. } => {
+                // The next handle subscribes again
+                self.stream_subscriptions
+                    .retain(|_, owned| owned.subscribe_id != *id);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn current_sid(&self, sid: u32) -> u32 {
+        self.sid_aliases.get(&sid).copied().unwrap_or(sid)
+    }
+}
+
+/// Whether [`WsState::resubscribe_commands`] takes care of `cmd` if the connection is lost before
+/// it's acknowledged: subscribes are restored and markets being added are folded into them.
+pub(super) fn restored_on_reconnect(cmd: &KalshiCommand) -> bool {
+    match cmd {
+        KalshiCommand::Subscribe { .. } => true,
+        KalshiCommand::UpdateSubscription { params, .. } => {
+            matches!(params.action, KalshiUpdateSubscriptionAction::AddMarkets)
+        }
+        KalshiCommand::Unsubscribe { .. } | KalshiCommand::End => false,
+    }
+}
+
+/// What's awaiting an acknowledgement when `cmd` is sent.
+fn pending_command(cmd: &KalshiCommand) -> Option<KalshiPendingCommand> {
+    let (id, cmd, channels, market_tickers, sids) = match cmd {
+        KalshiCommand::Subscribe { id, params } => (
+            *id,
+            "subscribe",
+            params.channels.clone(),
+            params.market_tickers.clone(),
+            Vec::new(),
+        ),
+        KalshiCommand::UpdateSubscription { id, params } => (
+            *id,
+            "update_subscription",
+            Vec::new(),
+            params.market_tickers.clone(),
+            params.sids.to_vec(),
+        ),
+        KalshiCommand::Unsubscribe { id, params } => (
+            *id,
+            "unsubscribe",
+            Vec::new(),
+            Vec::new(),
+            params.sids.clone(),
+        ),
+        KalshiCommand::End => return None,
+    };
+    Some(KalshiPendingCommand {
+        id,
+        cmd,
+        channels,
+        market_tickers,
+        sids,
+        sent_at: Instant::now(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::websockets::{responses::KalshiOrderbookSubscribedMessage, KalshiChannel};
+    use std::time::Duration;
+
+    fn subscribed(id: u32, channel: KalshiChannel, sid: u32) -> KalshiWebsocketResponse {
+        KalshiWebsocketResponse::Subscribed {
+            id: Some(id),
+            msg: KalshiOrderbookSubscribedMessage { channel, sid },
+        }
+    }
+
+    #[test]
+    fn test_every_frame_counted() {
+        let mut state = WsState::default();
+        state.on_frame(r#"{"type":"ok","id":1}"#);
+        state.on_frame("not json");
+
+        let stats = state.snapshot();
+        assert_eq!(stats.messages_received, 2);
+        assert_eq!(stats.bytes_received, 28);
+        assert!(stats.last_message_at.is_some());
+    }
+
+    #[test]
+    fn test_subscriptions_tracked_from_acks() {
+        let mut state = WsState::default();
+        let mut cmd = KalshiCommand::Subscribe {
+            id: 1,
+            params: KalshiSubscribeCommandParams {
+                channels: vec![KalshiChannel::Ticker, KalshiChannel::Trade],
+                market_tickers: vec!["KXHIGHNY-25OCT02-B80.5".to_string()],
+            },
+        };
+        state.on_command(&mut cmd);
+        state.on_response(
+            &subscribed(1, KalshiChannel::Ticker, 10),
+            64,
+            Instant::now(),
+        );
+        state.on_response(&subscribed(1, KalshiChannel::Trade, 11), 64, Instant::now());
+
+        let stats = state.snapshot();
+        assert_eq!(stats.subscriptions.len(), 2);
+        assert!(state.pending_subscribes.is_empty());
+
+        state.on_response(
+            &KalshiWebsocketResponse::Unsubscribed { sid: 10 },
+            32,
+            Instant::now(),
+        );
+        assert_eq!(state.snapshot().subscriptions.len(), 1);
+    }
+
+    #[test]
+    fn test_throughput_and_drops_per_sid() {
+        let mut state = WsState::default();
+        let mut cmd = KalshiCommand::Subscribe {
+            id: 1,
+            params: KalshiSubscribeCommandParams {
+                channels: vec![KalshiChannel::Ticker, KalshiChannel::Trade],
+                market_tickers: vec!["KXHIGHNY-25OCT02-B80.5".to_string()],
+            },
+        };
+        state.on_command(&mut cmd);
+        let start = Instant::now();
+        state.on_response(&subscribed(1, KalshiChannel::Ticker, 10), 64, start);
+        state.on_response(&subscribed(1, KalshiChannel::Trade, 11), 64, start);
+
+        let ticker = KalshiWebsocketResponse::from_text(r#"{"type":"ticker","sid":10,"msg":{"market_ticker":"KXHIGHNY-25OCT02-B80.5","price":48,"yes_bid":47,"yes_ask":49,"volume":100,"open_interest":50,"dollar_volume":48,"dollar_open_interest":24,"ts":1759350609}}"#).unwrap();
+        let trade = KalshiWebsocketResponse::from_text(r#"{"type":"trade","sid":11,"seq":1,"msg":{"trade_id":"a","market_ticker":"KXHIGHNY-25OCT02-B80.5","yes_price":48,"no_price":52,"count":1,"taker_side":"yes","ts":1759350609}}"#).unwrap();
+        for i in 0..3 {
+            state.on_response(&ticker, 100, start + Duration::from_millis(i * 100));
+        }
+        state.on_response(&trade, 50, start);
+        // Closes the first window of the ticker subscription, 3 messages over 2 seconds
+        state.on_response(&ticker, 100, start + Duration::from_secs(2));
+
+        state.on_lagged(10);
+        let stats = state.snapshot();
+        assert_eq!(stats.dropped_messages, 10);
+        let ticker = stats.throughput_by_sid[&10];
+        assert_eq!((ticker.messages, ticker.bytes), (4, 400));
+        assert_eq!(ticker.messages_per_sec, 1.5);
+        assert_eq!(ticker.dropped, 8);
+        assert_eq!(stats.throughput_by_sid[&11].dropped, 2);
+
+        state.on_response(
+            &KalshiWebsocketResponse::Unsubscribed { sid: 11 },
+            32,
+            Instant::now(),
+        );
+        assert!(!state.snapshot().throughput_by_sid.contains_key(&11));
+    }
+
+    #[test]
+    fn test_unacknowledged_commands_expire() {
+        let mut state = WsState::default();
+        state.set_command_timeout(Some(Duration::from_secs(5)));
+        for id in [1, 2] {
+            let mut cmd = KalshiCommand::Subscribe {
+                id,
+                params: KalshiSubscribeCommandParams {
+                    channels: vec![KalshiChannel::Ticker],
+                    market_tickers: vec![format!("M{}", id)],
+                },
+            };
+            state.on_command(&mut cmd);
+        }
+        let pending = state.pending_commands();
+        assert_eq!(pending.len(), 2);
+        assert_eq!((pending[0].id, pending[0].cmd), (1, "subscribe"));
+        assert_eq!(pending[1].market_tickers, vec!["M2".to_string()]);
+
+        state.on_response(
+            &subscribed(1, KalshiChannel::Ticker, 10),
+            64,
+            Instant::now(),
+        );
+        assert_eq!(state.pending_commands().len(), 1);
+        assert!(state.expired_commands(Instant::now()).is_empty());
+
+        let later = Instant::now() + Duration::from_secs(5);
+        assert_eq!(state.expired_commands(later), vec![2]);
+        assert!(state.pending_commands().is_empty());
+        // A late ack for the expired command doesn't create a subscription
+        state.on_response(
+            &subscribed(2, KalshiChannel::Ticker, 11),
+            64,
+            Instant::now(),
+        );
+        assert_eq!(state.snapshot().subscriptions.len(), 1);
+
+        state.set_command_timeout(None);
+        let mut cmd = KalshiCommand::Unsubscribe {
+            id: 3,
+            params: crate::websockets::commands::KalshiUnsubscribeCommandParams { sids: vec![10] },
+        };
+        state.on_command(&mut cmd);
+        assert!(state
+            .expired_commands(later + Duration::from_secs(60))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_expired_stream_subscribe_retried() {
+        let mut state = WsState::default();
+        state.set_command_timeout(Some(Duration::from_secs(5)));
+        let next_cmd_id = AtomicU32::new(1);
+        let key = (
+            KalshiChannel::Ticker,
+            vec!["KXHIGHNY-25OCT02-B80.5".to_string()],
+        );
+
+        let mut cmd = state.acquire_stream(&key, &next_cmd_id).unwrap();
+        state.on_command(&mut cmd);
+        assert!(state.acquire_stream(&key, &next_cmd_id).is_none());
+        let later = Instant::now() + Duration::from_secs(5);
+        assert_eq!(state.expired_commands(later), vec![1]);
+
+        let Some(KalshiCommand::Subscribe { id, .. }) = state.acquire_stream(&key, &next_cmd_id)
+        else {
+            panic!("expected a new subscribe");
+        };
+        assert_eq!(id, 2);
+        state.on_response(&subscribed(2, KalshiChannel::Ticker, 10), 64, later);
+        assert!(state
+            .stream_unsubscribes(&subscribed(2, KalshiChannel::Ticker, 10), &next_cmd_id)
+            .is_empty());
+        assert!(matches!(
+            state.release_stream(&key, &next_cmd_id),
+            Some(KalshiCommand::Unsubscribe { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resubscribe_translates_old_sids() {
+        let mut state = WsState::default();
+        let next_cmd_id = AtomicU32::new(2);
+        let mut cmd = KalshiCommand::Subscribe {
+            id: 1,
+            params: KalshiSubscribeCommandParams {
+                channels: vec![KalshiChannel::Fill],
+                market_tickers: vec![],
+            },
+        };
+        state.on_command(&mut cmd);
+        state.on_response(&subscribed(1, KalshiChannel::Fill, 5), 64, Instant::now());
+
+        let cmds = state.resubscribe_commands(&next_cmd_id);
+        assert_eq!(cmds.len(), 1);
+        state.on_response(&subscribed(2, KalshiChannel::Fill, 42), 64, Instant::now());
+
+        let mut unsubscribe = KalshiCommand::Unsubscribe {
+            id: 3,
+            params: crate::websockets::commands::KalshiUnsubscribeCommandParams { sids: vec![5] },
+        };
+        state.on_command(&mut unsubscribe);
+        match unsubscribe {
+            KalshiCommand::Unsubscribe { params, .. } => assert_eq!(params.sids, vec![42]),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_followed_event_adds_created_markets() {
+        let mut state = WsState::default();
+        let next_cmd_id = AtomicU32::new(2);
+        let tickers = vec!["KXHIGHNY-25OCT02-B80.5".to_string()];
+        state.follow_event("KXHIGHNY-25OCT02", 1, &tickers);
+        let mut cmd = KalshiCommand::Subscribe {
+            id: 1,
+            params: KalshiSubscribeCommandParams {
+                channels: vec![KalshiChannel::Ticker],
+                market_tickers: tickers,
+            },
+        };
+        state.on_command(&mut cmd);
+        state.on_response(&subscribed(1, KalshiChannel::Ticker, 7), 64, Instant::now());
+
+        let created = KalshiWebsocketResponse::from_text(r#"{"type":"market_lifecycle_v2","sid":1,"msg":{"market_ticker":"KXHIGHNY-25OCT02-B82.5","open_ts":1759352700,"close_ts":1760598000,"additional_metadata":{"name":"82-83","title":"Highest temperature in NYC","yes_sub_title":"82-83","no_sub_title":"82-83","rules_primary":"","rules_secondary":"","can_close_early":true,"event_ticker":"KXHIGHNY-25OCT02","expected_expiration_ts":1759399200},"event_type":"created"}}"#).unwrap();
+        let cmds = state.followed_event_updates(&created, &next_cmd_id);
+        assert_eq!(cmds.len(), 1);
+        match &cmds[0] {
+            KalshiCommand::UpdateSubscription { params, .. } => {
+                assert_eq!(params.sids, [7]);
+                assert_eq!(params.market_tickers, vec!["KXHIGHNY-25OCT02-B82.5"]);
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        // The same market is only added once
+        assert!(state
+            .followed_event_updates(&created, &next_cmd_id)
+            .is_empty());
+    }
+}
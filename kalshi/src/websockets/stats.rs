@@ -0,0 +1,145 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant, SystemTime},
+};
+
+use super::{latency::KalshiFeedLatency, KalshiChannel};
+
+/// A point-in-time view of the health of a websocket connection.
+///
+/// Obtained through [`KalshiWebsocketClient::stats`](super::client::KalshiWebsocketClient::stats),
+/// every call returns a fresh copy so it can be logged or exported without holding any lock.
+#[derive(Debug, Clone, Default)]
+pub struct KalshiWebsocketStats {
+    /// Number of data messages received, keyed by the channel they were delivered on.
+    pub messages_by_channel: HashMap<KalshiChannel, u64>,
+    /// Total number of text frames received, including acks and errors.
+    pub messages_received: u64,
+    /// Total size of all text frames received, in bytes.
+    pub bytes_received: u64,
+    /// When the most recent text frame was received.
+    pub last_message_at: Option<SystemTime>,
+    /// When the current connection was established.
+    pub connected_at: Option<SystemTime>,
+    /// How many times the connection was re-established after being lost.
+    pub reconnect_count: u32,
+    /// Subscriptions acknowledged by the exchange and still active.
+    pub subscriptions: Vec<KalshiSubscription>,
+    /// Feed latency of ticker, trade and fill messages, keyed by channel.
+    pub latency_by_channel: HashMap<KalshiChannel, KalshiFeedLatency>,
+    /// The clock offset applied to latency measurements, in milliseconds.
+    pub clock_offset_ms: i64,
+    /// Throughput of every active subscription, keyed by sid.
+    pub throughput_by_sid: HashMap<u32, KalshiSubscriptionThroughput>,
+    /// Messages the client's streams skipped because their consumer fell behind.
+    pub dropped_messages: u64,
+}
+
+/// A command sent to the exchange that wasn't acknowledged yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KalshiPendingCommand {
+    /// The command id.
+    pub id: u32,
+    /// The command, `subscribe`, `update_subscription` or `unsubscribe`.
+    pub cmd: &'static str,
+    /// Channels of a subscribe command.
+    pub channels: Vec<KalshiChannel>,
+    /// Markets the command subscribes, adds or removes.
+    pub market_tickers: Vec<String>,
+    /// Subscriptions an update or unsubscribe command applies to.
+    pub sids: Vec<u32>,
+    /// When the command was sent.
+    pub sent_at: Instant,
+}
+
+/// How much a subscription delivers, to spot the subscriptions overwhelming a consumer.
+///
+/// Counts restart when a reconnect re-establishes the subscription under a new sid.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct KalshiSubscriptionThroughput {
+    /// Data messages received on the subscription.
+    pub messages: u64,
+    /// Size of those messages, in bytes.
+    pub bytes: u64,
+    /// Messages per second over the last second or so.
+    pub messages_per_sec: f64,
+    /// Bytes per second over the last second or so.
+    pub bytes_per_sec: f64,
+    /// Messages of this subscription skipped by a lagging consumer.
+    ///
+    /// Consumers only learn how many messages they missed, not which, so
+    /// [`KalshiWebsocketStats::dropped_messages`] is split between subscriptions by their share
+    /// of the messages received. Treat it as an estimate.
+    pub dropped: u64,
+}
+
+/// Shortest period messages per second are measured over.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Counts the messages of one subscription, rates are measured over windows of [`RATE_WINDOW`].
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ThroughputCounter {
+    totals: KalshiSubscriptionThroughput,
+    window_start: Instant,
+    window_messages: u64,
+    window_bytes: u64,
+}
+
+impl ThroughputCounter {
+    pub(super) fn new(now: Instant) -> Self {
+        ThroughputCounter {
+            totals: KalshiSubscriptionThroughput::default(),
+            window_start: now,
+            window_messages: 0,
+            window_bytes: 0,
+        }
+    }
+
+    pub(super) fn record(&mut self, bytes: usize, now: Instant) {
+        self.roll(now);
+        self.totals.messages += 1;
+        self.totals.bytes += bytes as u64;
+        self.window_messages += 1;
+        self.window_bytes += bytes as u64;
+    }
+
+    pub(super) fn add_dropped(&mut self, dropped: u64) {
+        self.totals.dropped += dropped;
+    }
+
+    pub(super) fn messages(&self) -> u64 {
+        self.totals.messages
+    }
+
+    /// Closes the current window once it's long enough, updating the rates.
+    fn roll(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed < RATE_WINDOW {
+            return;
+        }
+        let secs = elapsed.as_secs_f64();
+        self.totals.messages_per_sec = self.window_messages as f64 / secs;
+        self.totals.bytes_per_sec = self.window_bytes as f64 / secs;
+        self.window_start = now;
+        self.window_messages = 0;
+        self.window_bytes = 0;
+    }
+
+    /// The counts and rates as of `now`, rates drop to zero once a subscription goes quiet.
+    pub(super) fn snapshot(&self, now: Instant) -> KalshiSubscriptionThroughput {
+        let mut counter = *self;
+        counter.roll(now);
+        counter.totals
+    }
+}
+
+/// A subscription acknowledged by the exchange.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KalshiSubscription {
+    /// The subscription id assigned by the exchange.
+    pub sid: u32,
+    /// The channel subscribed to.
+    pub channel: KalshiChannel,
+    /// The markets covered by the subscription, empty when subscribed to all markets.
+    pub market_tickers: Vec<String>,
+}
@@ -0,0 +1,227 @@
+//! Logging of the raw HTTP exchanges with the API, with the `wire-logging` feature, and timing
+//! of the requests.
+
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::timing::{timing_hook_set, PendingTiming, RequestTiming};
+
+#[cfg(feature = "wire-logging")]
+pub use logging::*;
+#[cfg(feature = "wire-logging")]
+use logging::{log_request, log_response};
+
+/// Sends requests through the wire log and the request timing hook when they're enabled.
+#[async_trait]
+pub(crate) trait SendLogged {
+    async fn send_logged(self) -> reqwest::Result<reqwest::Response>;
+}
+
+/// JSON fields whose values are never logged or recorded, the login credentials and token.
+#[cfg(any(test, feature = "wire-logging", feature = "testing"))]
+const REDACTED_FIELDS: [&str; 3] = ["password", "token", "email"];
+
+/// Replaces credentials and login tokens anywhere in `value` with `<redacted>`, for the wire log
+/// and for recorded cassettes.
+#[cfg(any(test, feature = "wire-logging", feature = "testing"))]
+pub(crate) fn redact_value(value: &mut serde_json::Value) {
+    use serde_json::Value;
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if REDACTED_FIELDS.contains(&name.as_str()) {
+                    *field = Value::String("<redacted>".to_string());
+                } else {
+                    redact_value(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+#[cfg(not(feature = "wire-logging"))]
+fn wire_logging_enabled() -> bool {
+    false
+}
+
+#[cfg(not(feature = "wire-logging"))]
+fn log_request(_request: &reqwest::Request) {}
+
+#[cfg(not(feature = "wire-logging"))]
+fn log_response(
+    _status: reqwest::StatusCode,
+    _url: &reqwest::Url,
+    _headers: &reqwest::header::HeaderMap,
+    _body: &[u8],
+) {
+}
+
+#[async_trait]
+impl SendLogged for reqwest::RequestBuilder {
+    async fn send_logged(self) -> reqwest::Result<reqwest::Response> {
+        let timed = timing_hook_set();
+        if !timed && !wire_logging_enabled() {
+            return self.send().await;
+        }
+        let (client, request) = self.build_split();
+        let request = request?;
+        log_request(&request);
+        let method = request.method().clone();
+        let url = request.url().clone();
+
+        let sent_at = Instant::now();
+        let response = client.execute(request).await?;
+        let ttfb = sent_at.elapsed();
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let response_url = response.url().clone();
+        let body = response.bytes().await?;
+        let body_time = sent_at.elapsed() - ttfb;
+        log_response(status, &response_url, &headers, &body);
+
+        // The body was consumed, hand the caller an identical response
+        let mut rebuilt = http::Response::new(body);
+        *rebuilt.status_mut() = status;
+        *rebuilt.version_mut() = version;
+        *rebuilt.headers_mut() = headers;
+        if timed {
+            rebuilt
+                .extensions_mut()
+                .insert(PendingTiming::new(RequestTiming {
+                    method,
+                    url,
+                    status: status.as_u16(),
+                    ttfb,
+                    body: body_time,
+                    deserialize: None,
+                }));
+        }
+        Ok(reqwest::Response::from(rebuilt))
+    }
+}
+
+#[cfg(feature = "wire-logging")]
+mod logging {
+    use super::redact_value;
+    use reqwest::header::HeaderMap;
+    use serde_json::Value;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static ENABLED: AtomicBool = AtomicBool::new(false);
+
+    /// Headers whose values are never logged.
+    const REDACTED_HEADERS: [&str; 4] = [
+        "authorization",
+        "kalshi-access-key",
+        "kalshi-access-signature",
+        "cookie",
+    ];
+
+    /// Starts or stops logging every request and response body sent to the API, under the
+    /// `kalshi::wire` log target at the debug level.
+    ///
+    /// Off by default. Credentials, signatures and login tokens are replaced with `<redacted>`.
+    /// While enabled, responses are downloaded whole before being parsed, including the ones
+    /// otherwise streamed.
+    ///
+    /// ```
+    /// kalshi::set_wire_logging(true);
+    /// let order = kalshi_instance.create_order(...).await;
+    /// kalshi::set_wire_logging(false);
+    /// ```
+    pub fn set_wire_logging(enabled: bool) {
+        ENABLED.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether requests are being logged, see [`set_wire_logging`].
+    pub fn wire_logging_enabled() -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    fn redact_headers(headers: &HeaderMap) -> String {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if REDACTED_HEADERS.contains(&name.as_str()) {
+                    "<redacted>"
+                } else {
+                    value.to_str().unwrap_or("<binary>")
+                };
+                format!("{}: {}", name, value)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The body as logged, JSON with its secrets redacted or the raw text otherwise.
+    pub(crate) fn redact_body(body: &[u8]) -> String {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(mut value) => {
+                redact_value(&mut value);
+                value.to_string()
+            }
+            Err(_) => String::from_utf8_lossy(body).into_owned(),
+        }
+    }
+
+    pub(super) fn log_request(request: &reqwest::Request) {
+        log::debug!(
+            target: "kalshi::wire",
+            "--> {} {} [{}] {}",
+            request.method(),
+            request.url(),
+            redact_headers(request.headers()),
+            request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(redact_body)
+                .unwrap_or_default()
+        );
+    }
+
+    pub(super) fn log_response(
+        status: reqwest::StatusCode,
+        url: &reqwest::Url,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) {
+        log::debug!(
+            target: "kalshi::wire",
+            "<-- {} {} [{}] {}",
+            status,
+            url,
+            redact_headers(headers),
+            redact_body(body)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "wire-logging"))]
+mod test {
+    use super::*;
+    use crate::testing::MockHttpServer;
+
+    #[test]
+    fn test_bodies_redacted() {
+        let body = br#"{"email":"a@b.c","password":"hunter2","orders":[{"token":"t","count":1}]}"#;
+        let logged = redact_body(body);
+        assert!(!logged.contains("hunter2") && !logged.contains("a@b.c"));
+        assert!(logged.contains(r#""token":"<redacted>""#));
+        assert!(logged.contains(r#""count":1"#));
+        assert_eq!(redact_body(b"not json"), "not json");
+    }
+
+    #[tokio::test]
+    async fn test_logged_responses_still_parse() {
+        let server = MockHttpServer::with_fixtures().await.unwrap();
+        let kalshi = server.kalshi().await.unwrap();
+        set_wire_logging(true);
+        let balance = kalshi.get_balance().await;
+        set_wire_logging(false);
+        assert!(balance.is_ok());
+    }
+}